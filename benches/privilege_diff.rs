@@ -0,0 +1,154 @@
+//! Benchmarks for the privilege diff engine and the privilege editor.
+//!
+//! These are the hot paths of `edit-privs` for users in large groups, which
+//! may have access to thousands of privilege rows.
+//!
+//! Run with `cargo bench --bench privilege_diff`.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+
+use muscl_lib::core::database_privileges::{
+    DATABASE_PRIVILEGE_FIELDS, DatabasePrivilegeRow, diff_privileges,
+    generate_editor_content_from_privilege_data, parse_privilege_data_from_editor_content,
+    reduce_privilege_diffs,
+};
+
+const ROW_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+const USERS_PER_DATABASE: usize = 20;
+
+/// Deterministically generate `count` privilege rows, with a varied mix of privileges.
+fn generate_rows(count: usize, seed: usize) -> Vec<DatabasePrivilegeRow> {
+    (0..count)
+        .map(|i| {
+            let bit = |n: usize| ((i.wrapping_mul(31) ^ seed.wrapping_mul(17)) >> n) & 1 == 1;
+            DatabasePrivilegeRow {
+                db: format!("group_db{}", i / USERS_PER_DATABASE).into(),
+                user: format!("group_user{}", i % USERS_PER_DATABASE).into(),
+                select_priv: bit(0),
+                insert_priv: bit(1),
+                update_priv: bit(2),
+                delete_priv: bit(3),
+                create_priv: bit(4),
+                drop_priv: bit(5),
+                alter_priv: bit(6),
+                index_priv: bit(7),
+                create_tmp_table_priv: bit(8),
+                lock_tables_priv: bit(9),
+                references_priv: bit(10),
+                create_view_priv: bit(11),
+                show_view_priv: bit(12),
+                trigger_priv: bit(13),
+                event_priv: bit(14),
+                execute_priv: bit(15),
+                create_routine_priv: bit(16),
+                alter_routine_priv: bit(17),
+                grant_priv: bit(18),
+            }
+        })
+        .collect()
+}
+
+/// Generate a modified copy of `from`, where some rows are changed, some are
+/// removed, and some new rows are added.
+fn generate_modified_rows(from: &[DatabasePrivilegeRow]) -> Vec<DatabasePrivilegeRow> {
+    let mut to: Vec<_> = generate_rows(from.len(), 1)
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % 10 != 0)
+        .map(|(_, row)| row)
+        .collect();
+
+    to.extend(
+        generate_rows(from.len() / 10, 2)
+            .into_iter()
+            .map(|mut row| {
+                row.db = format!("new_{}", row.db).into();
+                row
+            }),
+    );
+
+    to
+}
+
+fn bench_diff_privileges(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff_privileges");
+    for count in ROW_COUNTS {
+        let from = generate_rows(count, 0);
+        let to = generate_modified_rows(&from);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| diff_privileges(black_box(&from), black_box(&to)));
+        });
+    }
+    group.finish();
+}
+
+fn bench_reduce_privilege_diffs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reduce_privilege_diffs");
+    for count in ROW_COUNTS {
+        let from = generate_rows(count, 0);
+        let diffs = diff_privileges(&from, &generate_modified_rows(&from));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter_batched(
+                || diffs.clone(),
+                |diffs| reduce_privilege_diffs(black_box(&from), diffs).unwrap(),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn bench_generate_editor_content(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_editor_content_from_privilege_data");
+    for count in ROW_COUNTS {
+        let rows = generate_rows(count, 0);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                generate_editor_content_from_privilege_data(
+                    black_box(&rows),
+                    &DATABASE_PRIVILEGE_FIELDS,
+                    "group",
+                    None,
+                    &[],
+                    &[],
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_parse_editor_content(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_privilege_data_from_editor_content");
+    for count in ROW_COUNTS {
+        let content = generate_editor_content_from_privilege_data(
+            &generate_rows(count, 0),
+            &DATABASE_PRIVILEGE_FIELDS,
+            "group",
+            None,
+            &[],
+            &[],
+        );
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                parse_privilege_data_from_editor_content(
+                    black_box(&content),
+                    &DATABASE_PRIVILEGE_FIELDS,
+                )
+                .unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_diff_privileges,
+    bench_reduce_privilege_diffs,
+    bench_generate_editor_content,
+    bench_parse_editor_content,
+);
+criterion_main!(benches);
@@ -64,6 +64,7 @@
       nativeBuildInputs = with pkgs; [
         toolchain
         mariadb.client
+        mariadb.server
         cargo-nextest
         cargo-edit
         cargo-deny
@@ -0,0 +1,11 @@
+//! Fuzz the parser for the content written back by the user's editor in `edit-privs`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use muscl_lib::core::database_privileges::parse_privilege_data_from_editor_content;
+
+fuzz_target!(|content: &str| {
+    let _ = parse_privilege_data_from_editor_content(content);
+});
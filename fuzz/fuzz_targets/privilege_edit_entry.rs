@@ -0,0 +1,11 @@
+//! Fuzz the parser for `edit-privs` command line arguments, e.g. `db:user:+siu`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use muscl_lib::core::database_privileges::DatabasePrivilegeEditEntry;
+
+fuzz_target!(|arg: &str| {
+    let _ = DatabasePrivilegeEditEntry::parse_from_str(arg);
+});
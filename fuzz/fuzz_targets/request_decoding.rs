@@ -0,0 +1,40 @@
+//! Fuzz the decoding of requests on the server side of the socket.
+//!
+//! The input is written as raw bytes to one end of a socket pair, and read back
+//! through the same framed message stream that the server uses for its sessions.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use futures_util::StreamExt;
+use libfuzzer_sys::fuzz_target;
+use tokio::{io::AsyncWriteExt, net::UnixStream, runtime::Runtime};
+
+use muscl_lib::core::protocol::create_server_to_client_message_stream;
+
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap()
+});
+
+fuzz_target!(|data: &[u8]| {
+    RUNTIME.block_on(async {
+        let (mut client, server) = UnixStream::pair().unwrap();
+
+        // NOTE: the input is written concurrently, since it might not fit in the socket buffer.
+        let writer = async move {
+            client.write_all(data).await.ok();
+            client.shutdown().await.ok();
+        };
+
+        let reader = async move {
+            let mut stream = create_server_to_client_message_stream(server);
+            while let Some(Ok(_request)) = stream.next().await {}
+        };
+
+        tokio::join!(writer, reader);
+    });
+});
@@ -130,6 +130,11 @@ in
     systemd.services."muscl" = {
       reloadTriggers = [ config.environment.etc."muscl/config.toml".source ];
       serviceConfig = {
+        ExecStartPre = [
+          ""
+          "${lib.getExe' cfg.package "muscl-server"} --systemd check-config"
+        ];
+
         ExecStart = [
           ""
           "${lib.getExe' cfg.package "muscl-server"} ${cfg.logLevel} --systemd --disable-landlock socket-activate"
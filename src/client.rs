@@ -1,4 +1,9 @@
 pub mod commands;
+pub mod config;
+pub mod editor;
+pub mod hooks;
+pub mod interactive;
+pub mod progress;
 
 #[cfg(feature = "mysql-admutils-compatibility")]
 pub mod mysql_admutils_compatibility;
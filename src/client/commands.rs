@@ -1,34 +1,93 @@
+mod accept_grant;
+mod admin;
+mod adopt;
 mod check_auth;
+mod check_name;
+mod cleanup_privs;
 mod create_db;
 mod create_user;
 mod drop_db;
 mod drop_user;
+mod edit_column_privs;
 mod edit_privs;
+mod edit_routine_privs;
+mod grant_role;
+mod kill_connections;
+mod list_snapshots;
 mod lock_user;
+mod offer_grant;
 mod passwd_user;
+mod require_ssl;
+mod rollback_privs;
+mod search;
+mod server_info;
+mod set_limits;
+mod set_user_comment;
+mod show_column_privs;
+mod show_connections;
 mod show_db;
 mod show_privs;
+mod show_routine_privs;
+mod show_routines;
 mod show_user;
+mod snapshot_privs;
+mod stats;
+mod transfer_db;
 mod unlock_user;
 
+pub use accept_grant::*;
+pub use admin::*;
+pub use adopt::*;
 pub use check_auth::*;
+pub use check_name::*;
+pub use cleanup_privs::*;
 pub use create_db::*;
 pub use create_user::*;
 pub use drop_db::*;
 pub use drop_user::*;
+pub use edit_column_privs::*;
 pub use edit_privs::*;
+pub use edit_routine_privs::*;
+pub use grant_role::*;
+pub use kill_connections::*;
+pub use list_snapshots::*;
 pub use lock_user::*;
+pub use offer_grant::*;
 pub use passwd_user::*;
+pub use require_ssl::*;
+pub use rollback_privs::*;
+pub use search::*;
+pub use server_info::*;
+pub use set_limits::*;
+pub use set_user_comment::*;
+pub use show_column_privs::*;
+pub use show_connections::*;
 pub use show_db::*;
 pub use show_privs::*;
+pub use show_routine_privs::*;
+pub use show_routines::*;
 pub use show_user::*;
+pub use snapshot_privs::*;
+pub use stats::*;
+pub use transfer_db::*;
 pub use unlock_user::*;
 
+use std::{collections::BTreeMap, io::IsTerminal};
+
+use anyhow::Context;
+use dialoguer::Confirm;
 use futures_util::SinkExt;
 use itertools::Itertools;
 use tokio_stream::StreamExt;
 
-use crate::core::protocol::{ClientToServerMessageStream, Request, Response};
+use crate::{
+    client::interactive,
+    core::{
+        database_privileges::{DatabasePrivilegeEdit, PrivilegeAliases, database_privilege_fields},
+        protocol::{ClientToServerMessageStream, Request, Response},
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
+    },
+};
 
 /// Handle an unexpected or erroneous response from the server.
 ///
@@ -38,8 +97,8 @@ pub fn erroneous_server_response(
     response: Option<Result<Response, std::io::Error>>,
 ) -> anyhow::Result<()> {
     match response {
-        Some(Ok(Response::Error(e))) => {
-            anyhow::bail!("Server returned error: {e}");
+        Some(Ok(Response::Error { message, reference })) => {
+            anyhow::bail!("Server returned error: {message}\n(reference: {reference})");
         }
         Some(Err(e)) => {
             anyhow::bail!(e);
@@ -53,8 +112,39 @@ pub fn erroneous_server_response(
     }
 }
 
+/// Prepend `prefix` and the `_` delimiter to `name`, as given with `--prefix`.
+///
+/// Stray delimiters on either side are ignored, and names that already
+/// start with the prefix are left as they are.
+fn with_name_prefix(prefix: Option<&str>, name: &str) -> String {
+    let Some(prefix) = prefix else {
+        return name.to_string();
+    };
+    let prefix = prefix.trim_end_matches('_');
+    let name = name.trim_start_matches('_');
+
+    if name.starts_with(&format!("{prefix}_")) {
+        name.to_string()
+    } else {
+        format!("{prefix}_{name}")
+    }
+}
+
+/// Pick the prefix to prepend to a name given to `create-db` or `create-user`.
+///
+/// `--prefix` applies to every name, while the preferred prefix from the
+/// client config only applies to names that do not have a prefix already.
+fn name_prefix_for<'a>(
+    prefix: Option<&'a str>,
+    preferred_prefix: Option<&'a str>,
+    name: &str,
+) -> Option<&'a str> {
+    prefix.or_else(|| preferred_prefix.filter(|_| !name.contains('_')))
+}
+
 /// Print a hint about which name prefixes the user is authorized to manage
-/// by querying the server for valid name prefixes.
+/// by querying the server for valid name prefixes, followed by the error
+/// footer of the server, if any.
 ///
 /// This function should be used when an authorization error occurs,
 /// to help the user understand which databases or users they are allowed to manage.
@@ -75,5 +165,446 @@ async fn print_authorization_owner_hint(
         response.into_iter().map(|p| format!(" - {p}")).join("\n")
     );
 
+    server_connection.send(Request::ServerInfo).await?;
+
+    let error_footer = match server_connection.next().await {
+        Some(Ok(Response::ServerInfo(server_info))) => server_info.error_footer,
+        response => return erroneous_server_response(response),
+    };
+
+    if let Some(error_footer) = error_footer {
+        eprintln!("{error_footer}");
+    }
+
+    Ok(())
+}
+
+/// Fetch the names of all databases the user is allowed to manage.
+async fn fetch_database_names(
+    server_connection: &mut ClientToServerMessageStream,
+) -> anyhow::Result<Vec<String>> {
+    server_connection.send(Request::ListDatabases(None)).await?;
+
+    match server_connection.next().await {
+        Some(Ok(Response::ListAllDatabases(result))) => result
+            .map(|rows| {
+                rows.into_iter()
+                    .map(|row| row.database.to_string())
+                    .collect()
+            })
+            .map_err(|err| anyhow::anyhow!(err.to_error_message()))
+            .context("Failed to list databases"),
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Fetch the names of all users the user is allowed to manage.
+async fn fetch_user_names(
+    server_connection: &mut ClientToServerMessageStream,
+) -> anyhow::Result<Vec<String>> {
+    server_connection.send(Request::ListUsers(None)).await?;
+
+    match server_connection.next().await {
+        Some(Ok(Response::ListAllUsers(result))) => result
+            .map(|rows| rows.into_iter().map(|row| row.user.to_string()).collect())
+            .map_err(|err| anyhow::anyhow!(err.to_error_message()))
+            .context("Failed to list users"),
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Fetch the privilege templates for the name prefixes available to the user.
+async fn fetch_privilege_templates(
+    server_connection: &mut ClientToServerMessageStream,
+) -> anyhow::Result<BTreeMap<String, DatabasePrivilegeEdit>> {
+    server_connection
+        .send(Request::ListPrivilegeTemplates)
+        .await?;
+
+    let templates = match server_connection.next().await {
+        Some(Ok(Response::ListPrivilegeTemplates(templates))) => templates,
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            BTreeMap::new()
+        }
+    };
+
+    templates
+        .into_iter()
+        .map(|(prefix, template)| {
+            let template = DatabasePrivilegeEdit::parse_template_from_str(&template).context(
+                format!("Server sent an invalid privilege template for {prefix:?}"),
+            )?;
+            Ok((prefix, template))
+        })
+        .collect()
+}
+
+/// Fetch the privilege aliases configured on the server.
+async fn fetch_privilege_aliases(
+    server_connection: &mut ClientToServerMessageStream,
+) -> anyhow::Result<PrivilegeAliases> {
+    server_connection
+        .send(Request::ListPrivilegeAliases)
+        .await?;
+
+    match server_connection.next().await {
+        Some(Ok(Response::ListPrivilegeAliases(aliases))) => Ok(aliases),
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            Ok(PrivilegeAliases::new())
+        }
+    }
+}
+
+/// Fetch the privilege fields to show the user, which only include the extended
+/// privileges if they are enabled on the server.
+async fn fetch_database_privilege_fields(
+    server_connection: &mut ClientToServerMessageStream,
+) -> anyhow::Result<&'static [&'static str]> {
+    server_connection.send(Request::ServerInfo).await?;
+
+    match server_connection.next().await {
+        Some(Ok(Response::ServerInfo(server_info))) => Ok(database_privilege_fields(
+            server_info.extended_database_privileges,
+        )),
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            Ok(database_privilege_fields(false))
+        }
+    }
+}
+
+/// Let the user pick among the databases they are allowed to manage,
+/// for commands that were run on a terminal without any database names.
+async fn pick_databases_interactively(
+    server_connection: &mut ClientToServerMessageStream,
+    prompt: &str,
+) -> anyhow::Result<Vec<MySQLDatabase>> {
+    let candidates = fetch_database_names(server_connection).await?;
+    if candidates.is_empty() {
+        eprintln!("You do not have any databases to choose from.");
+    }
+
+    Ok(interactive::pick_many(prompt, &candidates)?
+        .into_iter()
+        .map(MySQLDatabase::from)
+        .collect())
+}
+
+/// Let the user pick among the users they are allowed to manage,
+/// for commands that were run on a terminal without any usernames.
+async fn pick_users_interactively(
+    server_connection: &mut ClientToServerMessageStream,
+    prompt: &str,
+) -> anyhow::Result<Vec<MySQLUser>> {
+    let candidates = fetch_user_names(server_connection).await?;
+    if candidates.is_empty() {
+        eprintln!("You do not have any users to choose from.");
+    }
+
+    Ok(interactive::pick_many(prompt, &candidates)?
+        .into_iter()
+        .map(MySQLUser::from)
+        .collect())
+}
+
+/// Let the user pick a single one of the users they are allowed to manage.
+async fn pick_user_interactively(
+    server_connection: &mut ClientToServerMessageStream,
+    prompt: &str,
+) -> anyhow::Result<Option<MySQLUser>> {
+    let candidates = fetch_user_names(server_connection).await?;
+    if candidates.is_empty() {
+        eprintln!("You do not have any users to choose from.");
+    }
+
+    Ok(interactive::pick_one(prompt, &candidates)?.map(MySQLUser::from))
+}
+
+/// A name affected by a bulk operation, as shown by [`confirm_bulk_operation`].
+struct BulkOperationItem {
+    name: String,
+    /// The size of the database, if the operation drops one that exists.
+    size_bytes: Option<u64>,
+}
+
+impl BulkOperationItem {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            size_bytes: None,
+        }
+    }
+
+    fn with_size(name: &str, size_bytes: Option<u64>) -> Self {
+        Self {
+            name: name.to_string(),
+            size_bytes,
+        }
+    }
+}
+
+/// Summarize the names affected by a bulk operation, grouped by their prefix,
+/// with counts and (where known) sizes per prefix.
+fn format_bulk_operation_preview(noun: &str, items: &[BulkOperationItem]) -> String {
+    let plural = |count: usize| {
+        if count == 1 {
+            format!("1 {noun}")
+        } else {
+            format!("{count} {noun}s")
+        }
+    };
+    let size_suffix = |items: &[&BulkOperationItem]| {
+        let sizes = items
+            .iter()
+            .filter_map(|item| item.size_bytes)
+            .collect::<Vec<_>>();
+        if sizes.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ", {}",
+                humansize::format_size(sizes.iter().sum::<u64>(), humansize::DECIMAL)
+            )
+        }
+    };
+
+    let groups = items
+        .iter()
+        .map(|item| {
+            let prefix = item
+                .name
+                .split_once('_')
+                .map_or("(no prefix)", |(prefix, _)| prefix);
+            (prefix, item)
+        })
+        .into_group_map();
+
+    let mut lines = Vec::new();
+    for (prefix, group) in groups.into_iter().sorted_by_key(|(prefix, _)| *prefix) {
+        lines.push(format!(
+            "{prefix} ({}{})",
+            plural(group.len()),
+            size_suffix(&group)
+        ));
+        for item in group.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+            match item.size_bytes {
+                Some(size) => lines.push(format!(
+                    "  - {} ({})",
+                    item.name,
+                    humansize::format_size(size, humansize::DECIMAL)
+                )),
+                None => lines.push(format!("  - {}", item.name)),
+            }
+        }
+    }
+
+    let all_items = items.iter().collect::<Vec<_>>();
+    lines.push(String::new());
+    lines.push(format!(
+        "Total: {}{}",
+        plural(items.len()),
+        size_suffix(&all_items)
+    ));
+
+    lines.join("\n")
+}
+
+/// Show a preview of a bulk operation and ask the user to confirm it.
+///
+/// The prompt is skipped when `yes` is set, and the operation is refused when
+/// stdin is not a terminal and `yes` is not set. Returns whether to go ahead.
+fn confirm_bulk_operation(
+    action: &str,
+    noun: &str,
+    items: &[BulkOperationItem],
+    yes: bool,
+    irreversible: bool,
+) -> anyhow::Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
+        );
+    }
+
+    println!(
+        "The following {noun}s will be {action}:\n\n{}\n",
+        format_bulk_operation_preview(noun, items)
+    );
+
+    Confirm::new()
+        .with_prompt(if irreversible {
+            "Are you sure? This action cannot be undone"
+        } else {
+            "Do you want to continue?"
+        })
+        .default(false)
+        .interact()
+        .map_err(Into::into)
+}
+
+/// The maximum edit distance for a name to be suggested in a "did you mean" hint.
+const DID_YOU_MEAN_MAX_DISTANCE: usize = 2;
+
+/// Find the candidates that are within [`DID_YOU_MEAN_MAX_DISTANCE`] edits of `name`,
+/// ordered by distance and then alphabetically.
+fn find_close_matches<'a>(name: &str, candidates: &'a [String]) -> Vec<&'a str> {
+    candidates
+        .iter()
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| (strsim::levenshtein(name, candidate), candidate.as_str()))
+        .filter(|(distance, _)| *distance <= DID_YOU_MEAN_MAX_DISTANCE)
+        .sorted()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Print "did you mean" suggestions for databases and users that the server
+/// reported as nonexistent, based on the names the user is allowed to manage.
+///
+/// This function should be used when a `DatabaseDoesNotExist` or `UserDoesNotExist`
+/// error occurs, to help the user spot typos.
+async fn print_did_you_mean_hint(
+    server_connection: &mut ClientToServerMessageStream,
+    missing: &[DbOrUser],
+) -> anyhow::Result<()> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut databases: Option<Vec<String>> = None;
+    let mut users: Option<Vec<String>> = None;
+
+    for item in missing {
+        // The hint is best effort, so failing to list the names just means no suggestions.
+        let candidates = match item {
+            DbOrUser::Database(_) => {
+                if databases.is_none() {
+                    databases = Some(
+                        fetch_database_names(server_connection)
+                            .await
+                            .unwrap_or_default(),
+                    );
+                }
+                databases.as_deref().unwrap_or_default()
+            }
+            DbOrUser::User(_) => {
+                if users.is_none() {
+                    users = Some(
+                        fetch_user_names(server_connection)
+                            .await
+                            .unwrap_or_default(),
+                    );
+                }
+                users.as_deref().unwrap_or_default()
+            }
+        };
+
+        let matches = find_close_matches(item.name(), candidates);
+        if !matches.is_empty() {
+            eprintln!(
+                "Note: {} '{}' does not exist. Did you mean {}?",
+                item.capitalized_noun(),
+                item.name(),
+                matches.iter().map(|m| format!("'{m}'")).join(" or ")
+            );
+        }
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_name_prefix() {
+        assert_eq!(with_name_prefix(None, "mydb"), "mydb");
+        assert_eq!(with_name_prefix(Some("webgroup"), "mydb"), "webgroup_mydb");
+        assert_eq!(
+            with_name_prefix(Some("webgroup_"), "_mydb"),
+            "webgroup_mydb"
+        );
+        assert_eq!(
+            with_name_prefix(Some("webgroup"), "webgroup_mydb"),
+            "webgroup_mydb"
+        );
+        assert_eq!(
+            with_name_prefix(Some("web"), "webgroup_mydb"),
+            "web_webgroup_mydb"
+        );
+    }
+
+    #[test]
+    fn test_name_prefix_for() {
+        assert_eq!(name_prefix_for(None, None, "mydb"), None);
+        assert_eq!(
+            name_prefix_for(Some("webgroup"), Some("alice"), "mydb"),
+            Some("webgroup")
+        );
+        assert_eq!(name_prefix_for(None, Some("alice"), "mydb"), Some("alice"));
+        assert_eq!(name_prefix_for(None, Some("alice"), "webgroup_mydb"), None);
+    }
+
+    #[test]
+    fn test_find_close_matches() {
+        let candidates = vec![
+            "user_mydb".to_string(),
+            "user_mydb2".to_string(),
+            "user_other".to_string(),
+            "user_mydb_old".to_string(),
+        ];
+        assert_eq!(
+            find_close_matches("user_mybd", &candidates),
+            vec!["user_mydb", "user_mydb2"]
+        );
+        assert_eq!(
+            find_close_matches("user_mydb", &candidates),
+            vec!["user_mydb2"]
+        );
+        assert!(find_close_matches("something_else", &candidates).is_empty());
+    }
+
+    #[test]
+    fn test_format_bulk_operation_preview() {
+        let items = vec![
+            BulkOperationItem::with_size("user_b", Some(2000)),
+            BulkOperationItem::with_size("group_x", None),
+            BulkOperationItem::with_size("user_a", Some(1000)),
+        ];
+        assert_eq!(
+            format_bulk_operation_preview("database", &items),
+            indoc::indoc! {"
+                group (1 database)
+                  - group_x
+                user (2 databases, 3 kB)
+                  - user_a (1 kB)
+                  - user_b (2 kB)
+
+                Total: 3 databases, 3 kB"}
+        );
+
+        let items = vec![BulkOperationItem::new("nounderscore")];
+        assert_eq!(
+            format_bulk_operation_preview("user", &items),
+            "(no prefix) (1 user)\n  - nounderscore\n\nTotal: 1 user"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::io::IsTerminal;
+
+use clap::Parser;
+use dialoguer::Confirm;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{commands::erroneous_server_response, config as client_config},
+    core::{
+        database_privileges::format_privileges_as_cli_string,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            AcceptGrantOutput, ClientToServerMessageStream, GrantOfferId, Request, Response,
+        },
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct AcceptGrantArgs {
+    /// The ID of the offer to accept, or nothing to list the pending offers
+    #[arg(value_name = "ID")]
+    id: Option<GrantOfferId>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// Automatically confirm action without prompting
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn accept_grant(
+    args: AcceptGrantArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    if args.id.is_some() && !std::io::stdin().is_terminal() && !yes {
+        anyhow::bail!(
+            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
+        );
+    }
+
+    server_connection.send(Request::ListGrantOffers).await?;
+
+    let offers = match server_connection.next().await {
+        Some(Ok(Response::ListGrantOffers(Ok(offers)))) => offers,
+        Some(Ok(Response::ListGrantOffers(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(
+                anyhow::anyhow!(err.to_error_message()).context("Failed to list grant offers")
+            );
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let Some(id) = args.id else {
+        print_output(offers.as_slice(), &output_options);
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    };
+
+    if !yes && let Some(offer) = offers.iter().find(|offer| offer.id == id) {
+        let confirmation = Confirm::new()
+            .with_prompt(format!(
+                "Give user '{}' the privileges '{}' on database '{}', as offered by '{}'?",
+                offer.row.user,
+                format_privileges_as_cli_string(&offer.row),
+                offer.row.db,
+                offer.offered_by,
+            ))
+            .interact()?;
+
+        if !confirmation {
+            print_message("Aborting.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    if let Err(err) = server_connection.send(Request::AcceptGrant(id)).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::AcceptGrant(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output = AcceptGrantOutput {
+        id,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use clap::{Parser, Subcommand};
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, LIST_DATABASES_COLUMNS, LIST_USERS_COLUMNS, LOG_LEVELS,
+            ListDatabasesOutput, ListUsersOutput, Request, Response,
+        },
+        table::TableViewArgs,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct AdminArgs {
+    #[command(subcommand)]
+    command: AdminCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AdminCommand {
+    /// Print a JSON report of the databases, users and sizes of every prefix on the server
+    ///
+    /// This is meant for capacity planning and chargeback.
+    Report,
+
+    /// Print information about every database on the server that belongs to a user or group
+    ShowDb(AdminShowDbArgs),
+
+    /// Print information about every database user on the server that belongs to a user or group
+    ShowUser(AdminShowUserArgs),
+
+    /// Show or change the log level of the server, without restarting it
+    ///
+    /// The change lasts until the server is restarted.
+    LogLevel(AdminLogLevelArgs),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct AdminShowDbArgs {
+    /// Show every database, regardless of which prefix it belongs to
+    ///
+    /// This is currently the only supported mode, and has to be given explicitly.
+    #[arg(long)]
+    all: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// Show sizes in bytes instead of human-readable format
+    #[arg(short, long)]
+    bytes: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct AdminShowUserArgs {
+    /// Show every user, regardless of which prefix it belongs to
+    ///
+    /// This is currently the only supported mode, and has to be given explicitly.
+    #[arg(long)]
+    all: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct AdminLogLevelArgs {
+    /// The new log level, or nothing to show the current one
+    ///
+    /// Note that 'trace' logs SQL queries, which might include passwords.
+    #[arg(value_parser = LOG_LEVELS)]
+    level: Option<String>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn admin(
+    args: AdminArgs,
+    server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    match args.command {
+        AdminCommand::Report => admin_report(server_connection).await,
+        AdminCommand::ShowDb(args) => admin_show_databases(args, server_connection).await,
+        AdminCommand::ShowUser(args) => admin_show_users(args, server_connection).await,
+        AdminCommand::LogLevel(args) => admin_log_level(args, server_connection).await,
+    }
+}
+
+async fn admin_report(mut server_connection: ClientToServerMessageStream) -> anyhow::Result<()> {
+    server_connection.send(Request::AdminReport).await?;
+
+    let report = match server_connection.next().await {
+        Some(Ok(Response::AdminReport(Ok(report)))) => report,
+        Some(Ok(Response::AdminReport(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(
+                anyhow::anyhow!(err.to_error_message()).context("Failed to generate report")
+            );
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    // The report is always JSON, as it is meant to be consumed by other tools.
+    print_output(&report, &output::options().with_json(true));
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok(())
+}
+
+/// The column ids of a table with an extra `owner` column in front.
+fn columns_with_owner(columns: &[&'static str]) -> Vec<&'static str> {
+    std::iter::once("owner")
+        .chain(columns.iter().copied())
+        .collect()
+}
+
+async fn admin_show_databases(
+    args: AdminShowDbArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    if !args.all {
+        anyhow::bail!("Please use --all to show every database on the server");
+    }
+    args.table_view
+        .validate(&columns_with_owner(&LIST_DATABASES_COLUMNS))?;
+
+    server_connection.send(Request::AdminListDatabases).await?;
+
+    let database_list = match server_connection.next().await {
+        Some(Ok(Response::AdminListDatabases(Ok(database_list)))) => database_list,
+        Some(Ok(Response::AdminListDatabases(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message()).context("Failed to list databases"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let owners = database_list
+        .iter()
+        .map(|(owner, db)| (db.database.clone(), owner.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let databases = database_list
+        .into_iter()
+        .map(|(_, db)| (db.database.clone(), Ok(db)))
+        .collect();
+
+    let output = ListDatabasesOutput {
+        databases: &databases,
+        owners: Some(&owners),
+        display_size_as_bytes: args.bytes,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output::options().with_json(args.json));
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
+
+async fn admin_show_users(
+    args: AdminShowUserArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    if !args.all {
+        anyhow::bail!("Please use --all to show every user on the server");
+    }
+    args.table_view
+        .validate(&columns_with_owner(&LIST_USERS_COLUMNS))?;
+
+    server_connection.send(Request::AdminListUsers).await?;
+
+    let user_list = match server_connection.next().await {
+        Some(Ok(Response::AdminListUsers(Ok(user_list)))) => user_list,
+        Some(Ok(Response::AdminListUsers(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message()).context("Failed to list users"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let owners = user_list
+        .iter()
+        .map(|(owner, user)| (user.user.clone(), owner.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let users = user_list
+        .into_iter()
+        .map(|(_, user)| (user.user.clone(), Ok(user)))
+        .collect();
+
+    let output = ListUsersOutput {
+        users: &users,
+        owners: Some(&owners),
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output::options().with_json(args.json));
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
+
+async fn admin_log_level(
+    args: AdminLogLevelArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    server_connection
+        .send(Request::AdminLogLevel(args.level))
+        .await?;
+
+    let log_level = match server_connection.next().await {
+        Some(Ok(Response::AdminLogLevel(Ok(log_level)))) => log_level,
+        Some(Ok(Response::AdminLogLevel(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(
+                anyhow::anyhow!(err.to_error_message()).context("Failed to change the log level")
+            );
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&log_level, &output::options().with_json(args.json));
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    core::{
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            AdoptError, AdoptRequest, ClientToServerMessageStream, Request, Response,
+            request_validation::ValidationError,
+        },
+        types::DbOrUser,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct AdoptArgs {
+    /// The `MySQL` database(s) or user(s) to adopt
+    #[arg(num_args = 1.., value_name = "NAME")]
+    name: Vec<String>,
+
+    /// Treat the provided names as users instead of databases
+    #[arg(short, long)]
+    users: bool,
+
+    /// Make the adopted objects look like the ones muscl creates
+    ///
+    /// Users limited to a single host are moved to the `%` host, which is the only one muscl manages,
+    /// and the grant option is removed from their privileges, unless the server has
+    /// `extended_database_privileges` turned on, as muscl does not hand it out otherwise.
+    #[arg(long)]
+    normalize: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn adopt(
+    args: AdoptArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    if args.name.is_empty() {
+        anyhow::bail!("No database/user names provided");
+    }
+
+    let names = args
+        .name
+        .into_iter()
+        .map(|name| {
+            if args.users {
+                DbOrUser::User(name.into())
+            } else {
+                DbOrUser::Database(name.into())
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let message = Request::Adopt(AdoptRequest {
+        names,
+        normalize: args.normalize,
+    });
+    server_connection.send(message).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::Adopt(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    print_output(&result, &output_options);
+
+    if !output_options.is_json()
+        && result.values().any(|res| {
+            matches!(
+                res,
+                Err(AdoptError::ValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
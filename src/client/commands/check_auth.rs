@@ -1,10 +1,9 @@
 use crate::{
     client::commands::erroneous_server_response,
     core::{
-        protocol::{
-            ClientToServerMessageStream, Request, Response,
-            print_check_authorization_output_status, print_check_authorization_output_status_json,
-        },
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response},
         types::DbOrUser,
     },
 };
@@ -57,15 +56,8 @@ pub async fn check_authorization(
 
     server_connection.send(Request::Exit).await?;
 
-    if args.json {
-        print_check_authorization_output_status_json(&result);
-    } else {
-        print_check_authorization_output_status(&result);
-    }
-
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
+    let output_options = output::options().with_json(args.json);
+    print_output(&result, &output_options);
 
-    Ok(())
+    ensure_success(&result)
 }
@@ -0,0 +1,63 @@
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response},
+        types::DbOrUser,
+    },
+};
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+#[derive(Parser, Debug, Clone)]
+pub struct CheckNameArgs {
+    /// The `MySQL` database or user name(s) to check
+    #[arg(num_args = 1.., value_name = "NAME")]
+    name: Vec<String>,
+
+    /// Treat the provided names as users instead of databases
+    #[arg(short, long)]
+    users: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn check_names(
+    args: CheckNameArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    if args.name.is_empty() {
+        anyhow::bail!("No database/user names provided");
+    }
+
+    let payload = args
+        .name
+        .into_iter()
+        .map(|name| {
+            if args.users {
+                DbOrUser::User(name.into())
+            } else {
+                DbOrUser::Database(name.into())
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let message = Request::CheckNames(payload);
+    server_connection.send(message).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::CheckNames(response))) => response,
+        response => return erroneous_server_response(response),
+    };
+
+    server_connection.send(Request::Exit).await?;
+
+    let output_options = output::options().with_json(args.json);
+    print_output(&result, &output_options);
+
+    ensure_success(&result)
+}
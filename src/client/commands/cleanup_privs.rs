@@ -0,0 +1,102 @@
+use std::io::IsTerminal;
+
+use clap::Parser;
+use dialoguer::Confirm;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{commands::erroneous_server_response, config as client_config},
+    core::{
+        exit_code::ensure_success,
+        output::{self, CommandOutput, print_message, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct CleanupPrivsArgs {
+    /// Only list the orphaned privileges, without removing them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// Automatically confirm action without prompting
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn cleanup_privileges(
+    args: CleanupPrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    if !args.dry_run && !std::io::stdin().is_terminal() && !yes {
+        anyhow::bail!(
+            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
+        );
+    }
+
+    server_connection
+        .send(Request::ListOrphanedPrivileges)
+        .await?;
+
+    let orphaned_privileges = match server_connection.next().await {
+        Some(Ok(Response::ListOrphanedPrivileges(Ok(rows)))) => rows,
+        Some(Ok(Response::ListOrphanedPrivileges(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message())
+                .context("Failed to list orphaned privileges"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    if args.dry_run || orphaned_privileges.is_empty() {
+        print_output(orphaned_privileges.as_slice(), &output_options);
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    if !yes {
+        orphaned_privileges.print_human();
+        let confirmation = Confirm::new()
+            .with_prompt(
+                "Are you sure you want to remove these privileges?\n\nThis action cannot be undone",
+            )
+            .interact()?;
+
+        if !confirmation {
+            print_message("Aborting cleanup.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    let message = Request::DeleteOrphanedPrivileges(
+        orphaned_privileges
+            .into_iter()
+            .map(|row| (row.database, row.user))
+            .collect(),
+    );
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::DeleteOrphanedPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&result, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
@@ -1,21 +1,42 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
 use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            BulkOperationItem, confirm_bulk_operation, erroneous_server_response,
+            fetch_privilege_templates, generate_password, name_prefix_for,
+            print_authorization_owner_hint, with_name_prefix,
+        },
+        config as client_config,
+        hooks::{run_post_hook, run_pre_hook},
+        progress::next_response_with_progress,
+    },
     core::{
-        completion::prefix_completer,
+        completion::{bare_prefix_completer, prefix_completer},
+        database_privileges::{
+            DatabasePrivilegeEdit, DatabasePrivilegeEditEntry, create_or_modify_privilege_rows,
+            find_privilege_template,
+        },
+        exit_code::{CommandFailed, ExitCode},
+        output::{self, CommandOutput, OutputOptions, print_message, print_output},
         protocol::{
-            ClientToServerMessageStream, CreateDatabaseError, Request, Response,
-            print_create_databases_output_status, print_create_databases_output_status_json,
-            request_validation::ValidationError,
+            ClientToServerMessageStream, CreateDatabaseError, CreateUsersRequest,
+            GeneratedPasswordOutput, ModifyPrivilegesRequest, Request, Response,
+            SetUserPasswordOutput, request_validation::ValidationError,
         },
-        types::MySQLDatabase,
+        types::{MySQLDatabase, MySQLUser},
     },
 };
 
+/// The privileges given to the users made by `--with-user`,
+/// when there is no privilege template for the name prefix.
+const DEFAULT_WITH_USER_PRIVILEGES: &str = "A";
+
 #[derive(Parser, Debug, Clone)]
 pub struct CreateDbArgs {
     /// The `MySQL` database(s) to create
@@ -23,6 +44,40 @@ pub struct CreateDbArgs {
     #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(prefix_completer)))]
     name: Vec<MySQLDatabase>,
 
+    /// Prepend this prefix and the `_` delimiter to the database names, e.g. one of your groups
+    ///
+    /// Defaults to `prefix` in the client config, for names without a prefix of their own.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(bare_prefix_completer)))]
+    #[arg(long, value_name = "PREFIX")]
+    prefix: Option<String>,
+
+    /// Also create a user with the same name as each database, with privileges on it
+    ///
+    /// If any step of setting up the user fails, both the user and the database are dropped again.
+    #[arg(long)]
+    with_user: bool,
+
+    /// The privileges to give the new user(s), in the same format as `edit-privs`, e.g. `siud`
+    ///
+    /// Defaults to the privilege template for the name prefix if there is one, and all privileges otherwise.
+    #[arg(
+        long,
+        value_name = "PRIVILEGES",
+        requires = "with_user",
+        value_parser = DatabasePrivilegeEdit::parse_template_from_str,
+    )]
+    privileges: Option<DatabasePrivilegeEdit>,
+
+    /// Set a randomly generated password for the new user(s), and print it
+    #[arg(long, requires = "with_user")]
+    generate_password: bool,
+
+    /// Automatically confirm action without prompting
+    ///
+    /// Creating more than one name at once asks for confirmation first.
+    #[arg(short, long)]
+    yes: bool,
+
     /// Print the information as JSON
     #[arg(short, long)]
     json: bool,
@@ -32,39 +87,278 @@ pub async fn create_databases(
     args: CreateDbArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
     if args.name.is_empty() {
         anyhow::bail!("No database names provided");
     }
 
-    let message = Request::CreateDatabases(args.name.clone());
+    let names = args
+        .name
+        .iter()
+        .map(|name| {
+            MySQLDatabase::from(with_name_prefix(
+                name_prefix_for(
+                    args.prefix.as_deref(),
+                    client_config::get().prefix.as_deref(),
+                    name,
+                ),
+                name,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if names.len() > 1 {
+        let preview_items = names
+            .iter()
+            .map(|name| BulkOperationItem::new(name))
+            .collect::<Vec<_>>();
+
+        if !confirm_bulk_operation("created", "database", &preview_items, yes, false)? {
+            print_message("Aborting create operation.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    let hook_names = names.iter().map(ToString::to_string).collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("create-db", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::CreateDatabases(names);
     server_connection.send(message).await?;
 
-    let result = match server_connection.next().await {
+    let result = match next_response_with_progress(&mut server_connection).await {
         Some(Ok(Response::CreateDatabases(result))) => result,
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_create_databases_output_status_json(&result);
-    } else {
-        print_create_databases_output_status(&result);
+    print_output(&result, &output_options);
+    run_post_hook("create-db", &hook_names, &result);
 
-        if result.iter().any(|(_, res)| {
+    if !output_options.is_json()
+        && result.iter().any(|(_, res)| {
             matches!(
                 res,
                 Err(CreateDatabaseError::ValidationError(
                     ValidationError::AuthorizationError(_)
                 ))
             )
-        }) {
-            print_authorization_owner_hint(&mut server_connection).await?;
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
+
+    let mut exit_codes = result.exit_code().into_iter().collect::<Vec<_>>();
+    if args.with_user {
+        let created_databases = result
+            .iter()
+            .filter_map(|(name, result)| result.as_ref().ok().map(|()| name.clone()))
+            .collect::<Vec<_>>();
+
+        if !created_databases.is_empty() {
+            exit_codes.extend(
+                create_users_for_databases(
+                    &created_databases,
+                    &args,
+                    &mut server_connection,
+                    &output_options,
+                )
+                .await?,
+            );
         }
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
+    match ExitCode::combine(exit_codes) {
+        Some(code) => Err(CommandFailed(code).into()),
+        None => Ok(()),
+    }
+}
+
+/// Create a user with the same name as each of the new databases, give it privileges
+/// on the database, and optionally set a generated password for it.
+///
+/// The user and the database are dropped again if any of the steps fail,
+/// so that no half-finished setups are left behind.
+async fn create_users_for_databases(
+    databases: &[MySQLDatabase],
+    args: &CreateDbArgs,
+    server_connection: &mut ClientToServerMessageStream,
+    output_options: &OutputOptions,
+) -> anyhow::Result<Vec<ExitCode>> {
+    let mut exit_codes = Vec::new();
+    let mut failed_databases = BTreeSet::new();
+    let mut failed_users = BTreeSet::new();
+
+    let users = databases
+        .iter()
+        .map(|database| (database.clone(), MySQLUser::from(database.to_string())))
+        .collect::<BTreeMap<_, _>>();
+
+    server_connection
+        .send(Request::CreateUsers(CreateUsersRequest {
+            users: users.values().cloned().collect(),
+            locked: false,
+        }))
+        .await?;
+    let result = match server_connection.next().await {
+        Some(Ok(Response::CreateUsers(result))) => result,
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            BTreeMap::new()
+        }
+    };
+    print_output(&result, output_options);
+    exit_codes.extend(result.exit_code());
+
+    for (database, user) in &users {
+        if result.get(user).is_none_or(Result::is_err) {
+            failed_databases.insert(database.clone());
+        }
+    }
+
+    let templates = if args.privileges.is_none() {
+        fetch_privilege_templates(server_connection).await?
+    } else {
+        BTreeMap::new()
+    };
+
+    let privilege_rows = users
+        .iter()
+        .filter(|(database, _)| !failed_databases.contains(*database))
+        .map(|(database, user)| {
+            let privilege_edit = match &args.privileges {
+                Some(privileges) => privileges.clone(),
+                None => find_privilege_template(&templates, database).map_or_else(
+                    || DatabasePrivilegeEdit::parse_template_from_str(DEFAULT_WITH_USER_PRIVILEGES),
+                    |template| Ok(template.clone()),
+                )?,
+            };
+            DatabasePrivilegeEditEntry {
+                database: database.clone(),
+                user: user.clone(),
+                privilege_edit,
+            }
+            .as_database_privileges_diff()
+        })
+        .collect::<anyhow::Result<BTreeSet<_>>>()?;
+
+    if !privilege_rows.is_empty() {
+        let diffs = create_or_modify_privilege_rows(&[], &privilege_rows, &BTreeMap::new())?;
+        server_connection
+            .send(Request::ModifyPrivileges(ModifyPrivilegesRequest {
+                diffs,
+                atomic: false,
+            }))
+            .await?;
+        let result = match next_response_with_progress(server_connection).await {
+            Some(Ok(Response::ModifyPrivileges(result))) => result,
+            response => {
+                erroneous_server_response(response)?;
+                // Unreachable, but needed to satisfy the type checker
+                BTreeMap::new()
+            }
+        };
+        print_output(&result, output_options);
+        exit_codes.extend(result.exit_code());
+
+        for ((database, user), result) in &result {
+            if result.is_err() {
+                failed_databases.insert(database.clone());
+                failed_users.insert(user.clone());
+            }
+        }
+    }
+
+    if args.generate_password {
+        for (database, user) in &users {
+            if failed_databases.contains(database) {
+                continue;
+            }
+
+            let password = generate_password();
+            server_connection
+                .send(Request::PasswdUser((user.clone(), password.clone())))
+                .await?;
+            let result = match server_connection.next().await {
+                Some(Ok(Response::SetUserPassword(result))) => result,
+                response => {
+                    erroneous_server_response(response)?;
+                    // Unreachable, but needed to satisfy the type checker
+                    Ok(())
+                }
+            };
+            let output = SetUserPasswordOutput {
+                username: user,
+                result: &result,
+            };
+            print_output(&output, output_options);
+            exit_codes.extend(output.exit_code());
+
+            if result.is_ok() {
+                let output = GeneratedPasswordOutput {
+                    username: user,
+                    password: &password,
+                };
+                // NOTE: the password is printed even with `--quiet`, as it can not be looked up later.
+                if output_options.is_json() {
+                    print_output(&output, output_options);
+                } else {
+                    output.print_human();
+                }
+            } else {
+                failed_databases.insert(database.clone());
+                failed_users.insert(user.clone());
+            }
+        }
+    }
+
+    if !failed_databases.is_empty() {
+        roll_back(
+            &failed_databases,
+            &failed_users,
+            server_connection,
+            output_options,
+        )
+        .await?;
+    }
+
+    Ok(exit_codes)
+}
+
+/// Drop the users and databases that were created by a failed `--with-user`.
+async fn roll_back(
+    databases: &BTreeSet<MySQLDatabase>,
+    users: &BTreeSet<MySQLUser>,
+    server_connection: &mut ClientToServerMessageStream,
+    output_options: &OutputOptions,
+) -> anyhow::Result<()> {
+    if !output_options.is_json() {
+        eprintln!("Setting up the user failed, dropping what was created...");
+    }
+
+    if !users.is_empty() {
+        server_connection
+            .send(Request::DropUsers(users.iter().cloned().collect()))
+            .await?;
+        match server_connection.next().await {
+            Some(Ok(Response::DropUsers(result))) => print_output(&result, output_options),
+            response => erroneous_server_response(response)?,
+        }
+    }
+
+    server_connection
+        .send(Request::DropDatabases(databases.iter().cloned().collect()))
+        .await?;
+    match server_connection.next().await {
+        Some(Ok(Response::DropDatabases(result))) => print_output(&result, output_options),
+        response => erroneous_server_response(response)?,
     }
 
     Ok(())
@@ -1,4 +1,4 @@
-use std::io::IsTerminal;
+use std::{collections::BTreeMap, io::IsTerminal, path::PathBuf};
 
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
@@ -7,18 +7,24 @@ use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{
-        erroneous_server_response, print_authorization_owner_hint,
-        read_password_from_stdin_with_double_check,
+    client::{
+        commands::{
+            BulkOperationItem, confirm_bulk_operation, erroneous_server_response, name_prefix_for,
+            print_authorization_owner_hint, read_password_from_file, read_password_from_stdin,
+            read_password_from_stdin_with_double_check, with_name_prefix,
+        },
+        config as client_config,
+        hooks::{run_post_hook, run_pre_hook},
     },
     core::{
-        completion::prefix_completer,
+        completion::{bare_prefix_completer, prefix_completer},
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
-            ClientToServerMessageStream, CreateUserError, Request, Response,
-            print_create_users_output_status, print_create_users_output_status_json,
-            print_set_password_output_status, request_validation::ValidationError,
+            ClientToServerMessageStream, CreateUserError, CreateUsersOutput, CreateUsersRequest,
+            Request, Response, SetUserPasswordOutput, request_validation::ValidationError,
         },
-        types::MySQLUser,
+        types::{MySQLUser, SecretString},
     },
 };
 
@@ -29,13 +35,48 @@ pub struct CreateUserArgs {
     #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(prefix_completer)))]
     username: Vec<MySQLUser>,
 
+    /// Prepend this prefix and the `_` delimiter to the user names, e.g. one of your groups
+    ///
+    /// Defaults to `prefix` in the client config, for names without a prefix of their own.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(bare_prefix_completer)))]
+    #[arg(long, value_name = "PREFIX")]
+    prefix: Option<String>,
+
     /// Do not ask for a password, leave it unset
-    #[clap(long)]
+    #[clap(long, conflicts_with_all = ["password_file", "password_stdin", "password"])]
     no_password: bool,
 
+    /// Set the password of the new user(s) to the contents of a file, instead of prompting for it
+    #[clap(long, value_name = "PATH", conflicts_with_all = ["password_stdin", "password"])]
+    password_file: Option<PathBuf>,
+
+    /// Read the password of the new user(s) from stdin, instead of prompting for it
+    #[clap(long, conflicts_with = "password")]
+    password_stdin: bool,
+
+    /// Set the password of the new user(s) to this, instead of prompting for it
+    ///
+    /// This is discouraged, as the password ends up in your shell history, and can be seen
+    /// by other users while the command is running. Use `--password-file` or `--password-stdin` if you can.
+    #[clap(long, value_name = "PASSWORD")]
+    password: Option<String>,
+
+    /// Create the user(s) with their accounts locked
+    ///
+    /// Locked users can not log in until they are unlocked with `muscl unlock-user`.
+    #[arg(long)]
+    locked: bool,
+
+    /// Automatically confirm action without prompting
+    ///
+    /// Creating more than one name at once asks for confirmation first.
+    #[arg(short, long)]
+    yes: bool,
+
     /// Print the information as JSON
     ///
-    /// Note that this implies `--no-password`, since the command will become non-interactive.
+    /// Note that this implies `--no-password`, since the command will become non-interactive,
+    /// unless the password is given with `--password-file`, `--password-stdin` or `--password`.
     #[arg(short, long)]
     json: bool,
 }
@@ -44,11 +85,64 @@ pub async fn create_users(
     args: CreateUserArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
     if args.username.is_empty() {
         anyhow::bail!("No usernames provided");
     }
 
-    let message = Request::CreateUsers(args.username.clone());
+    // NOTE: the password is read before creating the users, so that a missing password
+    //       file does not leave the new users without a password.
+    let password = if let Some(password_file) = &args.password_file {
+        Some(read_password_from_file(password_file)?)
+    } else if args.password_stdin {
+        Some(read_password_from_stdin()?)
+    } else {
+        args.password.as_deref().map(SecretString::from)
+    };
+
+    let usernames = args
+        .username
+        .iter()
+        .map(|username| {
+            MySQLUser::from(with_name_prefix(
+                name_prefix_for(
+                    args.prefix.as_deref(),
+                    client_config::get().prefix.as_deref(),
+                    username,
+                ),
+                username,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if usernames.len() > 1 {
+        let preview_items = usernames
+            .iter()
+            .map(|name| BulkOperationItem::new(name))
+            .collect::<Vec<_>>();
+
+        if !confirm_bulk_operation("created", "user", &preview_items, yes, false)? {
+            print_message("Aborting create operation.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    let hook_names = usernames
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("create-user", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::CreateUsers(CreateUsersRequest {
+        users: usernames,
+        locked: args.locked,
+    });
     if let Err(err) = server_connection.send(message).await {
         server_connection.close().await.ok();
         anyhow::bail!(anyhow::Error::from(err).context("Failed to communicate with server"));
@@ -59,33 +153,63 @@ pub async fn create_users(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_create_users_output_status_json(&result);
-    } else {
-        print_create_users_output_status(&result);
+    // NOTE: with `--json`, the results of setting the passwords are printed
+    //       together with the created users, so that stdout is a single document.
+    if !output_options.is_json() {
+        print_output(&result, &output_options);
+    }
+    run_post_hook("create-user", &hook_names, &result);
 
-        if result.iter().any(|(_, res)| {
+    if !output_options.is_json()
+        && result.iter().any(|(_, res)| {
             matches!(
                 res,
                 Err(CreateUserError::ValidationError(
                     ValidationError::AuthorizationError(_)
                 ))
             )
-        }) {
-            print_authorization_owner_hint(&mut server_connection).await?;
-        }
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
 
-        let successfully_created_users = result
-            .iter()
-            .filter_map(|(username, result)| result.as_ref().ok().map(|()| username))
-            .collect::<Vec<_>>();
+    let successfully_created_users = result
+        .iter()
+        .filter_map(|(username, result)| result.as_ref().ok().map(|()| username))
+        .collect::<Vec<_>>();
 
+    let mut password_results = BTreeMap::new();
+    if let Some(password) = &password {
+        for username in successfully_created_users {
+            let message = Request::PasswdUser((username.to_owned(), password.clone()));
+            if let Err(err) = server_connection.send(message).await {
+                server_connection.close().await.ok();
+                anyhow::bail!(err);
+            }
+
+            match server_connection.next().await {
+                Some(Ok(Response::SetUserPassword(result))) => {
+                    if !output_options.is_json() {
+                        print_output(
+                            &SetUserPasswordOutput {
+                                username,
+                                result: &result,
+                            },
+                            &output_options,
+                        );
+                    }
+                    password_results.insert(username.to_owned(), result);
+                }
+                response => return erroneous_server_response(response),
+            }
+        }
+    } else if !output_options.is_json() {
         if !std::io::stdin().is_terminal()
             && !args.no_password
             && !successfully_created_users.is_empty()
         {
             anyhow::bail!(
-                "Cannot prompt for passwords in non-interactive mode. Use --no-password to skip setting passwords."
+                "Cannot prompt for passwords in non-interactive mode. Use --no-password to skip setting passwords, or --password-file or --password-stdin to provide one."
             );
         }
 
@@ -108,7 +232,13 @@ pub async fn create_users(
 
                 match server_connection.next().await {
                     Some(Ok(Response::SetUserPassword(result))) => {
-                        print_set_password_output_status(&result, username);
+                        print_output(
+                            &SetUserPasswordOutput {
+                                username,
+                                result: &result,
+                            },
+                            &output_options,
+                        );
                     }
                     response => return erroneous_server_response(response),
                 }
@@ -120,9 +250,13 @@ pub async fn create_users(
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
+    let output = CreateUsersOutput {
+        result: &result,
+        passwords: &password_results,
+    };
+    if output_options.is_json() {
+        print_output(&output, &output_options);
     }
 
-    Ok(())
+    ensure_success(&output)
 }
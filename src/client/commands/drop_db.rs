@@ -1,21 +1,28 @@
-use std::io::IsTerminal;
+use std::{collections::BTreeMap, io::IsTerminal};
 
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
-use dialoguer::Confirm;
 use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            BulkOperationItem, confirm_bulk_operation, erroneous_server_response,
+            pick_databases_interactively, print_authorization_owner_hint, print_did_you_mean_hint,
+        },
+        config as client_config,
+        hooks::{run_post_hook, run_pre_hook},
+    },
     core::{
         completion::mysql_database_completer,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
             ClientToServerMessageStream, DropDatabaseError, Request, Response,
-            print_drop_databases_output_status, print_drop_databases_output_status_json,
             request_validation::ValidationError,
         },
-        types::MySQLDatabase,
+        types::{DbOrUser, MySQLDatabase},
     },
 };
 
@@ -36,39 +43,49 @@ pub struct DropDbArgs {
 }
 
 pub async fn drop_databases(
-    args: DropDbArgs,
+    mut args: DropDbArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    if args.name.is_empty() {
-        anyhow::bail!("No database names provided");
-    }
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
 
-    if !std::io::stdin().is_terminal() && !args.yes {
-        anyhow::bail!(
-            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
-        );
-    }
+    if args.name.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("No database names provided");
+        }
 
-    if !args.yes {
-        let confirmation = Confirm::new()
-            .with_prompt(format!(
-                "Are you sure you want to drop the databases?\n\n{}\n\nThis action cannot be undone",
-                args.name
-                    .iter()
-                    .map(|d| format!("- {d}"))
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            ))
-            .interact()?;
-
-        if !confirmation {
-            // TODO: should we return with an error code here?
-            println!("Aborting drop operation.");
+        args.name =
+            pick_databases_interactively(&mut server_connection, "Databases to drop").await?;
+        if args.name.is_empty() {
+            print_message("No databases selected.", &output_options);
             server_connection.send(Request::Exit).await?;
             return Ok(());
         }
     }
 
+    let preview_items = if yes {
+        Vec::new()
+    } else {
+        fetch_drop_preview_items(&mut server_connection, &args.name).await?
+    };
+
+    if !confirm_bulk_operation("dropped", "database", &preview_items, yes, true)? {
+        // TODO: should we return with an error code here?
+        print_message("Aborting drop operation.", &output_options);
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    let hook_names = args
+        .name
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("drop-db", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
     let message = Request::DropDatabases(args.name.clone());
     server_connection.send(message).await?;
 
@@ -77,11 +94,10 @@ pub async fn drop_databases(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_drop_databases_output_status_json(&result);
-    } else {
-        print_drop_databases_output_status(&result);
+    print_output(&result, &output_options);
+    run_post_hook("drop-db", &hook_names, &result);
 
+    if !output_options.is_json() {
         if result.iter().any(|(_, res)| {
             matches!(
                 res,
@@ -92,13 +108,49 @@ pub async fn drop_databases(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(DropDatabaseError::DatabaseDoesNotExist)))
+            .map(|(name, _)| DbOrUser::Database(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
+    ensure_success(&result)
+}
+
+/// Look up the sizes of the databases about to be dropped, for the confirmation preview.
+///
+/// Databases that can not be listed are shown without a size, and will
+/// report their error when the drop is attempted.
+async fn fetch_drop_preview_items(
+    server_connection: &mut ClientToServerMessageStream,
+    names: &[MySQLDatabase],
+) -> anyhow::Result<Vec<BulkOperationItem>> {
+    server_connection
+        .send(Request::ListDatabases(Some(names.to_vec())))
+        .await?;
+
+    let databases = match server_connection.next().await {
+        Some(Ok(Response::ListDatabases(databases))) => databases,
+        response => {
+            erroneous_server_response(response)?;
+            // Unreachable, but needed to satisfy the type checker
+            BTreeMap::new()
+        }
+    };
 
-    Ok(())
+    Ok(names
+        .iter()
+        .map(|name| {
+            let size_bytes = databases
+                .get(name)
+                .and_then(|res| res.as_ref().ok())
+                .map(|row| row.size_bytes);
+            BulkOperationItem::with_size(name, size_bytes)
+        })
+        .collect())
 }
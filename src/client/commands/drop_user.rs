@@ -2,20 +2,27 @@ use std::io::IsTerminal;
 
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
-use dialoguer::Confirm;
 use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            BulkOperationItem, confirm_bulk_operation, erroneous_server_response,
+            pick_users_interactively, print_authorization_owner_hint, print_did_you_mean_hint,
+        },
+        config as client_config,
+        hooks::{run_post_hook, run_pre_hook},
+    },
     core::{
         completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
             ClientToServerMessageStream, DropUserError, Request, Response,
-            print_drop_users_output_status, print_drop_users_output_status_json,
             request_validation::ValidationError,
         },
-        types::MySQLUser,
+        types::{DbOrUser, MySQLUser},
     },
 };
 
@@ -36,39 +43,48 @@ pub struct DropUserArgs {
 }
 
 pub async fn drop_users(
-    args: DropUserArgs,
+    mut args: DropUserArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    if args.username.is_empty() {
-        anyhow::bail!("No usernames provided");
-    }
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
 
-    if !std::io::stdin().is_terminal() && !args.yes {
-        anyhow::bail!(
-            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
-        );
-    }
+    if args.username.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("No usernames provided");
+        }
 
-    if !args.yes {
-        let confirmation = Confirm::new()
-            .with_prompt(format!(
-                "Are you sure you want to drop the users?\n\n{}\n\nThis action cannot be undone",
-                args.username
-                    .iter()
-                    .map(|d| format!("- {d}"))
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            ))
-            .interact()?;
-
-        if !confirmation {
-            // TODO: should we return with an error code here?
-            println!("Aborting drop operation.");
+        args.username = pick_users_interactively(&mut server_connection, "Users to drop").await?;
+        if args.username.is_empty() {
+            print_message("No users selected.", &output_options);
             server_connection.send(Request::Exit).await?;
             return Ok(());
         }
     }
 
+    let preview_items = args
+        .username
+        .iter()
+        .map(|username| BulkOperationItem::new(username))
+        .collect::<Vec<_>>();
+
+    if !confirm_bulk_operation("dropped", "user", &preview_items, yes, true)? {
+        // TODO: should we return with an error code here?
+        print_message("Aborting drop operation.", &output_options);
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    let hook_names = args
+        .username
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("drop-user", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
     let message = Request::DropUsers(args.username.clone());
 
     if let Err(err) = server_connection.send(message).await {
@@ -81,11 +97,10 @@ pub async fn drop_users(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_drop_users_output_status_json(&result);
-    } else {
-        print_drop_users_output_status(&result);
+    print_output(&result, &output_options);
+    run_post_hook("drop-user", &hook_names, &result);
 
+    if !output_options.is_json() {
         if result.iter().any(|(_, res)| {
             matches!(
                 res,
@@ -96,13 +111,16 @@ pub async fn drop_users(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(DropUserError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&result)
 }
@@ -0,0 +1,241 @@
+use std::{collections::BTreeSet, io::IsTerminal};
+
+use anyhow::Context;
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use dialoguer::{Confirm, Editor};
+use futures_util::SinkExt;
+use nix::unistd::{User, getuid};
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{
+        commands::{
+            erroneous_server_response, fetch_privilege_aliases, print_authorization_owner_hint,
+        },
+        config as client_config,
+        editor::resolve_editor,
+        hooks::{run_post_hook, run_pre_hook},
+    },
+    core::{
+        column_privileges::{
+            ColumnPrivilegeEditEntry, ColumnPrivilegeRow, ColumnPrivilegesDiff,
+            create_or_modify_column_privilege_rows, diff_column_privileges,
+            display_column_privilege_diffs, generate_column_editor_content,
+            parse_column_privilege_data_from_editor_content,
+        },
+        completion::mysql_database_completer,
+        database_privileges::PrivilegeAliases,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            ClientToServerMessageStream, ListColumnPrivilegesRequest, ModifyColumnPrivilegesError,
+            ModifyColumnPrivilegesRequest, ModifyColumnPrivilegesResponse, Request, Response,
+            request_validation::ValidationError,
+        },
+        types::MySQLDatabase,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct EditColumnPrivsArgs {
+    /// Only edit the column privileges on this database
+    ///
+    /// This opens the editor with only the rows for the given database.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    #[arg(value_name = "DB_NAME", conflicts_with = "privs")]
+    pub db_name: Option<MySQLDatabase>,
+
+    /// The privileges to set, grant or revoke, in the format `DB_NAME.TABLE_NAME.COLUMN_NAME:USER_NAME:[+-]PRIVILEGES`
+    ///
+    /// The privileges are any of `s` (SELECT), `i` (INSERT), `u` (UPDATE) and `r` (REFERENCES),
+    /// or `A` for all of them.
+    #[arg(
+      short,
+      long,
+      value_name = "DB_NAME.TABLE_NAME.COLUMN_NAME:USER_NAME:[+-]PRIVILEGES",
+      num_args = 0..,
+    )]
+    pub privs: Vec<String>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    pub json: bool,
+
+    /// Specify the text editor to use for editing privileges
+    ///
+    /// Defaults to `editor` in the client config, `$VISUAL`, `$EDITOR`,
+    /// or the first of `editor`, `nano`, `vim` and `vi` that is installed.
+    #[arg(
+      short,
+      long,
+      value_name = "COMMAND",
+      value_hint = clap::ValueHint::CommandString,
+    )]
+    pub editor: Option<String>,
+
+    /// Disable interactive confirmation before saving changes
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+pub async fn edit_column_privileges(
+    args: EditColumnPrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    let privilege_aliases = if args.privs.is_empty() {
+        PrivilegeAliases::new()
+    } else {
+        fetch_privilege_aliases(&mut server_connection).await?
+    };
+    let edits = args
+        .privs
+        .iter()
+        .map(|arg| ColumnPrivilegeEditEntry::parse_from_str(arg, &privilege_aliases))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let message = Request::ListColumnPrivileges(ListColumnPrivilegesRequest {
+        databases: args.db_name.clone().map(|db| vec![db]),
+        user: None,
+    });
+    server_connection.send(message).await?;
+
+    let existing_privilege_rows = match server_connection.next().await {
+        Some(Ok(Response::ListColumnPrivileges(databases))) => databases
+            .into_iter()
+            .filter_map(|(database_name, result)| match result {
+                Ok(privileges) => Some(privileges),
+                Err(err) => {
+                    eprintln!("{}", err.to_error_message(&database_name));
+                    eprintln!("Skipping...");
+                    eprintln!();
+                    None
+                }
+            })
+            .flatten()
+            .collect::<Vec<_>>(),
+        response => return erroneous_server_response(response),
+    };
+
+    let diffs: BTreeSet<ColumnPrivilegesDiff> = if edits.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Cannot launch editor in non-interactive mode. Please provide privileges via command line arguments."
+            );
+        }
+        let editor = resolve_editor(args.editor.as_deref())?;
+        let privileges_to_change =
+            edit_column_privileges_with_editor(&editor, &existing_privilege_rows)?;
+        diff_column_privileges(&existing_privilege_rows, &privileges_to_change)
+    } else {
+        create_or_modify_column_privilege_rows(&existing_privilege_rows, &edits)
+    };
+
+    if diffs.is_empty() {
+        if output_options.is_json() {
+            print_output(&ModifyColumnPrivilegesResponse::new(), &output_options);
+        } else {
+            println!("No changes to make.");
+        }
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    print_message("The following changes will be made:\n", &output_options);
+    print_message(&display_column_privilege_diffs(&diffs), &output_options);
+
+    if std::io::stdin().is_terminal()
+        && !yes
+        && !Confirm::new()
+            .with_prompt("Do you want to apply these changes?")
+            .default(false)
+            .show_default(true)
+            .interact()?
+    {
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    let hook_names = diffs
+        .iter()
+        .map(|diff| diff.key().to_string())
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("edit-column-privs", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::ModifyColumnPrivileges(ModifyColumnPrivilegesRequest { diffs });
+    server_connection.send(message).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::ModifyColumnPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&result, &output_options);
+    run_post_hook("edit-column-privs", &hook_names, &result);
+
+    if !output_options.is_json()
+        && result.values().any(|res| {
+            matches!(
+                res,
+                Err(ModifyColumnPrivilegesError::UserValidationError(
+                    ValidationError::AuthorizationError(_)
+                ) | ModifyColumnPrivilegesError::DatabaseValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
+
+fn edit_column_privileges_with_editor(
+    editor: &str,
+    privilege_data: &[ColumnPrivilegeRow],
+) -> anyhow::Result<Vec<ColumnPrivilegeRow>> {
+    let unix_user = User::from_uid(getuid())
+        .context("Failed to look up your UNIX username")
+        .and_then(|u| u.ok_or(anyhow::anyhow!("Failed to look up your UNIX username")))?;
+
+    let mut editor_content = generate_column_editor_content(privilege_data, &unix_user.name);
+
+    // Re-open the editor until the content can be parsed,
+    // or the user quits the editor without saving.
+    loop {
+        let result = Editor::new()
+            .executable(editor)
+            .extension("tsv")
+            .edit(&editor_content)
+            .with_context(|| format!("Failed to run the editor '{editor}'"))?;
+
+        let Some(result) = result else {
+            return Ok(privilege_data.to_vec());
+        };
+
+        match parse_column_privilege_data_from_editor_content(&result) {
+            Ok(privileges) => return Ok(privileges),
+            Err(err) => {
+                eprintln!("Could not parse column privilege data from editor: {err:#}");
+                if !Confirm::new()
+                    .with_prompt("Do you want to fix the errors in the editor?")
+                    .default(true)
+                    .show_default(true)
+                    .interact()?
+                {
+                    return Err(err.context("Could not parse column privilege data from editor"));
+                }
+                editor_content = result;
+            }
+        }
+    }
+}
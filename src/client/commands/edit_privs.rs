@@ -12,22 +12,41 @@ use nix::unistd::{User, getuid};
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            erroneous_server_response, fetch_database_names, fetch_database_privilege_fields,
+            fetch_privilege_aliases, fetch_privilege_templates, fetch_user_names,
+            print_authorization_owner_hint, print_did_you_mean_hint,
+        },
+        config as client_config,
+        editor::resolve_editor,
+        hooks::{run_post_hook, run_pre_hook},
+        progress::next_response_with_progress,
+    },
     core::{
-        completion::{mysql_database_completer, mysql_user_completer},
+        completion::{
+            database_name_and_prefix_completer, mysql_user_completer, privilege_completer,
+            privilege_edit_entry_completer, user_name_and_prefix_completer,
+        },
         database_privileges::{
             DatabasePrivilegeEdit, DatabasePrivilegeEditEntry, DatabasePrivilegeRow,
-            DatabasePrivilegeRowDiff, DatabasePrivilegesDiff, create_or_modify_privilege_rows,
-            diff_privileges, display_privilege_diffs, generate_editor_content_from_privilege_data,
+            DatabasePrivilegeRowDiff, DatabasePrivilegesDiff, DiffFormat, PrivilegeAliases,
+            annotate_editor_content_with_errors, create_or_modify_privilege_rows, diff_privileges,
+            display_privilege_diffs, display_privilege_diffs_unified,
+            generate_editor_content_from_privilege_data, keep_hidden_privileges,
             parse_privilege_data_from_editor_content, reduce_privilege_diffs,
         },
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
-            ClientToServerMessageStream, ListDatabasesError, ListUsersError,
-            ModifyDatabasePrivilegesError, Request, Response,
-            print_modify_database_privileges_output_status, request_validation::ValidationError,
+            ClientToServerMessageStream, ListDatabasesError, ListPrivilegesRequest, ListUsersError,
+            ModifyDatabasePrivilegesError, ModifyPrivilegesRequest, ModifyPrivilegesResponse,
+            Request, Response, SchedulePrivilegeChangesRequest,
+            request_validation::ValidationError,
         },
-        types::{MySQLDatabase, MySQLUser},
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
     },
+    server::scheduler::{format_utc_datetime, parse_utc_datetime},
 };
 
 #[derive(Parser, Debug, Clone)]
@@ -36,25 +55,38 @@ pub struct EditPrivsArgs {
     ///
     /// This option allows for changing privileges for multiple databases and users in batch.
     ///
+    /// Besides the privilege characters, `PRIVILEGES` can be a comma separated list of the
+    /// privilege aliases configured on the server, and privilege characters, e.g. `+rw,t`.
+    ///
     /// This can not be used together with the positional `DB_NAME`, `USER_NAME` and `PRIVILEGES` arguments.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(privilege_edit_entry_completer)))]
     #[arg(
       short,
       long,
       value_name = "DB_NAME:USER_NAME:[+-]PRIVILEGES",
       num_args = 0..,
-      value_parser = DatabasePrivilegeEditEntry::parse_from_str,
       conflicts_with("single_priv"),
     )]
-    pub privs: Vec<DatabasePrivilegeEditEntry>,
+    pub privs: Vec<String>,
 
     #[command(flatten)]
     pub single_priv: Option<SinglePrivilegeEditArgs>,
 
+    /// Only edit the privileges of this user, across all of your databases
+    ///
+    /// This opens the editor with only the rows of the given user.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(long, value_name = "USER_NAME", conflicts_with_all = ["privs", "db_name"])]
+    pub user: Option<MySQLUser>,
+
     /// Print the information as JSON
     #[arg(short, long)]
     pub json: bool,
 
     /// Specify the text editor to use for editing privileges
+    ///
+    /// Defaults to `editor` in the client config, `$VISUAL`, `$EDITOR`,
+    /// or the first of `editor`, `nano`, `vim` and `vi` that is installed.
     #[arg(
       short,
       long,
@@ -63,6 +95,27 @@ pub struct EditPrivsArgs {
     )]
     pub editor: Option<String>,
 
+    /// How to display the changes before they are applied
+    ///
+    /// The unified format can be copied as plain text, e.g. into a ticket.
+    #[arg(long, value_name = "FORMAT", default_value_t, value_enum)]
+    pub diff_format: DiffFormat,
+
+    /// Apply either all of the changes or none of them
+    ///
+    /// By default, every change is applied on its own, so that a single failing change
+    /// does not stop the others. With this flag, all changes are validated first,
+    /// and then applied in a single transaction.
+    #[arg(long)]
+    pub atomic: bool,
+
+    /// Let the server apply the changes at a later time, given as `YYYY-MM-DDTHH:MM` in UTC
+    ///
+    /// The changes are approved now, and applied by the server at the given time,
+    /// e.g. to coincide with a deployment at night.
+    #[arg(long, value_name = "TIME", value_parser = parse_utc_datetime)]
+    pub at: Option<u64>,
+
     /// Disable interactive confirmation before saving changes
     #[arg(short, long)]
     pub yes: bool,
@@ -71,7 +124,7 @@ pub struct EditPrivsArgs {
 #[derive(Args, Debug, Clone)]
 pub struct SinglePrivilegeEditArgs {
     /// The `MySQL` database to edit privileges for
-    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(database_name_and_prefix_completer)))]
     #[arg(
         value_name = "DB_NAME",
         requires = "user_name",
@@ -80,17 +133,14 @@ pub struct SinglePrivilegeEditArgs {
     pub db_name: Option<MySQLDatabase>,
 
     /// The `MySQL` database to edit privileges for
-    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(user_name_and_prefix_completer)))]
     #[arg(value_name = "USER_NAME")]
     pub user_name: Option<MySQLUser>,
 
-    /// The privileges to set, grant or revoke
-    #[arg(
-      allow_hyphen_values = true,
-      value_name = "[+-]PRIVILEGES",
-      value_parser = DatabasePrivilegeEdit::parse_from_str,
-    )]
-    pub single_priv: Option<DatabasePrivilegeEdit>,
+    /// The privileges to set, grant or revoke, as privilege characters and/or privilege aliases
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(privilege_completer)))]
+    #[arg(allow_hyphen_values = true, value_name = "[+-]PRIVILEGES")]
+    pub single_priv: Option<String>,
 }
 
 async fn users_exist(
@@ -151,6 +201,34 @@ async fn databases_exist(
     Ok(result)
 }
 
+/// Fetch the databases and users to suggest new privilege rows for in the editor,
+/// limited to the database or user that is being edited, if any.
+async fn fetch_suggestion_candidates(
+    server_connection: &mut ClientToServerMessageStream,
+    args: &EditPrivsArgs,
+    use_database: Option<&MySQLDatabase>,
+) -> anyhow::Result<(Vec<MySQLDatabase>, Vec<MySQLUser>)> {
+    let databases = match use_database {
+        Some(database) => vec![database.clone()],
+        None => fetch_database_names(server_connection)
+            .await?
+            .into_iter()
+            .map(MySQLDatabase::from)
+            .collect(),
+    };
+
+    let users = match &args.user {
+        Some(user) => vec![user.clone()],
+        None => fetch_user_names(server_connection)
+            .await?
+            .into_iter()
+            .map(MySQLUser::from)
+            .collect(),
+    };
+
+    Ok((databases, users))
+}
+
 // TODO: reduce the complexity of this function
 pub async fn edit_database_privileges(
     args: EditPrivsArgs,
@@ -158,12 +236,27 @@ pub async fn edit_database_privileges(
     use_database: Option<MySQLDatabase>,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let message = Request::ListPrivileges(use_database.clone().map(|db| vec![db]));
-
-    server_connection.send(message).await?;
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
 
     debug_assert!(args.privs.is_empty() ^ args.single_priv.is_none());
 
+    // NOTE: the privileges given on the command line can only be parsed once the
+    //       privilege aliases of the server are known.
+    let privilege_aliases = if args.privs.is_empty() && args.single_priv.is_none() {
+        PrivilegeAliases::new()
+    } else {
+        fetch_privilege_aliases(&mut server_connection).await?
+    };
+    let privilege_fields = fetch_database_privilege_fields(&mut server_connection).await?;
+
+    let message = Request::ListPrivileges(ListPrivilegesRequest {
+        databases: use_database.clone().map(|db| vec![db]),
+        user: args.user.clone(),
+    });
+
+    server_connection.send(message).await?;
+
     let privs = if let Some(single_priv_entry) = &args.single_priv {
         let database = single_priv_entry.db_name.clone().ok_or_else(|| {
             anyhow::anyhow!(
@@ -175,11 +268,13 @@ pub async fn edit_database_privileges(
                 "USER_NAME must be specified when DB_NAME is specified in single privilege mode"
             )
         })?;
-        let privilege_edit = single_priv_entry.single_priv.clone().ok_or_else(|| {
+        let privilege_edit = single_priv_entry.single_priv.as_deref().ok_or_else(|| {
             anyhow::anyhow!(
                 "PRIVILEGES must be specified when DB_NAME is specified in single privilege mode"
             )
         })?;
+        let privilege_edit =
+            DatabasePrivilegeEdit::parse_from_str(privilege_edit, &privilege_aliases)?;
 
         vec![DatabasePrivilegeEditEntry {
             database,
@@ -187,7 +282,10 @@ pub async fn edit_database_privileges(
             privilege_edit,
         }]
     } else {
-        args.privs.clone()
+        args.privs
+            .iter()
+            .map(|arg| DatabasePrivilegeEditEntry::parse_from_str(arg, &privilege_aliases))
+            .collect::<anyhow::Result<Vec<_>>>()?
     };
 
     let existing_privilege_rows = match server_connection.next().await {
@@ -198,7 +296,7 @@ pub async fn edit_database_privileges(
                 Err(err) => {
                     eprintln!("{}", err.to_error_message(&database_name));
                     eprintln!("Skipping...");
-                    println!();
+                    eprintln!();
                     None
                 }
             })
@@ -221,12 +319,27 @@ pub async fn edit_database_privileges(
                 "Cannot launch editor in non-interactive mode. Please provide privileges via command line arguments."
             );
         }
-        let privileges_to_change =
-            edit_privileges_with_editor(&existing_privilege_rows, use_database.as_ref())?;
+        let editor = resolve_editor(args.editor.as_deref())?;
+        let (suggested_databases, suggested_users) =
+            fetch_suggestion_candidates(&mut server_connection, &args, use_database.as_ref())
+                .await?;
+        let privileges_to_change = edit_privileges_with_editor(
+            &editor,
+            &existing_privilege_rows,
+            privilege_fields,
+            use_database.as_ref(),
+            &suggested_databases,
+            &suggested_users,
+        )?;
         diff_privileges(&existing_privilege_rows, &privileges_to_change)
     } else {
         let privileges_to_change = parse_privilege_tables(&privs)?;
-        create_or_modify_privilege_rows(&existing_privilege_rows, &privileges_to_change)?
+        let privilege_templates = fetch_privilege_templates(&mut server_connection).await?;
+        create_or_modify_privilege_rows(
+            &existing_privilege_rows,
+            &privileges_to_change,
+            &privilege_templates,
+        )?
     };
 
     let database_existence_map = databases_exist(&mut server_connection, &diffs).await?;
@@ -239,14 +352,14 @@ pub async fn edit_database_privileges(
             let username = diff.get_user_name();
 
             if let Some(Err(err)) = database_existence_map.get(database_name) {
-                println!("{}", err.to_error_message(database_name));
-                println!("Skipping...");
+                print_message(&err.to_error_message(database_name), &output_options);
+                print_message("Skipping...", &output_options);
                 return false;
             }
 
             if let Some(Err(err)) = user_existence_map.get(username) {
-                println!("{}", err.to_error_message(username));
-                println!("Skipping...");
+                print_message(&err.to_error_message(username), &output_options);
+                print_message("Skipping...", &output_options);
                 return false;
             }
 
@@ -269,22 +382,54 @@ pub async fn edit_database_privileges(
             ))
         )
     }) {
-        println!();
+        eprintln!();
         print_authorization_owner_hint(&mut server_connection).await?;
-        println!();
+        eprintln!();
     }
 
+    let missing = database_existence_map
+        .iter()
+        .filter(|(_, res)| matches!(res, Err(ListDatabasesError::DatabaseDoesNotExist)))
+        .map(|(name, _)| DbOrUser::Database(name.clone()))
+        .chain(
+            user_existence_map
+                .iter()
+                .filter(|(_, res)| matches!(res, Err(ListUsersError::UserDoesNotExist)))
+                .map(|(name, _)| DbOrUser::User(name.clone())),
+        )
+        .collect::<Vec<_>>();
+    print_did_you_mean_hint(&mut server_connection, &missing).await?;
+
     if diffs.is_empty() {
-        println!("No changes to make.");
+        if output_options.is_json() {
+            print_output(&ModifyPrivilegesResponse::new(), &output_options);
+        } else {
+            println!("No changes to make.");
+        }
         server_connection.send(Request::Exit).await?;
         return Ok(());
     }
 
-    println!("The following changes will be made:\n");
-    println!("{}", display_privilege_diffs(&diffs));
+    match args.at {
+        Some(apply_at) => print_message(
+            &format!(
+                "The following changes will be made at {}:\n",
+                format_utc_datetime(apply_at)
+            ),
+            &output_options,
+        ),
+        None => print_message("The following changes will be made:\n", &output_options),
+    }
+    match args.diff_format {
+        DiffFormat::Table => print_message(&display_privilege_diffs(&diffs), &output_options),
+        DiffFormat::Unified => print_message(
+            &display_privilege_diffs_unified(&existing_privilege_rows, &diffs, privilege_fields),
+            &output_options,
+        ),
+    }
 
     if std::io::stdin().is_terminal()
-        && !args.yes
+        && !yes
         && !Confirm::new()
             .with_prompt("Do you want to apply these changes?")
             .default(false)
@@ -295,36 +440,68 @@ pub async fn edit_database_privileges(
         return Ok(());
     }
 
-    let message = Request::ModifyPrivileges(diffs);
+    let hook_names = diffs
+        .iter()
+        .map(|diff| format!("{}:{}", diff.get_database_name(), diff.get_user_name()))
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("edit-privs", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    if let Some(apply_at) = args.at {
+        let message = Request::SchedulePrivilegeChanges(SchedulePrivilegeChangesRequest {
+            diffs,
+            atomic: args.atomic,
+            apply_at,
+        });
+        server_connection.send(message).await?;
+
+        let result = match server_connection.next().await {
+            Some(Ok(Response::SchedulePrivilegeChanges(result))) => result,
+            response => return erroneous_server_response(response),
+        };
+
+        print_output(&result, &output_options);
+        run_post_hook("edit-privs", &hook_names, &result);
+
+        server_connection.send(Request::Exit).await?;
+
+        return ensure_success(&result);
+    }
+
+    let message = Request::ModifyPrivileges(ModifyPrivilegesRequest {
+        diffs,
+        atomic: args.atomic,
+    });
     server_connection.send(message).await?;
 
-    let result = match server_connection.next().await {
+    let result = match next_response_with_progress(&mut server_connection).await {
         Some(Ok(Response::ModifyPrivileges(result))) => result,
         response => return erroneous_server_response(response),
     };
 
-    print_modify_database_privileges_output_status(&result);
-
-    if result.iter().any(|(_, res)| {
-        matches!(
-            res,
-            Err(ModifyDatabasePrivilegesError::UserValidationError(
-                ValidationError::AuthorizationError(_)
-            ) | ModifyDatabasePrivilegesError::DatabaseValidationError(
-                ValidationError::AuthorizationError(_)
-            ))
-        )
-    }) {
+    print_output(&result, &output_options);
+    run_post_hook("edit-privs", &hook_names, &result);
+
+    if !output_options.is_json()
+        && result.iter().any(|(_, res)| {
+            matches!(
+                res,
+                Err(ModifyDatabasePrivilegesError::UserValidationError(
+                    ValidationError::AuthorizationError(_)
+                ) | ModifyDatabasePrivilegesError::DatabaseValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        })
+    {
         print_authorization_owner_hint(&mut server_connection).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&result)
 }
 
 fn parse_privilege_tables(
@@ -344,23 +521,57 @@ fn parse_privilege_tables(
 }
 
 fn edit_privileges_with_editor(
+    editor: &str,
     privilege_data: &[DatabasePrivilegeRow],
+    privilege_fields: &[&str],
     // NOTE: this is only used for backwards compat with mysql-admtools
     database_name: Option<&MySQLDatabase>,
+    suggested_databases: &[MySQLDatabase],
+    suggested_users: &[MySQLUser],
 ) -> anyhow::Result<Vec<DatabasePrivilegeRow>> {
     let unix_user = User::from_uid(getuid())
         .context("Failed to look up your UNIX username")
         .and_then(|u| u.ok_or(anyhow::anyhow!("Failed to look up your UNIX username")))?;
 
-    let editor_content =
-        generate_editor_content_from_privilege_data(privilege_data, &unix_user.name, database_name);
-
-    // TODO: handle errors better here
-    let result = Editor::new().extension("tsv").edit(&editor_content)?;
-
-    match result {
-        None => Ok(privilege_data.to_vec()),
-        Some(result) => parse_privilege_data_from_editor_content(&result)
-            .context("Could not parse privilege data from editor"),
+    let mut editor_content = generate_editor_content_from_privilege_data(
+        privilege_data,
+        privilege_fields,
+        &unix_user.name,
+        database_name,
+        suggested_databases,
+        suggested_users,
+    );
+
+    // Re-open the editor with the errors marked until the content can be parsed,
+    // or the user quits the editor without saving.
+    loop {
+        let result = Editor::new()
+            .executable(editor)
+            .extension("tsv")
+            .edit(&editor_content)
+            .with_context(|| format!("Failed to run the editor '{editor}'"))?;
+
+        let Some(result) = result else {
+            return Ok(privilege_data.to_vec());
+        };
+
+        match parse_privilege_data_from_editor_content(&result, privilege_fields) {
+            Ok(mut privileges) => {
+                keep_hidden_privileges(&mut privileges, privilege_data, privilege_fields);
+                return Ok(privileges);
+            }
+            Err(err) => {
+                eprintln!("Could not parse privilege data from editor: {err:#}");
+                if !Confirm::new()
+                    .with_prompt("Do you want to fix the errors in the editor?")
+                    .default(true)
+                    .show_default(true)
+                    .interact()?
+                {
+                    return Err(err.context("Could not parse privilege data from editor"));
+                }
+                editor_content = annotate_editor_content_with_errors(&result, privilege_fields);
+            }
+        }
     }
 }
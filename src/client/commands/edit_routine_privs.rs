@@ -0,0 +1,259 @@
+use std::{collections::BTreeSet, io::IsTerminal};
+
+use anyhow::Context;
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use dialoguer::{Confirm, Editor};
+use futures_util::SinkExt;
+use nix::unistd::{User, getuid};
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{
+        commands::{
+            erroneous_server_response, fetch_privilege_aliases, print_authorization_owner_hint,
+        },
+        config as client_config,
+        editor::resolve_editor,
+        hooks::{run_post_hook, run_pre_hook},
+    },
+    core::{
+        completion::mysql_database_completer,
+        database_privileges::PrivilegeAliases,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            ClientToServerMessageStream, ListRoutinePrivilegesRequest,
+            ModifyRoutinePrivilegesError, ModifyRoutinePrivilegesRequest,
+            ModifyRoutinePrivilegesResponse, Request, Response,
+            request_validation::ValidationError,
+        },
+        routine_privileges::{
+            RoutinePrivilegeEditEntry, RoutinePrivilegeRow, RoutinePrivilegesDiff, StoredRoutine,
+            create_or_modify_routine_privilege_rows, diff_routine_privileges,
+            display_routine_privilege_diffs, generate_routine_editor_content,
+            parse_routine_privilege_data_from_editor_content,
+        },
+        types::MySQLDatabase,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct EditRoutinePrivsArgs {
+    /// Only edit the routine privileges on this database
+    ///
+    /// This opens the editor with only the rows for the given database.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    #[arg(value_name = "DB_NAME", conflicts_with = "privs")]
+    pub db_name: Option<MySQLDatabase>,
+
+    /// The privileges to set, grant or revoke, in the format `[procedure:|function:]DB_NAME.ROUTINE_NAME:USER_NAME:[+-]PRIVILEGES`
+    ///
+    /// The privileges are any of `x` (EXECUTE) and `P` (ALTER ROUTINE), or `A` for both of them.
+    /// The routine type only has to be given if both a procedure and a function have the name.
+    #[arg(
+      short,
+      long,
+      value_name = "[procedure:|function:]DB_NAME.ROUTINE_NAME:USER_NAME:[+-]PRIVILEGES",
+      num_args = 0..,
+    )]
+    pub privs: Vec<String>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    pub json: bool,
+
+    /// Specify the text editor to use for editing privileges
+    ///
+    /// Defaults to `editor` in the client config, `$VISUAL`, `$EDITOR`,
+    /// or the first of `editor`, `nano`, `vim` and `vi` that is installed.
+    #[arg(
+      short,
+      long,
+      value_name = "COMMAND",
+      value_hint = clap::ValueHint::CommandString,
+    )]
+    pub editor: Option<String>,
+
+    /// Disable interactive confirmation before saving changes
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+pub async fn edit_routine_privileges(
+    args: EditRoutinePrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    let privilege_aliases = if args.privs.is_empty() {
+        PrivilegeAliases::new()
+    } else {
+        fetch_privilege_aliases(&mut server_connection).await?
+    };
+    let edits = args
+        .privs
+        .iter()
+        .map(|arg| RoutinePrivilegeEditEntry::parse_from_str(arg, &privilege_aliases))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let message = Request::ListRoutinePrivileges(ListRoutinePrivilegesRequest {
+        databases: args.db_name.clone().map(|db| vec![db]),
+        user: None,
+    });
+    server_connection.send(message).await?;
+
+    let existing_privilege_rows = match server_connection.next().await {
+        Some(Ok(Response::ListRoutinePrivileges(databases))) => databases
+            .into_iter()
+            .filter_map(|(database_name, result)| match result {
+                Ok(privileges) => Some(privileges),
+                Err(err) => {
+                    eprintln!("{}", err.to_error_message(&database_name));
+                    eprintln!("Skipping...");
+                    eprintln!();
+                    None
+                }
+            })
+            .flatten()
+            .collect::<Vec<_>>(),
+        response => return erroneous_server_response(response),
+    };
+
+    // NOTE: errors for the databases were already printed above.
+    server_connection
+        .send(Request::ListRoutines(
+            args.db_name.clone().map(|db| vec![db]),
+        ))
+        .await?;
+    let routines = match server_connection.next().await {
+        Some(Ok(Response::ListRoutines(databases))) => databases
+            .into_values()
+            .filter_map(Result::ok)
+            .flatten()
+            .collect::<Vec<_>>(),
+        response => return erroneous_server_response(response),
+    };
+
+    let diffs: BTreeSet<RoutinePrivilegesDiff> = if edits.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Cannot launch editor in non-interactive mode. Please provide privileges via command line arguments."
+            );
+        }
+        let editor = resolve_editor(args.editor.as_deref())?;
+        let privileges_to_change =
+            edit_routine_privileges_with_editor(&editor, &existing_privilege_rows, &routines)?;
+        diff_routine_privileges(&existing_privilege_rows, &privileges_to_change)
+    } else {
+        create_or_modify_routine_privilege_rows(&existing_privilege_rows, &edits, &routines)?
+    };
+
+    if diffs.is_empty() {
+        if output_options.is_json() {
+            print_output(&ModifyRoutinePrivilegesResponse::new(), &output_options);
+        } else {
+            println!("No changes to make.");
+        }
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    print_message("The following changes will be made:\n", &output_options);
+    print_message(&display_routine_privilege_diffs(&diffs), &output_options);
+
+    if std::io::stdin().is_terminal()
+        && !yes
+        && !Confirm::new()
+            .with_prompt("Do you want to apply these changes?")
+            .default(false)
+            .show_default(true)
+            .interact()?
+    {
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    let hook_names = diffs
+        .iter()
+        .map(|diff| diff.key().to_string())
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("edit-routine-privs", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::ModifyRoutinePrivileges(ModifyRoutinePrivilegesRequest { diffs });
+    server_connection.send(message).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::ModifyRoutinePrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&result, &output_options);
+    run_post_hook("edit-routine-privs", &hook_names, &result);
+
+    if !output_options.is_json()
+        && result.values().any(|res| {
+            matches!(
+                res,
+                Err(ModifyRoutinePrivilegesError::UserValidationError(
+                    ValidationError::AuthorizationError(_)
+                ) | ModifyRoutinePrivilegesError::DatabaseValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
+
+fn edit_routine_privileges_with_editor(
+    editor: &str,
+    privilege_data: &[RoutinePrivilegeRow],
+    routines: &[StoredRoutine],
+) -> anyhow::Result<Vec<RoutinePrivilegeRow>> {
+    let unix_user = User::from_uid(getuid())
+        .context("Failed to look up your UNIX username")
+        .and_then(|u| u.ok_or(anyhow::anyhow!("Failed to look up your UNIX username")))?;
+
+    let mut editor_content =
+        generate_routine_editor_content(privilege_data, routines, &unix_user.name);
+
+    // Re-open the editor until the content can be parsed,
+    // or the user quits the editor without saving.
+    loop {
+        let result = Editor::new()
+            .executable(editor)
+            .extension("tsv")
+            .edit(&editor_content)
+            .with_context(|| format!("Failed to run the editor '{editor}'"))?;
+
+        let Some(result) = result else {
+            return Ok(privilege_data.to_vec());
+        };
+
+        match parse_routine_privilege_data_from_editor_content(&result) {
+            Ok(privileges) => return Ok(privileges),
+            Err(err) => {
+                eprintln!("Could not parse routine privilege data from editor: {err:#}");
+                if !Confirm::new()
+                    .with_prompt("Do you want to fix the errors in the editor?")
+                    .default(true)
+                    .show_default(true)
+                    .interact()?
+                {
+                    return Err(err.context("Could not parse routine privilege data from editor"));
+                }
+                editor_content = result;
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::IsTerminal;
+
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, pick_users_interactively, print_authorization_owner_hint,
+        print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            ClientToServerMessageStream, GrantRoleError, GrantRolesOutput, Request, Response,
+            request_validation::ValidationError,
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct GrantRoleArgs {
+    /// The `MySQL` user to grant as a role
+    #[arg(value_name = "ROLE_NAME")]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    role: MySQLUser,
+
+    /// The `MySQL` user(s) to grant the role to
+    #[arg(num_args = 1.., value_name = "USER_NAME")]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    username: Vec<MySQLUser>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn grant_role(
+    mut args: GrantRoleArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let output_options = output::options().with_json(args.json);
+    if args.username.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("No usernames provided");
+        }
+
+        args.username =
+            pick_users_interactively(&mut server_connection, "Users to grant the role to").await?;
+        if args.username.is_empty() {
+            print_message("No users selected.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    let message = Request::GrantRoles((args.role.clone(), args.username.clone()));
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::GrantRoles(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output = GrantRolesOutput {
+        role: &args.role,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        if result.iter().any(|(_, res)| {
+            matches!(
+                res,
+                Err(
+                    GrantRoleError::ValidationError(ValidationError::AuthorizationError(_))
+                        | GrantRoleError::RoleValidationError(ValidationError::AuthorizationError(
+                            _
+                        ))
+                )
+            )
+        }) {
+            print_authorization_owner_hint(&mut server_connection).await?;
+        }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(GrantRoleError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
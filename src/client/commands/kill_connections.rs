@@ -0,0 +1,76 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, KillConnectionsError, Request, Response,
+            request_validation::ValidationError,
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct KillConnectionsArgs {
+    /// The `MySQL` user(s) whose connections should be killed
+    #[arg(num_args = 1.., required = true, value_name = "USER_NAME")]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    username: Vec<MySQLUser>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn kill_connections(
+    args: KillConnectionsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let message = Request::KillConnections(args.username.clone());
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::KillConnections(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    print_output(&result, &output_options);
+
+    if !output_options.is_json() {
+        if result.iter().any(|(_, res)| {
+            matches!(
+                res,
+                Err(KillConnectionsError::ValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        }) {
+            print_authorization_owner_hint(&mut server_connection).await?;
+        }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(KillConnectionsError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ListSnapshotsArgs {
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn list_snapshots(
+    args: ListSnapshotsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    server_connection
+        .send(Request::ListPrivilegeSnapshots)
+        .await?;
+
+    let snapshots = match server_connection.next().await {
+        Some(Ok(Response::ListPrivilegeSnapshots(Ok(snapshots)))) => snapshots,
+        Some(Ok(Response::ListPrivilegeSnapshots(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message())
+                .context("Failed to list privilege snapshots"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    print_output(snapshots.as_slice(), &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok(())
+}
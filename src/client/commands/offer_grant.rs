@@ -0,0 +1,99 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        completion::mysql_database_completer,
+        database_privileges::{
+            DatabasePrivilegeEdit, DatabasePrivilegeEditEntry, DatabasePrivilegeRow,
+        },
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, OfferGrantOutput, Request, Response},
+        types::{MySQLDatabase, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct OfferGrantArgs {
+    /// The database to offer privileges on
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    #[arg(value_name = "DB_NAME")]
+    db_name: MySQLDatabase,
+
+    /// The user to offer the privileges to, which is owned by another user or group
+    #[arg(value_name = "USER_NAME")]
+    user_name: MySQLUser,
+
+    /// The full set of privileges the user will have on the database, see `muscl edit-privs --help`
+    #[arg(
+      value_name = "PRIVILEGES",
+      value_parser = DatabasePrivilegeEdit::parse_template_from_str,
+    )]
+    privileges: DatabasePrivilegeEdit,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn offer_grant(
+    args: OfferGrantArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let mut row = DatabasePrivilegeRow {
+        db: args.db_name.clone(),
+        user: args.user_name.clone(),
+        select_priv: false,
+        insert_priv: false,
+        update_priv: false,
+        delete_priv: false,
+        create_priv: false,
+        drop_priv: false,
+        alter_priv: false,
+        index_priv: false,
+        create_tmp_table_priv: false,
+        lock_tables_priv: false,
+        references_priv: false,
+        create_view_priv: false,
+        show_view_priv: false,
+        trigger_priv: false,
+        event_priv: false,
+        execute_priv: false,
+        create_routine_priv: false,
+        alter_routine_priv: false,
+        grant_priv: false,
+    };
+    DatabasePrivilegeEditEntry {
+        database: args.db_name,
+        user: args.user_name,
+        privilege_edit: args.privileges,
+    }
+    .as_database_privileges_diff()?
+    .apply(&mut row);
+
+    let message = Request::OfferGrant(row.clone());
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::OfferGrant(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = OfferGrantOutput {
+        row: &row,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
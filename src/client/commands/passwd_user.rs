@@ -1,30 +1,45 @@
-use std::{io::IsTerminal, path::PathBuf};
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
 use dialoguer::Password;
 use futures_util::SinkExt;
+use rand::{Rng, distr::Alphanumeric};
 use tokio_stream::StreamExt;
+use zeroize::Zeroizing;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            erroneous_server_response, pick_user_interactively, print_authorization_owner_hint,
+        },
+        hooks::{run_post_hook, run_pre_hook},
+    },
     core::{
         completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
             ClientToServerMessageStream, ListUsersError, Request, Response, SetPasswordError,
-            print_set_password_output_status, request_validation::ValidationError,
+            SetUserPasswordOutput, request_validation::ValidationError,
         },
-        types::MySQLUser,
+        types::{MySQLUser, SecretString},
     },
 };
 
+/// The length of the passwords made by [`generate_password`].
+pub const GENERATED_PASSWORD_LENGTH: usize = 24;
+
 #[derive(Parser, Debug, Clone)]
 pub struct PasswdUserArgs {
     /// The `MySQL` user whose password is to be changed
     #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
     #[arg(value_name = "USER_NAME")]
-    username: MySQLUser,
+    username: Option<MySQLUser>,
 
     /// Read the new password from a file instead of prompting for it
     #[clap(short, long, value_name = "PATH", conflicts_with = "stdin")]
@@ -39,7 +54,9 @@ pub struct PasswdUserArgs {
     json: bool,
 }
 
-pub fn read_password_from_stdin_with_double_check(username: &MySQLUser) -> anyhow::Result<String> {
+pub fn read_password_from_stdin_with_double_check(
+    username: &MySQLUser,
+) -> anyhow::Result<SecretString> {
     Password::new()
         .with_prompt(format!("New MySQL password for user '{username}'"))
         .with_confirmation(
@@ -47,15 +64,65 @@ pub fn read_password_from_stdin_with_double_check(username: &MySQLUser) -> anyho
             "Passwords do not match",
         )
         .interact()
+        .map(SecretString::from)
         .map_err(Into::into)
 }
 
+/// Generate a random password of letters and digits.
+#[must_use]
+pub fn generate_password() -> SecretString {
+    // NOTE: the buffer is allocated up front, as growing it would leave copies of the password behind.
+    let mut password = String::with_capacity(GENERATED_PASSWORD_LENGTH);
+    password.extend(
+        rand::rng()
+            .sample_iter(Alphanumeric)
+            .take(GENERATED_PASSWORD_LENGTH)
+            .map(char::from),
+    );
+    SecretString::from(password)
+}
+
+/// Read a password from the first line of a file, ignoring surrounding whitespace.
+pub fn read_password_from_file(path: &Path) -> anyhow::Result<SecretString> {
+    let contents =
+        Zeroizing::new(std::fs::read_to_string(path).context("Failed to read password file")?);
+    Ok(SecretString::from(contents.trim()))
+}
+
+/// Read a password from a line on stdin, ignoring surrounding whitespace.
+pub fn read_password_from_stdin() -> anyhow::Result<SecretString> {
+    // NOTE: the buffer is allocated up front, as growing it would leave copies of the password behind.
+    let mut buffer = Zeroizing::new(String::with_capacity(1024));
+    std::io::stdin()
+        .read_line(&mut buffer)
+        .context("Failed to read password from stdin")?;
+    Ok(SecretString::from(buffer.trim()))
+}
+
 pub async fn passwd_user(
     args: PasswdUserArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
+    let output_options = output::options().with_json(args.json);
+    let username = match args.username {
+        Some(username) => username,
+        None if std::io::stdin().is_terminal() => {
+            match pick_user_interactively(&mut server_connection, "User to change the password for")
+                .await?
+            {
+                Some(username) => username,
+                None => {
+                    print_message("No user selected.", &output_options);
+                    server_connection.send(Request::Exit).await?;
+                    return Ok(());
+                }
+            }
+        }
+        None => anyhow::bail!("No username provided"),
+    };
+
     // TODO: create a "user" exists check" command
-    let message = Request::ListUsers(Some(vec![args.username.clone()]));
+    let message = Request::ListUsers(Some(vec![username.clone()]));
     if let Err(err) = server_connection.send(message).await {
         server_connection.close().await.ok();
         anyhow::bail!(err);
@@ -65,38 +132,37 @@ pub async fn passwd_user(
         response => return erroneous_server_response(response),
     };
     match response
-        .get(&args.username)
+        .get(&username)
         .unwrap_or(&Err(ListUsersError::UserDoesNotExist))
     {
         Ok(_) => {}
         Err(err) => {
             server_connection.send(Request::Exit).await?;
             server_connection.close().await.ok();
-            anyhow::bail!("{}", err.to_error_message(&args.username));
+            anyhow::bail!("{}", err.to_error_message(&username));
         }
     }
 
     let password = if let Some(password_file) = args.password_file {
-        std::fs::read_to_string(password_file)
-            .context("Failed to read password file")?
-            .trim()
-            .to_string()
+        read_password_from_file(&password_file)?
     } else if args.stdin {
-        let mut buffer = String::new();
-        std::io::stdin()
-            .read_line(&mut buffer)
-            .context("Failed to read password from stdin")?;
-        buffer.trim().to_string()
+        read_password_from_stdin()?
     } else {
         if !std::io::stdin().is_terminal() {
             anyhow::bail!(
                 "Cannot prompt for password in non-interactive mode. Use --stdin or --password-file to provide the password."
             );
         }
-        read_password_from_stdin_with_double_check(&args.username)?
+        read_password_from_stdin_with_double_check(&username)?
     };
 
-    let message = Request::PasswdUser((args.username.clone(), password));
+    let hook_names = vec![username.to_string()];
+    if let Err(err) = run_pre_hook("passwd-user", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::PasswdUser((username.clone(), password));
 
     if let Err(err) = server_connection.send(message).await {
         server_connection.close().await.ok();
@@ -108,22 +174,25 @@ pub async fn passwd_user(
         response => return erroneous_server_response(response),
     };
 
-    print_set_password_output_status(&result, &args.username);
-
-    if matches!(
-        result,
-        Err(SetPasswordError::ValidationError(
-            ValidationError::AuthorizationError(_)
-        ))
-    ) {
+    let output = SetUserPasswordOutput {
+        username: &username,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+    run_post_hook("passwd-user", &hook_names, &output);
+
+    if !output_options.is_json()
+        && matches!(
+            result,
+            Err(SetPasswordError::ValidationError(
+                ValidationError::AuthorizationError(_)
+            ))
+        )
+    {
         print_authorization_owner_hint(&mut server_connection).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.is_err() {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&output)
 }
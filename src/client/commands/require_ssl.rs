@@ -0,0 +1,96 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, RequireSslError, RequireSslOutput,
+            RequireSslRequest, Response, TlsRequirement, request_validation::ValidationError,
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct RequireSslArgs {
+    /// The `MySQL` user that has to connect with TLS
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(value_name = "USER_NAME")]
+    username: MySQLUser,
+
+    /// Also require the client to present a valid certificate
+    #[arg(long)]
+    x509: bool,
+
+    /// Allow unencrypted connections again
+    #[arg(long, conflicts_with = "x509")]
+    none: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn require_ssl(
+    args: RequireSslArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let requirement = if args.none {
+        TlsRequirement::None
+    } else if args.x509 {
+        TlsRequirement::X509
+    } else {
+        TlsRequirement::Ssl
+    };
+
+    let message = Request::RequireSsl(RequireSslRequest {
+        user: args.username.clone(),
+        requirement,
+    });
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::RequireSsl(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = RequireSslOutput {
+        username: &args.username,
+        requirement,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        match result {
+            Err(RequireSslError::ValidationError(ValidationError::AuthorizationError(_))) => {
+                print_authorization_owner_hint(&mut server_connection).await?;
+            }
+            Err(RequireSslError::UserDoesNotExist) => {
+                print_did_you_mean_hint(
+                    &mut server_connection,
+                    &[DbOrUser::User(args.username.clone())],
+                )
+                .await?;
+            }
+            _ => {}
+        }
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
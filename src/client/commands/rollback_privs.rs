@@ -0,0 +1,163 @@
+use std::io::IsTerminal;
+
+use clap::Parser;
+use dialoguer::Confirm;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{
+        commands::{
+            erroneous_server_response, fetch_database_privilege_fields,
+            print_authorization_owner_hint,
+        },
+        config as client_config,
+        progress::next_response_with_progress,
+    },
+    core::{
+        database_privileges::{
+            DiffFormat, diff_privileges, display_privilege_diffs, display_privilege_diffs_unified,
+        },
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            ClientToServerMessageStream, ListPrivilegesRequest, ModifyDatabasePrivilegesError,
+            ModifyPrivilegesRequest, ModifyPrivilegesResponse, Request, Response,
+            request_validation::ValidationError,
+        },
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct RollbackPrivsArgs {
+    /// The tag of the snapshot to restore, see `muscl list-snapshots`
+    #[arg(long, value_name = "TAG")]
+    tag: String,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// How to display the changes before they are applied
+    #[arg(long, value_name = "FORMAT", default_value_t, value_enum)]
+    diff_format: DiffFormat,
+
+    /// Apply either all of the changes or none of them
+    ///
+    /// By default, every change is applied on its own, so that e.g. a database that
+    /// has been dropped since the snapshot was taken does not stop the other changes.
+    #[arg(long)]
+    atomic: bool,
+
+    /// Disable interactive confirmation before saving changes
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn rollback_privileges(
+    args: RollbackPrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    server_connection
+        .send(Request::GetPrivilegeSnapshot(args.tag.clone()))
+        .await?;
+
+    let snapshot_rows = match server_connection.next().await {
+        Some(Ok(Response::GetPrivilegeSnapshot(Ok(rows)))) => rows,
+        Some(Ok(Response::GetPrivilegeSnapshot(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message(&args.tag))
+                .context("Failed to get privilege snapshot"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    server_connection
+        .send(Request::ListPrivileges(ListPrivilegesRequest::default()))
+        .await?;
+
+    let existing_privilege_rows = match server_connection.next().await {
+        Some(Ok(Response::ListAllPrivileges(Ok(rows)))) => rows,
+        Some(Ok(Response::ListAllPrivileges(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message())
+                .context("Failed to list database privileges"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let diffs = diff_privileges(&existing_privilege_rows, &snapshot_rows);
+    let privilege_fields = fetch_database_privilege_fields(&mut server_connection).await?;
+
+    if diffs.is_empty() {
+        if output_options.is_json() {
+            print_output(&ModifyPrivilegesResponse::new(), &output_options);
+        } else {
+            println!("The privileges already match the snapshot '{}'.", args.tag);
+        }
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    print_message(
+        &format!(
+            "The following changes will be made to restore the snapshot '{}':\n",
+            args.tag
+        ),
+        &output_options,
+    );
+    match args.diff_format {
+        DiffFormat::Table => print_message(&display_privilege_diffs(&diffs), &output_options),
+        DiffFormat::Unified => print_message(
+            &display_privilege_diffs_unified(&existing_privilege_rows, &diffs, privilege_fields),
+            &output_options,
+        ),
+    }
+
+    if std::io::stdin().is_terminal()
+        && !yes
+        && !Confirm::new()
+            .with_prompt("Do you want to apply these changes?")
+            .default(false)
+            .show_default(true)
+            .interact()?
+    {
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    let message = Request::ModifyPrivileges(ModifyPrivilegesRequest {
+        diffs,
+        atomic: args.atomic,
+    });
+    server_connection.send(message).await?;
+
+    let result = match next_response_with_progress(&mut server_connection).await {
+        Some(Ok(Response::ModifyPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&result, &output_options);
+
+    if !output_options.is_json()
+        && result.iter().any(|(_, res)| {
+            matches!(
+                res,
+                Err(ModifyDatabasePrivilegesError::UserValidationError(
+                    ValidationError::AuthorizationError(_)
+                ) | ModifyDatabasePrivilegesError::DatabaseValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        })
+    {
+        print_authorization_owner_hint(&mut server_connection).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&result)
+}
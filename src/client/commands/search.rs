@@ -0,0 +1,69 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, Response, SEARCH_COLUMNS, SearchOutput,
+            SearchRequest,
+        },
+        table::TableViewArgs,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct SearchArgs {
+    /// The pattern to match the names of your databases and users against
+    ///
+    /// By default this is a glob that has to match the whole name,
+    /// where `*` matches any number of characters and `?` a single character.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Treat the pattern as a regular expression, which may match any part of the name
+    #[arg(short = 'E', long)]
+    regex: bool,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+pub async fn search(
+    args: SearchArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    args.table_view.validate(&SEARCH_COLUMNS)?;
+
+    let message = Request::Search(SearchRequest {
+        pattern: args.pattern.clone(),
+        regex: args.regex,
+    });
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::Search(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output = SearchOutput {
+        result: &result,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output::options().with_json(args.json));
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
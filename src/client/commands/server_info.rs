@@ -0,0 +1,40 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ServerInfoArgs {
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn server_info(
+    args: ServerInfoArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    server_connection.send(Request::ServerInfo).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::ServerInfo(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    if !output_options.is_json() {
+        println!("muscl client version: {}", env!("CARGO_PKG_VERSION"));
+    }
+    print_output(&result, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok(())
+}
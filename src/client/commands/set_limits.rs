@@ -0,0 +1,89 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, Response, SetUserLimitsError,
+            SetUserLimitsOutput, SetUserLimitsRequest, request_validation::ValidationError,
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+#[command(group(clap::ArgGroup::new("limits").required(true).multiple(true)))]
+pub struct SetLimitsArgs {
+    /// The `MySQL` user to set the limits for
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(value_name = "USER_NAME")]
+    username: MySQLUser,
+
+    /// The number of queries the user may run per hour, or `0` for no limit
+    #[arg(long, value_name = "COUNT", group = "limits")]
+    max_queries_per_hour: Option<u32>,
+
+    /// The number of statements that change data the user may run per hour, or `0` for no limit
+    #[arg(long, value_name = "COUNT", group = "limits")]
+    max_updates_per_hour: Option<u32>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn set_limits(
+    args: SetLimitsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let message = Request::SetUserLimits(SetUserLimitsRequest {
+        user: args.username.clone(),
+        max_queries_per_hour: args.max_queries_per_hour,
+        max_updates_per_hour: args.max_updates_per_hour,
+    });
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::SetUserLimits(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = SetUserLimitsOutput {
+        username: &args.username,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        match result {
+            Err(SetUserLimitsError::ValidationError(ValidationError::AuthorizationError(_))) => {
+                print_authorization_owner_hint(&mut server_connection).await?;
+            }
+            Err(SetUserLimitsError::UserDoesNotExist) => {
+                print_did_you_mean_hint(
+                    &mut server_connection,
+                    &[DbOrUser::User(args.username.clone())],
+                )
+                .await?;
+            }
+            _ => {}
+        }
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -0,0 +1,85 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, Response, SetUserCommentError,
+            SetUserCommentOutput, SetUserCommentRequest, request_validation::ValidationError,
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetUserCommentArgs {
+    /// The `MySQL` user to set the comment for
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(value_name = "USER_NAME")]
+    username: MySQLUser,
+
+    /// The comment, e.g. what the user is used for
+    ///
+    /// An empty comment removes the existing comment.
+    #[arg(value_name = "COMMENT")]
+    comment: String,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn set_user_comment(
+    args: SetUserCommentArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let message = Request::SetUserComment(SetUserCommentRequest {
+        user: args.username.clone(),
+        comment: args.comment.trim().to_string(),
+    });
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::SetUserComment(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = SetUserCommentOutput {
+        username: &args.username,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        match result {
+            Err(SetUserCommentError::ValidationError(ValidationError::AuthorizationError(_))) => {
+                print_authorization_owner_hint(&mut server_connection).await?;
+            }
+            Err(SetUserCommentError::UserDoesNotExist) => {
+                print_did_you_mean_hint(
+                    &mut server_connection,
+                    &[DbOrUser::User(args.username.clone())],
+                )
+                .await?;
+            }
+            _ => {}
+        }
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -0,0 +1,98 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, fetch_user_names, print_authorization_owner_hint,
+        print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, Response, SHOW_CONNECTIONS_COLUMNS,
+            ShowConnectionsError, ShowConnectionsOutput, request_validation::ValidationError,
+        },
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ShowConnectionsArgs {
+    /// The `MySQL` user(s) to show the connections of
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(num_args = 0.., value_name = "USER_NAME")]
+    username: Vec<MySQLUser>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+pub async fn show_connections(
+    args: ShowConnectionsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    args.table_view.validate(&SHOW_CONNECTIONS_COLUMNS)?;
+
+    let users = if args.username.is_empty() {
+        fetch_user_names(&mut server_connection)
+            .await?
+            .into_iter()
+            .map(MySQLUser::from)
+            .collect()
+    } else {
+        args.username.clone()
+    };
+
+    if let Err(err) = server_connection
+        .send(Request::ShowConnections(users))
+        .await
+    {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::ShowConnections(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = ShowConnectionsOutput {
+        connections: &result,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        if result.iter().any(|(_, res)| {
+            matches!(
+                res,
+                Err(ShowConnectionsError::ValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        }) {
+            print_authorization_owner_hint(&mut server_connection).await?;
+        }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ShowConnectionsError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -4,15 +4,19 @@ use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
     core::{
         completion::mysql_database_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
         protocol::{
-            ClientToServerMessageStream, ListDatabasesError, Request, Response,
-            print_list_databases_output_status, print_list_databases_output_status_json,
-            request_validation::ValidationError,
+            ClientToServerMessageStream, LIST_DATABASES_COLUMNS, ListDatabasesError,
+            ListDatabasesOutput, Request, Response, request_validation::ValidationError,
         },
-        types::MySQLDatabase,
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLDatabase},
     },
 };
 
@@ -30,13 +34,36 @@ pub struct ShowDbArgs {
     /// Show sizes in bytes instead of human-readable format
     #[arg(short, long)]
     bytes: bool,
+
+    /// Include the privileges of all users on each database
+    ///
+    /// This flag can only be used together with --json
+    #[arg(long, requires = "json")]
+    with_privs: bool,
+
+    /// Only show databases that no user has any privileges on
+    ///
+    /// These are likely forgotten, and might be worth archiving or dropping.
+    #[arg(long, conflicts_with_all = ["name", "with_privs"])]
+    unused: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
 }
 
 pub async fn show_databases(
     args: ShowDbArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let message = if args.name.is_empty() {
+    args.table_view.validate(&LIST_DATABASES_COLUMNS)?;
+
+    if args.with_privs {
+        return show_databases_with_privileges(args, server_connection).await;
+    }
+
+    let message = if args.unused {
+        Request::ListUnusedDatabases
+    } else if args.name.is_empty() {
         Request::ListDatabases(None)
     } else {
         Request::ListDatabases(Some(args.name.clone()))
@@ -46,7 +73,10 @@ pub async fn show_databases(
 
     let databases = match server_connection.next().await {
         Some(Ok(Response::ListDatabases(databases))) => databases,
-        Some(Ok(Response::ListAllDatabases(database_list))) => match database_list {
+        Some(Ok(
+            Response::ListAllDatabases(database_list)
+            | Response::ListUnusedDatabases(database_list),
+        )) => match database_list {
             Ok(list) => list
                 .into_iter()
                 .map(|db| (db.database.clone(), Ok(db)))
@@ -61,11 +91,16 @@ pub async fn show_databases(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_list_databases_output_status_json(&databases);
-    } else {
-        print_list_databases_output_status(&databases, args.bytes);
+    let output_options = output::options().with_json(args.json);
+    let output = ListDatabasesOutput {
+        databases: &databases,
+        owners: None,
+        display_size_as_bytes: args.bytes,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
 
+    if !output_options.is_json() {
         if databases.iter().any(|(_, res)| {
             matches!(
                 res,
@@ -76,13 +111,52 @@ pub async fn show_databases(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = databases
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ListDatabasesError::DatabaseDoesNotExist)))
+            .map(|(name, _)| DbOrUser::Database(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if databases.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
+    ensure_success(&output)
+}
+
+async fn show_databases_with_privileges(
+    args: ShowDbArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let message = if args.name.is_empty() {
+        Request::ListDatabasesWithPrivileges(None)
+    } else {
+        Request::ListDatabasesWithPrivileges(Some(args.name.clone()))
+    };
+
+    server_connection.send(message).await?;
+
+    let databases = match server_connection.next().await {
+        Some(Ok(Response::ListDatabasesWithPrivileges(databases))) => databases,
+        Some(Ok(Response::ListAllDatabasesWithPrivileges(database_list))) => match database_list {
+            Ok(list) => list
+                .into_iter()
+                .map(|db| (db.database.database.clone(), Ok(db)))
+                .collect(),
+            Err(err) => {
+                server_connection.send(Request::Exit).await?;
+                return Err(
+                    anyhow::anyhow!(err.to_error_message()).context("Failed to list databases")
+                );
+            }
+        },
+        response => return erroneous_server_response(response),
+    };
+
+    print_output(&databases, &output::options().with_json(args.json));
+
+    server_connection.send(Request::Exit).await?;
 
-    Ok(())
+    ensure_success(&databases)
 }
@@ -5,15 +5,21 @@ use itertools::Itertools;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::commands::{
+        erroneous_server_response, fetch_database_privilege_fields, print_authorization_owner_hint,
+        print_did_you_mean_hint,
+    },
     core::{
         completion::mysql_database_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
         protocol::{
-            ClientToServerMessageStream, ListPrivilegesError, Request, Response,
-            print_list_privileges_output_status, print_list_privileges_output_status_json,
-            request_validation::ValidationError,
+            ClientToServerMessageStream, LIST_PRIVILEGES_COLUMNS, LIST_PRIVILEGES_COMPACT_COLUMNS,
+            ListPrivilegesError, ListPrivilegesOutput, ListPrivilegesRequest, Request, Response,
+            print_partial_revokes_warnings, request_validation::ValidationError,
         },
-        types::MySQLDatabase,
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLDatabase},
     },
 };
 
@@ -33,17 +39,38 @@ pub struct ShowPrivsArgs {
     /// This flag has no effect when used with --json
     #[arg(short, long)]
     long: bool,
+
+    /// Show the privileges as a single string of the characters used by `edit-privs`
+    ///
+    /// This flag has no effect when used with --json
+    #[arg(long, conflicts_with = "long")]
+    compact: bool,
+
+    /// Print the privileges as equivalent `GRANT` statements
+    ///
+    /// The statements can be used to set up the same privileges on another database server.
+    /// This flag has no effect when used with --json
+    #[arg(long, conflicts_with_all = ["long", "compact"])]
+    as_sql: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
 }
 
 pub async fn show_database_privileges(
     args: ShowPrivsArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let message = if args.name.is_empty() {
-        Request::ListPrivileges(None)
+    if args.compact {
+        args.table_view.validate(&LIST_PRIVILEGES_COMPACT_COLUMNS)?;
     } else {
-        Request::ListPrivileges(Some(args.name.clone()))
-    };
+        args.table_view.validate(&LIST_PRIVILEGES_COLUMNS)?;
+    }
+
+    let message = Request::ListPrivileges(ListPrivilegesRequest {
+        databases: (!args.name.is_empty()).then(|| args.name.clone()),
+        user: None,
+    });
     server_connection.send(message).await?;
 
     let privilege_data = match server_connection.next().await {
@@ -65,10 +92,40 @@ pub async fn show_database_privileges(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_list_privileges_output_status_json(&privilege_data);
-    } else {
-        print_list_privileges_output_status(&privilege_data, args.long);
+    let privilege_fields = fetch_database_privilege_fields(&mut server_connection).await?;
+
+    let output_options = output::options().with_json(args.json);
+    let output = ListPrivilegesOutput {
+        privileges: &privilege_data,
+        long_names: args.long,
+        compact: args.compact,
+        fields: privilege_fields,
+        as_sql: args.as_sql,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        let users = privilege_data
+            .values()
+            .filter_map(|res| res.as_ref().ok())
+            .flatten()
+            .map(|row| row.user.clone())
+            .unique()
+            .collect::<Vec<_>>();
+
+        if !users.is_empty() {
+            server_connection
+                .send(Request::ListPartialRevokes(users))
+                .await?;
+
+            match server_connection.next().await {
+                Some(Ok(Response::ListPartialRevokes(partial_revokes))) => {
+                    print_partial_revokes_warnings(&partial_revokes);
+                }
+                response => return erroneous_server_response(response),
+            }
+        }
 
         if privilege_data.iter().any(|(_, res)| {
             matches!(
@@ -80,13 +137,16 @@ pub async fn show_database_privileges(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = privilege_data
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ListPrivilegesError::DatabaseDoesNotExist)))
+            .map(|(name, _)| DbOrUser::Database(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if privilege_data.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&output)
 }
@@ -0,0 +1,91 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::{mysql_database_completer, mysql_user_completer},
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, LIST_ROUTINE_PRIVILEGES_COLUMNS, ListPrivilegesError,
+            ListRoutinePrivilegesOutput, ListRoutinePrivilegesRequest, Request, Response,
+            request_validation::ValidationError,
+        },
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ShowRoutinePrivsArgs {
+    /// The `MySQL` database(s) to show routine privileges for
+    #[arg(num_args = 0.., value_name = "DB_NAME")]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    name: Vec<MySQLDatabase>,
+
+    /// Only show the routine privileges of this user
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_user_completer)))]
+    #[arg(long, value_name = "USER_NAME")]
+    user: Option<MySQLUser>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+pub async fn show_routine_privileges(
+    args: ShowRoutinePrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    args.table_view.validate(&LIST_ROUTINE_PRIVILEGES_COLUMNS)?;
+
+    let message = Request::ListRoutinePrivileges(ListRoutinePrivilegesRequest {
+        databases: (!args.name.is_empty()).then(|| args.name.clone()),
+        user: args.user.clone(),
+    });
+    server_connection.send(message).await?;
+
+    let privilege_data = match server_connection.next().await {
+        Some(Ok(Response::ListRoutinePrivileges(databases))) => databases,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = ListRoutinePrivilegesOutput {
+        privileges: &privilege_data,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        if privilege_data.values().any(|res| {
+            matches!(
+                res,
+                Err(ListPrivilegesError::ValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        }) {
+            print_authorization_owner_hint(&mut server_connection).await?;
+        }
+
+        let missing = privilege_data
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ListPrivilegesError::DatabaseDoesNotExist)))
+            .map(|(name, _)| DbOrUser::Database(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
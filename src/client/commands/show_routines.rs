@@ -0,0 +1,82 @@
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
+    core::{
+        completion::mysql_database_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{
+            ClientToServerMessageStream, LIST_ROUTINES_COLUMNS, ListDatabasesError,
+            ListRoutinesOutput, Request, Response, request_validation::ValidationError,
+        },
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLDatabase},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ShowRoutinesArgs {
+    /// The `MySQL` database(s) to show stored routines in
+    #[arg(num_args = 0.., value_name = "DB_NAME")]
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    name: Vec<MySQLDatabase>,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
+}
+
+pub async fn show_routines(
+    args: ShowRoutinesArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    args.table_view.validate(&LIST_ROUTINES_COLUMNS)?;
+
+    let message = Request::ListRoutines((!args.name.is_empty()).then(|| args.name.clone()));
+    server_connection.send(message).await?;
+
+    let routines = match server_connection.next().await {
+        Some(Ok(Response::ListRoutines(databases))) => databases,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = ListRoutinesOutput {
+        routines: &routines,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
+
+    if !output_options.is_json() {
+        if routines.values().any(|res| {
+            matches!(
+                res,
+                Err(ListDatabasesError::ValidationError(
+                    ValidationError::AuthorizationError(_)
+                ))
+            )
+        }) {
+            print_authorization_owner_hint(&mut server_connection).await?;
+        }
+
+        let missing = routines
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ListDatabasesError::DatabaseDoesNotExist)))
+            .map(|(name, _)| DbOrUser::Database(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
+    }
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -4,15 +4,19 @@ use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::commands::{
+        erroneous_server_response, print_authorization_owner_hint, print_did_you_mean_hint,
+    },
     core::{
         completion::mysql_user_completer,
+        exit_code::ensure_success,
+        output::{self, print_output},
         protocol::{
-            ClientToServerMessageStream, ListUsersError, Request, Response,
-            print_list_users_output_status, print_list_users_output_status_json,
-            request_validation::ValidationError,
+            ClientToServerMessageStream, LIST_USERS_COLUMNS, ListUsersError, ListUsersOutput,
+            Request, Response, request_validation::ValidationError,
         },
-        types::MySQLUser,
+        table::TableViewArgs,
+        types::{DbOrUser, MySQLUser},
     },
 };
 
@@ -26,12 +30,17 @@ pub struct ShowUserArgs {
     /// Print the information as JSON
     #[arg(short, long)]
     json: bool,
+
+    #[command(flatten)]
+    table_view: TableViewArgs,
 }
 
 pub async fn show_users(
     args: ShowUserArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
+    args.table_view.validate(&LIST_USERS_COLUMNS)?;
+
     let message = if args.username.is_empty() {
         Request::ListUsers(None)
     } else {
@@ -60,11 +69,15 @@ pub async fn show_users(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_list_users_output_status_json(&users);
-    } else {
-        print_list_users_output_status(&users);
+    let output_options = output::options().with_json(args.json);
+    let output = ListUsersOutput {
+        users: &users,
+        owners: None,
+        table_view: &args.table_view,
+    };
+    print_output(&output, &output_options);
 
+    if !output_options.is_json() {
         if users.iter().any(|(_, res)| {
             matches!(
                 res,
@@ -75,13 +88,16 @@ pub async fn show_users(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = users
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(ListUsersError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if users.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&output)
 }
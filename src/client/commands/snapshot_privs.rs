@@ -0,0 +1,50 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        exit_code::ensure_success,
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response, SnapshotPrivilegesOutput},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct SnapshotPrivsArgs {
+    /// The name to store the snapshot under, e.g. `before-upgrade`
+    #[arg(long, value_name = "TAG")]
+    tag: String,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+}
+
+pub async fn snapshot_privileges(
+    args: SnapshotPrivsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let message = Request::SnapshotPrivileges(args.tag.clone());
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::SnapshotPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    let output = SnapshotPrivilegesOutput {
+        tag: &args.tag,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
@@ -0,0 +1,51 @@
+use clap::Parser;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        output::{self, print_output},
+        protocol::{ClientToServerMessageStream, Request, Response, StatsOutput},
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct StatsArgs {
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// Show sizes in bytes instead of human-readable format
+    #[arg(short, long)]
+    bytes: bool,
+}
+
+pub async fn stats(
+    args: StatsArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    server_connection.send(Request::Stats).await?;
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::Stats(Ok(result)))) => result,
+        Some(Ok(Response::Stats(Err(err)))) => {
+            server_connection.send(Request::Exit).await?;
+            return Err(anyhow::anyhow!(err.to_error_message()).context("Failed to get stats"));
+        }
+        response => return erroneous_server_response(response),
+    };
+
+    let output_options = output::options().with_json(args.json);
+    print_output(
+        &StatsOutput {
+            stats: &result,
+            display_size_as_bytes: args.bytes,
+        },
+        &output_options,
+    );
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok(())
+}
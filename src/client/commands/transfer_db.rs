@@ -0,0 +1,99 @@
+use std::io::IsTerminal;
+
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use dialoguer::Confirm;
+use futures_util::SinkExt;
+use tokio_stream::StreamExt;
+
+use crate::{
+    client::{commands::erroneous_server_response, config as client_config},
+    core::{
+        completion::{mysql_database_completer, prefix_completer},
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
+        protocol::{
+            ClientToServerMessageStream, Request, Response, TransferDatabaseOutput,
+            TransferDatabaseRequest,
+        },
+        types::MySQLDatabase,
+    },
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct TransferDbArgs {
+    /// The `MySQL` database to transfer
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
+    #[arg(value_name = "DB_NAME")]
+    from: MySQLDatabase,
+
+    /// The new name of the database, under the prefix it should be transferred to
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(prefix_completer)))]
+    #[arg(value_name = "NEW_DB_NAME")]
+    to: MySQLDatabase,
+
+    /// Print the information as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    /// Automatically confirm action without prompting
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn transfer_database(
+    args: TransferDbArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let yes = args.yes || client_config::get().assume_yes;
+    let output_options = output::options().with_json(args.json);
+
+    if !std::io::stdin().is_terminal() && !yes {
+        anyhow::bail!(
+            "Cannot prompt for confirmation in non-interactive mode. Use --yes to automatically confirm."
+        );
+    }
+
+    if !yes {
+        let confirmation = Confirm::new()
+            .with_prompt(format!(
+                "Are you sure you want to transfer database '{}' to '{}'?\n\nAnyone using the old name will need to be updated",
+                args.from, args.to,
+            ))
+            .interact()?;
+
+        if !confirmation {
+            print_message("Aborting transfer.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
+    }
+
+    let request = TransferDatabaseRequest {
+        from: args.from,
+        to: args.to,
+    };
+
+    if let Err(err) = server_connection
+        .send(Request::TransferDatabase(request.clone()))
+        .await
+    {
+        server_connection.close().await.ok();
+        anyhow::bail!(err);
+    }
+
+    let result = match server_connection.next().await {
+        Some(Ok(Response::TransferDatabase(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let output = TransferDatabaseOutput {
+        request: &request,
+        result: &result,
+    };
+    print_output(&output, &output_options);
+
+    server_connection.send(Request::Exit).await?;
+
+    ensure_success(&output)
+}
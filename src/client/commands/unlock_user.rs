@@ -1,18 +1,27 @@
+use std::io::IsTerminal;
+
 use clap::Parser;
 use clap_complete::ArgValueCompleter;
 use futures_util::SinkExt;
 use tokio_stream::StreamExt;
 
 use crate::{
-    client::commands::{erroneous_server_response, print_authorization_owner_hint},
+    client::{
+        commands::{
+            erroneous_server_response, pick_users_interactively, print_authorization_owner_hint,
+            print_did_you_mean_hint,
+        },
+        hooks::{run_post_hook, run_pre_hook},
+    },
     core::{
-        completion::mysql_user_completer,
+        completion::{bare_prefix_completer, mysql_user_completer},
+        exit_code::ensure_success,
+        output::{self, print_message, print_output},
         protocol::{
-            ClientToServerMessageStream, Request, Response, UnlockUserError,
-            print_unlock_users_output_status, print_unlock_users_output_status_json,
+            ClientToServerMessageStream, Request, Response, UnlockUserError, UnlockUsersRequest,
             request_validation::ValidationError,
         },
-        types::MySQLUser,
+        types::{DbOrUser, MySQLUser},
     },
 };
 
@@ -23,20 +32,51 @@ pub struct UnlockUserArgs {
     #[arg(num_args = 1.., value_name = "USER_NAME")]
     username: Vec<MySQLUser>,
 
+    /// Also unlock every user under this prefix, e.g. one of your groups
+    ///
+    /// The users are looked up by the server, so this also unlocks users
+    /// that were created by other members of the group.
+    #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(bare_prefix_completer)))]
+    #[arg(long, value_name = "PREFIX")]
+    all_with_prefix: Option<String>,
+
     /// Print the information as JSON
     #[arg(short, long)]
     json: bool,
 }
 
 pub async fn unlock_users(
-    args: UnlockUserArgs,
+    mut args: UnlockUserArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    if args.username.is_empty() {
-        anyhow::bail!("No usernames provided");
+    let output_options = output::options().with_json(args.json);
+    if args.username.is_empty() && args.all_with_prefix.is_none() {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("No usernames provided");
+        }
+
+        args.username = pick_users_interactively(&mut server_connection, "Users to unlock").await?;
+        if args.username.is_empty() {
+            print_message("No users selected.", &output_options);
+            server_connection.send(Request::Exit).await?;
+            return Ok(());
+        }
     }
 
-    let message = Request::UnlockUsers(args.username.clone());
+    let hook_names = args
+        .username
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Err(err) = run_pre_hook("unlock-user", &hook_names) {
+        server_connection.send(Request::Exit).await?;
+        return Err(err);
+    }
+
+    let message = Request::UnlockUsers(UnlockUsersRequest {
+        users: args.username.clone(),
+        all_with_prefix: args.all_with_prefix.clone(),
+    });
 
     if let Err(err) = server_connection.send(message).await {
         server_connection.close().await.ok();
@@ -48,11 +88,17 @@ pub async fn unlock_users(
         response => return erroneous_server_response(response),
     };
 
-    if args.json {
-        print_unlock_users_output_status_json(&result);
-    } else {
-        print_unlock_users_output_status(&result);
+    print_output(&result, &output_options);
+    run_post_hook("unlock-user", &hook_names, &result);
+
+    if result.is_empty()
+        && !output_options.is_json()
+        && let Some(prefix) = &args.all_with_prefix
+    {
+        println!("No users with the prefix '{prefix}_' were found.");
+    }
 
+    if !output_options.is_json() {
         if result.iter().any(|(_, res)| {
             matches!(
                 res,
@@ -63,13 +109,16 @@ pub async fn unlock_users(
         }) {
             print_authorization_owner_hint(&mut server_connection).await?;
         }
+
+        let missing = result
+            .iter()
+            .filter(|(_, res)| matches!(res, Err(UnlockUserError::UserDoesNotExist)))
+            .map(|(name, _)| DbOrUser::User(name.clone()))
+            .collect::<Vec<_>>();
+        print_did_you_mean_hint(&mut server_connection, &missing).await?;
     }
 
     server_connection.send(Request::Exit).await?;
 
-    if result.values().any(std::result::Result::is_err) {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    ensure_success(&result)
 }
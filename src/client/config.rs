@@ -0,0 +1,151 @@
+//! The client config file, with the user's own defaults for command line flags.
+//!
+//! The config is read from `$XDG_CONFIG_HOME/muscl/config.toml` or
+//! `~/.config/muscl/config.toml`, and every setting can be overridden by
+//! the corresponding flag. `format`, `server_socket` and `timeout` can also be
+//! overridden with `MUSCL_FORMAT`, `MUSCL_SOCKET` and `MUSCL_TIMEOUT`:
+//!
+//! ```toml
+//! # --format
+//! format = "json"
+//!
+//! # --editor for edit-privs
+//! editor = "nvim"
+//!
+//! # --yes for commands that ask for confirmation
+//! assume_yes = false
+//!
+//! # --prefix for create-db and create-user, only used for names without a prefix
+//! prefix = "alice"
+//!
+//! # --server-socket, ignored when running as SUID/SGID
+//! server_socket = "/run/muscl/muscl.sock"
+//!
+//! # --timeout, in seconds, also used when waiting for the server to become ready
+//! timeout = 30
+//!
+//! # where to look for hooks, see `client::hooks`
+//! hooks_dir = "/home/groups/alice/muscl-hooks"
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::core::{output::OutputFormat, protocol::DEFAULT_READY_TIMEOUT};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub format: Option<OutputFormat>,
+    pub editor: Option<String>,
+    pub assume_yes: bool,
+    pub prefix: Option<String>,
+    pub server_socket: Option<PathBuf>,
+    pub timeout: Option<u64>,
+    pub hooks_dir: Option<PathBuf>,
+}
+
+impl ClientConfig {
+    /// The directory of the client config, `$XDG_CONFIG_HOME/muscl` or `~/.config/muscl`.
+    pub(crate) fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("muscl"))
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        Self::default_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Read a client config from the given path.
+    pub fn read_from_path(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// How long to wait for the server to become ready, which is `timeout` if it is set.
+    #[must_use]
+    pub fn ready_timeout(&self) -> Duration {
+        self.timeout
+            .map_or(DEFAULT_READY_TIMEOUT, Duration::from_secs)
+    }
+
+    /// Load the client config of the current user, falling back to the
+    /// default config if no config file exists or it could not be parsed.
+    ///
+    /// **WARNING:** This must not be called before privileges are dropped.
+    #[must_use]
+    pub fn load() -> Self {
+        let Some(path) = Self::default_path() else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match Self::read_from_path(&path) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!(
+                    "Warning: Failed to read client config from {}: {}",
+                    path.display(),
+                    err
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+static CONFIG: OnceLock<ClientConfig> = OnceLock::new();
+
+/// Set up the client config for the rest of the process.
+pub fn init(config: ClientConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// The client config set up by [`init`], or the default config if it was never called.
+#[must_use]
+pub fn get() -> &'static ClientConfig {
+    CONFIG.get_or_init(ClientConfig::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_client_config() {
+        let config: ClientConfig = toml::from_str(indoc::indoc! {r#"
+            format = "json"
+            editor = "nvim"
+            prefix = "alice"
+            timeout = 30
+            hooks_dir = "/home/groups/alice/muscl-hooks"
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            config,
+            ClientConfig {
+                format: Some(OutputFormat::Json),
+                editor: Some("nvim".to_string()),
+                assume_yes: false,
+                prefix: Some("alice".to_string()),
+                server_socket: None,
+                timeout: Some(30),
+                hooks_dir: Some(PathBuf::from("/home/groups/alice/muscl-hooks")),
+            }
+        );
+
+        assert!(toml::from_str::<ClientConfig>("colour = \"never\"").is_err());
+    }
+}
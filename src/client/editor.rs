@@ -0,0 +1,138 @@
+//! Finding the text editor to use for interactive editing.
+//!
+//! The editor is picked from the first of these that names an existing program:
+//! `--editor` (or `editor` in the client config), `$VISUAL`, `$EDITOR`,
+//! and finally a few common editors.
+
+use std::path::Path;
+
+use crate::client::config as client_config;
+
+/// Editors to try when none is configured, in order.
+const DEFAULT_EDITORS: &[&str] = &["editor", "nano", "vim", "vi"];
+
+/// Where an editor command came from, used in error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorSource {
+    Flag,
+    Visual,
+    Editor,
+    Default,
+}
+
+impl std::fmt::Display for EditorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditorSource::Flag => write!(f, "--editor"),
+            EditorSource::Visual => write!(f, "$VISUAL"),
+            EditorSource::Editor => write!(f, "$EDITOR"),
+            EditorSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// The program that an editor command would run, e.g. `code` for `code --wait`.
+fn editor_program(command: &str) -> Option<String> {
+    shell_words::split(command).ok()?.into_iter().next()
+}
+
+/// Check whether `program` is a path to a file, or the name of a file in `$PATH`.
+fn program_exists(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Pick the first candidate whose program exists, warning about configured ones that do not.
+fn pick_editor(
+    candidates: &[(EditorSource, String)],
+    exists: impl Fn(&str) -> bool,
+) -> anyhow::Result<String> {
+    for (source, command) in candidates {
+        if editor_program(command).is_some_and(|program| exists(&program)) {
+            return Ok(command.clone());
+        }
+
+        if *source != EditorSource::Default {
+            eprintln!("Warning: Could not find the editor '{command}' from {source}, skipping it.");
+        }
+    }
+
+    anyhow::bail!(
+        "Could not find a text editor to use, tried: {}\n\
+         Please choose one with --editor, or by setting the VISUAL or EDITOR environment variable.",
+        candidates
+            .iter()
+            .map(|(source, command)| format!("'{command}' ({source})"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// Find the editor command to use, preferring the one given with `--editor`.
+pub fn resolve_editor(editor_flag: Option<&str>) -> anyhow::Result<String> {
+    let configured = editor_flag
+        .map(str::to_string)
+        .or_else(|| client_config::get().editor.clone())
+        .map(|command| (EditorSource::Flag, command));
+
+    let from_env = [
+        (EditorSource::Visual, "VISUAL"),
+        (EditorSource::Editor, "EDITOR"),
+    ]
+    .into_iter()
+    .filter_map(|(source, var)| {
+        std::env::var(var)
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+            .map(|command| (source, command))
+    });
+
+    let defaults = DEFAULT_EDITORS
+        .iter()
+        .map(|command| (EditorSource::Default, (*command).to_string()));
+
+    let candidates = configured
+        .into_iter()
+        .chain(from_env)
+        .chain(defaults)
+        .collect::<Vec<_>>();
+
+    pick_editor(&candidates, program_exists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_program() {
+        assert_eq!(editor_program("vim"), Some("vim".to_string()));
+        assert_eq!(editor_program("code --wait"), Some("code".to_string()));
+        assert_eq!(
+            editor_program("'/opt/my editor/bin/edit' -n"),
+            Some("/opt/my editor/bin/edit".to_string())
+        );
+        assert_eq!(editor_program(""), None);
+    }
+
+    #[test]
+    fn test_pick_editor() {
+        let candidates = vec![
+            (EditorSource::Flag, "missing-editor".to_string()),
+            (EditorSource::Editor, "code --wait".to_string()),
+            (EditorSource::Default, "vi".to_string()),
+        ];
+
+        let exists = |program: &str| program == "code" || program == "vi";
+        assert_eq!(pick_editor(&candidates, exists).unwrap(), "code --wait");
+
+        let exists = |program: &str| program == "vi";
+        assert_eq!(pick_editor(&candidates, exists).unwrap(), "vi");
+
+        assert!(pick_editor(&candidates, |_| false).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! User configured hooks, run around the commands that change something on the server.
+//!
+//! A hook is an executable named `pre-<command>` or `post-<command>`, e.g.
+//! `post-create-db`, in the hooks directory. The directory is `hooks_dir` from the
+//! client config, or `$XDG_CONFIG_HOME/muscl/hooks` by default. Hooks that do not
+//! exist are skipped.
+//!
+//! Every hook gets the operation and the affected names as JSON on stdin:
+//!
+//! ```json
+//! {
+//!   "hook": "post",
+//!   "operation": "create-db",
+//!   "names": ["alice_db"],
+//!   "results": { ... }
+//! }
+//! ```
+//!
+//! `results` is only given to post hooks, and is the JSON output of the command.
+//! A pre hook that fails stops the command before anything is sent to the server,
+//! while a post hook that fails only leads to a warning.
+
+use std::{
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::{
+    client::config::{self as client_config, ClientConfig},
+    core::{
+        output::CommandOutput,
+        style::{Role, paint},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookStage {
+    Pre,
+    Post,
+}
+
+impl HookStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookStage::Pre => "pre",
+            HookStage::Post => "post",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct HookInput<'a> {
+    hook: HookStage,
+    operation: &'a str,
+    names: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    results: Option<serde_json::Value>,
+}
+
+fn hooks_dir() -> Option<PathBuf> {
+    client_config::get()
+        .hooks_dir
+        .clone()
+        .or_else(|| ClientConfig::default_dir().map(|dir| dir.join("hooks")))
+}
+
+/// Find the executable for a hook, if there is one.
+fn find_hook(stage: HookStage, operation: &str) -> Option<PathBuf> {
+    let path = hooks_dir()?.join(format!("{}-{operation}", stage.as_str()));
+    let metadata = std::fs::metadata(&path).ok()?;
+    (metadata.is_file() && metadata.permissions().mode() & 0o111 != 0).then_some(path)
+}
+
+fn run_hook(path: &Path, input: &HookInput) -> anyhow::Result<()> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to run hook {}", path.display()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // NOTE: a hook that does not read its input closes the pipe, which is not an error.
+        stdin.write_all(&serde_json::to_vec(input)?).ok();
+    }
+
+    let status = child
+        .wait()
+        .context(format!("Failed to run hook {}", path.display()))?;
+    if !status.success() {
+        anyhow::bail!("Hook {} exited with {}", path.display(), status);
+    }
+
+    Ok(())
+}
+
+/// Run the pre hook of an operation, if there is one.
+///
+/// Returns an error if the hook fails, in which case the operation should not go ahead.
+pub fn run_pre_hook(operation: &str, names: &[String]) -> anyhow::Result<()> {
+    let Some(path) = find_hook(HookStage::Pre, operation) else {
+        return Ok(());
+    };
+
+    run_hook(
+        &path,
+        &HookInput {
+            hook: HookStage::Pre,
+            operation,
+            names,
+            results: None,
+        },
+    )
+    .context(format!("The pre-{operation} hook stopped the operation"))
+}
+
+/// Run the post hook of an operation, if there is one, printing a warning if it fails.
+pub fn run_post_hook<T: CommandOutput + ?Sized>(operation: &str, names: &[String], results: &T) {
+    let Some(path) = find_hook(HookStage::Post, operation) else {
+        return;
+    };
+
+    let input = HookInput {
+        hook: HookStage::Post,
+        operation,
+        names,
+        results: Some(results.to_json()),
+    };
+    if let Err(err) = run_hook(&path, &input) {
+        eprintln!("{}", paint(Role::Warning, &format!("Warning: {err:#}")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_input() {
+        let names = vec!["alice_db".to_string()];
+        let input = HookInput {
+            hook: HookStage::Pre,
+            operation: "create-db",
+            names: &names,
+            results: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&input).unwrap(),
+            serde_json::json!({
+              "hook": "pre",
+              "operation": "create-db",
+              "names": ["alice_db"],
+            }),
+        );
+    }
+}
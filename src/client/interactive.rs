@@ -0,0 +1,135 @@
+//! Interactive pickers for commands that are run on a terminal without any names.
+//!
+//! The pickers first ask for a search string, narrow down the candidates with a
+//! simple fuzzy (subsequence) match, and then let the user pick among the matches.
+
+use dialoguer::{Input, MultiSelect, Select};
+
+/// Score how well `pattern` matches `candidate`, or `None` if it does not match at all.
+///
+/// A candidate matches if every character in the pattern appears in the candidate,
+/// in order, ignoring case. Lower scores are better: the score is the number of
+/// skipped characters before and between the matched ones.
+fn fuzzy_score(pattern: &str, candidate: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut candidate_chars = candidate.chars().flat_map(char::to_lowercase);
+
+    for pattern_char in pattern.chars().flat_map(char::to_lowercase) {
+        loop {
+            if candidate_chars.next()? == pattern_char {
+                break;
+            }
+            score += 1;
+        }
+    }
+
+    Some(score)
+}
+
+/// Return the candidates matching `pattern`, best matches first.
+///
+/// An empty pattern matches every candidate.
+fn fuzzy_filter<'a>(pattern: &str, candidates: &'a [String]) -> Vec<&'a str> {
+    let mut matches = candidates
+        .iter()
+        .filter_map(|candidate| {
+            fuzzy_score(pattern, candidate).map(|score| (score, candidate.as_str()))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_unstable();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Ask for a search string until at least one candidate matches it.
+fn prompt_for_matches<'a>(prompt: &str, candidates: &'a [String]) -> anyhow::Result<Vec<&'a str>> {
+    loop {
+        let pattern: String = Input::new()
+            .with_prompt(format!(
+                "{prompt} (type to search, or press enter to list all)"
+            ))
+            .allow_empty(true)
+            .interact_text()?;
+
+        let matches = fuzzy_filter(pattern.trim(), candidates);
+        if matches.is_empty() {
+            eprintln!("Nothing matches '{}', try again.", pattern.trim());
+            continue;
+        }
+
+        return Ok(matches);
+    }
+}
+
+/// Let the user pick any number of the candidates.
+///
+/// Returns an empty list if there are no candidates, or if the user picked nothing.
+pub fn pick_many(prompt: &str, candidates: &[String]) -> anyhow::Result<Vec<String>> {
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let matches = prompt_for_matches(prompt, candidates)?;
+    let selection = MultiSelect::new()
+        .with_prompt("Select with space, confirm with enter")
+        .items(&matches)
+        .interact()?;
+
+    Ok(selection
+        .into_iter()
+        .map(|index| matches[index].to_string())
+        .collect())
+}
+
+/// Let the user pick exactly one of the candidates.
+///
+/// Returns `None` if there are no candidates, or if the user aborted with escape.
+pub fn pick_one(prompt: &str, candidates: &[String]) -> anyhow::Result<Option<String>> {
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    let matches = prompt_for_matches(prompt, candidates)?;
+    let selection = Select::new()
+        .with_prompt("Select with the arrow keys, confirm with enter")
+        .items(&matches)
+        .default(0)
+        .interact_opt()?;
+
+    Ok(selection.map(|index| matches[index].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "user_db"), Some(0));
+        assert_eq!(fuzzy_score("user", "user_db"), Some(0));
+        assert_eq!(fuzzy_score("udb", "user_db"), Some(4));
+        assert_eq!(fuzzy_score("UDB", "user_db"), Some(4));
+        assert_eq!(fuzzy_score("dbu", "user_db"), None);
+        assert_eq!(fuzzy_score("user_db_2", "user_db"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let candidates = vec![
+            "user_blog".to_string(),
+            "user_db".to_string(),
+            "user_old_db".to_string(),
+        ];
+        assert_eq!(
+            fuzzy_filter("db", &candidates),
+            vec!["user_db", "user_old_db"]
+        );
+        assert_eq!(
+            fuzzy_filter("", &candidates),
+            vec!["user_blog", "user_db", "user_old_db"]
+        );
+        assert!(fuzzy_filter("xyz", &candidates).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod common;
 mod error_messages;
+pub mod legacy_config;
 pub mod mysql_dbadm;
 pub mod mysql_useradm;
@@ -1,5 +1,19 @@
+use std::collections::BTreeMap;
+
 use crate::core::types::{MySQLDatabase, MySQLUser};
 
+/// Setting this environment variable to a non-empty value other than `0` enables
+/// strict compatibility mode for `mysql-dbadm` and `mysql-useradm`.
+///
+/// In this mode, the output formatting and exit codes follow the original tools
+/// as closely as possible, for scripts that parse their output.
+pub const STRICT_COMPATIBILITY_ENV_VAR: &str = "MUSCL_STRICT_ADMUTILS_COMPAT";
+
+#[must_use]
+pub fn strict_compatibility_mode() -> bool {
+    std::env::var(STRICT_COMPATIBILITY_ENV_VAR).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
 #[inline]
 #[must_use]
 pub fn trim_db_name_to_32_chars(db_name: &MySQLDatabase) -> MySQLDatabase {
@@ -11,3 +25,72 @@ pub fn trim_db_name_to_32_chars(db_name: &MySQLDatabase) -> MySQLDatabase {
 pub fn trim_user_name_to_32_chars(user_name: &MySQLUser) -> MySQLUser {
     user_name.chars().take(32).collect::<String>().into()
 }
+
+/// Orders the results from the server the way they should be printed.
+///
+/// The server returns results sorted by name. In strict compatibility mode, they are
+/// reordered to follow the order of the command line arguments, like the original tools
+/// which processed one argument at a time.
+#[must_use]
+pub fn order_results_for_output<K: Ord + Clone, V>(
+    names: &[K],
+    results: BTreeMap<K, V>,
+) -> Vec<(K, V)> {
+    order_results(names, results, strict_compatibility_mode())
+}
+
+fn order_results<K: Ord + Clone, V>(
+    names: &[K],
+    mut results: BTreeMap<K, V>,
+    in_argument_order: bool,
+) -> Vec<(K, V)> {
+    if !in_argument_order {
+        return results.into_iter().collect();
+    }
+
+    let mut ordered = Vec::with_capacity(results.len());
+    for name in names {
+        if let Some(result) = results.remove(name) {
+            ordered.push((name.clone(), result));
+        }
+    }
+    ordered.extend(results);
+    ordered
+}
+
+/// Prints an error that would otherwise have been propagated to the muscl entrypoint.
+///
+/// In strict compatibility mode, this mimics the `argv0: message` format of the original
+/// tools and exits with status 1. Otherwise, the error is returned as is.
+pub fn handle_fatal_error(default_argv0: &str, result: anyhow::Result<()>) -> anyhow::Result<()> {
+    match result {
+        Err(err) if strict_compatibility_mode() => {
+            let argv0 = std::env::args()
+                .next()
+                .unwrap_or_else(|| default_argv0.to_string());
+            eprintln!("{argv0}: {err}");
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_results() {
+        let names = vec!["c", "a", "c"];
+        let results = BTreeMap::from([("a", 1), ("b", 2), ("c", 3)]);
+
+        assert_eq!(
+            order_results(&names, results.clone(), false),
+            vec![("a", 1), ("b", 2), ("c", 3)]
+        );
+        assert_eq!(
+            order_results(&names, results, true),
+            vec![("c", 3), ("a", 1), ("b", 2)]
+        );
+    }
+}
@@ -32,24 +32,25 @@ pub fn authorization_error_message(db_or_user: &DbOrUser) -> String {
 }
 
 pub fn handle_create_user_error(error: &CreateUserError, name: &str) {
+    eprintln!("{}", create_user_error_message(error, name));
+}
+
+fn create_user_error_message(error: &CreateUserError, name: &str) -> String {
     let argv0 = std::env::args()
         .next()
         .unwrap_or_else(|| "mysql-useradm".to_string());
     match error {
         CreateUserError::ValidationError(ValidationError::NameValidationError(_)) => {
-            eprintln!(
-                "{}",
-                name_validation_error_to_error_message(&DbOrUser::User(name.into()))
-            );
+            name_validation_error_to_error_message(&DbOrUser::User(name.into()))
         }
         CreateUserError::ValidationError(ValidationError::AuthorizationError(_)) => {
-            eprintln!(
-                "{}",
-                authorization_error_message(&DbOrUser::User(name.into()))
-            );
+            authorization_error_message(&DbOrUser::User(name.into()))
+        }
+        CreateUserError::AccountLockingNotSupported => {
+            format!("{argv0}: Failed to create user '{name}': account locking is not supported.")
         }
         CreateUserError::MySqlError(_) | CreateUserError::UserAlreadyExists => {
-            eprintln!("{argv0}: Failed to create user '{name}'.");
+            format!("{argv0}: Failed to create user '{name}'.")
         }
     }
 }
@@ -178,3 +179,22 @@ pub fn format_show_database_error_message(error: &ListPrivilegesError, name: &st
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_user_error_message() {
+        let message =
+            create_user_error_message(&CreateUserError::AccountLockingNotSupported, "alice_user");
+        assert!(
+            message.ends_with(
+                ": Failed to create user 'alice_user': account locking is not supported."
+            )
+        );
+
+        let message = create_user_error_message(&CreateUserError::UserAlreadyExists, "alice_user");
+        assert!(message.ends_with(": Failed to create user 'alice_user'."));
+    }
+}
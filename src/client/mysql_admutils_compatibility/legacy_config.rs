@@ -0,0 +1,176 @@
+//! The config file of the original `mysql-admutils` tools.
+//!
+//! Hosts migrating from the original tools might still have `/etc/mysql-admutils.conf`
+//! around. When `mysql-dbadm` or `mysql-useradm` start the internal server in SUID/SGID mode,
+//! the settings from this file that map cleanly onto the muscl server config are used
+//! wherever the muscl config does not say anything itself:
+//!
+//! ```text
+//! # The socket of the MySQL server, used if neither `mysql.socket_path`
+//! # nor `mysql.host` is set in the muscl config.
+//! socket = /run/mysqld/mysqld.sock
+//!
+//! # Whether names may be prefixed with the unix groups of the user,
+//! # or only with their username.
+//! allow_group_prefixes = no
+//! ```
+//!
+//! Any other settings are ignored.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use nix::unistd::Group;
+
+use crate::{
+    core::{common::UnixUser, protocol::request_validation::GroupDenylist},
+    server::config::MysqlConfig,
+};
+
+pub const LEGACY_CONFIG_PATH: &str = "/etc/mysql-admutils.conf";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyConfig {
+    pub socket: Option<PathBuf>,
+    pub allow_group_prefixes: Option<bool>,
+}
+
+impl LegacyConfig {
+    /// Parse the `key = value` lines of a legacy config file.
+    ///
+    /// Empty lines and lines starting with `#` are ignored, and so are unknown keys
+    /// and values that can not be parsed, as the original tools did not complain about them either.
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+
+        for (line_number, line) in content.lines().enumerate() {
+            let trimmed_line = line.trim();
+
+            if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = trimmed_line.split_once('=') else {
+                tracing::debug!(
+                    "Ignoring invalid line {} in legacy config: {}",
+                    line_number + 1,
+                    line
+                );
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+
+            match key.trim() {
+                "socket" if !value.is_empty() => config.socket = Some(PathBuf::from(value)),
+                "allow_group_prefixes" => match value.to_ascii_lowercase().as_str() {
+                    "yes" | "true" | "1" => config.allow_group_prefixes = Some(true),
+                    "no" | "false" | "0" => config.allow_group_prefixes = Some(false),
+                    _ => tracing::debug!(
+                        "Ignoring invalid value for allow_group_prefixes in legacy config: {}",
+                        value
+                    ),
+                },
+                key => tracing::debug!("Ignoring unsupported legacy config setting: {}", key),
+            }
+        }
+
+        config
+    }
+
+    /// Read the legacy config from the given path, if it exists.
+    pub fn read_from_path(path: &Path) -> anyhow::Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(Self::parse(&content))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context(format!("Failed to read legacy config file at {path:?}")),
+        }
+    }
+
+    /// Fill in the MySQL connection settings that the muscl config leaves unset.
+    pub fn apply_to_mysql_config(&self, mysql_config: &mut MysqlConfig) {
+        if let Some(socket) = &self.socket
+            && mysql_config.socket_path.is_none()
+            && mysql_config.host.is_none()
+        {
+            tracing::debug!("Using MySQL socket {:?} from legacy config", socket);
+            mysql_config.socket_path = Some(socket.clone());
+        }
+    }
+
+    /// Add all groups of the user to the denylist, if the legacy config
+    /// only allows names prefixed with the username.
+    pub fn apply_to_group_denylist(
+        &self,
+        unix_user: &UnixUser,
+        group_denylist: &mut GroupDenylist,
+    ) {
+        if self.allow_group_prefixes != Some(false) {
+            return;
+        }
+
+        for group_name in &unix_user.groups {
+            match Group::from_name(group_name) {
+                Ok(Some(group)) => {
+                    group_denylist.insert(group.gid.as_raw());
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!("Failed to look up group '{}': {}", group_name, err);
+                }
+            }
+        }
+    }
+}
+
+static LEGACY_CONFIG: OnceLock<LegacyConfig> = OnceLock::new();
+
+/// Read the legacy config for the rest of the process, so that it is
+/// picked up by the internal server if one is started.
+///
+/// **WARNING:** This may be run with elevated privileges, so it only ever reads [`LEGACY_CONFIG_PATH`].
+pub fn init() {
+    match LegacyConfig::read_from_path(Path::new(LEGACY_CONFIG_PATH)) {
+        Ok(Some(config)) => {
+            let _ = LEGACY_CONFIG.set(config);
+        }
+        Ok(None) => {}
+        Err(err) => eprintln!("Warning: {err:#}"),
+    }
+}
+
+/// The legacy config read by [`init`], if there was one.
+#[must_use]
+pub fn get() -> Option<&'static LegacyConfig> {
+    LEGACY_CONFIG.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_config() {
+        let config = LegacyConfig::parse(indoc::indoc! {r#"
+            # comment
+            socket = "/run/mysqld/mysqld.sock"
+
+            allow_group_prefixes = No
+            host = db.example.com
+            this line is garbage
+        "#});
+
+        assert_eq!(
+            config,
+            LegacyConfig {
+                socket: Some(PathBuf::from("/run/mysqld/mysqld.sock")),
+                allow_group_prefixes: Some(false),
+            }
+        );
+
+        assert_eq!(LegacyConfig::parse(""), LegacyConfig::default());
+    }
+}
@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand};
 use clap_complete::ArgValueCompleter;
 use clap_verbosity_flag::Verbosity;
+use dialoguer::Editor;
 use futures_util::{SinkExt, StreamExt};
 use std::os::unix::net::UnixStream as StdUnixStream;
 use std::path::PathBuf;
@@ -8,24 +9,33 @@ use tokio::net::UnixStream as TokioUnixStream;
 
 use crate::{
     client::{
-        commands::{EditPrivsArgs, edit_database_privileges, erroneous_server_response},
+        commands::erroneous_server_response,
         mysql_admutils_compatibility::{
-            common::trim_db_name_to_32_chars,
+            common::{
+                handle_fatal_error, order_results_for_output, strict_compatibility_mode,
+                trim_db_name_to_32_chars,
+            },
             error_messages::{
                 format_show_database_error_message, handle_create_database_error,
                 handle_drop_database_error,
             },
+            legacy_config,
         },
+        progress::next_response_with_progress,
     },
     core::{
         bootstrap::bootstrap_server_connection_and_drop_privileges,
         completion::{mysql_database_completer, prefix_completer},
-        database_privileges::DatabasePrivilegeRow,
+        database_privileges::{
+            DatabasePrivilegeRow, database_privilege_fields, diff_privileges,
+            keep_hidden_privileges,
+        },
         protocol::{
-            ClientToServerMessageStream, ListPrivilegesError, Request, Response,
-            create_client_to_server_message_stream,
+            ClientToServerMessageStream, DEFAULT_READY_TIMEOUT, ListPrivilegesError,
+            ListPrivilegesRequest, ModifyPrivilegesRequest, Request, Response,
+            create_client_to_server_message_stream, wait_for_server_ready,
         },
-        types::MySQLDatabase,
+        types::{MySQLDatabase, MySQLUser},
     },
 };
 
@@ -118,6 +128,7 @@ pub enum Command {
     /// to make changes to the permission table.
     /// Run 'mysql-dbadm --help-editperm' for more
     /// information.
+    #[command(alias = "edit-perm")]
     Editperm(EditPermArgs),
 }
 
@@ -147,13 +158,18 @@ pub struct DatabaseShowArgs {
 
 #[derive(Parser)]
 pub struct EditPermArgs {
-    /// The name of the DATABASE to edit permissions for.
+    /// The name of the DATABASE(s) to edit permissions for.
+    #[arg(num_args = 1..)]
     #[cfg_attr(not(feature = "suid-sgid-mode"), arg(add = ArgValueCompleter::new(mysql_database_completer)))]
-    pub database: MySQLDatabase,
+    pub name: Vec<MySQLDatabase>,
 }
 
 /// **WARNING:** This function may be run with elevated privileges.
 pub fn main() -> anyhow::Result<()> {
+    handle_fatal_error("mysql-dbadm", run())
+}
+
+fn run() -> anyhow::Result<()> {
     let args: Args = Args::parse();
 
     if args.help_editperm {
@@ -161,17 +177,25 @@ pub fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    legacy_config::init();
+
     let server_connection = bootstrap_server_connection_and_drop_privileges(
         args.server_socket_path,
         args.config,
         Verbosity::default(),
+        None,
     )?;
 
     let Some(command) = args.command else {
-        println!(
+        let message = format!(
             "Try `{} --help' for more information.",
             std::env::args().next().unwrap_or("mysql-dbadm".to_string())
         );
+        if strict_compatibility_mode() {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+        println!("{message}");
         return Ok(());
     };
 
@@ -189,38 +213,13 @@ fn tokio_run_command(command: Command, server_connection: StdUnixStream) -> anyh
             let tokio_socket = TokioUnixStream::from_std(server_connection)?;
             let mut message_stream = create_client_to_server_message_stream(tokio_socket);
 
-            while let Some(Ok(message)) = message_stream.next().await {
-                match message {
-                    Response::Error(err) => {
-                        anyhow::bail!("{err}");
-                    }
-                    Response::Ready => break,
-                    message => {
-                        eprintln!("Unexpected message from server: {message:?}");
-                    }
-                }
-            }
+            wait_for_server_ready(&mut message_stream, DEFAULT_READY_TIMEOUT).await?;
 
             match command {
                 Command::Create(args) => create_databases(args, message_stream).await,
                 Command::Drop(args) => drop_databases(args, message_stream).await,
                 Command::Show(args) => show_databases(args, message_stream).await,
-                Command::Editperm(args) => {
-                    let edit_privileges_args = EditPrivsArgs {
-                        single_priv: None,
-                        privs: vec![],
-                        json: false,
-                        editor: None,
-                        yes: false,
-                    };
-
-                    edit_database_privileges(
-                        edit_privileges_args,
-                        Some(args.database),
-                        message_stream,
-                    )
-                    .await
-                }
+                Command::Editperm(args) => edit_permissions(args, message_stream).await,
             }
         })
 }
@@ -229,19 +228,20 @@ async fn create_databases(
     args: CreateArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let database_names = args.name.iter().map(trim_db_name_to_32_chars).collect();
+    let database_names: Vec<MySQLDatabase> =
+        args.name.iter().map(trim_db_name_to_32_chars).collect();
 
-    let message = Request::CreateDatabases(database_names);
+    let message = Request::CreateDatabases(database_names.clone());
     server_connection.send(message).await?;
 
-    let result = match server_connection.next().await {
+    let result = match next_response_with_progress(&mut server_connection).await {
         Some(Ok(Response::CreateDatabases(result))) => result,
         response => return erroneous_server_response(response),
     };
 
     server_connection.send(Request::Exit).await?;
 
-    for (name, result) in result {
+    for (name, result) in order_results_for_output(&database_names, result) {
         match result {
             Ok(()) => println!("Database {name} created."),
             Err(err) => handle_create_database_error(&err, &name),
@@ -255,9 +255,10 @@ async fn drop_databases(
     args: DatabaseDropArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let database_names = args.name.iter().map(trim_db_name_to_32_chars).collect();
+    let database_names: Vec<MySQLDatabase> =
+        args.name.iter().map(trim_db_name_to_32_chars).collect();
 
-    let message = Request::DropDatabases(database_names);
+    let message = Request::DropDatabases(database_names.clone());
     server_connection.send(message).await?;
 
     let result = match server_connection.next().await {
@@ -267,9 +268,9 @@ async fn drop_databases(
 
     server_connection.send(Request::Exit).await?;
 
-    for (name, result) in result {
+    for (name, result) in order_results_for_output(&database_names, result) {
         match result {
-            Ok(()) => println!("Database {name} dropped."),
+            Ok(_) => println!("Database {name} dropped."),
             Err(err) => handle_drop_database_error(&err, &name),
         }
     }
@@ -295,9 +296,15 @@ async fn show_databases(
 
         let database_names = databases.into_iter().map(|db| db.database).collect();
 
-        Request::ListPrivileges(Some(database_names))
+        Request::ListPrivileges(ListPrivilegesRequest {
+            databases: Some(database_names),
+            user: None,
+        })
     } else {
-        Request::ListPrivileges(Some(database_names))
+        Request::ListPrivileges(ListPrivilegesRequest {
+            databases: Some(database_names.clone()),
+            user: None,
+        })
     };
     server_connection.send(message).await?;
 
@@ -308,14 +315,16 @@ async fn show_databases(
     // NOTE: mysql-dbadm show has a quirk where valid database names
     //       for non-existent databases will report with no users.
     let results: Vec<Result<(MySQLDatabase, Vec<DatabasePrivilegeRow>), String>> = match response {
-        Some(Ok(Response::ListPrivileges(result))) => result
-            .into_iter()
-            .map(|(name, rows)| match rows.map(|rows| (name.clone(), rows)) {
-                Ok(rows) => Ok(rows),
-                Err(ListPrivilegesError::DatabaseDoesNotExist) => Ok((name, vec![])),
-                Err(err) => Err(format_show_database_error_message(&err, &name)),
-            })
-            .collect(),
+        Some(Ok(Response::ListPrivileges(result))) => {
+            order_results_for_output(&database_names, result)
+                .into_iter()
+                .map(|(name, rows)| match rows.map(|rows| (name.clone(), rows)) {
+                    Ok(rows) => Ok(rows),
+                    Err(ListPrivilegesError::DatabaseDoesNotExist) => Ok((name, vec![])),
+                    Err(err) => Err(format_show_database_error_message(&err, &name)),
+                })
+                .collect()
+        }
         response => return erroneous_server_response(response),
     };
 
@@ -334,20 +343,19 @@ fn yn(value: bool) -> &'static str {
     if value { "Y" } else { "N" }
 }
 
-fn print_db_privs(name: &str, rows: Vec<DatabasePrivilegeRow>) {
-    println!(
-        concat!(
-            "Database '{}':\n",
-            "# User                Select  Insert  Update  Delete  Create   Drop   Alter   Index    Temp    Lock  References\n",
-            "# ----------------    ------  ------  ------  ------  ------   ----   -----   -----    ----    ----  ----------"
-        ),
-        name,
-    );
+const DB_PRIVS_TABLE_HEADER: &str = concat!(
+    "# User                Select  Insert  Update  Delete  Create   Drop   Alter   Index    Temp    Lock  References\n",
+    "# ----------------    ------  ------  ------  ------  ------   ----   -----   -----    ----    ----  ----------"
+);
+
+fn format_db_privs_rows(rows: &[DatabasePrivilegeRow]) -> String {
     if rows.is_empty() {
-        println!("# (no permissions currently granted to any users)");
-    } else {
-        for privilege in rows {
-            println!(
+        return "# (no permissions currently granted to any users)".to_string();
+    }
+
+    rows.iter()
+        .map(|privilege| {
+            format!(
                 "  {:<16}      {:<7} {:<7} {:<7} {:<7} {:<7} {:<7} {:<7} {:<7} {:<7} {:<7} {}",
                 privilege.user,
                 yn(privilege.select_priv),
@@ -361,7 +369,199 @@ fn print_db_privs(name: &str, rows: Vec<DatabasePrivilegeRow>) {
                 yn(privilege.create_tmp_table_priv),
                 yn(privilege.lock_tables_priv),
                 yn(privilege.references_priv)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn print_db_privs(name: &str, rows: Vec<DatabasePrivilegeRow>) {
+    println!("Database '{name}':\n{DB_PRIVS_TABLE_HEADER}");
+    println!("{}", format_db_privs_rows(&rows));
+}
+
+/// Parses the content of the legacy `editperm` file.
+///
+/// Each non-comment line consists of a username followed by one Y/N-value
+/// per privilege. Users where all privileges are set to N are left out,
+/// which will revoke all their privileges on the database.
+///
+/// The legacy format has no columns for the extended privileges, so they are never granted.
+fn parse_editperm_content(
+    database: &MySQLDatabase,
+    content: &str,
+) -> anyhow::Result<Vec<DatabasePrivilegeRow>> {
+    let mut rows = Vec::new();
+
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        if parts.len() != 12 {
+            anyhow::bail!(
+                "Syntax error on line {}: expected a username followed by 11 Y/N-values",
+                line_number + 1
             );
         }
+
+        let values = parts[1..]
+            .iter()
+            .map(|value| match *value {
+                "Y" | "y" => Ok(true),
+                "N" | "n" => Ok(false),
+                value => Err(anyhow::anyhow!(
+                    "Syntax error on line {}: invalid value '{}', expected Y or N",
+                    line_number + 1,
+                    value
+                )),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if values.iter().all(|value| !value) {
+            continue;
+        }
+
+        rows.push(DatabasePrivilegeRow {
+            db: database.clone(),
+            user: MySQLUser::from(parts[0]),
+            select_priv: values[0],
+            insert_priv: values[1],
+            update_priv: values[2],
+            delete_priv: values[3],
+            create_priv: values[4],
+            drop_priv: values[5],
+            alter_priv: values[6],
+            index_priv: values[7],
+            create_tmp_table_priv: values[8],
+            lock_tables_priv: values[9],
+            references_priv: values[10],
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Opens the legacy permission table for each database in `$EDITOR`
+/// (or `pico`, like the original tool), and applies the changes afterwards.
+async fn edit_permissions(
+    args: EditPermArgs,
+    mut server_connection: ClientToServerMessageStream,
+) -> anyhow::Result<()> {
+    let database_names: Vec<MySQLDatabase> =
+        args.name.iter().map(trim_db_name_to_32_chars).collect();
+
+    server_connection
+        .send(Request::ListPrivileges(ListPrivilegesRequest {
+            databases: Some(database_names),
+            user: None,
+        }))
+        .await?;
+
+    let existing_privileges = match server_connection.next().await {
+        Some(Ok(Response::ListPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "pico".to_string());
+
+    let mut diffs = std::collections::BTreeSet::new();
+    for (name, rows) in existing_privileges {
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(err) => {
+                eprintln!("{}", format_show_database_error_message(&err, &name));
+                continue;
+            }
+        };
+
+        let content = format!(
+            "# Database '{name}':\n{DB_PRIVS_TABLE_HEADER}\n{}\n",
+            format_db_privs_rows(&rows)
+        );
+
+        let Some(edited_content) = Editor::new()
+            .executable(&editor)
+            .extension("txt")
+            .edit(&content)?
+        else {
+            println!("No changes made to database {name}.");
+            continue;
+        };
+
+        match parse_editperm_content(&name, &edited_content) {
+            Ok(mut new_rows) => {
+                keep_hidden_privileges(&mut new_rows, &rows, database_privilege_fields(false));
+                diffs.extend(diff_privileges(&rows, &new_rows));
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                eprintln!("No changes made to database {name}.");
+            }
+        }
+    }
+
+    if diffs.is_empty() {
+        server_connection.send(Request::Exit).await?;
+        return Ok(());
+    }
+
+    server_connection
+        .send(Request::ModifyPrivileges(ModifyPrivilegesRequest {
+            diffs,
+            atomic: false,
+        }))
+        .await?;
+
+    let result = match next_response_with_progress(&mut server_connection).await {
+        Some(Ok(Response::ModifyPrivileges(result))) => result,
+        response => return erroneous_server_response(response),
+    };
+
+    server_connection.send(Request::Exit).await?;
+
+    for ((database_name, username), result) in result {
+        if let Err(err) = result {
+            eprintln!("{}", err.to_error_message(&database_name, &username));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_editperm_content() {
+        let database = MySQLDatabase::from("user_db");
+        let content = indoc::indoc! {"
+            # Database 'user_db':
+            # User                Select  Insert  Update  Delete  Create   Drop   Alter   Index    Temp    Lock  References
+            # ----------------    ------  ------  ------  ------  ------   ----   -----   -----    ----    ----  ----------
+              user_alice            Y       Y       N       N       N       N       N       N       N       N       N
+              user_bob              N       N       N       N       N       N       N       N       N       N       N
+        "};
+
+        let rows = parse_editperm_content(&database, content).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].user, MySQLUser::from("user_alice"));
+        assert!(rows[0].select_priv);
+        assert!(rows[0].insert_priv);
+        assert!(!rows[0].update_priv);
+
+        assert!(parse_editperm_content(&database, "user_alice Y N").is_err());
+        assert!(parse_editperm_content(&database, "user_alice Y N N N N N N N N N X").is_err());
     }
 }
@@ -10,17 +10,22 @@ use crate::{
     client::{
         commands::{erroneous_server_response, read_password_from_stdin_with_double_check},
         mysql_admutils_compatibility::{
-            common::trim_user_name_to_32_chars,
+            common::{
+                handle_fatal_error, order_results_for_output, strict_compatibility_mode,
+                trim_user_name_to_32_chars,
+            },
             error_messages::{
                 handle_create_user_error, handle_drop_user_error, handle_list_users_error,
             },
+            legacy_config,
         },
     },
     core::{
         bootstrap::bootstrap_server_connection_and_drop_privileges,
         completion::{mysql_user_completer, prefix_completer},
         protocol::{
-            ClientToServerMessageStream, Request, Response, create_client_to_server_message_stream,
+            ClientToServerMessageStream, CreateUsersRequest, DEFAULT_READY_TIMEOUT, Request,
+            Response, create_client_to_server_message_stream, wait_for_server_ready,
         },
         types::MySQLUser,
     },
@@ -117,22 +122,34 @@ pub struct ShowArgs {
 
 /// **WARNING:** This function may be run with elevated privileges.
 pub fn main() -> anyhow::Result<()> {
+    handle_fatal_error("mysql-useradm", run())
+}
+
+fn run() -> anyhow::Result<()> {
     let args: Args = Args::parse();
 
     let Some(command) = args.command else {
-        println!(
+        let message = format!(
             "Try `{} --help' for more information.",
             std::env::args()
                 .next()
                 .unwrap_or("mysql-useradm".to_string())
         );
+        if strict_compatibility_mode() {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+        println!("{message}");
         return Ok(());
     };
 
+    legacy_config::init();
+
     let server_connection = bootstrap_server_connection_and_drop_privileges(
         args.server_socket_path,
         args.config,
         Default::default(),
+        None,
     )?;
 
     tokio_run_command(command, server_connection)?;
@@ -149,17 +166,7 @@ fn tokio_run_command(command: Command, server_connection: StdUnixStream) -> anyh
             let tokio_socket = TokioUnixStream::from_std(server_connection)?;
             let mut message_stream = create_client_to_server_message_stream(tokio_socket);
 
-            while let Some(Ok(message)) = message_stream.next().await {
-                match message {
-                    Response::Error(err) => {
-                        anyhow::bail!("{err}");
-                    }
-                    Response::Ready => break,
-                    message => {
-                        eprintln!("Unexpected message from server: {message:?}");
-                    }
-                }
-            }
+            wait_for_server_ready(&mut message_stream, DEFAULT_READY_TIMEOUT).await?;
 
             match command {
                 Command::Create(args) => create_user(args, message_stream).await,
@@ -174,9 +181,12 @@ async fn create_user(
     args: CreateArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let db_users = args.name.iter().map(trim_user_name_to_32_chars).collect();
+    let db_users: Vec<MySQLUser> = args.name.iter().map(trim_user_name_to_32_chars).collect();
 
-    let message = Request::CreateUsers(db_users);
+    let message = Request::CreateUsers(CreateUsersRequest {
+        users: db_users.clone(),
+        locked: false,
+    });
     server_connection.send(message).await?;
 
     let result = match server_connection.next().await {
@@ -186,7 +196,7 @@ async fn create_user(
 
     server_connection.send(Request::Exit).await?;
 
-    for (name, result) in result {
+    for (name, result) in order_results_for_output(&db_users, result) {
         match result {
             Ok(()) => println!("User '{name}' created."),
             Err(err) => handle_create_user_error(&err, &name),
@@ -200,9 +210,9 @@ async fn drop_users(
     args: DeleteArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let db_users = args.name.iter().map(trim_user_name_to_32_chars).collect();
+    let db_users: Vec<MySQLUser> = args.name.iter().map(trim_user_name_to_32_chars).collect();
 
-    let message = Request::DropUsers(db_users);
+    let message = Request::DropUsers(db_users.clone());
     server_connection.send(message).await?;
 
     let result = match server_connection.next().await {
@@ -212,9 +222,9 @@ async fn drop_users(
 
     server_connection.send(Request::Exit).await?;
 
-    for (name, result) in result {
+    for (name, result) in order_results_for_output(&db_users, result) {
         match result {
-            Ok(()) => println!("User '{name}' deleted."),
+            Ok(_) => println!("User '{name}' deleted."),
             Err(err) => handle_drop_user_error(&err, &name),
         }
     }
@@ -226,9 +236,9 @@ async fn passwd_users(
     args: PasswdArgs,
     mut server_connection: ClientToServerMessageStream,
 ) -> anyhow::Result<()> {
-    let db_users = args.name.iter().map(trim_user_name_to_32_chars).collect();
+    let db_users: Vec<MySQLUser> = args.name.iter().map(trim_user_name_to_32_chars).collect();
 
-    let message = Request::ListUsers(Some(db_users));
+    let message = Request::ListUsers(Some(db_users.clone()));
     server_connection.send(message).await?;
 
     let response = match server_connection.next().await {
@@ -240,7 +250,7 @@ async fn passwd_users(
         .next()
         .unwrap_or("mysql-useradm".to_string());
 
-    let users = response
+    let users = order_results_for_output(&db_users, response)
         .into_iter()
         .filter_map(|(name, result)| match result {
             Ok(user) => Some(user),
@@ -281,7 +291,7 @@ async fn show_users(
     let message = if db_users.is_empty() {
         Request::ListUsers(None)
     } else {
-        Request::ListUsers(Some(db_users))
+        Request::ListUsers(Some(db_users.clone()))
     };
     server_connection.send(message).await?;
 
@@ -293,7 +303,7 @@ async fn show_users(
                 return Ok(());
             }
         },
-        Some(Ok(Response::ListUsers(result))) => result
+        Some(Ok(Response::ListUsers(result))) => order_results_for_output(&db_users, result)
             .into_iter()
             .filter_map(|(name, result)| match result {
                 Ok(user) => Some(user),
@@ -0,0 +1,89 @@
+//! Showing the progress of long running bulk requests.
+//!
+//! The server sends [`Response::Progress`] messages ahead of the final response
+//! for large bulk requests, which are rendered as a progress bar on stderr.
+
+use std::io::{IsTerminal, Write};
+
+use tokio_stream::StreamExt;
+
+use crate::core::{
+    output,
+    protocol::{ClientToServerMessageStream, Response},
+};
+
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// Render a single line progress bar, e.g. `[#####-----] 5/10 alice_db5`.
+fn render_progress_bar(done: usize, total: usize, current_item: &str) -> String {
+    let filled = (done * PROGRESS_BAR_WIDTH).checked_div(total).unwrap_or(0);
+    let filled = filled.min(PROGRESS_BAR_WIDTH);
+    format!(
+        "[{}{}] {done}/{total} {current_item}",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled),
+    )
+}
+
+/// Receive the next response from the server, showing a progress bar on stderr
+/// for any progress messages that come before it.
+///
+/// The progress bar is only shown on a terminal, and never with `--quiet`.
+pub async fn next_response_with_progress(
+    server_connection: &mut ClientToServerMessageStream,
+) -> Option<Result<Response, std::io::Error>> {
+    let show_progress = std::io::stderr().is_terminal() && !output::options().quiet;
+    let mut progress_shown = false;
+
+    let response = loop {
+        match server_connection.next().await {
+            Some(Ok(Response::Progress {
+                done,
+                total,
+                current_item,
+            })) => {
+                if show_progress {
+                    eprint!(
+                        "\r\x1b[K{}",
+                        render_progress_bar(done, total, &current_item)
+                    );
+                    std::io::stderr().flush().ok();
+                    progress_shown = true;
+                }
+            }
+            response => break response,
+        }
+    };
+
+    if progress_shown {
+        eprint!("\r\x1b[K");
+        std::io::stderr().flush().ok();
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_progress_bar() {
+        assert_eq!(
+            render_progress_bar(0, 10, "alice_db0"),
+            format!("[{}] 0/10 alice_db0", "-".repeat(30)),
+        );
+        assert_eq!(
+            render_progress_bar(5, 10, "alice_db5"),
+            format!("[{}{}] 5/10 alice_db5", "#".repeat(15), "-".repeat(15)),
+        );
+        assert_eq!(
+            render_progress_bar(10, 10, ""),
+            format!("[{}] 10/10 ", "#".repeat(30)),
+        );
+        assert_eq!(
+            render_progress_bar(0, 0, ""),
+            format!("[{}] 0/0 ", "-".repeat(30)),
+        );
+    }
+}
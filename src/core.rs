@@ -1,6 +1,14 @@
 pub mod bootstrap;
+pub mod column_privileges;
 pub mod common;
 pub mod completion;
 pub mod database_privileges;
+pub mod exit_code;
+pub mod i18n;
+pub mod output;
+pub mod pager;
 pub mod protocol;
+pub mod routine_privileges;
+pub mod style;
+pub mod table;
 pub mod types;
@@ -2,7 +2,7 @@ use std::{
     fs,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, anyhow};
@@ -10,30 +10,124 @@ use clap_verbosity_flag::{InfoLevel, Verbosity};
 use nix::libc::{EXIT_SUCCESS, exit};
 use sqlx::mysql::MySqlPoolOptions;
 use std::os::unix::net::UnixStream as StdUnixStream;
+use thiserror::Error;
 use tokio::{net::UnixStream as TokioUnixStream, sync::RwLock};
 use tracing_subscriber::prelude::*;
 
 use crate::{
+    client::mysql_admutils_compatibility::legacy_config,
     core::{
-        common::{DEFAULT_CONFIG_PATH, DEFAULT_SOCKET_PATH, UnixUser, executing_in_suid_sgid_mode},
+        common::{
+            DEFAULT_CONFIG_PATH, DEFAULT_SOCKET_PATH, UnixUser, connect_unix_socket,
+            executing_in_suid_sgid_mode,
+        },
         protocol::request_validation::GroupDenylist,
     },
     server::{
         authorization::read_and_parse_group_denylist,
+        backend_capabilities::BackendCapabilities,
         config::{MysqlConfig, ServerConfig},
         landlock::landlock_restrict_server,
-        session_handler,
+        policy::PolicyEngine,
+        session_handler::{self, SessionContext},
     },
 };
 
+/// How long the client waits for the server to come back up by default,
+/// e.g. while it is being restarted by systemd.
+const DEFAULT_WAIT_FOR_SERVER: Duration = Duration::from_secs(5);
+
+/// Environment variable that can be used to override [`DEFAULT_WAIT_FOR_SERVER`], in seconds.
+pub const WAIT_FOR_SERVER_ENV_VAR: &str = "MUSCL_WAIT_FOR_SERVER";
+
+const INITIAL_CONNECT_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_CONNECT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// The client could not find or connect to a muscl server.
+#[derive(Error, Debug)]
+pub enum ServerConnectionError {
+    #[error("No socket path provided, and no default socket found")]
+    NoSocket,
+
+    #[error("No socket path or config path provided, and no default socket or config found")]
+    NoSocketOrConfig,
+
+    #[error("Socket not found")]
+    SocketNotFound,
+
+    #[error("Permission denied")]
+    PermissionDenied,
+
+    #[error("Failed to connect to socket: {0}")]
+    Io(std::io::Error),
+}
+
+/// Config paths that an unprivileged caller may pass with `--config` while the
+/// executable is running as SUID/SGID.
+///
+/// Additional paths can be allowed at compile time by setting
+/// `MUSCL_SUID_SGID_ALLOWED_CONFIG_PATHS` to a colon separated list of paths.
+fn allowed_suid_sgid_config_paths() -> Vec<&'static Path> {
+    std::iter::once(DEFAULT_CONFIG_PATH)
+        .chain(
+            option_env!("MUSCL_SUID_SGID_ALLOWED_CONFIG_PATHS")
+                .unwrap_or_default()
+                .split(':')
+                .filter(|path| !path.is_empty()),
+        )
+        .map(Path::new)
+        .collect()
+}
+
+/// Refuse client-supplied paths that could be abused while running with elevated privileges.
+///
+/// In SUID/SGID mode, `--config` is restricted to [`allowed_suid_sgid_config_paths`],
+/// and `--server-socket` is not allowed at all, since the client will never connect to
+/// an external server while it holds elevated privileges.
+///
+/// **WARNING:** This function may be run with elevated privileges.
+fn validate_paths_for_suid_sgid_mode(
+    server_socket_path: Option<&Path>,
+    config_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    if !executing_in_suid_sgid_mode()? {
+        return Ok(());
+    }
+
+    if server_socket_path.is_some() {
+        anyhow::bail!(
+            "The --server-socket option (or MUSCL_SOCKET) can not be used when running as SUID/SGID"
+        );
+    }
+
+    if let Some(config_path) = config_path {
+        let allowed_paths = allowed_suid_sgid_config_paths();
+        if !allowed_paths.contains(&config_path) {
+            anyhow::bail!(
+                "Refusing to use config file {:?} when running as SUID/SGID, allowed paths are: {}",
+                config_path,
+                allowed_paths
+                    .iter()
+                    .map(|path| format!("{path:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// Determine whether we will make a connection to an external server
 /// or start an internal server with elevated privileges.
 ///
-/// If neither is feasible, an error is returned.
+/// If neither is feasible, an error is returned, unless the client was asked
+/// to wait for the server, in which case it waits for the default socket to show up.
 fn will_connect_to_external_server(
     server_socket_path: Option<&PathBuf>,
     // This parameter is only used in suid-sgid-mode
     #[allow(unused_variables)] config_path: Option<&PathBuf>,
+    wait_for_default_socket: bool,
 ) -> anyhow::Result<bool> {
     if server_socket_path.is_some() {
         return Ok(true);
@@ -53,11 +147,17 @@ fn will_connect_to_external_server(
         return Ok(false);
     }
 
+    if wait_for_default_socket {
+        return Ok(true);
+    }
+
     #[cfg(feature = "suid-sgid-mode")]
-    anyhow::bail!("No socket path or config path provided, and no default socket or config found");
+    let error = ServerConnectionError::NoSocketOrConfig;
 
     #[cfg(not(feature = "suid-sgid-mode"))]
-    anyhow::bail!("No socket path provided, and no default socket found");
+    let error = ServerConnectionError::NoSocket;
+
+    Err(error.into())
 }
 
 /// This function is used to bootstrap the connection to the server.
@@ -76,6 +176,13 @@ fn will_connect_to_external_server(
 ///
 /// If neither of these options are available, the function will fail.
 ///
+/// When connecting to an external server, connection attempts are retried
+/// with exponential backoff for up to `wait_for_server`, in case the server
+/// is restarting. If `wait_for_server` is `None`, the value is read from
+/// the `MUSCL_WAIT_FOR_SERVER` environment variable, or a default is used.
+/// If a wait was asked for explicitly, the client also waits for the default
+/// socket to be created, instead of giving up right away when it is missing.
+///
 /// Note that this function is also responsible for setting up logging,
 /// because in the case of an internal server, we need to drop privileges
 /// before we can initialize logging.
@@ -85,8 +192,20 @@ pub fn bootstrap_server_connection_and_drop_privileges(
     server_socket_path: Option<PathBuf>,
     config: Option<PathBuf>,
     verbose: Verbosity<InfoLevel>,
+    wait_for_server: Option<Duration>,
 ) -> anyhow::Result<StdUnixStream> {
-    if will_connect_to_external_server(server_socket_path.as_ref(), config.as_ref())? {
+    validate_paths_for_suid_sgid_mode(server_socket_path.as_deref(), config.as_deref())?;
+
+    let wait_for_server = match wait_for_server {
+        Some(duration) => Some(duration),
+        None => wait_for_server_from_env()?,
+    };
+
+    if will_connect_to_external_server(
+        server_socket_path.as_ref(),
+        config.as_ref(),
+        wait_for_server.is_some_and(|duration| !duration.is_zero()),
+    )? {
         assert!(
             !executing_in_suid_sgid_mode()?,
             "The executable should not be SUID or SGID when connecting to an external server"
@@ -105,7 +224,10 @@ pub fn bootstrap_server_connection_and_drop_privileges(
         tracing::subscriber::set_global_default(subscriber)
             .context("Failed to set global default tracing subscriber")?;
 
-        connect_to_external_server(server_socket_path)
+        connect_to_external_server(
+            server_socket_path,
+            wait_for_server.unwrap_or(DEFAULT_WAIT_FOR_SERVER),
+        )
     } else if cfg!(feature = "suid-sgid-mode") {
         // NOTE: We need to be really careful with the code up until this point,
         //       as we might be running with elevated privileges.
@@ -130,35 +252,185 @@ pub fn bootstrap_server_connection_and_drop_privileges(
     }
 }
 
+fn wait_for_server_from_env() -> anyhow::Result<Option<Duration>> {
+    match std::env::var(WAIT_FOR_SERVER_ENV_VAR) {
+        Ok(value) => value
+            .trim()
+            .parse::<u64>()
+            .map(|seconds| Some(Duration::from_secs(seconds)))
+            .with_context(|| format!("Invalid value for {WAIT_FOR_SERVER_ENV_VAR}: '{value}'")),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Connect to the socket, retrying with exponential backoff while the socket
+/// is missing or refuses connections, until `wait_for_server` has passed.
+fn connect_with_retry(
+    socket_path: &Path,
+    wait_for_server: Duration,
+) -> anyhow::Result<StdUnixStream> {
+    let deadline = Instant::now() + wait_for_server;
+    let mut delay = INITIAL_CONNECT_RETRY_DELAY;
+
+    loop {
+        let error = match connect_unix_socket(socket_path) {
+            Ok(socket) => return Ok(socket),
+            Err(e) => e,
+        };
+
+        let is_retryable = matches!(
+            error.kind(),
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+        );
+        let now = Instant::now();
+
+        if !is_retryable || now >= deadline {
+            return Err(match error.kind() {
+                std::io::ErrorKind::NotFound => ServerConnectionError::SocketNotFound,
+                std::io::ErrorKind::PermissionDenied => ServerConnectionError::PermissionDenied,
+                _ => ServerConnectionError::Io(error),
+            }
+            .into());
+        }
+
+        tracing::debug!(
+            "Failed to connect to socket at {:?} ({}), retrying in {:?}",
+            socket_path,
+            error,
+            delay,
+        );
+
+        std::thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(MAX_CONNECT_RETRY_DELAY);
+    }
+}
+
 fn connect_to_external_server(
     server_socket_path: Option<PathBuf>,
+    wait_for_server: Duration,
 ) -> anyhow::Result<StdUnixStream> {
     // TODO: ensure this is both readable and writable
     if let Some(socket_path) = server_socket_path {
         tracing::debug!("Connecting to socket at {:?}", socket_path);
-        return match StdUnixStream::connect(socket_path) {
-            Ok(socket) => Ok(socket),
-            Err(e) => match e.kind() {
-                std::io::ErrorKind::NotFound => Err(anyhow::anyhow!("Socket not found")),
-                std::io::ErrorKind::PermissionDenied => Err(anyhow::anyhow!("Permission denied")),
-                _ => Err(anyhow::anyhow!("Failed to connect to socket: {e}")),
-            },
-        };
+        return connect_with_retry(&socket_path, wait_for_server);
     }
 
-    if fs::metadata(DEFAULT_SOCKET_PATH).is_ok() {
-        tracing::debug!("Connecting to default socket at {:?}", DEFAULT_SOCKET_PATH);
-        return match StdUnixStream::connect(DEFAULT_SOCKET_PATH) {
-            Ok(socket) => Ok(socket),
-            Err(e) => match e.kind() {
-                std::io::ErrorKind::NotFound => Err(anyhow::anyhow!("Socket not found")),
-                std::io::ErrorKind::PermissionDenied => Err(anyhow::anyhow!("Permission denied")),
-                _ => Err(anyhow::anyhow!("Failed to connect to socket: {e}")),
-            },
-        };
-    }
+    // NOTE: the default socket might not exist yet if the server is starting up,
+    //       in which case it is retried like any other socket.
+    tracing::debug!("Connecting to default socket at {:?}", DEFAULT_SOCKET_PATH);
+    connect_with_retry(Path::new(DEFAULT_SOCKET_PATH), wait_for_server)
+}
+
+/// Bootstrap a connection in direct mode, where the server session runs in a
+/// thread inside the client process and connects straight to `MySQL`,
+/// without going through a daemon.
+///
+/// The config (typically `/etc/muscl/config.toml`) is read and the database
+/// connection is established before dropping privileges, so the config only
+/// needs to be readable by the (possibly SUID/SGID) executable.
+///
+/// **WARNING:** This function may be run with elevated privileges.
+#[cfg(feature = "direct-mode")]
+pub fn bootstrap_direct_connection_and_drop_privileges(
+    config_path: Option<PathBuf>,
+    verbose: Verbosity<InfoLevel>,
+) -> anyhow::Result<StdUnixStream> {
+    validate_paths_for_suid_sgid_mode(None, config_path.as_deref())?;
+
+    let config_path = config_path.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let config = ServerConfig::read_config_from_path(&config_path)
+        .context("Failed to read config for direct mode")?;
+
+    let group_denylist = if let Some(denylist_path) = &config.authorization.group_denylist_file {
+        read_and_parse_group_denylist(denylist_path)
+            .context("Failed to read and parse group denylist")?
+    } else {
+        GroupDenylist::new()
+    };
+
+    let policy_engine = PolicyEngine::from_config(config.policy.as_ref())
+        .context("Failed to load policy modules")?;
+    let context = SessionContext::new(&config, group_denylist, policy_engine)
+        // NOTE: the pool only has a single connection.
+        .with_single_connection();
 
-    anyhow::bail!("No socket path provided, and no default socket found");
+    let unix_user = UnixUser::from_uid(nix::unistd::getuid().as_raw())?;
+    let (server_socket, client_socket) = StdUnixStream::pair()?;
+    let (ready_sender, ready_receiver) = std::sync::mpsc::channel::<anyhow::Result<()>>();
+
+    std::thread::spawn(move || {
+        let result =
+            run_direct_session(&config, server_socket, &unix_user, &context, &ready_sender);
+        // NOTE: if the client is still waiting for the session to become ready,
+        //       the error is reported by the client instead.
+        if let Err(e) = result
+            && let Err(std::sync::mpsc::SendError(Err(e))) = ready_sender.send(Err(e))
+        {
+            tracing::error!("Direct mode session failed: {:#}", e);
+        }
+    });
+
+    ready_receiver
+        .recv()
+        .context("Direct mode session exited unexpectedly")??;
+
+    drop_privs()?;
+
+    let subscriber = tracing_subscriber::Registry::default()
+        .with(verbose.tracing_level_filter())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_line_number(cfg!(debug_assertions))
+                .with_target(cfg!(debug_assertions))
+                .with_thread_ids(false)
+                .with_thread_names(false),
+        );
+
+    tracing::subscriber::set_global_default(subscriber)
+        .context("Failed to set global default tracing subscriber")?;
+
+    Ok(client_socket)
+}
+
+/// Run a single server session for direct mode.
+///
+/// The `ready` channel is notified once the database connection is established,
+/// after which the caller is free to drop privileges.
+#[cfg(feature = "direct-mode")]
+fn run_direct_session(
+    config: &ServerConfig,
+    server_socket: StdUnixStream,
+    unix_user: &UnixUser,
+    context: &SessionContext,
+    ready: &std::sync::mpsc::Sender<anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start Tokio runtime")?
+        .block_on(async {
+            let socket = TokioUnixStream::from_std(server_socket)?;
+            let db_pool = construct_single_connection_mysql_pool(&config.mysql).await?;
+            let backend_capabilities = BackendCapabilities::detect(&db_pool).await?;
+
+            // NOTE: the receiver only goes away if the client gave up on us.
+            let _ = ready.send(Ok(()));
+
+            let db_pool = Arc::new(RwLock::new(db_pool));
+            // NOTE: a single session is not worth a second connection to the read-only replica.
+            let db_replica_pool = Arc::new(RwLock::new(None));
+            session_handler::session_handler_with_unix_user(
+                socket,
+                unix_user,
+                db_pool,
+                db_replica_pool,
+                &backend_capabilities,
+                context,
+            )
+            .await?;
+
+            Ok(())
+        })
 }
 
 // TODO: this function is security critical, it should be integration tested
@@ -246,9 +518,9 @@ fn invoke_server_with_config(config_path: &Path) -> anyhow::Result<StdUnixStream
 
 /// Construct a `MySQL` connection pool that consists of exactly one connection.
 ///
-/// This is used for the internal server in SUID/SGID mode, where the server session
-/// only ever will get a single client.
-async fn construct_single_connection_mysql_pool(
+/// This is used for the internal server in SUID/SGID mode and for worker processes,
+/// where the server session only ever will get a single client.
+pub(crate) async fn construct_single_connection_mysql_pool(
     config: &MysqlConfig,
 ) -> anyhow::Result<sqlx::MySqlPool> {
     let mysql_config = config.as_mysql_connect_options()?;
@@ -282,16 +554,28 @@ fn run_forked_server(
     server_socket: StdUnixStream,
     unix_user: &UnixUser,
 ) -> anyhow::Result<()> {
-    let config = ServerConfig::read_config_from_path(config_path)
+    let mut config = ServerConfig::read_config_from_path(config_path)
         .context("Failed to read server config in forked process")?;
 
-    let group_denylist = if let Some(denylist_path) = &config.authorization.group_denylist_file {
+    let mut group_denylist = if let Some(denylist_path) = &config.authorization.group_denylist_file
+    {
         read_and_parse_group_denylist(denylist_path)
             .context("Failed to read and parse group denylist")?
     } else {
         GroupDenylist::new()
     };
 
+    if let Some(legacy_config) = legacy_config::get() {
+        legacy_config.apply_to_mysql_config(&mut config.mysql);
+        legacy_config.apply_to_group_denylist(unix_user, &mut group_denylist);
+    }
+
+    let policy_engine = PolicyEngine::from_config(config.policy.as_ref())
+        .context("Failed to load policy modules")?;
+    let context = SessionContext::new(&config, group_denylist, policy_engine)
+        // NOTE: the pool only has a single connection.
+        .with_single_connection();
+
     let result: anyhow::Result<()> = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
@@ -299,22 +583,18 @@ fn run_forked_server(
         .block_on(async {
             let socket = TokioUnixStream::from_std(server_socket)?;
             let db_pool = construct_single_connection_mysql_pool(&config.mysql).await?;
-            let db_is_mariadb = {
-                let mut conn = db_pool.acquire().await?;
-                let version_row: String = sqlx::query_scalar("SELECT VERSION()")
-                    .fetch_one(&mut *conn)
-                    .await
-                    .context("Failed to query MySQL version")?;
-                version_row.to_lowercase().contains("mariadb")
-            };
+            let backend_capabilities = BackendCapabilities::detect(&db_pool).await?;
 
             let db_pool = Arc::new(RwLock::new(db_pool));
+            // NOTE: a single session is not worth a second connection to the read-only replica.
+            let db_replica_pool = Arc::new(RwLock::new(None));
             session_handler::session_handler_with_unix_user(
                 socket,
                 unix_user,
                 db_pool,
-                db_is_mariadb,
-                &group_denylist,
+                db_replica_pool,
+                &backend_capabilities,
+                &context,
             )
             .await?;
             Ok(())
@@ -0,0 +1,898 @@
+//! Datastructures and logic for column-level privileges, stored in `mysql.columns_priv`.
+//!
+//! Column privileges work much like the database privileges in
+//! [`crate::core::database_privileges`], but are given on a single column of a table,
+//! and only cover the SELECT, INSERT, UPDATE and REFERENCES privileges.
+//! They use the same privilege characters as `edit-privs`, and the same editor format,
+//! with the table and column added after the database name.
+
+use std::{
+    cmp::max,
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use anyhow::{Context, anyhow};
+use itertools::Itertools;
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        common::{rev_yn, yn},
+        database_privileges::{
+            DatabasePrivilegeChange, DatabasePrivilegeEdit, DatabasePrivilegeEditEntryType,
+            PrivilegeAliases, db_priv_field_human_readable_name,
+            db_priv_field_single_character_name, db_priv_field_sql_name,
+        },
+        style::{Role, paint},
+        types::{MySQLDatabase, MySQLUser},
+    },
+    server::sql::{quote_identifier, quote_literal},
+};
+
+/// The privileges that can be given on a single column.
+///
+/// The names are the same as the matching fields of
+/// [`DATABASE_PRIVILEGE_FIELDS`](crate::core::database_privileges::DATABASE_PRIVILEGE_FIELDS).
+pub const COLUMN_PRIVILEGE_FIELDS: [&str; 4] = [
+    "select_priv",
+    "insert_priv",
+    "update_priv",
+    "references_priv",
+];
+
+const VALID_COLUMN_PRIVILEGE_EDIT_CHARS: &[char] = &['s', 'i', 'u', 'r', 'A'];
+
+/// The privileges of a single user on a single column of a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct ColumnPrivilegeRow {
+    pub db: MySQLDatabase,
+    pub table: String,
+    pub column: String,
+    pub user: MySQLUser,
+    pub select_priv: bool,
+    pub insert_priv: bool,
+    pub update_priv: bool,
+    pub references_priv: bool,
+}
+
+impl ColumnPrivilegeRow {
+    /// A row for the column without any privileges.
+    #[must_use]
+    pub fn new(db: MySQLDatabase, table: String, column: String, user: MySQLUser) -> Self {
+        Self {
+            db,
+            table,
+            column,
+            user,
+            select_priv: false,
+            insert_priv: false,
+            update_priv: false,
+            references_priv: false,
+        }
+    }
+
+    /// Parses the `Column_priv` set of a `mysql.columns_priv` row, e.g. `Select,Update`.
+    #[must_use]
+    pub fn with_privileges_from_mysql_set(mut self, set: &str) -> Self {
+        for privilege in set.split(',').filter(|p| !p.is_empty()) {
+            match COLUMN_PRIVILEGE_FIELDS
+                .into_iter()
+                .find(|field| db_priv_field_human_readable_name(field) == privilege)
+            {
+                // SAFETY: unwrap is safe here because the field names are static
+                Some(field) => *self.get_privilege_mut_by_name(field).unwrap() = true,
+                None => tracing::warn!("Unknown column privilege '{}'", privilege),
+            }
+        }
+        self
+    }
+
+    /// Gets the value of a privilege by its name as a &str.
+    #[must_use]
+    pub fn get_privilege_by_name(&self, name: &str) -> Option<bool> {
+        match name {
+            "select_priv" => Some(self.select_priv),
+            "insert_priv" => Some(self.insert_priv),
+            "update_priv" => Some(self.update_priv),
+            "references_priv" => Some(self.references_priv),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to a privilege by its name as a &str.
+    pub fn get_privilege_mut_by_name(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "select_priv" => Some(&mut self.select_priv),
+            "insert_priv" => Some(&mut self.insert_priv),
+            "update_priv" => Some(&mut self.update_priv),
+            "references_priv" => Some(&mut self.references_priv),
+            _ => None,
+        }
+    }
+
+    /// Whether the user has any privileges on the column.
+    #[must_use]
+    pub fn has_privileges(&self) -> bool {
+        COLUMN_PRIVILEGE_FIELDS
+            .into_iter()
+            .any(|field| self.get_privilege_by_name(field).unwrap())
+    }
+
+    /// The column and user the row is for, which identify it.
+    #[must_use]
+    pub fn key(&self) -> ColumnPrivilegeKey {
+        ColumnPrivilegeKey {
+            db: self.db.clone(),
+            table: self.table.clone(),
+            column: self.column.clone(),
+            user: self.user.clone(),
+        }
+    }
+}
+
+/// A column of a table, and a user with privileges on it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct ColumnPrivilegeKey {
+    pub db: MySQLDatabase,
+    pub table: String,
+    pub column: String,
+    pub user: MySQLUser,
+}
+
+impl fmt::Display for ColumnPrivilegeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}: {}",
+            self.db, self.table, self.column, self.user
+        )
+    }
+}
+
+/// Renders the granted privileges of a row as a string of the single-character
+/// privilege names used by `edit-column-privs`, e.g. `su`.
+#[must_use]
+pub fn format_column_privileges_as_cli_string(row: &ColumnPrivilegeRow) -> String {
+    COLUMN_PRIVILEGE_FIELDS
+        .into_iter()
+        .filter(|field| row.get_privilege_by_name(field) == Some(true))
+        .map(db_priv_field_single_character_name)
+        .collect()
+}
+
+/// A single CLI argument for editing column privileges, parsed from a string like
+///
+///   `database_name.table_name.column_name:username:[+|-]privileges`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnPrivilegeEditEntry {
+    pub database: MySQLDatabase,
+    pub table: String,
+    pub column: String,
+    pub user: MySQLUser,
+    pub privilege_edit: DatabasePrivilegeEdit,
+}
+
+impl ColumnPrivilegeEditEntry {
+    /// Parses a column privilege edit entry from a string.
+    ///
+    /// The expected format is:
+    ///
+    ///   `database_name.table_name.column_name:username:[+|-]privileges`
+    ///
+    /// where the privileges are any of `siur`, or `A` for all of them, or the privilege
+    /// aliases from `aliases` that only stand for those. The database name ends at the first
+    /// `.` and the column name starts after the last one, so the table name may contain dots.
+    pub fn parse_from_str(arg: &str, aliases: &PrivilegeAliases) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = arg.split(':').collect();
+        if parts.len() != 3 {
+            anyhow::bail!("Invalid column privilege edit entry format: {arg}");
+        }
+
+        let (column_path, user, privileges) = (parts[0], parts[1], parts[2]);
+
+        let (database, table, column) = column_path
+            .split_once('.')
+            .and_then(|(database, rest)| {
+                rest.rsplit_once('.')
+                    .map(|(table, column)| (database, table, column))
+            })
+            .filter(|(database, table, column)| {
+                !database.is_empty() && !table.is_empty() && !column.is_empty()
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "Expected the column as DB_NAME.TABLE_NAME.COLUMN_NAME, found: {column_path}"
+                )
+            })?;
+
+        if user.is_empty() {
+            anyhow::bail!("Username cannot be empty in column privilege edit entry: {arg}");
+        }
+
+        let privilege_edit = parse_column_privilege_edit(privileges, aliases)?;
+
+        Ok(ColumnPrivilegeEditEntry {
+            database: MySQLDatabase::from(database.to_string()),
+            table: table.to_string(),
+            column: column.to_string(),
+            user: MySQLUser::from(user.to_string()),
+            privilege_edit,
+        })
+    }
+
+    /// Applies the edit to the existing privileges of the column, if there are any.
+    #[must_use]
+    pub fn apply(&self, existing: Option<&ColumnPrivilegeRow>) -> ColumnPrivilegeRow {
+        let mut row = existing.cloned().unwrap_or_else(|| {
+            ColumnPrivilegeRow::new(
+                self.database.clone(),
+                self.table.clone(),
+                self.column.clone(),
+                self.user.clone(),
+            )
+        });
+
+        let value = match self.privilege_edit.type_ {
+            DatabasePrivilegeEditEntryType::Set => {
+                for field in COLUMN_PRIVILEGE_FIELDS {
+                    *row.get_privilege_mut_by_name(field).unwrap() = false;
+                }
+                true
+            }
+            DatabasePrivilegeEditEntryType::Add => true,
+            DatabasePrivilegeEditEntryType::Remove => false,
+        };
+
+        for field in COLUMN_PRIVILEGE_FIELDS {
+            let priv_char = db_priv_field_single_character_name(field);
+            if self
+                .privilege_edit
+                .privileges
+                .iter()
+                .any(|c| *c == 'A' || priv_char.starts_with(*c))
+            {
+                *row.get_privilege_mut_by_name(field).unwrap() = value;
+            }
+        }
+
+        row
+    }
+}
+
+impl fmt::Display for ColumnPrivilegeEditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}:{}:{}",
+            self.database, self.table, self.column, self.user, self.privilege_edit
+        )
+    }
+}
+
+/// Parses a `[+-]PRIVILEGES` string like [`DatabasePrivilegeEdit::parse_from_str`],
+/// but only allows the privileges that can be given on a column.
+pub fn parse_column_privilege_edit(
+    input: &str,
+    aliases: &PrivilegeAliases,
+) -> anyhow::Result<DatabasePrivilegeEdit> {
+    let edit = DatabasePrivilegeEdit::parse_from_str(input, aliases)?;
+
+    let invalid_chars = edit
+        .privileges
+        .iter()
+        .filter(|c| !VALID_COLUMN_PRIVILEGE_EDIT_CHARS.contains(c))
+        .map(|c| format!("'{c}'"))
+        .join(", ");
+    if !invalid_chars.is_empty() {
+        anyhow::bail!(
+            "Privilege(s) {invalid_chars} can not be given on a column\n\nValid characters are: {}",
+            VALID_COLUMN_PRIVILEGE_EDIT_CHARS
+                .iter()
+                .map(|c| format!("'{c}'"))
+                .join(", ")
+        );
+    }
+
+    Ok(edit)
+}
+
+/// The changes to the privileges of a single user on a single column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct ColumnPrivilegeRowDiff {
+    pub db: MySQLDatabase,
+    pub table: String,
+    pub column: String,
+    pub user: MySQLUser,
+    pub select_priv: Option<DatabasePrivilegeChange>,
+    pub insert_priv: Option<DatabasePrivilegeChange>,
+    pub update_priv: Option<DatabasePrivilegeChange>,
+    pub references_priv: Option<DatabasePrivilegeChange>,
+}
+
+impl ColumnPrivilegeRowDiff {
+    /// Calculates the difference between two [`ColumnPrivilegeRow`] instances.
+    #[must_use]
+    pub fn from_rows(row1: &ColumnPrivilegeRow, row2: &ColumnPrivilegeRow) -> Self {
+        debug_assert!(row1.key() == row2.key());
+
+        ColumnPrivilegeRowDiff {
+            db: row1.db.clone(),
+            table: row1.table.clone(),
+            column: row1.column.clone(),
+            user: row1.user.clone(),
+            select_priv: DatabasePrivilegeChange::new(row1.select_priv, row2.select_priv),
+            insert_priv: DatabasePrivilegeChange::new(row1.insert_priv, row2.insert_priv),
+            update_priv: DatabasePrivilegeChange::new(row1.update_priv, row2.update_priv),
+            references_priv: DatabasePrivilegeChange::new(
+                row1.references_priv,
+                row2.references_priv,
+            ),
+        }
+    }
+
+    /// Returns true if there are no changes in this diff.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        COLUMN_PRIVILEGE_FIELDS
+            .into_iter()
+            .all(|field| self.get_privilege_change_by_name(field).is_none())
+    }
+
+    /// Retrieves the privilege change for a given privilege name.
+    #[must_use]
+    pub fn get_privilege_change_by_name(&self, name: &str) -> Option<DatabasePrivilegeChange> {
+        match name {
+            "select_priv" => self.select_priv,
+            "insert_priv" => self.insert_priv,
+            "update_priv" => self.update_priv,
+            "references_priv" => self.references_priv,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ColumnPrivilegeRowDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in COLUMN_PRIVILEGE_FIELDS {
+            match self.get_privilege_change_by_name(field) {
+                Some(DatabasePrivilegeChange::YesToNo) => writeln!(
+                    f,
+                    "{}: {}",
+                    db_priv_field_human_readable_name(field),
+                    paint(Role::DiffRemoved, "Y -> N"),
+                )?,
+                Some(DatabasePrivilegeChange::NoToYes) => writeln!(
+                    f,
+                    "{}: {}",
+                    db_priv_field_human_readable_name(field),
+                    paint(Role::DiffAdded, "N -> Y"),
+                )?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a [`ColumnPrivilegeRow`] was introduced, modified or deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub enum ColumnPrivilegesDiff {
+    New(ColumnPrivilegeRow),
+    Modified(ColumnPrivilegeRowDiff),
+    Deleted(ColumnPrivilegeRow),
+}
+
+impl ColumnPrivilegesDiff {
+    /// The column and user the diff is for.
+    #[must_use]
+    pub fn key(&self) -> ColumnPrivilegeKey {
+        match self {
+            ColumnPrivilegesDiff::New(p) | ColumnPrivilegesDiff::Deleted(p) => p.key(),
+            ColumnPrivilegesDiff::Modified(p) => ColumnPrivilegeKey {
+                db: p.db.clone(),
+                table: p.table.clone(),
+                column: p.column.clone(),
+                user: p.user.clone(),
+            },
+        }
+    }
+
+    /// The change of every privilege that is changed by the diff.
+    #[must_use]
+    pub fn changes(&self) -> Vec<(&'static str, DatabasePrivilegeChange)> {
+        COLUMN_PRIVILEGE_FIELDS
+            .into_iter()
+            .filter_map(|field| {
+                let change = match self {
+                    ColumnPrivilegesDiff::New(p) => p
+                        .get_privilege_by_name(field)
+                        .unwrap()
+                        .then_some(DatabasePrivilegeChange::NoToYes),
+                    ColumnPrivilegesDiff::Modified(p) => p.get_privilege_change_by_name(field),
+                    ColumnPrivilegesDiff::Deleted(p) => p
+                        .get_privilege_by_name(field)
+                        .unwrap()
+                        .then_some(DatabasePrivilegeChange::YesToNo),
+                };
+                change.map(|change| (field, change))
+            })
+            .collect()
+    }
+
+    /// Whether the diff applies to `existing`, the current privileges of the column,
+    /// i.e. it only grants privileges that are not granted, and revokes ones that are.
+    #[must_use]
+    pub fn applies_to(&self, existing: Option<&ColumnPrivilegeRow>) -> bool {
+        match (self, existing) {
+            (ColumnPrivilegesDiff::New(_), None) => true,
+            (ColumnPrivilegesDiff::New(_), Some(_)) | (_, None) => false,
+            (_, Some(existing)) => self.changes().into_iter().all(|(field, change)| {
+                let granted = existing.get_privilege_by_name(field).unwrap();
+                match change {
+                    DatabasePrivilegeChange::NoToYes => !granted,
+                    DatabasePrivilegeChange::YesToNo => granted,
+                }
+            }),
+        }
+    }
+
+    /// The `GRANT` and `REVOKE` statements that apply the diff.
+    ///
+    /// Column privileges are changed with statements rather than by editing
+    /// `mysql.columns_priv`, so that `mysql.tables_priv` is kept in sync.
+    #[must_use]
+    pub fn to_sql_statements(&self) -> Vec<String> {
+        let key = self.key();
+        let changes = self.changes();
+        let privileges = |change: DatabasePrivilegeChange| {
+            changes
+                .iter()
+                .filter(|(_, c)| *c == change)
+                .map(|(field, _)| {
+                    format!(
+                        "{} ({})",
+                        db_priv_field_sql_name(field),
+                        quote_identifier(&key.column)
+                    )
+                })
+                .join(", ")
+        };
+        let target = format!(
+            "{}.{}",
+            quote_identifier(&key.db),
+            quote_identifier(&key.table)
+        );
+
+        let mut statements = Vec::new();
+        let granted = privileges(DatabasePrivilegeChange::NoToYes);
+        if !granted.is_empty() {
+            statements.push(format!(
+                "GRANT {granted} ON {target} TO {}@'%'",
+                quote_literal(&key.user)
+            ));
+        }
+        let revoked = privileges(DatabasePrivilegeChange::YesToNo);
+        if !revoked.is_empty() {
+            statements.push(format!(
+                "REVOKE {revoked} ON {target} FROM {}@'%'",
+                quote_literal(&key.user)
+            ));
+        }
+        statements
+    }
+}
+
+/// Calculates the differences between two sets of column privileges.
+///
+/// Rows in `to` without any privileges are treated as if they were not there.
+#[must_use]
+pub fn diff_column_privileges(
+    from: &[ColumnPrivilegeRow],
+    to: &[ColumnPrivilegeRow],
+) -> BTreeSet<ColumnPrivilegesDiff> {
+    let from_lookup_table: HashMap<ColumnPrivilegeKey, &ColumnPrivilegeRow> =
+        from.iter().map(|p| (p.key(), p)).collect();
+    let to_lookup_table: HashMap<ColumnPrivilegeKey, &ColumnPrivilegeRow> = to
+        .iter()
+        .filter(|p| p.has_privileges())
+        .map(|p| (p.key(), p))
+        .collect();
+
+    let mut result = BTreeSet::new();
+
+    for (key, p) in &to_lookup_table {
+        if let Some(old_p) = from_lookup_table.get(key) {
+            let diff = ColumnPrivilegeRowDiff::from_rows(old_p, p);
+            if !diff.is_empty() {
+                result.insert(ColumnPrivilegesDiff::Modified(diff));
+            }
+        } else {
+            result.insert(ColumnPrivilegesDiff::New((*p).to_owned()));
+        }
+    }
+
+    for (key, p) in &from_lookup_table {
+        if !to_lookup_table.contains_key(key) {
+            result.insert(ColumnPrivilegesDiff::Deleted((*p).to_owned()));
+        }
+    }
+
+    result
+}
+
+/// Applies the edits from the command line to the existing column privileges,
+/// and returns the changes needed to get there.
+#[must_use]
+pub fn create_or_modify_column_privilege_rows(
+    from: &[ColumnPrivilegeRow],
+    edits: &[ColumnPrivilegeEditEntry],
+) -> BTreeSet<ColumnPrivilegesDiff> {
+    let mut rows = from.to_vec();
+    for edit in edits {
+        let position = rows.iter().position(|row| {
+            row.db == edit.database
+                && row.table == edit.table
+                && row.column == edit.column
+                && row.user == edit.user
+        });
+        let row = edit.apply(position.map(|i| &rows[i]));
+        match position {
+            Some(i) => rows[i] = row,
+            None => rows.push(row),
+        }
+    }
+
+    diff_column_privileges(from, &rows)
+}
+
+/// Renders a set of [`ColumnPrivilegesDiff`] into a human-readable formatted table.
+#[must_use]
+pub fn display_column_privilege_diffs(diffs: &BTreeSet<ColumnPrivilegesDiff>) -> String {
+    let mut table = Table::new();
+    table.set_titles(row!["Column", "User", "Privilege diff"]);
+    for diff in diffs {
+        let key = diff.key();
+        let column = format!("{}.{}.{}", key.db, key.table, key.column);
+        match diff {
+            ColumnPrivilegesDiff::New(p) => {
+                table.add_row(row![
+                    column,
+                    key.user,
+                    paint(Role::DiffAdded, "(Previously unprivileged)")
+                        + "\n"
+                        + &COLUMN_PRIVILEGE_FIELDS
+                            .into_iter()
+                            .map(|field| format!(
+                                "{}: {}\n",
+                                db_priv_field_human_readable_name(field),
+                                yn(p.get_privilege_by_name(field).unwrap())
+                            ))
+                            .join("")
+                ]);
+            }
+            ColumnPrivilegesDiff::Modified(p) => {
+                table.add_row(row![column, key.user, p.to_string()]);
+            }
+            ColumnPrivilegesDiff::Deleted(_) => {
+                table.add_row(row![column, key.user, paint(Role::DiffRemoved, "Removed")]);
+            }
+        }
+    }
+
+    table.to_string()
+}
+
+const EDITOR_COMMENT: &str = r"
+# Welcome to the column privilege editor.
+# Each line defines what privileges a single user has on a single column of a table.
+# The first four columns respectively represent the database, table, column and user, and the remaining columns are the privileges.
+# If the user should have a certain privilege, write 'Y', otherwise write 'N'.
+# To grant privileges on another column, add a line for it.
+#
+# Lines starting with '#' are comments and will be ignored.
+";
+
+const EDITOR_NAME_HEADERS: [&str; 4] = ["Database", "Table", "Column", "User"];
+
+/// Generates a single row of the column privileges table for the editor.
+fn format_column_privileges_line_for_editor(
+    privs: &ColumnPrivilegeRow,
+    name_widths: [usize; 4],
+) -> String {
+    [
+        privs.db.as_str(),
+        privs.table.as_str(),
+        privs.column.as_str(),
+        privs.user.as_str(),
+    ]
+    .into_iter()
+    .zip(name_widths)
+    .map(|(name, width)| format!("{name:width$}"))
+    .chain(COLUMN_PRIVILEGE_FIELDS.into_iter().map(|field| {
+        format!(
+            "{:width$}",
+            yn(privs.get_privilege_by_name(field).unwrap()),
+            width = db_priv_field_human_readable_name(field).len()
+        )
+    }))
+    .join(" ")
+    .trim()
+    .to_string()
+}
+
+/// Generates the content for the column privilege editor.
+///
+/// The unix user is used in case there are no privileges to edit,
+/// so that the user can see an example line based on their username.
+#[must_use]
+pub fn generate_column_editor_content(
+    privilege_data: &[ColumnPrivilegeRow],
+    unix_user: &str,
+) -> String {
+    let example = ColumnPrivilegeRow {
+        select_priv: true,
+        ..ColumnPrivilegeRow::new(
+            format!("{unix_user}_db").into(),
+            "table".to_string(),
+            "column".to_string(),
+            format!("{unix_user}_user").into(),
+        )
+    };
+    let shown_rows = if privilege_data.is_empty() {
+        std::slice::from_ref(&example)
+    } else {
+        privilege_data
+    };
+
+    let name_widths = [0, 1, 2, 3].map(|i| {
+        shown_rows
+            .iter()
+            .map(|row| match i {
+                0 => row.db.len(),
+                1 => row.table.len(),
+                2 => row.column.len(),
+                _ => row.user.len(),
+            })
+            .fold(EDITOR_NAME_HEADERS[i].len(), max)
+    });
+
+    let header = EDITOR_NAME_HEADERS
+        .into_iter()
+        .zip(name_widths)
+        .map(|(name, width)| format!("{name:width$}"))
+        .chain(
+            COLUMN_PRIVILEGE_FIELDS
+                .into_iter()
+                .map(db_priv_field_human_readable_name),
+        )
+        .join(" ");
+
+    let rows = shown_rows
+        .iter()
+        .map(|row| format_column_privileges_line_for_editor(row, name_widths))
+        .map(|line| {
+            if privilege_data.is_empty() {
+                format!("# {line}")
+            } else {
+                line
+            }
+        })
+        .join("\n");
+
+    format!("{EDITOR_COMMENT}\n{header}\n{rows}")
+}
+
+/// Parse the rows of the column privileges table from the editor.
+pub fn parse_column_privilege_data_from_editor_content(
+    content: &str,
+) -> anyhow::Result<Vec<ColumnPrivilegeRow>> {
+    let field_count = EDITOR_NAME_HEADERS.len() + COLUMN_PRIVILEGE_FIELDS.len();
+
+    content
+        .trim()
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .filter(|(_, line)| {
+            !line
+                .split_ascii_whitespace()
+                .zip(EDITOR_NAME_HEADERS)
+                .all(|(cell, header)| cell == header)
+        })
+        .map(|(i, line)| {
+            let parts: Vec<&str> = line.split_ascii_whitespace().collect();
+            if parts.len() != field_count {
+                anyhow::bail!(
+                    "Wrong number of fields in line {i}:\n  {line}\n  Expected to find {field_count} fields, found {}",
+                    parts.len(),
+                );
+            }
+
+            let mut row = ColumnPrivilegeRow::new(
+                parts[0].into(),
+                parts[1].to_string(),
+                parts[2].to_string(),
+                parts[3].into(),
+            );
+            for (cell, field) in parts[4..].iter().zip(COLUMN_PRIVILEGE_FIELDS) {
+                let value = rev_yn(cell)
+                    .ok_or_else(|| anyhow!("Expected Y or N, found {cell}"))
+                    .context(format!(
+                        "Could not parse '{}' privilege in line {i}:\n  {line}",
+                        db_priv_field_human_readable_name(field)
+                    ))?;
+                *row.get_privilege_mut_by_name(field).unwrap() = value;
+            }
+            Ok(row)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(select_priv: bool, update_priv: bool) -> ColumnPrivilegeRow {
+        ColumnPrivilegeRow {
+            select_priv,
+            update_priv,
+            ..ColumnPrivilegeRow::new(
+                "alice_db".into(),
+                "grades".to_string(),
+                "score".to_string(),
+                "alice_user".into(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_with_privileges_from_mysql_set() {
+        let parsed = ColumnPrivilegeRow::new(
+            "alice_db".into(),
+            "grades".to_string(),
+            "score".to_string(),
+            "alice_user".into(),
+        )
+        .with_privileges_from_mysql_set("Select,Update");
+        assert_eq!(parsed, row(true, true));
+    }
+
+    #[test]
+    fn test_parse_column_privilege_edit_entry() {
+        let entry = ColumnPrivilegeEditEntry::parse_from_str(
+            "alice_db.grades.v1.score:alice_user:+su",
+            &PrivilegeAliases::new(),
+        )
+        .unwrap();
+        assert_eq!(entry.database, MySQLDatabase::from("alice_db"));
+        assert_eq!(entry.table, "grades.v1");
+        assert_eq!(entry.column, "score");
+        assert_eq!(entry.user, MySQLUser::from("alice_user"));
+        assert_eq!(
+            entry.privilege_edit.type_,
+            DatabasePrivilegeEditEntryType::Add
+        );
+
+        assert!(
+            ColumnPrivilegeEditEntry::parse_from_str(
+                "alice_db.grades:alice_user:s",
+                &PrivilegeAliases::new()
+            )
+            .is_err()
+        );
+        assert!(
+            ColumnPrivilegeEditEntry::parse_from_str(
+                "alice_db.grades.score:alice_user:sd",
+                &PrivilegeAliases::new()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_create_or_modify_column_privilege_rows() {
+        let existing = vec![row(true, false)];
+        let edit = |arg: &str| {
+            ColumnPrivilegeEditEntry::parse_from_str(arg, &PrivilegeAliases::new()).unwrap()
+        };
+
+        let diffs = create_or_modify_column_privilege_rows(
+            &existing,
+            &[edit("alice_db.grades.score:alice_user:+u")],
+        );
+        assert_eq!(
+            diffs,
+            BTreeSet::from([ColumnPrivilegesDiff::Modified(
+                ColumnPrivilegeRowDiff::from_rows(&row(true, false), &row(true, true))
+            )])
+        );
+
+        let diffs = create_or_modify_column_privilege_rows(
+            &existing,
+            &[edit("alice_db.grades.score:alice_user:-s")],
+        );
+        assert_eq!(
+            diffs,
+            BTreeSet::from([ColumnPrivilegesDiff::Deleted(row(true, false))])
+        );
+
+        let diffs = create_or_modify_column_privilege_rows(
+            &[],
+            &[edit("alice_db.grades.score:alice_user:A")],
+        );
+        assert_eq!(diffs.len(), 1);
+        assert!(matches!(
+            diffs.first(),
+            Some(ColumnPrivilegesDiff::New(p)) if p.select_priv && p.insert_priv && p.update_priv && p.references_priv
+        ));
+    }
+
+    #[test]
+    fn test_column_privileges_diff_to_sql_statements() {
+        let diff = ColumnPrivilegesDiff::Modified(ColumnPrivilegeRowDiff::from_rows(
+            &row(true, false),
+            &row(false, true),
+        ));
+        assert_eq!(
+            diff.to_sql_statements(),
+            vec![
+                "GRANT UPDATE (`score`) ON `alice_db`.`grades` TO 'alice_user'@'%'".to_string(),
+                "REVOKE SELECT (`score`) ON `alice_db`.`grades` FROM 'alice_user'@'%'".to_string(),
+            ]
+        );
+        assert!(diff.applies_to(Some(&row(true, false))));
+        assert!(!diff.applies_to(Some(&row(true, true))));
+        assert!(!diff.applies_to(None));
+    }
+
+    #[test]
+    fn test_column_privileges_diff_to_sql_statements_quotes_names() {
+        let diff = ColumnPrivilegesDiff::New(ColumnPrivilegeRow {
+            select_priv: true,
+            ..ColumnPrivilegeRow::new(
+                "alice_db".into(),
+                "grades` TO bob_db.x, alice_db.y TO bob_db.z -- ".to_string(),
+                "score`, secret".to_string(),
+                "alice_user".into(),
+            )
+        });
+        assert_eq!(
+            diff.to_sql_statements(),
+            vec![
+                "GRANT SELECT (`score``, secret`) ON `alice_db`.`grades`` TO bob_db.x, alice_db.y TO bob_db.z -- ` TO 'alice_user'@'%'"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_editor_content_roundtrip() {
+        let rows = vec![
+            row(true, false),
+            ColumnPrivilegeRow {
+                column: "name".to_string(),
+                ..row(false, true)
+            },
+        ];
+        let content = generate_column_editor_content(&rows, "alice");
+        assert_eq!(
+            parse_column_privilege_data_from_editor_content(&content).unwrap(),
+            rows
+        );
+
+        let content = generate_column_editor_content(&[], "alice");
+        assert!(
+            parse_column_privilege_data_from_editor_content(&content)
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(
+            parse_column_privilege_data_from_editor_content("alice_db grades score alice_user Y N")
+                .is_err()
+        );
+    }
+}
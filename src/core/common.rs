@@ -5,9 +5,16 @@ use nix::unistd::{Group as LibcGroup, User as LibcUser};
 #[cfg(not(target_os = "macos"))]
 use std::ffi::CString;
 use std::fmt;
+use std::os::unix::{
+    ffi::OsStrExt,
+    net::{UnixListener as StdUnixListener, UnixStream as StdUnixStream},
+};
+use std::path::Path;
 
 pub const DEFAULT_CONFIG_PATH: &str = "/etc/muscl/config.toml";
 pub const DEFAULT_SOCKET_PATH: &str = "/run/muscl/muscl.sock";
+/// The unix user the server runs as, see `assets/systemd/muscl.service`.
+pub const DEFAULT_SERVER_USER: &str = "muscl";
 
 pub const ASCII_BANNER: &str = indoc! {
   r"
@@ -84,6 +91,69 @@ pub fn executing_in_suid_sgid_mode() -> anyhow::Result<bool> {
     Ok(false)
 }
 
+/// Returns the name of the Linux abstract namespace socket, if the socket path
+/// is written as `@name`.
+///
+/// Abstract sockets do not exist in the filesystem, so they don't need to be
+/// cleaned up. They are not protected by filesystem permissions either, so any
+/// local user can bind the name while the server is not running. Clients
+/// therefore check who is on the other end, see [`connect_unix_socket`].
+#[must_use]
+pub fn abstract_socket_name(socket_path: &Path) -> Option<&[u8]> {
+    if cfg!(target_os = "linux") {
+        socket_path.as_os_str().as_bytes().strip_prefix(b"@")
+    } else {
+        None
+    }
+}
+
+/// Connect to a unix socket, which may be an abstract socket (see [`abstract_socket_name`]).
+///
+/// The peer of an abstract socket has to be root, the [`DEFAULT_SERVER_USER`] or the
+/// current user, so that no other user can pose as the server and collect passwords.
+pub fn connect_unix_socket(socket_path: &Path) -> std::io::Result<StdUnixStream> {
+    #[cfg(target_os = "linux")]
+    if let Some(name) = abstract_socket_name(socket_path) {
+        use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+        let stream = StdUnixStream::connect_addr(&SocketAddr::from_abstract_name(name)?)?;
+        check_abstract_socket_peer(&stream)?;
+        return Ok(stream);
+    }
+
+    StdUnixStream::connect(socket_path)
+}
+
+#[cfg(target_os = "linux")]
+fn check_abstract_socket_peer(stream: &StdUnixStream) -> std::io::Result<()> {
+    use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+    use nix::unistd::{Uid, getuid};
+
+    let peer_uid = Uid::from_raw(getsockopt(stream, PeerCredentials)?.uid());
+    let server_uid = LibcUser::from_name(DEFAULT_SERVER_USER)
+        .ok()
+        .flatten()
+        .map(|user| user.uid);
+
+    if peer_uid.is_root() || peer_uid == getuid() || Some(peer_uid) == server_uid {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "The abstract socket is owned by UID {peer_uid}, which is neither root nor the '{DEFAULT_SERVER_USER}' user"
+        )))
+    }
+}
+
+/// Bind a unix socket, which may be an abstract socket (see [`abstract_socket_name`]).
+pub fn bind_unix_listener(socket_path: &Path) -> std::io::Result<StdUnixListener> {
+    #[cfg(target_os = "linux")]
+    if let Some(name) = abstract_socket_name(socket_path) {
+        use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+        return StdUnixListener::bind_addr(&SocketAddr::from_abstract_name(name)?);
+    }
+
+    StdUnixListener::bind(socket_path)
+}
+
 impl UnixUser {
     pub fn from_uid(uid: u32) -> anyhow::Result<Self> {
         let libc_uid = nix::unistd::Uid::from_raw(uid);
@@ -99,6 +169,19 @@ impl UnixUser {
         })
     }
 
+    pub fn from_username(username: &str) -> anyhow::Result<Self> {
+        let libc_user = LibcUser::from_name(username)
+            .context(format!("Failed to look up UNIX user '{username}'"))?
+            .ok_or(anyhow::anyhow!("UNIX user '{username}' does not exist"))?;
+
+        let groups = get_unix_groups(&libc_user)?;
+
+        Ok(UnixUser {
+            username: libc_user.name,
+            groups: groups.iter().map(|g| g.name.clone()).collect(),
+        })
+    }
+
     // pub fn from_enviroment() -> anyhow::Result<Self> {
     //     let libc_uid = nix::unistd::getuid();
     //     UnixUser::from_uid(libc_uid.as_raw())
@@ -137,4 +220,17 @@ mod test {
         assert_eq!(rev_yn("n"), Some(false));
         assert_eq!(rev_yn("X"), None);
     }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_abstract_socket_name() {
+        assert_eq!(
+            abstract_socket_name(Path::new("@muscl")),
+            Some(b"muscl".as_slice())
+        );
+        assert_eq!(
+            abstract_socket_name(Path::new("/run/muscl/muscl.sock")),
+            None
+        );
+    }
 }
@@ -1,7 +1,10 @@
+mod completion_cache;
 mod mysql_database_completer;
 mod mysql_user_completer;
 mod prefix_completer;
+mod privilege_completer;
 
 pub use mysql_database_completer::*;
 pub use mysql_user_completer::*;
 pub use prefix_completer::*;
+pub use privilege_completer::*;
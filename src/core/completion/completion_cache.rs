@@ -0,0 +1,131 @@
+//! A cache of the last successful completions, for when the server can not be reached.
+//!
+//! Shell completion should not come back empty just because the server is being restarted,
+//! so every completer stores the names it got from the server in
+//! `$XDG_CACHE_HOME/muscl/completions.json` (or `~/.cache/muscl/completions.json`),
+//! and falls back to these names if it can not connect.
+//!
+//! The cache lives in the home directory of the unix user, so every user only ever
+//! sees the names they were allowed to see themselves.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedNameKind {
+    Databases,
+    Users,
+    Prefixes,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct CompletionCache {
+    databases: Vec<String>,
+    users: Vec<String>,
+    prefixes: Vec<String>,
+}
+
+impl CompletionCache {
+    fn names(&self, kind: CachedNameKind) -> &Vec<String> {
+        match kind {
+            CachedNameKind::Databases => &self.databases,
+            CachedNameKind::Users => &self.users,
+            CachedNameKind::Prefixes => &self.prefixes,
+        }
+    }
+
+    fn names_mut(&mut self, kind: CachedNameKind) -> &mut Vec<String> {
+        match kind {
+            CachedNameKind::Databases => &mut self.databases,
+            CachedNameKind::Users => &mut self.users,
+            CachedNameKind::Prefixes => &mut self.prefixes,
+        }
+    }
+
+    /// Replace the cached names starting with `current` with the ones the server returned,
+    /// as the server only returns the names matching what has been written so far.
+    fn update(&mut self, kind: CachedNameKind, current: &str, names: &[String]) {
+        let cached_names = self.names_mut(kind);
+        cached_names.retain(|name| !name.starts_with(current));
+        cached_names.extend(names.iter().cloned());
+        cached_names.sort();
+        cached_names.dedup();
+    }
+
+    fn matching(&self, kind: CachedNameKind, current: &str) -> Vec<String> {
+        self.names(kind)
+            .iter()
+            .filter(|name| name.starts_with(current))
+            .cloned()
+            .collect()
+    }
+}
+
+fn cache_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("muscl").join("completions.json"))
+}
+
+fn read_cache() -> CompletionCache {
+    cache_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Store the names the server returned for `current` in the cache.
+///
+/// Failing to write the cache is not worth interrupting the completion for, so errors are ignored.
+///
+/// **WARNING:** This must not be called before privileges are dropped.
+pub fn update_completion_cache(kind: CachedNameKind, current: &str, names: &[String]) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+
+    let mut cache = read_cache();
+    cache.update(kind, current, names);
+
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(content) = serde_json::to_string(&cache) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+/// The cached names starting with `current`.
+#[must_use]
+pub fn cached_completions(kind: CachedNameKind, current: &str) -> Vec<String> {
+    read_cache().matching(kind, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_completion_cache() {
+        let mut cache = CompletionCache::default();
+        let names = |names: &[&str]| names.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        cache.update(
+            CachedNameKind::Databases,
+            "",
+            &names(&["alice_a", "alice_b", "bob_a"]),
+        );
+        cache.update(CachedNameKind::Databases, "alice_", &names(&["alice_c"]));
+
+        assert_eq!(cache.databases, names(&["alice_c", "bob_a"]));
+        assert_eq!(
+            cache.matching(CachedNameKind::Databases, "b"),
+            names(&["bob_a"])
+        );
+        assert!(cache.matching(CachedNameKind::Users, "").is_empty());
+    }
+}
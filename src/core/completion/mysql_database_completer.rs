@@ -1,14 +1,19 @@
+use std::time::Duration;
+
 use clap_complete::CompletionCandidate;
 use clap_verbosity_flag::Verbosity;
 use futures_util::SinkExt;
 use tokio::net::UnixStream as TokioUnixStream;
 use tokio_stream::StreamExt;
 
+use super::completion_cache::{CachedNameKind, cached_completions, update_completion_cache};
 use crate::{
-    client::commands::erroneous_server_response,
+    client::{commands::erroneous_server_response, config::ClientConfig},
     core::{
         bootstrap::bootstrap_server_connection_and_drop_privileges,
-        protocol::{Request, Response, create_client_to_server_message_stream},
+        protocol::{
+            Request, Response, create_client_to_server_message_stream, wait_for_server_ready,
+        },
     },
 };
 
@@ -21,8 +26,12 @@ pub fn mysql_database_completer(current: &std::ffi::OsStr) -> Vec<CompletionCand
         Ok(runtime) => match runtime.block_on(mysql_database_completer_(current)) {
             Ok(completions) => completions,
             Err(err) => {
-                eprintln!("Error getting MySQL database completions: {err}");
-                Vec::new()
+                let cached =
+                    cached_completions(CachedNameKind::Databases, &current.to_string_lossy());
+                if cached.is_empty() {
+                    eprintln!("Error getting MySQL database completions: {err}");
+                }
+                cached.into_iter().map(CompletionCandidate::new).collect()
             }
         },
         Err(err) => {
@@ -36,25 +45,20 @@ pub fn mysql_database_completer(current: &std::ffi::OsStr) -> Vec<CompletionCand
 async fn mysql_database_completer_(
     current: &std::ffi::OsStr,
 ) -> anyhow::Result<Vec<CompletionCandidate>> {
-    let server_connection =
-        bootstrap_server_connection_and_drop_privileges(None, None, Verbosity::new(0, 1))?;
+    let server_connection = bootstrap_server_connection_and_drop_privileges(
+        None,
+        None,
+        Verbosity::new(0, 1),
+        Some(Duration::ZERO),
+    )?;
 
     let tokio_socket = TokioUnixStream::from_std(server_connection)?;
     let mut server_connection = create_client_to_server_message_stream(tokio_socket);
 
-    while let Some(Ok(message)) = server_connection.next().await {
-        match message {
-            Response::Error(err) => {
-                anyhow::bail!("{err}");
-            }
-            Response::Ready => break,
-            message => {
-                eprintln!("Unexpected message from server: {message:?}");
-            }
-        }
-    }
+    wait_for_server_ready(&mut server_connection, ClientConfig::load().ready_timeout()).await?;
 
-    let message = Request::CompleteDatabaseName(current.to_string_lossy().to_string());
+    let current = current.to_string_lossy().to_string();
+    let message = Request::CompleteDatabaseName(current.clone());
 
     if let Err(err) = server_connection.send(message).await {
         server_connection.close().await.ok();
@@ -68,7 +72,10 @@ async fn mysql_database_completer_(
 
     server_connection.send(Request::Exit).await?;
 
-    let result = result.into_iter().map(CompletionCandidate::new).collect();
+    let names: Vec<String> = result.into_iter().map(|name| name.to_string()).collect();
+    update_completion_cache(CachedNameKind::Databases, &current, &names);
+
+    let result = names.into_iter().map(CompletionCandidate::new).collect();
 
     Ok(result)
 }
@@ -1,28 +1,50 @@
+use std::time::Duration;
+
 use clap_complete::CompletionCandidate;
 use clap_verbosity_flag::Verbosity;
 use futures_util::SinkExt;
 use tokio::net::UnixStream as TokioUnixStream;
 use tokio_stream::StreamExt;
 
+use super::completion_cache::{CachedNameKind, cached_completions, update_completion_cache};
 use crate::{
-    client::commands::erroneous_server_response,
+    client::{commands::erroneous_server_response, config::ClientConfig},
     core::{
         bootstrap::bootstrap_server_connection_and_drop_privileges,
-        protocol::{Request, Response, create_client_to_server_message_stream},
+        protocol::{
+            Request, Response, create_client_to_server_message_stream, wait_for_server_ready,
+        },
     },
 };
 
+/// Completes the prefixes of names, including the `_` delimiter.
 #[must_use]
 pub fn prefix_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_prefixes(current, "_")
+}
+
+/// Completes the bare prefixes, for use with `--prefix`.
+#[must_use]
+pub fn bare_prefix_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    complete_prefixes(current, "")
+}
+
+fn complete_prefixes(current: &std::ffi::OsStr, delimiter: &str) -> Vec<CompletionCandidate> {
     match tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
     {
-        Ok(runtime) => match runtime.block_on(prefix_completer_(current)) {
+        Ok(runtime) => match runtime.block_on(prefix_completer_(current, delimiter)) {
             Ok(completions) => completions,
             Err(err) => {
-                eprintln!("Error getting prefix completions: {err}");
-                Vec::new()
+                let cached = cached_completions(CachedNameKind::Prefixes, "");
+                if cached.is_empty() {
+                    eprintln!("Error getting prefix completions: {err}");
+                }
+                cached
+                    .into_iter()
+                    .map(|prefix| CompletionCandidate::new(prefix + delimiter))
+                    .collect()
             }
         },
         Err(err) => {
@@ -33,24 +55,21 @@ pub fn prefix_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
 }
 
 /// Connect to the server to get `MySQL` user completions.
-async fn prefix_completer_(_current: &std::ffi::OsStr) -> anyhow::Result<Vec<CompletionCandidate>> {
-    let server_connection =
-        bootstrap_server_connection_and_drop_privileges(None, None, Verbosity::new(0, 1))?;
+async fn prefix_completer_(
+    _current: &std::ffi::OsStr,
+    delimiter: &str,
+) -> anyhow::Result<Vec<CompletionCandidate>> {
+    let server_connection = bootstrap_server_connection_and_drop_privileges(
+        None,
+        None,
+        Verbosity::new(0, 1),
+        Some(Duration::ZERO),
+    )?;
 
     let tokio_socket = TokioUnixStream::from_std(server_connection)?;
     let mut server_connection = create_client_to_server_message_stream(tokio_socket);
 
-    while let Some(Ok(message)) = server_connection.next().await {
-        match message {
-            Response::Error(err) => {
-                anyhow::bail!("{err}");
-            }
-            Response::Ready => break,
-            message => {
-                eprintln!("Unexpected message from server: {message:?}");
-            }
-        }
-    }
+    wait_for_server_ready(&mut server_connection, ClientConfig::load().ready_timeout()).await?;
 
     let message = Request::ListValidNamePrefixes;
 
@@ -66,9 +85,11 @@ async fn prefix_completer_(_current: &std::ffi::OsStr) -> anyhow::Result<Vec<Com
 
     server_connection.send(Request::Exit).await?;
 
+    update_completion_cache(CachedNameKind::Prefixes, "", &result);
+
     let result = result
         .into_iter()
-        .map(|prefix| prefix + "_")
+        .map(|prefix| prefix + delimiter)
         .map(CompletionCandidate::new)
         .collect();
 
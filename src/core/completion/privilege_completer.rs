@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use clap_complete::CompletionCandidate;
+use clap_verbosity_flag::Verbosity;
+use futures_util::SinkExt;
+use tokio::net::UnixStream as TokioUnixStream;
+use tokio_stream::StreamExt;
+
+use super::completion_cache::{CachedNameKind, cached_completions, update_completion_cache};
+use crate::{
+    client::commands::erroneous_server_response,
+    core::{
+        bootstrap::bootstrap_server_connection_and_drop_privileges,
+        database_privileges::{
+            DATABASE_PRIVILEGE_FIELDS, db_priv_field_human_readable_name,
+            db_priv_field_single_character_name,
+        },
+        protocol::{
+            DEFAULT_HANDSHAKE_TIMEOUT, Request, Response, create_client_to_server_message_stream,
+            wait_for_server_ready,
+        },
+    },
+};
+
+/// Completes the privilege characters of a `[+-]PRIVILEGES` argument,
+/// one character at a time, without asking the server.
+#[must_use]
+pub fn privilege_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    privilege_candidates(&current.to_string_lossy())
+}
+
+/// Completes the `DB_NAME` positional argument of `edit-privs`,
+/// suggesting your prefixes as well as the existing databases.
+#[must_use]
+pub fn database_name_and_prefix_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    run_completer(
+        &current.to_string_lossy(),
+        "database",
+        |current| async move {
+            let names = fetch_names_and_prefixes(NameKind::Database, &current).await?;
+            Ok(names.into_iter().map(CompletionCandidate::new).collect())
+        },
+    )
+}
+
+/// Completes the `USER_NAME` positional argument of `edit-privs`,
+/// suggesting your prefixes as well as the existing users.
+#[must_use]
+pub fn user_name_and_prefix_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    run_completer(&current.to_string_lossy(), "user", |current| async move {
+        let names = fetch_names_and_prefixes(NameKind::User, &current).await?;
+        Ok(names.into_iter().map(CompletionCandidate::new).collect())
+    })
+}
+
+/// Completes `DB_NAME:USER_NAME:[+-]PRIVILEGES` for `edit-privs --privs`,
+/// depending on which part of the argument is being written.
+#[must_use]
+pub fn privilege_edit_entry_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let mut parts = current.splitn(3, ':');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(database), Some(user), Some(privileges)) => privilege_candidates(privileges)
+            .into_iter()
+            .map(|candidate| candidate.add_prefix(format!("{database}:{user}:")))
+            .collect(),
+        (Some(database), Some(user), None) => {
+            let database = database.to_string();
+            run_completer(user, "user", |current| async move {
+                let names = fetch_names_and_prefixes(NameKind::User, &current).await?;
+                Ok(names
+                    .into_iter()
+                    .map(|name| CompletionCandidate::new(format!("{database}:{name}")))
+                    .collect())
+            })
+        }
+        (Some(database), None, None) => run_completer(database, "database", |current| async move {
+            let names = fetch_names_and_prefixes(NameKind::Database, &current).await?;
+            Ok(names.into_iter().map(CompletionCandidate::new).collect())
+        }),
+        _ => Vec::new(),
+    }
+}
+
+/// The candidates for a partially written `[+-]PRIVILEGES` string.
+///
+/// Every privilege that is not already in the string is suggested as the next character,
+/// with the name of the privilege as help text. `A` (all basic privileges) is only suggested
+/// on its own, as there is nothing to add to it.
+fn privilege_candidates(current: &str) -> Vec<CompletionCandidate> {
+    let privilege_chars: Vec<(char, String)> = DATABASE_PRIVILEGE_FIELDS
+        .into_iter()
+        .skip(2)
+        .filter_map(|field| {
+            db_priv_field_single_character_name(field)
+                .chars()
+                .next()
+                .map(|c| (c, db_priv_field_human_readable_name(field)))
+        })
+        .collect();
+
+    let body = current
+        .strip_prefix('+')
+        .or_else(|| current.strip_prefix('-'))
+        .unwrap_or(current);
+
+    if body.contains('A')
+        || body
+            .chars()
+            .any(|c| !privilege_chars.iter().any(|(p, _)| *p == c))
+    {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+
+    if current.is_empty() {
+        candidates.push(CompletionCandidate::new("+").help(Some("Grant privileges".into())));
+        candidates.push(CompletionCandidate::new("-").help(Some("Revoke privileges".into())));
+    }
+
+    if body.is_empty() {
+        candidates.push(
+            CompletionCandidate::new(format!("{current}A"))
+                .help(Some("All basic privileges".into())),
+        );
+    }
+
+    candidates.extend(
+        privilege_chars
+            .into_iter()
+            .filter(|(c, _)| !body.contains(*c))
+            .map(|(c, name)| {
+                CompletionCandidate::new(format!("{current}{c}")).help(Some(name.into()))
+            }),
+    );
+
+    candidates
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NameKind {
+    Database,
+    User,
+}
+
+impl NameKind {
+    fn cache_kind(self) -> CachedNameKind {
+        match self {
+            NameKind::Database => CachedNameKind::Databases,
+            NameKind::User => CachedNameKind::Users,
+        }
+    }
+}
+
+fn run_completer<F, Fut>(current: &str, kind: &str, completer: F) -> Vec<CompletionCandidate>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<CompletionCandidate>>>,
+{
+    match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => match runtime.block_on(completer(current.to_string())) {
+            Ok(completions) => completions,
+            Err(err) => {
+                eprintln!("Error getting MySQL {kind} completions: {err}");
+                Vec::new()
+            }
+        },
+        Err(err) => {
+            eprintln!("Error starting Tokio runtime: {err}");
+            Vec::new()
+        }
+    }
+}
+
+/// The names matching `current`, followed by the prefixes that `current` could
+/// still be extended to, falling back to the completion cache if the server can not be reached.
+async fn fetch_names_and_prefixes(kind: NameKind, current: &str) -> anyhow::Result<Vec<String>> {
+    let (names, prefixes) = match fetch_names_and_prefixes_from_server(kind, current).await {
+        Ok((names, prefixes)) => {
+            update_completion_cache(kind.cache_kind(), current, &names);
+            update_completion_cache(CachedNameKind::Prefixes, "", &prefixes);
+            (names, prefixes)
+        }
+        Err(err) => {
+            let names = cached_completions(kind.cache_kind(), current);
+            let prefixes = cached_completions(CachedNameKind::Prefixes, "");
+            if names.is_empty() && prefixes.is_empty() {
+                return Err(err);
+            }
+            (names, prefixes)
+        }
+    };
+
+    Ok(names
+        .into_iter()
+        .chain(
+            prefixes
+                .into_iter()
+                .map(|prefix| prefix + "_")
+                .filter(|prefix| prefix.starts_with(current) && prefix != current),
+        )
+        .collect())
+}
+
+/// Connect to the server to get the names matching `current`, and the valid name prefixes.
+async fn fetch_names_and_prefixes_from_server(
+    kind: NameKind,
+    current: &str,
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    let server_connection = bootstrap_server_connection_and_drop_privileges(
+        None,
+        None,
+        Verbosity::new(0, 1),
+        Some(Duration::ZERO),
+    )?;
+
+    let tokio_socket = TokioUnixStream::from_std(server_connection)?;
+    let mut server_connection = create_client_to_server_message_stream(tokio_socket);
+
+    wait_for_server_ready(&mut server_connection, DEFAULT_HANDSHAKE_TIMEOUT).await?;
+
+    let message = match kind {
+        NameKind::Database => Request::CompleteDatabaseName(current.to_string()),
+        NameKind::User => Request::CompleteUserName(current.to_string()),
+    };
+
+    if let Err(err) = server_connection.send(message).await {
+        server_connection.close().await.ok();
+        anyhow::bail!(anyhow::Error::from(err).context("Failed to communicate with server"));
+    }
+
+    let names: Vec<String> = match server_connection.next().await {
+        Some(Ok(Response::CompleteDatabaseName(suggestions))) if kind == NameKind::Database => {
+            suggestions
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect()
+        }
+        Some(Ok(Response::CompleteUserName(suggestions))) if kind == NameKind::User => suggestions
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect(),
+        response => return erroneous_server_response(response).map(|()| (vec![], vec![])),
+    };
+
+    server_connection
+        .send(Request::ListValidNamePrefixes)
+        .await?;
+
+    let prefixes = match server_connection.next().await {
+        Some(Ok(Response::ListValidNamePrefixes(prefixes))) => prefixes,
+        response => return erroneous_server_response(response).map(|()| (vec![], vec![])),
+    };
+
+    server_connection.send(Request::Exit).await?;
+
+    Ok((names, prefixes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate_values(current: &str) -> Vec<String> {
+        privilege_candidates(current)
+            .iter()
+            .map(|candidate| candidate.get_value().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_privilege_candidates() {
+        let values = candidate_values("");
+        assert!(values.contains(&"+".to_string()));
+        assert!(values.contains(&"A".to_string()));
+        assert!(values.contains(&"s".to_string()));
+
+        let values = candidate_values("+si");
+        assert!(values.contains(&"+siu".to_string()));
+        assert!(!values.contains(&"+sis".to_string()));
+        assert!(!values.contains(&"+siA".to_string()));
+
+        assert!(candidate_values("-A").is_empty());
+        assert!(candidate_values("sz").is_empty());
+    }
+}
@@ -2,8 +2,10 @@ mod base;
 mod cli;
 mod diff;
 mod editor;
+mod sql;
 
 pub use base::*;
 pub use cli::*;
 pub use diff::*;
 pub use editor::*;
+pub use sql::*;
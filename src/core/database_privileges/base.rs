@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 /// This is the list of fields that are used to fetch the db + user + privileges
 /// from the `db` table in the database. If you need to add or remove privilege
 /// fields, this is a good place to start.
-pub const DATABASE_PRIVILEGE_FIELDS: [&str; 13] = [
+pub const DATABASE_PRIVILEGE_FIELDS: [&str; 21] = [
     "Db",
     "User",
     "select_priv",
@@ -23,8 +23,41 @@ pub const DATABASE_PRIVILEGE_FIELDS: [&str; 13] = [
     "create_tmp_table_priv",
     "lock_tables_priv",
     "references_priv",
+    "create_view_priv",
+    "show_view_priv",
+    "trigger_priv",
+    "event_priv",
+    "execute_priv",
+    "create_routine_priv",
+    "alter_routine_priv",
+    "grant_priv",
 ];
 
+/// The fields of [`DATABASE_PRIVILEGE_FIELDS`] for the privileges that are only
+/// shown and granted when `extended_database_privileges` is enabled in the server config.
+pub const EXTENDED_DATABASE_PRIVILEGE_FIELDS: [&str; 8] = [
+    "create_view_priv",
+    "show_view_priv",
+    "trigger_priv",
+    "event_priv",
+    "execute_priv",
+    "create_routine_priv",
+    "alter_routine_priv",
+    "grant_priv",
+];
+
+/// The fields of [`DATABASE_PRIVILEGE_FIELDS`] to show to the user, which leaves
+/// out the [`EXTENDED_DATABASE_PRIVILEGE_FIELDS`] unless they are enabled.
+#[must_use]
+pub fn database_privilege_fields(extended: bool) -> &'static [&'static str] {
+    if extended {
+        &DATABASE_PRIVILEGE_FIELDS
+    } else {
+        &DATABASE_PRIVILEGE_FIELDS
+            [..DATABASE_PRIVILEGE_FIELDS.len() - EXTENDED_DATABASE_PRIVILEGE_FIELDS.len()]
+    }
+}
+
 // NOTE: ord is needed for BTreeSet to accept the type, but it
 //       doesn't have any natural implementation semantics.
 
@@ -45,6 +78,24 @@ pub struct DatabasePrivilegeRow {
     pub create_tmp_table_priv: bool,
     pub lock_tables_priv: bool,
     pub references_priv: bool,
+    // NOTE: the extended privileges default to false, so that rows saved
+    //       before they were added, e.g. in snapshots, can still be read.
+    #[serde(default)]
+    pub create_view_priv: bool,
+    #[serde(default)]
+    pub show_view_priv: bool,
+    #[serde(default)]
+    pub trigger_priv: bool,
+    #[serde(default)]
+    pub event_priv: bool,
+    #[serde(default)]
+    pub execute_priv: bool,
+    #[serde(default)]
+    pub create_routine_priv: bool,
+    #[serde(default)]
+    pub alter_routine_priv: bool,
+    #[serde(default)]
+    pub grant_priv: bool,
 }
 
 impl DatabasePrivilegeRow {
@@ -63,14 +114,63 @@ impl DatabasePrivilegeRow {
             "create_tmp_table_priv" => Some(self.create_tmp_table_priv),
             "lock_tables_priv" => Some(self.lock_tables_priv),
             "references_priv" => Some(self.references_priv),
+            "create_view_priv" => Some(self.create_view_priv),
+            "show_view_priv" => Some(self.show_view_priv),
+            "trigger_priv" => Some(self.trigger_priv),
+            "event_priv" => Some(self.event_priv),
+            "execute_priv" => Some(self.execute_priv),
+            "create_routine_priv" => Some(self.create_routine_priv),
+            "alter_routine_priv" => Some(self.alter_routine_priv),
+            "grant_priv" => Some(self.grant_priv),
             _ => None,
         }
     }
+
+    /// Gets a mutable reference to a privilege by its name as a &str.
+    pub fn get_privilege_mut_by_name(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "select_priv" => Some(&mut self.select_priv),
+            "insert_priv" => Some(&mut self.insert_priv),
+            "update_priv" => Some(&mut self.update_priv),
+            "delete_priv" => Some(&mut self.delete_priv),
+            "create_priv" => Some(&mut self.create_priv),
+            "drop_priv" => Some(&mut self.drop_priv),
+            "alter_priv" => Some(&mut self.alter_priv),
+            "index_priv" => Some(&mut self.index_priv),
+            "create_tmp_table_priv" => Some(&mut self.create_tmp_table_priv),
+            "lock_tables_priv" => Some(&mut self.lock_tables_priv),
+            "references_priv" => Some(&mut self.references_priv),
+            "create_view_priv" => Some(&mut self.create_view_priv),
+            "show_view_priv" => Some(&mut self.show_view_priv),
+            "trigger_priv" => Some(&mut self.trigger_priv),
+            "event_priv" => Some(&mut self.event_priv),
+            "execute_priv" => Some(&mut self.execute_priv),
+            "create_routine_priv" => Some(&mut self.create_routine_priv),
+            "alter_routine_priv" => Some(&mut self.alter_routine_priv),
+            "grant_priv" => Some(&mut self.grant_priv),
+            _ => None,
+        }
+    }
+
+    /// Whether any of the [`EXTENDED_DATABASE_PRIVILEGE_FIELDS`] are granted.
+    #[must_use]
+    pub fn has_extended_privileges(&self) -> bool {
+        EXTENDED_DATABASE_PRIVILEGE_FIELDS
+            .into_iter()
+            .any(|field| self.get_privilege_by_name(field).unwrap())
+    }
 }
 
+// NOTE: the extended privileges are only listed when they are granted,
+//       so that sites without them do not see a wall of 'N's.
 impl fmt::Display for DatabasePrivilegeRow {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for field in DATABASE_PRIVILEGE_FIELDS.into_iter().skip(2) {
+            if EXTENDED_DATABASE_PRIVILEGE_FIELDS.contains(&field)
+                && !self.get_privilege_by_name(field).unwrap()
+            {
+                continue;
+            }
             if self.get_privilege_by_name(field).unwrap() {
                 f.write_str(db_priv_field_human_readable_name(field).as_str())?;
                 f.write_str(": Y\n")?;
@@ -100,6 +200,14 @@ pub fn db_priv_field_human_readable_name(name: &str) -> String {
         "create_tmp_table_priv" => "Temp".to_owned(),
         "lock_tables_priv" => "Lock".to_owned(),
         "references_priv" => "References".to_owned(),
+        "create_view_priv" => "CreateView".to_owned(),
+        "show_view_priv" => "ShowView".to_owned(),
+        "trigger_priv" => "Trigger".to_owned(),
+        "event_priv" => "Event".to_owned(),
+        "execute_priv" => "Execute".to_owned(),
+        "create_routine_priv" => "CreateRoutine".to_owned(),
+        "alter_routine_priv" => "AlterRoutine".to_owned(),
+        "grant_priv" => "Grant".to_owned(),
         _ => format!("Unknown({name})"),
     }
 }
@@ -120,6 +228,14 @@ pub fn db_priv_field_single_character_name(name: &str) -> &str {
         "create_tmp_table_priv" => "t",
         "lock_tables_priv" => "l",
         "references_priv" => "r",
+        "create_view_priv" => "v",
+        "show_view_priv" => "V",
+        "trigger_priv" => "T",
+        "event_priv" => "e",
+        "execute_priv" => "x",
+        "create_routine_priv" => "p",
+        "alter_routine_priv" => "P",
+        "grant_priv" => "g",
         _ => "?",
     }
 }
@@ -1,15 +1,35 @@
 //! This module contains serialization and deserialization logic for
 //! database privileges related CLI commands.
 
+use std::collections::BTreeMap;
+
 use itertools::Itertools;
 
-use super::diff::{DatabasePrivilegeChange, DatabasePrivilegeRowDiff};
+use super::{
+    base::{DATABASE_PRIVILEGE_FIELDS, DatabasePrivilegeRow, db_priv_field_single_character_name},
+    diff::{DatabasePrivilegeChange, DatabasePrivilegeRowDiff},
+};
 use crate::core::types::{MySQLDatabase, MySQLUser};
 
 const VALID_PRIVILEGE_EDIT_CHARS: &[char] = &[
-    's', 'i', 'u', 'd', 'c', 'D', 'a', 'A', 'I', 't', 'l', 'r', 'A',
+    's', 'i', 'u', 'd', 'c', 'D', 'a', 'A', 'I', 't', 'l', 'r', 'v', 'V', 'T', 'e', 'x', 'p', 'P',
+    'g',
 ];
 
+/// Extra names for sets of privilege characters, configured on the server,
+/// mapping each alias to the privilege characters it stands for, e.g. `rw` to `siud`.
+pub type PrivilegeAliases = BTreeMap<String, String>;
+
+/// Checks that an alias has a name that can be used in a privilege edit,
+/// and stands for a valid set of privilege characters.
+pub fn validate_privilege_alias(alias: &str, privileges: &str) -> anyhow::Result<()> {
+    if alias.is_empty() || !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        anyhow::bail!("Alias names may only contain letters, digits and '_'");
+    }
+    DatabasePrivilegeEdit::parse_template_from_str(privileges)?;
+    Ok(())
+}
+
 /// This enum represents a part of a CLI argument for editing database privileges,
 /// indicating whether privileges are to be added, set, or removed.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -26,7 +46,12 @@ pub struct DatabasePrivilegeEdit {
 }
 
 impl DatabasePrivilegeEdit {
-    pub fn parse_from_str(input: &str) -> anyhow::Result<Self> {
+    /// Parses a `[+-]PRIVILEGES` string.
+    ///
+    /// The privileges are either written as privilege characters, e.g. `siud`, or as a comma
+    /// separated list of privilege aliases and privilege characters, e.g. `rw,t`.
+    /// An alias takes precedence over privilege characters with the same name.
+    pub fn parse_from_str(input: &str, aliases: &PrivilegeAliases) -> anyhow::Result<Self> {
         let (edit_type, privs_str) = if let Some(privs_str) = input.strip_prefix('+') {
             (DatabasePrivilegeEditEntryType::Add, privs_str)
         } else if let Some(privs_str) = input.strip_prefix('-') {
@@ -35,7 +60,11 @@ impl DatabasePrivilegeEdit {
             (DatabasePrivilegeEditEntryType::Set, input)
         };
 
-        let privileges: Vec<char> = privs_str.chars().collect();
+        let privileges: Vec<char> = privs_str
+            .split(',')
+            .flat_map(|part| aliases.get(part).map_or(part, String::as_str).chars())
+            .unique()
+            .collect();
 
         if privileges
             .iter()
@@ -50,8 +79,16 @@ impl DatabasePrivilegeEdit {
                 .iter()
                 .map(|c| format!("'{c}'"))
                 .join(", ");
+            let valid_aliases = if aliases.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "\nValid aliases are: {}",
+                    aliases.keys().map(|alias| format!("'{alias}'")).join(", ")
+                )
+            };
             anyhow::bail!(
-                "Invalid character(s) in privilege edit entry: {invalid_chars}\n\nValid characters are: {valid_characters}",
+                "Invalid character(s) in privilege edit entry: {invalid_chars}\n\nValid characters are: {valid_characters}{valid_aliases}",
             );
         }
 
@@ -60,6 +97,18 @@ impl DatabasePrivilegeEdit {
             privileges,
         })
     }
+
+    /// Parses a privilege template, which uses the same characters as a privilege edit,
+    /// but always describes the full set of privileges, without a `+` or `-` prefix.
+    pub fn parse_template_from_str(input: &str) -> anyhow::Result<Self> {
+        let edit = Self::parse_from_str(input, &PrivilegeAliases::new())?;
+        if edit.type_ != DatabasePrivilegeEditEntryType::Set {
+            anyhow::bail!(
+                "Expected a plain set of privileges, without a leading '+' or '-': {input}"
+            );
+        }
+        Ok(edit)
+    }
 }
 
 impl std::fmt::Display for DatabasePrivilegeEdit {
@@ -101,8 +150,9 @@ impl DatabasePrivilegeEditEntry {
     /// - username is the name of the user to edit privileges for
     /// - privileges is a string of characters representing the privileges to add, set or remove
     /// - the `+` or `-` prefix indicates whether to add or remove the privileges, if omitted the privileges are set directly
-    /// - privileges characters are: siudcDaAItlrA
-    pub fn parse_from_str(arg: &str) -> anyhow::Result<Self> {
+    /// - privileges characters are: siudcDaItlrvVTexpPg, or `A` for all of siudcDaItlr,
+    ///   or the privilege aliases from `aliases`
+    pub fn parse_from_str(arg: &str, aliases: &PrivilegeAliases) -> anyhow::Result<Self> {
         let parts: Vec<&str> = arg.split(':').collect();
         if parts.len() != 3 {
             anyhow::bail!("Invalid privilege edit entry format: {arg}");
@@ -114,7 +164,7 @@ impl DatabasePrivilegeEditEntry {
             anyhow::bail!("Username cannot be empty in privilege edit entry: {arg}");
         }
 
-        let privilege_edit = DatabasePrivilegeEdit::parse_from_str(user_privs)?;
+        let privilege_edit = DatabasePrivilegeEdit::parse_from_str(user_privs, aliases)?;
 
         Ok(DatabasePrivilegeEditEntry {
             database: MySQLDatabase::from(database),
@@ -141,6 +191,14 @@ impl DatabasePrivilegeEditEntry {
                     create_tmp_table_priv: Some(DatabasePrivilegeChange::YesToNo),
                     lock_tables_priv: Some(DatabasePrivilegeChange::YesToNo),
                     references_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    create_view_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    show_view_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    trigger_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    event_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    execute_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    create_routine_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    alter_routine_priv: Some(DatabasePrivilegeChange::YesToNo),
+                    grant_priv: Some(DatabasePrivilegeChange::YesToNo),
                 };
                 for priv_char in &self.privilege_edit.privileges {
                     match priv_char {
@@ -155,6 +213,14 @@ impl DatabasePrivilegeEditEntry {
                         't' => diff.create_tmp_table_priv = Some(DatabasePrivilegeChange::NoToYes),
                         'l' => diff.lock_tables_priv = Some(DatabasePrivilegeChange::NoToYes),
                         'r' => diff.references_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'v' => diff.create_view_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'V' => diff.show_view_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'T' => diff.trigger_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'e' => diff.event_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'x' => diff.execute_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'p' => diff.create_routine_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'P' => diff.alter_routine_priv = Some(DatabasePrivilegeChange::NoToYes),
+                        'g' => diff.grant_priv = Some(DatabasePrivilegeChange::NoToYes),
                         'A' => {
                             diff.select_priv = Some(DatabasePrivilegeChange::NoToYes);
                             diff.insert_priv = Some(DatabasePrivilegeChange::NoToYes);
@@ -187,6 +253,14 @@ impl DatabasePrivilegeEditEntry {
                     create_tmp_table_priv: None,
                     lock_tables_priv: None,
                     references_priv: None,
+                    create_view_priv: None,
+                    show_view_priv: None,
+                    trigger_priv: None,
+                    event_priv: None,
+                    execute_priv: None,
+                    create_routine_priv: None,
+                    alter_routine_priv: None,
+                    grant_priv: None,
                 };
                 let value = match self.privilege_edit.type_ {
                     DatabasePrivilegeEditEntryType::Add => DatabasePrivilegeChange::NoToYes,
@@ -206,6 +280,14 @@ impl DatabasePrivilegeEditEntry {
                         't' => diff.create_tmp_table_priv = Some(value),
                         'l' => diff.lock_tables_priv = Some(value),
                         'r' => diff.references_priv = Some(value),
+                        'v' => diff.create_view_priv = Some(value),
+                        'V' => diff.show_view_priv = Some(value),
+                        'T' => diff.trigger_priv = Some(value),
+                        'e' => diff.event_priv = Some(value),
+                        'x' => diff.execute_priv = Some(value),
+                        'p' => diff.create_routine_priv = Some(value),
+                        'P' => diff.alter_routine_priv = Some(value),
+                        'g' => diff.grant_priv = Some(value),
                         'A' => {
                             diff.select_priv = Some(value);
                             diff.insert_priv = Some(value);
@@ -238,13 +320,56 @@ impl std::fmt::Display for DatabasePrivilegeEditEntry {
     }
 }
 
+/// Renders the granted privileges of a row as a string of the single-character
+/// privilege names used by `edit-privs`, e.g. `siud`.
+///
+/// The resulting string can be passed back to `edit-privs` to set the same privileges.
+#[must_use]
+pub fn format_privileges_as_cli_string(row: &DatabasePrivilegeRow) -> String {
+    DATABASE_PRIVILEGE_FIELDS
+        .into_iter()
+        .skip(2)
+        .filter(|field| row.get_privilege_by_name(field) == Some(true))
+        .map(db_priv_field_single_character_name)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_privileges_as_cli_string() {
+        let row = DatabasePrivilegeRow {
+            db: "db".into(),
+            user: "user".into(),
+            select_priv: true,
+            insert_priv: true,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: true,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: true,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        };
+        assert_eq!(format_privileges_as_cli_string(&row), "siDr");
+    }
+
     #[test]
     fn test_cli_arg_parse_set_db_user_all() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:A");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:A", &PrivilegeAliases::new());
         assert_eq!(
             result.ok(),
             Some(DatabasePrivilegeEditEntry {
@@ -260,7 +385,8 @@ mod tests {
 
     #[test]
     fn test_cli_arg_parse_set_db_user_none() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:", &PrivilegeAliases::new());
         assert_eq!(
             result.ok(),
             Some(DatabasePrivilegeEditEntry {
@@ -276,7 +402,8 @@ mod tests {
 
     #[test]
     fn test_cli_arg_parse_set_db_user_misc() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:siud");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:siud", &PrivilegeAliases::new());
         assert_eq!(
             result.ok(),
             Some(DatabasePrivilegeEditEntry {
@@ -292,25 +419,27 @@ mod tests {
 
     #[test]
     fn test_cli_arg_parse_set_db_user_nonexistent_privilege() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:F");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:F", &PrivilegeAliases::new());
         assert!(result.is_err());
     }
 
     #[test]
     fn test_cli_arg_parse_set_user_empty_string() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("::");
+        let result = DatabasePrivilegeEditEntry::parse_from_str("::", &PrivilegeAliases::new());
         assert!(result.is_err());
     }
 
     #[test]
     fn test_cli_arg_parse_set_db_user_empty_string() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db::");
+        let result = DatabasePrivilegeEditEntry::parse_from_str("db::", &PrivilegeAliases::new());
         assert!(result.is_err());
     }
 
     #[test]
     fn test_cli_arg_parse_add_db_user_misc() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:+siud");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:+siud", &PrivilegeAliases::new());
         assert_eq!(
             result.ok(),
             Some(DatabasePrivilegeEditEntry {
@@ -324,9 +453,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_arg_parse_aliases() {
+        let aliases = PrivilegeAliases::from([
+            ("ro".to_string(), "s".to_string()),
+            ("rw".to_string(), "siud".to_string()),
+        ]);
+
+        let result = DatabasePrivilegeEdit::parse_from_str("+rw,t", &aliases);
+        assert_eq!(
+            result.ok(),
+            Some(DatabasePrivilegeEdit {
+                type_: DatabasePrivilegeEditEntryType::Add,
+                privileges: vec!['s', 'i', 'u', 'd', 't'],
+            })
+        );
+
+        let result = DatabasePrivilegeEdit::parse_from_str("ro,rw", &aliases);
+        assert_eq!(
+            result.ok().map(|edit| edit.privileges),
+            Some(vec!['s', 'i', 'u', 'd'])
+        );
+
+        assert!(DatabasePrivilegeEdit::parse_from_str("rw", &PrivilegeAliases::new()).is_err());
+        assert!(DatabasePrivilegeEdit::parse_from_str("+wo", &aliases).is_err());
+    }
+
+    #[test]
+    fn test_validate_privilege_alias() {
+        assert!(validate_privilege_alias("rw", "siud").is_ok());
+        assert!(validate_privilege_alias("read_only", "s").is_ok());
+        assert!(validate_privilege_alias("", "s").is_err());
+        assert!(validate_privilege_alias("r,w", "siud").is_err());
+        assert!(validate_privilege_alias("rw", "+siud").is_err());
+        assert!(validate_privilege_alias("rw", "F").is_err());
+    }
+
     #[test]
     fn test_cli_arg_parse_remove_db_user_misc() {
-        let result = DatabasePrivilegeEditEntry::parse_from_str("db:user:-siud");
+        let result =
+            DatabasePrivilegeEditEntry::parse_from_str("db:user:-siud", &PrivilegeAliases::new());
         assert_eq!(
             result.ok(),
             Some(DatabasePrivilegeEditEntry {
@@ -1,12 +1,21 @@
 //! This module contains datastructures and logic for comparing database privileges,
 //! generating, validating and reducing diffs between two sets of database privileges.
 
-use super::base::{DatabasePrivilegeRow, db_priv_field_human_readable_name};
-use crate::core::types::{MySQLDatabase, MySQLUser};
+use super::{
+    base::{
+        DatabasePrivilegeRow, EXTENDED_DATABASE_PRIVILEGE_FIELDS, db_priv_field_human_readable_name,
+    },
+    cli::{DatabasePrivilegeEdit, DatabasePrivilegeEditEntry},
+    editor::format_privileges_line_for_editor,
+};
+use crate::core::{
+    style::{Role, paint},
+    types::{MySQLDatabase, MySQLUser},
+};
 use prettytable::Table;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeSet, HashMap, hash_map::Entry},
+    collections::{BTreeMap, BTreeSet, HashMap, hash_map::Entry},
     fmt,
 };
 
@@ -46,6 +55,14 @@ pub struct DatabasePrivilegeRowDiff {
     pub create_tmp_table_priv: Option<DatabasePrivilegeChange>,
     pub lock_tables_priv: Option<DatabasePrivilegeChange>,
     pub references_priv: Option<DatabasePrivilegeChange>,
+    pub create_view_priv: Option<DatabasePrivilegeChange>,
+    pub show_view_priv: Option<DatabasePrivilegeChange>,
+    pub trigger_priv: Option<DatabasePrivilegeChange>,
+    pub event_priv: Option<DatabasePrivilegeChange>,
+    pub execute_priv: Option<DatabasePrivilegeChange>,
+    pub create_routine_priv: Option<DatabasePrivilegeChange>,
+    pub alter_routine_priv: Option<DatabasePrivilegeChange>,
+    pub grant_priv: Option<DatabasePrivilegeChange>,
 }
 
 impl DatabasePrivilegeRowDiff {
@@ -80,6 +97,23 @@ impl DatabasePrivilegeRowDiff {
                 row1.references_priv,
                 row2.references_priv,
             ),
+            create_view_priv: DatabasePrivilegeChange::new(
+                row1.create_view_priv,
+                row2.create_view_priv,
+            ),
+            show_view_priv: DatabasePrivilegeChange::new(row1.show_view_priv, row2.show_view_priv),
+            trigger_priv: DatabasePrivilegeChange::new(row1.trigger_priv, row2.trigger_priv),
+            event_priv: DatabasePrivilegeChange::new(row1.event_priv, row2.event_priv),
+            execute_priv: DatabasePrivilegeChange::new(row1.execute_priv, row2.execute_priv),
+            create_routine_priv: DatabasePrivilegeChange::new(
+                row1.create_routine_priv,
+                row2.create_routine_priv,
+            ),
+            alter_routine_priv: DatabasePrivilegeChange::new(
+                row1.alter_routine_priv,
+                row2.alter_routine_priv,
+            ),
+            grant_priv: DatabasePrivilegeChange::new(row1.grant_priv, row2.grant_priv),
         }
     }
 
@@ -97,6 +131,25 @@ impl DatabasePrivilegeRowDiff {
             && self.create_tmp_table_priv.is_none()
             && self.lock_tables_priv.is_none()
             && self.references_priv.is_none()
+            && self.create_view_priv.is_none()
+            && self.show_view_priv.is_none()
+            && self.trigger_priv.is_none()
+            && self.event_priv.is_none()
+            && self.execute_priv.is_none()
+            && self.create_routine_priv.is_none()
+            && self.alter_routine_priv.is_none()
+            && self.grant_priv.is_none()
+    }
+
+    /// Whether the diff grants any of the [`EXTENDED_DATABASE_PRIVILEGE_FIELDS`].
+    #[must_use]
+    pub fn grants_extended_privileges(&self) -> bool {
+        EXTENDED_DATABASE_PRIVILEGE_FIELDS.into_iter().any(|field| {
+            matches!(
+                self.get_privilege_change_by_name(field),
+                Ok(Some(DatabasePrivilegeChange::NoToYes))
+            )
+        })
     }
 
     /// Retrieves the privilege change for a given privilege name.
@@ -116,6 +169,14 @@ impl DatabasePrivilegeRowDiff {
             "create_tmp_table_priv" => Ok(self.create_tmp_table_priv),
             "lock_tables_priv" => Ok(self.lock_tables_priv),
             "references_priv" => Ok(self.references_priv),
+            "create_view_priv" => Ok(self.create_view_priv),
+            "show_view_priv" => Ok(self.show_view_priv),
+            "trigger_priv" => Ok(self.trigger_priv),
+            "event_priv" => Ok(self.event_priv),
+            "execute_priv" => Ok(self.execute_priv),
+            "create_routine_priv" => Ok(self.create_routine_priv),
+            "alter_routine_priv" => Ok(self.alter_routine_priv),
+            "grant_priv" => Ok(self.grant_priv),
             _ => anyhow::bail!("Unknown privilege name: {privilege_name}"),
         }
     }
@@ -157,6 +218,30 @@ impl DatabasePrivilegeRowDiff {
         if other.references_priv.is_some() {
             self.references_priv = other.references_priv;
         }
+        if other.create_view_priv.is_some() {
+            self.create_view_priv = other.create_view_priv;
+        }
+        if other.show_view_priv.is_some() {
+            self.show_view_priv = other.show_view_priv;
+        }
+        if other.trigger_priv.is_some() {
+            self.trigger_priv = other.trigger_priv;
+        }
+        if other.event_priv.is_some() {
+            self.event_priv = other.event_priv;
+        }
+        if other.execute_priv.is_some() {
+            self.execute_priv = other.execute_priv;
+        }
+        if other.create_routine_priv.is_some() {
+            self.create_routine_priv = other.create_routine_priv;
+        }
+        if other.alter_routine_priv.is_some() {
+            self.alter_routine_priv = other.alter_routine_priv;
+        }
+        if other.grant_priv.is_some() {
+            self.grant_priv = other.grant_priv;
+        }
     }
 
     /// Removes any no-op changes from the diff, based on the original privilege row.
@@ -190,9 +275,20 @@ impl DatabasePrivilegeRowDiff {
         );
         self.lock_tables_priv = new_value(self.lock_tables_priv.as_ref(), from.lock_tables_priv);
         self.references_priv = new_value(self.references_priv.as_ref(), from.references_priv);
+        self.create_view_priv = new_value(self.create_view_priv.as_ref(), from.create_view_priv);
+        self.show_view_priv = new_value(self.show_view_priv.as_ref(), from.show_view_priv);
+        self.trigger_priv = new_value(self.trigger_priv.as_ref(), from.trigger_priv);
+        self.event_priv = new_value(self.event_priv.as_ref(), from.event_priv);
+        self.execute_priv = new_value(self.execute_priv.as_ref(), from.execute_priv);
+        self.create_routine_priv =
+            new_value(self.create_routine_priv.as_ref(), from.create_routine_priv);
+        self.alter_routine_priv =
+            new_value(self.alter_routine_priv.as_ref(), from.alter_routine_priv);
+        self.grant_priv = new_value(self.grant_priv.as_ref(), from.grant_priv);
     }
 
-    fn apply(&self, base: &mut DatabasePrivilegeRow) {
+    /// Applies the changes in the diff to the given row.
+    pub fn apply(&self, base: &mut DatabasePrivilegeRow) {
         fn apply_change(change: Option<&DatabasePrivilegeChange>, target: &mut bool) {
             match change {
                 Some(DatabasePrivilegeChange::YesToNo) => *target = false,
@@ -215,6 +311,20 @@ impl DatabasePrivilegeRowDiff {
         );
         apply_change(self.lock_tables_priv.as_ref(), &mut base.lock_tables_priv);
         apply_change(self.references_priv.as_ref(), &mut base.references_priv);
+        apply_change(self.create_view_priv.as_ref(), &mut base.create_view_priv);
+        apply_change(self.show_view_priv.as_ref(), &mut base.show_view_priv);
+        apply_change(self.trigger_priv.as_ref(), &mut base.trigger_priv);
+        apply_change(self.event_priv.as_ref(), &mut base.event_priv);
+        apply_change(self.execute_priv.as_ref(), &mut base.execute_priv);
+        apply_change(
+            self.create_routine_priv.as_ref(),
+            &mut base.create_routine_priv,
+        );
+        apply_change(
+            self.alter_routine_priv.as_ref(),
+            &mut base.alter_routine_priv,
+        );
+        apply_change(self.grant_priv.as_ref(), &mut base.grant_priv);
     }
 }
 
@@ -228,12 +338,14 @@ impl fmt::Display for DatabasePrivilegeRowDiff {
             if let Some(change) = change {
                 match change {
                     DatabasePrivilegeChange::YesToNo => f.write_fmt(format_args!(
-                        "{}: Y -> N\n",
-                        db_priv_field_human_readable_name(field_name)
+                        "{}: {}\n",
+                        db_priv_field_human_readable_name(field_name),
+                        paint(Role::DiffRemoved, "Y -> N"),
                     )),
                     DatabasePrivilegeChange::NoToYes => f.write_fmt(format_args!(
-                        "{}: N -> Y\n",
-                        db_priv_field_human_readable_name(field_name)
+                        "{}: {}\n",
+                        db_priv_field_human_readable_name(field_name),
+                        paint(Role::DiffAdded, "N -> Y"),
                     )),
                 }
             } else {
@@ -252,6 +364,14 @@ impl fmt::Display for DatabasePrivilegeRowDiff {
         format_change(f, self.create_tmp_table_priv, "create_tmp_table_priv")?;
         format_change(f, self.lock_tables_priv, "lock_tables_priv")?;
         format_change(f, self.references_priv, "references_priv")?;
+        format_change(f, self.create_view_priv, "create_view_priv")?;
+        format_change(f, self.show_view_priv, "show_view_priv")?;
+        format_change(f, self.trigger_priv, "trigger_priv")?;
+        format_change(f, self.event_priv, "event_priv")?;
+        format_change(f, self.execute_priv, "execute_priv")?;
+        format_change(f, self.create_routine_priv, "create_routine_priv")?;
+        format_change(f, self.alter_routine_priv, "alter_routine_priv")?;
+        format_change(f, self.grant_priv, "grant_priv")?;
 
         Ok(())
     }
@@ -287,6 +407,16 @@ impl DatabasePrivilegesDiff {
         }
     }
 
+    /// Whether the diff grants any of the [`EXTENDED_DATABASE_PRIVILEGE_FIELDS`].
+    #[must_use]
+    pub fn grants_extended_privileges(&self) -> bool {
+        match self {
+            DatabasePrivilegesDiff::New(p) => p.has_extended_privileges(),
+            DatabasePrivilegesDiff::Modified(p) => p.grants_extended_privileges(),
+            DatabasePrivilegesDiff::Deleted(_) | DatabasePrivilegesDiff::Noop { .. } => false,
+        }
+    }
+
     /// Merges another [`DatabasePrivilegesDiff`] into this one, combining them in a sequential manner.
     /// For example, if this diff represents a creation and the other represents a modification,
     /// the result will be a creation with the modifications applied.
@@ -401,13 +531,33 @@ pub fn diff_privileges(
     result
 }
 
+/// Finds the privilege template for the longest name prefix that the database belongs to.
+pub fn find_privilege_template<'a>(
+    templates: &'a BTreeMap<String, DatabasePrivilegeEdit>,
+    database: &MySQLDatabase,
+) -> Option<&'a DatabasePrivilegeEdit> {
+    templates
+        .iter()
+        .filter(|(prefix, _)| {
+            database
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('_'))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, template)| template)
+}
+
 /// Converts a set of [`DatabasePrivilegeRowDiff`] into a set of [`DatabasePrivilegesDiff`],
 /// representing either creating new privilege rows, or modifying the existing ones.
 ///
+/// New rows start out with the privileges from the template of the database's name prefix,
+/// if there is one in `templates`, and with no privileges otherwise.
+///
 /// This is particularly useful for processing CLI arguments.
 pub fn create_or_modify_privilege_rows(
     from: DatabasePrivilegeState<'_>,
     to: &BTreeSet<DatabasePrivilegeRowDiff>,
+    templates: &BTreeMap<String, DatabasePrivilegeEdit>,
 ) -> anyhow::Result<BTreeSet<DatabasePrivilegesDiff>> {
     let from_lookup_table: HashMap<(MySQLDatabase, MySQLUser), DatabasePrivilegeRow> = from
         .iter()
@@ -439,7 +589,24 @@ pub fn create_or_modify_privilege_rows(
                 create_tmp_table_priv: false,
                 lock_tables_priv: false,
                 references_priv: false,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
             };
+            if let Some(template) = find_privilege_template(templates, &diff.db) {
+                DatabasePrivilegeEditEntry {
+                    database: diff.db.clone(),
+                    user: diff.user.clone(),
+                    privilege_edit: template.clone(),
+                }
+                .as_database_privileges_diff()?
+                .apply(&mut new_row);
+            }
             diff.apply(&mut new_row);
             result.insert(DatabasePrivilegesDiff::New(new_row));
         }
@@ -512,38 +679,153 @@ pub fn reduce_privilege_diffs(
         .collect::<BTreeSet<DatabasePrivilegesDiff>>())
 }
 
+/// How to display a set of [`DatabasePrivilegesDiff`] before applying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DiffFormat {
+    /// A table with one row per changed user, grouped by database.
+    #[default]
+    Table,
+
+    /// A unified diff of the privilege rows, which can be stored as plain text.
+    Unified,
+}
+
+/// Groups a set of [`DatabasePrivilegesDiff`] by database, sorted by user within each group.
+fn group_diffs_by_database(
+    diffs: &BTreeSet<DatabasePrivilegesDiff>,
+) -> BTreeMap<&MySQLDatabase, Vec<&DatabasePrivilegesDiff>> {
+    let mut groups: BTreeMap<&MySQLDatabase, Vec<&DatabasePrivilegesDiff>> = BTreeMap::new();
+    for diff in diffs {
+        groups
+            .entry(diff.get_database_name())
+            .or_default()
+            .push(diff);
+    }
+    for group in groups.values_mut() {
+        group.sort_by_key(|diff| diff.get_user_name());
+    }
+    groups
+}
+
 /// Renders a set of [`DatabasePrivilegesDiff`] into a human-readable formatted table.
+///
+/// The rows are grouped by database, and the database name is only shown
+/// on the first row of each group.
 #[must_use]
 pub fn display_privilege_diffs(diffs: &BTreeSet<DatabasePrivilegesDiff>) -> String {
     let mut table = Table::new();
     table.set_titles(row!["Database", "User", "Privilege diff",]);
-    for row in diffs {
-        match row {
-            DatabasePrivilegesDiff::New(p) => {
-                table.add_row(row![
-                    p.db,
-                    p.user,
-                    "(Previously unprivileged)\n".to_string() + &p.to_string()
-                ]);
+    for (db, group) in group_diffs_by_database(diffs) {
+        for (i, row) in group.into_iter().enumerate() {
+            let db = if i == 0 {
+                db.to_string()
+            } else {
+                String::new()
+            };
+            match row {
+                DatabasePrivilegesDiff::New(p) => {
+                    table.add_row(row![
+                        db,
+                        p.user,
+                        paint(Role::DiffAdded, "(Previously unprivileged)") + "\n" + &p.to_string()
+                    ]);
+                }
+                DatabasePrivilegesDiff::Modified(p) => {
+                    table.add_row(row![db, p.user, p.to_string(),]);
+                }
+                DatabasePrivilegesDiff::Deleted(p) => {
+                    table.add_row(row![db, p.user, paint(Role::DiffRemoved, "Removed")]);
+                }
+                DatabasePrivilegesDiff::Noop { user, .. } => {
+                    table.add_row(row![db, user, "No changes".to_string()]);
+                }
             }
-            DatabasePrivilegesDiff::Modified(p) => {
-                table.add_row(row![p.db, p.user, p.to_string(),]);
+        }
+    }
+
+    table.to_string()
+}
+
+/// Renders a set of [`DatabasePrivilegesDiff`] as a unified diff of the privilege rows,
+/// in the same format as the privilege editor, with one section per database.
+///
+/// The `from` parameter is the current state of the privileges, used to show
+/// the old version of modified rows, and `fields` are the columns to show.
+#[must_use]
+pub fn display_privilege_diffs_unified(
+    from: DatabasePrivilegeState<'_>,
+    diffs: &BTreeSet<DatabasePrivilegesDiff>,
+    fields: &[&str],
+) -> String {
+    let from_lookup_table: HashMap<(&MySQLDatabase, &MySQLUser), &DatabasePrivilegeRow> =
+        from.iter().map(|p| ((&p.db, &p.user), p)).collect();
+
+    // The old and new version of every changed row, grouped by database.
+    let changed_rows: Vec<(&MySQLDatabase, Vec<_>)> = group_diffs_by_database(diffs)
+        .into_iter()
+        .map(|(db, group)| {
+            let rows = group
+                .into_iter()
+                .map(|diff| {
+                    let old_row = from_lookup_table
+                        .get(&(diff.get_database_name(), diff.get_user_name()))
+                        .map(|p| (*p).clone());
+                    let new_row = match diff {
+                        DatabasePrivilegesDiff::New(p) => Some(p.clone()),
+                        DatabasePrivilegesDiff::Modified(p) => old_row.clone().map(|mut row| {
+                            p.apply(&mut row);
+                            row
+                        }),
+                        DatabasePrivilegesDiff::Deleted(_) => None,
+                        DatabasePrivilegesDiff::Noop { .. } => old_row.clone(),
+                    };
+                    (old_row, new_row)
+                })
+                .collect();
+            (db, rows)
+        })
+        .collect();
+
+    let all_rows = || {
+        changed_rows
+            .iter()
+            .flat_map(|(_, rows)| rows)
+            .flat_map(|(old, new)| old.iter().chain(new))
+    };
+    let longest_database_name = all_rows().map(|p| p.db.len()).max().unwrap_or(0);
+    let longest_username = all_rows().map(|p| p.user.len()).max().unwrap_or(0);
+    let format_line = |p: &DatabasePrivilegeRow| {
+        format_privileges_line_for_editor(p, fields, longest_database_name, longest_username)
+    };
+
+    let mut lines = Vec::new();
+    for (db, rows) in &changed_rows {
+        lines.push(format!("--- a/{db}"));
+        lines.push(format!("+++ b/{db}"));
+        for (old_row, new_row) in rows {
+            let old_line = old_row.as_ref().map(format_line);
+            let new_line = new_row.as_ref().map(format_line);
+
+            if old_line == new_line {
+                lines.extend(old_line.map(|line| format!(" {line}")));
+                continue;
             }
-            DatabasePrivilegesDiff::Deleted(p) => {
-                table.add_row(row![p.db, p.user, "Removed".to_string()]);
+            if let Some(line) = old_line {
+                lines.push(paint(Role::DiffRemoved, &format!("-{line}")));
             }
-            DatabasePrivilegesDiff::Noop { db, user } => {
-                table.add_row(row![db, user, "No changes".to_string()]);
+            if let Some(line) = new_line {
+                lines.push(paint(Role::DiffAdded, &format!("+{line}")));
             }
         }
     }
 
-    table.to_string()
+    lines.join("\n")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::database_privileges::base::database_privilege_fields;
 
     #[test]
     fn test_database_privilege_change_creation() {
@@ -577,6 +859,14 @@ mod tests {
             create_tmp_table_priv: false,
             lock_tables_priv: false,
             references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
         };
         let row2 = DatabasePrivilegeRow {
             db: "db".into(),
@@ -594,6 +884,14 @@ mod tests {
             create_tmp_table_priv: false,
             lock_tables_priv: false,
             references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
         };
 
         let diff = DatabasePrivilegeRowDiff::from_rows(&row1, &row2);
@@ -632,13 +930,10 @@ mod tests {
     }
 
     // TODO: test in isolation:
-    // DatabasePrivilegeRowDiff::mappend
-    // DatabasePrivilegeRowDiff::remove_noops
-    // DatabasePrivilegeRowDiff::apply
-    //
     // DatabasePrivilegesDiff::mappend
     //
-    // reduce_privilege_diffs
+    // See the `proptests` module below for DatabasePrivilegeRowDiff::{mappend,remove_noops,apply}
+    // and reduce_privilege_diffs.
 
     #[test]
     fn test_diff_privileges() {
@@ -656,6 +951,14 @@ mod tests {
             create_tmp_table_priv: true,
             lock_tables_priv: true,
             references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
         };
 
         let mut row_to_be_deleted = row_to_be_modified.to_owned();
@@ -691,4 +994,368 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_create_or_modify_privilege_rows_with_template() {
+        let templates = BTreeMap::from([
+            (
+                "web".to_string(),
+                DatabasePrivilegeEdit::parse_template_from_str("s").unwrap(),
+            ),
+            (
+                "webgroup".to_string(),
+                DatabasePrivilegeEdit::parse_template_from_str("siud").unwrap(),
+            ),
+        ]);
+
+        let to = BTreeSet::from([
+            DatabasePrivilegeRowDiff {
+                db: "webgroup_db".into(),
+                user: "webgroup_user".into(),
+                create_priv: Some(DatabasePrivilegeChange::NoToYes),
+                delete_priv: Some(DatabasePrivilegeChange::YesToNo),
+                ..Default::default()
+            },
+            DatabasePrivilegeRowDiff {
+                db: "other_db".into(),
+                user: "other_user".into(),
+                create_priv: Some(DatabasePrivilegeChange::NoToYes),
+                ..Default::default()
+            },
+        ]);
+
+        let diffs = create_or_modify_privilege_rows(&[], &to, &templates).unwrap();
+
+        let new_rows: Vec<_> = diffs
+            .into_iter()
+            .map(|diff| match diff {
+                DatabasePrivilegesDiff::New(row) => row,
+                other => panic!("Expected a new row, got {other:?}"),
+            })
+            .collect();
+
+        assert_eq!(new_rows.len(), 2);
+        for row in new_rows {
+            let expected = if row.db.as_str() == "webgroup_db" {
+                (true, true, true, false, true)
+            } else {
+                (false, false, false, false, true)
+            };
+            assert_eq!(
+                (
+                    row.select_priv,
+                    row.insert_priv,
+                    row.update_priv,
+                    row.delete_priv,
+                    row.create_priv,
+                ),
+                expected,
+                "unexpected privileges for {}",
+                row.db,
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_privilege_diffs_unified() {
+        let row = |db: &str, user: &str, select_priv: bool| DatabasePrivilegeRow {
+            db: db.into(),
+            user: user.into(),
+            select_priv,
+            insert_priv: false,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        };
+
+        let from = vec![row("db1", "user1", false), row("db2", "user1", true)];
+        let to = vec![row("db1", "user1", true), row("db1", "user2", true)];
+
+        let diffs = diff_privileges(&from, &to);
+        let lines =
+            display_privilege_diffs_unified(&from, &diffs, database_privilege_fields(false));
+
+        assert_eq!(
+            lines.lines().collect::<Vec<_>>(),
+            vec![
+                "--- a/db1",
+                "+++ b/db1",
+                "-db1 user1 N      N      N      N      N      N    N     N     N    N    N",
+                "+db1 user1 Y      N      N      N      N      N    N     N     N    N    N",
+                "+db1 user2 Y      N      N      N      N      N    N     N     N    N    N",
+                "--- a/db2",
+                "+++ b/db2",
+                "-db2 user1 Y      N      N      N      N      N    N     N     N    N    N",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grants_extended_privileges() {
+        let mut from = DatabasePrivilegeRow {
+            db: "db".into(),
+            user: "user".into(),
+            select_priv: true,
+            insert_priv: false,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: true,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        };
+        assert!(DatabasePrivilegesDiff::New(from.clone()).grants_extended_privileges());
+        assert!(!DatabasePrivilegesDiff::Deleted(from.clone()).grants_extended_privileges());
+
+        // Revoking an extended privilege is not granting one.
+        let mut to = from.clone();
+        to.execute_priv = false;
+        to.insert_priv = true;
+        let diff = DatabasePrivilegeRowDiff::from_rows(&from, &to);
+        assert!(!diff.grants_extended_privileges());
+
+        from.execute_priv = false;
+        to.grant_priv = true;
+        let diff = DatabasePrivilegeRowDiff::from_rows(&from, &to);
+        assert!(DatabasePrivilegesDiff::Modified(diff).grants_extended_privileges());
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use std::collections::BTreeMap;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn row_from_privileges(db: &str, user: &str, privs: [bool; 19]) -> DatabasePrivilegeRow {
+        DatabasePrivilegeRow {
+            db: db.into(),
+            user: user.into(),
+            select_priv: privs[0],
+            insert_priv: privs[1],
+            update_priv: privs[2],
+            delete_priv: privs[3],
+            create_priv: privs[4],
+            drop_priv: privs[5],
+            alter_priv: privs[6],
+            index_priv: privs[7],
+            create_tmp_table_priv: privs[8],
+            lock_tables_priv: privs[9],
+            references_priv: privs[10],
+            create_view_priv: privs[11],
+            show_view_priv: privs[12],
+            trigger_priv: privs[13],
+            event_priv: privs[14],
+            execute_priv: privs[15],
+            create_routine_priv: privs[16],
+            alter_routine_priv: privs[17],
+            grant_priv: privs[18],
+        }
+    }
+
+    fn row_diff_from_changes(
+        db: &MySQLDatabase,
+        user: &MySQLUser,
+        changes: [Option<DatabasePrivilegeChange>; 19],
+    ) -> DatabasePrivilegeRowDiff {
+        DatabasePrivilegeRowDiff {
+            db: db.clone(),
+            user: user.clone(),
+            select_priv: changes[0],
+            insert_priv: changes[1],
+            update_priv: changes[2],
+            delete_priv: changes[3],
+            create_priv: changes[4],
+            drop_priv: changes[5],
+            alter_priv: changes[6],
+            index_priv: changes[7],
+            create_tmp_table_priv: changes[8],
+            lock_tables_priv: changes[9],
+            references_priv: changes[10],
+            create_view_priv: changes[11],
+            show_view_priv: changes[12],
+            trigger_priv: changes[13],
+            event_priv: changes[14],
+            execute_priv: changes[15],
+            create_routine_priv: changes[16],
+            alter_routine_priv: changes[17],
+            grant_priv: changes[18],
+        }
+    }
+
+    /// A privilege row for one of a few databases and users, so that states often overlap.
+    fn arb_row() -> impl Strategy<Value = DatabasePrivilegeRow> {
+        (0..3usize, 0..3usize, prop::array::uniform19(any::<bool>())).prop_map(
+            |(db, user, privs)| {
+                row_from_privileges(&format!("db{db}"), &format!("user{user}"), privs)
+            },
+        )
+    }
+
+    /// A set of privilege rows, with at most one row per database and user.
+    fn arb_state() -> impl Strategy<Value = Vec<DatabasePrivilegeRow>> {
+        prop::collection::vec(arb_row(), 0..10).prop_map(|rows| {
+            rows.into_iter()
+                .map(|row| ((row.db.clone(), row.user.clone()), row))
+                .collect::<BTreeMap<_, _>>()
+                .into_values()
+                .collect()
+        })
+    }
+
+    fn arb_changes() -> impl Strategy<Value = [Option<DatabasePrivilegeChange>; 19]> {
+        prop::array::uniform19(prop::option::of(prop_oneof![
+            Just(DatabasePrivilegeChange::YesToNo),
+            Just(DatabasePrivilegeChange::NoToYes),
+        ]))
+    }
+
+    /// A state, together with arbitrary (possibly no-op) modifications of its rows.
+    fn arb_state_with_modifications()
+    -> impl Strategy<Value = (Vec<DatabasePrivilegeRow>, BTreeSet<DatabasePrivilegesDiff>)> {
+        (arb_state(), prop::collection::vec(arb_changes(), 10)).prop_map(|(state, changes)| {
+            let diffs = state
+                .iter()
+                .zip(changes)
+                .map(|(row, changes)| {
+                    DatabasePrivilegesDiff::Modified(row_diff_from_changes(
+                        &row.db, &row.user, changes,
+                    ))
+                })
+                .collect();
+            (state, diffs)
+        })
+    }
+
+    /// Apply the diffs to the state, the same way the server applies them to the database.
+    fn apply_diffs(
+        from: &[DatabasePrivilegeRow],
+        diffs: &BTreeSet<DatabasePrivilegesDiff>,
+    ) -> BTreeSet<DatabasePrivilegeRow> {
+        let mut state: BTreeMap<_, _> = from
+            .iter()
+            .map(|row| ((row.db.clone(), row.user.clone()), row.clone()))
+            .collect();
+
+        for diff in diffs {
+            let key = (
+                diff.get_database_name().clone(),
+                diff.get_user_name().clone(),
+            );
+            match diff {
+                DatabasePrivilegesDiff::New(row) => {
+                    state.insert(key, row.clone());
+                }
+                DatabasePrivilegesDiff::Modified(row_diff) => {
+                    if let Some(row) = state.get_mut(&key) {
+                        row_diff.apply(row);
+                    }
+                }
+                DatabasePrivilegesDiff::Deleted(_) => {
+                    state.remove(&key);
+                }
+                DatabasePrivilegesDiff::Noop { .. } => {}
+            }
+        }
+
+        state.into_values().collect()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_apply_diff_yields_target(from in arb_state(), to in arb_state()) {
+            let diffs = diff_privileges(&from, &to);
+            prop_assert_eq!(
+                apply_diffs(&from, &diffs),
+                to.into_iter().collect::<BTreeSet<_>>()
+            );
+        }
+
+        #[test]
+        fn prop_diff_is_already_reduced(from in arb_state(), to in arb_state()) {
+            let diffs = diff_privileges(&from, &to);
+            prop_assert_eq!(reduce_privilege_diffs(&from, diffs.clone()).unwrap(), diffs);
+        }
+
+        #[test]
+        fn prop_reduce_is_idempotent((from, diffs) in arb_state_with_modifications()) {
+            let reduced = reduce_privilege_diffs(&from, diffs).unwrap();
+            prop_assert_eq!(reduce_privilege_diffs(&from, reduced.clone()).unwrap(), reduced);
+        }
+
+        #[test]
+        fn prop_reduce_preserves_result((from, diffs) in arb_state_with_modifications()) {
+            let reduced = reduce_privilege_diffs(&from, diffs.clone()).unwrap();
+            prop_assert_eq!(apply_diffs(&from, &reduced), apply_diffs(&from, &diffs));
+            let no_empty_modifications = reduced.iter().all(|diff| match diff {
+                DatabasePrivilegesDiff::Modified(row_diff) => !row_diff.is_empty(),
+                _ => true,
+            });
+            prop_assert!(no_empty_modifications);
+        }
+
+        #[test]
+        fn prop_row_diff_mappend_is_sequential_apply(
+            row in arb_row(),
+            first in arb_changes(),
+            second in arb_changes(),
+        ) {
+            let first = row_diff_from_changes(&row.db, &row.user, first);
+            let second = row_diff_from_changes(&row.db, &row.user, second);
+
+            let mut sequential = row.clone();
+            first.apply(&mut sequential);
+            second.apply(&mut sequential);
+
+            let mut combined = first.clone();
+            combined.mappend(&second);
+            let mut merged = row.clone();
+            combined.apply(&mut merged);
+
+            prop_assert_eq!(merged, sequential);
+        }
+
+        #[test]
+        fn prop_row_diff_remove_noops(row in arb_row(), changes in arb_changes()) {
+            let diff = row_diff_from_changes(&row.db, &row.user, changes);
+            let mut reduced = diff.clone();
+            reduced.remove_noops(&row);
+
+            let mut expected = row.clone();
+            diff.apply(&mut expected);
+            let mut actual = row.clone();
+            reduced.apply(&mut actual);
+            prop_assert_eq!(&actual, &expected);
+
+            // NOTE: every remaining change should actually change something
+            prop_assert_eq!(DatabasePrivilegeRowDiff::from_rows(&row, &actual), reduced);
+        }
+    }
 }
@@ -6,22 +6,24 @@ use super::base::{
 };
 use crate::core::{
     common::{rev_yn, yn},
-    types::MySQLDatabase,
+    types::{MySQLDatabase, MySQLUser},
 };
 use anyhow::{Context, anyhow};
 use itertools::Itertools;
-use std::cmp::max;
+use std::{cmp::max, collections::HashSet};
 
-/// Generates a single row of the privileges table for the editor.
+/// Generates a single row of the privileges table for the editor,
+/// with a column for each of `fields`.
 #[must_use]
 pub fn format_privileges_line_for_editor(
     privs: &DatabasePrivilegeRow,
+    fields: &[&str],
     database_name_len: usize,
     username_len: usize,
 ) -> String {
-    DATABASE_PRIVILEGE_FIELDS
-        .into_iter()
-        .map(|field| match field {
+    fields
+        .iter()
+        .map(|&field| match field {
             "Db" => format!("{:width$}", privs.db, width = database_name_len),
             "User" => format!("{:width$}", privs.user, width = username_len),
             privilege => format!(
@@ -45,15 +47,70 @@ const EDITOR_COMMENT: &str = r"
 # Lines starting with '#' are comments and will be ignored.
 ";
 
-/// Generates the content for the privilege editor.
+const SUGGESTIONS_COMMENT: &str = r"
+# The users below do not have any privileges on these databases yet.
+# To grant some, uncomment the line and change the privileges you want to 'Y'.";
+
+/// Find every combination of the given databases and users that does not
+/// have a row in the privilege data yet, with all privileges set to `N`.
+fn suggested_privilege_rows(
+    privilege_data: &[DatabasePrivilegeRow],
+    databases: &[MySQLDatabase],
+    users: &[MySQLUser],
+) -> Vec<DatabasePrivilegeRow> {
+    let existing_rows: HashSet<(&MySQLDatabase, &MySQLUser)> = privilege_data
+        .iter()
+        .map(|row| (&row.db, &row.user))
+        .collect();
+
+    databases
+        .iter()
+        .cartesian_product(users)
+        .filter(|row| !existing_rows.contains(row))
+        .map(|(db, user)| DatabasePrivilegeRow {
+            db: db.clone(),
+            user: user.clone(),
+            select_priv: false,
+            insert_priv: false,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        })
+        .collect()
+}
+
+/// Generates the content for the privilege editor, with a column for each of `fields`.
 ///
 /// The unix user is used in case there are no privileges to edit,
 /// so that the user can see an example line based on their username.
+///
+/// Every combination of `databases` and `users` without a row in the privilege
+/// data is added as a commented out line, so that new privileges can be granted
+/// by uncommenting it.
 pub fn generate_editor_content_from_privilege_data(
     privilege_data: &[DatabasePrivilegeRow],
+    fields: &[&str],
     unix_user: &str,
     database_name: Option<&MySQLDatabase>,
+    databases: &[MySQLDatabase],
+    users: &[MySQLUser],
 ) -> String {
+    let suggestions = suggested_privilege_rows(privilege_data, databases, users);
+
     let example_user = format!("{unix_user}_user");
     let example_db = database_name
         .unwrap_or(&format!("{unix_user}_db").into())
@@ -67,6 +124,7 @@ pub fn generate_editor_content_from_privilege_data(
     let longest_username = max(
         privilege_data
             .iter()
+            .chain(&suggestions)
             .map(|p| p.user.len())
             .max()
             .unwrap_or(example_user.len()),
@@ -76,15 +134,16 @@ pub fn generate_editor_content_from_privilege_data(
     let longest_database_name = max(
         privilege_data
             .iter()
+            .chain(&suggestions)
             .map(|p| p.db.len())
             .max()
             .unwrap_or(example_db.len()),
         "Database".len(),
     );
 
-    let mut header: Vec<_> = DATABASE_PRIVILEGE_FIELDS
-        .into_iter()
-        .map(db_priv_field_human_readable_name)
+    let mut header: Vec<_> = fields
+        .iter()
+        .map(|field| db_priv_field_human_readable_name(field))
         .collect();
 
     // Pad the first two columns with spaces to align the privileges.
@@ -106,16 +165,25 @@ pub fn generate_editor_content_from_privilege_data(
             create_tmp_table_priv: false,
             lock_tables_priv: false,
             references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
         },
+        fields,
         longest_database_name,
         longest_username,
     );
 
-    format!(
+    let mut content = format!(
         "{}\n{}\n{}",
         EDITOR_COMMENT,
         header.join(" "),
-        if privilege_data.is_empty() {
+        if privilege_data.is_empty() && suggestions.is_empty() {
             format!("# {example_line}")
         } else {
             privilege_data
@@ -123,13 +191,30 @@ pub fn generate_editor_content_from_privilege_data(
                 .map(|privs| {
                     format_privileges_line_for_editor(
                         privs,
+                        fields,
                         longest_database_name,
                         longest_username,
                     )
                 })
                 .join("\n")
         }
-    )
+    );
+
+    if !suggestions.is_empty() {
+        content.push('\n');
+        content.push_str(SUGGESTIONS_COMMENT);
+        for privs in &suggestions {
+            content.push_str("\n# ");
+            content.push_str(&format_privileges_line_for_editor(
+                privs,
+                fields,
+                longest_database_name,
+                longest_username,
+            ));
+        }
+    }
+
+    content
 }
 
 #[derive(Debug)]
@@ -152,15 +237,17 @@ fn parse_privilege_cell_from_editor(yn: &str, name: &str) -> anyhow::Result<bool
 }
 
 #[inline]
-fn editor_row_is_header(row: &str) -> bool {
+fn editor_row_is_header(row: &str, fields: &[&str]) -> bool {
     row.split_ascii_whitespace()
-        .zip(DATABASE_PRIVILEGE_FIELDS.iter())
+        .zip(fields.iter())
         .map(|(field, priv_name)| (field, db_priv_field_human_readable_name(priv_name)))
         .all(|(field, header_field)| field == header_field)
 }
 
-/// Parse a single row of the privileges table from the editor.
-fn parse_privilege_row_from_editor(row: &str) -> PrivilegeRowParseResult {
+/// Parse a single row of the privileges table from the editor, with a column for each of `fields`.
+///
+/// The privileges that are not in `fields` are not granted in the resulting row.
+fn parse_privilege_row_from_editor(row: &str, fields: &[&str]) -> PrivilegeRowParseResult {
     if row.starts_with('#') || row.starts_with("//") {
         return PrivilegeRowParseResult::Comment;
     }
@@ -172,106 +259,57 @@ fn parse_privilege_row_from_editor(row: &str) -> PrivilegeRowParseResult {
     let parts: Vec<&str> = row.trim().split_ascii_whitespace().collect();
 
     match parts.len() {
-        n if (n < DATABASE_PRIVILEGE_FIELDS.len()) => {
+        n if (n < fields.len()) => {
             return PrivilegeRowParseResult::TooFewFields(n);
         }
-        n if (n > DATABASE_PRIVILEGE_FIELDS.len()) => {
+        n if (n > fields.len()) => {
             return PrivilegeRowParseResult::TooManyFields(n);
         }
         _ => {}
     }
 
-    if editor_row_is_header(row) {
+    if editor_row_is_header(row, fields) {
         return PrivilegeRowParseResult::Header;
     }
 
-    let row = DatabasePrivilegeRow {
+    let mut row = DatabasePrivilegeRow {
         db: (*parts.first().unwrap()).into(),
         user: (*parts.get(1).unwrap()).into(),
-        select_priv: match parse_privilege_cell_from_editor(
-            parts.get(2).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[2],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        insert_priv: match parse_privilege_cell_from_editor(
-            parts.get(3).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[3],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        update_priv: match parse_privilege_cell_from_editor(
-            parts.get(4).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[4],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        delete_priv: match parse_privilege_cell_from_editor(
-            parts.get(5).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[5],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        create_priv: match parse_privilege_cell_from_editor(
-            parts.get(6).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[6],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        drop_priv: match parse_privilege_cell_from_editor(
-            parts.get(7).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[7],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        alter_priv: match parse_privilege_cell_from_editor(
-            parts.get(8).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[8],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        index_priv: match parse_privilege_cell_from_editor(
-            parts.get(9).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[9],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        create_tmp_table_priv: match parse_privilege_cell_from_editor(
-            parts.get(10).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[10],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        lock_tables_priv: match parse_privilege_cell_from_editor(
-            parts.get(11).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[11],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
-        references_priv: match parse_privilege_cell_from_editor(
-            parts.get(12).unwrap(),
-            DATABASE_PRIVILEGE_FIELDS[12],
-        ) {
-            Ok(p) => p,
-            Err(e) => return PrivilegeRowParseResult::ParserError(e),
-        },
+        select_priv: false,
+        insert_priv: false,
+        update_priv: false,
+        delete_priv: false,
+        create_priv: false,
+        drop_priv: false,
+        alter_priv: false,
+        index_priv: false,
+        create_tmp_table_priv: false,
+        lock_tables_priv: false,
+        references_priv: false,
+        create_view_priv: false,
+        show_view_priv: false,
+        trigger_priv: false,
+        event_priv: false,
+        execute_priv: false,
+        create_routine_priv: false,
+        alter_routine_priv: false,
+        grant_priv: false,
     };
 
+    for (cell, field) in parts.iter().zip(fields).skip(2) {
+        match parse_privilege_cell_from_editor(cell, field) {
+            // SAFETY: unwrap is safe here because the field names are static
+            Ok(p) => *row.get_privilege_mut_by_name(field).unwrap() = p,
+            Err(e) => return PrivilegeRowParseResult::ParserError(e),
+        }
+    }
+
     PrivilegeRowParseResult::PrivilegeRow(row)
 }
 
 pub fn parse_privilege_data_from_editor_content(
     content: &str,
+    fields: &[&str],
 ) -> anyhow::Result<Vec<DatabasePrivilegeRow>> {
     content
         .trim()
@@ -279,9 +317,9 @@ pub fn parse_privilege_data_from_editor_content(
         .map(str::trim)
         .enumerate()
         .map(|(i, line)| {
-            let mut header: Vec<_> = DATABASE_PRIVILEGE_FIELDS
-                .into_iter()
-                .map(db_priv_field_human_readable_name)
+            let mut header: Vec<_> = fields
+                .iter()
+                .map(|field| db_priv_field_human_readable_name(field))
                 .collect();
 
             let splitline = line.split_ascii_whitespace().collect::<Vec<&str>>();
@@ -294,7 +332,7 @@ pub fn parse_privilege_data_from_editor_content(
 
             let header: String = header.join(" ");
 
-            match parse_privilege_row_from_editor(line) {
+            match parse_privilege_row_from_editor(line, fields) {
                 PrivilegeRowParseResult::PrivilegeRow(row) => Ok(Some(row)),
                 PrivilegeRowParseResult::ParserError(e) => Err(anyhow!(
                     "Could not parse privilege row from line {i}:\n  {header}\n  {line}\n  {e}",
@@ -302,11 +340,11 @@ pub fn parse_privilege_data_from_editor_content(
 
                 PrivilegeRowParseResult::TooFewFields(n) => Err(anyhow!(
                     "Too few fields in line {i}:\n  {header}\n  {line}\n  Expected to find {} fields, found {n}",
-                    DATABASE_PRIVILEGE_FIELDS.len(),
+                    fields.len(),
                 )),
                 PrivilegeRowParseResult::TooManyFields(n) => Err(anyhow!(
                     "Too many fields in line {i}:\n  {header}\n  {line}\n  Expected to find {} fields, found {n}",
-                    DATABASE_PRIVILEGE_FIELDS.len(),
+                    fields.len(),
                 )),
                 PrivilegeRowParseResult::Header => Ok(None),
                 PrivilegeRowParseResult::Comment => Ok(None),
@@ -317,9 +355,78 @@ pub fn parse_privilege_data_from_editor_content(
         .collect::<anyhow::Result<Vec<DatabasePrivilegeRow>>>()
 }
 
+/// Copies the privileges that are not in `fields` from the existing rows into the rows
+/// parsed from the editor, so that privileges which were not shown in the editor are kept.
+pub fn keep_hidden_privileges(
+    rows: &mut [DatabasePrivilegeRow],
+    existing_rows: &[DatabasePrivilegeRow],
+    fields: &[&str],
+) {
+    let hidden_fields: Vec<&str> = DATABASE_PRIVILEGE_FIELDS
+        .into_iter()
+        .skip(2)
+        .filter(|field| !fields.contains(field))
+        .collect();
+
+    for row in rows {
+        let Some(existing_row) = existing_rows
+            .iter()
+            .find(|existing| existing.db == row.db && existing.user == row.user)
+        else {
+            continue;
+        };
+        for field in &hidden_fields {
+            // SAFETY: unwrap is safe here because the field names are static
+            *row.get_privilege_mut_by_name(field).unwrap() =
+                existing_row.get_privilege_by_name(field).unwrap();
+        }
+    }
+}
+
+/// The prefix of the comments added by [`annotate_editor_content_with_errors`].
+const ERROR_COMMENT_PREFIX: &str = "# ERROR: ";
+
+/// Describe what is wrong with a single line of the editor content,
+/// or `None` if it can be parsed.
+fn editor_line_error(line: &str, fields: &[&str]) -> Option<String> {
+    match parse_privilege_row_from_editor(line.trim(), fields) {
+        PrivilegeRowParseResult::ParserError(e) => Some(format!("{e:#}")),
+        PrivilegeRowParseResult::TooFewFields(n) => Some(format!(
+            "Too few fields, expected to find {} fields, found {n}",
+            fields.len(),
+        )),
+        PrivilegeRowParseResult::TooManyFields(n) => Some(format!(
+            "Too many fields, expected to find {} fields, found {n}",
+            fields.len(),
+        )),
+        PrivilegeRowParseResult::PrivilegeRow(_)
+        | PrivilegeRowParseResult::Header
+        | PrivilegeRowParseResult::Comment
+        | PrivilegeRowParseResult::Empty => None,
+    }
+}
+
+/// Add an `# ERROR:` comment below every line of the editor content that
+/// could not be parsed, so that the content can be fixed in the editor.
+///
+/// The error comments from an earlier call are removed first.
+#[must_use]
+pub fn annotate_editor_content_with_errors(content: &str, fields: &[&str]) -> String {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(ERROR_COMMENT_PREFIX))
+        .flat_map(|line| {
+            let error =
+                editor_line_error(line, fields).map(|e| format!("{ERROR_COMMENT_PREFIX}{e}"));
+            std::iter::once(line.to_string()).chain(error)
+        })
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::database_privileges::base::database_privilege_fields;
 
     use pretty_assertions::assert_eq;
 
@@ -340,6 +447,14 @@ mod tests {
                 create_tmp_table_priv: true,
                 lock_tables_priv: false,
                 references_priv: true,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
             },
             DatabasePrivilegeRow {
                 db: "test_abcdefghijlkmno".into(),
@@ -355,10 +470,25 @@ mod tests {
                 create_tmp_table_priv: true,
                 lock_tables_priv: false,
                 references_priv: true,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
             },
         ];
 
-        let content = generate_editor_content_from_privilege_data(&permissions, "test", None);
+        let content = generate_editor_content_from_privilege_data(
+            &permissions,
+            database_privilege_fields(false),
+            "test",
+            None,
+            &[],
+            &[],
+        );
 
         let expected_lines = vec![
             "",
@@ -396,6 +526,14 @@ mod tests {
                 create_tmp_table_priv: true,
                 lock_tables_priv: true,
                 references_priv: true,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
             },
             DatabasePrivilegeRow {
                 db: "db".into(),
@@ -411,13 +549,188 @@ mod tests {
                 create_tmp_table_priv: false,
                 lock_tables_priv: false,
                 references_priv: false,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
             },
         ];
 
-        let content = generate_editor_content_from_privilege_data(&permissions, "user", None);
+        let content = generate_editor_content_from_privilege_data(
+            &permissions,
+            database_privilege_fields(false),
+            "user",
+            None,
+            &[],
+            &[],
+        );
+
+        let parsed_permissions =
+            parse_privilege_data_from_editor_content(&content, database_privilege_fields(false))
+                .unwrap();
+
+        assert_eq!(permissions, parsed_permissions);
+    }
 
-        let parsed_permissions = parse_privilege_data_from_editor_content(&content).unwrap();
+    #[test]
+    fn test_generate_editor_content_with_suggestions() {
+        let permissions = vec![DatabasePrivilegeRow {
+            db: "test_db".into(),
+            user: "test_user".into(),
+            select_priv: true,
+            insert_priv: true,
+            update_priv: true,
+            delete_priv: true,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        }];
+
+        let content = generate_editor_content_from_privilege_data(
+            &permissions,
+            database_privilege_fields(false),
+            "test",
+            None,
+            &["test_db".into(), "test_other_db".into()],
+            &["test_user".into()],
+        );
+
+        let generated_lines: Vec<&str> = content.lines().skip(8).collect();
+
+        assert_eq!(
+            generated_lines,
+            vec![
+                "Database      User      Select Insert Update Delete Create Drop Alter Index Temp Lock References",
+                "test_db       test_user Y      Y      Y      Y      N      N    N     N     N    N    N",
+                "",
+                "# The users below do not have any privileges on these databases yet.",
+                "# To grant some, uncomment the line and change the privileges you want to 'Y'.",
+                "# test_other_db test_user N      N      N      N      N      N    N     N     N    N    N",
+            ]
+        );
+
+        let parsed_permissions =
+            parse_privilege_data_from_editor_content(&content, database_privilege_fields(false))
+                .unwrap();
 
         assert_eq!(permissions, parsed_permissions);
     }
+
+    #[test]
+    fn test_annotate_editor_content_with_errors() {
+        let content = indoc::indoc! {"
+            # A comment
+            Database User Select Insert Update Delete Create Drop Alter Index Temp Lock References
+            db       user Y      Y      Y      Y      N      N    N     N     N    N    N
+            db       user2 Y Y Y
+            db       user3 Y      Y      Y      Y      X      N    N     N     N    N    N"};
+
+        let annotated =
+            annotate_editor_content_with_errors(content, database_privilege_fields(false));
+        let annotated_lines: Vec<&str> = annotated.lines().collect();
+
+        assert_eq!(
+            annotated_lines,
+            vec![
+                "# A comment",
+                "Database User Select Insert Update Delete Create Drop Alter Index Temp Lock References",
+                "db       user Y      Y      Y      Y      N      N    N     N     N    N    N",
+                "db       user2 Y Y Y",
+                "# ERROR: Too few fields, expected to find 13 fields, found 5",
+                "db       user3 Y      Y      Y      Y      X      N    N     N     N    N    N",
+                "# ERROR: Could not parse 'Create' privilege: Expected Y or N, found X",
+            ]
+        );
+
+        // Annotating the content again should not duplicate the errors.
+        assert_eq!(
+            annotate_editor_content_with_errors(&annotated, database_privilege_fields(false)),
+            annotated
+        );
+
+        // Fixed lines lose their error comments.
+        let fixed = annotated.replace("user2 Y Y Y", "user2 Y Y Y Y Y Y Y Y Y Y Y");
+        assert_eq!(
+            annotate_editor_content_with_errors(&fixed, database_privilege_fields(false))
+                .lines()
+                .filter(|line| line.starts_with(ERROR_COMMENT_PREFIX))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_editor_content_with_extended_privileges() {
+        let permissions = vec![DatabasePrivilegeRow {
+            db: "test_db".into(),
+            user: "test_user".into(),
+            select_priv: true,
+            insert_priv: false,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: false,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: true,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: true,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        }];
+
+        let content = generate_editor_content_from_privilege_data(
+            &permissions,
+            database_privilege_fields(true),
+            "test",
+            None,
+            &[],
+            &[],
+        );
+        assert_eq!(
+            parse_privilege_data_from_editor_content(&content, database_privilege_fields(true))
+                .unwrap(),
+            permissions,
+        );
+
+        // Without the extended privileges, they are neither shown nor parsed,
+        // and are only kept by `keep_hidden_privileges`.
+        let content = generate_editor_content_from_privilege_data(
+            &permissions,
+            database_privilege_fields(false),
+            "test",
+            None,
+            &[],
+            &[],
+        );
+        assert!(!content.contains("Execute"));
+        let mut parsed =
+            parse_privilege_data_from_editor_content(&content, database_privilege_fields(false))
+                .unwrap();
+        assert!(!parsed[0].execute_priv);
+        keep_hidden_privileges(&mut parsed, &permissions, database_privilege_fields(false));
+        assert_eq!(parsed, permissions);
+    }
 }
@@ -0,0 +1,129 @@
+//! Rendering privilege rows as the equivalent `GRANT` statements,
+//! e.g. for moving a project to a database server that is not managed by muscl.
+
+use crate::{
+    core::database_privileges::{DATABASE_PRIVILEGE_FIELDS, DatabasePrivilegeRow},
+    server::sql::{quote_identifier, quote_literal},
+};
+
+/// Converts a database privilege field name to the name of the privilege in a `GRANT` statement.
+#[must_use]
+pub fn db_priv_field_sql_name(name: &str) -> &str {
+    match name {
+        "select_priv" => "SELECT",
+        "insert_priv" => "INSERT",
+        "update_priv" => "UPDATE",
+        "delete_priv" => "DELETE",
+        "create_priv" => "CREATE",
+        "drop_priv" => "DROP",
+        "alter_priv" => "ALTER",
+        "index_priv" => "INDEX",
+        "create_tmp_table_priv" => "CREATE TEMPORARY TABLES",
+        "lock_tables_priv" => "LOCK TABLES",
+        "references_priv" => "REFERENCES",
+        "create_view_priv" => "CREATE VIEW",
+        "show_view_priv" => "SHOW VIEW",
+        "trigger_priv" => "TRIGGER",
+        "event_priv" => "EVENT",
+        "execute_priv" => "EXECUTE",
+        "create_routine_priv" => "CREATE ROUTINE",
+        "alter_routine_priv" => "ALTER ROUTINE",
+        "grant_priv" => "GRANT OPTION",
+        _ => "USAGE",
+    }
+}
+
+/// Renders a row as a `GRANT` statement giving the user the same privileges on the database,
+/// or `None` if the row does not grant anything.
+///
+/// The grant option is given with `WITH GRANT OPTION`, rather than in the list of privileges.
+///
+/// The database name is not escaped, so `_` and `%` in it are wildcards just like
+/// in the `mysql.db` row the statement creates.
+#[must_use]
+pub fn format_privileges_as_grant_statement(row: &DatabasePrivilegeRow) -> Option<String> {
+    let mut privileges = DATABASE_PRIVILEGE_FIELDS
+        .into_iter()
+        .skip(2)
+        .filter(|field| *field != "grant_priv" && row.get_privilege_by_name(field) == Some(true))
+        .map(db_priv_field_sql_name)
+        .collect::<Vec<_>>();
+
+    if privileges.is_empty() {
+        if !row.grant_priv {
+            return None;
+        }
+        privileges.push("USAGE");
+    }
+
+    Some(format!(
+        "GRANT {} ON {}.* TO {}@'%'{};",
+        privileges.join(", "),
+        quote_identifier(&row.db),
+        quote_literal(&row.user),
+        if row.grant_priv {
+            " WITH GRANT OPTION"
+        } else {
+            ""
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_privileges_as_grant_statement() {
+        let mut row = DatabasePrivilegeRow {
+            db: "alice_db".into(),
+            user: "alice_user".into(),
+            select_priv: true,
+            insert_priv: true,
+            update_priv: false,
+            delete_priv: false,
+            create_priv: false,
+            drop_priv: false,
+            alter_priv: false,
+            index_priv: false,
+            create_tmp_table_priv: true,
+            lock_tables_priv: false,
+            references_priv: false,
+            create_view_priv: false,
+            show_view_priv: false,
+            trigger_priv: false,
+            event_priv: false,
+            execute_priv: false,
+            create_routine_priv: false,
+            alter_routine_priv: false,
+            grant_priv: false,
+        };
+        assert_eq!(
+            format_privileges_as_grant_statement(&row).as_deref(),
+            Some(
+                "GRANT SELECT, INSERT, CREATE TEMPORARY TABLES ON `alice_db`.* TO 'alice_user'@'%';"
+            )
+        );
+
+        row.execute_priv = true;
+        row.grant_priv = true;
+        assert_eq!(
+            format_privileges_as_grant_statement(&row).as_deref(),
+            Some(
+                "GRANT SELECT, INSERT, CREATE TEMPORARY TABLES, EXECUTE ON `alice_db`.* TO 'alice_user'@'%' WITH GRANT OPTION;"
+            )
+        );
+
+        row.select_priv = false;
+        row.insert_priv = false;
+        row.create_tmp_table_priv = false;
+        row.execute_priv = false;
+        assert_eq!(
+            format_privileges_as_grant_statement(&row).as_deref(),
+            Some("GRANT USAGE ON `alice_db`.* TO 'alice_user'@'%' WITH GRANT OPTION;")
+        );
+
+        row.grant_priv = false;
+        assert_eq!(format_privileges_as_grant_statement(&row), None);
+    }
+}
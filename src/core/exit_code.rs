@@ -0,0 +1,165 @@
+//! Conventional exit codes for the client, as described in `sysexits(3)`.
+//!
+//! Commands do not exit the process themselves. Errors are returned all the way
+//! up to `main`, which maps them to an exit code with [`ExitCode::for_error`].
+//! Commands that have already printed their (partially) failed result return
+//! a [`CommandFailed`] through [`ensure_success`], so that `main` exits
+//! with the right code without printing anything more.
+
+use thiserror::Error;
+
+use crate::core::{
+    bootstrap::ServerConnectionError, output::CommandOutput, protocol::HandshakeError,
+};
+
+// NOTE: these are not exported by the libc crate, so they are copied from sysexits.h.
+const EX_USAGE: u8 = 64;
+const EX_UNAVAILABLE: u8 = 69;
+const EX_TEMPFAIL: u8 = 75;
+const EX_NOPERM: u8 = 77;
+
+/// The reason the client exits unsuccessfully.
+///
+/// The variants are ordered by how specific they are, so that the most
+/// specific one can be picked when several things went wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitCode {
+    /// Any other error.
+    Failure,
+
+    /// The command line arguments could not be parsed (`EX_USAGE`).
+    Usage,
+
+    /// The muscl server could not be reached (`EX_UNAVAILABLE`).
+    Unavailable,
+
+    /// The `MySQL` server is temporarily unavailable (`EX_TEMPFAIL`).
+    TempFail,
+
+    /// The user is not allowed to manage the database or user (`EX_NOPERM`).
+    NoPerm,
+}
+
+impl ExitCode {
+    #[must_use]
+    pub fn code(self) -> u8 {
+        match self {
+            ExitCode::Failure => 1,
+            ExitCode::Usage => EX_USAGE,
+            ExitCode::Unavailable => EX_UNAVAILABLE,
+            ExitCode::TempFail => EX_TEMPFAIL,
+            ExitCode::NoPerm => EX_NOPERM,
+        }
+    }
+
+    /// Pick the most specific exit code, or `None` if there are none.
+    pub fn combine(codes: impl IntoIterator<Item = ExitCode>) -> Option<ExitCode> {
+        codes.into_iter().max()
+    }
+
+    /// Find the exit code for an error that made it all the way up to `main`.
+    #[must_use]
+    pub fn for_error(err: &anyhow::Error) -> ExitCode {
+        for cause in err.chain() {
+            if let Some(CommandFailed(code)) = cause.downcast_ref::<CommandFailed>() {
+                return *code;
+            }
+
+            if cause.is::<clap::Error>() {
+                return ExitCode::Usage;
+            }
+
+            if cause.is::<ServerConnectionError>() {
+                return ExitCode::Unavailable;
+            }
+
+            // See `ResponseTimeoutStream`, the server stopped responding in the middle of a command.
+            if let Some(err) = cause.downcast_ref::<std::io::Error>()
+                && err.kind() == std::io::ErrorKind::TimedOut
+            {
+                return ExitCode::Unavailable;
+            }
+
+            if let Some(err) = cause.downcast_ref::<HandshakeError>() {
+                return match err {
+                    HandshakeError::DatabaseUnavailable => ExitCode::TempFail,
+                    HandshakeError::ServerError { .. } => ExitCode::Failure,
+                    _ => ExitCode::Unavailable,
+                };
+            }
+
+            // In direct mode, the client connects to MySQL by itself.
+            if cause.is::<sqlx::Error>() {
+                return ExitCode::TempFail;
+            }
+        }
+
+        ExitCode::Failure
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code.code())
+    }
+}
+
+/// The result of the command has been printed, and contained errors.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("The command failed with exit code {}", .0.code())]
+pub struct CommandFailed(pub ExitCode);
+
+/// Return a [`CommandFailed`] error if the printed result contains errors.
+pub fn ensure_success<T: CommandOutput + ?Sized>(output: &T) -> anyhow::Result<()> {
+    match output.exit_code() {
+        Some(code) => Err(CommandFailed(code).into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_exit_codes() {
+        assert_eq!(ExitCode::combine([]), None);
+        assert_eq!(
+            ExitCode::combine([ExitCode::Failure, ExitCode::Failure]),
+            Some(ExitCode::Failure)
+        );
+        assert_eq!(
+            ExitCode::combine([ExitCode::Failure, ExitCode::NoPerm, ExitCode::Failure]),
+            Some(ExitCode::NoPerm)
+        );
+    }
+
+    #[test]
+    fn test_exit_code_values() {
+        assert_eq!(ExitCode::Failure.code(), 1);
+        assert_eq!(ExitCode::Usage.code(), 64);
+        assert_eq!(ExitCode::Unavailable.code(), 69);
+        assert_eq!(ExitCode::TempFail.code(), 75);
+        assert_eq!(ExitCode::NoPerm.code(), 77);
+    }
+
+    #[test]
+    fn test_exit_code_for_error() {
+        let err = anyhow::Error::from(CommandFailed(ExitCode::NoPerm));
+        assert_eq!(ExitCode::for_error(&err), ExitCode::NoPerm);
+
+        let err = anyhow::Error::from(ServerConnectionError::SocketNotFound)
+            .context("Failed to connect to the server");
+        assert_eq!(ExitCode::for_error(&err), ExitCode::Unavailable);
+
+        let err = anyhow::Error::from(HandshakeError::DatabaseUnavailable);
+        assert_eq!(ExitCode::for_error(&err), ExitCode::TempFail);
+
+        let err = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(ExitCode::for_error(&err), ExitCode::Unavailable);
+
+        let err = anyhow::anyhow!("Something else went wrong");
+        assert_eq!(ExitCode::for_error(&err), ExitCode::Failure);
+        assert_eq!(ExitCode::Usage.code(), 64);
+    }
+}
@@ -0,0 +1,237 @@
+//! A small message catalog for user-facing strings.
+//!
+//! The language is selected from the standard locale environment variables,
+//! in the order `LC_ALL`, `LC_MESSAGES`, `LANG`, the same way gettext does it.
+//! Messages that do not have a translation for the selected language fall back
+//! to English.
+//!
+//! Placeholders in the templates are written as `{name}`, and are filled in
+//! by [`tr`].
+
+use std::{fmt::Display, sync::OnceLock};
+
+use crate::core::types::DbOrUser;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    NorwegianBokmal,
+}
+
+impl Language {
+    /// Parse a locale string like `nb_NO.UTF-8` into a supported language.
+    #[must_use]
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let language = locale
+            .split(['_', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match language.as_str() {
+            "nb" | "no" | "nn" => Some(Language::NorwegianBokmal),
+            "en" | "c" | "posix" => Some(Language::English),
+            _ => None,
+        }
+    }
+
+    fn detect() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::from_locale(&value))
+            .unwrap_or(Language::English)
+    }
+
+    /// The language selected by the environment of the current process.
+    #[must_use]
+    pub fn current() -> Self {
+        static LANGUAGE: OnceLock<Language> = OnceLock::new();
+        *LANGUAGE.get_or_init(Language::detect)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    NounDatabase,
+    NounUser,
+    NounDatabaseCapitalized,
+    NounUserCapitalized,
+
+    Skipping,
+    MySqlError,
+    BulkSummary,
+
+    NameEmpty,
+    NameTooLong,
+    NameInvalidCharacters,
+    NameReserved,
+    IllegalPrefix,
+    DeniedByGroupDenylist,
+
+    DatabaseCreated,
+    DatabaseDropped,
+    DatabasePrivilegesRemoved,
+    DatabaseAlreadyExists,
+    DatabaseDoesNotExist,
+    UserCreated,
+    UserDropped,
+    UserPrivilegesRemoved,
+    UserAlreadyExists,
+    UserDoesNotExist,
+}
+
+impl Message {
+    #[must_use]
+    pub fn template(self, language: Language) -> &'static str {
+        match language {
+            Language::English => self.english(),
+            Language::NorwegianBokmal => self.norwegian_bokmal(),
+        }
+    }
+
+    fn english(self) -> &'static str {
+        match self {
+            Message::NounDatabase => "database",
+            Message::NounUser => "user",
+            Message::NounDatabaseCapitalized => "Database",
+            Message::NounUserCapitalized => "User",
+
+            Message::Skipping => "Skipping...",
+            Message::MySqlError => "MySQL error: {error}",
+            Message::BulkSummary => "{succeeded} succeeded, {failed} failed, {skipped} skipped",
+
+            Message::NameEmpty => "The {noun} name can not be empty.",
+            Message::NameTooLong => "The {noun} name is too long, maximum length is 64 characters.",
+            Message::NameInvalidCharacters => {
+                "Invalid characters in {noun} name: '{name}', only A-Z, a-z, 0-9, _ (underscore) and - (dash) are permitted."
+            }
+            Message::NameReserved => "'{name}' is reserved by the database server.",
+            Message::IllegalPrefix => {
+                "Illegal {noun} name prefix: you are not allowed to manage databases or users prefixed with '{prefix}'"
+            }
+            Message::DeniedByGroupDenylist => "'{name}' is denied by the group denylist",
+
+            Message::DatabaseCreated => "Database '{name}' created successfully.",
+            Message::DatabaseDropped => "Database '{name}' dropped successfully.",
+            Message::DatabasePrivilegesRemoved => {
+                "Removed the privileges of these users on the database: {users}"
+            }
+            Message::DatabaseAlreadyExists => "Database {name} already exists.",
+            Message::DatabaseDoesNotExist => "Database {name} does not exist.",
+            Message::UserCreated => "User '{name}' created successfully.",
+            Message::UserDropped => "User '{name}' dropped successfully.",
+            Message::UserPrivilegesRemoved => {
+                "Removed {count} database privilege grant(s) along with the user."
+            }
+            Message::UserAlreadyExists => "User '{name}' already exists.",
+            Message::UserDoesNotExist => "User '{name}' does not exist.",
+        }
+    }
+
+    fn norwegian_bokmal(self) -> &'static str {
+        match self {
+            Message::NounDatabase => "database",
+            Message::NounUser => "bruker",
+            Message::NounDatabaseCapitalized => "Database",
+            Message::NounUserCapitalized => "Bruker",
+
+            Message::Skipping => "Hopper over...",
+            Message::MySqlError => "MySQL-feil: {error}",
+            Message::BulkSummary => "{succeeded} vellykket, {failed} feilet, {skipped} hoppet over",
+
+            Message::NameEmpty => "Navnet på {noun} kan ikke være tomt.",
+            Message::NameTooLong => "Navnet på {noun} er for langt, maksimal lengde er 64 tegn.",
+            Message::NameInvalidCharacters => {
+                "Ugyldige tegn i navnet på {noun}: '{name}', kun A-Z, a-z, 0-9, _ (understrek) og - (bindestrek) er tillatt."
+            }
+            Message::NameReserved => "'{name}' er reservert av databaseserveren.",
+            Message::IllegalPrefix => {
+                "Ugyldig prefiks for {noun}: du har ikke lov til å administrere databaser eller brukere med prefikset '{prefix}'"
+            }
+            Message::DeniedByGroupDenylist => "'{name}' er blokkert av gruppe-svartelisten",
+
+            Message::DatabaseCreated => "Databasen '{name}' ble opprettet.",
+            Message::DatabaseDropped => "Databasen '{name}' ble slettet.",
+            Message::DatabasePrivilegesRemoved => {
+                "Fjernet rettighetene til disse brukerne på databasen: {users}"
+            }
+            Message::DatabaseAlreadyExists => "Databasen {name} finnes allerede.",
+            Message::DatabaseDoesNotExist => "Databasen {name} finnes ikke.",
+            Message::UserCreated => "Brukeren '{name}' ble opprettet.",
+            Message::UserDropped => "Brukeren '{name}' ble slettet.",
+            Message::UserPrivilegesRemoved => {
+                "Fjernet {count} databaserettighet(er) sammen med brukeren."
+            }
+            Message::UserAlreadyExists => "Brukeren '{name}' finnes allerede.",
+            Message::UserDoesNotExist => "Brukeren '{name}' finnes ikke.",
+        }
+    }
+}
+
+/// Fill the placeholders of a template with the given arguments.
+#[must_use]
+pub fn fill_template(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = template.to_string();
+    for (key, value) in args {
+        result = result.replace(&format!("{{{key}}}"), &value.to_string());
+    }
+    result
+}
+
+/// Translate a message into the current language, filling in the placeholders.
+#[must_use]
+pub fn tr(message: Message, args: &[(&str, &dyn Display)]) -> String {
+    fill_template(message.template(Language::current()), args)
+}
+
+/// The translated noun for a database or user, e.g. `"database"` or `"bruker"`.
+#[must_use]
+pub fn noun(db_or_user: &DbOrUser, capitalized: bool) -> String {
+    let message = match (db_or_user, capitalized) {
+        (DbOrUser::Database(_), false) => Message::NounDatabase,
+        (DbOrUser::Database(_), true) => Message::NounDatabaseCapitalized,
+        (DbOrUser::User(_), false) => Message::NounUser,
+        (DbOrUser::User(_), true) => Message::NounUserCapitalized,
+    };
+    tr(message, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(
+            Language::from_locale("nb_NO.UTF-8"),
+            Some(Language::NorwegianBokmal)
+        );
+        assert_eq!(
+            Language::from_locale("nn_NO"),
+            Some(Language::NorwegianBokmal)
+        );
+        assert_eq!(
+            Language::from_locale("en_US.UTF-8"),
+            Some(Language::English)
+        );
+        assert_eq!(Language::from_locale("C"), Some(Language::English));
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), None);
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let template = Message::UserCreated.template(Language::NorwegianBokmal);
+        assert_eq!(
+            fill_template(template, &[("name", &"user_test")]),
+            "Brukeren 'user_test' ble opprettet."
+        );
+
+        let template = Message::IllegalPrefix.template(Language::English);
+        assert_eq!(
+            fill_template(template, &[("noun", &"user"), ("prefix", &"abc")]),
+            "Illegal user name prefix: you are not allowed to manage databases or users prefixed with 'abc'"
+        );
+    }
+}
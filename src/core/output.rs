@@ -0,0 +1,318 @@
+//! The output layer shared by all client commands.
+//!
+//! Every command result implements [`CommandOutput`], and is printed with
+//! [`print_output`], which picks between the human readable and the JSON
+//! format, and applies `--quiet`, according to the [`OutputOptions`] set up
+//! by [`init`] at startup.
+//!
+//! Results that need more context to be printed than the server response
+//! itself, like a name from the request or a table view, are wrapped in a
+//! small `*Output` struct next to the response type.
+
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::level_filters::LevelFilter;
+
+use crate::core::{
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    style::{self, ColorChoice, Role, paint},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Human readable text and tables.
+    #[default]
+    Human,
+
+    /// Pretty-printed JSON, for use in scripts.
+    Json,
+}
+
+/// Global output settings, given as flags before or after the command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    pub color: ColorChoice,
+    /// Only print human readable output when something went wrong.
+    pub quiet: bool,
+    pub verbosity: LevelFilter,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Human,
+            color: ColorChoice::Auto,
+            quiet: false,
+            verbosity: LevelFilter::INFO,
+        }
+    }
+}
+
+impl OutputOptions {
+    /// Build the options from the verbosity given with `-v`/`-q`,
+    /// where anything below the default level counts as quiet.
+    #[must_use]
+    pub fn new(format: OutputFormat, color: ColorChoice, verbosity: LevelFilter) -> Self {
+        Self {
+            format,
+            color,
+            quiet: verbosity < LevelFilter::INFO,
+            verbosity,
+        }
+    }
+
+    /// Switch to JSON output if the per-command `--json` flag was given.
+    #[must_use]
+    pub fn with_json(self, json: bool) -> Self {
+        if json {
+            Self {
+                format: OutputFormat::Json,
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+
+    #[must_use]
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+}
+
+static OPTIONS: OnceLock<OutputOptions> = OnceLock::new();
+
+/// Set up the output options for the rest of the process, including the color style.
+///
+/// If this is never called, [`OutputOptions::default`] is used.
+pub fn init(options: OutputOptions) {
+    style::init(options.color);
+    let _ = OPTIONS.set(options);
+}
+
+/// The output options set up by [`init`].
+#[must_use]
+pub fn options() -> OutputOptions {
+    OPTIONS.get().copied().unwrap_or_default()
+}
+
+/// The warnings from the server so far, to be included in the JSON output.
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Show a warning from the server on stderr, and keep it for the JSON output of the command.
+pub fn report_warning(message: String) {
+    eprintln!("{}", paint(Role::Warning, &format!("Warning: {message}")));
+    WARNINGS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push(message);
+}
+
+/// Add the warnings reported so far to a JSON result, wrapping it in an object if needed.
+fn with_warnings(value: serde_json::Value) -> serde_json::Value {
+    let warnings = std::mem::take(
+        &mut *WARNINGS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+    );
+    if warnings.is_empty() {
+        return value;
+    }
+
+    match value {
+        serde_json::Value::Object(mut object) => {
+            object.insert("warnings".to_string(), serde_json::json!(warnings));
+            serde_json::Value::Object(object)
+        }
+        value => serde_json::json!({
+          "results": value,
+          "warnings": warnings,
+        }),
+    }
+}
+
+/// A command result that can be printed in every [`OutputFormat`].
+pub trait CommandOutput {
+    /// Print the result as human readable text.
+    fn print_human(&self);
+
+    /// Convert the result to JSON.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// The exit code for the result, or `None` if no part of it is an error.
+    fn exit_code(&self) -> Option<ExitCode>;
+
+    /// Whether any part of the result is an error.
+    fn has_errors(&self) -> bool {
+        self.exit_code().is_some()
+    }
+}
+
+/// Print a command result according to the given options.
+pub fn print_output<T: CommandOutput + ?Sized>(output: &T, options: &OutputOptions) {
+    match options.format {
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&with_warnings(output.to_json()))
+                    .unwrap_or("Failed to serialize result to JSON".to_string())
+            );
+        }
+        OutputFormat::Human => {
+            if !options.quiet || output.has_errors() {
+                output.print_human();
+            }
+        }
+    }
+}
+
+/// The number of items of a bulk operation that succeeded, failed or were skipped,
+/// shown after the results so that failures are not lost in long output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct BulkSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Items that were already in the requested state, like a database that already exists.
+    pub skipped: usize,
+}
+
+impl BulkSummary {
+    /// Count the results of a bulk operation, where the errors for which `is_skipped`
+    /// returns true are counted as skipped rather than failed.
+    pub fn from_results<'a, T: 'a, E: 'a>(
+        results: impl IntoIterator<Item = &'a Result<T, E>>,
+        is_skipped: impl Fn(&E) -> bool,
+    ) -> Self {
+        results
+            .into_iter()
+            .fold(Self::default(), |mut summary, result| {
+                match result {
+                    Ok(_) => summary.succeeded += 1,
+                    Err(err) if is_skipped(err) => summary.skipped += 1,
+                    Err(_) => summary.failed += 1,
+                }
+                summary
+            })
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.succeeded + self.failed + self.skipped
+    }
+
+    /// Print the summary as the last line of the human readable output,
+    /// unless the operation only had a single item.
+    pub fn print_footer(&self) {
+        if self.total() > 1 {
+            println!("{self}");
+        }
+    }
+
+    /// Put the JSON of the per-item results next to the summary.
+    #[must_use]
+    pub fn wrap_json(&self, results: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+          "results": results,
+          "summary": self,
+        })
+    }
+}
+
+impl std::fmt::Display for BulkSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr(
+                Message::BulkSummary,
+                &[
+                    ("succeeded", &self.succeeded),
+                    ("failed", &self.failed),
+                    ("skipped", &self.skipped),
+                ],
+            )
+        )
+    }
+}
+
+/// Print a message that is not part of the command result, like a notice or a preview
+/// of the changes to confirm.
+///
+/// In JSON mode, the message is printed to stderr, so that stdout only contains the result.
+pub fn print_message<T: std::fmt::Display + ?Sized>(message: &T, options: &OutputOptions) {
+    match options.format {
+        OutputFormat::Json => eprintln!("{message}"),
+        OutputFormat::Human => println!("{message}"),
+    }
+}
+
+/// Convert any serializable value to JSON, for results that are sent as is.
+pub(crate) fn serialize_to_json<T: serde::Serialize + ?Sized>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_else(|err| {
+        serde_json::json!({
+          "status": "error",
+          "type": "serialization-error",
+          "error": err.to_string(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_options() {
+        let options =
+            OutputOptions::new(OutputFormat::Human, ColorChoice::Never, LevelFilter::WARN);
+        assert!(options.quiet);
+        assert!(!options.is_json());
+        assert!(options.with_json(true).is_json());
+        assert!(!options.with_json(false).is_json());
+
+        let options =
+            OutputOptions::new(OutputFormat::Json, ColorChoice::Never, LevelFilter::DEBUG);
+        assert!(!options.quiet);
+        assert!(options.with_json(false).is_json());
+    }
+
+    #[test]
+    fn test_with_warnings() {
+        assert_eq!(with_warnings(serde_json::json!([])), serde_json::json!([]));
+
+        report_warning("first".to_string());
+        assert_eq!(
+            with_warnings(serde_json::json!({ "status": "success" })),
+            serde_json::json!({ "status": "success", "warnings": ["first"] }),
+        );
+
+        report_warning("second".to_string());
+        assert_eq!(
+            with_warnings(serde_json::json!([1])),
+            serde_json::json!({ "results": [1], "warnings": ["second"] }),
+        );
+    }
+
+    #[test]
+    fn test_bulk_summary() {
+        let results: Vec<Result<(), &str>> = vec![Ok(()), Err("exists"), Err("failed"), Ok(())];
+        let summary = BulkSummary::from_results(&results, |err| *err == "exists");
+        assert_eq!(
+            summary,
+            BulkSummary {
+                succeeded: 2,
+                failed: 1,
+                skipped: 1,
+            }
+        );
+        assert_eq!(summary.total(), 4);
+        assert_eq!(
+            summary.wrap_json(serde_json::json!({}))["summary"],
+            serde_json::json!({ "succeeded": 2, "failed": 1, "skipped": 1 }),
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Paging of long output through `$PAGER`, the way `git` does it.
+//!
+//! Output is only paged when stdout is a terminal, and the output is taller
+//! than the terminal. The pager can be disabled with `--no-pager`, or by
+//! setting `PAGER` to an empty string or `cat`.
+
+use std::{
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use nix::libc;
+
+const DEFAULT_PAGER: &str = "less";
+
+/// Options passed to `less` unless the user has set `LESS` themselves.
+///
+/// - `F`: quit if the output fits on one screen
+/// - `R`: pass through color escape sequences
+/// - `X`: don't clear the screen on exit
+const DEFAULT_LESS_OPTIONS: &str = "FRX";
+
+static PAGER_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable the pager for the rest of the process.
+pub fn set_pager_enabled(enabled: bool) {
+    PAGER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn terminal_height() -> Option<usize> {
+    let mut winsize = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: TIOCGWINSZ only writes into the provided winsize struct.
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut winsize) };
+
+    if result == 0 && winsize.ws_row > 0 {
+        Some(winsize.ws_row as usize)
+    } else {
+        std::env::var("LINES").ok().and_then(|s| s.parse().ok())
+    }
+}
+
+fn pager_command() -> Option<String> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    if pager.trim().is_empty() || pager.trim() == "cat" {
+        None
+    } else {
+        Some(pager)
+    }
+}
+
+fn should_page(content: &str) -> bool {
+    if !PAGER_ENABLED.load(Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+        return false;
+    }
+
+    terminal_height().is_some_and(|height| content.lines().count() >= height)
+}
+
+fn run_pager(pager: &str, content: &str) -> std::io::Result<()> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(pager).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", DEFAULT_LESS_OPTIONS);
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // NOTE: the user might quit the pager before reading everything,
+        //       in which case we get a broken pipe that should be ignored.
+        match stdin.write_all(content.as_bytes()) {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err),
+            _ => {}
+        }
+    }
+    child.wait()?;
+
+    Ok(())
+}
+
+/// Print the content to stdout, through the pager if it does not fit on the screen.
+pub fn print_paged(content: &str) {
+    if should_page(content)
+        && let Some(pager) = pager_command()
+    {
+        match run_pager(&pager, content) {
+            Ok(()) => return,
+            Err(err) => tracing::debug!("Failed to run pager '{}': {}", pager, err),
+        }
+    }
+
+    print!("{content}");
+}
@@ -1,4 +1,9 @@
 mod commands;
+pub mod control;
+mod handshake;
 pub mod request_validation;
+mod response_timeout;
 
 pub use commands::*;
+pub use handshake::*;
+pub use response_timeout::*;
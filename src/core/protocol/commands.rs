@@ -1,46 +1,126 @@
+mod accept_grant;
+mod admin_list;
+mod admin_log_level;
+mod admin_report;
+mod adopt;
 mod check_authorization;
+mod check_names;
 mod complete_database_name;
 mod complete_user_name;
 mod create_databases;
 mod create_users;
+mod delete_orphaned_privileges;
 mod drop_databases;
 mod drop_users;
+mod get_privilege_snapshot;
+mod grant_roles;
+mod kill_connections;
 mod list_all_databases;
 mod list_all_privileges;
 mod list_all_users;
+mod list_column_privileges;
 mod list_databases;
+mod list_databases_with_privileges;
+mod list_grant_offers;
+mod list_orphaned_privileges;
+mod list_partial_revokes;
+mod list_privilege_aliases;
+mod list_privilege_snapshots;
+mod list_privilege_templates;
 mod list_privileges;
+mod list_routine_privileges;
+mod list_routines;
+mod list_unused_databases;
 mod list_users;
 mod list_valid_name_prefixes;
 mod lock_users;
+mod modify_column_privileges;
 mod modify_privileges;
+mod modify_routine_privileges;
+mod offer_grant;
 mod passwd_user;
+mod require_ssl;
+mod schedule_privilege_changes;
+mod search;
+mod server_info;
+mod set_user_comment;
+mod set_user_limits;
+mod show_connections;
+mod snapshot_privileges;
+mod stats;
+mod transfer_database;
 mod unlock_users;
 
+pub use accept_grant::*;
+pub use admin_list::*;
+pub use admin_log_level::*;
+pub use admin_report::*;
+pub use adopt::*;
 pub use check_authorization::*;
+pub use check_names::*;
 pub use complete_database_name::*;
 pub use complete_user_name::*;
 pub use create_databases::*;
 pub use create_users::*;
+pub use delete_orphaned_privileges::*;
 pub use drop_databases::*;
 pub use drop_users::*;
+pub use get_privilege_snapshot::*;
+pub use grant_roles::*;
+pub use kill_connections::*;
 pub use list_all_databases::*;
 pub use list_all_privileges::*;
 pub use list_all_users::*;
+pub use list_column_privileges::*;
 pub use list_databases::*;
+pub use list_databases_with_privileges::*;
+pub use list_grant_offers::*;
+pub use list_orphaned_privileges::*;
+pub use list_partial_revokes::*;
+pub use list_privilege_aliases::*;
+pub use list_privilege_snapshots::*;
+pub use list_privilege_templates::*;
 pub use list_privileges::*;
+pub use list_routine_privileges::*;
+pub use list_routines::*;
+pub use list_unused_databases::*;
 pub use list_users::*;
 pub use list_valid_name_prefixes::*;
 pub use lock_users::*;
+pub use modify_column_privileges::*;
 pub use modify_privileges::*;
+pub use modify_routine_privileges::*;
+pub use offer_grant::*;
 pub use passwd_user::*;
+pub use require_ssl::*;
+pub use schedule_privilege_changes::*;
+pub use search::*;
+pub use server_info::*;
+pub use set_user_comment::*;
+pub use set_user_limits::*;
+pub use show_connections::*;
+pub use snapshot_privileges::*;
+pub use stats::*;
+pub use transfer_database::*;
 pub use unlock_users::*;
 
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{Sink, Stream};
 use serde::{Deserialize, Serialize};
 use tokio::net::UnixStream;
 use tokio_serde::{Framed as SerdeFramed, formats::Bincode};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 
+use crate::core::{
+    database_privileges::DatabasePrivilegesDiff, output::report_warning,
+    protocol::ResponseTimeoutStream,
+};
+
 pub type ServerToClientMessageStream = SerdeFramed<
     Framed<UnixStream, LengthDelimitedCodec>,
     Request,
@@ -48,24 +128,77 @@ pub type ServerToClientMessageStream = SerdeFramed<
     Bincode<Request, Response>,
 >;
 
-pub type ClientToServerMessageStream = SerdeFramed<
-    Framed<UnixStream, LengthDelimitedCodec>,
+type ClientToServerFramedStream = SerdeFramed<
+    Framed<ResponseTimeoutStream, LengthDelimitedCodec>,
     Response,
     Request,
     Bincode<Response, Request>,
 >;
 
+/// The client side of a session.
+///
+/// [`Response::Warning`] messages can come at any point in the session, so they are
+/// passed on to [`report_warning`] here rather than returned as responses.
+pub struct ClientToServerMessageStream {
+    inner: ClientToServerFramedStream,
+}
+
+impl Stream for ClientToServerMessageStream {
+    type Item = Result<Response, std::io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(Response::Warning(message)))) => report_warning(message),
+                poll => return poll,
+            }
+        }
+    }
+}
+
+impl Sink<Request> for ClientToServerMessageStream {
+    type Error = std::io::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
 const MAX_REQUEST_FRAME_LENGTH: usize = 100 * 1024; // 100 KB
 const MAX_RESPONSE_FRAME_LENGTH: usize = 1024 * 1024; // 1 MB
 
 pub fn create_client_to_server_message_stream(socket: UnixStream) -> ClientToServerMessageStream {
+    create_client_to_server_message_stream_with_response_timeout(socket, None)
+}
+
+/// Like [`create_client_to_server_message_stream`], but fails with a
+/// [`std::io::ErrorKind::TimedOut`] error if the server takes longer than
+/// `timeout` to respond, see [`ResponseTimeoutStream`].
+pub fn create_client_to_server_message_stream_with_response_timeout(
+    socket: UnixStream,
+    timeout: Option<Duration>,
+) -> ClientToServerMessageStream {
     let codec = {
         let mut codec = LengthDelimitedCodec::new();
         codec.set_max_frame_length(MAX_REQUEST_FRAME_LENGTH);
         codec
     };
-    let length_delimited = Framed::new(socket, codec);
-    tokio_serde::Framed::new(length_delimited, Bincode::default())
+    let length_delimited = Framed::new(ResponseTimeoutStream::new(socket, timeout), codec);
+    ClientToServerMessageStream {
+        inner: tokio_serde::Framed::new(length_delimited, Bincode::default()),
+    }
 }
 
 pub fn create_server_to_client_message_stream(socket: UnixStream) -> ServerToClientMessageStream {
@@ -82,6 +215,7 @@ pub fn create_server_to_client_message_stream(socket: UnixStream) -> ServerToCli
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Request {
     CheckAuthorization(CheckAuthorizationRequest),
+    CheckNames(CheckNamesRequest),
 
     ListValidNamePrefixes,
     CompleteDatabaseName(CompleteDatabaseNameRequest),
@@ -89,27 +223,140 @@ pub enum Request {
 
     CreateDatabases(CreateDatabasesRequest),
     DropDatabases(DropDatabasesRequest),
+    TransferDatabase(TransferDatabaseRequest),
+    Adopt(AdoptRequest),
     ListDatabases(ListDatabasesRequest),
+    ListDatabasesWithPrivileges(ListDatabasesWithPrivilegesRequest),
+    ListUnusedDatabases,
     ListPrivileges(ListPrivilegesRequest),
+    ListPrivilegeTemplates,
+    ListPrivilegeAliases,
     ModifyPrivileges(ModifyPrivilegesRequest),
+    SchedulePrivilegeChanges(SchedulePrivilegeChangesRequest),
+    ListOrphanedPrivileges,
+    DeleteOrphanedPrivileges(DeleteOrphanedPrivilegesRequest),
+    OfferGrant(OfferGrantRequest),
+    ListGrantOffers,
+    AcceptGrant(AcceptGrantRequest),
+    SnapshotPrivileges(SnapshotPrivilegesRequest),
+    ListPrivilegeSnapshots,
+    GetPrivilegeSnapshot(GetPrivilegeSnapshotRequest),
+    ListColumnPrivileges(ListColumnPrivilegesRequest),
+    ModifyColumnPrivileges(ModifyColumnPrivilegesRequest),
+    ListRoutines(ListRoutinesRequest),
+    ListRoutinePrivileges(ListRoutinePrivilegesRequest),
+    ModifyRoutinePrivileges(ModifyRoutinePrivilegesRequest),
 
     CreateUsers(CreateUsersRequest),
     DropUsers(DropUsersRequest),
     PasswdUser(SetUserPasswordRequest),
+    SetUserComment(SetUserCommentRequest),
+    RequireSsl(RequireSslRequest),
+    SetUserLimits(SetUserLimitsRequest),
     ListUsers(ListUsersRequest),
     LockUsers(LockUsersRequest),
     UnlockUsers(UnlockUsersRequest),
+    KillConnections(KillConnectionsRequest),
+    ShowConnections(ShowConnectionsRequest),
+    GrantRoles(GrantRolesRequest),
+    ListPartialRevokes(ListPartialRevokesRequest),
+
+    ServerInfo,
+    Stats,
+    Search(SearchRequest),
+
+    AdminReport,
+    AdminListDatabases,
+    AdminListUsers,
+    AdminLogLevel(AdminLogLevelRequest),
 
     // Commit,
     Exit,
 }
 
+impl Request {
+    /// Whether the request only reads from the database, and can be served by a read-only replica.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Request::CompleteDatabaseName(_)
+                | Request::CompleteUserName(_)
+                | Request::ListDatabases(_)
+                | Request::ListDatabasesWithPrivileges(_)
+                | Request::ListUnusedDatabases
+                | Request::ListPrivileges(_)
+                | Request::ListColumnPrivileges(_)
+                | Request::ListRoutines(_)
+                | Request::ListRoutinePrivileges(_)
+                | Request::ListOrphanedPrivileges
+                | Request::ListUsers(_)
+                | Request::ListPartialRevokes(_)
+                | Request::Stats
+                | Request::Search(_)
+                | Request::AdminReport
+                | Request::AdminListDatabases
+                | Request::AdminListUsers
+        )
+    }
+
+    /// Whether the request changes anything, and should be checked against the
+    /// server policy, see [`crate::server::policy`].
+    #[must_use]
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Request::CreateDatabases(_)
+                | Request::DropDatabases(_)
+                | Request::TransferDatabase(_)
+                | Request::Adopt(_)
+                | Request::ModifyPrivileges(_)
+                | Request::SchedulePrivilegeChanges(_)
+                | Request::DeleteOrphanedPrivileges(_)
+                | Request::OfferGrant(_)
+                | Request::AcceptGrant(_)
+                | Request::SnapshotPrivileges(_)
+                | Request::ModifyColumnPrivileges(_)
+                | Request::ModifyRoutinePrivileges(_)
+                | Request::CreateUsers(_)
+                | Request::DropUsers(_)
+                | Request::PasswdUser(_)
+                | Request::SetUserComment(_)
+                | Request::RequireSsl(_)
+                | Request::SetUserLimits(_)
+                | Request::LockUsers(_)
+                | Request::UnlockUsers(_)
+                | Request::KillConnections(_)
+                | Request::GrantRoles(_)
+        )
+    }
+
+    /// Whether the request grants any of the extended database privileges, which
+    /// are only allowed when `extended_database_privileges` is enabled on the server.
+    #[must_use]
+    pub fn grants_extended_database_privileges(&self) -> bool {
+        match self {
+            Request::ModifyPrivileges(request) => request
+                .diffs
+                .iter()
+                .any(DatabasePrivilegesDiff::grants_extended_privileges),
+            Request::SchedulePrivilegeChanges(request) => request
+                .diffs
+                .iter()
+                .any(DatabasePrivilegesDiff::grants_extended_privileges),
+            Request::OfferGrant(row) => row.has_extended_privileges(),
+            _ => false,
+        }
+    }
+}
+
 // TODO: include a generic "message" that will display a message to the user?
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Response {
     CheckAuthorization(CheckAuthorizationResponse),
+    CheckNames(CheckNamesResponse),
 
     ListValidNamePrefixes(ListValidNamePrefixesResponse),
     CompleteDatabaseName(CompleteDatabaseNameResponse),
@@ -118,21 +365,77 @@ pub enum Response {
     // Specific data for specific commands
     CreateDatabases(CreateDatabasesResponse),
     DropDatabases(DropDatabasesResponse),
+    TransferDatabase(TransferDatabaseResponse),
+    Adopt(AdoptResponse),
     ListDatabases(ListDatabasesResponse),
     ListAllDatabases(ListAllDatabasesResponse),
+    ListDatabasesWithPrivileges(ListDatabasesWithPrivilegesResponse),
+    ListAllDatabasesWithPrivileges(ListAllDatabasesWithPrivilegesResponse),
+    ListUnusedDatabases(ListUnusedDatabasesResponse),
     ListPrivileges(ListPrivilegesResponse),
     ListAllPrivileges(ListAllPrivilegesResponse),
+    ListPrivilegeTemplates(ListPrivilegeTemplatesResponse),
+    ListPrivilegeAliases(ListPrivilegeAliasesResponse),
     ModifyPrivileges(ModifyPrivilegesResponse),
+    SchedulePrivilegeChanges(SchedulePrivilegeChangesResponse),
+    ListOrphanedPrivileges(ListOrphanedPrivilegesResponse),
+    DeleteOrphanedPrivileges(DeleteOrphanedPrivilegesResponse),
+    OfferGrant(OfferGrantResponse),
+    ListGrantOffers(ListGrantOffersResponse),
+    AcceptGrant(AcceptGrantResponse),
+    SnapshotPrivileges(SnapshotPrivilegesResponse),
+    ListPrivilegeSnapshots(ListPrivilegeSnapshotsResponse),
+    GetPrivilegeSnapshot(GetPrivilegeSnapshotResponse),
+    ListColumnPrivileges(ListColumnPrivilegesResponse),
+    ModifyColumnPrivileges(ModifyColumnPrivilegesResponse),
+    ListRoutines(ListRoutinesResponse),
+    ListRoutinePrivileges(ListRoutinePrivilegesResponse),
+    ModifyRoutinePrivileges(ModifyRoutinePrivilegesResponse),
 
     CreateUsers(CreateUsersResponse),
     DropUsers(DropUsersResponse),
     SetUserPassword(SetUserPasswordResponse),
+    SetUserComment(SetUserCommentResponse),
+    RequireSsl(RequireSslResponse),
+    SetUserLimits(SetUserLimitsResponse),
     ListUsers(ListUsersResponse),
     ListAllUsers(ListAllUsersResponse),
     LockUsers(LockUsersResponse),
     UnlockUsers(UnlockUsersResponse),
+    KillConnections(KillConnectionsResponse),
+    ShowConnections(ShowConnectionsResponse),
+    GrantRoles(GrantRolesResponse),
+    ListPartialRevokes(ListPartialRevokesResponse),
+
+    ServerInfo(ServerInfoResponse),
+    Stats(StatsResponse),
+    Search(SearchResponse),
+
+    AdminReport(AdminReportResponse),
+    AdminListDatabases(AdminListDatabasesResponse),
+    AdminListUsers(AdminListUsersResponse),
+    AdminLogLevel(AdminLogLevelResponse),
 
     // Generic responses
     Ready,
-    Error(String),
+    /// Sent while a large bulk request is processed, before the final response.
+    Progress {
+        done: usize,
+        total: usize,
+        current_item: String,
+    },
+    /// A failure that is not tied to the response of a specific request.
+    Error {
+        message: String,
+        /// The ID of the request in the server logs, for users to quote to the administrators.
+        reference: String,
+    },
+    /// The server could not get a connection to `MySQL`, sent instead of [`Response::Ready`].
+    DatabaseUnavailable,
+    /// An announcement from the administrators, sent just before [`Response::Ready`].
+    Motd(String),
+    /// Something the user should know about, which does not make the request fail.
+    ///
+    /// Warnings can be sent at any point in a session, ahead of the response they belong to.
+    Warning(String),
 }
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    database_privileges::format_privileges_as_cli_string,
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::{GrantOffer, GrantOfferId, request_validation::ValidationError},
+    types::{DbOrUser, MySQLUser},
+};
+
+pub type AcceptGrantRequest = GrantOfferId;
+
+pub type AcceptGrantResponse = Result<GrantOffer, AcceptGrantError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AcceptGrantError {
+    #[error("Grant offers are not enabled on this server")]
+    Disabled,
+
+    #[error("Offer does not exist")]
+    OfferDoesNotExist,
+
+    /// The user of the offer is not owned by the unix user.
+    #[error("Validation error: {1}")]
+    ValidationError(MySQLUser, ValidationError),
+
+    /// The unix user that made the offer no longer owns the database.
+    #[error("Offer is no longer valid")]
+    OfferIsNoLongerValid,
+
+    #[error("Database does not exist")]
+    DatabaseDoesNotExist,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("Failed to update the offers: {0}")]
+    StoreError(String),
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// An [`AcceptGrantResponse`] together with the id of the offer that was accepted.
+pub struct AcceptGrantOutput<'a> {
+    pub id: GrantOfferId,
+    pub result: &'a AcceptGrantResponse,
+}
+
+impl CommandOutput for AcceptGrantOutput<'_> {
+    fn print_human(&self) {
+        let id = self.id;
+        match self.result {
+            Ok(offer) => {
+                println!(
+                    "User '{}' now has privileges '{}' on database '{}'.",
+                    offer.row.user,
+                    format_privileges_as_cli_string(&offer.row),
+                    offer.row.db,
+                );
+            }
+            Err(err) => {
+                eprintln!("{}", err.to_error_message(id));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let id = self.id;
+        match self.result {
+            Ok(offer) => json!({
+              "id": offer.id,
+              "database": offer.row.db,
+              "user": offer.row.user,
+              "privileges": format_privileges_as_cli_string(&offer.row),
+              "status": "success",
+            }),
+            Err(err) => json!({
+              "id": id,
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(id),
+            }),
+        }
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result.as_ref().err().map(AcceptGrantError::exit_code)
+    }
+}
+
+impl AcceptGrantError {
+    #[must_use]
+    pub fn to_error_message(&self, id: GrantOfferId) -> String {
+        match self {
+            AcceptGrantError::Disabled => {
+                "Grant offers are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            AcceptGrantError::OfferDoesNotExist => {
+                format!("Grant offer {id} does not exist, or has expired.")
+            }
+            AcceptGrantError::ValidationError(username, err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            AcceptGrantError::OfferIsNoLongerValid => {
+                format!(
+                    "Grant offer {id} is no longer valid, as the one who made it no longer owns the database."
+                )
+            }
+            AcceptGrantError::DatabaseDoesNotExist => {
+                format!("The database of grant offer {id} no longer exists.")
+            }
+            AcceptGrantError::UserDoesNotExist => {
+                format!("The user of grant offer {id} no longer exists.")
+            }
+            AcceptGrantError::StoreError(err) => {
+                format!("Failed to update the offers: {err}")
+            }
+            AcceptGrantError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            AcceptGrantError::Disabled => "disabled".to_string(),
+            AcceptGrantError::OfferDoesNotExist => "offer-does-not-exist".to_string(),
+            AcceptGrantError::ValidationError(_, err) => err.error_type(),
+            AcceptGrantError::OfferIsNoLongerValid => "offer-is-no-longer-valid".to_string(),
+            AcceptGrantError::DatabaseDoesNotExist => "database-does-not-exist".to_string(),
+            AcceptGrantError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            AcceptGrantError::StoreError(_) => "store-error".to_string(),
+            AcceptGrantError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            AcceptGrantError::ValidationError(_, err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::server::sql::{database_operations::DatabaseRow, user_operations::DatabaseUser};
+
+/// Every managed database on the server, together with the unix user or group that owns it.
+pub type AdminListDatabasesResponse = Result<Vec<(String, DatabaseRow)>, AdminListError>;
+
+/// Every managed database user on the server, together with the unix user or group that owns it.
+pub type AdminListUsersResponse = Result<Vec<(String, DatabaseUser)>, AdminListError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AdminListError {
+    #[error("Not authorized")]
+    NotAuthorized,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl AdminListError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            AdminListError::NotAuthorized => {
+                "You need to be a member of one of the admin groups to do this.".to_string()
+            }
+            AdminListError::MySqlError(err) => format!("MySQL error: {err}"),
+        }
+    }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            AdminListError::NotAuthorized => "not-authorized".to_string(),
+            AdminListError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+}
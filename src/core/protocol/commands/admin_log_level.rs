@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::{CommandOutput, serialize_to_json},
+};
+
+/// The log levels the server can be set to, from least to most verbose.
+pub const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// The new log level of the server, or `None` to only look up the current one.
+pub type AdminLogLevelRequest = Option<String>;
+
+pub type AdminLogLevelResponse = Result<AdminLogLevel, AdminLogLevelError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdminLogLevel {
+    /// The log level of the server, after the request was handled.
+    pub level: String,
+    /// The log level of the server before it was changed, if it was changed.
+    pub previous_level: Option<String>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AdminLogLevelError {
+    #[error("Not authorized")]
+    NotAuthorized,
+
+    #[error("Invalid log level: {0}")]
+    InvalidLogLevel(String),
+
+    #[error("The log level of this server can not be changed at runtime")]
+    NotSupported,
+}
+
+impl CommandOutput for AdminLogLevel {
+    fn print_human(&self) {
+        match &self.previous_level {
+            Some(previous_level) => println!(
+                "Changed the log level of the server from '{}' to '{}'",
+                previous_level, self.level
+            ),
+            None => println!("The log level of the server is '{}'", self.level),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serialize_to_json(self)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl AdminLogLevelError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            AdminLogLevelError::NotAuthorized => {
+                "You need to be a member of one of the admin groups to do this.".to_string()
+            }
+            AdminLogLevelError::InvalidLogLevel(level) => format!(
+                "Invalid log level '{}', expected one of: {}",
+                level,
+                LOG_LEVELS.join(", ")
+            ),
+            AdminLogLevelError::NotSupported => {
+                "The log level of this server can not be changed at runtime.".to_string()
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            AdminLogLevelError::NotAuthorized => "not-authorized".to_string(),
+            AdminLogLevelError::InvalidLogLevel(_) => "invalid-log-level".to_string(),
+            AdminLogLevelError::NotSupported => "not-supported".to_string(),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::{CommandOutput, serialize_to_json},
+    protocol::PrefixStats,
+};
+
+pub type AdminReportResponse = Result<AdminReport, AdminReportError>;
+
+/// Usage of every managed prefix on the server, for capacity planning and chargeback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdminReport {
+    /// Unix timestamp of when the report was generated.
+    pub generated_at: u64,
+    /// Statistics for each unix user or group that owns at least one database or user.
+    pub prefixes: BTreeMap<String, PrefixStats>,
+    /// Databases and users that are not owned by any unix user or group.
+    pub unowned: PrefixStats,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AdminReportError {
+    #[error("Not authorized")]
+    NotAuthorized,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for AdminReport {
+    fn print_human(&self) {
+        let mut table = Table::new();
+        table.set_titles(row!["Prefix", "Databases", "Size", "Users", "Locked users"]);
+        let unowned_label = "(unowned)".to_string();
+        for (prefix, stats) in self
+            .prefixes
+            .iter()
+            .chain([(&unowned_label, &self.unowned)])
+        {
+            table.add_row(row![
+                prefix,
+                r->stats.databases,
+                r->humansize::format_size(stats.size_bytes, humansize::DECIMAL),
+                r->stats.users,
+                r->stats.locked_users,
+            ]);
+        }
+        table.printstd();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serialize_to_json(self)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl AdminReportError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            AdminReportError::NotAuthorized => {
+                "You need to be a member of one of the admin groups to do this.".to_string()
+            }
+            AdminReportError::MySqlError(err) => format!("MySQL error: {err}"),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            AdminReportError::NotAuthorized => "not-authorized".to_string(),
+            AdminReportError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+}
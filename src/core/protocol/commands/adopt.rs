@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::DbOrUser,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdoptRequest {
+    /// Databases and users that were created outside of muscl.
+    pub names: Vec<DbOrUser>,
+
+    /// Bring the privileges of the adopted objects in line with what muscl would have set up.
+    pub normalize: bool,
+}
+
+pub type AdoptResponse = BTreeMap<DbOrUser, Result<AdoptedObject, AdoptError>>;
+
+/// What was changed while normalizing an adopted database or user.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdoptedObject {
+    /// The host the user used to be limited to, before it was moved to `%`,
+    /// which is the only host muscl manages users for.
+    pub moved_from_host: Option<String>,
+
+    /// The number of privilege rows the grant option was removed from,
+    /// as muscl never hands it out.
+    pub revoked_grant_options: u64,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AdoptError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("Does not exist")]
+    DoesNotExist,
+
+    #[error("User exists on several hosts: {0:?}")]
+    AmbiguousUserHosts(Vec<String>),
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for AdoptResponse {
+    fn print_human(&self) {
+        for (db_or_user, result) in self {
+            let noun = db_or_user.capitalized_noun();
+            let name = db_or_user.name();
+            match result {
+                Ok(adopted) => {
+                    println!("{noun} '{name}' adopted successfully.");
+                    if let Some(host) = &adopted.moved_from_host {
+                        println!("  Moved the user from host '{host}' to '%'.");
+                    }
+                    if adopted.revoked_grant_options > 0 {
+                        println!(
+                            "  Removed the grant option from {} privilege row(s).",
+                            adopted.revoked_grant_options
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_or_user)));
+                }
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(db_or_user, result)| match result {
+                Ok(adopted) => (
+                    db_or_user.name().to_string(),
+                    json!({
+                      "status": "success",
+                      "moved_from_host": adopted.moved_from_host,
+                      "revoked_grant_options": adopted.revoked_grant_options,
+                    }),
+                ),
+                Err(err) => (
+                    db_or_user.name().to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(db_or_user),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(AdoptError::exit_code),
+        )
+    }
+}
+
+impl AdoptError {
+    #[must_use]
+    pub fn to_error_message(&self, db_or_user: &DbOrUser) -> String {
+        let noun = db_or_user.capitalized_noun();
+        let name = db_or_user.name();
+        match self {
+            AdoptError::ValidationError(err) => err.to_error_message(db_or_user),
+            AdoptError::DoesNotExist => format!("{noun} '{name}' does not exist."),
+            AdoptError::AmbiguousUserHosts(hosts) => format!(
+                "User '{name}' exists on several hosts ({}), ask the administrators to merge them.",
+                hosts.join(", ")
+            ),
+            AdoptError::MySqlError(err) => format!("MySQL error: {err}"),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            AdoptError::ValidationError(err) => err.error_type(),
+            AdoptError::DoesNotExist => "does-not-exist".to_string(),
+            AdoptError::AmbiguousUserHosts(_) => "ambiguous-user-hosts".to_string(),
+            AdoptError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            AdoptError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -4,7 +4,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
-use crate::core::{protocol::request_validation::ValidationError, types::DbOrUser};
+use crate::core::{
+    exit_code::ExitCode, output::CommandOutput, protocol::request_validation::ValidationError,
+    types::DbOrUser,
+};
 
 pub type CheckAuthorizationRequest = Vec<DbOrUser>;
 
@@ -14,46 +17,52 @@ pub type CheckAuthorizationResponse = BTreeMap<DbOrUser, Result<(), CheckAuthori
 #[error("Validation error: {0}")]
 pub struct CheckAuthorizationError(#[from] pub ValidationError);
 
-pub fn print_check_authorization_output_status(output: &CheckAuthorizationResponse) {
-    for (db_or_user, result) in output {
-        match result {
-            Ok(()) => {
-                println!("'{}': OK", db_or_user.name());
-            }
-            Err(err) => {
-                eprintln!(
-                    "'{}': {}",
-                    db_or_user.name(),
-                    err.to_error_message(db_or_user)
-                );
+impl CommandOutput for CheckAuthorizationResponse {
+    fn print_human(&self) {
+        for (db_or_user, result) in self {
+            match result {
+                Ok(()) => {
+                    println!("'{}': OK", db_or_user.name());
+                }
+                Err(err) => {
+                    eprintln!(
+                        "'{}': {}",
+                        db_or_user.name(),
+                        err.to_error_message(db_or_user)
+                    );
+                }
             }
         }
     }
-}
 
-pub fn print_check_authorization_output_status_json(output: &CheckAuthorizationResponse) {
-    let value = output
-        .iter()
-        .map(|(db_or_user, result)| match result {
-            Ok(()) => (
-                db_or_user.name().to_string(),
-                json!({ "status": "success" }),
-            ),
-            Err(err) => (
-                db_or_user.name().to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(db_or_user),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(db_or_user, result)| match result {
+                Ok(()) => (
+                    db_or_user.name().to_string(),
+                    json!({ "status": "success" }),
+                ),
+                Err(err) => (
+                    db_or_user.name().to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(db_or_user),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(CheckAuthorizationError::exit_code),
+        )
+    }
 }
 
 impl CheckAuthorizationError {
@@ -66,4 +75,9 @@ impl CheckAuthorizationError {
     pub fn error_type(&self) -> String {
         self.0.error_type()
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        self.0.exit_code()
+    }
 }
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::DbOrUser,
+};
+
+/// Names to validate as if they were about to be created, without creating anything.
+pub type CheckNamesRequest = Vec<DbOrUser>;
+
+pub type CheckNamesResponse = BTreeMap<DbOrUser, Result<(), CheckNameError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CheckNameError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("Already exists")]
+    AlreadyExists,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for CheckNamesResponse {
+    fn print_human(&self) {
+        for (db_or_user, result) in self {
+            match result {
+                Ok(()) => {
+                    println!("'{}': OK, can be created", db_or_user.name());
+                }
+                Err(err) => {
+                    eprintln!(
+                        "'{}': {}",
+                        db_or_user.name(),
+                        paint(Role::Error, &err.to_error_message(db_or_user))
+                    );
+                }
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(db_or_user, result)| match result {
+                Ok(()) => (
+                    db_or_user.name().to_string(),
+                    json!({ "status": "success" }),
+                ),
+                Err(err) => (
+                    db_or_user.name().to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(db_or_user),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(CheckNameError::exit_code),
+        )
+    }
+}
+
+impl CheckNameError {
+    #[must_use]
+    pub fn to_error_message(&self, db_or_user: &DbOrUser) -> String {
+        match self {
+            CheckNameError::ValidationError(err) => err.to_error_message(db_or_user),
+            CheckNameError::AlreadyExists => match db_or_user {
+                DbOrUser::Database(name) => tr(Message::DatabaseAlreadyExists, &[("name", name)]),
+                DbOrUser::User(name) => tr(Message::UserAlreadyExists, &[("name", name)]),
+            },
+            CheckNameError::MySqlError(err) => tr(Message::MySqlError, &[("error", err)]),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            CheckNameError::ValidationError(err) => err.error_type(),
+            CheckNameError::AlreadyExists => "already-exists".to_string(),
+            CheckNameError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            CheckNameError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
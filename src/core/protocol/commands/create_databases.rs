@@ -5,7 +5,11 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    output::{BulkSummary, CommandOutput},
     protocol::request_validation::ValidationError,
+    style::{Role, paint},
     types::{DbOrUser, MySQLDatabase},
 };
 
@@ -25,44 +29,65 @@ pub enum CreateDatabaseError {
     MySqlError(String),
 }
 
-pub fn print_create_databases_output_status(output: &CreateDatabasesResponse) {
-    for (database_name, result) in output {
-        match result {
-            Ok(()) => {
-                println!("Database '{database_name}' created successfully.");
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(database_name));
-                eprintln!("Skipping...");
+impl CommandOutput for CreateDatabasesResponse {
+    fn print_human(&self) {
+        for (database_name, result) in self {
+            match result {
+                Ok(()) => {
+                    println!(
+                        "{}",
+                        tr(Message::DatabaseCreated, &[("name", database_name)])
+                    );
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        paint(Role::Error, &err.to_error_message(database_name))
+                    );
+                    eprintln!("{}", tr(Message::Skipping, &[]));
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), CreateDatabaseError::is_skipped).print_footer();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(()) => (name.to_string(), json!({ "status": "success" })),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), CreateDatabaseError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
     }
-}
 
-pub fn print_create_databases_output_status_json(output: &CreateDatabasesResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(CreateDatabaseError::exit_code),
+        )
+    }
 }
 
 impl CreateDatabaseError {
+    /// Whether the database already existed, so that there was nothing to create.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, CreateDatabaseError::DatabaseAlreadyExists)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, database_name: &MySQLDatabase) -> String {
         match self {
@@ -70,11 +95,9 @@ impl CreateDatabaseError {
                 err.to_error_message(&DbOrUser::Database(database_name.clone()))
             }
             CreateDatabaseError::DatabaseAlreadyExists => {
-                format!("Database {database_name} already exists.")
-            }
-            CreateDatabaseError::MySqlError(err) => {
-                format!("MySQL error: {err}")
+                tr(Message::DatabaseAlreadyExists, &[("name", database_name)])
             }
+            CreateDatabaseError::MySqlError(err) => tr(Message::MySqlError, &[("error", err)]),
         }
     }
 
@@ -86,4 +109,12 @@ impl CreateDatabaseError {
             CreateDatabaseError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            CreateDatabaseError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
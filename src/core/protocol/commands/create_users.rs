@@ -5,11 +5,23 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
-    protocol::request_validation::ValidationError,
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    output::{BulkSummary, CommandOutput},
+    protocol::{
+        SetUserPasswordOutput, SetUserPasswordResponse, request_validation::ValidationError,
+    },
+    style::{Role, paint},
     types::{DbOrUser, MySQLUser},
 };
 
-pub type CreateUsersRequest = Vec<MySQLUser>;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateUsersRequest {
+    pub users: Vec<MySQLUser>,
+
+    /// Create the users with their accounts locked, to be unlocked later with `unlock-user`.
+    pub locked: bool,
+}
 
 pub type CreateUsersResponse = BTreeMap<MySQLUser, Result<(), CreateUserError>>;
 
@@ -21,48 +33,100 @@ pub enum CreateUserError {
     #[error("User already exists")]
     UserAlreadyExists,
 
+    #[error("Account locking is not supported by the database server")]
+    AccountLockingNotSupported,
+
     #[error("MySQL error: {0}")]
     MySqlError(String),
 }
 
-pub fn print_create_users_output_status(output: &CreateUsersResponse) {
-    for (username, result) in output {
-        match result {
-            Ok(()) => {
-                println!("User '{username}' created successfully.");
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(username));
-                eprintln!("Skipping...");
+impl CommandOutput for CreateUsersResponse {
+    fn print_human(&self) {
+        for (username, result) in self {
+            match result {
+                Ok(()) => {
+                    println!("{}", tr(Message::UserCreated, &[("name", username)]));
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("{}", tr(Message::Skipping, &[]));
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), CreateUserError::is_skipped).print_footer();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(()) => (name.to_string(), json!({ "status": "success" })),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), CreateUserError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(CreateUserError::exit_code),
+        )
     }
 }
 
-pub fn print_create_users_output_status_json(output: &CreateUsersResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+/// A [`CreateUsersResponse`] together with the results of setting the passwords
+/// of the new users, so that `--json` prints them as a single document.
+pub struct CreateUsersOutput<'a> {
+    pub result: &'a CreateUsersResponse,
+    pub passwords: &'a BTreeMap<MySQLUser, SetUserPasswordResponse>,
+}
+
+impl CommandOutput for CreateUsersOutput<'_> {
+    fn print_human(&self) {
+        self.result.print_human();
+        for (username, result) in self.passwords {
+            SetUserPasswordOutput { username, result }.print_human();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut value = self.result.to_json();
+        for (username, result) in self.passwords {
+            let mut password = SetUserPasswordOutput { username, result }.to_json();
+            value["results"][username.to_string()]["password"] =
+                password[username.to_string()].take();
+        }
+        value
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(self.result.exit_code().into_iter().chain(
+            self.passwords.iter().filter_map(|(username, result)| {
+                SetUserPasswordOutput { username, result }.exit_code()
+            }),
+        ))
+    }
 }
 
 impl CreateUserError {
+    /// Whether the user already existed, so that there was nothing to create.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, CreateUserError::UserAlreadyExists)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, username: &MySQLUser) -> String {
         match self {
@@ -70,11 +134,12 @@ impl CreateUserError {
                 err.to_error_message(&DbOrUser::User(username.clone()))
             }
             CreateUserError::UserAlreadyExists => {
-                format!("User '{username}' already exists.")
+                tr(Message::UserAlreadyExists, &[("name", username)])
             }
-            CreateUserError::MySqlError(err) => {
-                format!("MySQL error: {err}")
+            CreateUserError::AccountLockingNotSupported => {
+                "The database server does not support creating locked users.".to_string()
             }
+            CreateUserError::MySqlError(err) => tr(Message::MySqlError, &[("error", err)]),
         }
     }
 
@@ -83,7 +148,39 @@ impl CreateUserError {
         match self {
             CreateUserError::ValidationError(err) => err.error_type(),
             CreateUserError::UserAlreadyExists => "user-already-exists".to_string(),
+            CreateUserError::AccountLockingNotSupported => {
+                "account-locking-not-supported".to_string()
+            }
             CreateUserError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            CreateUserError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_users_output_json_includes_passwords() {
+        let user = MySQLUser::from("user_a");
+        let result = BTreeMap::from([(user.clone(), Ok(()))]);
+        let passwords = BTreeMap::from([(user.clone(), Ok(()))]);
+        let output = CreateUsersOutput {
+            result: &result,
+            passwords: &passwords,
+        };
+
+        let json = output.to_json();
+        assert_eq!(json["results"]["user_a"]["status"], "success");
+        assert_eq!(json["results"]["user_a"]["password"]["status"], "success");
+        assert_eq!(output.exit_code(), None);
+    }
 }
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLDatabase, MySQLUser},
+};
+
+pub type DeleteOrphanedPrivilegesRequest = Vec<(MySQLDatabase, MySQLUser)>;
+
+pub type DeleteOrphanedPrivilegesResponse =
+    BTreeMap<(MySQLDatabase, MySQLUser), Result<(), DeleteOrphanedPrivilegeError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DeleteOrphanedPrivilegeError {
+    /// Neither the database nor the user is owned by the unix user.
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("Privileges row does not exist")]
+    RowDoesNotExist,
+
+    #[error("Both the database and the user exist")]
+    RowIsNotOrphaned,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for DeleteOrphanedPrivilegesResponse {
+    fn print_human(&self) {
+        for ((database_name, username), result) in self {
+            match result {
+                Ok(()) => {
+                    println!(
+                        "Removed privileges for user '{username}' on database '{database_name}'."
+                    );
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        paint(Role::Error, &err.to_error_message(database_name, username))
+                    );
+                    eprintln!("Skipping...");
+                }
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|((database_name, username), result)| match result {
+                Ok(()) => json!({
+                  "database": database_name,
+                  "user": username,
+                  "status": "success",
+                }),
+                Err(err) => json!({
+                  "database": database_name,
+                  "user": username,
+                  "status": "error",
+                  "type": err.error_type(),
+                  "error": err.to_error_message(database_name, username),
+                }),
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(DeleteOrphanedPrivilegeError::exit_code),
+        )
+    }
+}
+
+impl DeleteOrphanedPrivilegeError {
+    #[must_use]
+    pub fn to_error_message(&self, database_name: &MySQLDatabase, username: &MySQLUser) -> String {
+        match self {
+            DeleteOrphanedPrivilegeError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(database_name.clone()))
+            }
+            DeleteOrphanedPrivilegeError::RowDoesNotExist => {
+                format!(
+                    "Privileges for user '{username}' on database '{database_name}' do not exist."
+                )
+            }
+            DeleteOrphanedPrivilegeError::RowIsNotOrphaned => {
+                format!(
+                    "Both database '{database_name}' and user '{username}' exist, refusing to remove their privileges."
+                )
+            }
+            DeleteOrphanedPrivilegeError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            DeleteOrphanedPrivilegeError::ValidationError(err) => err.error_type(),
+            DeleteOrphanedPrivilegeError::RowDoesNotExist => "row-does-not-exist".to_string(),
+            DeleteOrphanedPrivilegeError::RowIsNotOrphaned => "row-is-not-orphaned".to_string(),
+            DeleteOrphanedPrivilegeError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            DeleteOrphanedPrivilegeError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
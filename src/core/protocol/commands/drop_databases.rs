@@ -1,17 +1,23 @@
 use std::collections::BTreeMap;
 
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    output::{BulkSummary, CommandOutput},
     protocol::request_validation::ValidationError,
-    types::{DbOrUser, MySQLDatabase},
+    style::{Role, paint},
+    types::{DbOrUser, MySQLDatabase, MySQLUser},
 };
 
 pub type DropDatabasesRequest = Vec<MySQLDatabase>;
 
-pub type DropDatabasesResponse = BTreeMap<MySQLDatabase, Result<(), DropDatabaseError>>;
+/// The users that had privileges on each dropped database, which were removed along with it.
+pub type DropDatabasesResponse = BTreeMap<MySQLDatabase, Result<Vec<MySQLUser>, DropDatabaseError>>;
 
 #[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DropDatabaseError {
@@ -25,47 +31,80 @@ pub enum DropDatabaseError {
     MySqlError(String),
 }
 
-pub fn print_drop_databases_output_status(output: &DropDatabasesResponse) {
-    for (database_name, result) in output {
-        match result {
-            Ok(()) => {
-                println!(
-                    "Database '{}' dropped successfully.",
-                    database_name.as_str()
-                );
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(database_name));
-                eprintln!("Skipping...");
+impl CommandOutput for DropDatabasesResponse {
+    fn print_human(&self) {
+        for (database_name, result) in self {
+            match result {
+                Ok(users) => {
+                    println!(
+                        "{}",
+                        tr(Message::DatabaseDropped, &[("name", database_name)])
+                    );
+                    if !users.is_empty() {
+                        println!(
+                            "{}",
+                            tr(
+                                Message::DatabasePrivilegesRemoved,
+                                &[("users", &users.iter().join(", "))]
+                            )
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        paint(Role::Error, &err.to_error_message(database_name))
+                    );
+                    eprintln!("{}", tr(Message::Skipping, &[]));
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), DropDatabaseError::is_skipped).print_footer();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(users) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "removed_privileges_for_users": users,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), DropDatabaseError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
     }
-}
 
-pub fn print_drop_databases_output_status_json(output: &DropDatabasesResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(DropDatabaseError::exit_code),
+        )
+    }
 }
 
 impl DropDatabaseError {
+    /// Whether the database did not exist, so that there was nothing to drop.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, DropDatabaseError::DatabaseDoesNotExist)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, database_name: &MySQLDatabase) -> String {
         match self {
@@ -73,11 +112,9 @@ impl DropDatabaseError {
                 err.to_error_message(&DbOrUser::Database(database_name.clone()))
             }
             DropDatabaseError::DatabaseDoesNotExist => {
-                format!("Database {database_name} does not exist.")
-            }
-            DropDatabaseError::MySqlError(err) => {
-                format!("MySQL error: {err}")
+                tr(Message::DatabaseDoesNotExist, &[("name", database_name)])
             }
+            DropDatabaseError::MySqlError(err) => tr(Message::MySqlError, &[("error", err)]),
         }
     }
 
@@ -89,4 +126,12 @@ impl DropDatabaseError {
             DropDatabaseError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            DropDatabaseError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
@@ -5,13 +5,18 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    i18n::{Message, tr},
+    output::{BulkSummary, CommandOutput},
     protocol::request_validation::ValidationError,
+    style::{Role, paint},
     types::{DbOrUser, MySQLUser},
 };
 
 pub type DropUsersRequest = Vec<MySQLUser>;
 
-pub type DropUsersResponse = BTreeMap<MySQLUser, Result<(), DropUserError>>;
+/// The number of privilege rows that were removed along with each dropped user.
+pub type DropUsersResponse = BTreeMap<MySQLUser, Result<u64, DropUserError>>;
 
 #[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DropUserError {
@@ -25,56 +30,82 @@ pub enum DropUserError {
     MySqlError(String),
 }
 
-pub fn print_drop_users_output_status(output: &DropUsersResponse) {
-    for (username, result) in output {
-        match result {
-            Ok(()) => {
-                println!("User '{username}' dropped successfully.");
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(username));
-                eprintln!("Skipping...");
+impl CommandOutput for DropUsersResponse {
+    fn print_human(&self) {
+        for (username, result) in self {
+            match result {
+                Ok(removed_privileges) => {
+                    println!("{}", tr(Message::UserDropped, &[("name", username)]));
+                    if *removed_privileges > 0 {
+                        println!(
+                            "{}",
+                            tr(
+                                Message::UserPrivilegesRemoved,
+                                &[("count", removed_privileges)]
+                            )
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("{}", tr(Message::Skipping, &[]));
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), DropUserError::is_skipped).print_footer();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(removed_privileges) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "removed_privileges": removed_privileges,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), DropUserError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
     }
-}
 
-pub fn print_drop_users_output_status_json(output: &DropUsersResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(DropUserError::exit_code),
+        )
+    }
 }
 
 impl DropUserError {
+    /// Whether the user did not exist, so that there was nothing to drop.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, DropUserError::UserDoesNotExist)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, username: &MySQLUser) -> String {
         match self {
             DropUserError::ValidationError(err) => {
                 err.to_error_message(&DbOrUser::User(username.clone()))
             }
-            DropUserError::UserDoesNotExist => {
-                format!("User '{username}' does not exist.")
-            }
-            DropUserError::MySqlError(err) => {
-                format!("MySQL error: {err}")
-            }
+            DropUserError::UserDoesNotExist => tr(Message::UserDoesNotExist, &[("name", username)]),
+            DropUserError::MySqlError(err) => tr(Message::MySqlError, &[("error", err)]),
         }
     }
 
@@ -86,4 +117,12 @@ impl DropUserError {
             DropUserError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            DropUserError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::{database_privileges::DatabasePrivilegeRow, protocol::PrivilegeSnapshotTag};
+
+pub type GetPrivilegeSnapshotRequest = PrivilegeSnapshotTag;
+
+/// The privilege rows stored in the snapshot.
+pub type GetPrivilegeSnapshotResponse =
+    Result<Vec<DatabasePrivilegeRow>, GetPrivilegeSnapshotError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GetPrivilegeSnapshotError {
+    #[error("Privilege snapshots are not enabled on this server")]
+    Disabled,
+
+    #[error("Snapshot does not exist")]
+    SnapshotDoesNotExist,
+
+    #[error("Failed to read the snapshots: {0}")]
+    StoreError(String),
+}
+
+impl GetPrivilegeSnapshotError {
+    #[must_use]
+    pub fn to_error_message(&self, tag: &str) -> String {
+        match self {
+            GetPrivilegeSnapshotError::Disabled => {
+                "Privilege snapshots are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            GetPrivilegeSnapshotError::SnapshotDoesNotExist => {
+                format!("You do not have a snapshot with the tag '{tag}'.")
+            }
+            GetPrivilegeSnapshotError::StoreError(err) => {
+                format!("Failed to read the snapshots: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            GetPrivilegeSnapshotError::Disabled => "disabled".to_string(),
+            GetPrivilegeSnapshotError::SnapshotDoesNotExist => {
+                "snapshot-does-not-exist".to_string()
+            }
+            GetPrivilegeSnapshotError::StoreError(_) => "store-error".to_string(),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser},
+};
+
+/// The role to grant, and the users to grant it to.
+pub type GrantRolesRequest = (MySQLUser, Vec<MySQLUser>);
+
+pub type GrantRolesResponse = BTreeMap<MySQLUser, Result<(), GrantRoleError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GrantRoleError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("Role validation error: {0}")]
+    RoleValidationError(ValidationError),
+
+    #[error("Roles are not supported by the database server")]
+    RolesNotSupported,
+
+    #[error("Role does not exist")]
+    RoleDoesNotExist,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// A [`GrantRolesResponse`] together with the role that was granted.
+pub struct GrantRolesOutput<'a> {
+    pub role: &'a MySQLUser,
+    pub result: &'a GrantRolesResponse,
+}
+
+impl CommandOutput for GrantRolesOutput<'_> {
+    fn print_human(&self) {
+        let role = self.role;
+        for (username, result) in self.result {
+            match result {
+                Ok(()) => {
+                    println!("Role '{role}' granted to user '{username}' successfully.");
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        paint(Role::Error, &err.to_error_message(role, username))
+                    );
+                    eprintln!("Skipping...");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let role = self.role;
+        let value = self
+            .result
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(()) => (name.to_string(), json!({ "status": "success" })),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(role, name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.result
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(GrantRoleError::exit_code),
+        )
+    }
+}
+
+impl GrantRoleError {
+    #[must_use]
+    pub fn to_error_message(&self, role: &MySQLUser, username: &MySQLUser) -> String {
+        match self {
+            GrantRoleError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            GrantRoleError::RoleValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(role.clone()))
+            }
+            GrantRoleError::RolesNotSupported => {
+                "The database server does not support roles.".to_string()
+            }
+            GrantRoleError::RoleDoesNotExist => {
+                format!("Role '{role}' does not exist.")
+            }
+            GrantRoleError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            GrantRoleError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            GrantRoleError::ValidationError(err) | GrantRoleError::RoleValidationError(err) => {
+                err.error_type()
+            }
+            GrantRoleError::RolesNotSupported => "roles-not-supported".to_string(),
+            GrantRoleError::RoleDoesNotExist => "role-does-not-exist".to_string(),
+            GrantRoleError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            GrantRoleError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            GrantRoleError::ValidationError(err) | GrantRoleError::RoleValidationError(err) => {
+                err.exit_code()
+            }
+            _ => ExitCode::Failure,
+        }
+    }
+}
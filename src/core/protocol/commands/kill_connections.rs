@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser},
+};
+
+pub type KillConnectionsRequest = Vec<MySQLUser>;
+
+/// The ids of the connections that were killed for each user.
+pub type KillConnectionsResponse = BTreeMap<MySQLUser, Result<Vec<u64>, KillConnectionsError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KillConnectionsError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for KillConnectionsResponse {
+    fn print_human(&self) {
+        for (username, result) in self {
+            match result {
+                Ok(ids) if ids.is_empty() => {
+                    println!("User '{username}' has no open connections.");
+                }
+                Ok(ids) => {
+                    println!(
+                        "Killed {} connection(s) of user '{username}': {}",
+                        ids.len(),
+                        ids.iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    );
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("Skipping...");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(ids) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "killed_connections": ids,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(KillConnectionsError::exit_code),
+        )
+    }
+}
+
+impl KillConnectionsError {
+    #[must_use]
+    pub fn to_error_message(&self, username: &MySQLUser) -> String {
+        match self {
+            KillConnectionsError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            KillConnectionsError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            KillConnectionsError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            KillConnectionsError::ValidationError(err) => err.error_type(),
+            KillConnectionsError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            KillConnectionsError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            KillConnectionsError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
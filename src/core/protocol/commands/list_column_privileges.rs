@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::core::{
+    column_privileges::{COLUMN_PRIVILEGE_FIELDS, ColumnPrivilegeRow},
+    common::yn,
+    database_privileges::db_priv_field_human_readable_name,
+    exit_code::ExitCode,
+    output::CommandOutput,
+    pager::print_paged,
+    protocol::ListPrivilegesError,
+    style::{Role, paint},
+    table::{TableCell, TableColumn, TableViewArgs},
+    types::{MySQLDatabase, MySQLUser},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListColumnPrivilegesRequest {
+    /// The databases to list column privileges for, or all databases owned by the user if `None`.
+    pub databases: Option<Vec<MySQLDatabase>>,
+
+    /// Only list the column privileges of this user.
+    pub user: Option<MySQLUser>,
+}
+
+pub type ListColumnPrivilegesResponse =
+    BTreeMap<MySQLDatabase, Result<Vec<ColumnPrivilegeRow>, ListPrivilegesError>>;
+
+/// Column ids for `--columns` and `--sort-by`.
+pub const LIST_COLUMN_PRIVILEGES_COLUMNS: [&str; 8] = [
+    "database",
+    "table",
+    "column",
+    "user",
+    "select",
+    "insert",
+    "update",
+    "references",
+];
+
+/// A [`ListColumnPrivilegesResponse`], and how to display it.
+pub struct ListColumnPrivilegesOutput<'a> {
+    pub privileges: &'a ListColumnPrivilegesResponse,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListColumnPrivilegesOutput<'_> {
+    fn print_human(&self) {
+        let mut rows = Vec::new();
+        for (db_name, db_result) in self.privileges {
+            match db_result {
+                Ok(db_rows) => rows.extend(db_rows),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
+            }
+        }
+
+        if rows.is_empty() {
+            println!("No column privileges to show.");
+            return;
+        }
+
+        let columns = LIST_COLUMN_PRIVILEGES_COLUMNS
+            .into_iter()
+            .zip(["Database", "Table", "Column", "User"])
+            .map(|(id, header)| TableColumn::new(id, header))
+            .chain(
+                LIST_COLUMN_PRIVILEGES_COLUMNS
+                    .into_iter()
+                    .skip(4)
+                    .zip(COLUMN_PRIVILEGE_FIELDS)
+                    .map(|(id, field)| {
+                        TableColumn::new(id, db_priv_field_human_readable_name(field)).centered()
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                [
+                    row.db.as_str(),
+                    row.table.as_str(),
+                    row.column.as_str(),
+                    row.user.as_str(),
+                ]
+                .into_iter()
+                .map(TableCell::text)
+                .chain(COLUMN_PRIVILEGE_FIELDS.into_iter().map(|field| {
+                    TableCell::text(yn(row.get_privilege_by_name(field).unwrap_or(false)))
+                }))
+                .collect()
+            })
+            .collect();
+
+        print_paged(&self.table_view.render(&columns, rows).to_string());
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .privileges
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(rows) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "value": rows,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.privileges
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListPrivilegesError::exit_code),
+        )
+    }
+}
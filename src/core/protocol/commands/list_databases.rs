@@ -1,14 +1,18 @@
 use std::collections::BTreeMap;
 
 use itertools::Itertools;
-use prettytable::Table;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
 use crate::{
     core::{
+        exit_code::ExitCode,
+        output::CommandOutput,
+        pager::print_paged,
         protocol::request_validation::ValidationError,
+        style::{Role, paint},
+        table::{TableCell, TableColumn, TableViewArgs},
         types::{DbOrUser, MySQLDatabase},
     },
     server::sql::database_operations::DatabaseRow,
@@ -30,86 +34,139 @@ pub enum ListDatabasesError {
     MySqlError(String),
 }
 
-pub fn print_list_databases_output_status(
-    output: &ListDatabasesResponse,
-    display_size_as_bytes: bool,
-) {
-    let mut final_database_list: Vec<&DatabaseRow> = Vec::new();
-    for (db_name, db_result) in output {
-        match db_result {
-            Ok(db_row) => final_database_list.push(db_row),
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(db_name));
-                eprintln!("Skipping...");
+pub const LIST_DATABASES_COLUMNS: [&str; 8] = [
+    "database",
+    "tables",
+    "users",
+    "collation",
+    "character-set",
+    "size",
+    "rows",
+    "last-updated",
+];
+
+/// A [`ListDatabasesResponse`], and how to display it.
+pub struct ListDatabasesOutput<'a> {
+    pub databases: &'a ListDatabasesResponse,
+    /// The owner of each database, shown in an extra `owner` column if given.
+    pub owners: Option<&'a BTreeMap<MySQLDatabase, String>>,
+    pub display_size_as_bytes: bool,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListDatabasesOutput<'_> {
+    fn print_human(&self) {
+        let display_size_as_bytes = self.display_size_as_bytes;
+        let table_view = self.table_view;
+        let mut final_database_list: Vec<&DatabaseRow> = Vec::new();
+        for (db_name, db_result) in self.databases {
+            match db_result {
+                Ok(db_row) => final_database_list.push(db_row),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
             }
         }
-    }
 
-    if final_database_list.is_empty() {
-        println!("No databases to show.");
-    } else {
-        let mut table = Table::new();
-        table.add_row(row![
-            "Database",
-            "Tables",
-            "Users",
-            "Collation",
-            "Character Set",
-            if display_size_as_bytes {
-                "Size (Bytes)"
-            } else {
-                "Size"
+        if final_database_list.is_empty() {
+            println!("No databases to show.");
+        } else {
+            let mut columns = vec![
+                TableColumn::new("database", "Database"),
+                TableColumn::new("tables", "Tables"),
+                TableColumn::new("users", "Users"),
+                TableColumn::new("collation", "Collation"),
+                TableColumn::new("character-set", "Character Set"),
+                TableColumn::new(
+                    "size",
+                    if display_size_as_bytes {
+                        "Size (Bytes)"
+                    } else {
+                        "Size"
+                    },
+                ),
+                TableColumn::new("rows", "Rows (approx.)"),
+                TableColumn::new("last-updated", "Last Updated"),
+            ];
+            if self.owners.is_some() {
+                columns.insert(0, TableColumn::new("owner", "Owner"));
             }
-        ]);
-        for db in final_database_list {
-            table.add_row(row![
-                db.database,
-                db.tables.join("\n"),
-                db.users.iter().map(|user| user.as_str()).join("\n"),
-                db.collation.as_deref().unwrap_or("N/A"),
-                db.character_set.as_deref().unwrap_or("N/A"),
-                if display_size_as_bytes {
-                    db.size_bytes.to_string()
-                } else {
-                    humansize::format_size(db.size_bytes, humansize::DECIMAL)
-                }
-            ]);
+
+            let rows = final_database_list
+                .into_iter()
+                .map(|db| {
+                    let mut row = vec![
+                        TableCell::text(db.database.as_str()),
+                        TableCell::text(db.tables.join("\n")),
+                        TableCell::text(db.users.iter().map(|user| user.as_str()).join("\n")),
+                        TableCell::text(db.collation.as_deref().unwrap_or("N/A")),
+                        TableCell::text(db.character_set.as_deref().unwrap_or("N/A")),
+                        TableCell::number(
+                            if display_size_as_bytes {
+                                db.size_bytes.to_string()
+                            } else {
+                                humansize::format_size(db.size_bytes, humansize::DECIMAL)
+                            },
+                            db.size_bytes,
+                        ),
+                        TableCell::number(db.approximate_rows.to_string(), db.approximate_rows),
+                        TableCell::text(db.last_updated.as_deref().unwrap_or("Unknown")),
+                    ];
+                    if let Some(owners) = self.owners {
+                        let owner = owners.get(&db.database).map_or("", String::as_str);
+                        row.insert(0, TableCell::text(owner));
+                    }
+                    row
+                })
+                .collect();
+
+            print_paged(&table_view.render(&columns, rows).to_string());
         }
+    }
 
-        table.printstd();
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .databases
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(row) => {
+                    let mut value = json!({
+                      "status": "success",
+                      "tables": row.tables,
+                      "users": row.users,
+                      "collation": row.collation,
+                      "character_set": row.character_set,
+                      "size_bytes": row.size_bytes,
+                      "approximate_rows": row.approximate_rows,
+                      "last_updated": row.last_updated,
+                    });
+                    if let Some(owners) = self.owners {
+                        value["owner"] = json!(owners.get(name));
+                    }
+                    (name.to_string(), value)
+                }
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
     }
-}
 
-pub fn print_list_databases_output_status_json(output: &ListDatabasesResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(row) => (
-                name.to_string(),
-                json!({
-                  "status": "success",
-                  "tables": row.tables,
-                  "users": row.users,
-                  "collation": row.collation,
-                  "character_set": row.character_set,
-                  "size_bytes": row.size_bytes,
-                }),
-            ),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.databases
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListDatabasesError::exit_code),
+        )
+    }
 }
 
 impl ListDatabasesError {
@@ -136,4 +193,12 @@ impl ListDatabasesError {
             ListDatabasesError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ListDatabasesError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
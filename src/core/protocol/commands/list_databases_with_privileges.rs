@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    core::{
+        database_privileges::{DatabasePrivilegeRow, format_privileges_as_cli_string},
+        exit_code::ExitCode,
+        output::CommandOutput,
+        protocol::{ListAllDatabasesError, ListDatabasesError},
+        style::{Role, paint},
+        types::MySQLDatabase,
+    },
+    server::sql::database_operations::DatabaseRow,
+};
+
+pub type ListDatabasesWithPrivilegesRequest = Option<Vec<MySQLDatabase>>;
+
+/// A database, together with the privilege rows of all users on that database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseWithPrivileges {
+    pub database: DatabaseRow,
+    pub privileges: Vec<DatabasePrivilegeRow>,
+}
+
+pub type ListDatabasesWithPrivilegesResponse =
+    BTreeMap<MySQLDatabase, Result<DatabaseWithPrivileges, ListDatabasesError>>;
+
+pub type ListAllDatabasesWithPrivilegesResponse =
+    Result<Vec<DatabaseWithPrivileges>, ListAllDatabasesError>;
+
+impl CommandOutput for ListDatabasesWithPrivilegesResponse {
+    fn print_human(&self) {
+        for (name, result) in self {
+            match result {
+                Ok(DatabaseWithPrivileges {
+                    database: row,
+                    privileges,
+                }) => {
+                    println!(
+                        "Database '{name}' ({}):",
+                        humansize::format_size(row.size_bytes, humansize::DECIMAL)
+                    );
+                    if privileges.is_empty() {
+                        println!("  No users have privileges on this database.");
+                    }
+                    for priv_row in privileges {
+                        println!(
+                            "  {}: {}",
+                            priv_row.user,
+                            format_privileges_as_cli_string(priv_row)
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(name)));
+                    eprintln!("Skipping...");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+        .iter()
+        .map(|(name, result)| match result {
+            Ok(DatabaseWithPrivileges {
+                database: row,
+                privileges,
+            }) => (
+                name.to_string(),
+                json!({
+                  "status": "success",
+                  "tables": row.tables,
+                  "users": row.users,
+                  "collation": row.collation,
+                  "character_set": row.character_set,
+                  "size_bytes": row.size_bytes,
+                  "privileges": privileges.iter().into_group_map_by(|priv_row| priv_row.user.clone()),
+                }),
+            ),
+            Err(err) => (
+                name.to_string(),
+                json!({
+                  "status": "error",
+                  "type": err.error_type(),
+                  "error": err.to_error_message(name),
+                }),
+            ),
+        })
+        .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListDatabasesError::exit_code),
+        )
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    database_privileges::format_privileges_as_cli_string, exit_code::ExitCode,
+    output::CommandOutput, protocol::GrantOffer,
+};
+
+/// The pending grant offers made by the unix user,
+/// and those made to database users owned by the unix user.
+pub type ListGrantOffersResponse = Result<Vec<GrantOffer>, ListGrantOffersError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ListGrantOffersError {
+    #[error("Grant offers are not enabled on this server")]
+    Disabled,
+
+    #[error("Failed to read the offers: {0}")]
+    StoreError(String),
+}
+
+fn format_time_left(expires_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let hours = expires_at.saturating_sub(now) / (60 * 60);
+    if hours >= 48 {
+        format!("{} days", hours / 24)
+    } else {
+        format!("{hours} hours")
+    }
+}
+
+impl CommandOutput for [GrantOffer] {
+    fn print_human(&self) {
+        if self.is_empty() {
+            println!("No pending grant offers.");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.set_titles(row![
+            "ID",
+            "Database",
+            "User",
+            "Privileges",
+            "Offered by",
+            "Expires in"
+        ]);
+        for offer in self {
+            table.add_row(row![
+                r->offer.id,
+                offer.row.db,
+                offer.row.user,
+                format_privileges_as_cli_string(&offer.row),
+                offer.offered_by,
+                format_time_left(offer.expires_at),
+            ]);
+        }
+        table.printstd();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|offer| {
+                json!({
+                  "id": offer.id,
+                  "database": offer.row.db,
+                  "user": offer.row.user,
+                  "privileges": format_privileges_as_cli_string(&offer.row),
+                  "offered_by": offer.offered_by,
+                  "expires_at": offer.expires_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl ListGrantOffersError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            ListGrantOffersError::Disabled => {
+                "Grant offers are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            ListGrantOffersError::StoreError(err) => format!("Failed to read the offers: {err}"),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ListGrantOffersError::Disabled => "disabled".to_string(),
+            ListGrantOffersError::StoreError(_) => "store-error".to_string(),
+        }
+    }
+}
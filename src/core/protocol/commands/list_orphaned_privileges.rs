@@ -0,0 +1,92 @@
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    types::{MySQLDatabase, MySQLUser},
+};
+
+/// Privilege rows in `mysql.db` that refer to a database or a user that no longer exists,
+/// and that are owned by the unix user through either the database or the user name.
+pub type ListOrphanedPrivilegesResponse =
+    Result<Vec<OrphanedPrivilege>, ListOrphanedPrivilegesError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrphanedPrivilege {
+    pub database: MySQLDatabase,
+    pub user: MySQLUser,
+    pub database_exists: bool,
+    pub user_exists: bool,
+}
+
+impl OrphanedPrivilege {
+    #[must_use]
+    pub fn reason(&self) -> &'static str {
+        match (self.database_exists, self.user_exists) {
+            (false, false) => "Neither the database nor the user exists",
+            (false, true) => "The database does not exist",
+            (true, false) => "The user does not exist",
+            (true, true) => "Not orphaned",
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ListOrphanedPrivilegesError {
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for [OrphanedPrivilege] {
+    fn print_human(&self) {
+        if self.is_empty() {
+            println!("No orphaned privileges found.");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.set_titles(row!["Database", "User", "Reason"]);
+        for row in self {
+            table.add_row(row![row.database, row.user, row.reason()]);
+        }
+        table.printstd();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|row| {
+                json!({
+                  "database": row.database,
+                  "user": row.user,
+                  "database_exists": row.database_exists,
+                  "user_exists": row.user_exists,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl ListOrphanedPrivilegesError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            ListOrphanedPrivilegesError::MySqlError(err) => format!("MySQL error: {err}"),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ListOrphanedPrivilegesError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+}
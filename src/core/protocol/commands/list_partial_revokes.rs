@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    style::{Role, paint},
+    types::{MySQLDatabase, MySQLUser},
+};
+
+pub type ListPartialRevokesRequest = Vec<MySQLUser>;
+
+/// Partial revokes for each of the requested users.
+///
+/// Users without partial revokes, and users that the client is not
+/// authorized to manage, are left out of the response.
+pub type ListPartialRevokesResponse = BTreeMap<MySQLUser, Vec<PartialRevoke>>;
+
+/// A partial revoke (MySQL 8.0.16+) of global privileges on a single database.
+///
+/// These take precedence over the database-level privileges, so the privileges
+/// shown by `show-privs` might not be what the user effectively has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialRevoke {
+    pub database: MySQLDatabase,
+    pub privileges: Vec<String>,
+}
+
+pub fn print_partial_revokes_warnings(output: &ListPartialRevokesResponse) {
+    for (username, revokes) in output {
+        for revoke in revokes {
+            let warning = format!(
+                "Warning: user '{}' has partially revoked privileges on database '{}' ({}), which are not reflected above.",
+                username,
+                revoke.database,
+                revoke.privileges.join(", "),
+            );
+            eprintln!("{}", paint(Role::Warning, &warning));
+        }
+    }
+}
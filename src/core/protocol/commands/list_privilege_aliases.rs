@@ -0,0 +1,5 @@
+use std::collections::BTreeMap;
+
+/// The privilege aliases configured on the server, mapping each alias
+/// to the `edit-privs` privilege characters it stands for.
+pub type ListPrivilegeAliasesResponse = BTreeMap<String, String>;
@@ -0,0 +1,96 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{exit_code::ExitCode, output::CommandOutput, protocol::PrivilegeSnapshotInfo};
+
+/// The privilege snapshots taken by the unix user, oldest first.
+pub type ListPrivilegeSnapshotsResponse =
+    Result<Vec<PrivilegeSnapshotInfo>, ListPrivilegeSnapshotsError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ListPrivilegeSnapshotsError {
+    #[error("Privilege snapshots are not enabled on this server")]
+    Disabled,
+
+    #[error("Failed to read the snapshots: {0}")]
+    StoreError(String),
+}
+
+fn format_age(created_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let minutes = now.saturating_sub(created_at) / 60;
+    if minutes >= 48 * 60 {
+        format!("{} days ago", minutes / (24 * 60))
+    } else if minutes >= 60 {
+        format!("{} hours ago", minutes / 60)
+    } else {
+        format!("{minutes} minutes ago")
+    }
+}
+
+impl CommandOutput for [PrivilegeSnapshotInfo] {
+    fn print_human(&self) {
+        if self.is_empty() {
+            println!("No privilege snapshots.");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.set_titles(row!["Tag", "Taken", "Privilege rows"]);
+        for snapshot in self {
+            table.add_row(row![
+                snapshot.tag,
+                format_age(snapshot.created_at),
+                r->snapshot.rows,
+            ]);
+        }
+        table.printstd();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|snapshot| {
+                json!({
+                  "tag": snapshot.tag,
+                  "created_at": snapshot.created_at,
+                  "rows": snapshot.rows,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl ListPrivilegeSnapshotsError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            ListPrivilegeSnapshotsError::Disabled => {
+                "Privilege snapshots are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            ListPrivilegeSnapshotsError::StoreError(err) => {
+                format!("Failed to read the snapshots: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ListPrivilegeSnapshotsError::Disabled => "disabled".to_string(),
+            ListPrivilegeSnapshotsError::StoreError(_) => "store-error".to_string(),
+        }
+    }
+}
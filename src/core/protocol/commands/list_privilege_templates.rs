@@ -0,0 +1,5 @@
+use std::collections::BTreeMap;
+
+/// The privilege templates for the name prefixes available to the user,
+/// keyed by prefix, in the same format as the `edit-privs` privilege characters.
+pub type ListPrivilegeTemplatesResponse = BTreeMap<String, String>;
@@ -5,7 +5,6 @@
 use std::collections::BTreeMap;
 
 use itertools::Itertools;
-use prettytable::{Cell, Row, Table};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
@@ -14,106 +13,206 @@ use crate::core::{
     common::yn,
     database_privileges::{
         DATABASE_PRIVILEGE_FIELDS, DatabasePrivilegeRow, db_priv_field_human_readable_name,
-        db_priv_field_single_character_name,
+        db_priv_field_single_character_name, format_privileges_as_cli_string,
+        format_privileges_as_grant_statement,
     },
+    exit_code::ExitCode,
+    output::CommandOutput,
+    pager::print_paged,
     protocol::request_validation::ValidationError,
-    types::{DbOrUser, MySQLDatabase},
+    style::{Role, paint},
+    table::{TableCell, TableColumn, TableViewArgs},
+    types::{DbOrUser, MySQLDatabase, MySQLUser},
 };
 
-pub type ListPrivilegesRequest = Option<Vec<MySQLDatabase>>;
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListPrivilegesRequest {
+    /// The databases to list privileges for, or all databases owned by the user if `None`.
+    pub databases: Option<Vec<MySQLDatabase>>,
+
+    /// Only list the privilege rows of this user.
+    pub user: Option<MySQLUser>,
+}
 
 pub type ListPrivilegesResponse =
     BTreeMap<MySQLDatabase, Result<Vec<DatabasePrivilegeRow>, ListPrivilegesError>>;
 
-pub fn print_list_privileges_output_status(output: &ListPrivilegesResponse, long_names: bool) {
-    let mut final_privs_map: BTreeMap<MySQLDatabase, Vec<DatabasePrivilegeRow>> = BTreeMap::new();
-    for (db_name, db_result) in output {
-        match db_result {
-            Ok(db_rows) => {
-                final_privs_map.insert(db_name.clone(), db_rows.clone());
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(db_name));
-                eprintln!("Skipping...");
+/// Column ids for `--columns` and `--sort-by`, in the same order as [`DATABASE_PRIVILEGE_FIELDS`].
+pub const LIST_PRIVILEGES_COLUMNS: [&str; 21] = [
+    "database",
+    "user",
+    "select",
+    "insert",
+    "update",
+    "delete",
+    "create",
+    "drop",
+    "alter",
+    "index",
+    "create-tmp-table",
+    "lock-tables",
+    "references",
+    "create-view",
+    "show-view",
+    "trigger",
+    "event",
+    "execute",
+    "create-routine",
+    "alter-routine",
+    "grant",
+];
+
+/// Column ids for `--columns` and `--sort-by` when using `--compact`.
+pub const LIST_PRIVILEGES_COMPACT_COLUMNS: [&str; 3] = ["database", "user", "privileges"];
+
+/// A [`ListPrivilegesResponse`], and how to display it.
+pub struct ListPrivilegesOutput<'a> {
+    pub privileges: &'a ListPrivilegesResponse,
+    pub long_names: bool,
+    pub compact: bool,
+    /// The privilege fields to show columns for, see
+    /// [`database_privilege_fields`](crate::core::database_privileges::database_privilege_fields).
+    pub fields: &'a [&'a str],
+    /// Print the privileges as `GRANT` statements instead of a table.
+    pub as_sql: bool,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListPrivilegesOutput<'_> {
+    fn print_human(&self) {
+        let long_names = self.long_names;
+        let compact = self.compact;
+        let table_view = self.table_view;
+        let mut final_privs_map: BTreeMap<MySQLDatabase, Vec<DatabasePrivilegeRow>> =
+            BTreeMap::new();
+        for (db_name, db_result) in self.privileges {
+            match db_result {
+                Ok(db_rows) => {
+                    final_privs_map.insert(db_name.clone(), db_rows.clone());
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
             }
         }
-    }
 
-    if final_privs_map.is_empty() {
-        println!("No privileges to show.");
-    } else {
-        let mut table = Table::new();
+        if self.as_sql {
+            let statements = final_privs_map
+                .values()
+                .flatten()
+                .filter_map(format_privileges_as_grant_statement)
+                .collect::<Vec<_>>();
+            if statements.is_empty() {
+                eprintln!("No privileges to show.");
+            } else {
+                println!("{}", statements.join("\n"));
+            }
+        } else if final_privs_map.is_empty() {
+            println!("No privileges to show.");
+        } else if compact {
+            let columns = [
+                TableColumn::new("database", "Database"),
+                TableColumn::new("user", "User"),
+                TableColumn::new("privileges", "Privileges"),
+            ];
+
+            let rows = final_privs_map
+                .values()
+                .flatten()
+                .map(|row| {
+                    let privileges = format_privileges_as_cli_string(row);
+                    vec![
+                        TableCell::text(row.db.as_str()),
+                        TableCell::text(row.user.as_str()),
+                        TableCell::text(if privileges.is_empty() {
+                            "-".to_string()
+                        } else {
+                            privileges
+                        }),
+                    ]
+                })
+                .collect();
 
-        table.add_row(Row::new(
-            DATABASE_PRIVILEGE_FIELDS
+            print_paged(&table_view.render(&columns, rows).to_string());
+        } else {
+            let columns = LIST_PRIVILEGES_COLUMNS
                 .into_iter()
-                .map(|field| {
+                .zip(DATABASE_PRIVILEGE_FIELDS)
+                .filter(|(_, field)| self.fields.contains(field))
+                .map(|(id, field)| {
                     if field == "Db" || field == "User" {
-                        db_priv_field_human_readable_name(field)
+                        TableColumn::new(id, db_priv_field_human_readable_name(field))
                     } else if long_names {
-                        format!(
-                            "{} ({})",
-                            db_priv_field_human_readable_name(field),
-                            db_priv_field_single_character_name(field),
+                        TableColumn::new(
+                            id,
+                            format!(
+                                "{} ({})",
+                                db_priv_field_human_readable_name(field),
+                                db_priv_field_single_character_name(field),
+                            ),
                         )
+                        .centered()
                     } else {
-                        db_priv_field_human_readable_name(field)
+                        TableColumn::new(id, db_priv_field_human_readable_name(field)).centered()
                     }
                 })
-                .map(|name| Cell::new(&name))
-                .collect(),
-        ));
-
-        for (_database, rows) in final_privs_map {
-            for row in &rows {
-                table.add_row(row![
-                    row.db,
-                    row.user,
-                    c->yn(row.select_priv),
-                    c->yn(row.insert_priv),
-                    c->yn(row.update_priv),
-                    c->yn(row.delete_priv),
-                    c->yn(row.create_priv),
-                    c->yn(row.drop_priv),
-                    c->yn(row.alter_priv),
-                    c->yn(row.index_priv),
-                    c->yn(row.create_tmp_table_priv),
-                    c->yn(row.lock_tables_priv),
-                    c->yn(row.references_priv),
-                ]);
-            }
+                .collect::<Vec<_>>();
+
+            let rows = final_privs_map
+                .values()
+                .flatten()
+                .map(|row| {
+                    self.fields
+                        .iter()
+                        .map(|&field| match field {
+                            "Db" => TableCell::text(row.db.as_str()),
+                            "User" => TableCell::text(row.user.as_str()),
+                            privilege => TableCell::text(yn(row
+                                .get_privilege_by_name(privilege)
+                                .unwrap_or(false))),
+                        })
+                        .collect()
+                })
+                .collect();
+
+            print_paged(&table_view.render(&columns, rows).to_string());
         }
+    }
 
-        table.printstd();
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .privileges
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(row) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "value": row.iter().into_group_map_by(|priv_row| priv_row.user.clone()),
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
     }
-}
 
-pub fn print_list_privileges_output_status_json(output: &ListPrivilegesResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(row) => (
-                name.to_string(),
-                json!({
-                  "status": "success",
-                  "value": row.iter().into_group_map_by(|priv_row| priv_row.user.clone()),
-                }),
-            ),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.privileges
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListPrivilegesError::exit_code),
+        )
+    }
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -152,4 +251,12 @@ impl ListPrivilegesError {
             ListPrivilegesError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ListPrivilegesError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
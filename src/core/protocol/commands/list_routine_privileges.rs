@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::core::{
+    common::yn,
+    database_privileges::db_priv_field_human_readable_name,
+    exit_code::ExitCode,
+    output::CommandOutput,
+    pager::print_paged,
+    protocol::ListPrivilegesError,
+    routine_privileges::{ROUTINE_PRIVILEGE_FIELDS, RoutinePrivilegeRow},
+    style::{Role, paint},
+    table::{TableCell, TableColumn, TableViewArgs},
+    types::{MySQLDatabase, MySQLUser},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListRoutinePrivilegesRequest {
+    /// The databases to list routine privileges for, or all databases owned by the user if `None`.
+    pub databases: Option<Vec<MySQLDatabase>>,
+
+    /// Only list the routine privileges of this user.
+    pub user: Option<MySQLUser>,
+}
+
+pub type ListRoutinePrivilegesResponse =
+    BTreeMap<MySQLDatabase, Result<Vec<RoutinePrivilegeRow>, ListPrivilegesError>>;
+
+/// Column ids for `--columns` and `--sort-by`.
+pub const LIST_ROUTINE_PRIVILEGES_COLUMNS: [&str; 6] = [
+    "database",
+    "type",
+    "routine",
+    "user",
+    "execute",
+    "alter-routine",
+];
+
+/// A [`ListRoutinePrivilegesResponse`], and how to display it.
+pub struct ListRoutinePrivilegesOutput<'a> {
+    pub privileges: &'a ListRoutinePrivilegesResponse,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListRoutinePrivilegesOutput<'_> {
+    fn print_human(&self) {
+        let mut rows = Vec::new();
+        for (db_name, db_result) in self.privileges {
+            match db_result {
+                Ok(db_rows) => rows.extend(db_rows),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
+            }
+        }
+
+        if rows.is_empty() {
+            println!("No routine privileges to show.");
+            return;
+        }
+
+        let columns = LIST_ROUTINE_PRIVILEGES_COLUMNS
+            .into_iter()
+            .zip(["Database", "Type", "Routine", "User"])
+            .map(|(id, header)| TableColumn::new(id, header))
+            .chain(
+                LIST_ROUTINE_PRIVILEGES_COLUMNS
+                    .into_iter()
+                    .skip(4)
+                    .zip(ROUTINE_PRIVILEGE_FIELDS)
+                    .map(|(id, field)| {
+                        TableColumn::new(id, db_priv_field_human_readable_name(field)).centered()
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                [
+                    row.db.to_string(),
+                    row.routine_type.to_string(),
+                    row.routine.clone(),
+                    row.user.to_string(),
+                ]
+                .into_iter()
+                .map(TableCell::text)
+                .chain(ROUTINE_PRIVILEGE_FIELDS.into_iter().map(|field| {
+                    TableCell::text(yn(row.get_privilege_by_name(field).unwrap_or(false)))
+                }))
+                .collect()
+            })
+            .collect();
+
+        print_paged(&self.table_view.render(&columns, rows).to_string());
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .privileges
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(rows) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "value": rows,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.privileges
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListPrivilegesError::exit_code),
+        )
+    }
+}
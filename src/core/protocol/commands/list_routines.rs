@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use serde_json::json;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    pager::print_paged,
+    protocol::ListDatabasesError,
+    routine_privileges::StoredRoutine,
+    style::{Role, paint},
+    table::{TableCell, TableColumn, TableViewArgs},
+    types::MySQLDatabase,
+};
+
+/// The databases to list stored routines in, or all databases owned by the user if `None`.
+pub type ListRoutinesRequest = Option<Vec<MySQLDatabase>>;
+
+pub type ListRoutinesResponse =
+    BTreeMap<MySQLDatabase, Result<Vec<StoredRoutine>, ListDatabasesError>>;
+
+/// Column ids for `--columns` and `--sort-by`.
+pub const LIST_ROUTINES_COLUMNS: [&str; 3] = ["database", "type", "routine"];
+
+/// A [`ListRoutinesResponse`], and how to display it.
+pub struct ListRoutinesOutput<'a> {
+    pub routines: &'a ListRoutinesResponse,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListRoutinesOutput<'_> {
+    fn print_human(&self) {
+        let mut routines = Vec::new();
+        for (db_name, db_result) in self.routines {
+            match db_result {
+                Ok(db_routines) => routines.extend(db_routines),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
+            }
+        }
+
+        if routines.is_empty() {
+            println!("No stored routines to show.");
+            return;
+        }
+
+        let columns = vec![
+            TableColumn::new("database", "Database"),
+            TableColumn::new("type", "Type"),
+            TableColumn::new("routine", "Routine"),
+        ];
+
+        let rows = routines
+            .into_iter()
+            .map(|routine| {
+                vec![
+                    TableCell::text(routine.db.as_str()),
+                    TableCell::text(routine.routine_type.to_string()),
+                    TableCell::text(routine.name.as_str()),
+                ]
+            })
+            .collect();
+
+        print_paged(&self.table_view.render(&columns, rows).to_string());
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .routines
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(routines) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "value": routines,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.routines
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListDatabasesError::exit_code),
+        )
+    }
+}
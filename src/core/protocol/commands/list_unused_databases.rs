@@ -0,0 +1,8 @@
+use crate::core::protocol::{ListAllDatabasesError, ListAllDatabasesResponse};
+
+/// Databases owned by the unix user that no existing user has any privileges on.
+///
+/// These are likely forgotten, and candidates for being archived or dropped.
+pub type ListUnusedDatabasesResponse = ListAllDatabasesResponse;
+
+pub type ListUnusedDatabasesError = ListAllDatabasesError;
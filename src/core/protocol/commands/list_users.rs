@@ -1,16 +1,20 @@
 use std::collections::BTreeMap;
 
-use prettytable::Table;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
 use crate::{
     core::{
+        exit_code::ExitCode,
+        output::CommandOutput,
+        pager::print_paged,
         protocol::request_validation::ValidationError,
+        style::{Role, paint},
+        table::{TableCell, TableColumn, TableViewArgs},
         types::{DbOrUser, MySQLUser},
     },
-    server::sql::user_operations::DatabaseUser,
+    server::sql::user_operations::{DatabaseUser, LockReason},
 };
 
 pub type ListUsersRequest = Option<Vec<MySQLUser>>;
@@ -29,71 +33,153 @@ pub enum ListUsersError {
     MySqlError(String),
 }
 
-pub fn print_list_users_output_status(output: &ListUsersResponse) {
-    let mut final_user_list: Vec<&DatabaseUser> = Vec::new();
-    for (db_name, db_result) in output {
-        match db_result {
-            Ok(db_row) => final_user_list.push(db_row),
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(db_name));
-                eprintln!("Skipping...");
+pub const LIST_USERS_COLUMNS: [&str; 9] = [
+    "user",
+    "has-password",
+    "locked",
+    "lock-reason",
+    "tls",
+    "databases",
+    "comment",
+    "connections",
+    "total-connections",
+];
+
+/// A connection count, or `Unknown` if the database server could not tell.
+fn connection_count_cell(count: Option<u64>) -> TableCell {
+    match count {
+        Some(count) => TableCell::number(count.to_string(), count),
+        None => TableCell::number("Unknown", 0),
+    }
+}
+
+fn lock_reason_text(lock_reason: Option<&LockReason>) -> String {
+    match lock_reason {
+        Some(LockReason {
+            reason,
+            locked_by: Some(locked_by),
+        }) => format!("{reason} (by {locked_by})"),
+        Some(LockReason {
+            reason,
+            locked_by: None,
+        }) => reason.clone(),
+        None => String::new(),
+    }
+}
+
+/// A [`ListUsersResponse`], and how to display it.
+pub struct ListUsersOutput<'a> {
+    pub users: &'a ListUsersResponse,
+    /// The owner of each user, shown in an extra `owner` column if given.
+    pub owners: Option<&'a BTreeMap<MySQLUser, String>>,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for ListUsersOutput<'_> {
+    fn print_human(&self) {
+        let table_view = self.table_view;
+        let mut final_user_list: Vec<&DatabaseUser> = Vec::new();
+        for (db_name, db_result) in self.users {
+            match db_result {
+                Ok(db_row) => final_user_list.push(db_row),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(db_name)));
+                    eprintln!("Skipping...");
+                }
             }
         }
-    }
 
-    if final_user_list.is_empty() {
-        println!("No users to show.");
-    } else {
-        let mut table = Table::new();
-        table.add_row(row![
-            "User",
-            "Password is set",
-            "Locked",
-            "Databases where user has privileges"
-        ]);
-        for user in final_user_list {
-            table.add_row(row![
-                user.user,
-                user.has_password,
-                user.is_locked,
-                user.databases.join("\n")
-            ]);
+        if final_user_list.is_empty() {
+            println!("No users to show.");
+        } else {
+            let mut columns = vec![
+                TableColumn::new("user", "User"),
+                TableColumn::new("has-password", "Password is set"),
+                TableColumn::new("locked", "Locked"),
+                TableColumn::new("lock-reason", "Lock reason"),
+                TableColumn::new("tls", "Required TLS"),
+                TableColumn::new("databases", "Databases where user has privileges"),
+                TableColumn::new("comment", "Comment"),
+                TableColumn::new("connections", "Open connections"),
+                TableColumn::new("total-connections", "Connections since server start"),
+            ];
+            if self.owners.is_some() {
+                columns.insert(0, TableColumn::new("owner", "Owner"));
+            }
+
+            let rows = final_user_list
+                .into_iter()
+                .map(|user| {
+                    let mut row = vec![
+                        TableCell::text(user.user.as_str()),
+                        TableCell::text(user.has_password.to_string()),
+                        TableCell::text(user.is_locked.to_string()),
+                        TableCell::text(lock_reason_text(user.lock_reason.as_ref())),
+                        TableCell::text(user.tls_requirement.to_string()),
+                        TableCell::text(user.databases.join("\n")),
+                        TableCell::text(user.comment.clone().unwrap_or_default()),
+                        connection_count_cell(user.current_connections),
+                        connection_count_cell(user.total_connections),
+                    ];
+                    if let Some(owners) = self.owners {
+                        let owner = owners.get(&user.user).map_or("", String::as_str);
+                        row.insert(0, TableCell::text(owner));
+                    }
+                    row
+                })
+                .collect();
+
+            print_paged(&table_view.render(&columns, rows).to_string());
         }
-        table.printstd();
     }
-}
 
-pub fn print_list_users_output_status_json(output: &ListUsersResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(row) => (
-                name.to_string(),
-                json!({
-                  "status": "success",
-                  "value": {
-                    "user": row.user,
-                    "has_password": row.has_password,
-                    "is_locked": row.is_locked,
-                    "databases": row.databases,
-                  }
-                }),
-            ),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .users
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(row) => {
+                    let mut value = json!({
+                      "status": "success",
+                      "value": {
+                        "user": row.user,
+                        "has_password": row.has_password,
+                        "is_locked": row.is_locked,
+                        "lock_reason": row.lock_reason,
+                        "tls_requirement": row.tls_requirement,
+                        "limits": row.limits,
+                        "databases": row.databases,
+                        "comment": row.comment,
+                        "current_connections": row.current_connections,
+                        "total_connections": row.total_connections,
+                      }
+                    });
+                    if let Some(owners) = self.owners {
+                        value["value"]["owner"] = json!(owners.get(name));
+                    }
+                    (name.to_string(), value)
+                }
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.users
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ListUsersError::exit_code),
+        )
+    }
 }
 
 impl ListUsersError {
@@ -120,4 +206,12 @@ impl ListUsersError {
             ListUsersError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ListUsersError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
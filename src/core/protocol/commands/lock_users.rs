@@ -5,11 +5,23 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    output::{BulkSummary, CommandOutput},
     protocol::request_validation::ValidationError,
+    style::{Role, paint},
     types::{DbOrUser, MySQLUser},
 };
 
-pub type LockUsersRequest = Vec<MySQLUser>;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockUsersRequest {
+    pub users: Vec<MySQLUser>,
+
+    /// Also lock every user under this prefix, resolved by the server.
+    pub all_with_prefix: Option<String>,
+
+    /// Why the users are being locked, stored with the user to be shown by `show-user`.
+    pub reason: Option<String>,
+}
 
 pub type LockUsersResponse = BTreeMap<MySQLUser, Result<(), LockUserError>>;
 
@@ -24,48 +36,66 @@ pub enum LockUserError {
     #[error("User is already locked")]
     UserIsAlreadyLocked,
 
+    #[error("Lock reasons are not supported by the database server")]
+    LockReasonsNotSupported,
+
     #[error("MySQL error: {0}")]
     MySqlError(String),
 }
 
-pub fn print_lock_users_output_status(output: &LockUsersResponse) {
-    for (username, result) in output {
-        match result {
-            Ok(()) => {
-                println!("User '{username}' locked successfully.");
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(username));
-                eprintln!("Skipping...");
+impl CommandOutput for LockUsersResponse {
+    fn print_human(&self) {
+        for (username, result) in self {
+            match result {
+                Ok(()) => {
+                    println!("User '{username}' locked successfully.");
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("Skipping...");
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), LockUserError::is_skipped).print_footer();
     }
-}
 
-pub fn print_lock_users_output_status_json(output: &LockUsersResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(()) => (name.to_string(), json!({ "status": "success" })),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), LockUserError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(LockUserError::exit_code),
+        )
+    }
 }
 
 impl LockUserError {
+    /// Whether the user was already locked.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, LockUserError::UserIsAlreadyLocked)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, username: &MySQLUser) -> String {
         match self {
@@ -78,6 +108,10 @@ impl LockUserError {
             LockUserError::UserIsAlreadyLocked => {
                 format!("User '{username}' is already locked.")
             }
+            LockUserError::LockReasonsNotSupported => {
+                "The database server does not support storing a reason for locking users."
+                    .to_string()
+            }
             LockUserError::MySqlError(err) => {
                 format!("MySQL error: {err}")
             }
@@ -90,7 +124,16 @@ impl LockUserError {
             LockUserError::ValidationError(err) => err.error_type(),
             LockUserError::UserDoesNotExist => "user-does-not-exist".to_string(),
             LockUserError::UserIsAlreadyLocked => "user-is-already-locked".to_string(),
+            LockUserError::LockReasonsNotSupported => "lock-reasons-not-supported".to_string(),
             LockUserError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            LockUserError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
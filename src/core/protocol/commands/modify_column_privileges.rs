@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    column_privileges::{ColumnPrivilegeKey, ColumnPrivilegesDiff},
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::DbOrUser,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifyColumnPrivilegesRequest {
+    pub diffs: BTreeSet<ColumnPrivilegesDiff>,
+}
+
+pub type ModifyColumnPrivilegesResponse =
+    BTreeMap<ColumnPrivilegeKey, Result<(), ModifyColumnPrivilegesError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ModifyColumnPrivilegesError {
+    #[error("Database validation error: {0}")]
+    DatabaseValidationError(ValidationError),
+
+    #[error("User validation error: {0}")]
+    UserValidationError(ValidationError),
+
+    #[error("Database does not exist")]
+    DatabaseDoesNotExist,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("Column does not exist")]
+    ColumnDoesNotExist,
+
+    #[error("Diff does not apply to the current column privileges")]
+    DiffDoesNotApply,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for ModifyColumnPrivilegesResponse {
+    fn print_human(&self) {
+        for (key, result) in self {
+            match result {
+                Ok(()) => {
+                    println!(
+                        "Privileges for user '{}' on column '{}.{}.{}' modified successfully.",
+                        key.user, key.db, key.table, key.column
+                    );
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(key)));
+                    eprintln!("Skipping...");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(key, result)| match result {
+                Ok(()) => json!({
+                  "database": key.db,
+                  "table": key.table,
+                  "column": key.column,
+                  "user": key.user,
+                  "status": "success",
+                }),
+                Err(err) => json!({
+                  "database": key.db,
+                  "table": key.table,
+                  "column": key.column,
+                  "user": key.user,
+                  "status": "error",
+                  "type": err.error_type(),
+                  "error": err.to_error_message(key),
+                }),
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ModifyColumnPrivilegesError::exit_code),
+        )
+    }
+}
+
+impl ModifyColumnPrivilegesError {
+    #[must_use]
+    pub fn to_error_message(&self, key: &ColumnPrivilegeKey) -> String {
+        match self {
+            ModifyColumnPrivilegesError::DatabaseValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(key.db.clone()))
+            }
+            ModifyColumnPrivilegesError::UserValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(key.user.clone()))
+            }
+            ModifyColumnPrivilegesError::DatabaseDoesNotExist => {
+                format!("Database '{}' does not exist.", key.db)
+            }
+            ModifyColumnPrivilegesError::UserDoesNotExist => {
+                format!("User '{}' does not exist.", key.user)
+            }
+            ModifyColumnPrivilegesError::ColumnDoesNotExist => {
+                format!(
+                    "Column '{}' does not exist in table '{}' of database '{}'.",
+                    key.column, key.table, key.db
+                )
+            }
+            ModifyColumnPrivilegesError::DiffDoesNotApply => {
+                format!(
+                    "The privileges for user '{}' on column '{}.{}.{}' have changed since they were listed.",
+                    key.user, key.db, key.table, key.column
+                )
+            }
+            ModifyColumnPrivilegesError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ModifyColumnPrivilegesError::DatabaseValidationError(err) => {
+                err.error_type() + "/database"
+            }
+            ModifyColumnPrivilegesError::UserValidationError(err) => err.error_type() + "/user",
+            ModifyColumnPrivilegesError::DatabaseDoesNotExist => {
+                "database-does-not-exist".to_string()
+            }
+            ModifyColumnPrivilegesError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            ModifyColumnPrivilegesError::ColumnDoesNotExist => "column-does-not-exist".to_string(),
+            ModifyColumnPrivilegesError::DiffDoesNotApply => "diff-does-not-apply".to_string(),
+            ModifyColumnPrivilegesError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ModifyColumnPrivilegesError::DatabaseValidationError(err)
+            | ModifyColumnPrivilegesError::UserValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
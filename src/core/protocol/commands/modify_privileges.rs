@@ -1,15 +1,26 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
     database_privileges::{DatabasePrivilegeRow, DatabasePrivilegeRowDiff, DatabasePrivilegesDiff},
+    exit_code::ExitCode,
+    output::CommandOutput,
     protocol::request_validation::ValidationError,
+    style::{Role, paint},
     types::{DbOrUser, MySQLDatabase, MySQLUser},
 };
 
-pub type ModifyPrivilegesRequest = BTreeSet<DatabasePrivilegesDiff>;
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifyPrivilegesRequest {
+    pub diffs: BTreeSet<DatabasePrivilegesDiff>,
+
+    /// Validate every diff before applying any of them, and apply them in a single
+    /// transaction, so that either all of them or none of them are applied.
+    pub atomic: bool,
+}
 
 pub type ModifyPrivilegesResponse =
     BTreeMap<(MySQLDatabase, MySQLUser), Result<(), ModifyDatabasePrivilegesError>>;
@@ -33,6 +44,9 @@ pub enum ModifyDatabasePrivilegesError {
 
     #[error("MySQL error: {0}")]
     MySqlError(String),
+
+    #[error("Not applied, because another change in the atomic request failed")]
+    AtomicRequestFailed,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -48,20 +62,54 @@ pub enum DiffDoesNotApplyError {
     RowPrivilegeChangeDoesNotApply(DatabasePrivilegeRowDiff, DatabasePrivilegeRow),
 }
 
-pub fn print_modify_database_privileges_output_status(output: &ModifyPrivilegesResponse) {
-    for ((database_name, username), result) in output {
-        match result {
-            Ok(()) => {
-                println!(
-                    "Privileges for user '{username}' on database '{database_name}' modified successfully."
-                );
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(database_name, username));
-                eprintln!("Skipping...");
+impl CommandOutput for ModifyPrivilegesResponse {
+    fn print_human(&self) {
+        for ((database_name, username), result) in self {
+            match result {
+                Ok(()) => {
+                    println!(
+                        "Privileges for user '{username}' on database '{database_name}' modified successfully."
+                    );
+                }
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        paint(Role::Error, &err.to_error_message(database_name, username))
+                    );
+                    eprintln!("Skipping...");
+                }
             }
+            println!();
         }
-        println!();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|((database_name, username), result)| match result {
+                Ok(()) => json!({
+                  "database": database_name,
+                  "user": username,
+                  "status": "success",
+                }),
+                Err(err) => json!({
+                  "database": database_name,
+                  "user": username,
+                  "status": "error",
+                  "type": err.error_type(),
+                  "error": err.to_error_message(database_name, username),
+                }),
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ModifyDatabasePrivilegesError::exit_code),
+        )
     }
 }
 
@@ -90,10 +138,12 @@ impl ModifyDatabasePrivilegesError {
             ModifyDatabasePrivilegesError::MySqlError(err) => {
                 format!("MySQL error: {err}")
             }
+            ModifyDatabasePrivilegesError::AtomicRequestFailed => format!(
+                "Privileges for user '{username}' on database '{database_name}' were not modified, because another change failed."
+            ),
         }
     }
 
-    #[allow(dead_code)]
     #[must_use]
     pub fn error_type(&self) -> String {
         match self {
@@ -109,6 +159,18 @@ impl ModifyDatabasePrivilegesError {
                 format!("diff-does-not-apply/{}", err.error_type())
             }
             ModifyDatabasePrivilegesError::MySqlError(_) => "mysql-error".to_string(),
+            ModifyDatabasePrivilegesError::AtomicRequestFailed => {
+                "atomic-request-failed".to_string()
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ModifyDatabasePrivilegesError::DatabaseValidationError(err)
+            | ModifyDatabasePrivilegesError::UserValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
         }
     }
 }
@@ -0,0 +1,162 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    routine_privileges::{RoutinePrivilegeKey, RoutinePrivilegesDiff},
+    style::{Role, paint},
+    types::DbOrUser,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifyRoutinePrivilegesRequest {
+    pub diffs: BTreeSet<RoutinePrivilegesDiff>,
+}
+
+pub type ModifyRoutinePrivilegesResponse =
+    BTreeMap<RoutinePrivilegeKey, Result<(), ModifyRoutinePrivilegesError>>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ModifyRoutinePrivilegesError {
+    #[error("Database validation error: {0}")]
+    DatabaseValidationError(ValidationError),
+
+    #[error("User validation error: {0}")]
+    UserValidationError(ValidationError),
+
+    #[error("Database does not exist")]
+    DatabaseDoesNotExist,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("Routine does not exist")]
+    RoutineDoesNotExist,
+
+    #[error("Diff does not apply to the current routine privileges")]
+    DiffDoesNotApply,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+impl CommandOutput for ModifyRoutinePrivilegesResponse {
+    fn print_human(&self) {
+        for (key, result) in self {
+            match result {
+                Ok(()) => {
+                    println!(
+                        "Privileges for user '{}' on {} '{}.{}' modified successfully.",
+                        key.user, key.routine_type, key.db, key.routine
+                    );
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(key)));
+                    eprintln!("Skipping...");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(key, result)| match result {
+                Ok(()) => json!({
+                  "database": key.db,
+                  "routine": key.routine,
+                  "routine_type": key.routine_type,
+                  "user": key.user,
+                  "status": "success",
+                }),
+                Err(err) => json!({
+                  "database": key.db,
+                  "routine": key.routine,
+                  "routine_type": key.routine_type,
+                  "user": key.user,
+                  "status": "error",
+                  "type": err.error_type(),
+                  "error": err.to_error_message(key),
+                }),
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ModifyRoutinePrivilegesError::exit_code),
+        )
+    }
+}
+
+impl ModifyRoutinePrivilegesError {
+    #[must_use]
+    pub fn to_error_message(&self, key: &RoutinePrivilegeKey) -> String {
+        match self {
+            ModifyRoutinePrivilegesError::DatabaseValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(key.db.clone()))
+            }
+            ModifyRoutinePrivilegesError::UserValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(key.user.clone()))
+            }
+            ModifyRoutinePrivilegesError::DatabaseDoesNotExist => {
+                format!("Database '{}' does not exist.", key.db)
+            }
+            ModifyRoutinePrivilegesError::UserDoesNotExist => {
+                format!("User '{}' does not exist.", key.user)
+            }
+            ModifyRoutinePrivilegesError::RoutineDoesNotExist => {
+                format!(
+                    "There is no {} named '{}' in database '{}'.",
+                    key.routine_type, key.routine, key.db
+                )
+            }
+            ModifyRoutinePrivilegesError::DiffDoesNotApply => {
+                format!(
+                    "The privileges for user '{}' on {} '{}.{}' have changed since they were listed.",
+                    key.user, key.routine_type, key.db, key.routine
+                )
+            }
+            ModifyRoutinePrivilegesError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ModifyRoutinePrivilegesError::DatabaseValidationError(err) => {
+                err.error_type() + "/database"
+            }
+            ModifyRoutinePrivilegesError::UserValidationError(err) => err.error_type() + "/user",
+            ModifyRoutinePrivilegesError::DatabaseDoesNotExist => {
+                "database-does-not-exist".to_string()
+            }
+            ModifyRoutinePrivilegesError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            ModifyRoutinePrivilegesError::RoutineDoesNotExist => {
+                "routine-does-not-exist".to_string()
+            }
+            ModifyRoutinePrivilegesError::DiffDoesNotApply => "diff-does-not-apply".to_string(),
+            ModifyRoutinePrivilegesError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ModifyRoutinePrivilegesError::DatabaseValidationError(err)
+            | ModifyRoutinePrivilegesError::UserValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    database_privileges::{DatabasePrivilegeRow, format_privileges_as_cli_string},
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    types::{DbOrUser, MySQLDatabase, MySQLUser},
+};
+
+pub type GrantOfferId = u64;
+
+/// An offer from the owner of a database to give a user owned by someone else
+/// a set of privileges on it, which is only applied once the owner of the user accepts it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrantOffer {
+    pub id: GrantOfferId,
+    /// The unix user that made the offer.
+    pub offered_by: String,
+    /// The full set of privileges the user will have on the database once accepted.
+    pub row: DatabasePrivilegeRow,
+    /// Unix timestamp after which the offer can no longer be accepted.
+    pub expires_at: u64,
+}
+
+pub type OfferGrantRequest = DatabasePrivilegeRow;
+
+pub type OfferGrantResponse = Result<GrantOffer, OfferGrantError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OfferGrantError {
+    #[error("Grant offers are not enabled on this server")]
+    Disabled,
+
+    /// The database is not owned by the unix user.
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("Invalid user name: {0}")]
+    InvalidUserName(ValidationError),
+
+    #[error("User is owned by the unix user")]
+    UserIsOwnedByYou,
+
+    #[error("User does not belong to any unix user or group")]
+    UserHasNoOwner,
+
+    #[error("Database does not exist")]
+    DatabaseDoesNotExist,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("Failed to store the offer: {0}")]
+    StoreError(String),
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// An [`OfferGrantResponse`] together with the privilege row that was offered.
+pub struct OfferGrantOutput<'a> {
+    pub row: &'a DatabasePrivilegeRow,
+    pub result: &'a OfferGrantResponse,
+}
+
+impl CommandOutput for OfferGrantOutput<'_> {
+    fn print_human(&self) {
+        let row = self.row;
+        match self.result {
+            Ok(offer) => {
+                println!(
+                    "Offered privileges '{}' on database '{}' to user '{}'.",
+                    format_privileges_as_cli_string(&offer.row),
+                    offer.row.db,
+                    offer.row.user,
+                );
+                println!(
+                    "The owner of '{}' can accept it with `muscl accept-grant {}`.",
+                    offer.row.user, offer.id,
+                );
+            }
+            Err(err) => {
+                eprintln!("{}", err.to_error_message(&row.db, &row.user));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let row = self.row;
+        match self.result {
+            Ok(offer) => json!({
+              "id": offer.id,
+              "database": offer.row.db,
+              "user": offer.row.user,
+              "privileges": format_privileges_as_cli_string(&offer.row),
+              "expires_at": offer.expires_at,
+              "status": "success",
+            }),
+            Err(err) => json!({
+              "database": row.db,
+              "user": row.user,
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(&row.db, &row.user),
+            }),
+        }
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result.as_ref().err().map(OfferGrantError::exit_code)
+    }
+}
+
+impl OfferGrantError {
+    #[must_use]
+    pub fn to_error_message(&self, database_name: &MySQLDatabase, username: &MySQLUser) -> String {
+        match self {
+            OfferGrantError::Disabled => {
+                "Grant offers are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            OfferGrantError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(database_name.clone()))
+            }
+            OfferGrantError::InvalidUserName(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            OfferGrantError::UserIsOwnedByYou => {
+                format!(
+                    "You own user '{username}' yourself, use `muscl edit-privs` to give it privileges directly."
+                )
+            }
+            OfferGrantError::UserHasNoOwner => {
+                format!("User '{username}' does not belong to any unix user or group.")
+            }
+            OfferGrantError::DatabaseDoesNotExist => {
+                format!("Database '{database_name}' does not exist.")
+            }
+            OfferGrantError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            OfferGrantError::StoreError(err) => {
+                format!("Failed to store the offer: {err}")
+            }
+            OfferGrantError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            OfferGrantError::Disabled => "disabled".to_string(),
+            OfferGrantError::ValidationError(err) => err.error_type(),
+            OfferGrantError::InvalidUserName(err) => err.error_type(),
+            OfferGrantError::UserIsOwnedByYou => "user-is-owned-by-you".to_string(),
+            OfferGrantError::UserHasNoOwner => "user-has-no-owner".to_string(),
+            OfferGrantError::DatabaseDoesNotExist => "database-does-not-exist".to_string(),
+            OfferGrantError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            OfferGrantError::StoreError(_) => "store-error".to_string(),
+            OfferGrantError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            OfferGrantError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
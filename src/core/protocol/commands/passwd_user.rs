@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
     protocol::request_validation::ValidationError,
-    types::{DbOrUser, MySQLUser},
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser, SecretString},
 };
 
-pub type SetUserPasswordRequest = (MySQLUser, String);
+pub type SetUserPasswordRequest = (MySQLUser, SecretString);
 
 pub type SetUserPasswordResponse = Result<(), SetPasswordError>;
 
@@ -22,16 +26,67 @@ pub enum SetPasswordError {
     MySqlError(String),
 }
 
-pub fn print_set_password_output_status(output: &SetUserPasswordResponse, username: &MySQLUser) {
-    match output {
-        Ok(()) => {
-            println!("Password for user '{username}' set successfully.");
-        }
-        Err(err) => {
-            eprintln!("{}", err.to_error_message(username));
-            eprintln!("Skipping...");
+/// A [`SetUserPasswordResponse`] together with the user whose password was set.
+pub struct SetUserPasswordOutput<'a> {
+    pub username: &'a MySQLUser,
+    pub result: &'a SetUserPasswordResponse,
+}
+
+impl CommandOutput for SetUserPasswordOutput<'_> {
+    fn print_human(&self) {
+        let username = self.username;
+        match self.result {
+            Ok(()) => {
+                println!("Password for user '{username}' set successfully.");
+            }
+            Err(err) => {
+                eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                eprintln!("Skipping...");
+            }
         }
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        let username = self.username;
+        let value = match self.result {
+            Ok(()) => json!({ "status": "success" }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(username),
+            }),
+        };
+        json!({ username.to_string(): value })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result.as_ref().err().map(SetPasswordError::exit_code)
+    }
+}
+
+/// A password that was generated by the client and set for a user,
+/// which has to be shown to the user, as there is no other way to get it back.
+pub struct GeneratedPasswordOutput<'a> {
+    pub username: &'a MySQLUser,
+    pub password: &'a SecretString,
+}
+
+impl CommandOutput for GeneratedPasswordOutput<'_> {
+    fn print_human(&self) {
+        println!(
+            "Generated password for user '{}': {}",
+            self.username,
+            self.password.expose_secret()
+        );
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({ self.username.to_string(): { "password": self.password.expose_secret() } })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
 }
 
 impl SetPasswordError {
@@ -50,7 +105,6 @@ impl SetPasswordError {
         }
     }
 
-    #[allow(dead_code)]
     #[must_use]
     pub fn error_type(&self) -> String {
         match self {
@@ -59,4 +113,12 @@ impl SetPasswordError {
             SetPasswordError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            SetPasswordError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
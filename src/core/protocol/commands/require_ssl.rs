@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser},
+};
+
+/// What kind of encrypted connection a user has to connect with,
+/// from the `ssl_type` column of `mysql.user`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsRequirement {
+    /// Unencrypted connections are allowed.
+    #[default]
+    None,
+    /// The connection has to be encrypted.
+    Ssl,
+    /// The connection has to be encrypted, and the client has to present a valid certificate.
+    X509,
+    /// A specific cipher, certificate issuer or subject is required,
+    /// which can only be set up by the administrators.
+    Specified,
+}
+
+impl TlsRequirement {
+    #[must_use]
+    pub fn from_ssl_type(ssl_type: &str) -> Self {
+        match ssl_type.to_ascii_uppercase().as_str() {
+            "ANY" => TlsRequirement::Ssl,
+            "X509" => TlsRequirement::X509,
+            "SPECIFIED" => TlsRequirement::Specified,
+            _ => TlsRequirement::None,
+        }
+    }
+
+    /// The `REQUIRE` clause of `ALTER USER` for this requirement.
+    ///
+    /// `Specified` is never requested by the client, and falls back to `NONE`.
+    #[must_use]
+    pub fn require_clause(self) -> &'static str {
+        match self {
+            TlsRequirement::Ssl => "REQUIRE SSL",
+            TlsRequirement::X509 => "REQUIRE X509",
+            TlsRequirement::None | TlsRequirement::Specified => "REQUIRE NONE",
+        }
+    }
+}
+
+impl std::fmt::Display for TlsRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsRequirement::None => write!(f, "none"),
+            TlsRequirement::Ssl => write!(f, "ssl"),
+            TlsRequirement::X509 => write!(f, "x509"),
+            TlsRequirement::Specified => write!(f, "specified"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequireSslRequest {
+    pub user: MySQLUser,
+    pub requirement: TlsRequirement,
+}
+
+pub type RequireSslResponse = Result<(), RequireSslError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RequireSslError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// A [`RequireSslResponse`] together with the user and the requirement that was set.
+pub struct RequireSslOutput<'a> {
+    pub username: &'a MySQLUser,
+    pub requirement: TlsRequirement,
+    pub result: &'a RequireSslResponse,
+}
+
+impl CommandOutput for RequireSslOutput<'_> {
+    fn print_human(&self) {
+        let username = self.username;
+        match self.result {
+            Ok(()) => match self.requirement {
+                TlsRequirement::None => {
+                    println!("User '{username}' can now connect without TLS.");
+                }
+                TlsRequirement::X509 => {
+                    println!(
+                        "User '{username}' now has to connect with TLS and a valid client certificate."
+                    );
+                }
+                TlsRequirement::Ssl | TlsRequirement::Specified => {
+                    println!("User '{username}' now has to connect with TLS.");
+                }
+            },
+            Err(err) => {
+                eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let username = self.username;
+        let value = match self.result {
+            Ok(()) => json!({
+              "status": "success",
+              "requirement": self.requirement,
+            }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(username),
+            }),
+        };
+        json!({ username.to_string(): value })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result.as_ref().err().map(RequireSslError::exit_code)
+    }
+}
+
+impl RequireSslError {
+    #[must_use]
+    pub fn to_error_message(&self, username: &MySQLUser) -> String {
+        match self {
+            RequireSslError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            RequireSslError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            RequireSslError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            RequireSslError::ValidationError(err) => err.error_type(),
+            RequireSslError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            RequireSslError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            RequireSslError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_requirement_from_ssl_type() {
+        assert_eq!(TlsRequirement::from_ssl_type(""), TlsRequirement::None);
+        assert_eq!(TlsRequirement::from_ssl_type("ANY"), TlsRequirement::Ssl);
+        assert_eq!(TlsRequirement::from_ssl_type("X509"), TlsRequirement::X509);
+        assert_eq!(
+            TlsRequirement::from_ssl_type("SPECIFIED"),
+            TlsRequirement::Specified
+        );
+    }
+}
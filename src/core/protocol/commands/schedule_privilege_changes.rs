@@ -0,0 +1,137 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    core::{
+        database_privileges::DatabasePrivilegesDiff, exit_code::ExitCode, output::CommandOutput,
+        protocol::request_validation::ValidationError, types::DbOrUser,
+    },
+    server::scheduler::format_utc_datetime,
+};
+
+pub type ScheduledPrivilegeChangeId = u64;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulePrivilegeChangesRequest {
+    pub diffs: BTreeSet<DatabasePrivilegesDiff>,
+
+    /// See [`ModifyPrivilegesRequest::atomic`](crate::core::protocol::ModifyPrivilegesRequest::atomic).
+    pub atomic: bool,
+
+    /// Unix timestamp of when to apply the changes.
+    pub apply_at: u64,
+}
+
+/// A set of privilege changes that the server applies on behalf of a unix user at a given time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledPrivilegeChange {
+    pub id: ScheduledPrivilegeChangeId,
+    /// The unix user that scheduled the changes.
+    pub scheduled_by: String,
+    /// Unix timestamp of when to apply the changes.
+    pub apply_at: u64,
+    pub atomic: bool,
+    pub diffs: BTreeSet<DatabasePrivilegesDiff>,
+}
+
+pub type SchedulePrivilegeChangesResponse =
+    Result<ScheduledPrivilegeChange, SchedulePrivilegeChangesError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SchedulePrivilegeChangesError {
+    #[error("Scheduled privilege changes are not enabled on this server")]
+    Disabled,
+
+    #[error("Time is in the past")]
+    TimeIsInThePast,
+
+    /// One of the databases or users in the changes is not owned by the unix user.
+    #[error("Validation error: {1}")]
+    ValidationError(DbOrUser, ValidationError),
+
+    #[error("Failed to store the changes: {0}")]
+    StoreError(String),
+}
+
+impl CommandOutput for SchedulePrivilegeChangesResponse {
+    fn print_human(&self) {
+        match self {
+            Ok(change) => {
+                println!(
+                    "Scheduled {} privilege change(s) to be applied at {} (id {}).",
+                    change.diffs.len(),
+                    format_utc_datetime(change.apply_at),
+                    change.id,
+                );
+            }
+            Err(err) => {
+                eprintln!("{}", err.to_error_message());
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Ok(change) => json!({
+              "id": change.id,
+              "apply_at": change.apply_at,
+              "changes": change.diffs.len(),
+              "status": "success",
+            }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(),
+            }),
+        }
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.as_ref()
+            .err()
+            .map(SchedulePrivilegeChangesError::exit_code)
+    }
+}
+
+impl SchedulePrivilegeChangesError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            SchedulePrivilegeChangesError::Disabled => {
+                "Scheduled privilege changes are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            SchedulePrivilegeChangesError::TimeIsInThePast => {
+                "The changes can only be scheduled for a time in the future.".to_string()
+            }
+            SchedulePrivilegeChangesError::ValidationError(db_or_user, err) => {
+                err.to_error_message(db_or_user)
+            }
+            SchedulePrivilegeChangesError::StoreError(err) => {
+                format!("Failed to store the changes: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            SchedulePrivilegeChangesError::Disabled => "disabled".to_string(),
+            SchedulePrivilegeChangesError::TimeIsInThePast => "time-is-in-the-past".to_string(),
+            SchedulePrivilegeChangesError::ValidationError(_, err) => err.error_type(),
+            SchedulePrivilegeChangesError::StoreError(_) => "store-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            SchedulePrivilegeChangesError::TimeIsInThePast => ExitCode::Usage,
+            SchedulePrivilegeChangesError::ValidationError(_, err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    core::{
+        exit_code::ExitCode,
+        output::CommandOutput,
+        pager::print_paged,
+        protocol::{ListAllDatabasesResponse, ListAllUsersResponse},
+        style::{Role, paint},
+        table::{TableCell, TableColumn, TableViewArgs},
+    },
+    server::sql::{database_operations::DatabaseRow, user_operations::DatabaseUser},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchRequest {
+    pub pattern: String,
+
+    /// Whether the pattern is a regular expression rather than a glob.
+    pub regex: bool,
+}
+
+impl SearchRequest {
+    /// The pattern as a `MySQL` regular expression.
+    ///
+    /// A glob has to match the whole name, and supports `*` for any number of
+    /// characters and `?` for a single character. A regular expression is used
+    /// as is, and may match any part of the name.
+    #[must_use]
+    pub fn to_mysql_regex(&self) -> String {
+        if self.regex {
+            return self.pattern.clone();
+        }
+
+        let mut regex = String::from("^");
+        for c in self.pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                '\\' | '.' | '+' | '^' | '$' | '|' | '(' | ')' | '[' | ']' | '{' | '}' => {
+                    regex.push('\\');
+                    regex.push(c);
+                }
+                _ => regex.push(c),
+            }
+        }
+        regex.push('$');
+        regex
+    }
+}
+
+/// The databases and users of the unix user that match a [`SearchRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub databases: ListAllDatabasesResponse,
+    pub users: ListAllUsersResponse,
+}
+
+pub const SEARCH_COLUMNS: [&str; 3] = ["type", "name", "privileges"];
+
+/// A [`SearchResponse`], and how to display it.
+pub struct SearchOutput<'a> {
+    pub result: &'a SearchResponse,
+    pub table_view: &'a TableViewArgs,
+}
+
+impl CommandOutput for SearchOutput<'_> {
+    fn print_human(&self) {
+        let databases: &[DatabaseRow] = match &self.result.databases {
+            Ok(databases) => databases,
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    paint(
+                        Role::Error,
+                        &format!("Failed to search databases: {}", err.to_error_message())
+                    )
+                );
+                &[]
+            }
+        };
+
+        let users: &[DatabaseUser] = match &self.result.users {
+            Ok(users) => users,
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    paint(
+                        Role::Error,
+                        &format!("Failed to search users: {}", err.to_error_message())
+                    )
+                );
+                &[]
+            }
+        };
+
+        if databases.is_empty() && users.is_empty() {
+            println!("No databases or users matched the pattern.");
+            return;
+        }
+
+        let columns = [
+            TableColumn::new("type", "Type"),
+            TableColumn::new("name", "Name"),
+            TableColumn::new("privileges", "Users / Databases with privileges"),
+        ];
+
+        let rows = databases
+            .iter()
+            .map(|db| {
+                vec![
+                    TableCell::text("database"),
+                    TableCell::text(db.database.as_str()),
+                    TableCell::text(db.users.iter().map(|user| user.as_str()).join("\n")),
+                ]
+            })
+            .chain(users.iter().map(|user| {
+                vec![
+                    TableCell::text("user"),
+                    TableCell::text(user.user.as_str()),
+                    TableCell::text(user.databases.join("\n")),
+                ]
+            }))
+            .collect();
+
+        print_paged(&self.table_view.render(&columns, rows).to_string());
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let databases = match &self.result.databases {
+            Ok(databases) => json!({
+              "status": "success",
+              "value": databases
+                .iter()
+                .map(|db| json!({
+                  "database": db.database,
+                  "users": db.users,
+                }))
+                .collect::<Vec<_>>(),
+            }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(),
+            }),
+        };
+
+        let users = match &self.result.users {
+            Ok(users) => json!({
+              "status": "success",
+              "value": users
+                .iter()
+                .map(|user| json!({
+                  "user": user.user,
+                  "databases": user.databases,
+                }))
+                .collect::<Vec<_>>(),
+            }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(),
+            }),
+        };
+
+        json!({
+          "databases": databases,
+          "users": users,
+        })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        (self.result.databases.is_err() || self.result.users.is_err()).then_some(ExitCode::Failure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mysql_regex() {
+        let glob = |pattern: &str| SearchRequest {
+            pattern: pattern.to_string(),
+            regex: false,
+        };
+
+        assert_eq!(glob("alice_*").to_mysql_regex(), "^alice_.*$");
+        assert_eq!(glob("alice_db?").to_mysql_regex(), "^alice_db.$");
+        assert_eq!(
+            glob("alice_(a|b).*").to_mysql_regex(),
+            r"^alice_\(a\|b\)\..*$"
+        );
+
+        let regex = SearchRequest {
+            pattern: "^alice_(a|b)".to_string(),
+            regex: true,
+        };
+        assert_eq!(regex.to_mysql_regex(), "^alice_(a|b)");
+    }
+}
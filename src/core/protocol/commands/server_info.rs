@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::{CommandOutput, serialize_to_json},
+};
+
+/// Information about the server, meant to be included in bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerInfoResponse {
+    pub muscl_version: String,
+    pub muscl_commit: String,
+    pub backend_flavor: String,
+    pub backend_version: String,
+    pub enabled_features: Vec<String>,
+    /// The `error_footer` from the server config, appended to error messages by the client.
+    #[serde(default)]
+    pub error_footer: Option<String>,
+    /// Whether the extended database privileges can be granted, and should be shown to the user.
+    #[serde(default)]
+    pub extended_database_privileges: bool,
+}
+
+impl ServerInfoResponse {
+    /// Collects information about the currently running server.
+    #[must_use]
+    pub fn for_current_server(
+        backend_flavor: String,
+        backend_version: String,
+        error_footer: Option<&str>,
+        extended_database_privileges: bool,
+    ) -> Self {
+        let mut enabled_features = Vec::new();
+        if cfg!(feature = "suid-sgid-mode") {
+            enabled_features.push("suid-sgid-mode".to_string());
+        }
+        if cfg!(feature = "direct-mode") {
+            enabled_features.push("direct-mode".to_string());
+        }
+        if cfg!(feature = "mysql-admutils-compatibility") {
+            enabled_features.push("mysql-admutils-compatibility".to_string());
+        }
+
+        Self {
+            muscl_version: env!("CARGO_PKG_VERSION").to_string(),
+            muscl_commit: env!("GIT_COMMIT").to_string(),
+            backend_flavor,
+            backend_version,
+            enabled_features,
+            error_footer: error_footer.map(ToOwned::to_owned),
+            extended_database_privileges,
+        }
+    }
+}
+
+impl CommandOutput for ServerInfoResponse {
+    fn print_human(&self) {
+        println!("muscl server version: {}", self.muscl_version);
+        println!("muscl server commit:  {}", self.muscl_commit);
+        println!(
+            "Database server:      {} ({})",
+            self.backend_flavor, self.backend_version
+        );
+        println!(
+            "Enabled features:     {}",
+            if self.enabled_features.is_empty() {
+                "none".to_string()
+            } else {
+                self.enabled_features.join(", ")
+            }
+        );
+        println!(
+            "Extended privileges:  {}",
+            if self.extended_database_privileges {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        if let Some(error_footer) = &self.error_footer {
+            println!("Support:              {error_footer}");
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serialize_to_json(self)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetUserCommentRequest {
+    pub user: MySQLUser,
+
+    /// The new comment, where an empty comment removes the existing one.
+    pub comment: String,
+}
+
+pub type SetUserCommentResponse = Result<(), SetUserCommentError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SetUserCommentError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("User comments are not supported by the database server")]
+    UserCommentsNotSupported,
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// A [`SetUserCommentResponse`] together with the user whose comment was set.
+pub struct SetUserCommentOutput<'a> {
+    pub username: &'a MySQLUser,
+    pub result: &'a SetUserCommentResponse,
+}
+
+impl CommandOutput for SetUserCommentOutput<'_> {
+    fn print_human(&self) {
+        let username = self.username;
+        match self.result {
+            Ok(()) => {
+                println!("Comment for user '{username}' set successfully.");
+            }
+            Err(err) => {
+                eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let username = self.username;
+        let value = match self.result {
+            Ok(()) => json!({ "status": "success" }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(username),
+            }),
+        };
+        json!({ username.to_string(): value })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result
+            .as_ref()
+            .err()
+            .map(SetUserCommentError::exit_code)
+    }
+}
+
+impl SetUserCommentError {
+    #[must_use]
+    pub fn to_error_message(&self, username: &MySQLUser) -> String {
+        match self {
+            SetUserCommentError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            SetUserCommentError::UserCommentsNotSupported => {
+                "The database server does not support user comments.".to_string()
+            }
+            SetUserCommentError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            SetUserCommentError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            SetUserCommentError::ValidationError(err) => err.error_type(),
+            SetUserCommentError::UserCommentsNotSupported => {
+                "user-comments-not-supported".to_string()
+            }
+            SetUserCommentError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            SetUserCommentError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            SetUserCommentError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
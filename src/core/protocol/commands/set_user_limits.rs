@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLUser},
+};
+
+/// The hourly resource limits of a user, where `0` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserLimits {
+    pub max_queries_per_hour: u64,
+    pub max_updates_per_hour: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetUserLimitsRequest {
+    pub user: MySQLUser,
+
+    /// The new limits, where `None` leaves the current limit as it is, and `0` removes it.
+    pub max_queries_per_hour: Option<u32>,
+    pub max_updates_per_hour: Option<u32>,
+}
+
+pub type SetUserLimitsResponse = Result<(), SetUserLimitsError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SetUserLimitsError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// A [`SetUserLimitsResponse`] together with the user whose limits were set.
+pub struct SetUserLimitsOutput<'a> {
+    pub username: &'a MySQLUser,
+    pub result: &'a SetUserLimitsResponse,
+}
+
+impl CommandOutput for SetUserLimitsOutput<'_> {
+    fn print_human(&self) {
+        let username = self.username;
+        match self.result {
+            Ok(()) => {
+                println!("Limits for user '{username}' set successfully.");
+            }
+            Err(err) => {
+                eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let username = self.username;
+        let value = match self.result {
+            Ok(()) => json!({ "status": "success" }),
+            Err(err) => json!({
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(username),
+            }),
+        };
+        json!({ username.to_string(): value })
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result
+            .as_ref()
+            .err()
+            .map(SetUserLimitsError::exit_code)
+    }
+}
+
+impl SetUserLimitsError {
+    #[must_use]
+    pub fn to_error_message(&self, username: &MySQLUser) -> String {
+        match self {
+            SetUserLimitsError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            SetUserLimitsError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            SetUserLimitsError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            SetUserLimitsError::ValidationError(err) => err.error_type(),
+            SetUserLimitsError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            SetUserLimitsError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            SetUserLimitsError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    pager::print_paged,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    table::{TableCell, TableColumn, TableViewArgs},
+    types::{DbOrUser, MySQLUser},
+};
+
+pub type ShowConnectionsRequest = Vec<MySQLUser>;
+
+pub type ShowConnectionsResponse =
+    BTreeMap<MySQLUser, Result<Vec<UserConnection>, ShowConnectionsError>>;
+
+/// How many characters of the running statement are sent to the client.
+pub const CONNECTION_STATEMENT_MAX_LENGTH: usize = 100;
+
+/// An open connection of a database user, from `information_schema.PROCESSLIST`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserConnection {
+    pub id: u64,
+    /// The host and port the connection comes from.
+    pub host: String,
+    /// The default database of the connection.
+    pub database: Option<String>,
+    /// What the connection is doing, e.g. `Query` or `Sleep`.
+    pub command: String,
+    /// How many seconds the connection has been in its current state.
+    pub time_seconds: u64,
+    pub state: Option<String>,
+    /// The statement that is running, cut off after [`CONNECTION_STATEMENT_MAX_LENGTH`] characters.
+    pub statement: Option<String>,
+    pub statement_truncated: bool,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ShowConnectionsError {
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+
+    #[error("User does not exist")]
+    UserDoesNotExist,
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+pub const SHOW_CONNECTIONS_COLUMNS: [&str; 8] = [
+    "id",
+    "user",
+    "host",
+    "database",
+    "command",
+    "time",
+    "state",
+    "statement",
+];
+
+/// A [`ShowConnectionsResponse`], and how to display it.
+pub struct ShowConnectionsOutput<'a> {
+    pub connections: &'a ShowConnectionsResponse,
+    pub table_view: &'a TableViewArgs,
+}
+
+fn statement_text(connection: &UserConnection) -> String {
+    match &connection.statement {
+        Some(statement) if connection.statement_truncated => format!("{statement}..."),
+        Some(statement) => statement.clone(),
+        None => String::new(),
+    }
+}
+
+impl CommandOutput for ShowConnectionsOutput<'_> {
+    fn print_human(&self) {
+        let mut final_connection_list: Vec<(&MySQLUser, &UserConnection)> = Vec::new();
+        for (username, result) in self.connections {
+            match result {
+                Ok(connections) => final_connection_list
+                    .extend(connections.iter().map(|connection| (username, connection))),
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("Skipping...");
+                }
+            }
+        }
+
+        if final_connection_list.is_empty() {
+            println!("No connections to show.");
+        } else {
+            let columns = vec![
+                TableColumn::new("id", "Id"),
+                TableColumn::new("user", "User"),
+                TableColumn::new("host", "Host"),
+                TableColumn::new("database", "Database"),
+                TableColumn::new("command", "Command"),
+                TableColumn::new("time", "Seconds"),
+                TableColumn::new("state", "State"),
+                TableColumn::new("statement", "Statement"),
+            ];
+
+            let rows = final_connection_list
+                .into_iter()
+                .map(|(username, connection)| {
+                    vec![
+                        TableCell::number(connection.id.to_string(), connection.id),
+                        TableCell::text(username.as_str()),
+                        TableCell::text(connection.host.as_str()),
+                        TableCell::text(connection.database.clone().unwrap_or_default()),
+                        TableCell::text(connection.command.as_str()),
+                        TableCell::number(
+                            connection.time_seconds.to_string(),
+                            connection.time_seconds,
+                        ),
+                        TableCell::text(connection.state.clone().unwrap_or_default()),
+                        TableCell::text(statement_text(connection)),
+                    ]
+                })
+                .collect();
+
+            print_paged(&self.table_view.render(&columns, rows).to_string());
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .connections
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(connections) => (
+                    name.to_string(),
+                    json!({
+                      "status": "success",
+                      "value": connections,
+                    }),
+                ),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(value)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.connections
+                .values()
+                .filter_map(|result| result.as_ref().err())
+                .map(ShowConnectionsError::exit_code),
+        )
+    }
+}
+
+impl ShowConnectionsError {
+    #[must_use]
+    pub fn to_error_message(&self, username: &MySQLUser) -> String {
+        match self {
+            ShowConnectionsError::ValidationError(err) => {
+                err.to_error_message(&DbOrUser::User(username.clone()))
+            }
+            ShowConnectionsError::UserDoesNotExist => {
+                format!("User '{username}' does not exist.")
+            }
+            ShowConnectionsError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            ShowConnectionsError::ValidationError(err) => err.error_type(),
+            ShowConnectionsError::UserDoesNotExist => "user-does-not-exist".to_string(),
+            ShowConnectionsError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ShowConnectionsError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
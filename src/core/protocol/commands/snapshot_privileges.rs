@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{exit_code::ExitCode, output::CommandOutput};
+
+/// The name of a privilege snapshot, chosen by the unix user who took it.
+pub type PrivilegeSnapshotTag = String;
+
+pub type SnapshotPrivilegesRequest = PrivilegeSnapshotTag;
+
+pub type SnapshotPrivilegesResponse = Result<PrivilegeSnapshotInfo, SnapshotPrivilegesError>;
+
+/// A stored snapshot of the privileges on the databases of a unix user, without the privileges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivilegeSnapshotInfo {
+    pub tag: PrivilegeSnapshotTag,
+    /// Unix timestamp of when the snapshot was taken.
+    pub created_at: u64,
+    /// The number of privilege rows in the snapshot.
+    pub rows: usize,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SnapshotPrivilegesError {
+    #[error("Privilege snapshots are not enabled on this server")]
+    Disabled,
+
+    #[error("Invalid tag")]
+    InvalidTag,
+
+    #[error("A snapshot with this tag already exists")]
+    SnapshotAlreadyExists,
+
+    #[error("Failed to store the snapshot: {0}")]
+    StoreError(String),
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// Whether a tag is a valid name for a snapshot.
+///
+/// Tags are shown in tables and passed on the command line,
+/// so they are limited to a short string without spaces or special characters.
+#[must_use]
+pub fn is_valid_privilege_snapshot_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= 64
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// A [`SnapshotPrivilegesResponse`] together with the tag that was requested.
+pub struct SnapshotPrivilegesOutput<'a> {
+    pub tag: &'a str,
+    pub result: &'a SnapshotPrivilegesResponse,
+}
+
+impl CommandOutput for SnapshotPrivilegesOutput<'_> {
+    fn print_human(&self) {
+        match self.result {
+            Ok(snapshot) => {
+                println!(
+                    "Stored {} privilege row(s) in snapshot '{}'.",
+                    snapshot.rows, snapshot.tag
+                );
+                println!(
+                    "You can restore them with `muscl rollback-privs --tag {}`.",
+                    snapshot.tag
+                );
+            }
+            Err(err) => {
+                eprintln!("{}", err.to_error_message(self.tag));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self.result {
+            Ok(snapshot) => json!({
+              "tag": snapshot.tag,
+              "created_at": snapshot.created_at,
+              "rows": snapshot.rows,
+              "status": "success",
+            }),
+            Err(err) => json!({
+              "tag": self.tag,
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(self.tag),
+            }),
+        }
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result
+            .as_ref()
+            .err()
+            .map(SnapshotPrivilegesError::exit_code)
+    }
+}
+
+impl SnapshotPrivilegesError {
+    #[must_use]
+    pub fn to_error_message(&self, tag: &str) -> String {
+        match self {
+            SnapshotPrivilegesError::Disabled => {
+                "Privilege snapshots are not enabled on this server, please contact the system administrators."
+                    .to_string()
+            }
+            SnapshotPrivilegesError::InvalidTag => {
+                format!(
+                    "Invalid tag '{tag}', only letters, digits, '-', '_' and '.' are allowed, up to 64 characters."
+                )
+            }
+            SnapshotPrivilegesError::SnapshotAlreadyExists => {
+                format!("A snapshot with the tag '{tag}' already exists.")
+            }
+            SnapshotPrivilegesError::StoreError(err) => {
+                format!("Failed to store the snapshot: {err}")
+            }
+            SnapshotPrivilegesError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            SnapshotPrivilegesError::Disabled => "disabled".to_string(),
+            SnapshotPrivilegesError::InvalidTag => "invalid-tag".to_string(),
+            SnapshotPrivilegesError::SnapshotAlreadyExists => "snapshot-already-exists".to_string(),
+            SnapshotPrivilegesError::StoreError(_) => "store-error".to_string(),
+            SnapshotPrivilegesError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            SnapshotPrivilegesError::InvalidTag => ExitCode::Usage,
+            _ => ExitCode::Failure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_privilege_snapshot_tag() {
+        assert!(is_valid_privilege_snapshot_tag("before-upgrade"));
+        assert!(is_valid_privilege_snapshot_tag("v1.2_3"));
+        assert!(!is_valid_privilege_snapshot_tag(""));
+        assert!(!is_valid_privilege_snapshot_tag("with space"));
+        assert!(!is_valid_privilege_snapshot_tag(&"a".repeat(65)));
+    }
+}
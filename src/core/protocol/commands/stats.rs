@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::{CommandOutput, serialize_to_json},
+};
+
+/// Statistics for each of the name prefixes the unix user is allowed to manage.
+pub type StatsResponse = Result<BTreeMap<String, PrefixStats>, StatsError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PrefixStats {
+    pub databases: u64,
+    pub size_bytes: u64,
+    pub users: u64,
+    pub locked_users: u64,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StatsError {
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// The statistics of a [`StatsResponse`], and how to display them.
+pub struct StatsOutput<'a> {
+    pub stats: &'a BTreeMap<String, PrefixStats>,
+    pub display_size_as_bytes: bool,
+}
+
+impl CommandOutput for StatsOutput<'_> {
+    fn print_human(&self) {
+        let mut table = Table::new();
+        table.set_titles(row![
+            "Prefix",
+            "Databases",
+            if self.display_size_as_bytes {
+                "Size (Bytes)"
+            } else {
+                "Size"
+            },
+            "Users",
+            "Locked users",
+        ]);
+        for (prefix, stats) in self.stats {
+            table.add_row(row![
+                prefix,
+                r->stats.databases,
+                r->if self.display_size_as_bytes {
+                    stats.size_bytes.to_string()
+                } else {
+                    humansize::format_size(stats.size_bytes, humansize::DECIMAL)
+                },
+                r->stats.users,
+                r->stats.locked_users,
+            ]);
+        }
+        table.printstd();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serialize_to_json(self.stats)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
+
+impl StatsError {
+    #[must_use]
+    pub fn to_error_message(&self) -> String {
+        match self {
+            StatsError::MySqlError(err) => format!("MySQL error: {err}"),
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            StatsError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+}
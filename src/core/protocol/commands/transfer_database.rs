@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::CommandOutput,
+    protocol::request_validation::ValidationError,
+    style::{Role, paint},
+    types::{DbOrUser, MySQLDatabase},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferDatabaseRequest {
+    pub from: MySQLDatabase,
+    pub to: MySQLDatabase,
+}
+
+pub type TransferDatabaseResponse = Result<(), TransferDatabaseError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransferDatabaseError {
+    /// The database to transfer is not owned by the unix user.
+    #[error("Validation error: {0}")]
+    SourceValidationError(ValidationError),
+
+    /// The new name is not under a prefix owned by the unix user.
+    #[error("Validation error: {0}")]
+    TargetValidationError(ValidationError),
+
+    #[error("Database does not exist")]
+    SourceDoesNotExist,
+
+    #[error("Target database already exists")]
+    TargetAlreadyExists,
+
+    /// Views, triggers, routines and events can not be moved to another database.
+    #[error("Database contains objects that can not be moved: {}", .0.join(", "))]
+    UnsupportedObjects(Vec<String>),
+
+    #[error("MySQL error: {0}")]
+    MySqlError(String),
+}
+
+/// A [`TransferDatabaseResponse`] together with the request it answers.
+pub struct TransferDatabaseOutput<'a> {
+    pub request: &'a TransferDatabaseRequest,
+    pub result: &'a TransferDatabaseResponse,
+}
+
+impl CommandOutput for TransferDatabaseOutput<'_> {
+    fn print_human(&self) {
+        let request = self.request;
+        match self.result {
+            Ok(()) => {
+                println!(
+                    "Database '{}' was transferred to '{}', together with its privileges.",
+                    request.from, request.to
+                );
+            }
+            Err(err) => {
+                eprintln!("{}", paint(Role::Error, &err.to_error_message(request)));
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let request = self.request;
+        match self.result {
+            Ok(()) => json!({
+              "from": request.from,
+              "to": request.to,
+              "status": "success",
+            }),
+            Err(err) => json!({
+              "from": request.from,
+              "to": request.to,
+              "status": "error",
+              "type": err.error_type(),
+              "error": err.to_error_message(request),
+            }),
+        }
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        self.result
+            .as_ref()
+            .err()
+            .map(TransferDatabaseError::exit_code)
+    }
+}
+
+impl TransferDatabaseError {
+    #[must_use]
+    pub fn to_error_message(&self, request: &TransferDatabaseRequest) -> String {
+        match self {
+            TransferDatabaseError::SourceValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(request.from.clone()))
+            }
+            TransferDatabaseError::TargetValidationError(err) => {
+                err.to_error_message(&DbOrUser::Database(request.to.clone()))
+            }
+            TransferDatabaseError::SourceDoesNotExist => {
+                format!("Database '{}' does not exist.", request.from)
+            }
+            TransferDatabaseError::TargetAlreadyExists => {
+                format!("Database '{}' already exists.", request.to)
+            }
+            TransferDatabaseError::UnsupportedObjects(objects) => {
+                format!(
+                    "Database '{}' contains objects that can not be moved to another database: {}\n\nPlease recreate them after transferring the database, or ask the system administrators for help.",
+                    request.from,
+                    objects.join(", ")
+                )
+            }
+            TransferDatabaseError::MySqlError(err) => {
+                format!("MySQL error: {err}")
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn error_type(&self) -> String {
+        match self {
+            TransferDatabaseError::SourceValidationError(err) => {
+                format!("source-{}", err.error_type())
+            }
+            TransferDatabaseError::TargetValidationError(err) => {
+                format!("target-{}", err.error_type())
+            }
+            TransferDatabaseError::SourceDoesNotExist => "source-does-not-exist".to_string(),
+            TransferDatabaseError::TargetAlreadyExists => "target-already-exists".to_string(),
+            TransferDatabaseError::UnsupportedObjects(_) => "unsupported-objects".to_string(),
+            TransferDatabaseError::MySqlError(_) => "mysql-error".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            TransferDatabaseError::SourceValidationError(err)
+            | TransferDatabaseError::TargetValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
+}
@@ -5,11 +5,20 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::core::{
+    exit_code::ExitCode,
+    output::{BulkSummary, CommandOutput},
     protocol::request_validation::ValidationError,
+    style::{Role, paint},
     types::{DbOrUser, MySQLUser},
 };
 
-pub type UnlockUsersRequest = Vec<MySQLUser>;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockUsersRequest {
+    pub users: Vec<MySQLUser>,
+
+    /// Also unlock every user under this prefix, resolved by the server.
+    pub all_with_prefix: Option<String>,
+}
 
 pub type UnlockUsersResponse = BTreeMap<MySQLUser, Result<(), UnlockUserError>>;
 
@@ -28,44 +37,59 @@ pub enum UnlockUserError {
     MySqlError(String),
 }
 
-pub fn print_unlock_users_output_status(output: &UnlockUsersResponse) {
-    for (username, result) in output {
-        match result {
-            Ok(()) => {
-                println!("User '{username}' unlocked successfully.");
-            }
-            Err(err) => {
-                eprintln!("{}", err.to_error_message(username));
-                eprintln!("Skipping...");
+impl CommandOutput for UnlockUsersResponse {
+    fn print_human(&self) {
+        for (username, result) in self {
+            match result {
+                Ok(()) => {
+                    println!("User '{username}' unlocked successfully.");
+                }
+                Err(err) => {
+                    eprintln!("{}", paint(Role::Error, &err.to_error_message(username)));
+                    eprintln!("Skipping...");
+                }
             }
+            println!();
         }
-        println!();
+
+        BulkSummary::from_results(self.values(), UnlockUserError::is_skipped).print_footer();
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let value = self
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(()) => (name.to_string(), json!({ "status": "success" })),
+                Err(err) => (
+                    name.to_string(),
+                    json!({
+                      "status": "error",
+                      "type": err.error_type(),
+                      "error": err.to_error_message(name),
+                    }),
+                ),
+            })
+            .collect::<serde_json::Map<_, _>>();
+        BulkSummary::from_results(self.values(), UnlockUserError::is_skipped)
+            .wrap_json(serde_json::Value::Object(value))
     }
-}
 
-pub fn print_unlock_users_output_status_json(output: &UnlockUsersResponse) {
-    let value = output
-        .iter()
-        .map(|(name, result)| match result {
-            Ok(()) => (name.to_string(), json!({ "status": "success" })),
-            Err(err) => (
-                name.to_string(),
-                json!({
-                  "status": "error",
-                  "type": err.error_type(),
-                  "error": err.to_error_message(name),
-                }),
-            ),
-        })
-        .collect::<serde_json::Map<_, _>>();
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&value)
-            .unwrap_or("Failed to serialize result to JSON".to_string())
-    );
+    fn exit_code(&self) -> Option<ExitCode> {
+        ExitCode::combine(
+            self.values()
+                .filter_map(|result| result.as_ref().err())
+                .map(UnlockUserError::exit_code),
+        )
+    }
 }
 
 impl UnlockUserError {
+    /// Whether the user was already unlocked.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, UnlockUserError::UserIsAlreadyUnlocked)
+    }
+
     #[must_use]
     pub fn to_error_message(&self, username: &MySQLUser) -> String {
         match self {
@@ -93,4 +117,12 @@ impl UnlockUserError {
             UnlockUserError::MySqlError(_) => "mysql-error".to_string(),
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            UnlockUserError::ValidationError(err) => err.exit_code(),
+            _ => ExitCode::Failure,
+        }
+    }
 }
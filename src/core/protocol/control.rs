@@ -0,0 +1,116 @@
+//! The protocol of the admin control socket.
+//!
+//! The control socket is separate from the socket for regular users, and only
+//! accepts connections from root and members of the admin groups. It is used
+//! by `muscl-server` subcommands like `status` and `reload` to control a running
+//! server. Every connection carries a single [`ControlRequest`] and its [`ControlResponse`].
+
+use serde::{Deserialize, Serialize};
+use tokio::net::UnixStream;
+use tokio_serde::{Framed as SerdeFramed, formats::Bincode};
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+use crate::core::{
+    exit_code::ExitCode,
+    output::{CommandOutput, serialize_to_json},
+    protocol::{AdminLogLevelRequest, AdminLogLevelResponse},
+};
+
+pub type ControlServerStream = SerdeFramed<
+    Framed<UnixStream, LengthDelimitedCodec>,
+    ControlRequest,
+    ControlResponse,
+    Bincode<ControlRequest, ControlResponse>,
+>;
+
+pub type ControlClientStream = SerdeFramed<
+    Framed<UnixStream, LengthDelimitedCodec>,
+    ControlResponse,
+    ControlRequest,
+    Bincode<ControlResponse, ControlRequest>,
+>;
+
+pub fn create_control_server_stream(socket: UnixStream) -> ControlServerStream {
+    let length_delimited = Framed::new(socket, LengthDelimitedCodec::new());
+    tokio_serde::Framed::new(length_delimited, Bincode::default())
+}
+
+pub fn create_control_client_stream(socket: UnixStream) -> ControlClientStream {
+    let length_delimited = Framed::new(socket, LengthDelimitedCodec::new());
+    tokio_serde::Framed::new(length_delimited, Bincode::default())
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlRequest {
+    Status,
+    Reload,
+    SetMaintenanceMode(bool),
+    LogLevel(AdminLogLevelRequest),
+    /// Stop accepting new connections, and exit once the active sessions have finished.
+    /// The number of seconds to wait for them, or `None` to use the configured default.
+    Drain(Option<u64>),
+    /// Start a new server process from the server binary, and hand the listening sockets over to it.
+    Upgrade,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ControlResponse {
+    Status(ServerStatus),
+    /// The reload was started, its result ends up in the server logs.
+    ReloadStarted,
+    /// Whether the server was in maintenance mode before the request.
+    SetMaintenanceMode(bool),
+    LogLevel(AdminLogLevelResponse),
+    /// The drain was started, with the number of sessions that were active.
+    DrainStarted(usize),
+    /// The upgrade was started, its result ends up in the server logs.
+    UpgradeStarted,
+    NotAuthorized,
+    Error(String),
+}
+
+/// The state of a running server, as shown by `muscl-server status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub version: String,
+    pub uptime_seconds: u64,
+    pub active_sessions: usize,
+    pub accepting_connections: bool,
+    /// While in maintenance mode, only members of the admin groups can start new sessions.
+    pub maintenance_mode: bool,
+    pub log_level: Option<String>,
+}
+
+impl CommandOutput for ServerStatus {
+    fn print_human(&self) {
+        println!("Version:                {}", self.version);
+        println!("Uptime:                 {}s", self.uptime_seconds);
+        println!("Active sessions:        {}", self.active_sessions);
+        println!(
+            "Accepting connections:  {}",
+            if self.accepting_connections {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+        println!(
+            "Maintenance mode:       {}",
+            if self.maintenance_mode { "on" } else { "off" }
+        );
+        println!(
+            "Log level:              {}",
+            self.log_level.as_deref().unwrap_or("unknown")
+        );
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serialize_to_json(self)
+    }
+
+    fn exit_code(&self) -> Option<ExitCode> {
+        None
+    }
+}
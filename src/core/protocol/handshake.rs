@@ -0,0 +1,143 @@
+//! The initial handshake of a session.
+//!
+//! After accepting a connection, the server checks the peer credentials and
+//! acquires a database connection before sending [`Response::Ready`]. The client
+//! waits for this message before sending any requests.
+//!
+//! If the server has a message of the day configured, it is sent as a
+//! [`Response::Motd`] just ahead of [`Response::Ready`], so that the client
+//! always has it in hand when the handshake is done.
+//!
+//! Both sides bound every step of the handshake with a timeout, so that a
+//! stalled peer can not keep a connection (and a database connection) open forever.
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use thiserror::Error;
+
+use crate::core::protocol::{ClientToServerMessageStream, Response, ServerToClientMessageStream};
+
+/// The default time to wait for each step of the handshake.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The default time for the client to wait for [`Response::Ready`].
+///
+/// The server only sends it after getting a database connection from its pool, which
+/// can take up to the `acquire_timeout` of the server, 30 seconds by default. Waiting
+/// longer than that lets the client show the error from the server, rather than timing out.
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum HandshakeError {
+    #[error("Timed out after {}s waiting for the server to become ready", .0.as_secs())]
+    ReadyTimeout(Duration),
+
+    #[error("Timed out after {}s sending the ready message to the client", .0.as_secs())]
+    SendReadyTimeout(Duration),
+
+    #[error("Timed out after {}s waiting for the first request from the client", .0.as_secs())]
+    FirstRequestTimeout(Duration),
+
+    #[error("{message}\n(reference: {reference})")]
+    ServerError { message: String, reference: String },
+
+    #[error(
+        "The database server is currently unavailable\n\
+         This is usually temporary, please try again in a little while\n\
+         If the problem persists, contact the system administrators"
+    )]
+    DatabaseUnavailable,
+
+    #[error("The connection was closed during the handshake")]
+    Disconnected,
+
+    #[error("I/O error during the handshake: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Wait for the server to send [`Response::Ready`], and return the message of the day, if any.
+pub async fn wait_for_server_ready(
+    stream: &mut ClientToServerMessageStream,
+    timeout: Duration,
+) -> Result<Option<String>, HandshakeError> {
+    let wait_for_ready = async {
+        let mut motd = None;
+        while let Some(message) = stream.next().await {
+            match message? {
+                Response::Error { message, reference } => {
+                    return Err(HandshakeError::ServerError { message, reference });
+                }
+                Response::DatabaseUnavailable => return Err(HandshakeError::DatabaseUnavailable),
+                Response::Motd(message) => motd = Some(message),
+                Response::Ready => return Ok(motd),
+                message => {
+                    eprintln!("Unexpected message from server: {message:?}");
+                }
+            }
+        }
+        Err(HandshakeError::Disconnected)
+    };
+
+    tokio::time::timeout(timeout, wait_for_ready)
+        .await
+        .map_err(|_| HandshakeError::ReadyTimeout(timeout))?
+}
+
+/// Send the message of the day, if any, and [`Response::Ready`] to the client.
+pub async fn send_server_ready(
+    stream: &mut ServerToClientMessageStream,
+    timeout: Duration,
+    motd: Option<&str>,
+) -> Result<(), HandshakeError> {
+    let send_ready = async {
+        if let Some(motd) = motd {
+            stream.feed(Response::Motd(motd.to_string())).await?;
+        }
+        stream.send(Response::Ready).await
+    };
+
+    tokio::time::timeout(timeout, send_ready)
+        .await
+        .map_err(|_| HandshakeError::SendReadyTimeout(timeout))??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::UnixStream;
+
+    use super::*;
+    use crate::core::protocol::{
+        create_client_to_server_message_stream, create_server_to_client_message_stream,
+    };
+
+    #[tokio::test]
+    async fn test_handshake() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let mut client = create_client_to_server_message_stream(client);
+        let mut server = create_server_to_client_message_stream(server);
+
+        let timeout = Duration::from_millis(100);
+        send_server_ready(&mut server, timeout, None).await.unwrap();
+        assert_eq!(
+            wait_for_server_ready(&mut client, timeout).await.unwrap(),
+            None
+        );
+
+        send_server_ready(&mut server, timeout, Some("Maintenance on friday"))
+            .await
+            .unwrap();
+        assert_eq!(
+            wait_for_server_ready(&mut client, timeout).await.unwrap(),
+            Some("Maintenance on friday".to_string())
+        );
+
+        let result = wait_for_server_ready(&mut client, timeout).await;
+        assert!(matches!(result, Err(HandshakeError::ReadyTimeout(_))));
+
+        drop(server);
+        let result = wait_for_server_ready(&mut client, timeout).await;
+        assert!(matches!(result, Err(HandshakeError::Disconnected)));
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashSet;
 
-use indoc::indoc;
 use nix::{libc::gid_t, unistd::Group};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::core::{common::UnixUser, types::DbOrUser};
+use crate::core::{
+    common::UnixUser,
+    exit_code::ExitCode,
+    i18n::{self, Message, tr},
+    types::DbOrUser,
+};
 
 #[derive(Error, Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum NameValidationError {
@@ -19,26 +23,33 @@ pub enum NameValidationError {
 
     #[error("Name is too long. Maximum length is 64 characters.")]
     TooLong,
+
+    #[error("Name is reserved by the database server.")]
+    Reserved,
 }
 
 impl NameValidationError {
     #[must_use]
     pub fn to_error_message(self, db_or_user: &DbOrUser) -> String {
         match self {
-            NameValidationError::EmptyString => {
-                format!("{} name can not be empty.", db_or_user.capitalized_noun())
-            }
-            NameValidationError::TooLong => format!(
-                "{} is too long, maximum length is 64 characters.",
-                db_or_user.capitalized_noun()
+            NameValidationError::EmptyString => tr(
+                Message::NameEmpty,
+                &[("noun", &i18n::noun(db_or_user, false))],
+            ),
+            NameValidationError::TooLong => tr(
+                Message::NameTooLong,
+                &[("noun", &i18n::noun(db_or_user, false))],
             ),
-            NameValidationError::InvalidCharacters => format!(
-                indoc! {r"
-                  Invalid characters in {} name: '{}', only A-Z, a-z, 0-9, _ (underscore) and - (dash) are permitted.
-                "},
-                db_or_user.lowercased_noun(),
-                db_or_user.name(),
+            NameValidationError::InvalidCharacters => tr(
+                Message::NameInvalidCharacters,
+                &[
+                    ("noun", &i18n::noun(db_or_user, false)),
+                    ("name", &db_or_user.name()),
+                ],
             ),
+            NameValidationError::Reserved => {
+                tr(Message::NameReserved, &[("name", &db_or_user.name())])
+            }
         }
     }
 
@@ -48,6 +59,7 @@ impl NameValidationError {
             NameValidationError::EmptyString => "empty-string",
             NameValidationError::InvalidCharacters => "invalid-characters",
             NameValidationError::TooLong => "too-long",
+            NameValidationError::Reserved => "reserved",
         }
     }
 }
@@ -69,19 +81,22 @@ impl AuthorizationError {
     #[must_use]
     pub fn to_error_message(self, db_or_user: &DbOrUser) -> String {
         match self {
-            AuthorizationError::IllegalPrefix => format!(
-                "Illegal {} name prefix: you are not allowed to manage databases or users prefixed with '{}'",
-                db_or_user.lowercased_noun(),
-                db_or_user.prefix(),
-            )
-            .to_owned(),
+            AuthorizationError::IllegalPrefix => tr(
+                Message::IllegalPrefix,
+                &[
+                    ("noun", &i18n::noun(db_or_user, false)),
+                    ("prefix", &db_or_user.prefix()),
+                ],
+            ),
             // TODO: This error message could be clearer
-            AuthorizationError::StringEmpty => {
-                format!("{} name can not be empty.", db_or_user.capitalized_noun())
-            }
-            AuthorizationError::DenylistError => {
-                format!("'{}' is denied by the group denylist", db_or_user.name())
-            }
+            AuthorizationError::StringEmpty => tr(
+                Message::NameEmpty,
+                &[("noun", &i18n::noun(db_or_user, false))],
+            ),
+            AuthorizationError::DenylistError => tr(
+                Message::DeniedByGroupDenylist,
+                &[("name", &db_or_user.name())],
+            ),
         }
     }
 
@@ -134,12 +149,32 @@ impl ValidationError {
               // }
         }
     }
+
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ValidationError::NameValidationError(_) => ExitCode::Failure,
+            ValidationError::AuthorizationError(_) => ExitCode::NoPerm,
+        }
+    }
 }
 
 pub type GroupDenylist = HashSet<gid_t>;
 
 const MAX_NAME_LENGTH: usize = 64;
 
+/// Names used by the database server itself, which can never be managed through muscl.
+///
+/// Most of these could never pass the prefix check anyway, but e.g. a unix group
+/// named `information` would otherwise own `information_schema`.
+const RESERVED_NAMES: &[&str] = &[
+    "information_schema",
+    "performance_schema",
+    "mysql",
+    "sys",
+    "root",
+];
+
 pub fn validate_name(name: &str) -> Result<(), NameValidationError> {
     if name.is_empty() {
         Err(NameValidationError::EmptyString)
@@ -150,6 +185,11 @@ pub fn validate_name(name: &str) -> Result<(), NameValidationError> {
         .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
     {
         Err(NameValidationError::InvalidCharacters)
+    } else if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    {
+        Err(NameValidationError::Reserved)
     } else {
         Ok(())
     }
@@ -254,6 +294,16 @@ mod tests {
             validate_name(&"a".repeat(MAX_NAME_LENGTH + 1)),
             Err(NameValidationError::TooLong)
         );
+
+        assert_eq!(
+            validate_name("information_schema"),
+            Err(NameValidationError::Reserved)
+        );
+        assert_eq!(
+            validate_name("Performance_Schema"),
+            Err(NameValidationError::Reserved)
+        );
+        assert_eq!(validate_name("information_schema2"), Ok(()));
     }
 
     #[test]
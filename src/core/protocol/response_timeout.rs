@@ -0,0 +1,129 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::UnixStream,
+    time::Sleep,
+};
+
+/// The client side of the server socket, which fails reads that wait longer than
+/// the response timeout for data from the server.
+///
+/// The timer only runs while the client is waiting for the server, so time spent
+/// on e.g. confirmation prompts or in the editor does not count.
+#[derive(Debug)]
+pub struct ResponseTimeoutStream {
+    inner: UnixStream,
+    timeout: Option<Duration>,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl ResponseTimeoutStream {
+    #[must_use]
+    pub fn new(inner: UnixStream, timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            timeout,
+            deadline: None,
+        }
+    }
+}
+
+impl AsyncRead for ResponseTimeoutStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        if let Poll::Ready(result) = Pin::new(&mut this.inner).poll_read(cx, buf) {
+            this.deadline = None;
+            return Poll::Ready(result);
+        }
+
+        let Some(timeout) = this.timeout else {
+            return Poll::Pending;
+        };
+
+        let deadline = this
+            .deadline
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                this.deadline = None;
+                Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "Timed out after {}s waiting for a response from the server",
+                        timeout.as_secs()
+                    ),
+                )))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl AsyncWrite for ResponseTimeoutStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{SinkExt, StreamExt};
+
+    use super::*;
+    use crate::core::protocol::{
+        Response, create_client_to_server_message_stream_with_response_timeout,
+        create_server_to_client_message_stream,
+    };
+
+    #[tokio::test]
+    async fn test_response_timeout() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let mut client = create_client_to_server_message_stream_with_response_timeout(
+            client,
+            Some(Duration::from_millis(50)),
+        );
+        let mut server = create_server_to_client_message_stream(server);
+
+        server.send(Response::Ready).await.unwrap();
+        assert!(matches!(client.next().await, Some(Ok(Response::Ready))));
+
+        let err = client.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,1074 @@
+//! Datastructures and logic for privileges on stored routines, stored in `mysql.procs_priv`.
+//!
+//! Routine privileges are given on a single stored procedure or function, and only cover
+//! the EXECUTE and ALTER ROUTINE privileges. Like [`crate::core::column_privileges`],
+//! they use the same privilege characters as `edit-privs`, and a similar editor format.
+
+use std::{
+    cmp::max,
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::{Context, anyhow};
+use itertools::Itertools;
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        common::{rev_yn, yn},
+        database_privileges::{
+            DatabasePrivilegeChange, DatabasePrivilegeEdit, DatabasePrivilegeEditEntryType,
+            PrivilegeAliases, db_priv_field_human_readable_name,
+            db_priv_field_single_character_name, db_priv_field_sql_name,
+        },
+        style::{Role, paint},
+        types::{MySQLDatabase, MySQLUser},
+    },
+    server::sql::{quote_identifier, quote_literal},
+};
+
+/// The privileges that can be given on a single stored routine.
+///
+/// The names are the same as the matching fields of
+/// [`DATABASE_PRIVILEGE_FIELDS`](crate::core::database_privileges::DATABASE_PRIVILEGE_FIELDS).
+pub const ROUTINE_PRIVILEGE_FIELDS: [&str; 2] = ["execute_priv", "alter_routine_priv"];
+
+const VALID_ROUTINE_PRIVILEGE_EDIT_CHARS: &[char] = &['x', 'P', 'A'];
+
+/// Whether a stored routine is a procedure or a function.
+///
+/// A procedure and a function in the same database may have the same name,
+/// and privileges are given on them separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutineType {
+    Procedure,
+    Function,
+}
+
+impl RoutineType {
+    /// The keyword used for the routine type in `GRANT` and `REVOKE` statements,
+    /// and in the `Routine_type` column of `mysql.procs_priv`.
+    #[must_use]
+    pub fn sql_keyword(self) -> &'static str {
+        match self {
+            RoutineType::Procedure => "PROCEDURE",
+            RoutineType::Function => "FUNCTION",
+        }
+    }
+}
+
+impl fmt::Display for RoutineType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoutineType::Procedure => write!(f, "procedure"),
+            RoutineType::Function => write!(f, "function"),
+        }
+    }
+}
+
+impl FromStr for RoutineType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "procedure" => Ok(RoutineType::Procedure),
+            "function" => Ok(RoutineType::Function),
+            _ => anyhow::bail!("Expected 'procedure' or 'function', found '{s}'"),
+        }
+    }
+}
+
+/// A stored procedure or function in a database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct StoredRoutine {
+    pub db: MySQLDatabase,
+    pub name: String,
+    pub routine_type: RoutineType,
+}
+
+impl fmt::Display for StoredRoutine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{} ({})", self.db, self.name, self.routine_type)
+    }
+}
+
+/// The privileges of a single user on a single stored routine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct RoutinePrivilegeRow {
+    pub db: MySQLDatabase,
+    pub routine: String,
+    pub routine_type: RoutineType,
+    pub user: MySQLUser,
+    pub execute_priv: bool,
+    pub alter_routine_priv: bool,
+}
+
+impl RoutinePrivilegeRow {
+    /// A row for the routine without any privileges.
+    #[must_use]
+    pub fn new(
+        db: MySQLDatabase,
+        routine: String,
+        routine_type: RoutineType,
+        user: MySQLUser,
+    ) -> Self {
+        Self {
+            db,
+            routine,
+            routine_type,
+            user,
+            execute_priv: false,
+            alter_routine_priv: false,
+        }
+    }
+
+    /// Parses the `Proc_priv` set of a `mysql.procs_priv` row, e.g. `Execute,Alter Routine`.
+    #[must_use]
+    pub fn with_privileges_from_mysql_set(mut self, set: &str) -> Self {
+        for privilege in set.split(',').filter(|p| !p.is_empty()) {
+            match ROUTINE_PRIVILEGE_FIELDS
+                .into_iter()
+                .find(|field| db_priv_field_sql_name(field).eq_ignore_ascii_case(privilege))
+            {
+                // SAFETY: unwrap is safe here because the field names are static
+                Some(field) => *self.get_privilege_mut_by_name(field).unwrap() = true,
+                None => tracing::warn!("Unknown routine privilege '{}'", privilege),
+            }
+        }
+        self
+    }
+
+    /// Gets the value of a privilege by its name as a &str.
+    #[must_use]
+    pub fn get_privilege_by_name(&self, name: &str) -> Option<bool> {
+        match name {
+            "execute_priv" => Some(self.execute_priv),
+            "alter_routine_priv" => Some(self.alter_routine_priv),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to a privilege by its name as a &str.
+    pub fn get_privilege_mut_by_name(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "execute_priv" => Some(&mut self.execute_priv),
+            "alter_routine_priv" => Some(&mut self.alter_routine_priv),
+            _ => None,
+        }
+    }
+
+    /// Whether the user has any privileges on the routine.
+    #[must_use]
+    pub fn has_privileges(&self) -> bool {
+        ROUTINE_PRIVILEGE_FIELDS
+            .into_iter()
+            .any(|field| self.get_privilege_by_name(field).unwrap())
+    }
+
+    /// The routine and user the row is for, which identify it.
+    #[must_use]
+    pub fn key(&self) -> RoutinePrivilegeKey {
+        RoutinePrivilegeKey {
+            db: self.db.clone(),
+            routine: self.routine.clone(),
+            routine_type: self.routine_type,
+            user: self.user.clone(),
+        }
+    }
+}
+
+/// A stored routine, and a user with privileges on it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct RoutinePrivilegeKey {
+    pub db: MySQLDatabase,
+    pub routine: String,
+    pub routine_type: RoutineType,
+    pub user: MySQLUser,
+}
+
+impl fmt::Display for RoutinePrivilegeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} ({}): {}",
+            self.db, self.routine, self.routine_type, self.user
+        )
+    }
+}
+
+/// Renders the granted privileges of a row as a string of the single-character
+/// privilege names used by `edit-routine-privs`, e.g. `xP`.
+#[must_use]
+pub fn format_routine_privileges_as_cli_string(row: &RoutinePrivilegeRow) -> String {
+    ROUTINE_PRIVILEGE_FIELDS
+        .into_iter()
+        .filter(|field| row.get_privilege_by_name(field) == Some(true))
+        .map(db_priv_field_single_character_name)
+        .collect()
+}
+
+/// A single CLI argument for editing routine privileges, parsed from a string like
+///
+///   `[procedure:|function:]database_name.routine_name:username:[+|-]privileges`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutinePrivilegeEditEntry {
+    /// The type of the routine, if it was given.
+    ///
+    /// See [`RoutinePrivilegeEditEntry::resolve_routine_type`].
+    pub routine_type: Option<RoutineType>,
+    pub database: MySQLDatabase,
+    pub routine: String,
+    pub user: MySQLUser,
+    pub privilege_edit: DatabasePrivilegeEdit,
+}
+
+impl RoutinePrivilegeEditEntry {
+    /// Parses a routine privilege edit entry from a string.
+    ///
+    /// The expected format is:
+    ///
+    ///   `[procedure:|function:]database_name.routine_name:username:[+|-]privileges`
+    ///
+    /// where the privileges are any of `xP`, or `A` for both of them, or the privilege
+    /// aliases from `aliases` that only stand for those. The database name ends at the
+    /// first `.`, so the routine name may contain dots.
+    pub fn parse_from_str(arg: &str, aliases: &PrivilegeAliases) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = arg.split(':').collect();
+        let (routine_type, routine_path, user, privileges) = match parts.as_slice() {
+            [routine_path, user, privileges] => (None, *routine_path, *user, *privileges),
+            [routine_type, routine_path, user, privileges] => (
+                Some(routine_type.parse::<RoutineType>()?),
+                *routine_path,
+                *user,
+                *privileges,
+            ),
+            _ => anyhow::bail!("Invalid routine privilege edit entry format: {arg}"),
+        };
+
+        let (database, routine) = routine_path
+            .split_once('.')
+            .filter(|(database, routine)| !database.is_empty() && !routine.is_empty())
+            .ok_or_else(|| {
+                anyhow!("Expected the routine as DB_NAME.ROUTINE_NAME, found: {routine_path}")
+            })?;
+
+        if user.is_empty() {
+            anyhow::bail!("Username cannot be empty in routine privilege edit entry: {arg}");
+        }
+
+        let privilege_edit = parse_routine_privilege_edit(privileges, aliases)?;
+
+        Ok(RoutinePrivilegeEditEntry {
+            routine_type,
+            database: MySQLDatabase::from(database.to_string()),
+            routine: routine.to_string(),
+            user: MySQLUser::from(user.to_string()),
+            privilege_edit,
+        })
+    }
+
+    /// The type of the routine the edit is for.
+    ///
+    /// If it was not given, it is looked up among the existing `routines`,
+    /// which fails if there is no routine with the name, or both a procedure and a function.
+    pub fn resolve_routine_type(&self, routines: &[StoredRoutine]) -> anyhow::Result<RoutineType> {
+        if let Some(routine_type) = self.routine_type {
+            return Ok(routine_type);
+        }
+
+        let matching_types = routines
+            .iter()
+            .filter(|routine| routine.db == self.database && routine.name == self.routine)
+            .map(|routine| routine.routine_type)
+            .collect::<BTreeSet<_>>();
+
+        match matching_types.len() {
+            0 => anyhow::bail!(
+                "Could not find a stored routine named '{}' in database '{}'",
+                self.routine,
+                self.database
+            ),
+            1 => Ok(*matching_types.first().unwrap()),
+            _ => anyhow::bail!(
+                "Both a procedure and a function are named '{}' in database '{}', prefix the entry with 'procedure:' or 'function:'",
+                self.routine,
+                self.database
+            ),
+        }
+    }
+
+    /// Applies the edit to the existing privileges of the routine, if there are any.
+    #[must_use]
+    pub fn apply(
+        &self,
+        routine_type: RoutineType,
+        existing: Option<&RoutinePrivilegeRow>,
+    ) -> RoutinePrivilegeRow {
+        let mut row = existing.cloned().unwrap_or_else(|| {
+            RoutinePrivilegeRow::new(
+                self.database.clone(),
+                self.routine.clone(),
+                routine_type,
+                self.user.clone(),
+            )
+        });
+
+        let value = match self.privilege_edit.type_ {
+            DatabasePrivilegeEditEntryType::Set => {
+                for field in ROUTINE_PRIVILEGE_FIELDS {
+                    *row.get_privilege_mut_by_name(field).unwrap() = false;
+                }
+                true
+            }
+            DatabasePrivilegeEditEntryType::Add => true,
+            DatabasePrivilegeEditEntryType::Remove => false,
+        };
+
+        for field in ROUTINE_PRIVILEGE_FIELDS {
+            let priv_char = db_priv_field_single_character_name(field);
+            if self
+                .privilege_edit
+                .privileges
+                .iter()
+                .any(|c| *c == 'A' || priv_char.starts_with(*c))
+            {
+                *row.get_privilege_mut_by_name(field).unwrap() = value;
+            }
+        }
+
+        row
+    }
+}
+
+impl fmt::Display for RoutinePrivilegeEditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(routine_type) = self.routine_type {
+            write!(f, "{routine_type}:")?;
+        }
+        write!(
+            f,
+            "{}.{}:{}:{}",
+            self.database, self.routine, self.user, self.privilege_edit
+        )
+    }
+}
+
+/// Parses a `[+-]PRIVILEGES` string like [`DatabasePrivilegeEdit::parse_from_str`],
+/// but only allows the privileges that can be given on a stored routine.
+pub fn parse_routine_privilege_edit(
+    input: &str,
+    aliases: &PrivilegeAliases,
+) -> anyhow::Result<DatabasePrivilegeEdit> {
+    let edit = DatabasePrivilegeEdit::parse_from_str(input, aliases)?;
+
+    let invalid_chars = edit
+        .privileges
+        .iter()
+        .filter(|c| !VALID_ROUTINE_PRIVILEGE_EDIT_CHARS.contains(c))
+        .map(|c| format!("'{c}'"))
+        .join(", ");
+    if !invalid_chars.is_empty() {
+        anyhow::bail!(
+            "Privilege(s) {invalid_chars} can not be given on a stored routine\n\nValid characters are: {}",
+            VALID_ROUTINE_PRIVILEGE_EDIT_CHARS
+                .iter()
+                .map(|c| format!("'{c}'"))
+                .join(", ")
+        );
+    }
+
+    Ok(edit)
+}
+
+/// The changes to the privileges of a single user on a single stored routine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct RoutinePrivilegeRowDiff {
+    pub db: MySQLDatabase,
+    pub routine: String,
+    pub routine_type: RoutineType,
+    pub user: MySQLUser,
+    pub execute_priv: Option<DatabasePrivilegeChange>,
+    pub alter_routine_priv: Option<DatabasePrivilegeChange>,
+}
+
+impl RoutinePrivilegeRowDiff {
+    /// Calculates the difference between two [`RoutinePrivilegeRow`] instances.
+    #[must_use]
+    pub fn from_rows(row1: &RoutinePrivilegeRow, row2: &RoutinePrivilegeRow) -> Self {
+        debug_assert!(row1.key() == row2.key());
+
+        RoutinePrivilegeRowDiff {
+            db: row1.db.clone(),
+            routine: row1.routine.clone(),
+            routine_type: row1.routine_type,
+            user: row1.user.clone(),
+            execute_priv: DatabasePrivilegeChange::new(row1.execute_priv, row2.execute_priv),
+            alter_routine_priv: DatabasePrivilegeChange::new(
+                row1.alter_routine_priv,
+                row2.alter_routine_priv,
+            ),
+        }
+    }
+
+    /// Returns true if there are no changes in this diff.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        ROUTINE_PRIVILEGE_FIELDS
+            .into_iter()
+            .all(|field| self.get_privilege_change_by_name(field).is_none())
+    }
+
+    /// Retrieves the privilege change for a given privilege name.
+    #[must_use]
+    pub fn get_privilege_change_by_name(&self, name: &str) -> Option<DatabasePrivilegeChange> {
+        match name {
+            "execute_priv" => self.execute_priv,
+            "alter_routine_priv" => self.alter_routine_priv,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RoutinePrivilegeRowDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in ROUTINE_PRIVILEGE_FIELDS {
+            match self.get_privilege_change_by_name(field) {
+                Some(DatabasePrivilegeChange::YesToNo) => writeln!(
+                    f,
+                    "{}: {}",
+                    db_priv_field_human_readable_name(field),
+                    paint(Role::DiffRemoved, "Y -> N"),
+                )?,
+                Some(DatabasePrivilegeChange::NoToYes) => writeln!(
+                    f,
+                    "{}: {}",
+                    db_priv_field_human_readable_name(field),
+                    paint(Role::DiffAdded, "N -> Y"),
+                )?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a [`RoutinePrivilegeRow`] was introduced, modified or deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub enum RoutinePrivilegesDiff {
+    New(RoutinePrivilegeRow),
+    Modified(RoutinePrivilegeRowDiff),
+    Deleted(RoutinePrivilegeRow),
+}
+
+impl RoutinePrivilegesDiff {
+    /// The routine and user the diff is for.
+    #[must_use]
+    pub fn key(&self) -> RoutinePrivilegeKey {
+        match self {
+            RoutinePrivilegesDiff::New(p) | RoutinePrivilegesDiff::Deleted(p) => p.key(),
+            RoutinePrivilegesDiff::Modified(p) => RoutinePrivilegeKey {
+                db: p.db.clone(),
+                routine: p.routine.clone(),
+                routine_type: p.routine_type,
+                user: p.user.clone(),
+            },
+        }
+    }
+
+    /// The change of every privilege that is changed by the diff.
+    #[must_use]
+    pub fn changes(&self) -> Vec<(&'static str, DatabasePrivilegeChange)> {
+        ROUTINE_PRIVILEGE_FIELDS
+            .into_iter()
+            .filter_map(|field| {
+                let change = match self {
+                    RoutinePrivilegesDiff::New(p) => p
+                        .get_privilege_by_name(field)
+                        .unwrap()
+                        .then_some(DatabasePrivilegeChange::NoToYes),
+                    RoutinePrivilegesDiff::Modified(p) => p.get_privilege_change_by_name(field),
+                    RoutinePrivilegesDiff::Deleted(p) => p
+                        .get_privilege_by_name(field)
+                        .unwrap()
+                        .then_some(DatabasePrivilegeChange::YesToNo),
+                };
+                change.map(|change| (field, change))
+            })
+            .collect()
+    }
+
+    /// Whether the diff applies to `existing`, the current privileges on the routine,
+    /// i.e. it only grants privileges that are not granted, and revokes ones that are.
+    #[must_use]
+    pub fn applies_to(&self, existing: Option<&RoutinePrivilegeRow>) -> bool {
+        match (self, existing) {
+            (RoutinePrivilegesDiff::New(_), None) => true,
+            (RoutinePrivilegesDiff::New(_), Some(_)) | (_, None) => false,
+            (_, Some(existing)) => self.changes().into_iter().all(|(field, change)| {
+                let granted = existing.get_privilege_by_name(field).unwrap();
+                match change {
+                    DatabasePrivilegeChange::NoToYes => !granted,
+                    DatabasePrivilegeChange::YesToNo => granted,
+                }
+            }),
+        }
+    }
+
+    /// The `GRANT` and `REVOKE` statements that apply the diff.
+    ///
+    /// Like for column privileges, these are used rather than editing
+    /// `mysql.procs_priv` directly, so that the server reloads the privileges.
+    #[must_use]
+    pub fn to_sql_statements(&self) -> Vec<String> {
+        let key = self.key();
+        let changes = self.changes();
+        let privileges = |change: DatabasePrivilegeChange| {
+            changes
+                .iter()
+                .filter(|(_, c)| *c == change)
+                .map(|(field, _)| db_priv_field_sql_name(field))
+                .join(", ")
+        };
+        let target = format!(
+            "{} {}.{}",
+            key.routine_type.sql_keyword(),
+            quote_identifier(&key.db),
+            quote_identifier(&key.routine)
+        );
+
+        let mut statements = Vec::new();
+        let granted = privileges(DatabasePrivilegeChange::NoToYes);
+        if !granted.is_empty() {
+            statements.push(format!(
+                "GRANT {granted} ON {target} TO {}@'%'",
+                quote_literal(&key.user)
+            ));
+        }
+        let revoked = privileges(DatabasePrivilegeChange::YesToNo);
+        if !revoked.is_empty() {
+            statements.push(format!(
+                "REVOKE {revoked} ON {target} FROM {}@'%'",
+                quote_literal(&key.user)
+            ));
+        }
+        statements
+    }
+}
+
+/// Calculates the differences between two sets of routine privileges.
+///
+/// Rows in `to` without any privileges are treated as if they were not there.
+#[must_use]
+pub fn diff_routine_privileges(
+    from: &[RoutinePrivilegeRow],
+    to: &[RoutinePrivilegeRow],
+) -> BTreeSet<RoutinePrivilegesDiff> {
+    let from_lookup_table: HashMap<RoutinePrivilegeKey, &RoutinePrivilegeRow> =
+        from.iter().map(|p| (p.key(), p)).collect();
+    let to_lookup_table: HashMap<RoutinePrivilegeKey, &RoutinePrivilegeRow> = to
+        .iter()
+        .filter(|p| p.has_privileges())
+        .map(|p| (p.key(), p))
+        .collect();
+
+    let mut result = BTreeSet::new();
+
+    for (key, p) in &to_lookup_table {
+        if let Some(old_p) = from_lookup_table.get(key) {
+            let diff = RoutinePrivilegeRowDiff::from_rows(old_p, p);
+            if !diff.is_empty() {
+                result.insert(RoutinePrivilegesDiff::Modified(diff));
+            }
+        } else {
+            result.insert(RoutinePrivilegesDiff::New((*p).to_owned()));
+        }
+    }
+
+    for (key, p) in &from_lookup_table {
+        if !to_lookup_table.contains_key(key) {
+            result.insert(RoutinePrivilegesDiff::Deleted((*p).to_owned()));
+        }
+    }
+
+    result
+}
+
+/// Applies the edits from the command line to the existing routine privileges,
+/// and returns the changes needed to get there.
+///
+/// `routines` is used to find the type of the routines that the edits did not give it for.
+pub fn create_or_modify_routine_privilege_rows(
+    from: &[RoutinePrivilegeRow],
+    edits: &[RoutinePrivilegeEditEntry],
+    routines: &[StoredRoutine],
+) -> anyhow::Result<BTreeSet<RoutinePrivilegesDiff>> {
+    let mut rows = from.to_vec();
+    for edit in edits {
+        let routine_type = edit
+            .resolve_routine_type(routines)
+            .with_context(|| format!("Could not apply '{edit}'"))?;
+        let position = rows.iter().position(|row| {
+            row.db == edit.database
+                && row.routine == edit.routine
+                && row.routine_type == routine_type
+                && row.user == edit.user
+        });
+        let row = edit.apply(routine_type, position.map(|i| &rows[i]));
+        match position {
+            Some(i) => rows[i] = row,
+            None => rows.push(row),
+        }
+    }
+
+    Ok(diff_routine_privileges(from, &rows))
+}
+
+/// Renders a set of [`RoutinePrivilegesDiff`] into a human-readable formatted table.
+#[must_use]
+pub fn display_routine_privilege_diffs(diffs: &BTreeSet<RoutinePrivilegesDiff>) -> String {
+    let mut table = Table::new();
+    table.set_titles(row!["Routine", "User", "Privilege diff"]);
+    for diff in diffs {
+        let key = diff.key();
+        let routine = format!("{}.{} ({})", key.db, key.routine, key.routine_type);
+        match diff {
+            RoutinePrivilegesDiff::New(p) => {
+                table.add_row(row![
+                    routine,
+                    key.user,
+                    paint(Role::DiffAdded, "(Previously unprivileged)")
+                        + "\n"
+                        + &ROUTINE_PRIVILEGE_FIELDS
+                            .into_iter()
+                            .map(|field| format!(
+                                "{}: {}\n",
+                                db_priv_field_human_readable_name(field),
+                                yn(p.get_privilege_by_name(field).unwrap())
+                            ))
+                            .join("")
+                ]);
+            }
+            RoutinePrivilegesDiff::Modified(p) => {
+                table.add_row(row![routine, key.user, p.to_string()]);
+            }
+            RoutinePrivilegesDiff::Deleted(_) => {
+                table.add_row(row![routine, key.user, paint(Role::DiffRemoved, "Removed")]);
+            }
+        }
+    }
+
+    table.to_string()
+}
+
+const EDITOR_COMMENT: &str = r"
+# Welcome to the routine privilege editor.
+# Each line defines what privileges a single user has on a single stored procedure or function.
+# The first four columns respectively represent the database, the routine type ('procedure' or 'function'),
+# the routine and the user, and the remaining columns are the privileges.
+# If the user should have a certain privilege, write 'Y', otherwise write 'N'.
+# To grant privileges on another routine, add a line for it.
+#
+# Lines starting with '#' are comments and will be ignored.
+";
+
+const EDITOR_NAME_HEADERS: [&str; 4] = ["Database", "Type", "Routine", "User"];
+
+/// Generates a single row of the routine privileges table for the editor.
+fn format_routine_privileges_line_for_editor(
+    privs: &RoutinePrivilegeRow,
+    name_widths: [usize; 4],
+) -> String {
+    [
+        privs.db.to_string(),
+        privs.routine_type.to_string(),
+        privs.routine.clone(),
+        privs.user.to_string(),
+    ]
+    .into_iter()
+    .zip(name_widths)
+    .map(|(name, width)| format!("{name:width$}"))
+    .chain(ROUTINE_PRIVILEGE_FIELDS.into_iter().map(|field| {
+        format!(
+            "{:width$}",
+            yn(privs.get_privilege_by_name(field).unwrap()),
+            width = db_priv_field_human_readable_name(field).len()
+        )
+    }))
+    .join(" ")
+    .trim()
+    .to_string()
+}
+
+/// Generates the content for the routine privilege editor.
+///
+/// The stored routines in `routines` are listed in a comment, so that the user can see
+/// which routines privileges can be given on. The unix user is used in case there are
+/// no privileges to edit, so that the user can see an example line based on their username.
+#[must_use]
+pub fn generate_routine_editor_content(
+    privilege_data: &[RoutinePrivilegeRow],
+    routines: &[StoredRoutine],
+    unix_user: &str,
+) -> String {
+    let example = RoutinePrivilegeRow {
+        execute_priv: true,
+        ..routines.first().map_or_else(
+            || {
+                RoutinePrivilegeRow::new(
+                    format!("{unix_user}_db").into(),
+                    "routine".to_string(),
+                    RoutineType::Procedure,
+                    format!("{unix_user}_user").into(),
+                )
+            },
+            |routine| {
+                RoutinePrivilegeRow::new(
+                    routine.db.clone(),
+                    routine.name.clone(),
+                    routine.routine_type,
+                    format!("{unix_user}_user").into(),
+                )
+            },
+        )
+    };
+    let shown_rows = if privilege_data.is_empty() {
+        std::slice::from_ref(&example)
+    } else {
+        privilege_data
+    };
+
+    let name_widths = [0, 1, 2, 3].map(|i| {
+        shown_rows
+            .iter()
+            .map(|row| match i {
+                0 => row.db.len(),
+                1 => row.routine_type.to_string().len(),
+                2 => row.routine.len(),
+                _ => row.user.len(),
+            })
+            .fold(EDITOR_NAME_HEADERS[i].len(), max)
+    });
+
+    let routine_list = if routines.is_empty() {
+        "# There are no stored routines in your databases.".to_string()
+    } else {
+        std::iter::once("# Stored routines in your databases:".to_string())
+            .chain(routines.iter().map(|routine| format!("#   {routine}")))
+            .join("\n")
+    };
+
+    let header = EDITOR_NAME_HEADERS
+        .into_iter()
+        .zip(name_widths)
+        .map(|(name, width)| format!("{name:width$}"))
+        .chain(
+            ROUTINE_PRIVILEGE_FIELDS
+                .into_iter()
+                .map(db_priv_field_human_readable_name),
+        )
+        .join(" ");
+
+    let rows = shown_rows
+        .iter()
+        .map(|row| format_routine_privileges_line_for_editor(row, name_widths))
+        .map(|line| {
+            if privilege_data.is_empty() {
+                format!("# {line}")
+            } else {
+                line
+            }
+        })
+        .join("\n");
+
+    format!("{EDITOR_COMMENT}{routine_list}\n\n{header}\n{rows}")
+}
+
+/// Parse the rows of the routine privileges table from the editor.
+pub fn parse_routine_privilege_data_from_editor_content(
+    content: &str,
+) -> anyhow::Result<Vec<RoutinePrivilegeRow>> {
+    let field_count = EDITOR_NAME_HEADERS.len() + ROUTINE_PRIVILEGE_FIELDS.len();
+
+    content
+        .trim()
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .filter(|(_, line)| {
+            !line
+                .split_ascii_whitespace()
+                .zip(EDITOR_NAME_HEADERS)
+                .all(|(cell, header)| cell == header)
+        })
+        .map(|(i, line)| {
+            let parts: Vec<&str> = line.split_ascii_whitespace().collect();
+            if parts.len() != field_count {
+                anyhow::bail!(
+                    "Wrong number of fields in line {i}:\n  {line}\n  Expected to find {field_count} fields, found {}",
+                    parts.len(),
+                );
+            }
+
+            let routine_type = parts[1]
+                .parse::<RoutineType>()
+                .context(format!("Could not parse routine type in line {i}:\n  {line}"))?;
+            let mut row = RoutinePrivilegeRow::new(
+                parts[0].into(),
+                parts[2].to_string(),
+                routine_type,
+                parts[3].into(),
+            );
+            for (cell, field) in parts[4..].iter().zip(ROUTINE_PRIVILEGE_FIELDS) {
+                let value = rev_yn(cell)
+                    .ok_or_else(|| anyhow!("Expected Y or N, found {cell}"))
+                    .context(format!(
+                        "Could not parse '{}' privilege in line {i}:\n  {line}",
+                        db_priv_field_human_readable_name(field)
+                    ))?;
+                *row.get_privilege_mut_by_name(field).unwrap() = value;
+            }
+            Ok(row)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(execute_priv: bool, alter_routine_priv: bool) -> RoutinePrivilegeRow {
+        RoutinePrivilegeRow {
+            execute_priv,
+            alter_routine_priv,
+            ..RoutinePrivilegeRow::new(
+                "alice_db".into(),
+                "refresh_grades".to_string(),
+                RoutineType::Procedure,
+                "alice_user".into(),
+            )
+        }
+    }
+
+    fn routine(name: &str, routine_type: RoutineType) -> StoredRoutine {
+        StoredRoutine {
+            db: "alice_db".into(),
+            name: name.to_string(),
+            routine_type,
+        }
+    }
+
+    #[test]
+    fn test_with_privileges_from_mysql_set() {
+        let parsed = RoutinePrivilegeRow::new(
+            "alice_db".into(),
+            "refresh_grades".to_string(),
+            RoutineType::Procedure,
+            "alice_user".into(),
+        )
+        .with_privileges_from_mysql_set("Execute,Alter Routine");
+        assert_eq!(parsed, row(true, true));
+    }
+
+    #[test]
+    fn test_parse_routine_privilege_edit_entry() {
+        let entry = RoutinePrivilegeEditEntry::parse_from_str(
+            "alice_db.refresh_grades:alice_user:+x",
+            &PrivilegeAliases::new(),
+        )
+        .unwrap();
+        assert_eq!(entry.routine_type, None);
+        assert_eq!(entry.database, MySQLDatabase::from("alice_db"));
+        assert_eq!(entry.routine, "refresh_grades");
+        assert_eq!(entry.user, MySQLUser::from("alice_user"));
+        assert_eq!(
+            entry.privilege_edit.type_,
+            DatabasePrivilegeEditEntryType::Add
+        );
+
+        let entry = RoutinePrivilegeEditEntry::parse_from_str(
+            "function:alice_db.average:alice_user:A",
+            &PrivilegeAliases::new(),
+        )
+        .unwrap();
+        assert_eq!(entry.routine_type, Some(RoutineType::Function));
+
+        assert!(
+            RoutinePrivilegeEditEntry::parse_from_str(
+                "alice_db:alice_user:x",
+                &PrivilegeAliases::new()
+            )
+            .is_err()
+        );
+        assert!(
+            RoutinePrivilegeEditEntry::parse_from_str(
+                "trigger:alice_db.refresh_grades:alice_user:x",
+                &PrivilegeAliases::new()
+            )
+            .is_err()
+        );
+        assert!(
+            RoutinePrivilegeEditEntry::parse_from_str(
+                "alice_db.refresh_grades:alice_user:xs",
+                &PrivilegeAliases::new()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_routine_type() {
+        let edit = |arg: &str| {
+            RoutinePrivilegeEditEntry::parse_from_str(arg, &PrivilegeAliases::new()).unwrap()
+        };
+        let routines = [
+            routine("refresh_grades", RoutineType::Procedure),
+            routine("average", RoutineType::Procedure),
+            routine("average", RoutineType::Function),
+        ];
+
+        assert_eq!(
+            edit("alice_db.refresh_grades:alice_user:x")
+                .resolve_routine_type(&routines)
+                .unwrap(),
+            RoutineType::Procedure
+        );
+        assert!(
+            edit("alice_db.average:alice_user:x")
+                .resolve_routine_type(&routines)
+                .is_err()
+        );
+        assert_eq!(
+            edit("function:alice_db.average:alice_user:x")
+                .resolve_routine_type(&routines)
+                .unwrap(),
+            RoutineType::Function
+        );
+        assert!(
+            edit("alice_db.missing:alice_user:x")
+                .resolve_routine_type(&routines)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_create_or_modify_routine_privilege_rows() {
+        let existing = vec![row(true, false)];
+        let routines = [routine("refresh_grades", RoutineType::Procedure)];
+        let edit = |arg: &str| {
+            RoutinePrivilegeEditEntry::parse_from_str(arg, &PrivilegeAliases::new()).unwrap()
+        };
+
+        let diffs = create_or_modify_routine_privilege_rows(
+            &existing,
+            &[edit("alice_db.refresh_grades:alice_user:+P")],
+            &routines,
+        )
+        .unwrap();
+        assert_eq!(
+            diffs,
+            BTreeSet::from([RoutinePrivilegesDiff::Modified(
+                RoutinePrivilegeRowDiff::from_rows(&row(true, false), &row(true, true))
+            )])
+        );
+
+        let diffs = create_or_modify_routine_privilege_rows(
+            &existing,
+            &[edit("alice_db.refresh_grades:alice_user:-x")],
+            &routines,
+        )
+        .unwrap();
+        assert_eq!(
+            diffs,
+            BTreeSet::from([RoutinePrivilegesDiff::Deleted(row(true, false))])
+        );
+
+        assert!(
+            create_or_modify_routine_privilege_rows(
+                &existing,
+                &[edit("alice_db.missing:alice_user:x")],
+                &routines,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_routine_privileges_diff_to_sql_statements() {
+        let diff = RoutinePrivilegesDiff::Modified(RoutinePrivilegeRowDiff::from_rows(
+            &row(true, false),
+            &row(false, true),
+        ));
+        assert_eq!(
+            diff.to_sql_statements(),
+            vec![
+                "GRANT ALTER ROUTINE ON PROCEDURE `alice_db`.`refresh_grades` TO 'alice_user'@'%'"
+                    .to_string(),
+                "REVOKE EXECUTE ON PROCEDURE `alice_db`.`refresh_grades` FROM 'alice_user'@'%'"
+                    .to_string(),
+            ]
+        );
+        assert!(diff.applies_to(Some(&row(true, false))));
+        assert!(!diff.applies_to(Some(&row(true, true))));
+        assert!(!diff.applies_to(None));
+    }
+
+    #[test]
+    fn test_routine_privileges_diff_to_sql_statements_quotes_names() {
+        let diff = RoutinePrivilegesDiff::New(RoutinePrivilegeRow {
+            execute_priv: true,
+            ..RoutinePrivilegeRow::new(
+                "alice_db".into(),
+                "refresh_grades` TO 'alice_user'@'%' WITH GRANT OPTION -- ".to_string(),
+                RoutineType::Procedure,
+                "alice_user".into(),
+            )
+        });
+        assert_eq!(
+            diff.to_sql_statements(),
+            vec![
+                "GRANT EXECUTE ON PROCEDURE `alice_db`.`refresh_grades`` TO 'alice_user'@'%' WITH GRANT OPTION -- ` TO 'alice_user'@'%'"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_routine_editor_content_roundtrip() {
+        let rows = vec![
+            row(true, false),
+            RoutinePrivilegeRow {
+                routine: "average".to_string(),
+                routine_type: RoutineType::Function,
+                ..row(true, true)
+            },
+        ];
+        let routines = [
+            routine("refresh_grades", RoutineType::Procedure),
+            routine("average", RoutineType::Function),
+        ];
+        let content = generate_routine_editor_content(&rows, &routines, "alice");
+        assert_eq!(
+            parse_routine_privilege_data_from_editor_content(&content).unwrap(),
+            rows
+        );
+
+        let content = generate_routine_editor_content(&[], &routines, "alice");
+        assert!(
+            parse_routine_privilege_data_from_editor_content(&content)
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(
+            parse_routine_privilege_data_from_editor_content(
+                "alice_db trigger refresh_grades alice_user Y N"
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Centralized styling of colored terminal output.
+//!
+//! All colored output should go through [`paint`], which decides whether to
+//! emit ANSI escape codes based on the `--color` flag, the `NO_COLOR`
+//! environment variable (see <https://no-color.org>) and whether the output
+//! is a terminal.
+//!
+//! The colors used for each [`Role`] can be changed with a small theme file,
+//! located at `$MUSCL_THEME`, `$XDG_CONFIG_HOME/muscl/theme.toml` or
+//! `~/.config/muscl/theme.toml`:
+//!
+//! ```toml
+//! success = "green"
+//! error = "bright-red"
+//! diff_removed = "none"
+//! ```
+
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Use colors if the output is a terminal, and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+
+    /// Always use colors.
+    Always,
+
+    /// Never use colors.
+    Never,
+}
+
+impl ColorChoice {
+    #[must_use]
+    pub fn should_colorize(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Color {
+    None,
+    Bold,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+}
+
+impl Color {
+    fn ansi_code(self) -> Option<&'static str> {
+        match self {
+            Color::None => None,
+            Color::Bold => Some("1"),
+            Color::Black => Some("30"),
+            Color::Red => Some("31"),
+            Color::Green => Some("32"),
+            Color::Yellow => Some("33"),
+            Color::Blue => Some("34"),
+            Color::Magenta => Some("35"),
+            Color::Cyan => Some("36"),
+            Color::White => Some("37"),
+            Color::BrightRed => Some("91"),
+            Color::BrightGreen => Some("92"),
+            Color::BrightYellow => Some("93"),
+            Color::BrightBlue => Some("94"),
+            Color::BrightMagenta => Some("95"),
+            Color::BrightCyan => Some("96"),
+        }
+    }
+}
+
+/// The semantic role of a piece of output, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Success,
+    Error,
+    Warning,
+    Emphasis,
+    DiffAdded,
+    DiffRemoved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub success: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub emphasis: Color,
+    pub diff_added: Color,
+    pub diff_removed: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            success: Color::Green,
+            error: Color::Red,
+            warning: Color::Yellow,
+            emphasis: Color::Bold,
+            diff_added: Color::Green,
+            diff_removed: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    #[must_use]
+    pub fn color_for(&self, role: Role) -> Color {
+        match role {
+            Role::Success => self.success,
+            Role::Error => self.error,
+            Role::Warning => self.warning,
+            Role::Emphasis => self.emphasis,
+            Role::DiffAdded => self.diff_added,
+            Role::DiffRemoved => self.diff_removed,
+        }
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("MUSCL_THEME") {
+            return Some(PathBuf::from(path));
+        }
+
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("muscl").join("theme.toml"))
+    }
+
+    /// Read a theme from the given path.
+    pub fn read_from_path(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Load the theme of the current user, falling back to the default theme
+    /// if no theme file exists or it could not be parsed.
+    ///
+    /// **WARNING:** This must not be called before privileges are dropped.
+    #[must_use]
+    pub fn load() -> Self {
+        let Some(path) = Self::default_path() else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match Self::read_from_path(&path) {
+            Ok(theme) => theme,
+            Err(err) => {
+                tracing::warn!("Failed to read theme from {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    colorize: bool,
+    theme: Theme,
+}
+
+static STYLE: OnceLock<Style> = OnceLock::new();
+
+/// Set up the output style for the rest of the process.
+///
+/// If this is never called, output is colored according to [`ColorChoice::Auto`]
+/// with the default theme.
+pub fn init(color_choice: ColorChoice) {
+    let colorize = color_choice.should_colorize();
+    let theme = if colorize {
+        Theme::load()
+    } else {
+        Theme::default()
+    };
+    let _ = STYLE.set(Style { colorize, theme });
+}
+
+fn current_style() -> &'static Style {
+    STYLE.get_or_init(|| Style {
+        colorize: ColorChoice::Auto.should_colorize(),
+        theme: Theme::default(),
+    })
+}
+
+fn paint_with(colorize: bool, theme: &Theme, role: Role, text: &str) -> String {
+    match theme.color_for(role).ansi_code() {
+        Some(code) if colorize => format!("\x1b[{code}m{text}\x1b[0m"),
+        _ => text.to_string(),
+    }
+}
+
+/// Wrap the text in the color for the given role, if colors are enabled.
+#[must_use]
+pub fn paint(role: Role, text: &str) -> String {
+    let style = current_style();
+    paint_with(style.colorize, &style.theme, role, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint_with() {
+        let theme = Theme::default();
+        assert_eq!(paint_with(false, &theme, Role::Error, "abc"), "abc");
+        assert_eq!(
+            paint_with(true, &theme, Role::Error, "abc"),
+            "\x1b[31mabc\x1b[0m"
+        );
+
+        let theme = Theme {
+            error: Color::None,
+            ..Theme::default()
+        };
+        assert_eq!(paint_with(true, &theme, Role::Error, "abc"), "abc");
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let theme: Theme = toml::from_str(indoc::indoc! {r#"
+            success = "bright-green"
+            diff_removed = "none"
+        "#})
+        .unwrap();
+
+        assert_eq!(theme.success, Color::BrightGreen);
+        assert_eq!(theme.diff_removed, Color::None);
+        assert_eq!(theme.error, Theme::default().error);
+    }
+}
@@ -0,0 +1,232 @@
+//! Shared rendering of the tables printed by the `show-*` commands.
+//!
+//! Every table is described by a list of [`TableColumn`]s, each with a stable
+//! kebab-case id that can be used with `--columns` and `--sort-by`.
+
+use clap::Args;
+use prettytable::{Cell, Row, Table, format::Alignment};
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct TableViewArgs {
+    /// Only show the given columns, in the given order
+    ///
+    /// Takes a comma separated list of column ids, e.g. `--columns user,locked`.
+    /// This flag has no effect when used with --json
+    #[arg(long, value_name = "COLUMN,...", value_delimiter = ',')]
+    pub columns: Option<Vec<String>>,
+
+    /// Sort the rows by the given column
+    ///
+    /// Prefix the column id with `-` to sort in descending order, e.g. `--sort-by=-size`.
+    /// This flag has no effect when used with --json
+    #[arg(long, value_name = "COLUMN", allow_hyphen_values = true)]
+    pub sort_by: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    pub id: &'static str,
+    pub header: String,
+    pub centered: bool,
+}
+
+impl TableColumn {
+    #[must_use]
+    pub fn new(id: &'static str, header: impl Into<String>) -> Self {
+        Self {
+            id,
+            header: header.into(),
+            centered: false,
+        }
+    }
+
+    #[must_use]
+    pub fn centered(mut self) -> Self {
+        self.centered = true;
+        self
+    }
+}
+
+/// The value used when sorting by a column.
+///
+/// Numbers are kept separate from their displayed text, so that e.g. sizes
+/// shown as `1.2 MB` still sort numerically.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableCell {
+    pub text: String,
+    pub sort_key: SortKey,
+}
+
+impl TableCell {
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            sort_key: SortKey::Text(text.to_lowercase()),
+            text,
+        }
+    }
+
+    #[must_use]
+    pub fn number(text: impl Into<String>, value: u64) -> Self {
+        Self {
+            text: text.into(),
+            sort_key: SortKey::Number(value),
+        }
+    }
+}
+
+impl TableViewArgs {
+    fn sort_column(&self) -> Option<(&str, bool)> {
+        self.sort_by
+            .as_deref()
+            .map(|sort_by| match sort_by.strip_prefix('-') {
+                Some(column) => (column, true),
+                None => (sort_by, false),
+            })
+    }
+
+    /// Check that all the columns mentioned by the flags exist in the table.
+    pub fn validate(&self, column_ids: &[&str]) -> anyhow::Result<()> {
+        let mentioned_columns = self
+            .columns
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(self.sort_column().map(|(column, _)| column));
+
+        for column in mentioned_columns {
+            if !column_ids.contains(&column) {
+                anyhow::bail!(
+                    "Unknown column '{}', available columns are: {}",
+                    column,
+                    column_ids.join(", ")
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Select and sort the columns and rows according to the flags.
+    ///
+    /// Returns the indices of the selected columns, and the sorted rows.
+    fn arrange(
+        &self,
+        columns: &[TableColumn],
+        mut rows: Vec<Vec<TableCell>>,
+    ) -> (Vec<usize>, Vec<Vec<TableCell>>) {
+        let selected_columns = match &self.columns {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| columns.iter().position(|column| column.id == id))
+                .collect(),
+            None => (0..columns.len()).collect(),
+        };
+
+        if let Some((id, descending)) = self.sort_column()
+            && let Some(index) = columns.iter().position(|column| column.id == id)
+        {
+            rows.sort_by(|a, b| {
+                let ordering = a[index].sort_key.cmp(&b[index].sort_key);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        (selected_columns, rows)
+    }
+
+    #[must_use]
+    pub fn render(&self, columns: &[TableColumn], rows: Vec<Vec<TableCell>>) -> Table {
+        let (selected_columns, rows) = self.arrange(columns, rows);
+
+        let mut table = Table::new();
+        table.add_row(Row::new(
+            selected_columns
+                .iter()
+                .map(|&index| Cell::new(&columns[index].header))
+                .collect(),
+        ));
+
+        for row in rows {
+            table.add_row(Row::new(
+                selected_columns
+                    .iter()
+                    .map(|&index| {
+                        if columns[index].centered {
+                            Cell::new_align(&row[index].text, Alignment::CENTER)
+                        } else {
+                            Cell::new(&row[index].text)
+                        }
+                    })
+                    .collect(),
+            ));
+        }
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrange() {
+        let columns = vec![
+            TableColumn::new("name", "Name"),
+            TableColumn::new("size", "Size"),
+        ];
+        let rows = vec![
+            vec![TableCell::text("b"), TableCell::number("2 kB", 2000)],
+            vec![TableCell::text("a"), TableCell::number("10 B", 10)],
+            vec![TableCell::text("c"), TableCell::number("1 MB", 1_000_000)],
+        ];
+
+        let view = TableViewArgs {
+            columns: Some(vec!["size".to_string()]),
+            sort_by: Some("-size".to_string()),
+        };
+        assert!(view.validate(&["name", "size"]).is_ok());
+
+        let (selected, sorted) = view.arrange(&columns, rows.clone());
+        assert_eq!(selected, vec![1]);
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|row| row[0].text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["c", "b", "a"]
+        );
+
+        let view = TableViewArgs {
+            columns: None,
+            sort_by: Some("name".to_string()),
+        };
+        let (selected, sorted) = view.arrange(&columns, rows);
+        assert_eq!(selected, vec![0, 1]);
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|row| row[0].text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+
+        let view = TableViewArgs {
+            columns: Some(vec!["nonexistent".to_string()]),
+            sort_by: None,
+        };
+        assert!(view.validate(&["name", "size"]).is_err());
+    }
+}
@@ -1,34 +1,54 @@
 use std::os::unix::net::UnixStream as StdUnixStream;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Context;
 use clap::{CommandFactory, Parser, Subcommand, crate_version};
 use clap_complete::CompleteEnv;
 use clap_verbosity_flag::{InfoLevel, Verbosity};
 use tokio::net::UnixStream as TokioUnixStream;
-use tokio_stream::StreamExt;
 
 use muscl_lib::{
     client::{
         commands::{
-            CheckAuthArgs, CreateDbArgs, CreateUserArgs, DropDbArgs, DropUserArgs, EditPrivsArgs,
-            LockUserArgs, PasswdUserArgs, ShowDbArgs, ShowPrivsArgs, ShowUserArgs, UnlockUserArgs,
-            check_authorization, create_databases, create_users, drop_databases, drop_users,
-            edit_database_privileges, lock_users, passwd_user, show_database_privileges,
-            show_databases, show_users, unlock_users,
+            AcceptGrantArgs, AdminArgs, AdoptArgs, CheckAuthArgs, CheckNameArgs, CleanupPrivsArgs,
+            CreateDbArgs, CreateUserArgs, DropDbArgs, DropUserArgs, EditColumnPrivsArgs,
+            EditPrivsArgs, EditRoutinePrivsArgs, GrantRoleArgs, KillConnectionsArgs,
+            ListSnapshotsArgs, LockUserArgs, OfferGrantArgs, PasswdUserArgs, RequireSslArgs,
+            RollbackPrivsArgs, SearchArgs, ServerInfoArgs, SetLimitsArgs, SetUserCommentArgs,
+            ShowColumnPrivsArgs, ShowConnectionsArgs, ShowDbArgs, ShowPrivsArgs,
+            ShowRoutinePrivsArgs, ShowRoutinesArgs, ShowUserArgs, SnapshotPrivsArgs, StatsArgs,
+            TransferDbArgs, UnlockUserArgs, accept_grant, admin, adopt, check_authorization,
+            check_names, cleanup_privileges, create_databases, create_users, drop_databases,
+            drop_users, edit_column_privileges, edit_database_privileges, edit_routine_privileges,
+            grant_role, kill_connections, list_snapshots, lock_users, offer_grant, passwd_user,
+            require_ssl, rollback_privileges, search, server_info, set_limits, set_user_comment,
+            show_column_privileges, show_connections, show_database_privileges, show_databases,
+            show_routine_privileges, show_routines, show_users, snapshot_privileges, stats,
+            transfer_database, unlock_users,
         },
+        config::{self as client_config, ClientConfig},
         mysql_admutils_compatibility::{mysql_dbadm, mysql_useradm},
     },
     core::{
         bootstrap::bootstrap_server_connection_and_drop_privileges,
         common::{ASCII_BANNER, KIND_REGARDS},
-        protocol::{ClientToServerMessageStream, Response, create_client_to_server_message_stream},
+        exit_code::{CommandFailed, ExitCode},
+        output::{self, OutputFormat, OutputOptions},
+        pager,
+        protocol::{
+            ClientToServerMessageStream, DEFAULT_READY_TIMEOUT,
+            create_client_to_server_message_stream_with_response_timeout, wait_for_server_ready,
+        },
+        style::ColorChoice,
     },
 };
 
-#[cfg(feature = "suid-sgid-mode")]
 use muscl_lib::core::common::executing_in_suid_sgid_mode;
 
+#[cfg(feature = "direct-mode")]
+use muscl_lib::core::bootstrap::bootstrap_direct_connection_and_drop_privileges;
+
 const fn long_version() -> &'static str {
     macro_rules! feature {
         ($title:expr, $flag:expr) => {
@@ -52,6 +72,8 @@ const fn long_version() -> &'static str {
         "[features]\n",
         feature!("SUID/SGID mode", "suid-sgid-mode"),
         "\n",
+        feature!("Direct mode", "direct-mode"),
+        "\n",
         feature!(
             "mysql-admutils compatibility",
             "mysql-admutils-compatibility"
@@ -87,11 +109,27 @@ const EXAMPLES: &str = const_format::concatcp!(
   # Show which users have privileges on which databases
   muscl show-privs
   muscl sp
+
+  # Find databases and users with 'test' in their name
+  muscl search '*test*'
+"#,
+);
+
+const EXIT_STATUS: &str = const_format::concatcp!(
+    color_print::cstr!("<bold><underline>Exit status:</underline></bold>"),
+    r#"
+  0   Success
+  1   Any other error
+  64  Invalid command line arguments (EX_USAGE)
+  69  The muscl server could not be reached (EX_UNAVAILABLE)
+  75  The database server is temporarily unavailable (EX_TEMPFAIL)
+  77  Not allowed to manage one of the databases or users (EX_NOPERM)
 "#,
 );
 
 const BEFORE_LONG_HELP: &str = const_format::concatcp!("\x1b[1m", ASCII_BANNER, "\x1b[0m");
-const AFTER_LONG_HELP: &str = const_format::concatcp!(EXAMPLES, "\n", KIND_REGARDS,);
+const AFTER_LONG_HELP: &str =
+    const_format::concatcp!(EXAMPLES, "\n", EXIT_STATUS, "\n", KIND_REGARDS,);
 
 /// Database administration tool for non-admin users to manage their own MySQL databases and users.
 ///
@@ -121,10 +159,14 @@ struct Args {
     //       characters. It should in theory be possible for `edit-privs` to ignore any options
     //       specified here, but in practice clap is being difficult to work with.
     /// Path to the socket of the server.
+    ///
+    /// On Linux, a path starting with `@` refers to a socket in the abstract namespace.
+    /// Defaults to the value of `MUSCL_SOCKET`, or `server_socket` in the client config.
     #[arg(
         long = "server-socket",
         value_name = "PATH",
         value_hint = clap::ValueHint::FilePath,
+        env = "MUSCL_SOCKET",
         global = true,
         hide_short_help = true
     )]
@@ -132,17 +174,81 @@ struct Args {
 
     /// Config file to use for the server.
     ///
-    /// This is only useful when running in SUID/SGID mode.
-    #[cfg(feature = "suid-sgid-mode")]
+    /// This is only useful when running in SUID/SGID mode or direct mode.
+    /// Defaults to the value of `MUSCL_CONFIG`.
+    #[cfg(any(feature = "suid-sgid-mode", feature = "direct-mode"))]
     #[arg(
         long = "config",
         value_name = "PATH",
         value_hint = clap::ValueHint::FilePath,
+        env = "MUSCL_CONFIG",
         global = true,
         hide_short_help = true
     )]
     config_path: Option<PathBuf>,
 
+    /// Connect directly to the database instead of going through the muscl server.
+    ///
+    /// The database credentials are read from the server config file.
+    #[cfg(feature = "direct-mode")]
+    #[arg(long = "direct", global = true, hide_short_help = true)]
+    direct: bool,
+
+    /// When to use colors in the output.
+    ///
+    /// Colors are also disabled by setting the `NO_COLOR` environment variable.
+    /// Defaults to the value of `MUSCL_COLOR`, or `auto`.
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        default_value = "auto",
+        env = "MUSCL_COLOR",
+        global = true,
+        hide_short_help = true
+    )]
+    color: ColorChoice,
+
+    /// The format to print command results in.
+    ///
+    /// `json` has the same effect as giving `--json` to every command that supports it.
+    /// Defaults to the value of `MUSCL_FORMAT`, `format` in the client config, or `human`.
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        env = "MUSCL_FORMAT",
+        global = true,
+        hide_short_help = true
+    )]
+    format: Option<OutputFormat>,
+
+    /// How many seconds to wait for the server if it is not responding, e.g. while it restarts.
+    ///
+    /// Defaults to the value of `MUSCL_WAIT_FOR_SERVER`, or 5 seconds.
+    #[arg(
+        long = "wait-for-server",
+        value_name = "SECONDS",
+        global = true,
+        hide_short_help = true
+    )]
+    wait_for_server: Option<u64>,
+
+    /// How many seconds to wait for each response from the server before giving up.
+    ///
+    /// Defaults to the value of `MUSCL_TIMEOUT`, `timeout` in the client config, or waiting forever.
+    /// This also bounds the wait for the server to become ready, which is 60 seconds otherwise.
+    #[arg(
+        long = "timeout",
+        value_name = "SECONDS",
+        env = "MUSCL_TIMEOUT",
+        global = true,
+        hide_short_help = true
+    )]
+    timeout: Option<u64>,
+
+    /// Do not pipe long output into a pager.
+    #[arg(long = "no-pager", global = true, hide_short_help = true)]
+    no_pager: bool,
+
     #[command(flatten)]
     verbose: Verbosity<InfoLevel>,
 }
@@ -153,6 +259,9 @@ const EDIT_PRIVS_EXAMPLES: &str = color_print::cstr!(
   # Open interactive editor to edit privileges
   muscl edit-privs
 
+  # Open interactive editor with only the privileges of user `my_user`
+  muscl edit-privs --user my_user
+
   # Set privileges `SELECT`, `INSERT`, and `UPDATE` for user `my_user` on database `my_db`
   muscl edit-privs my_db my_user siu
 
@@ -164,6 +273,9 @@ const EDIT_PRIVS_EXAMPLES: &str = color_print::cstr!(
 
   # Set miscellaneous privileges for multiple users on database `my_db`
   muscl edit-privs -p my_db:my_user:siu -p my_db:my_other_user:+ct -p my_db:yet_another_user:-d
+
+  # Show the changes as a unified diff, which can be pasted into a ticket
+  muscl edit-privs --diff-format unified my_db my_user +d
 "#
 );
 
@@ -174,11 +286,20 @@ pub enum ClientCommand {
     #[command(alias = "ca")]
     CheckAuth(CheckAuthArgs),
 
+    /// Check whether the specified databases or users could be created, without creating them
+    ///
+    /// Runs the same checks as creating them would, and explains why each name would be rejected,
+    /// e.g. because of invalid characters, a prefix you do not own, or because it already exists.
+    #[command(alias = "cn")]
+    CheckName(CheckNameArgs),
+
     /// Create one or more databases
     #[command(alias = "cd")]
     CreateDb(CreateDbArgs),
 
     /// Delete one or more databases
+    ///
+    /// If no names are provided on a terminal, you can pick among your databases interactively.
     #[command(alias = "dd")]
     DropDb(DropDbArgs),
 
@@ -188,6 +309,20 @@ pub enum ClientCommand {
     #[command(alias = "sd")]
     ShowDb(ShowDbArgs),
 
+    /// Move a database to another of your prefixes, together with its privileges
+    ///
+    /// This is useful when a project changes hands, e.g. from your own user to one of your groups.
+    /// The database is renamed, so anything using the old name needs to be updated.
+    /// Views, triggers, stored routines and events can not be moved, and need to be recreated.
+    TransferDb(TransferDbArgs),
+
+    /// Take over databases or users that were created outside of muscl
+    ///
+    /// Checks that they belong to one of your prefixes, and records that you took them over.
+    /// With `--normalize`, they are also changed to look like the ones muscl creates,
+    /// so that every command works on them.
+    Adopt(AdoptArgs),
+
     /// Print user privileges for one or more databases
     ///
     /// If no database names are provided, all databases you have access to will be shown.
@@ -202,7 +337,8 @@ pub enum ClientCommand {
     ///
     ///    If no arguments are provided, the user will be prompted to edit the privileges using a text editor.
     ///
-    ///    You can configure your preferred text editor by setting the `VISUAL` or `EDITOR` environment variables.
+    ///    You can configure your preferred text editor by setting `editor` in the client config,
+    ///    or the `VISUAL` or `EDITOR` environment variables.
     ///
     ///    Follow the instructions inside the editor for more information.
     ///
@@ -250,18 +386,103 @@ pub enum ClientCommand {
     )]
     EditPrivs(EditPrivsArgs),
 
+    /// Print user privileges on single columns of the tables in one or more databases
+    ///
+    /// If no database names are provided, all databases you have access to will be shown.
+    ShowColumnPrivs(ShowColumnPrivsArgs),
+
+    /// Change user privileges on single columns of the tables in your databases
+    ///
+    /// Without `-p`, the privileges are edited in a text editor, like with `edit-privs`.
+    /// With `-p`, the flag value should be formatted as
+    /// `DB_NAME.TABLE_NAME.COLUMN_NAME:USER_NAME:[+-]PRIVILEGES`, where the privileges
+    /// are any of `s` (SELECT), `i` (INSERT), `u` (UPDATE) and `r` (REFERENCES), or `A` for all of them.
+    EditColumnPrivs(EditColumnPrivsArgs),
+
+    /// Print the stored procedures and functions in one or more databases
+    ///
+    /// If no database names are provided, all databases you have access to will be shown.
+    ShowRoutines(ShowRoutinesArgs),
+
+    /// Print user privileges on the stored procedures and functions in one or more databases
+    ///
+    /// If no database names are provided, all databases you have access to will be shown.
+    ShowRoutinePrivs(ShowRoutinePrivsArgs),
+
+    /// Change user privileges on the stored procedures and functions in your databases
+    ///
+    /// Without `-p`, the privileges are edited in a text editor, like with `edit-privs`.
+    /// With `-p`, the flag value should be formatted as
+    /// `[procedure:|function:]DB_NAME.ROUTINE_NAME:USER_NAME:[+-]PRIVILEGES`, where the privileges
+    /// are any of `x` (EXECUTE) and `P` (ALTER ROUTINE), or `A` for both of them.
+    EditRoutinePrivs(EditRoutinePrivsArgs),
+
+    /// Remove privileges that refer to databases or users that no longer exist
+    ///
+    /// Dropping a database or user does not remove the privileges that other users
+    /// have on it. This lists such leftover privileges on your databases and users,
+    /// and removes them after asking for confirmation.
+    CleanupPrivs(CleanupPrivsArgs),
+
+    /// Store the current privileges on your databases under a tag
+    ///
+    /// The snapshot can later be restored with `muscl rollback-privs`,
+    /// e.g. after a large `edit-privs` that did not go as planned.
+    SnapshotPrivs(SnapshotPrivsArgs),
+
+    /// List the privilege snapshots you have taken
+    ListSnapshots(ListSnapshotsArgs),
+
+    /// Restore the privileges on your databases to a snapshot
+    ///
+    /// This shows the changes needed to get back to the snapshot,
+    /// and applies them after asking for confirmation.
+    RollbackPrivs(RollbackPrivsArgs),
+
+    /// Offer privileges on one of your databases to a user owned by someone else
+    ///
+    /// The privileges are only given once the owner of the user accepts the offer
+    /// with `muscl accept-grant`. Offers expire after a while if nobody accepts them.
+    OfferGrant(OfferGrantArgs),
+
+    /// List or accept privileges offered to your users on databases owned by someone else
+    ///
+    /// Without an ID, this lists the pending offers made to your users, and those you made yourself.
+    AcceptGrant(AcceptGrantArgs),
+
     /// Create one or more users
     #[command(alias = "cu")]
     CreateUser(CreateUserArgs),
 
     /// Delete one or more users
+    ///
+    /// If no names are provided on a terminal, you can pick among your users interactively.
     #[command(alias = "du")]
     DropUser(DropUserArgs),
 
     /// Change the MySQL password for a user
+    ///
+    /// If no username is provided on a terminal, you can pick among your users interactively.
     #[command(alias = "pu")]
     PasswdUser(PasswdUserArgs),
 
+    /// Set a comment on a user, e.g. to note what the user is used for
+    ///
+    /// The comment is shown by `show-user`. This is only supported on MySQL 8.0.21 and newer.
+    SetUserComment(SetUserCommentArgs),
+
+    /// Require a user to connect with TLS
+    ///
+    /// With `--x509`, the client also has to present a valid certificate.
+    /// Use `--none` to allow unencrypted connections again. The requirement is shown by `show-user`.
+    RequireSsl(RequireSslArgs),
+
+    /// Limit how many queries and updates a user may run per hour
+    ///
+    /// This can be used to throttle batch jobs that put too much load on the server.
+    /// A limit of `0` removes it again. The current limits are shown by `show-user --json`.
+    SetLimits(SetLimitsArgs),
+
     /// Print information about one or more users
     ///
     /// If no username is provided, all users you have access will be shown.
@@ -269,12 +490,61 @@ pub enum ClientCommand {
     ShowUser(ShowUserArgs),
 
     /// Lock account for one or more users
+    ///
+    /// If no names are provided on a terminal, you can pick among your users interactively.
     #[command(alias = "lu")]
     LockUser(LockUserArgs),
 
     /// Unlock account for one or more users
+    ///
+    /// If no names are provided on a terminal, you can pick among your users interactively.
     #[command(alias = "uu")]
     UnlockUser(UnlockUserArgs),
+
+    /// Kill all open connections of one or more users
+    ///
+    /// This is useful after changing a leaked password with `passwd-user`,
+    /// as the connections that are already open are not affected by the new password,
+    /// or to get rid of the connections of an application that has hung.
+    KillConnections(KillConnectionsArgs),
+
+    /// Show the open connections of one or more users
+    ///
+    /// Shows where each connection comes from, what it is doing and for how long,
+    /// and the start of the statement it is running, e.g. to find out who is holding a lock.
+    /// If no username is provided, the connections of all your users are shown.
+    ShowConnections(ShowConnectionsArgs),
+
+    /// Grant a role to one or more users
+    ///
+    /// The role is another user that you manage, and the users will be able to
+    /// activate its privileges with `SET ROLE`. This is only supported on MySQL 8 and newer.
+    /// If no usernames are provided on a terminal, you can pick among your users interactively.
+    #[command(alias = "gr")]
+    GrantRole(GrantRoleArgs),
+
+    /// Find your databases and users by a name pattern
+    ///
+    /// The pattern is a glob by default, e.g. `muscl search 'alice_*test*'`,
+    /// or a regular expression when using `--regex`.
+    Search(SearchArgs),
+
+    /// Print information about the server
+    ///
+    /// This is useful to include when reporting bugs.
+    #[command(alias = "si")]
+    ServerInfo(ServerInfoArgs),
+
+    /// Print an overview of the databases and users for each of your prefixes
+    ///
+    /// Shows the number of databases, their total size, the number of users
+    /// and how many of them are locked, for your own user and each of your groups.
+    Stats(StatsArgs),
+
+    /// Commands for the administrators of the server
+    ///
+    /// These are only available to members of the admin groups in the server config.
+    Admin(AdminArgs),
 }
 
 pub async fn handle_command(
@@ -283,24 +553,76 @@ pub async fn handle_command(
 ) -> anyhow::Result<()> {
     match command {
         ClientCommand::CheckAuth(args) => check_authorization(args, server_connection).await,
+        ClientCommand::CheckName(args) => check_names(args, server_connection).await,
         ClientCommand::CreateDb(args) => create_databases(args, server_connection).await,
         ClientCommand::DropDb(args) => drop_databases(args, server_connection).await,
         ClientCommand::ShowDb(args) => show_databases(args, server_connection).await,
+        ClientCommand::TransferDb(args) => transfer_database(args, server_connection).await,
+        ClientCommand::Adopt(args) => adopt(args, server_connection).await,
         ClientCommand::ShowPrivs(args) => show_database_privileges(args, server_connection).await,
         ClientCommand::EditPrivs(args) => {
             edit_database_privileges(args, None, server_connection).await
         }
+        ClientCommand::ShowColumnPrivs(args) => {
+            show_column_privileges(args, server_connection).await
+        }
+        ClientCommand::EditColumnPrivs(args) => {
+            edit_column_privileges(args, server_connection).await
+        }
+        ClientCommand::ShowRoutines(args) => show_routines(args, server_connection).await,
+        ClientCommand::ShowRoutinePrivs(args) => {
+            show_routine_privileges(args, server_connection).await
+        }
+        ClientCommand::EditRoutinePrivs(args) => {
+            edit_routine_privileges(args, server_connection).await
+        }
+        ClientCommand::CleanupPrivs(args) => cleanup_privileges(args, server_connection).await,
+        ClientCommand::SnapshotPrivs(args) => snapshot_privileges(args, server_connection).await,
+        ClientCommand::ListSnapshots(args) => list_snapshots(args, server_connection).await,
+        ClientCommand::RollbackPrivs(args) => rollback_privileges(args, server_connection).await,
+        ClientCommand::OfferGrant(args) => offer_grant(args, server_connection).await,
+        ClientCommand::AcceptGrant(args) => accept_grant(args, server_connection).await,
         ClientCommand::CreateUser(args) => create_users(args, server_connection).await,
         ClientCommand::DropUser(args) => drop_users(args, server_connection).await,
         ClientCommand::PasswdUser(args) => passwd_user(args, server_connection).await,
+        ClientCommand::SetUserComment(args) => set_user_comment(args, server_connection).await,
+        ClientCommand::RequireSsl(args) => require_ssl(args, server_connection).await,
+        ClientCommand::SetLimits(args) => set_limits(args, server_connection).await,
         ClientCommand::ShowUser(args) => show_users(args, server_connection).await,
         ClientCommand::LockUser(args) => lock_users(args, server_connection).await,
         ClientCommand::UnlockUser(args) => unlock_users(args, server_connection).await,
+        ClientCommand::KillConnections(args) => kill_connections(args, server_connection).await,
+        ClientCommand::ShowConnections(args) => show_connections(args, server_connection).await,
+        ClientCommand::GrantRole(args) => grant_role(args, server_connection).await,
+        ClientCommand::Search(args) => search(args, server_connection).await,
+        ClientCommand::ServerInfo(args) => server_info(args, server_connection).await,
+        ClientCommand::Stats(args) => stats(args, server_connection).await,
+        ClientCommand::Admin(args) => admin(args, server_connection).await,
     }
 }
 
 /// **WARNING:** This function may be run with elevated privileges.
-fn main() -> anyhow::Result<()> {
+fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => report_error(&err),
+    }
+}
+
+/// Print an error that made it all the way up to `main`, and map it to an exit code.
+fn report_error(err: &anyhow::Error) -> std::process::ExitCode {
+    if let Some(clap_err) = err.downcast_ref::<clap::Error>() {
+        clap_err.print().ok();
+    } else if !err.is::<CommandFailed>() {
+        // The result of a failed command has already been printed.
+        eprintln!("Error: {err:?}");
+    }
+
+    ExitCode::for_error(err).into()
+}
+
+/// **WARNING:** This function may be run with elevated privileges.
+fn run() -> anyhow::Result<()> {
     if handle_dynamic_completion()?.is_some() {
         return Ok(());
     }
@@ -310,18 +632,64 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let args: Args = Args::parse();
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        // --help and --version
+        Err(err) if !err.use_stderr() => err.exit(),
+        Err(err) => return Err(err.into()),
+    };
+
+    // The client config belongs to the user, and must not be read with elevated privileges.
+    // When running as SUID/SGID, it is read after the privileges are dropped instead,
+    // which is fine since the server socket can not be chosen in that mode anyway.
+    let early_client_config = if executing_in_suid_sgid_mode()? {
+        None
+    } else {
+        Some(ClientConfig::load())
+    };
+
+    let server_socket_path = args.server_socket_path.or_else(|| {
+        early_client_config
+            .as_ref()
+            .and_then(|config| config.server_socket.clone())
+    });
+    let verbosity = args.verbose.tracing_level_filter();
 
+    #[cfg(feature = "direct-mode")]
+    let connection = if args.direct {
+        bootstrap_direct_connection_and_drop_privileges(args.config_path, args.verbose)?
+    } else {
+        bootstrap_server_connection_and_drop_privileges(
+            server_socket_path,
+            args.config_path,
+            args.verbose,
+            args.wait_for_server.map(Duration::from_secs),
+        )?
+    };
+
+    #[cfg(not(feature = "direct-mode"))]
     let connection = bootstrap_server_connection_and_drop_privileges(
-        args.server_socket_path,
+        server_socket_path,
         #[cfg(feature = "suid-sgid-mode")]
         args.config_path,
         #[cfg(not(feature = "suid-sgid-mode"))]
         None,
         args.verbose,
+        args.wait_for_server.map(Duration::from_secs),
     )?;
 
-    tokio_run_command(args.command, connection)?;
+    let client_config = early_client_config.unwrap_or_else(ClientConfig::load);
+
+    let format = args.format.or(client_config.format).unwrap_or_default();
+    let response_timeout = args
+        .timeout
+        .or(client_config.timeout)
+        .map(Duration::from_secs);
+    output::init(OutputOptions::new(format, args.color, verbosity));
+    pager::set_pager_enabled(!args.no_pager);
+    client_config::init(client_config);
+
+    tokio_run_command(args.command, connection, response_timeout)?;
 
     Ok(())
 }
@@ -380,6 +748,7 @@ fn handle_mysql_admutils_command() -> anyhow::Result<Option<()>> {
 fn tokio_run_command(
     command: ClientCommand,
     server_connection: StdUnixStream,
+    response_timeout: Option<Duration>,
 ) -> anyhow::Result<()> {
     tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -387,18 +756,20 @@ fn tokio_run_command(
         .context("Failed to start Tokio runtime")?
         .block_on(async {
             let tokio_socket = TokioUnixStream::from_std(server_connection)?;
-            let mut message_stream = create_client_to_server_message_stream(tokio_socket);
-
-            while let Some(Ok(message)) = message_stream.next().await {
-                match message {
-                    Response::Error(err) => {
-                        anyhow::bail!("{}", err);
-                    }
-                    Response::Ready => break,
-                    message => {
-                        eprintln!("Unexpected message from server: {:?}", message);
-                    }
-                }
+            let mut message_stream = create_client_to_server_message_stream_with_response_timeout(
+                tokio_socket,
+                response_timeout,
+            );
+
+            let motd = wait_for_server_ready(
+                &mut message_stream,
+                response_timeout.unwrap_or(DEFAULT_READY_TIMEOUT),
+            )
+            .await?;
+            if let Some(motd) = motd
+                && !output::options().quiet
+            {
+                eprintln!("{motd}\n");
             }
 
             handle_command(command, message_stream).await
@@ -1,13 +1,32 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::Context;
 use clap::{Parser, Subcommand};
 use clap_verbosity_flag::{InfoLevel, Verbosity};
+use tracing::level_filters::LevelFilter;
 use tracing_subscriber::layer::SubscriberExt;
 
 use muscl_lib::{
-    core::common::{ASCII_BANNER, DEFAULT_CONFIG_PATH, KIND_REGARDS},
-    server::{landlock::landlock_restrict_server, supervisor::Supervisor},
+    core::{
+        common::{ASCII_BANNER, DEFAULT_CONFIG_PATH, KIND_REGARDS},
+        output::{self, print_output},
+        protocol::{
+            LOG_LEVELS,
+            control::{ControlRequest, ControlResponse},
+        },
+    },
+    server::{
+        check_config::check_config,
+        config::ServerConfig,
+        control_socket::send_control_request,
+        landlock::{landlock_restrict_server, log_landlock_status},
+        log_level::reloadable_level_filter,
+        supervisor::Supervisor,
+        worker::run_worker,
+    },
 };
 
 #[derive(Parser, Debug, Clone)]
@@ -38,6 +57,15 @@ pub struct ServerArgs {
     )]
     socket_path: Option<PathBuf>,
 
+    /// Path to the admin control socket of a running server, used by `status`, `reload`,
+    /// `maintenance` and `log-level`. Defaults to `admin_socket_path` from the config file.
+    #[arg(
+        long = "admin-socket",
+        value_name = "PATH",
+        value_hint = clap::ValueHint::FilePath,
+    )]
+    admin_socket_path: Option<PathBuf>,
+
     /// Config file to use for the server.
     #[arg(
         long = "config",
@@ -58,6 +86,103 @@ pub enum ServerCommand {
 
     /// Start the server using systemd socket activation.
     SocketActivate,
+
+    /// Validate the configuration file and exit.
+    ///
+    /// This checks that the config file can be parsed, that the socket path and
+    /// group denylist file are usable, and that the Landlock sandbox can be set up.
+    /// The exit code is non-zero if any of the checks failed, which makes this
+    /// suitable for use in systemd's `ExecStartPre=`.
+    CheckConfig {
+        /// Also try to connect to the MySQL server.
+        #[arg(long)]
+        connect: bool,
+    },
+
+    /// Handle a single connection, passed as stdin.
+    ///
+    /// This is started by the server for every connection when `process_isolation` is enabled.
+    #[command(hide = true)]
+    Worker {
+        #[arg(long)]
+        maintenance_mode: bool,
+
+        #[arg(long, value_parser = LOG_LEVELS)]
+        log_level: Option<String>,
+    },
+
+    /// Show the status of the running server.
+    Status {
+        /// Print the information as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+
+    /// Make the running server reload its configuration.
+    Reload,
+
+    /// Turn maintenance mode of the running server on or off.
+    ///
+    /// While in maintenance mode, the server refuses new sessions from
+    /// everyone except members of the admin groups.
+    Maintenance {
+        #[arg(value_enum)]
+        mode: MaintenanceMode,
+    },
+
+    /// Gracefully stop the running server.
+    ///
+    /// The server stops accepting new connections, waits for the active sessions
+    /// to finish, and then exits. Sending `SIGQUIT` to the server does the same.
+    Drain {
+        /// Seconds to wait for the active sessions before exiting anyway.
+        /// Defaults to `drain_timeout` from the config file.
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+    },
+
+    /// Replace the running server with a new server process, without dropping connections.
+    ///
+    /// The new process is started from the server binary, and takes over the listening
+    /// sockets, while the old process finishes its active sessions and exits.
+    /// This is useful after upgrading the server package. Sending `SIGUSR2` to the server
+    /// does the same. Note that this does not work with Landlock enabled.
+    Upgrade,
+
+    /// Show or change the log level of the running server.
+    LogLevel {
+        /// The new log level, or nothing to show the current one
+        ///
+        /// Note that 'trace' logs SQL queries, which might include passwords.
+        #[arg(value_parser = LOG_LEVELS)]
+        level: Option<String>,
+
+        /// Print the information as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceMode {
+    On,
+    Off,
+}
+
+impl ServerCommand {
+    /// Whether this command talks to a running server through the admin socket,
+    /// rather than running the server itself.
+    fn is_control_command(&self) -> bool {
+        matches!(
+            self,
+            ServerCommand::Status { .. }
+                | ServerCommand::Reload
+                | ServerCommand::Maintenance { .. }
+                | ServerCommand::Drain { .. }
+                | ServerCommand::Upgrade
+                | ServerCommand::LogLevel { .. }
+        )
+    }
 }
 
 const LOG_LEVEL_WARNING: &str = r#"
@@ -76,7 +201,12 @@ const MIN_TOKIO_WORKER_THREADS: usize = 4;
 fn main() -> anyhow::Result<()> {
     let args = ServerArgs::parse();
 
-    if !args.disable_landlock {
+    // The config check builds the Landlock ruleset by itself, and should be able to
+    // report problems with the config rather than failing to start.
+    let is_config_check = matches!(args.subcmd, ServerCommand::CheckConfig { .. });
+    let is_control_command = args.subcmd.is_control_command();
+
+    if !args.disable_landlock && !is_config_check && !is_control_command {
         landlock_restrict_server(args.config_path.as_deref())
             .context("Failed to apply Landlock restrictions to the server process")?;
     }
@@ -99,6 +229,10 @@ fn trace_server_prelude() {
 }
 
 async fn handle_command(args: ServerArgs) -> anyhow::Result<()> {
+    if args.subcmd.is_control_command() {
+        return handle_control_command(args).await;
+    }
+
     let mut auto_detected_systemd_mode = false;
 
     #[cfg(target_os = "linux")]
@@ -114,19 +248,31 @@ async fn handle_command(args: ServerArgs) -> anyhow::Result<()> {
     #[cfg(not(target_os = "linux"))]
     let systemd_mode = false;
 
+    // Worker processes follow the log level of the server, which might have been changed at runtime.
+    let level_filter = match &args.subcmd {
+        ServerCommand::Worker {
+            log_level: Some(log_level),
+            ..
+        } => LevelFilter::from_str(log_level)?,
+        _ => args.verbosity.tracing_level_filter(),
+    };
+    let is_worker = matches!(args.subcmd, ServerCommand::Worker { .. });
+
     if systemd_mode {
         #[cfg(target_os = "linux")]
         {
             let subscriber = tracing_subscriber::Registry::default()
-                .with(args.verbosity.tracing_level_filter())
+                .with(reloadable_level_filter(level_filter))
                 .with(tracing_journald::layer()?);
 
             tracing::subscriber::set_global_default(subscriber)
                 .context("Failed to set global default tracing subscriber")?;
 
-            trace_server_prelude();
+            if !is_worker {
+                trace_server_prelude();
+            }
 
-            if args.verbosity.tracing_level_filter() >= tracing::Level::TRACE {
+            if level_filter >= tracing::Level::TRACE {
                 tracing::warn!("{}", LOG_LEVEL_WARNING.trim());
             }
 
@@ -138,7 +284,7 @@ async fn handle_command(args: ServerArgs) -> anyhow::Result<()> {
         }
     } else {
         let subscriber = tracing_subscriber::Registry::default()
-            .with(args.verbosity.tracing_level_filter())
+            .with(reloadable_level_filter(level_filter))
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_line_number(cfg!(debug_assertions))
@@ -150,11 +296,15 @@ async fn handle_command(args: ServerArgs) -> anyhow::Result<()> {
         tracing::subscriber::set_global_default(subscriber)
             .context("Failed to set global default tracing subscriber")?;
 
-        trace_server_prelude();
+        if !is_worker {
+            trace_server_prelude();
+        }
 
         tracing::debug!("Running in standalone mode");
     }
 
+    log_landlock_status();
+
     let config_path = args
         .config_path
         .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
@@ -179,5 +329,123 @@ async fn handle_command(args: ServerArgs) -> anyhow::Result<()> {
                 .run()
                 .await
         }
+        ServerCommand::CheckConfig { connect } => {
+            if !check_config(&config_path, connect).await {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        ServerCommand::Worker {
+            maintenance_mode, ..
+        } => run_worker(&config_path, maintenance_mode).await,
+        _ => unreachable!("Control commands are handled by handle_control_command"),
     }
 }
+
+async fn handle_control_command(args: ServerArgs) -> anyhow::Result<()> {
+    let admin_socket_path = match args.admin_socket_path {
+        Some(path) => path,
+        None => {
+            let config_path = args
+                .config_path
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+            ServerConfig::read_config_from_path(&config_path)?
+                .admin_socket_path
+                .context(concat!(
+                    "The admin socket is not configured, ",
+                    "set `admin_socket_path` in the config file or use `--admin-socket`"
+                ))?
+        }
+    };
+
+    match args.subcmd {
+        ServerCommand::Status { json } => {
+            match control_request(&admin_socket_path, ControlRequest::Status).await? {
+                ControlResponse::Status(status) => {
+                    print_output(&status, &output::options().with_json(json));
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        ServerCommand::Reload => {
+            match control_request(&admin_socket_path, ControlRequest::Reload).await? {
+                ControlResponse::ReloadStarted => {
+                    println!("Reloading the server, check the server logs for the result");
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        ServerCommand::Maintenance { mode } => {
+            let enabled = mode == MaintenanceMode::On;
+            match control_request(
+                &admin_socket_path,
+                ControlRequest::SetMaintenanceMode(enabled),
+            )
+            .await?
+            {
+                ControlResponse::SetMaintenanceMode(previous) if previous == enabled => {
+                    println!(
+                        "Maintenance mode was already {}",
+                        if enabled { "on" } else { "off" }
+                    );
+                }
+                ControlResponse::SetMaintenanceMode(_) => {
+                    println!(
+                        "Turned maintenance mode {}",
+                        if enabled { "on" } else { "off" }
+                    );
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        ServerCommand::Drain { timeout } => {
+            match control_request(&admin_socket_path, ControlRequest::Drain(timeout)).await? {
+                ControlResponse::DrainStarted(active_sessions) => {
+                    println!(
+                        "Draining the server, waiting for {active_sessions} active sessions to finish"
+                    );
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        ServerCommand::Upgrade => {
+            match control_request(&admin_socket_path, ControlRequest::Upgrade).await? {
+                ControlResponse::UpgradeStarted => {
+                    println!("Upgrading the server, check the server logs for the result");
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        ServerCommand::LogLevel { level, json } => {
+            match control_request(&admin_socket_path, ControlRequest::LogLevel(level)).await? {
+                ControlResponse::LogLevel(Ok(log_level)) => {
+                    print_output(&log_level, &output::options().with_json(json));
+                }
+                ControlResponse::LogLevel(Err(err)) => {
+                    anyhow::bail!(err.to_error_message());
+                }
+                response => unexpected_control_response(response)?,
+            }
+        }
+        _ => unreachable!("Not a control command"),
+    }
+
+    Ok(())
+}
+
+async fn control_request(
+    admin_socket_path: &Path,
+    request: ControlRequest,
+) -> anyhow::Result<ControlResponse> {
+    match send_control_request(admin_socket_path, request).await? {
+        ControlResponse::NotAuthorized => anyhow::bail!(
+            "You need to be root or a member of one of the admin groups to use the admin socket"
+        ),
+        ControlResponse::Error(message) => anyhow::bail!(message),
+        response => Ok(response),
+    }
+}
+
+fn unexpected_control_response(response: ControlResponse) -> anyhow::Result<()> {
+    anyhow::bail!("Unexpected response from the server: {:?}", response)
+}
@@ -4,3 +4,6 @@ extern crate prettytable;
 pub mod client;
 pub mod core;
 pub mod server;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
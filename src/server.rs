@@ -1,7 +1,25 @@
+pub mod adopt;
 pub mod authorization;
+pub mod backend_capabilities;
+pub mod bulk;
+pub mod check_config;
 mod common;
 pub mod config;
+pub mod control_socket;
+pub mod grant_offers;
+pub mod handover;
+pub mod inactive_users;
 pub mod landlock;
+pub mod log_level;
+pub mod maintenance;
+pub mod policy;
+pub mod privilege_snapshots;
+pub mod progress;
+pub mod scheduled_privilege_changes;
+pub mod scheduler;
+pub mod seccomp;
 pub mod session_handler;
 pub mod sql;
+pub mod state_file;
 pub mod supervisor;
+pub mod worker;
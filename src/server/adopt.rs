@@ -0,0 +1,179 @@
+//! Adopting databases and users that were created outside of muscl.
+//!
+//! Anything matching one of the prefixes of a unix user can already be managed by them,
+//! but objects created by hand often do not look like the ones muscl creates: users can be
+//! limited to a single host, while muscl only manages users on `%`, and privilege rows can
+//! carry the grant option, which would let the user pass privileges on outside of muscl,
+//! unless the server allows it with `extended_database_privileges`.
+//! Adopting an object checks that it belongs to the unix user, and with `normalize`,
+//! fixes up these differences. Every adoption is logged, as a record of who took over what.
+
+use std::collections::BTreeMap;
+
+use indoc::indoc;
+use sqlx::MySqlConnection;
+
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            AdoptError, AdoptRequest, AdoptResponse, AdoptedObject,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        sql::{
+            database_operations::unsafe_database_exists, quote_literal, uncached_query,
+            user_operations::unsafe_user_exists,
+        },
+    },
+};
+
+pub async fn adopt(
+    request: AdoptRequest,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+    extended_database_privileges: bool,
+) -> AdoptResponse {
+    let mut results = BTreeMap::new();
+
+    for db_or_user in request.names {
+        if let Err(err) = validate_db_or_user_request(&db_or_user, unix_user, group_denylist) {
+            results.insert(db_or_user, Err(AdoptError::ValidationError(err)));
+            continue;
+        }
+
+        let exists = match &db_or_user {
+            DbOrUser::Database(name) => unsafe_database_exists(name, &mut *connection).await,
+            DbOrUser::User(name) => unsafe_user_exists(name, &mut *connection).await,
+        };
+        match exists {
+            Ok(true) => {}
+            Ok(false) => {
+                results.insert(db_or_user, Err(AdoptError::DoesNotExist));
+                continue;
+            }
+            Err(err) => {
+                results.insert(db_or_user, Err(AdoptError::MySqlError(err.to_string())));
+                continue;
+            }
+        }
+
+        let result = if request.normalize {
+            match &db_or_user {
+                DbOrUser::Database(name) => {
+                    unsafe_normalize_database(name, &mut *connection, extended_database_privileges)
+                        .await
+                }
+                DbOrUser::User(name) => {
+                    unsafe_normalize_user(name, &mut *connection, extended_database_privileges)
+                        .await
+                }
+            }
+        } else {
+            Ok(AdoptedObject::default())
+        };
+
+        match &result {
+            Ok(adopted) => tracing::info!(
+                "Unix user '{}' adopted {} '{}': {:?}",
+                unix_user.username,
+                db_or_user.lowercased_noun(),
+                db_or_user.name(),
+                adopted
+            ),
+            Err(err) => tracing::error!(
+                "Failed to adopt {} '{}': {:?}",
+                db_or_user.lowercased_noun(),
+                db_or_user.name(),
+                err
+            ),
+        }
+
+        results.insert(db_or_user, result);
+    }
+
+    results
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+async fn unsafe_normalize_database(
+    database_name: &MySQLDatabase,
+    connection: &mut MySqlConnection,
+    keep_grant_options: bool,
+) -> Result<AdoptedObject, AdoptError> {
+    if keep_grant_options {
+        return Ok(AdoptedObject::default());
+    }
+
+    let revoked_grant_options = sqlx::query(
+        "UPDATE `mysql`.`db` SET `Grant_priv` = 'N' WHERE `Db` = ? AND `Grant_priv` = 'Y'",
+    )
+    .bind(database_name.as_str())
+    .execute(&mut *connection)
+    .await
+    .map_err(|err| AdoptError::MySqlError(err.to_string()))?
+    .rows_affected();
+
+    Ok(AdoptedObject {
+        moved_from_host: None,
+        revoked_grant_options,
+    })
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+async fn unsafe_normalize_user(
+    db_user: &MySQLUser,
+    connection: &mut MySqlConnection,
+    keep_grant_options: bool,
+) -> Result<AdoptedObject, AdoptError> {
+    let hosts: Vec<String> = sqlx::query_scalar(indoc! {r"
+        SELECT CAST(`Host` AS CHAR(255))
+        FROM `mysql`.`user`
+        WHERE `User` = ?
+        ORDER BY `Host`
+    "})
+    .bind(db_user.as_str())
+    .fetch_all(&mut *connection)
+    .await
+    .map_err(|err| AdoptError::MySqlError(err.to_string()))?;
+
+    let moved_from_host = match hosts.as_slice() {
+        hosts if hosts.iter().any(|host| host == "%") => None,
+        [host] => {
+            uncached_query(&format!(
+                "RENAME USER {}@{} TO {}@'%'",
+                quote_literal(db_user),
+                quote_literal(host),
+                quote_literal(db_user),
+            ))
+            .execute(&mut *connection)
+            .await
+            .map_err(|err| AdoptError::MySqlError(err.to_string()))?;
+            Some(host.clone())
+        }
+        _ => return Err(AdoptError::AmbiguousUserHosts(hosts)),
+    };
+
+    let revoked_grant_options = if keep_grant_options {
+        0
+    } else {
+        sqlx::query(
+            "UPDATE `mysql`.`db` SET `Grant_priv` = 'N' WHERE `User` = ? AND `Grant_priv` = 'Y'",
+        )
+        .bind(db_user.as_str())
+        .execute(&mut *connection)
+        .await
+        .map_err(|err| AdoptError::MySqlError(err.to_string()))?
+        .rows_affected()
+    };
+
+    Ok(AdoptedObject {
+        moved_from_host,
+        revoked_grant_options,
+    })
+}
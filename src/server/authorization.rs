@@ -2,14 +2,20 @@ use std::{collections::HashSet, path::Path};
 
 use anyhow::Context;
 use nix::unistd::Group;
+use sqlx::MySqlConnection;
 
-use crate::core::{
-    common::UnixUser,
-    protocol::{
-        CheckAuthorizationError,
-        request_validation::{GroupDenylist, validate_db_or_user_request},
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            CheckAuthorizationError, CheckNameError, CheckNamesResponse,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        types::DbOrUser,
+    },
+    server::sql::{
+        database_operations::unsafe_database_exists, user_operations::unsafe_user_exists,
     },
-    types::DbOrUser,
 };
 
 pub async fn check_authorization(
@@ -32,6 +38,38 @@ pub async fn check_authorization(
     results
 }
 
+/// Runs every check that creating the databases or users would run, without creating them,
+/// so that the user can see up front why a name would be rejected.
+pub async fn check_names(
+    dbs_or_users: Vec<DbOrUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> CheckNamesResponse {
+    let mut results = std::collections::BTreeMap::new();
+
+    for db_or_user in dbs_or_users {
+        if let Err(err) = validate_db_or_user_request(&db_or_user, unix_user, group_denylist) {
+            results.insert(db_or_user, Err(CheckNameError::ValidationError(err)));
+            continue;
+        }
+
+        let exists = match &db_or_user {
+            DbOrUser::Database(name) => unsafe_database_exists(name, &mut *connection).await,
+            DbOrUser::User(name) => unsafe_user_exists(name, &mut *connection).await,
+        };
+
+        let result = match exists {
+            Ok(false) => Ok(()),
+            Ok(true) => Err(CheckNameError::AlreadyExists),
+            Err(err) => Err(CheckNameError::MySqlError(err.to_string())),
+        };
+        results.insert(db_or_user, result);
+    }
+
+    results
+}
+
 /// Reads and parses a group denylist file, returning a set of GUIDs
 ///
 /// The format of the denylist file is expected to be one group name or GID per line.
@@ -0,0 +1,180 @@
+use std::fmt;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+
+/// The flavor of the database server that muscl is connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatabaseFlavor {
+    MySql,
+    MariaDb,
+}
+
+impl fmt::Display for DatabaseFlavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseFlavor::MySql => write!(f, "MySQL"),
+            DatabaseFlavor::MariaDb => write!(f, "MariaDB"),
+        }
+    }
+}
+
+/// Describes which features the connected database server supports.
+///
+/// This is detected once whenever the connection pool is (re)created,
+/// and passed on to the sql modules so that they can pick the right queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    pub flavor: DatabaseFlavor,
+
+    /// The version string as reported by `SELECT VERSION()`.
+    pub version_string: String,
+
+    /// The parsed `(major, minor, patch)` version of the server.
+    pub version: (u32, u32, u32),
+
+    /// Whether account data is stored as JSON in `mysql.global_priv` (MariaDB 10.4+),
+    /// rather than in columns of `mysql.user`.
+    pub supports_json_priv_column: bool,
+
+    /// Whether the server supports `ALTER USER ... ACCOUNT LOCK`.
+    pub supports_account_locking: bool,
+
+    /// Whether the server supports roles.
+    pub supports_roles: bool,
+
+    /// Whether the server supports `RENAME USER`.
+    pub supports_rename_user: bool,
+
+    /// Whether the server supports partial revokes (MySQL 8.0.16+).
+    pub supports_partial_revokes: bool,
+
+    /// Whether the server supports `ALTER USER ... COMMENT` (MySQL 8.0.21+).
+    pub supports_user_comments: bool,
+}
+
+impl BackendCapabilities {
+    /// Queries the server version and derives the capabilities from it.
+    pub async fn detect(pool: &MySqlPool) -> anyhow::Result<Self> {
+        let version: String = sqlx::query_scalar("SELECT VERSION()")
+            .fetch_one(pool)
+            .await
+            .context("Failed to query database version")?;
+
+        let capabilities = Self::from_version_string(&version);
+        tracing::debug!(
+            "Connected to {} database server, version {}.{}.{}",
+            capabilities.flavor,
+            capabilities.version.0,
+            capabilities.version.1,
+            capabilities.version.2,
+        );
+
+        Ok(capabilities)
+    }
+
+    #[must_use]
+    pub fn from_version_string(version_string: &str) -> Self {
+        let flavor = if version_string.to_lowercase().contains("mariadb") {
+            DatabaseFlavor::MariaDb
+        } else {
+            DatabaseFlavor::MySql
+        };
+
+        // Older MariaDB versions prefix the version with `5.5.5-` for compatibility
+        // with the MySQL replication protocol.
+        let numeric_version = match flavor {
+            DatabaseFlavor::MariaDb => version_string
+                .strip_prefix("5.5.5-")
+                .unwrap_or(version_string),
+            DatabaseFlavor::MySql => version_string,
+        };
+        let version = parse_version(numeric_version);
+
+        match flavor {
+            DatabaseFlavor::MariaDb => Self {
+                flavor,
+                version_string: version_string.to_owned(),
+                version,
+                supports_json_priv_column: version >= (10, 4, 0),
+                supports_account_locking: version >= (10, 4, 2),
+                supports_roles: version >= (10, 0, 5),
+                supports_rename_user: true,
+                supports_partial_revokes: false,
+                supports_user_comments: false,
+            },
+            DatabaseFlavor::MySql => Self {
+                flavor,
+                version_string: version_string.to_owned(),
+                version,
+                supports_json_priv_column: false,
+                supports_account_locking: version >= (5, 7, 6),
+                supports_roles: version >= (8, 0, 0),
+                supports_rename_user: true,
+                supports_partial_revokes: version >= (8, 0, 16),
+                supports_user_comments: version >= (8, 0, 21),
+            },
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_mariadb(&self) -> bool {
+        self.flavor == DatabaseFlavor::MariaDb
+    }
+}
+
+/// Parses the leading `major.minor.patch` part of a version string,
+/// treating missing or unparsable components as `0`.
+fn parse_version(version: &str) -> (u32, u32, u32) {
+    let numeric_part = version
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .next()
+        .unwrap_or_default();
+
+    let mut components = numeric_part
+        .split('.')
+        .map(|component| component.parse::<u32>().unwrap_or(0));
+
+    (
+        components.next().unwrap_or(0),
+        components.next().unwrap_or(0),
+        components.next().unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("8.0.36"), (8, 0, 36));
+        assert_eq!(parse_version("10.11.6-MariaDB-0+deb12u1"), (10, 11, 6));
+        assert_eq!(parse_version("8.4"), (8, 4, 0));
+        assert_eq!(parse_version("garbage"), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_from_version_string() {
+        let mariadb = BackendCapabilities::from_version_string("10.11.6-MariaDB-0+deb12u1");
+        assert_eq!(mariadb.flavor, DatabaseFlavor::MariaDb);
+        assert_eq!(mariadb.version, (10, 11, 6));
+        assert!(mariadb.supports_json_priv_column);
+        assert!(mariadb.supports_roles);
+        assert!(!mariadb.supports_partial_revokes);
+        assert!(!mariadb.supports_user_comments);
+
+        let old_mariadb = BackendCapabilities::from_version_string("5.5.5-10.3.38-MariaDB");
+        assert_eq!(old_mariadb.version, (10, 3, 38));
+        assert!(!old_mariadb.supports_json_priv_column);
+
+        let mysql = BackendCapabilities::from_version_string("8.0.36");
+        assert_eq!(mysql.flavor, DatabaseFlavor::MySql);
+        assert!(!mysql.supports_json_priv_column);
+        assert!(mysql.supports_roles);
+        assert!(mysql.supports_partial_revokes);
+        assert!(mysql.supports_user_comments);
+    }
+}
@@ -0,0 +1,118 @@
+//! Processing the independent items of bulk requests concurrently.
+//!
+//! Besides the connection of the session, a bulk request may borrow a few
+//! extra connections from the pool, as configured by `mysql.bulk_concurrency`,
+//! and work through its items on all of them at once.
+
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use futures_util::future::{BoxFuture, join_all};
+use sqlx::{MySql, MySqlConnection, MySqlPool, pool::PoolConnection};
+
+use crate::server::progress::ProgressReporter;
+
+/// How long to wait for each extra connection, before going on with the ones we got.
+///
+/// Bulk requests should not hold up other sessions when the pool is busy,
+/// so the extra connections are only taken if they are readily available.
+const EXTRA_CONNECTION_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The connections a bulk request can work on: the connection of the session,
+/// and any extra connections borrowed from the pool.
+pub struct BulkConnections<'a> {
+    session: &'a mut MySqlConnection,
+    extra: Vec<PoolConnection<MySql>>,
+}
+
+impl<'a> BulkConnections<'a> {
+    /// Only use the connection of the session.
+    pub fn single(session: &'a mut MySqlConnection) -> Self {
+        Self {
+            session,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Borrow extra connections from the pool, so that up to `concurrency` of the
+    /// `item_count` items can be processed at once.
+    pub async fn acquire(
+        session: &'a mut MySqlConnection,
+        pool: &MySqlPool,
+        concurrency: usize,
+        item_count: usize,
+    ) -> Self {
+        let wanted = concurrency.min(item_count).saturating_sub(1);
+        let mut extra = Vec::with_capacity(wanted);
+        for _ in 0..wanted {
+            match tokio::time::timeout(EXTRA_CONNECTION_TIMEOUT, pool.acquire()).await {
+                Ok(Ok(connection)) => extra.push(connection),
+                Ok(Err(err)) => {
+                    tracing::warn!(
+                        "Failed to acquire extra connection for bulk request: {}",
+                        err
+                    );
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        if !extra.is_empty() {
+            tracing::debug!("Processing bulk request on {} connections", extra.len() + 1);
+        }
+
+        Self { session, extra }
+    }
+
+    /// The connection of the session, for work that has to happen on a single connection.
+    pub fn session(&mut self) -> &mut MySqlConnection {
+        self.session
+    }
+
+    /// Run `process` for every item, with one item at a time on each connection.
+    ///
+    /// The results are returned in the order the items finished in.
+    ///
+    /// The future returned by `process` only borrows the connection, so anything
+    /// else it needs has to be owned by it. This keeps the session future `Send`.
+    pub async fn process<T, R>(
+        &mut self,
+        items: Vec<T>,
+        progress: &ProgressReporter,
+        item_name: impl Fn(&T) -> String,
+        process: impl Fn(T, &mut MySqlConnection) -> BoxFuture<'_, R>,
+    ) -> Vec<R> {
+        let queue = &Mutex::new(items.into_iter());
+        let done = &AtomicUsize::new(0);
+        let finished = Mutex::new(Vec::new());
+        let results = &finished;
+        let item_name = &item_name;
+        let process = &process;
+
+        let connections = std::iter::once(&mut *self.session)
+            .chain(self.extra.iter_mut().map(|connection| &mut **connection));
+
+        join_all(connections.map(|connection| async move {
+            loop {
+                let Some(item) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                progress.report(done.load(Ordering::Relaxed), &item_name(&item));
+
+                let result = process(item, connection).await;
+
+                done.fetch_add(1, Ordering::Relaxed);
+                results.lock().unwrap().push(result);
+            }
+        }))
+        .await;
+
+        finished.into_inner().unwrap()
+    }
+}
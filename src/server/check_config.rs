@@ -0,0 +1,270 @@
+use std::{fmt, path::Path};
+
+use anyhow::Context;
+
+use crate::server::{
+    authorization::read_and_parse_group_denylist, config::ServerConfig,
+    landlock::landlock_check_server, policy::PolicyEngine, supervisor::create_db_connection_pool,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Ok => write!(f, "[  OK  ]"),
+            CheckStatus::Warning => write!(f, "[ WARN ]"),
+            CheckStatus::Failed => write!(f, "[ FAIL ]"),
+            CheckStatus::Skipped => write!(f, "[ SKIP ]"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CheckReport {
+    has_failures: bool,
+}
+
+impl CheckReport {
+    fn report(&mut self, status: CheckStatus, message: impl AsRef<str>) {
+        if status == CheckStatus::Failed {
+            self.has_failures = true;
+        }
+        println!("{} {}", status, message.as_ref());
+    }
+
+    fn report_result<T>(
+        &mut self,
+        result: &anyhow::Result<T>,
+        ok_message: impl FnOnce(&T) -> String,
+        failure_message: &str,
+    ) {
+        match result {
+            Ok(value) => self.report(CheckStatus::Ok, ok_message(value)),
+            Err(err) => self.report(CheckStatus::Failed, format!("{failure_message}: {err:#}")),
+        }
+    }
+}
+
+/// Validates the server configuration at the given path and prints a report to stdout.
+///
+/// If `connect` is set, a connection to the MySQL server is attempted as well.
+///
+/// Returns `true` if all checks passed (warnings are allowed), and `false` otherwise.
+pub async fn check_config(config_path: &Path, connect: bool) -> bool {
+    let mut report = CheckReport::default();
+
+    let config = match ServerConfig::read_config_from_path(config_path) {
+        Ok(config) => {
+            report.report(
+                CheckStatus::Ok,
+                format!("Config file {config_path:?} parsed successfully"),
+            );
+            config
+        }
+        Err(err) => {
+            report.report(
+                CheckStatus::Failed,
+                format!("Failed to read config file {config_path:?}: {err:#}"),
+            );
+            return false;
+        }
+    };
+
+    match &config.socket_path {
+        Some(socket_path) => match socket_path.parent() {
+            Some(parent) if parent.is_dir() => report.report(
+                CheckStatus::Ok,
+                format!("Socket directory {parent:?} exists"),
+            ),
+            Some(parent) if parent.exists() => report.report(
+                CheckStatus::Failed,
+                format!("Socket directory {parent:?} exists, but is not a directory"),
+            ),
+            Some(parent) => report.report(
+                CheckStatus::Warning,
+                format!(
+                    "Socket directory {parent:?} does not exist, it will be created on startup"
+                ),
+            ),
+            None => report.report(
+                CheckStatus::Failed,
+                format!("Socket path {socket_path:?} is not a valid socket path"),
+            ),
+        },
+        None => report.report(
+            CheckStatus::Skipped,
+            "No socket path configured, expecting systemd socket activation",
+        ),
+    }
+
+    match &config.authorization.group_denylist_file {
+        Some(denylist_path) => report.report_result(
+            &read_and_parse_group_denylist(denylist_path),
+            |denylist| {
+                format!(
+                    "Group denylist {denylist_path:?} loaded with {} entries",
+                    denylist.len()
+                )
+            },
+            "Failed to load group denylist",
+        ),
+        None => report.report(CheckStatus::Skipped, "No group denylist file configured"),
+    }
+
+    if let Some(password_file) = &config.mysql.password_file {
+        report.report_result(
+            &std::fs::metadata(password_file).context(format!("Cannot access {password_file:?}")),
+            |_| format!("MySQL password file {password_file:?} is accessible"),
+            "Failed to access MySQL password file",
+        );
+    }
+
+    match &config.inactive_user_locking {
+        Some(locking) => match locking.state_file.parent() {
+            Some(state_dir) if state_dir.is_dir() => report.report(
+                CheckStatus::Ok,
+                format!(
+                    "Inactive user locking enabled after {} days, state directory {state_dir:?} exists",
+                    locking.inactive_days
+                ),
+            ),
+            _ => report.report(
+                CheckStatus::Failed,
+                format!(
+                    "Directory of inactive user state file {:?} does not exist",
+                    locking.state_file
+                ),
+            ),
+        },
+        None => report.report(CheckStatus::Skipped, "Inactive user locking is not enabled"),
+    }
+
+    match &config.grant_offers {
+        Some(grant_offers) => match grant_offers.state_file.parent() {
+            Some(state_dir) if state_dir.is_dir() => report.report(
+                CheckStatus::Ok,
+                format!(
+                    "Grant offers enabled for {} days, state directory {state_dir:?} exists",
+                    grant_offers.expiry_days
+                ),
+            ),
+            _ => report.report(
+                CheckStatus::Failed,
+                format!(
+                    "Directory of grant offer state file {:?} does not exist",
+                    grant_offers.state_file
+                ),
+            ),
+        },
+        None => report.report(CheckStatus::Skipped, "Grant offers are not enabled"),
+    }
+
+    match &config.privilege_snapshots {
+        Some(privilege_snapshots) => match privilege_snapshots.state_file.parent() {
+            Some(state_dir) if state_dir.is_dir() => report.report(
+                CheckStatus::Ok,
+                format!(
+                    "Privilege snapshots enabled for up to {} snapshots per user, state directory {state_dir:?} exists",
+                    privilege_snapshots.max_snapshots
+                ),
+            ),
+            _ => report.report(
+                CheckStatus::Failed,
+                format!(
+                    "Directory of privilege snapshot state file {:?} does not exist",
+                    privilege_snapshots.state_file
+                ),
+            ),
+        },
+        None => report.report(CheckStatus::Skipped, "Privilege snapshots are not enabled"),
+    }
+
+    match &config.scheduled_privilege_changes {
+        Some(scheduled_privilege_changes) => {
+            match scheduled_privilege_changes.state_file.parent() {
+                Some(state_dir) if state_dir.is_dir() => report.report(
+                    CheckStatus::Ok,
+                    format!(
+                        "Scheduled privilege changes enabled, state directory {state_dir:?} exists"
+                    ),
+                ),
+                _ => report.report(
+                    CheckStatus::Failed,
+                    format!(
+                        "Directory of scheduled privilege change state file {:?} does not exist",
+                        scheduled_privilege_changes.state_file
+                    ),
+                ),
+            }
+        }
+        None => report.report(
+            CheckStatus::Skipped,
+            "Scheduled privilege changes are not enabled",
+        ),
+    }
+
+    match &config.policy {
+        Some(policy) => report.report_result(
+            &PolicyEngine::load(policy),
+            |_| match &policy.opa {
+                Some(opa) => format!(
+                    "Loaded {} policy module(s), and the OPA server at {} is configured",
+                    policy.modules.len(),
+                    opa.url
+                ),
+                None => format!("Loaded {} policy module(s)", policy.modules.len()),
+            },
+            "Failed to set up the policies",
+        ),
+        None => report.report(CheckStatus::Skipped, "No policy modules configured"),
+    }
+
+    if cfg!(target_os = "linux") {
+        report.report_result(
+            &landlock_check_server(Some(config_path)),
+            |_| "Landlock ruleset can be created".to_string(),
+            "Failed to create Landlock ruleset",
+        );
+    } else {
+        report.report(
+            CheckStatus::Skipped,
+            "Landlock is not supported on this platform",
+        );
+    }
+
+    if connect {
+        let version = check_mysql_connection(&config).await;
+        report.report_result(
+            &version,
+            |version| format!("Connected to database server (version {version})"),
+            "Failed to connect to the database server",
+        );
+    } else {
+        report.report(
+            CheckStatus::Skipped,
+            "MySQL connectivity (use --connect to check)",
+        );
+    }
+
+    !report.has_failures
+}
+
+async fn check_mysql_connection(config: &ServerConfig) -> anyhow::Result<String> {
+    let pool = create_db_connection_pool(&config.mysql).await?;
+
+    let version: String = sqlx::query_scalar("SELECT VERSION()")
+        .fetch_one(&pool)
+        .await
+        .context("Failed to query database version")?;
+
+    pool.close().await;
+
+    Ok(version)
+}
@@ -1,5 +1,5 @@
 use crate::core::{common::UnixUser, protocol::request_validation::GroupDenylist};
-use nix::unistd::Group;
+use nix::unistd::{Group, User};
 use sqlx::prelude::*;
 
 /// This function retrieves the groups of a user, filtering out any groups
@@ -24,6 +24,16 @@ pub fn get_user_filtered_groups(user: &UnixUser, group_denylist: &GroupDenylist)
         .collect()
 }
 
+/// Generate a short random ID for a request, to tag its lines in the server logs.
+///
+/// The ID is shown to the client on errors, so that users can quote it to the administrators.
+#[must_use]
+pub fn new_request_id() -> String {
+    let mut id = uuid::Uuid::new_v4().simple().to_string();
+    id.truncate(12);
+    id
+}
+
 /// This function creates a regex that matches items (users, databases)
 /// that belong to the user or any of the user's groups.
 pub fn create_user_group_matching_regex(user: &UnixUser, group_denylist: &GroupDenylist) -> String {
@@ -35,6 +45,22 @@ pub fn create_user_group_matching_regex(user: &UnixUser, group_denylist: &GroupD
     }
 }
 
+/// Find the unix user or group that owns a database or database user, based on its prefix.
+///
+/// Groups in the denylist do not own anything.
+pub fn find_name_owner(name: &str, group_denylist: &GroupDenylist) -> Option<String> {
+    name.match_indices('_')
+        .map(|(index, _)| &name[..index])
+        .find(|prefix| {
+            matches!(User::from_name(prefix), Ok(Some(_)))
+                || matches!(
+                    Group::from_name(prefix),
+                    Ok(Some(group)) if !group_denylist.contains(&group.gid.as_raw())
+                )
+        })
+        .map(str::to_string)
+}
+
 /// Some mysql versions with some collations mark some columns as binary fields,
 /// which in the current version of sqlx is not parsable as string.
 /// See: <https://github.com/launchbadge/sqlx/issues/3387>
@@ -73,4 +99,12 @@ mod tests {
         assert!(!re.is_match("user"));
         assert!(!re.is_match("usersomething"));
     }
+
+    #[test]
+    fn test_new_request_id() {
+        let id = new_request_id();
+        assert_eq!(id.len(), 12);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(id, new_request_id());
+    }
 }
@@ -1,11 +1,29 @@
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    time::Duration,
 };
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use sqlx::{ConnectOptions, mysql::MySqlConnectOptions};
+use sqlx::{
+    ConnectOptions,
+    mysql::{MySqlConnectOptions, MySqlPoolOptions},
+};
+
+use crate::{
+    core::{
+        common::executing_in_suid_sgid_mode,
+        database_privileges::{DatabasePrivilegeEdit, validate_privilege_alias},
+    },
+    server::scheduler::CronSchedule,
+};
+
+/// `PATH` used for `password_command` when running as SUID/SGID.
+const SUID_SGID_SAFE_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
 
 pub const DEFAULT_PORT: u16 = 3306;
 fn default_mysql_port() -> u16 {
@@ -17,6 +35,46 @@ fn default_mysql_timeout() -> u64 {
     DEFAULT_TIMEOUT
 }
 
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+fn default_mysql_max_connections() -> u32 {
+    DEFAULT_MAX_CONNECTIONS
+}
+
+pub const DEFAULT_MIN_CONNECTIONS: u32 = 0;
+fn default_mysql_min_connections() -> u32 {
+    DEFAULT_MIN_CONNECTIONS
+}
+
+pub const DEFAULT_ACQUIRE_TIMEOUT: u64 = 30;
+fn default_mysql_acquire_timeout() -> u64 {
+    DEFAULT_ACQUIRE_TIMEOUT
+}
+
+pub const DEFAULT_IDLE_TIMEOUT: u64 = 10 * 60;
+fn default_mysql_idle_timeout() -> u64 {
+    DEFAULT_IDLE_TIMEOUT
+}
+
+pub const DEFAULT_MAX_LIFETIME: u64 = 30 * 60;
+fn default_mysql_max_lifetime() -> u64 {
+    DEFAULT_MAX_LIFETIME
+}
+
+pub const DEFAULT_BULK_CONCURRENCY: u32 = 4;
+fn default_mysql_bulk_concurrency() -> u32 {
+    DEFAULT_BULK_CONCURRENCY
+}
+
+pub const DEFAULT_HANDSHAKE_TIMEOUT: u64 = 10;
+fn default_handshake_timeout() -> u64 {
+    DEFAULT_HANDSHAKE_TIMEOUT
+}
+
+pub const DEFAULT_DRAIN_TIMEOUT: u64 = 60;
+fn default_drain_timeout() -> u64 {
+    DEFAULT_DRAIN_TIMEOUT
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename = "mysql")]
 pub struct MysqlConfig {
@@ -27,11 +85,47 @@ pub struct MysqlConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub password_file: Option<PathBuf>,
+    /// A command (program followed by its arguments) which prints the password to stdout.
+    pub password_command: Option<Vec<String>>,
     #[serde(default = "default_mysql_timeout")]
     pub timeout: u64,
+    /// The maximum number of connections in the connection pool.
+    #[serde(default = "default_mysql_max_connections")]
+    pub max_connections: u32,
+    /// The number of idle connections the pool tries to keep open.
+    #[serde(default = "default_mysql_min_connections")]
+    pub min_connections: u32,
+    /// Seconds to wait for a free connection from the pool.
+    #[serde(default = "default_mysql_acquire_timeout")]
+    pub acquire_timeout: u64,
+    /// Seconds before an idle connection is closed, or `0` to keep them open indefinitely.
+    #[serde(default = "default_mysql_idle_timeout")]
+    pub idle_timeout: u64,
+    /// Seconds before a connection is closed and replaced, or `0` to never recycle connections.
+    #[serde(default = "default_mysql_max_lifetime")]
+    pub max_lifetime: u64,
+    /// The number of connections a single bulk request may use to process its items concurrently.
+    #[serde(default = "default_mysql_bulk_concurrency")]
+    pub bulk_concurrency: u32,
+    /// A `mysql://` URL of a read-only replica, which is used for requests that only read data.
+    pub replica_dsn: Option<String>,
 }
 
 impl MysqlConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.max_connections == 0 {
+            anyhow::bail!("mysql.max_connections must be at least 1");
+        }
+        if self.min_connections > self.max_connections {
+            anyhow::bail!(
+                "mysql.min_connections ({}) must not be larger than mysql.max_connections ({})",
+                self.min_connections,
+                self.max_connections
+            );
+        }
+        Ok(())
+    }
+
     pub fn as_mysql_connect_options(&self) -> anyhow::Result<MySqlConnectOptions> {
         let mut options = MySqlConnectOptions::new()
             .database("mysql")
@@ -41,16 +135,8 @@ impl MysqlConfig {
             options = options.username(username);
         }
 
-        if let Some(password_file) = &self.password_file {
-            let password = fs::read_to_string(password_file)
-                .with_context(|| {
-                    format!("Failed to read MySQL password file at {password_file:?}")
-                })?
-                .trim()
-                .to_owned();
+        if let Some(password) = self.resolve_password()? {
             options = options.password(&password);
-        } else if let Some(password) = &self.password {
-            options = options.password(password);
         }
 
         if let Some(socket_path) = &self.socket_path {
@@ -65,12 +151,94 @@ impl MysqlConfig {
         Ok(options)
     }
 
+    /// The connect options for the read-only replica, if one is configured.
+    pub fn as_replica_connect_options(&self) -> anyhow::Result<Option<MySqlConnectOptions>> {
+        let Some(replica_dsn) = &self.replica_dsn else {
+            return Ok(None);
+        };
+
+        let options = MySqlConnectOptions::from_str(replica_dsn)
+            .context("Failed to parse MySQL replica DSN")?
+            .database("mysql")
+            .log_statements(tracing::log::LevelFilter::Trace);
+
+        Ok(Some(options))
+    }
+
+    pub fn as_mysql_pool_options(&self) -> MySqlPoolOptions {
+        let seconds_or_none = |seconds: u64| (seconds != 0).then(|| Duration::from_secs(seconds));
+
+        MySqlPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout))
+            .idle_timeout(seconds_or_none(self.idle_timeout))
+            .max_lifetime(seconds_or_none(self.max_lifetime))
+    }
+
+    /// Resolves the password from `password_file`, `password_command` or `password`,
+    /// in that order of precedence.
+    ///
+    /// This is done every time the connection pool is (re)created, so that rotated
+    /// secrets are picked up on reload.
+    fn resolve_password(&self) -> anyhow::Result<Option<String>> {
+        if let Some(password_file) = &self.password_file {
+            let password = fs::read_to_string(password_file)
+                .with_context(|| {
+                    format!("Failed to read MySQL password file at {password_file:?}")
+                })?
+                .trim()
+                .to_owned();
+            return Ok(Some(password));
+        }
+
+        if let Some(password_command) = &self.password_command {
+            let (program, args) = password_command
+                .split_first()
+                .context("MySQL password command is empty")?;
+
+            let mut command = Command::new(program);
+            // NOTE: when running as SUID/SGID, the environment (and notably PATH)
+            //       is controlled by the unprivileged caller.
+            if executing_in_suid_sgid_mode().unwrap_or(true) {
+                command.env_clear().env("PATH", SUID_SGID_SAFE_PATH);
+            }
+
+            let output = command
+                .args(args)
+                .stdin(Stdio::null())
+                .stderr(Stdio::inherit())
+                .output()
+                .with_context(|| format!("Failed to run MySQL password command {program:?}"))?;
+
+            if !output.status.success() {
+                anyhow::bail!(
+                    "MySQL password command {:?} exited with {}",
+                    program,
+                    output.status
+                );
+            }
+
+            let password = String::from_utf8(output.stdout)
+                .context("MySQL password command did not output valid UTF-8")?
+                .trim()
+                .to_owned();
+            return Ok(Some(password));
+        }
+
+        Ok(self.password.clone())
+    }
+
     pub fn log_connection_notice(&self) {
         let mut display_config = self.to_owned();
         display_config.password = display_config
             .password
             .as_ref()
             .map(|_| "<REDACTED>".to_owned());
+        display_config.replica_dsn = display_config
+            .replica_dsn
+            .as_ref()
+            .map(|_| "<REDACTED>".to_owned());
         tracing::debug!(
             "Connecting to MySQL server with parameters: {:#?}",
             display_config
@@ -78,26 +246,563 @@ impl MysqlConfig {
     }
 }
 
+pub const DEFAULT_INACTIVE_USER_STATE_FILE: &str = "/var/lib/muscl/user_activity.json";
+fn default_inactive_user_state_file() -> PathBuf {
+    PathBuf::from(DEFAULT_INACTIVE_USER_STATE_FILE)
+}
+
+/// Configuration for automatically locking database users that have not connected for a while.
+///
+/// Activity is sampled from `performance_schema.accounts`, which needs to be enabled
+/// on the database server. Only the `'user'@'%'` account of an inactive user is locked.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InactiveUserLockingConfig {
+    /// Lock users that have not connected for this many days.
+    pub inactive_days: u64,
+    /// Where to keep track of when each user was last seen, across restarts.
+    #[serde(default = "default_inactive_user_state_file")]
+    pub state_file: PathBuf,
+    /// Only log which users would have been locked, without locking them.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// A command (program followed by its arguments) which is run for every locked user,
+    /// with the owning unix user or group, the database user and the number of inactive
+    /// days appended as arguments.
+    pub notify_command: Option<Vec<String>>,
+}
+
+impl InactiveUserLockingConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.inactive_days == 0 {
+            anyhow::bail!("inactive_user_locking.inactive_days must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+pub const DEFAULT_GRANT_OFFERS_STATE_FILE: &str = "/var/lib/muscl/grant_offers.json";
+fn default_grant_offers_state_file() -> PathBuf {
+    PathBuf::from(DEFAULT_GRANT_OFFERS_STATE_FILE)
+}
+
+pub const DEFAULT_GRANT_OFFER_EXPIRY_DAYS: u64 = 7;
+fn default_grant_offer_expiry_days() -> u64 {
+    DEFAULT_GRANT_OFFER_EXPIRY_DAYS
+}
+
+/// Configuration for offering privileges on a database to users owned by other
+/// unix users or groups, which need to be accepted before they are applied.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GrantOffersConfig {
+    /// Where to keep the pending offers, across restarts.
+    #[serde(default = "default_grant_offers_state_file")]
+    pub state_file: PathBuf,
+    /// Pending offers are dropped after this many days.
+    #[serde(default = "default_grant_offer_expiry_days")]
+    pub expiry_days: u64,
+}
+
+pub const DEFAULT_PRIVILEGE_SNAPSHOTS_STATE_FILE: &str = "/var/lib/muscl/privilege_snapshots.json";
+fn default_privilege_snapshots_state_file() -> PathBuf {
+    PathBuf::from(DEFAULT_PRIVILEGE_SNAPSHOTS_STATE_FILE)
+}
+
+pub const DEFAULT_MAX_PRIVILEGE_SNAPSHOTS: usize = 10;
+fn default_max_privilege_snapshots() -> usize {
+    DEFAULT_MAX_PRIVILEGE_SNAPSHOTS
+}
+
+/// Configuration for named snapshots of privileges, which can be rolled back to later.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PrivilegeSnapshotsConfig {
+    /// Where to keep the snapshots, across restarts.
+    #[serde(default = "default_privilege_snapshots_state_file")]
+    pub state_file: PathBuf,
+    /// How many snapshots every unix user can keep, before the oldest ones are dropped.
+    #[serde(default = "default_max_privilege_snapshots")]
+    pub max_snapshots: usize,
+}
+
+pub const DEFAULT_SCHEDULED_PRIVILEGE_CHANGES_STATE_FILE: &str =
+    "/var/lib/muscl/scheduled_privilege_changes.json";
+fn default_scheduled_privilege_changes_state_file() -> PathBuf {
+    PathBuf::from(DEFAULT_SCHEDULED_PRIVILEGE_CHANGES_STATE_FILE)
+}
+
+/// Configuration for privilege changes that are applied at a later time,
+/// by the `scheduled_privilege_changes` job.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScheduledPrivilegeChangesConfig {
+    /// Where to keep the pending changes, across restarts.
+    #[serde(default = "default_scheduled_privilege_changes_state_file")]
+    pub state_file: PathBuf,
+    /// A command (program followed by its arguments) which is run after every scheduled
+    /// change was applied, with the unix user that scheduled it, the id of the change and
+    /// either `success` or `failure` appended as arguments.
+    pub notify_command: Option<Vec<String>>,
+}
+
+pub const DEFAULT_POLICY_FUEL: u64 = 10_000_000;
+fn default_policy_fuel() -> u64 {
+    DEFAULT_POLICY_FUEL
+}
+
+/// Configuration for the policies that requests are checked against, see [`crate::server::policy`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PolicyConfig {
+    /// The WASM modules to check every request that changes something with, in order.
+    #[serde(default)]
+    pub modules: Vec<PathBuf>,
+    /// How much work a module may do for a single request before it is stopped,
+    /// which counts as the module denying the request.
+    #[serde(default = "default_policy_fuel")]
+    pub fuel: u64,
+    /// An Open Policy Agent server to ask after the modules.
+    pub opa: Option<OpaConfig>,
+}
+
+pub const DEFAULT_OPA_TIMEOUT: u64 = 2;
+fn default_opa_timeout() -> u64 {
+    DEFAULT_OPA_TIMEOUT
+}
+
+pub const DEFAULT_OPA_CACHE_TTL: u64 = 5;
+fn default_opa_cache_ttl() -> u64 {
+    DEFAULT_OPA_CACHE_TTL
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OpaConfig {
+    /// The URL of the decision to ask for, e.g. `http://localhost:8181/v1/data/muscl/decision`.
+    pub url: String,
+    /// How many seconds to wait for an answer before denying the request.
+    #[serde(default = "default_opa_timeout")]
+    pub timeout: u64,
+    /// How many seconds to reuse an answer for the same user and request.
+    #[serde(default = "default_opa_cache_ttl")]
+    pub cache_ttl: u64,
+}
+
+impl OpaConfig {
+    /// The TCP port the server connects to for the decisions.
+    pub fn port(&self) -> anyhow::Result<u16> {
+        let (scheme, rest) = self
+            .url
+            .split_once("://")
+            .ok_or_else(|| anyhow::anyhow!("Invalid OPA URL {:?}", self.url))?;
+        let authority = rest.split(['/', '?']).next().unwrap_or_default();
+        let host_and_port = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+
+        match host_and_port.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') && !host.is_empty() => port
+                .parse()
+                .context(format!("Invalid port in OPA URL {:?}", self.url)),
+            _ => match scheme {
+                "http" => Ok(80),
+                "https" => Ok(443),
+                _ => anyhow::bail!("Unsupported scheme in OPA URL {:?}", self.url),
+            },
+        }
+    }
+}
+
+/// Overrides for a periodic maintenance job, see [`crate::server::scheduler`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct JobConfig {
+    /// Whether to run the job, overriding the default of the job.
+    pub enabled: Option<bool>,
+    /// A cron expression in UTC, replacing the default schedule of the job.
+    pub schedule: Option<CronSchedule>,
+    /// The maximum number of seconds to randomly delay each run by.
+    pub jitter: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct AuthorizationConfig {
     pub group_denylist_file: Option<PathBuf>,
+    /// Members of these unix groups can use the `muscl admin` commands.
+    #[serde(default)]
+    pub admin_groups: Vec<String>,
+}
+
+/// Defaults applied to newly created objects, keyed by name prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct DefaultsConfig {
+    /// The privileges new privilege rows start out with for databases under a prefix,
+    /// written in the same format as the `edit-privs` privilege characters, e.g. `"siud"`.
+    #[serde(default)]
+    pub privileges: BTreeMap<String, String>,
+}
+
+impl DefaultsConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        for (prefix, template) in &self.privileges {
+            DatabasePrivilegeEdit::parse_template_from_str(template)
+                .context(format!("Invalid default privileges for prefix {prefix:?}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ServerConfig {
     pub socket_path: Option<PathBuf>,
+    /// Path to the admin control socket used by `muscl-server status` and friends,
+    /// which only root and members of the admin groups may use. Disabled if unset.
+    pub admin_socket_path: Option<PathBuf>,
+    /// A directory containing additional `*.toml` config fragments,
+    /// which are merged on top of the main config file in lexical order.
+    pub include_dir: Option<PathBuf>,
+    /// Seconds to wait for each step of the session handshake with a client.
+    /// Must be at least 1.
+    #[serde(default = "default_handshake_timeout")]
+    pub handshake_timeout: u64,
+    /// Seconds to wait for active sessions to finish when draining the server,
+    /// before exiting anyway.
+    #[serde(default = "default_drain_timeout")]
+    pub drain_timeout: u64,
+    /// Handle every connection in a separate worker process, see [`crate::server::worker`].
+    #[serde(default)]
+    pub process_isolation: bool,
     pub authorization: AuthorizationConfig,
     pub mysql: MysqlConfig,
+    pub inactive_user_locking: Option<InactiveUserLockingConfig>,
+    pub grant_offers: Option<GrantOffersConfig>,
+    pub privilege_snapshots: Option<PrivilegeSnapshotsConfig>,
+    pub scheduled_privilege_changes: Option<ScheduledPrivilegeChangesConfig>,
+    pub policy: Option<PolicyConfig>,
+    /// Text appended to the error messages shown to users, e.g. who to contact for help.
+    pub error_footer: Option<String>,
+    /// An announcement shown to users at the start of every session, e.g. for planned maintenance.
+    pub motd: Option<String>,
+    /// Per-job overrides for the periodic maintenance jobs, keyed by job name.
+    #[serde(default)]
+    pub jobs: BTreeMap<String, JobConfig>,
+    /// Per-prefix defaults for newly created objects.
+    #[serde(default)]
+    pub defaults: DefaultsConfig,
+    /// Extra names for sets of `edit-privs` privilege characters, e.g. `rw = "siud"`.
+    #[serde(default)]
+    pub privilege_aliases: BTreeMap<String, String>,
+    /// Let users grant the CREATE VIEW, SHOW VIEW, TRIGGER, EVENT, EXECUTE, CREATE ROUTINE,
+    /// ALTER ROUTINE and GRANT OPTION privileges on their databases, in addition to the short list.
+    #[serde(default)]
+    pub extended_database_privileges: bool,
 }
 
 impl ServerConfig {
     /// Reads the server configuration from the specified path, or the default path if none is provided.
+    ///
+    /// If the config file specifies an `include_dir`, all `*.toml` files in that directory
+    /// are merged on top of the main config in lexical order before the result is parsed.
     pub fn read_config_from_path(config_path: &Path) -> anyhow::Result<Self> {
         tracing::debug!("Reading config file at {:?}", config_path);
 
-        fs::read_to_string(config_path)
-            .context(format!("Failed to read config file at {config_path:?}"))
-            .and_then(|c| toml::from_str(&c).context("Failed to parse config file"))
-            .context(format!("Failed to parse config file at {config_path:?}"))
+        let mut config_table = read_toml_table_from_path(config_path)
+            .context(format!("Failed to parse config file at {config_path:?}"))?;
+
+        if let Some(include_dir) = config_table
+            .get("include_dir")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+        {
+            for fragment_path in list_config_fragments(&include_dir)? {
+                tracing::debug!("Merging config fragment at {:?}", fragment_path);
+                let fragment = read_toml_table_from_path(&fragment_path).context(format!(
+                    "Failed to parse config fragment at {fragment_path:?}"
+                ))?;
+                merge_toml_tables(&mut config_table, fragment);
+            }
+        }
+
+        let config: Self = config_table
+            .try_into()
+            .context(format!("Failed to parse config file at {config_path:?}"))?;
+
+        config
+            .defaults
+            .validate()
+            .context(format!("Failed to parse config file at {config_path:?}"))?;
+
+        config
+            .mysql
+            .validate()
+            .context(format!("Failed to parse config file at {config_path:?}"))?;
+
+        if config.handshake_timeout == 0 {
+            return Err(anyhow::anyhow!("handshake_timeout must be at least 1"))
+                .context(format!("Failed to parse config file at {config_path:?}"));
+        }
+
+        if let Some(inactive_user_locking) = &config.inactive_user_locking {
+            inactive_user_locking
+                .validate()
+                .context(format!("Failed to parse config file at {config_path:?}"))?;
+        }
+
+        for (alias, privileges) in &config.privilege_aliases {
+            validate_privilege_alias(alias, privileges)
+                .context(format!("Invalid privilege alias {alias:?}"))
+                .context(format!("Failed to parse config file at {config_path:?}"))?;
+        }
+
+        Ok(config)
+    }
+}
+
+fn read_toml_table_from_path(path: &Path) -> anyhow::Result<toml::Table> {
+    let mut table: toml::Table = fs::read_to_string(path)
+        .context(format!("Failed to read config file at {path:?}"))
+        .and_then(|c| toml::from_str(&c).context("Failed to parse config file"))?;
+
+    expand_env_vars_in_table(&mut table)?;
+
+    Ok(table)
+}
+
+/// Looks up environment variables referenced from the config.
+///
+/// When running as SUID/SGID, the environment is controlled by the unprivileged
+/// caller, so it is not allowed to influence the config at all.
+fn config_env_lookup(name: &str) -> Option<String> {
+    if executing_in_suid_sgid_mode().unwrap_or(true) {
+        None
+    } else {
+        std::env::var(name).ok()
+    }
+}
+
+/// Expands `${ENV_VAR}` references in all string values of the table, recursively.
+fn expand_env_vars_in_table(table: &mut toml::Table) -> anyhow::Result<()> {
+    for (key, value) in table.iter_mut() {
+        expand_env_vars_in_value(value).context(format!("Failed to expand value of {key:?}"))?;
+    }
+    Ok(())
+}
+
+fn expand_env_vars_in_value(value: &mut toml::Value) -> anyhow::Result<()> {
+    match value {
+        toml::Value::String(s) => *s = expand_env_vars(s, config_env_lookup)?,
+        toml::Value::Array(array) => {
+            for item in array {
+                expand_env_vars_in_value(item)?;
+            }
+        }
+        toml::Value::Table(table) => expand_env_vars_in_table(table)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replaces every `${NAME}` in `input` with the value returned by `lookup`.
+///
+/// A literal `${` can be written as `$${`. Referencing an unset variable is an error,
+/// to avoid silently connecting with e.g. an empty password.
+fn expand_env_vars(input: &str, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<String> {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .context("Unterminated environment variable reference")?;
+            let name = &reference[..end];
+            if name.is_empty() {
+                anyhow::bail!("Empty environment variable reference");
+            }
+            let value = lookup(name)
+                .with_context(|| format!("Environment variable {name:?} is not set"))?;
+            result.push_str(&value);
+            rest = &reference[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+/// Lists all `*.toml` files in the given directory, sorted lexically by file name.
+///
+/// A missing directory is treated as empty.
+fn list_config_fragments(include_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(include_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::debug!(
+                "Config include directory {:?} does not exist, skipping",
+                include_dir
+            );
+            return Ok(Vec::new());
+        }
+        Err(e) => {
+            return Err(e).context(format!(
+                "Failed to read config include directory at {include_dir:?}"
+            ));
+        }
+    };
+
+    let mut fragments = entries
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .context(format!(
+            "Failed to read config include directory at {include_dir:?}"
+        ))?
+        .into_iter()
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<_>>();
+
+    fragments.sort();
+
+    Ok(fragments)
+}
+
+/// Recursively merges `overlay` into `base`.
+///
+/// Tables are merged key by key, while any other value in `overlay` replaces the value in `base`.
+fn merge_toml_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_toml_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_toml_tables() {
+        let mut base: toml::Table = toml::from_str(indoc::indoc! {r#"
+            socket_path = "/run/muscl/muscl.sock"
+
+            [authorization]
+            group_denylist_file = "/etc/muscl/group_denylist.txt"
+
+            [mysql]
+            host = "localhost"
+            port = 3306
+        "#})
+        .unwrap();
+
+        let overlay: toml::Table = toml::from_str(indoc::indoc! {r#"
+            [mysql]
+            port = 3307
+            username = "muscl"
+        "#})
+        .unwrap();
+
+        merge_toml_tables(&mut base, overlay);
+
+        let expected: toml::Table = toml::from_str(indoc::indoc! {r#"
+            socket_path = "/run/muscl/muscl.sock"
+
+            [authorization]
+            group_denylist_file = "/etc/muscl/group_denylist.txt"
+
+            [mysql]
+            host = "localhost"
+            port = 3307
+            username = "muscl"
+        "#})
+        .unwrap();
+
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let lookup = |name: &str| match name {
+            "MUSCL_PASSWORD" => Some("secret".to_owned()),
+            "RUNTIME_DIR" => Some("/run/muscl".to_owned()),
+            _ => None,
+        };
+
+        assert_eq!(expand_env_vars("plain", lookup).unwrap(), "plain");
+        assert_eq!(
+            expand_env_vars("${MUSCL_PASSWORD}", lookup).unwrap(),
+            "secret"
+        );
+        assert_eq!(
+            expand_env_vars("${RUNTIME_DIR}/muscl.sock", lookup).unwrap(),
+            "/run/muscl/muscl.sock"
+        );
+        assert_eq!(expand_env_vars("pa$$word", lookup).unwrap(), "pa$$word");
+        assert_eq!(
+            expand_env_vars("$${NOT_EXPANDED}", lookup).unwrap(),
+            "${NOT_EXPANDED}"
+        );
+        assert!(expand_env_vars("${UNSET}", lookup).is_err());
+        assert!(expand_env_vars("${MUSCL_PASSWORD", lookup).is_err());
+        assert!(expand_env_vars("${}", lookup).is_err());
+    }
+
+    #[test]
+    fn test_opa_config_port() {
+        let opa = |url: &str| OpaConfig {
+            url: url.to_owned(),
+            timeout: DEFAULT_OPA_TIMEOUT,
+            cache_ttl: DEFAULT_OPA_CACHE_TTL,
+        };
+
+        assert_eq!(
+            opa("http://localhost:8181/v1/data/muscl/decision")
+                .port()
+                .unwrap(),
+            8181
+        );
+        assert_eq!(opa("http://opa.example.com/v1/data").port().unwrap(), 80);
+        assert_eq!(opa("https://opa.example.com").port().unwrap(), 443);
+        assert_eq!(opa("http://[::1]:8181/v1/data").port().unwrap(), 8181);
+        assert_eq!(opa("https://[::1]/v1/data").port().unwrap(), 443);
+        assert!(opa("localhost:8181").port().is_err());
+        assert!(opa("ftp://opa.example.com").port().is_err());
+    }
+
+    #[test]
+    fn test_inactive_user_locking_config_validate() {
+        let locking = |inactive_days: u64| InactiveUserLockingConfig {
+            inactive_days,
+            state_file: default_inactive_user_state_file(),
+            dry_run: false,
+            notify_command: None,
+        };
+
+        assert!(locking(90).validate().is_ok());
+        assert!(locking(1).validate().is_ok());
+        assert!(locking(0).validate().is_err());
+    }
+
+    #[test]
+    fn test_mysql_config_validate() {
+        let mysql = |max_connections: u32, min_connections: u32| {
+            toml::from_str::<MysqlConfig>(&format!(
+                "max_connections = {max_connections}\nmin_connections = {min_connections}"
+            ))
+            .unwrap()
+        };
+
+        assert!(mysql(10, 0).validate().is_ok());
+        assert!(mysql(10, 10).validate().is_ok());
+        assert!(mysql(0, 0).validate().is_err());
+        assert!(mysql(5, 6).validate().is_err());
     }
 }
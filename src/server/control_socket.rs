@@ -0,0 +1,222 @@
+//! The admin control socket, served by the [`Supervisor`](crate::server::supervisor::Supervisor).
+//!
+//! See [`crate::core::protocol::control`] for the protocol.
+
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    net::{UnixListener as TokioUnixListener, UnixStream},
+    sync::{Mutex, broadcast},
+    task::JoinHandle,
+};
+use tokio_util::task::TaskTracker;
+
+use crate::{
+    core::{
+        common::{UnixUser, abstract_socket_name, bind_unix_listener, connect_unix_socket},
+        protocol::control::{
+            ControlRequest, ControlResponse, ServerStatus, create_control_client_stream,
+            create_control_server_stream,
+        },
+    },
+    server::{
+        config::ServerConfig,
+        log_level::{current_log_level, handle_log_level_request},
+        supervisor::{DrainEvent, HandoverEvent, ReloadEvent},
+    },
+};
+
+/// How long a client of the control socket gets to send its request.
+const CONTROL_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The parts of the supervisor that the control socket can look at and act on.
+#[derive(Clone)]
+pub struct ControlContext {
+    pub config: Arc<Mutex<ServerConfig>>,
+    pub handler_task_tracker: TaskTracker,
+    pub maintenance_mode: Arc<AtomicBool>,
+    pub reload_sender: broadcast::Sender<ReloadEvent>,
+    pub drain_sender: broadcast::Sender<DrainEvent>,
+    pub handover_sender: broadcast::Sender<HandoverEvent>,
+    pub started_at: Instant,
+}
+
+/// Bind the control socket, which anyone may connect to, as access is checked
+/// with the peer credentials of every connection.
+pub fn create_control_listener(socket_path: &Path) -> anyhow::Result<TokioUnixListener> {
+    if abstract_socket_name(socket_path).is_none() {
+        if let Some(parent_directory) = socket_path.parent()
+            && !parent_directory.exists()
+        {
+            fs::create_dir_all(parent_directory)?;
+        }
+
+        match fs::remove_file(socket_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let listener = bind_unix_listener(socket_path)
+        .context(format!("Failed to bind admin socket at {socket_path:?}"))?;
+
+    if abstract_socket_name(socket_path).is_none() {
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o666)).context(format!(
+            "Failed to set permissions of admin socket at {socket_path:?}"
+        ))?;
+    }
+
+    listener.set_nonblocking(true)?;
+    tracing::info!("Listening for admin commands on socket {:?}", socket_path);
+
+    Ok(TokioUnixListener::from_std(listener)?)
+}
+
+pub fn spawn_control_socket_task(
+    listener: TokioUnixListener,
+    context: ControlContext,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _addr)) => {
+                    let context = context.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_control_connection(socket, &context).await {
+                            tracing::warn!("Failed to handle admin command: {:#}", err);
+                        }
+                    });
+                }
+                Err(err) => {
+                    tracing::error!("Failed to accept connection on admin socket: {}", err);
+                }
+            }
+        }
+    })
+}
+
+/// Send a single request to the control socket of a running server, and wait for the response.
+pub async fn send_control_request(
+    socket_path: &Path,
+    request: ControlRequest,
+) -> anyhow::Result<ControlResponse> {
+    let socket = connect_unix_socket(socket_path).context(format!(
+        "Failed to connect to admin socket at {socket_path:?}"
+    ))?;
+    socket.set_nonblocking(true)?;
+
+    let mut stream = create_control_client_stream(UnixStream::from_std(socket)?);
+    stream.send(request).await?;
+
+    match stream.next().await {
+        Some(response) => Ok(response?),
+        None => anyhow::bail!("The server closed the admin socket without responding"),
+    }
+}
+
+/// Whether the peer may use the control socket: root, the user the server runs as,
+/// and members of the admin groups.
+async fn is_authorized(socket: &UnixStream, context: &ControlContext) -> anyhow::Result<bool> {
+    let uid = socket
+        .peer_cred()
+        .context("Failed to get peer credentials from admin socket")?
+        .uid();
+
+    if uid == 0 || uid == nix::unistd::getuid().as_raw() {
+        return Ok(true);
+    }
+
+    // NOTE: looking up the groups goes through NSS, which might block for a while, e.g. with LDAP.
+    let unix_user = tokio::task::spawn_blocking(move || UnixUser::from_uid(uid)).await??;
+    let config = context.config.lock().await;
+    Ok(unix_user
+        .groups
+        .iter()
+        .any(|group| config.authorization.admin_groups.contains(group)))
+}
+
+async fn handle_control_connection(
+    socket: UnixStream,
+    context: &ControlContext,
+) -> anyhow::Result<()> {
+    let authorized = is_authorized(&socket, context).await?;
+    let mut stream = create_control_server_stream(socket);
+
+    // NOTE: the request of an unauthorized peer is never read, so that
+    //       anyone who can connect cannot make the server parse anything.
+    if !authorized {
+        tracing::warn!("Refused connection to the admin socket from unauthorized user");
+        stream.send(ControlResponse::NotAuthorized).await?;
+        return Ok(());
+    }
+
+    let Ok(request) = tokio::time::timeout(CONTROL_REQUEST_TIMEOUT, stream.next()).await else {
+        anyhow::bail!(
+            "Client did not send a request within {}s",
+            CONTROL_REQUEST_TIMEOUT.as_secs()
+        );
+    };
+    let Some(request) = request else {
+        return Ok(());
+    };
+    let request = request?;
+
+    tracing::info!("Received admin command: {:?}", request);
+    let response = handle_control_request(request, context);
+
+    stream.send(response).await?;
+    Ok(())
+}
+
+fn handle_control_request(request: ControlRequest, context: &ControlContext) -> ControlResponse {
+    match request {
+        ControlRequest::Status => ControlResponse::Status(ServerStatus {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_seconds: context.started_at.elapsed().as_secs(),
+            active_sessions: context.handler_task_tracker.len(),
+            accepting_connections: !context.handler_task_tracker.is_closed(),
+            maintenance_mode: context.maintenance_mode.load(Ordering::Relaxed),
+            log_level: current_log_level().map(|level| level.to_string()),
+        }),
+        ControlRequest::Reload => match context.reload_sender.send(ReloadEvent) {
+            Ok(_) => ControlResponse::ReloadStarted,
+            Err(err) => ControlResponse::Error(format!("Failed to start reload: {err}")),
+        },
+        ControlRequest::SetMaintenanceMode(enabled) => {
+            let previous = context.maintenance_mode.swap(enabled, Ordering::Relaxed);
+            tracing::info!(
+                "Maintenance mode turned {}",
+                if enabled { "on" } else { "off" }
+            );
+            ControlResponse::SetMaintenanceMode(previous)
+        }
+        ControlRequest::LogLevel(level) => {
+            ControlResponse::LogLevel(handle_log_level_request(level.as_deref()))
+        }
+        ControlRequest::Drain(timeout_seconds) => {
+            let drain_event = DrainEvent {
+                timeout: timeout_seconds.map(Duration::from_secs),
+            };
+            match context.drain_sender.send(drain_event) {
+                Ok(_) => ControlResponse::DrainStarted(context.handler_task_tracker.len()),
+                Err(err) => ControlResponse::Error(format!("Failed to start drain: {err}")),
+            }
+        }
+        ControlRequest::Upgrade => match context.handover_sender.send(HandoverEvent) {
+            Ok(_) => ControlResponse::UpgradeStarted,
+            Err(err) => ControlResponse::Error(format!("Failed to start upgrade: {err}")),
+        },
+    }
+}
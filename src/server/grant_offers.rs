@@ -0,0 +1,367 @@
+//! Privileges offered by the owner of a database to a database user owned by someone else.
+//!
+//! Offers are kept in a small state file until the owner of the user accepts them,
+//! at which point the privileges are applied, or until they expire. Both owners have
+//! to agree, so nobody can hand out access to a database they do not own, and nobody
+//! gets privileges attached to their users without asking for it.
+
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlConnection;
+
+use crate::{
+    core::{
+        common::UnixUser,
+        database_privileges::{
+            DATABASE_PRIVILEGE_FIELDS, DatabasePrivilegeRowDiff, DatabasePrivilegesDiff,
+        },
+        protocol::{
+            AcceptGrantError, AcceptGrantRequest, AcceptGrantResponse, GrantOffer, GrantOfferId,
+            ListGrantOffersError, ListGrantOffersResponse, OfferGrantError, OfferGrantRequest,
+            OfferGrantResponse,
+            request_validation::{
+                GroupDenylist, ValidationError, validate_db_or_user_request, validate_name,
+            },
+        },
+        types::DbOrUser,
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        common::find_name_owner,
+        config::GrantOffersConfig,
+        sql::{
+            database_operations::unsafe_database_exists,
+            database_privilege_operations::{
+                unsafe_apply_privilege_diff, unsafe_get_database_privileges_for_db_user_pair,
+            },
+            user_operations::unsafe_user_exists,
+        },
+        state_file::{LockedStateFile, STORE_ERROR_MESSAGE},
+    },
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GrantOfferState {
+    pub next_id: GrantOfferId,
+    pub offers: BTreeMap<GrantOfferId, GrantOffer>,
+}
+
+impl GrantOfferState {
+    fn remove_expired(&mut self, now: u64) {
+        self.offers.retain(|_, offer| offer.expires_at > now);
+    }
+
+    /// Adds a new offer, replacing any pending offer for the same database and user.
+    fn insert(&mut self, mut offer: GrantOffer) -> GrantOffer {
+        self.offers.retain(|_, other| {
+            (&other.row.db, &other.row.user) != (&offer.row.db, &offer.row.user)
+        });
+        self.next_id += 1;
+        offer.id = self.next_id;
+        self.offers.insert(offer.id, offer.clone());
+        offer
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Whether the offer concerns a database user that the unix user can manage.
+fn is_offered_to(offer: &GrantOffer, unix_user: &UnixUser, group_denylist: &GroupDenylist) -> bool {
+    validate_db_or_user_request(
+        &DbOrUser::User(offer.row.user.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .is_ok()
+}
+
+/// Store an offer to give a database user owned by someone else privileges on a
+/// database owned by the unix user.
+pub async fn offer_grant(
+    row: OfferGrantRequest,
+    config: Option<&GrantOffersConfig>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> OfferGrantResponse {
+    let config = config.ok_or(OfferGrantError::Disabled)?;
+
+    validate_db_or_user_request(
+        &DbOrUser::Database(row.db.clone()),
+        unix_user,
+        group_denylist,
+    )?;
+
+    validate_name(&row.user).map_err(|err| {
+        OfferGrantError::InvalidUserName(ValidationError::NameValidationError(err))
+    })?;
+
+    if validate_db_or_user_request(&DbOrUser::User(row.user.clone()), unix_user, group_denylist)
+        .is_ok()
+    {
+        return Err(OfferGrantError::UserIsOwnedByYou);
+    }
+
+    if find_name_owner(&row.user, group_denylist).is_none() {
+        return Err(OfferGrantError::UserHasNoOwner);
+    }
+
+    if !unsafe_database_exists(&row.db, &mut *connection)
+        .await
+        .map_err(|err| OfferGrantError::MySqlError(err.to_string()))?
+    {
+        return Err(OfferGrantError::DatabaseDoesNotExist);
+    }
+
+    if !unsafe_user_exists(&row.user, &mut *connection)
+        .await
+        .map_err(|err| OfferGrantError::MySqlError(err.to_string()))?
+    {
+        return Err(OfferGrantError::UserDoesNotExist);
+    }
+
+    let (state_file, mut state): (_, GrantOfferState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read grant offers: {:#}", err);
+                OfferGrantError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+    let now = now();
+    state.remove_expired(now);
+
+    let offer = state.insert(GrantOffer {
+        id: 0,
+        offered_by: unix_user.username.clone(),
+        row,
+        expires_at: now + config.expiry_days * SECONDS_PER_DAY,
+    });
+
+    state_file.write(&state).map_err(|err| {
+        tracing::error!("Failed to store grant offer: {:#}", err);
+        OfferGrantError::StoreError(STORE_ERROR_MESSAGE.to_string())
+    })?;
+
+    tracing::info!(
+        "User '{}' offered privileges on '{}' to '{}' (offer {})",
+        unix_user,
+        offer.row.db,
+        offer.row.user,
+        offer.id
+    );
+
+    Ok(offer)
+}
+
+/// List the pending offers made by the unix user, or made to database users it owns.
+pub async fn list_grant_offers(
+    config: Option<&GrantOffersConfig>,
+    unix_user: &UnixUser,
+    group_denylist: &GroupDenylist,
+) -> ListGrantOffersResponse {
+    let config = config.ok_or(ListGrantOffersError::Disabled)?;
+
+    let (_state_file, mut state): (_, GrantOfferState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read grant offers: {:#}", err);
+                ListGrantOffersError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+    state.remove_expired(now());
+
+    Ok(state
+        .offers
+        .into_values()
+        .filter(|offer| {
+            offer.offered_by == unix_user.username
+                || is_offered_to(offer, unix_user, group_denylist)
+        })
+        .collect())
+}
+
+/// Accept an offer made to a database user owned by the unix user, and apply its privileges.
+///
+/// The one who made the offer needs to still own the database for this to succeed.
+pub async fn accept_grant(
+    id: AcceptGrantRequest,
+    config: Option<&GrantOffersConfig>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> AcceptGrantResponse {
+    let config = config.ok_or(AcceptGrantError::Disabled)?;
+
+    let (state_file, mut state): (_, GrantOfferState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read grant offers: {:#}", err);
+                AcceptGrantError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+    state.remove_expired(now());
+
+    let offer = state
+        .offers
+        .get(&id)
+        .cloned()
+        .ok_or(AcceptGrantError::OfferDoesNotExist)?;
+
+    if let Err(err) = validate_db_or_user_request(
+        &DbOrUser::User(offer.row.user.clone()),
+        unix_user,
+        group_denylist,
+    ) {
+        // NOTE: only the one who made the offer gets to know that it exists.
+        return Err(if offer.offered_by == unix_user.username {
+            AcceptGrantError::ValidationError(offer.row.user.clone(), err)
+        } else {
+            AcceptGrantError::OfferDoesNotExist
+        });
+    }
+
+    // NOTE: looking up the user goes through NSS, which might block for a while, e.g. with LDAP.
+    let offered_by_still_owns_database = {
+        let offered_by = offer.offered_by.clone();
+        let db = offer.row.db.clone();
+        let group_denylist = group_denylist.clone();
+        tokio::task::spawn_blocking(move || {
+            UnixUser::from_username(&offered_by).is_ok_and(|offered_by| {
+                validate_db_or_user_request(&DbOrUser::Database(db), &offered_by, &group_denylist)
+                    .is_ok()
+            })
+        })
+        .await
+        .unwrap_or(false)
+    };
+    if !offered_by_still_owns_database {
+        state.offers.remove(&id);
+        state_file.write(&state).ok();
+        return Err(AcceptGrantError::OfferIsNoLongerValid);
+    }
+
+    if !unsafe_database_exists(&offer.row.db, &mut *connection)
+        .await
+        .map_err(|err| AcceptGrantError::MySqlError(err.to_string()))?
+    {
+        return Err(AcceptGrantError::DatabaseDoesNotExist);
+    }
+
+    if !unsafe_user_exists(&offer.row.user, &mut *connection)
+        .await
+        .map_err(|err| AcceptGrantError::MySqlError(err.to_string()))?
+    {
+        return Err(AcceptGrantError::UserDoesNotExist);
+    }
+
+    let existing_row = unsafe_get_database_privileges_for_db_user_pair(
+        &offer.row.db,
+        &offer.row.user,
+        &mut *connection,
+    )
+    .await
+    .map_err(|err| AcceptGrantError::MySqlError(err.to_string()))?;
+
+    // NOTE: accepting an offer only ever adds privileges, anything the user
+    //       already had on the database is kept.
+    let diff = match existing_row {
+        Some(existing_row) => {
+            let mut merged_row = offer.row.clone();
+            for field in DATABASE_PRIVILEGE_FIELDS.into_iter().skip(2) {
+                *merged_row.get_privilege_mut_by_name(field).unwrap() |=
+                    existing_row.get_privilege_by_name(field).unwrap();
+            }
+            DatabasePrivilegesDiff::Modified(DatabasePrivilegeRowDiff::from_rows(
+                &existing_row,
+                &merged_row,
+            ))
+        }
+        None => DatabasePrivilegesDiff::New(offer.row.clone()),
+    };
+
+    unsafe_apply_privilege_diff(&diff, &mut *connection)
+        .await
+        .map_err(|err| AcceptGrantError::MySqlError(err.to_string()))?;
+
+    state.offers.remove(&id);
+    if let Err(err) = state_file.write(&state) {
+        tracing::error!("Failed to remove accepted grant offer {}: {:#}", id, err);
+    }
+
+    tracing::info!(
+        "User '{}' accepted privileges on '{}' for '{}' offered by '{}' (offer {})",
+        unix_user,
+        offer.row.db,
+        offer.row.user,
+        offer.offered_by,
+        offer.id
+    );
+
+    Ok(offer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database_privileges::DatabasePrivilegeRow;
+
+    fn offer(db: &str, user: &str, expires_at: u64) -> GrantOffer {
+        GrantOffer {
+            id: 0,
+            offered_by: "owner".to_string(),
+            row: DatabasePrivilegeRow {
+                db: db.into(),
+                user: user.into(),
+                select_priv: true,
+                insert_priv: false,
+                update_priv: false,
+                delete_priv: false,
+                create_priv: false,
+                drop_priv: false,
+                alter_priv: false,
+                index_priv: false,
+                create_tmp_table_priv: false,
+                lock_tables_priv: false,
+                references_priv: false,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
+            },
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_grant_offer_state() {
+        let mut state = GrantOfferState::default();
+
+        let first = state.insert(offer("owner_db", "other_user", 100));
+        let second = state.insert(offer("owner_db", "another_user", 200));
+        assert_eq!((first.id, second.id), (1, 2));
+
+        // A new offer for the same pair replaces the old one, with a new id.
+        let third = state.insert(offer("owner_db", "other_user", 300));
+        assert_eq!(third.id, 3);
+        assert_eq!(state.offers.keys().copied().collect::<Vec<_>>(), vec![2, 3]);
+
+        state.remove_expired(200);
+        assert_eq!(state.offers.keys().copied().collect::<Vec<_>>(), vec![3]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Handing the server over to a new server process, for upgrades without downtime.
+//!
+//! On `SIGUSR2` or `muscl-server upgrade`, the supervisor starts the server binary again,
+//! letting it inherit the listening sockets. The state that should survive the upgrade
+//! is passed along in the [`HANDOVER_ENV_VAR`] environment variable.
+//!
+//! The new process starts accepting connections right away, while the old process stops
+//! accepting and keeps serving its active sessions until they are done, as in a drain.
+//! Connections that are waiting in the backlog of the socket are accepted by the new process,
+//! so no client sees the socket go away.
+
+use std::{
+    os::fd::{BorrowedFd, RawFd},
+    path::PathBuf,
+    process::Command,
+};
+
+use anyhow::Context;
+use nix::fcntl::{FcntlArg, FdFlag, fcntl};
+use serde::{Deserialize, Serialize};
+
+pub const HANDOVER_ENV_VAR: &str = "MUSCL_HANDOVER";
+
+/// The state passed from the old server process to the new one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandoverState {
+    /// The listening socket for regular users, which may come from systemd socket activation.
+    pub listener_fd: RawFd,
+    /// The listening admin control socket, if it is enabled.
+    pub control_listener_fd: Option<RawFd>,
+    pub maintenance_mode: bool,
+    pub log_level: Option<String>,
+}
+
+/// Read the state handed over by the previous server process, if this process was started by one.
+pub fn read_handover_state() -> anyhow::Result<Option<HandoverState>> {
+    let Ok(state) = std::env::var(HANDOVER_ENV_VAR) else {
+        return Ok(None);
+    };
+
+    let state: HandoverState = serde_json::from_str(&state).context(format!(
+        "Failed to parse {HANDOVER_ENV_VAR} environment variable"
+    ))?;
+
+    tracing::info!(
+        "Taking over from the previous server process, with listener fd {}",
+        state.listener_fd
+    );
+
+    Ok(Some(state))
+}
+
+/// The path of the server binary, for starting new server processes.
+pub fn server_executable() -> anyhow::Result<PathBuf> {
+    // NOTE: after a package upgrade, `/proc/self/exe` refers to the deleted old binary,
+    //       so the path we were started with is tried first, to pick up the new one.
+    match std::env::args_os().next().map(PathBuf::from) {
+        Some(path) if path.is_absolute() => Ok(path),
+        _ => std::env::current_exe().context("Failed to find the server executable"),
+    }
+}
+
+/// Start a new server process with the same arguments as this one,
+/// and hand the listening sockets over to it. Returns the pid of the new process.
+///
+/// Note that the new process inherits the Landlock restrictions of this one,
+/// which do not allow running the server binary.
+pub fn spawn_successor(state: &HandoverState) -> anyhow::Result<u32> {
+    for fd in std::iter::once(state.listener_fd).chain(state.control_listener_fd) {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+        fcntl(fd, FcntlArg::F_SETFD(FdFlag::empty()))
+            .context("Failed to let the new server process inherit the listening socket")?;
+    }
+
+    let executable = server_executable()?;
+    let child = Command::new(&executable)
+        .args(std::env::args_os().skip(1))
+        .env(HANDOVER_ENV_VAR, serde_json::to_string(state)?)
+        .spawn()
+        .context(format!(
+            "Failed to start new server process from {executable:?}"
+        ))?;
+
+    tracing::info!(
+        "Started new server process from {:?} with pid {}",
+        executable,
+        child.id()
+    );
+
+    Ok(child.id())
+}
@@ -0,0 +1,349 @@
+//! Automatic locking of database users that have not connected for a long time.
+//!
+//! Neither `MySQL` nor `MariaDB` records when a user last logged in, so the server
+//! samples `performance_schema.accounts` periodically and keeps track of when each
+//! user was last seen in a small state file. A user counts as seen if it has an
+//! open connection, or if its total connection count changed since the previous sample.
+//!
+//! Users that are not in the state file yet count as seen when they are first sampled,
+//! so enabling this never locks anyone right away.
+//!
+//! Only the `'user'@'%'` account is locked, which is the only account muscl creates.
+//! Accounts for the same user on other hosts, created by an administrator, are left alone.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::{MySqlConnection, MySqlPool, prelude::*};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    core::{protocol::request_validation::GroupDenylist, types::MySQLUser},
+    server::{
+        backend_capabilities::BackendCapabilities,
+        common::{find_name_owner, try_get_with_binary_fallback},
+        config::{InactiveUserLockingConfig, ServerConfig},
+        sql::{quote_literal, uncached_query, user_operations::database_user_is_locked_unsafe},
+        state_file::LockedStateFile,
+    },
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserActivity {
+    /// Unix timestamp of when the user was last seen.
+    pub last_seen: u64,
+    /// The total number of connections reported by `performance_schema` at the last sample.
+    pub total_connections: u64,
+    /// Unix timestamp of when the user was locked for being inactive, if it was.
+    #[serde(default)]
+    pub locked_at: Option<u64>,
+}
+
+pub type UserActivityState = BTreeMap<MySQLUser, UserActivity>;
+
+/// The current and total number of connections of a user, summed over all hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectionCounts {
+    pub current: u64,
+    pub total: u64,
+}
+
+/// Update the state with a new sample of connection counts.
+///
+/// Users that no longer exist are removed from the state.
+fn update_activity(
+    state: &mut UserActivityState,
+    users: &[MySQLUser],
+    connections: &BTreeMap<MySQLUser, ConnectionCounts>,
+    now: u64,
+) {
+    state.retain(|user, _| users.contains(user));
+
+    for user in users {
+        let counts = connections.get(user).copied().unwrap_or_default();
+        let activity = state.entry(user.clone()).or_insert(UserActivity {
+            last_seen: now,
+            total_connections: counts.total,
+            locked_at: None,
+        });
+
+        // NOTE: the total goes down when the database server restarts,
+        //       in which case any connection since the restart counts.
+        let seen =
+            counts.current > 0 || (counts.total != activity.total_connections && counts.total > 0);
+        if seen {
+            activity.last_seen = now;
+        }
+        activity.total_connections = counts.total;
+    }
+}
+
+/// Returns the users that have been inactive for at least `inactive_days`,
+/// together with the number of days they have been inactive.
+fn find_inactive_users(
+    state: &UserActivityState,
+    now: u64,
+    inactive_days: u64,
+) -> Vec<(MySQLUser, u64)> {
+    state
+        .iter()
+        .filter(|(_, activity)| activity.locked_at.is_none())
+        .map(|(user, activity)| {
+            (
+                user.clone(),
+                now.saturating_sub(activity.last_seen) / SECONDS_PER_DAY,
+            )
+        })
+        .filter(|(_, days)| *days >= inactive_days)
+        .collect()
+}
+
+async fn list_all_users_unsafe(
+    connection: &mut MySqlConnection,
+) -> Result<Vec<MySQLUser>, sqlx::Error> {
+    sqlx::query("SELECT DISTINCT `User` FROM `mysql`.`user`")
+        .fetch_all(connection)
+        .await?
+        .iter()
+        .map(|row| try_get_with_binary_fallback(row, "User").map(MySQLUser::from))
+        .collect()
+}
+
+async fn sample_connection_counts(
+    connection: &mut MySqlConnection,
+) -> Result<BTreeMap<MySQLUser, ConnectionCounts>, sqlx::Error> {
+    sqlx::query(
+        r"
+          SELECT
+            `USER`,
+            CAST(SUM(`CURRENT_CONNECTIONS`) AS UNSIGNED) AS `current_connections`,
+            CAST(SUM(`TOTAL_CONNECTIONS`) AS UNSIGNED) AS `total_connections`
+          FROM `performance_schema`.`accounts`
+          WHERE `USER` IS NOT NULL
+          GROUP BY `USER`
+        ",
+    )
+    .fetch_all(connection)
+    .await?
+    .iter()
+    .map(|row| {
+        Ok((
+            MySQLUser::from(try_get_with_binary_fallback(row, "USER")?),
+            ConnectionCounts {
+                current: row.try_get("current_connections")?,
+                total: row.try_get("total_connections")?,
+            },
+        ))
+    })
+    .collect()
+}
+
+fn notify_owner(command: &[String], owner: &str, db_user: &MySQLUser, days: u64) {
+    let Some((program, args)) = command.split_first() else {
+        return;
+    };
+
+    let result = std::process::Command::new(program)
+        .args(args)
+        .arg(owner)
+        .arg(db_user.as_str())
+        .arg(days.to_string())
+        .stdin(std::process::Stdio::null())
+        .status();
+
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::warn!(
+            "Notification command for inactive user '{}' exited with {}",
+            db_user,
+            status
+        ),
+        Err(err) => tracing::warn!(
+            "Failed to run notification command for inactive user '{}': {}",
+            db_user,
+            err
+        ),
+    }
+}
+
+/// Sample the activity of all managed users, and lock the ones that have been inactive for too long.
+pub async fn lock_inactive_users(
+    config: &InactiveUserLockingConfig,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> anyhow::Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    let all_users = list_all_users_unsafe(&mut *connection).await?;
+    let group_denylist = group_denylist.clone();
+    // NOTE: finding the owners looks up users and groups through NSS,
+    //       which might block for a while, e.g. with LDAP.
+    let owners: BTreeMap<MySQLUser, String> = tokio::task::spawn_blocking(move || {
+        all_users
+            .into_iter()
+            .filter_map(|user| {
+                let owner = find_name_owner(&user, &group_denylist)?;
+                Some((user, owner))
+            })
+            .collect()
+    })
+    .await?;
+    let users: Vec<MySQLUser> = owners.keys().cloned().collect();
+    let connections = sample_connection_counts(&mut *connection)
+        .await
+        .context("Failed to sample connections, is performance_schema enabled?")?;
+
+    let (state_file, mut state): (_, UserActivityState) =
+        LockedStateFile::lock_and_read(&config.state_file).await?;
+    update_activity(&mut state, &users, &connections, now);
+
+    // NOTE: if an administrator unlocked a user we locked, give it a fresh start.
+    for (user, activity) in &mut state {
+        if activity.locked_at.is_some()
+            && !database_user_is_locked_unsafe(user, &mut *connection, backend_capabilities).await?
+        {
+            tracing::info!(
+                "Inactive user '{}' was unlocked, resetting its activity",
+                user
+            );
+            activity.locked_at = None;
+            activity.last_seen = now;
+        }
+    }
+
+    for (user, days) in find_inactive_users(&state, now, config.inactive_days) {
+        if database_user_is_locked_unsafe(&user, &mut *connection, backend_capabilities).await? {
+            continue;
+        }
+
+        let owner = owners.get(&user).cloned().unwrap_or_default();
+
+        if config.dry_run {
+            tracing::info!(
+                "Would lock user '{}' owned by '{}', inactive for {} days (dry run)",
+                user,
+                owner,
+                days
+            );
+            continue;
+        }
+
+        let result = uncached_query(&format!(
+            "ALTER USER {}@'%' ACCOUNT LOCK",
+            quote_literal(&user)
+        ))
+        .execute(&mut *connection)
+        .await;
+
+        match result {
+            Ok(_) => {
+                tracing::warn!(
+                    "Locked user '{}' owned by '{}', inactive for {} days",
+                    user,
+                    owner,
+                    days
+                );
+                if let Some(activity) = state.get_mut(&user) {
+                    activity.locked_at = Some(now);
+                }
+                if let Some(command) = config.notify_command.clone() {
+                    let user = user.clone();
+                    tokio::task::spawn_blocking(move || {
+                        notify_owner(&command, &owner, &user, days)
+                    })
+                    .await
+                    .ok();
+                }
+            }
+            Err(err) => tracing::error!("Failed to lock inactive user '{}': {}", user, err),
+        }
+    }
+
+    state_file.write(&state)
+}
+
+/// The body of the `inactive_user_locking` job, which does nothing unless
+/// `inactive_user_locking` is configured.
+pub async fn run_inactive_user_locking_job(
+    config: Arc<Mutex<ServerConfig>>,
+    db_pool: Arc<RwLock<MySqlPool>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+    group_denylist: Arc<RwLock<GroupDenylist>>,
+) -> anyhow::Result<()> {
+    let Some(locking_config) = config.lock().await.inactive_user_locking.clone() else {
+        tracing::debug!("Inactive user locking is not configured, skipping");
+        return Ok(());
+    };
+
+    let mut connection = db_pool.read().await.acquire().await?;
+    lock_inactive_users(
+        &locking_config,
+        &mut connection,
+        &*backend_capabilities.read().await,
+        &*group_denylist.read().await,
+    )
+    .await
+    .context("Failed to check for inactive users")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_activity() {
+        let alice = MySQLUser::from("alice_user");
+        let bob = MySQLUser::from("bob_user");
+        let users = vec![alice.clone(), bob.clone()];
+
+        let mut state = UserActivityState::new();
+        state.insert(
+            "removed_user".into(),
+            UserActivity {
+                last_seen: 0,
+                total_connections: 0,
+                locked_at: None,
+            },
+        );
+
+        let connections = BTreeMap::from([(
+            alice.clone(),
+            ConnectionCounts {
+                current: 0,
+                total: 3,
+            },
+        )]);
+        update_activity(&mut state, &users, &connections, 100);
+
+        assert_eq!(state.len(), 2);
+        assert_eq!(state[&alice].last_seen, 100);
+        assert_eq!(state[&bob].last_seen, 100);
+
+        // Alice connected again, Bob did not
+        let connections = BTreeMap::from([(
+            alice.clone(),
+            ConnectionCounts {
+                current: 0,
+                total: 4,
+            },
+        )]);
+        let later = 100 + 40 * SECONDS_PER_DAY;
+        update_activity(&mut state, &users, &connections, later);
+
+        assert_eq!(state[&alice].last_seen, later);
+        assert_eq!(state[&bob].last_seen, 100);
+        assert_eq!(
+            find_inactive_users(&state, later, 30),
+            vec![(bob.clone(), 40)]
+        );
+        assert!(find_inactive_users(&state, later, 60).is_empty());
+    }
+}
@@ -1,8 +1,56 @@
 #[cfg(target_os = "linux")]
-use std::path::Path;
+use std::{path::Path, sync::OnceLock};
+
+/// How well the Landlock restrictions are enforced by the running kernel,
+/// kept around to be logged once logging is set up, see [`log_landlock_status`].
+#[cfg(target_os = "linux")]
+static LANDLOCK_STATUS: OnceLock<landlock::RulesetStatus> = OnceLock::new();
 
 #[cfg(target_os = "linux")]
 pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let status = create_server_ruleset(config_path)?
+        .restrict_self()
+        .context("Failed to apply Landlock restrictions to the server process")?;
+
+    let _ = LANDLOCK_STATUS.set(status.ruleset);
+
+    Ok(())
+}
+
+/// Log whether the Landlock restrictions applied by [`landlock_restrict_server`]
+/// are enforced, as older kernels only support some of them.
+#[cfg(target_os = "linux")]
+pub fn log_landlock_status() {
+    use landlock::RulesetStatus;
+
+    match LANDLOCK_STATUS.get() {
+        Some(RulesetStatus::FullyEnforced) => {
+            tracing::debug!("Landlock restrictions are fully enforced");
+        }
+        Some(RulesetStatus::PartiallyEnforced) => tracing::warn!(
+            "Landlock restrictions are only partially enforced by this kernel, \
+             network restrictions need Linux 6.7 or newer"
+        ),
+        Some(RulesetStatus::NotEnforced) => {
+            tracing::warn!(
+                "Landlock is not supported by this kernel, running without restrictions"
+            );
+        }
+        None => tracing::debug!("Landlock restrictions are disabled"),
+    }
+}
+
+/// Builds the server's Landlock ruleset without applying it, to verify that
+/// the sandbox can be set up with the current configuration.
+#[cfg(target_os = "linux")]
+pub fn landlock_check_server(config_path: Option<&Path>) -> anyhow::Result<()> {
+    create_server_ruleset(config_path).map(|_| ())
+}
+
+#[cfg(target_os = "linux")]
+fn create_server_ruleset(config_path: Option<&Path>) -> anyhow::Result<landlock::RulesetCreated> {
     use crate::{core::common::DEFAULT_CONFIG_PATH, server::config::ServerConfig};
     use anyhow::Context;
     use landlock::{
@@ -34,6 +82,17 @@ pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()
             config_path.display()
         ))?;
 
+    if let Some(include_dir) = &config.include_dir
+        && include_dir.exists()
+    {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(&[include_dir], AccessFs::from_read(abi)))
+            .context(format!(
+                "Failed to add Landlock rules for config include directory at {}",
+                include_dir.display()
+            ))?;
+    }
+
     if let Some(socket_path) = &config.socket_path {
         ruleset = ruleset
             .add_rules(path_beneath_rules(&[socket_path], AccessFs::from_all(abi)))
@@ -43,6 +102,18 @@ pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()
             ))?;
     }
 
+    if let Some(admin_socket_path) = &config.admin_socket_path {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(
+                &[admin_socket_path],
+                AccessFs::from_all(abi),
+            ))
+            .context(format!(
+                "Failed to add Landlock rules for admin socket path at {}",
+                admin_socket_path.display()
+            ))?;
+    }
+
     if let Some(mysql_socket_path) = &config.mysql.socket_path {
         ruleset = ruleset
             .add_rules(path_beneath_rules(
@@ -55,6 +126,9 @@ pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()
             ))?;
     }
 
+    // NOTE: all network access is handled by the ruleset, so without any `BindTcp` rules the
+    //       server can not listen on any TCP port, and it can only connect to the TCP ports
+    //       of the configured MySQL servers. The unix sockets are covered by the filesystem rules.
     if let Some(mysql_host) = &config.mysql.host {
         ruleset = ruleset
             .add_rule(NetPort::new(config.mysql.port, AccessNet::ConnectTcp))
@@ -64,6 +138,30 @@ pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()
             ))?;
     }
 
+    if let Some(replica_options) = config.mysql.as_replica_connect_options()? {
+        ruleset = match replica_options.get_socket() {
+            Some(replica_socket_path) => ruleset
+                .add_rules(path_beneath_rules(
+                    &[replica_socket_path],
+                    AccessFs::from_all(abi),
+                ))
+                .context(format!(
+                    "Failed to add Landlock rules for MySQL replica socket path at {}",
+                    replica_socket_path.display()
+                ))?,
+            None => ruleset
+                .add_rule(NetPort::new(
+                    replica_options.get_port(),
+                    AccessNet::ConnectTcp,
+                ))
+                .context(format!(
+                    "Failed to add Landlock rules for MySQL replica at {}:{}",
+                    replica_options.get_host(),
+                    replica_options.get_port()
+                ))?,
+        };
+    }
+
     if let Some(mysql_passwd_file) = &config.mysql.password_file {
         ruleset = ruleset
             .add_rules(path_beneath_rules(
@@ -76,14 +174,102 @@ pub fn landlock_restrict_server(config_path: Option<&Path>) -> anyhow::Result<()
             ))?;
     }
 
-    ruleset
-        .restrict_self()
-        .context("Failed to apply Landlock restrictions to the server process")?;
+    if let Some(policy) = &config.policy {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(
+                &policy.modules,
+                AccessFs::from_read(abi),
+            ))
+            .context("Failed to add Landlock rules for policy modules")?;
+
+        if let Some(opa) = &policy.opa {
+            ruleset = ruleset
+                .add_rule(NetPort::new(opa.port()?, AccessNet::ConnectTcp))
+                .context(format!(
+                    "Failed to add Landlock rules for OPA server at {}",
+                    opa.url
+                ))?;
+        }
+    }
 
+    let notify_command = config
+        .inactive_user_locking
+        .as_ref()
+        .and_then(|c| c.notify_command.as_ref())
+        .or_else(|| {
+            config
+                .scheduled_privilege_changes
+                .as_ref()
+                .and_then(|c| c.notify_command.as_ref())
+        });
+
+    if config.mysql.password_command.is_some() || notify_command.is_some() {
+        // The password and notification commands may be any program on the system,
+        // so allow executing and reading from the usual system directories.
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(
+                &["/bin", "/sbin", "/usr", "/lib", "/lib64", "/nix/store"],
+                AccessFs::from_read(abi),
+            ))
+            .context("Failed to add Landlock rules for external commands")?;
+    }
+
+    if let Some(inactive_user_locking) = &config.inactive_user_locking
+        && let Some(state_dir) = inactive_user_locking.state_file.parent()
+    {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(&[state_dir], AccessFs::from_all(abi)))
+            .context(format!(
+                "Failed to add Landlock rules for inactive user state directory at {}",
+                state_dir.display()
+            ))?;
+    }
+
+    if let Some(grant_offers) = &config.grant_offers
+        && let Some(state_dir) = grant_offers.state_file.parent()
+    {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(&[state_dir], AccessFs::from_all(abi)))
+            .context(format!(
+                "Failed to add Landlock rules for grant offer state directory at {}",
+                state_dir.display()
+            ))?;
+    }
+
+    if let Some(privilege_snapshots) = &config.privilege_snapshots
+        && let Some(state_dir) = privilege_snapshots.state_file.parent()
+    {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(&[state_dir], AccessFs::from_all(abi)))
+            .context(format!(
+                "Failed to add Landlock rules for privilege snapshot state directory at {}",
+                state_dir.display()
+            ))?;
+    }
+
+    if let Some(scheduled_privilege_changes) = &config.scheduled_privilege_changes
+        && let Some(state_dir) = scheduled_privilege_changes.state_file.parent()
+    {
+        ruleset = ruleset
+            .add_rules(path_beneath_rules(&[state_dir], AccessFs::from_all(abi)))
+            .context(format!(
+                "Failed to add Landlock rules for scheduled privilege change state directory at {}",
+                state_dir.display()
+            ))?;
+    }
+
+    Ok(ruleset)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn landlock_restrict_server(_config_path: Option<&std::path::Path>) -> anyhow::Result<()> {
     Ok(())
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn landlock_restrict_server() -> anyhow::Result<()> {
+pub fn log_landlock_status() {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn landlock_check_server(_config_path: Option<&std::path::Path>) -> anyhow::Result<()> {
     Ok(())
 }
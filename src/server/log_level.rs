@@ -0,0 +1,98 @@
+//! Changing the log level of the server at runtime.
+//!
+//! The level filter of the tracing subscriber is wrapped in a reload layer, so that
+//! intermittent problems can be debugged without restarting the server.
+//! The level can be changed by admins with `muscl admin log-level`,
+//! and `SIGUSR1` toggles debug logging on and off.
+
+use std::{str::FromStr, sync::OnceLock};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{Registry, reload};
+
+use crate::core::protocol::{AdminLogLevel, AdminLogLevelError, AdminLogLevelResponse};
+
+struct LogLevelHandle {
+    handle: reload::Handle<LevelFilter, Registry>,
+    /// The level the server was started with, which `SIGUSR1` returns to.
+    initial_level: LevelFilter,
+}
+
+static LOG_LEVEL_HANDLE: OnceLock<LogLevelHandle> = OnceLock::new();
+
+/// Create the level filter for the tracing subscriber of the server,
+/// which can be changed later with [`set_log_level`].
+pub fn reloadable_level_filter(level: LevelFilter) -> reload::Layer<LevelFilter, Registry> {
+    let (layer, handle) = reload::Layer::new(level);
+    if LOG_LEVEL_HANDLE
+        .set(LogLevelHandle {
+            handle,
+            initial_level: level,
+        })
+        .is_err()
+    {
+        tracing::warn!("The reloadable log level filter was created more than once");
+    }
+    layer
+}
+
+/// The current log level of the server, if it can be changed at runtime.
+#[must_use]
+pub fn current_log_level() -> Option<LevelFilter> {
+    LOG_LEVEL_HANDLE.get()?.handle.clone_current()
+}
+
+/// Change the log level of the server, returning the previous level.
+pub fn set_log_level(level: LevelFilter) -> Option<LevelFilter> {
+    let handle = LOG_LEVEL_HANDLE.get()?;
+    let previous_level = handle.handle.clone_current()?;
+
+    if let Err(err) = handle.handle.reload(level) {
+        tracing::error!("Failed to change the log level: {}", err);
+        return None;
+    }
+
+    tracing::info!("Changed log level from '{}' to '{}'", previous_level, level);
+    if level >= LevelFilter::TRACE {
+        tracing::warn!(
+            "The log level is set to 'trace', which logs SQL queries including passwords"
+        );
+    }
+
+    Some(previous_level)
+}
+
+/// Switch to debug logging, or back to the level the server was started with.
+pub fn toggle_debug_log_level() {
+    let Some(handle) = LOG_LEVEL_HANDLE.get() else {
+        tracing::warn!("The log level of this server can not be changed at runtime");
+        return;
+    };
+
+    let level = if current_log_level().is_some_and(|level| level < LevelFilter::DEBUG) {
+        LevelFilter::DEBUG
+    } else {
+        handle.initial_level
+    };
+    set_log_level(level);
+}
+
+/// Handle a request from an admin to look up or change the log level.
+pub fn handle_log_level_request(level: Option<&str>) -> AdminLogLevelResponse {
+    let Some(level) = level else {
+        let level = current_log_level().ok_or(AdminLogLevelError::NotSupported)?;
+        return Ok(AdminLogLevel {
+            level: level.to_string(),
+            previous_level: None,
+        });
+    };
+
+    let level = LevelFilter::from_str(level)
+        .map_err(|_| AdminLogLevelError::InvalidLogLevel(level.to_string()))?;
+    let previous_level = set_log_level(level).ok_or(AdminLogLevelError::NotSupported)?;
+
+    Ok(AdminLogLevel {
+        level: level.to_string(),
+        previous_level: Some(previous_level.to_string()),
+    })
+}
@@ -0,0 +1,58 @@
+//! Bodies of the periodic maintenance jobs run by the [`Scheduler`](crate::server::scheduler::Scheduler)
+//! that do not belong to a larger feature.
+
+use std::sync::Arc;
+
+use sqlx::MySqlPool;
+use tokio::sync::RwLock;
+
+use crate::{
+    core::protocol::request_validation::GroupDenylist,
+    server::{
+        common::find_name_owner,
+        sql::database_privilege_operations::{
+            unsafe_delete_privilege_row, unsafe_list_orphaned_privileges,
+        },
+    },
+};
+
+/// Remove privilege rows that refer to a database or user that no longer exists.
+///
+/// Only rows where either the database or the user is owned by some unix user or group
+/// are removed, to leave privileges that are not managed by muscl alone.
+pub async fn run_orphaned_privilege_cleanup_job(
+    db_pool: Arc<RwLock<MySqlPool>>,
+    group_denylist: Arc<RwLock<GroupDenylist>>,
+) -> anyhow::Result<()> {
+    let mut connection = db_pool.read().await.acquire().await?;
+    let group_denylist = group_denylist.read().await.clone();
+
+    let orphaned_privileges = unsafe_list_orphaned_privileges(&mut connection).await?;
+
+    let mut removed = 0;
+    for row in orphaned_privileges {
+        let owned = find_name_owner(&row.database, &group_denylist).is_some()
+            || find_name_owner(&row.user, &group_denylist).is_some();
+        if !owned {
+            continue;
+        }
+
+        // NOTE: errors are logged by the query function, keep going with the rest
+        if unsafe_delete_privilege_row(&row.database, &row.user, &mut connection)
+            .await
+            .is_ok()
+        {
+            tracing::info!(
+                "Removed orphaned privileges for user '{}' on database '{}' ({})",
+                row.user,
+                row.database,
+                row.reason().to_lowercase(),
+            );
+            removed += 1;
+        }
+    }
+
+    tracing::debug!("Removed {} orphaned privilege rows", removed);
+
+    Ok(())
+}
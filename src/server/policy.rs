@@ -0,0 +1,336 @@
+//! Site specific policies for the requests that change something, as WASM modules
+//! and/or an Open Policy Agent server, see [`opa`].
+//!
+//! Every configured module is given each mutating request before it is handled, and can
+//! allow it, deny it with a message for the user, or allow a changed version of it, e.g.
+//! to fill in different defaults. The modules are asked in the order they are configured,
+//! and each one sees the request as changed by the ones before it.
+//!
+//! A module must export its `memory`, and the functions
+//!
+//! - `muscl_alloc(len: i32) -> i32`, returning a pointer to `len` bytes of free memory, and
+//! - `muscl_check(ptr: i32, len: i32) -> i64`, returning `(ptr << 32) | len` of its output.
+//!
+//! The input is a JSON object with the `user`, their `groups` and the `request`:
+//!
+//! ```json
+//! { "user": "alice", "groups": ["alice"], "request": { "CreateDatabases": ["alice_db"] } }
+//! ```
+//!
+//! and the output is either `{ "decision": "allow" }`, optionally with a changed `request`,
+//! or `{ "decision": "deny", "message": "..." }`.
+//!
+//! Passwords are never given to the modules, and password changes can not be changed by them.
+//! A module that traps, runs out of fuel or gives invalid output denies the request.
+//!
+//! The OPA server is asked after all the modules have allowed the request, with the same input.
+
+mod opa;
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "wasm-policy")]
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{common::UnixUser, protocol::Request},
+    server::config::PolicyConfig,
+};
+
+use self::opa::{OpaClient, OpaDecision};
+
+const POLICY_FAILURE_MESSAGE: &str = "The request could not be checked against the server policy, please contact the system administrators";
+
+/// What to do with a request, according to the policies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyDecision {
+    /// Go ahead with the request, which might have been changed by the modules.
+    Allow(Request),
+    /// Refuse the request, with a message for the user.
+    Deny(String),
+}
+
+#[derive(Debug, Serialize)]
+struct PolicyInput<'a> {
+    user: &'a str,
+    groups: &'a [String],
+    request: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+enum PolicyOutput {
+    Allow {
+        #[serde(default)]
+        request: Option<serde_json::Value>,
+    },
+    Deny {
+        message: String,
+    },
+}
+
+fn policy_input(request: &Request, unix_user: &UnixUser) -> anyhow::Result<serde_json::Value> {
+    let request = match request {
+        Request::PasswdUser((user, _)) => {
+            serde_json::json!({ "PasswdUser": [user, "<REDACTED>"] })
+        }
+        request => serde_json::to_value(request)?,
+    };
+
+    Ok(serde_json::to_value(PolicyInput {
+        user: &unix_user.username,
+        groups: &unix_user.groups,
+        request,
+    })?)
+}
+
+fn parse_policy_output(output: &[u8], request: Request) -> anyhow::Result<PolicyDecision> {
+    match serde_json::from_slice(output)? {
+        PolicyOutput::Allow { request: None } => Ok(PolicyDecision::Allow(request)),
+        PolicyOutput::Allow {
+            request: Some(changed),
+        } => {
+            if matches!(request, Request::PasswdUser(_)) {
+                anyhow::bail!("Password changes can not be changed by policy modules");
+            }
+            let changed: Request = serde_json::from_value(changed)?;
+            if !changed.is_mutating()
+                || std::mem::discriminant(&changed) != std::mem::discriminant(&request)
+            {
+                anyhow::bail!("Policy modules can not change the kind of request");
+            }
+            Ok(PolicyDecision::Allow(changed))
+        }
+        PolicyOutput::Deny { message } => Ok(PolicyDecision::Deny(message)),
+    }
+}
+
+#[cfg_attr(not(feature = "wasm-policy"), allow(dead_code))]
+#[derive(Debug)]
+struct PolicyModule {
+    path: PathBuf,
+    #[cfg(feature = "wasm-policy")]
+    engine: wasmi::Engine,
+    #[cfg(feature = "wasm-policy")]
+    module: wasmi::Module,
+    #[cfg(feature = "wasm-policy")]
+    fuel: u64,
+}
+
+impl PolicyModule {
+    #[cfg(feature = "wasm-policy")]
+    fn load(path: &Path, engine: &wasmi::Engine, fuel: u64) -> anyhow::Result<Self> {
+        let wasm = std::fs::read(path).context(format!("Failed to read policy module {path:?}"))?;
+        let module = wasmi::Module::new(engine, &wasm)
+            .context(format!("Failed to load policy module {path:?}"))?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            engine: engine.clone(),
+            module,
+            fuel,
+        })
+    }
+
+    #[cfg(not(feature = "wasm-policy"))]
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        anyhow::bail!(
+            "Can not load policy module {path:?}, muscl was built without the wasm-policy feature"
+        )
+    }
+
+    /// Run `muscl_check` of the module on `input` in a fresh instance, and return its output.
+    #[cfg(feature = "wasm-policy")]
+    fn call(&self, input: &serde_json::Value) -> anyhow::Result<Vec<u8>> {
+        let input = serde_json::to_vec(input)?;
+        let mut store = wasmi::Store::new(&self.engine, ());
+        store.set_fuel(self.fuel)?;
+        let instance = wasmi::Linker::<()>::new(&self.engine)
+            .instantiate(&mut store, &self.module)?
+            .start(&mut store)?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("The module does not export its memory")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "muscl_alloc")?;
+        let check = instance.get_typed_func::<(i32, i32), i64>(&store, "muscl_check")?;
+
+        let input_len = i32::try_from(input.len()).context("The request is too large")?;
+        let input_ptr = alloc.call(&mut store, input_len)?;
+        memory.write(&mut store, input_ptr as u32 as usize, &input)?;
+
+        let packed = check.call(&mut store, (input_ptr, input_len))? as u64;
+        let mut output = vec![0; (packed & 0xffff_ffff) as usize];
+        memory.read(&store, (packed >> 32) as usize, &mut output)?;
+
+        Ok(output)
+    }
+
+    #[cfg(not(feature = "wasm-policy"))]
+    fn call(&self, _input: &serde_json::Value) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("muscl was built without the wasm-policy feature")
+    }
+}
+
+#[derive(Debug)]
+pub struct PolicyEngine {
+    modules: Vec<PolicyModule>,
+    opa: Option<OpaClient>,
+}
+
+impl PolicyEngine {
+    /// Load all the configured policy modules, and set up the client for the OPA server.
+    pub fn load(config: &PolicyConfig) -> anyhow::Result<Self> {
+        #[cfg(feature = "wasm-policy")]
+        let engine = wasmi::Engine::new(wasmi::Config::default().consume_fuel(true));
+
+        let modules = config
+            .modules
+            .iter()
+            .map(|path| {
+                #[cfg(feature = "wasm-policy")]
+                {
+                    PolicyModule::load(path, &engine, config.fuel)
+                }
+                #[cfg(not(feature = "wasm-policy"))]
+                {
+                    PolicyModule::load(path)
+                }
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let opa = config.opa.as_ref().map(OpaClient::new).transpose()?;
+
+        Ok(Self { modules, opa })
+    }
+
+    /// Load the policy modules from the server config, if there are any.
+    pub fn from_config(config: Option<&PolicyConfig>) -> anyhow::Result<Option<Self>> {
+        config.map(Self::load).transpose()
+    }
+
+    /// Ask every module, and then the OPA server, about a mutating request.
+    ///
+    /// Errors in the modules or with the OPA server are logged, and lead to the request being denied.
+    pub async fn check(&self, mut request: Request, unix_user: &UnixUser) -> PolicyDecision {
+        for module in &self.modules {
+            let result = policy_input(&request, unix_user)
+                .and_then(|input| module.call(&input))
+                .and_then(|output| parse_policy_output(&output, request.clone()));
+
+            match result {
+                Ok(PolicyDecision::Allow(changed)) => {
+                    if changed != request {
+                        tracing::info!("Policy module {:?} changed the request", module.path);
+                    }
+                    request = changed;
+                }
+                Ok(PolicyDecision::Deny(message)) => {
+                    tracing::info!(
+                        "Policy module {:?} denied the request: {}",
+                        module.path,
+                        message
+                    );
+                    return PolicyDecision::Deny(message);
+                }
+                Err(err) => {
+                    tracing::error!("Policy module {:?} failed: {:#}", module.path, err);
+                    return PolicyDecision::Deny(POLICY_FAILURE_MESSAGE.to_string());
+                }
+            }
+        }
+
+        if let Some(opa) = &self.opa {
+            let result = match policy_input(&request, unix_user) {
+                Ok(input) => opa.check(&input).await,
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(OpaDecision::Allow) => {}
+                Ok(OpaDecision::Deny(reasons)) => {
+                    tracing::info!(
+                        "OPA server at {} denied the request: {}",
+                        opa.url(),
+                        reasons.join(", ")
+                    );
+                    return PolicyDecision::Deny(if reasons.is_empty() {
+                        "The request was denied by the server policy".to_string()
+                    } else {
+                        reasons.join("\n")
+                    });
+                }
+                Err(err) => {
+                    tracing::error!("Failed to ask OPA server at {}: {:#}", opa.url(), err);
+                    return PolicyDecision::Deny(POLICY_FAILURE_MESSAGE.to_string());
+                }
+            }
+        }
+
+        PolicyDecision::Allow(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::MySQLUser;
+
+    fn unix_user() -> UnixUser {
+        UnixUser {
+            username: "alice".to_string(),
+            groups: vec!["alice".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_policy_input_redacts_passwords() {
+        let request =
+            Request::PasswdUser((MySQLUser::from("alice_user"), "hunter2".to_string().into()));
+        let input = policy_input(&request, &unix_user()).unwrap();
+
+        assert_eq!(
+            input,
+            serde_json::json!({
+              "user": "alice",
+              "groups": ["alice"],
+              "request": { "PasswdUser": ["alice_user", "<REDACTED>"] },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_parse_policy_output() {
+        let request = Request::DropUsers(vec![MySQLUser::from("alice_user")]);
+
+        assert_eq!(
+            parse_policy_output(br#"{"decision":"allow"}"#, request.clone()).unwrap(),
+            PolicyDecision::Allow(request.clone()),
+        );
+        assert_eq!(
+            parse_policy_output(
+                br#"{"decision":"deny","message":"Not on fridays"}"#,
+                request.clone()
+            )
+            .unwrap(),
+            PolicyDecision::Deny("Not on fridays".to_string()),
+        );
+        assert_eq!(
+            parse_policy_output(
+                br#"{"decision":"allow","request":{"DropUsers":["alice_other"]}}"#,
+                request.clone()
+            )
+            .unwrap(),
+            PolicyDecision::Allow(Request::DropUsers(vec![MySQLUser::from("alice_other")])),
+        );
+        assert!(
+            parse_policy_output(
+                br#"{"decision":"allow","request":{"DropDatabases":["alice_db"]}}"#,
+                request.clone()
+            )
+            .is_err()
+        );
+        assert!(parse_policy_output(b"not json", request).is_err());
+    }
+}
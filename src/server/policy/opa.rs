@@ -0,0 +1,199 @@
+//! Asking an [Open Policy Agent](https://www.openpolicyagent.org) server about requests.
+//!
+//! The input described in [`super`] is POSTed to the configured decision as
+//! `{ "input": ... }`. The decision is either a boolean, or an object like
+//! `{ "allow": false, "reasons": ["..."] }`, where the reasons are shown to the user.
+//! An undefined decision, a server that can not be reached or any other error
+//! denies the request.
+//!
+//! Decisions are cached for a few seconds per user and request, so that e.g. a script
+//! retrying the same request does not ask the server every time.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use crate::server::config::OpaConfig;
+
+#[cfg_attr(not(feature = "opa-policy"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpaDecision {
+    Allow,
+    Deny(Vec<String>),
+}
+
+#[cfg_attr(not(feature = "opa-policy"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+struct OpaResponse {
+    result: Option<OpaResult>,
+}
+
+#[cfg_attr(not(feature = "opa-policy"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OpaResult {
+    Allow(bool),
+    Decision {
+        allow: bool,
+        #[serde(default)]
+        reasons: Vec<String>,
+    },
+}
+
+#[cfg_attr(not(feature = "opa-policy"), allow(dead_code))]
+fn parse_opa_response(body: &[u8]) -> anyhow::Result<OpaDecision> {
+    let response: OpaResponse = serde_json::from_slice(body)?;
+    match response.result {
+        Some(OpaResult::Allow(true) | OpaResult::Decision { allow: true, .. }) => {
+            Ok(OpaDecision::Allow)
+        }
+        Some(OpaResult::Allow(false)) => Ok(OpaDecision::Deny(Vec::new())),
+        Some(OpaResult::Decision { reasons, .. }) => Ok(OpaDecision::Deny(reasons)),
+        None => anyhow::bail!("The decision is not defined for this input"),
+    }
+}
+
+#[derive(Debug, Default)]
+struct DecisionCache {
+    entries: HashMap<String, (Instant, OpaDecision)>,
+}
+
+impl DecisionCache {
+    fn get(&self, key: &str, now: Instant, ttl: Duration) -> Option<OpaDecision> {
+        self.entries
+            .get(key)
+            .filter(|(decided_at, _)| now.duration_since(*decided_at) < ttl)
+            .map(|(_, decision)| decision.clone())
+    }
+
+    fn insert(&mut self, key: String, decision: OpaDecision, now: Instant, ttl: Duration) {
+        self.entries
+            .retain(|_, (decided_at, _)| now.duration_since(*decided_at) < ttl);
+        self.entries.insert(key, (now, decision));
+    }
+}
+
+#[cfg_attr(not(feature = "opa-policy"), allow(dead_code))]
+#[derive(Debug)]
+pub struct OpaClient {
+    url: String,
+    cache_ttl: Duration,
+    cache: Mutex<DecisionCache>,
+    #[cfg(feature = "opa-policy")]
+    client: reqwest::Client,
+}
+
+impl OpaClient {
+    #[cfg(feature = "opa-policy")]
+    pub fn new(config: &OpaConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()?;
+
+        Ok(Self {
+            url: config.url.clone(),
+            cache_ttl: Duration::from_secs(config.cache_ttl),
+            cache: Mutex::new(DecisionCache::default()),
+            client,
+        })
+    }
+
+    #[cfg(not(feature = "opa-policy"))]
+    pub fn new(config: &OpaConfig) -> anyhow::Result<Self> {
+        anyhow::bail!(
+            "Can not use the OPA server at {:?}, muscl was built without the opa-policy feature",
+            config.url
+        )
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    #[cfg(feature = "opa-policy")]
+    async fn query(&self, input: &serde_json::Value) -> anyhow::Result<OpaDecision> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({ "input": input }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        parse_opa_response(&response.bytes().await?)
+    }
+
+    #[cfg(not(feature = "opa-policy"))]
+    async fn query(&self, _input: &serde_json::Value) -> anyhow::Result<OpaDecision> {
+        anyhow::bail!("muscl was built without the opa-policy feature")
+    }
+
+    /// Ask the server for a decision on `input`, or reuse a recent one.
+    pub async fn check(&self, input: &serde_json::Value) -> anyhow::Result<OpaDecision> {
+        let key = input.to_string();
+
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key, Instant::now(), self.cache_ttl);
+        if let Some(decision) = cached {
+            return Ok(decision);
+        }
+
+        let decision = self.query(input).await?;
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, decision.clone(), Instant::now(), self.cache_ttl);
+
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opa_response() {
+        assert_eq!(
+            parse_opa_response(br#"{"result": true}"#).unwrap(),
+            OpaDecision::Allow,
+        );
+        assert_eq!(
+            parse_opa_response(br#"{"result": false}"#).unwrap(),
+            OpaDecision::Deny(vec![]),
+        );
+        assert_eq!(
+            parse_opa_response(br#"{"result": {"allow": false, "reasons": ["No"]}}"#).unwrap(),
+            OpaDecision::Deny(vec!["No".to_string()]),
+        );
+        assert_eq!(
+            parse_opa_response(br#"{"result": {"allow": true}}"#).unwrap(),
+            OpaDecision::Allow,
+        );
+        assert!(parse_opa_response(br#"{}"#).is_err());
+        assert!(parse_opa_response(br#"{"result": "yes"}"#).is_err());
+    }
+
+    #[test]
+    fn test_decision_cache() {
+        let ttl = Duration::from_secs(5);
+        let now = Instant::now();
+        let mut cache = DecisionCache::default();
+
+        cache.insert("a".to_string(), OpaDecision::Allow, now, ttl);
+        assert_eq!(cache.get("a", now, ttl), Some(OpaDecision::Allow));
+        assert_eq!(cache.get("b", now, ttl), None);
+        assert_eq!(cache.get("a", now + ttl, ttl), None);
+
+        cache.insert("b".to_string(), OpaDecision::Allow, now + ttl, ttl);
+        assert_eq!(cache.entries.len(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Named snapshots of the privileges on the databases of a unix user.
+//!
+//! A snapshot is taken before a risky change, e.g. a large `edit-privs`, and can later be
+//! compared against the current privileges by `rollback-privs`, which applies the reverse
+//! diff through the regular privilege editing requests. Snapshots are kept in a small state
+//! file, and every unix user only ever sees and restores their own snapshots.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlConnection;
+
+use crate::{
+    core::{
+        common::UnixUser,
+        database_privileges::DatabasePrivilegeRow,
+        protocol::{
+            GetPrivilegeSnapshotError, GetPrivilegeSnapshotRequest, GetPrivilegeSnapshotResponse,
+            ListAllPrivilegesError, ListPrivilegeSnapshotsError, ListPrivilegeSnapshotsResponse,
+            PrivilegeSnapshotInfo, PrivilegeSnapshotTag, SnapshotPrivilegesError,
+            SnapshotPrivilegesRequest, SnapshotPrivilegesResponse, is_valid_privilege_snapshot_tag,
+            request_validation::GroupDenylist,
+        },
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        config::PrivilegeSnapshotsConfig,
+        sql::database_privilege_operations::get_all_database_privileges,
+        state_file::{LockedStateFile, STORE_ERROR_MESSAGE},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivilegeSnapshot {
+    pub tag: PrivilegeSnapshotTag,
+    /// The unix user that took the snapshot.
+    pub created_by: String,
+    pub created_at: u64,
+    pub rows: Vec<DatabasePrivilegeRow>,
+}
+
+impl PrivilegeSnapshot {
+    fn info(&self) -> PrivilegeSnapshotInfo {
+        PrivilegeSnapshotInfo {
+            tag: self.tag.clone(),
+            created_at: self.created_at,
+            rows: self.rows.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PrivilegeSnapshotState {
+    /// All snapshots, oldest first.
+    pub snapshots: Vec<PrivilegeSnapshot>,
+}
+
+impl PrivilegeSnapshotState {
+    fn find(&self, created_by: &str, tag: &str) -> Option<&PrivilegeSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.created_by == created_by && snapshot.tag == tag)
+    }
+
+    /// Adds a snapshot, dropping the oldest snapshots of the same unix user
+    /// if it has more than `max_snapshots`.
+    fn insert(&mut self, snapshot: PrivilegeSnapshot, max_snapshots: usize) {
+        let created_by = snapshot.created_by.clone();
+        self.snapshots.push(snapshot);
+
+        let count = self
+            .snapshots
+            .iter()
+            .filter(|snapshot| snapshot.created_by == created_by)
+            .count();
+        let mut to_remove = count.saturating_sub(max_snapshots);
+        self.snapshots.retain(|snapshot| {
+            if to_remove > 0 && snapshot.created_by == created_by {
+                to_remove -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Store the current privileges on every database the unix user can manage under a tag.
+pub async fn snapshot_privileges(
+    tag: SnapshotPrivilegesRequest,
+    config: Option<&PrivilegeSnapshotsConfig>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> SnapshotPrivilegesResponse {
+    let config = config.ok_or(SnapshotPrivilegesError::Disabled)?;
+
+    if !is_valid_privilege_snapshot_tag(&tag) {
+        return Err(SnapshotPrivilegesError::InvalidTag);
+    }
+
+    let rows = get_all_database_privileges(
+        None,
+        unix_user,
+        connection,
+        backend_capabilities,
+        group_denylist,
+    )
+    .await
+    .map_err(|ListAllPrivilegesError::MySqlError(err)| SnapshotPrivilegesError::MySqlError(err))?;
+
+    let (state_file, mut state): (_, PrivilegeSnapshotState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read privilege snapshots: {:#}", err);
+                SnapshotPrivilegesError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+
+    if state.find(&unix_user.username, &tag).is_some() {
+        return Err(SnapshotPrivilegesError::SnapshotAlreadyExists);
+    }
+
+    let snapshot = PrivilegeSnapshot {
+        tag,
+        created_by: unix_user.username.clone(),
+        created_at: now(),
+        rows,
+    };
+    let info = snapshot.info();
+    state.insert(snapshot, config.max_snapshots);
+
+    state_file.write(&state).map_err(|err| {
+        tracing::error!("Failed to store privilege snapshot: {:#}", err);
+        SnapshotPrivilegesError::StoreError(STORE_ERROR_MESSAGE.to_string())
+    })?;
+
+    tracing::info!(
+        "User '{}' stored {} privilege rows in snapshot '{}'",
+        unix_user,
+        info.rows,
+        info.tag
+    );
+
+    Ok(info)
+}
+
+/// List the snapshots taken by the unix user.
+pub async fn list_privilege_snapshots(
+    config: Option<&PrivilegeSnapshotsConfig>,
+    unix_user: &UnixUser,
+) -> ListPrivilegeSnapshotsResponse {
+    let config = config.ok_or(ListPrivilegeSnapshotsError::Disabled)?;
+
+    let (_state_file, state): (_, PrivilegeSnapshotState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read privilege snapshots: {:#}", err);
+                ListPrivilegeSnapshotsError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+
+    Ok(state
+        .snapshots
+        .iter()
+        .filter(|snapshot| snapshot.created_by == unix_user.username)
+        .map(PrivilegeSnapshot::info)
+        .collect())
+}
+
+/// Get the privilege rows of a snapshot taken by the unix user.
+///
+/// The rows are not validated here, as they are only used to compute the changes
+/// for a rollback, which are validated when they are applied.
+pub async fn get_privilege_snapshot(
+    tag: GetPrivilegeSnapshotRequest,
+    config: Option<&PrivilegeSnapshotsConfig>,
+    unix_user: &UnixUser,
+) -> GetPrivilegeSnapshotResponse {
+    let config = config.ok_or(GetPrivilegeSnapshotError::Disabled)?;
+
+    let (_state_file, state): (_, PrivilegeSnapshotState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read privilege snapshots: {:#}", err);
+                GetPrivilegeSnapshotError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+
+    state
+        .find(&unix_user.username, &tag)
+        .map(|snapshot| snapshot.rows.clone())
+        .ok_or(GetPrivilegeSnapshotError::SnapshotDoesNotExist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(created_by: &str, tag: &str) -> PrivilegeSnapshot {
+        PrivilegeSnapshot {
+            tag: tag.to_string(),
+            created_by: created_by.to_string(),
+            created_at: 0,
+            rows: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_privilege_snapshot_state() {
+        let mut state = PrivilegeSnapshotState::default();
+
+        state.insert(snapshot("alice", "a"), 2);
+        state.insert(snapshot("bob", "a"), 2);
+        state.insert(snapshot("alice", "b"), 2);
+        assert!(state.find("alice", "a").is_some());
+        assert!(state.find("bob", "b").is_none());
+
+        // The oldest snapshot of alice is dropped, but not the one of bob.
+        state.insert(snapshot("alice", "c"), 2);
+        assert!(state.find("alice", "a").is_none());
+        assert!(state.find("bob", "a").is_some());
+        assert_eq!(
+            state
+                .snapshots
+                .iter()
+                .map(|snapshot| (snapshot.created_by.as_str(), snapshot.tag.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("bob", "a"), ("alice", "b"), ("alice", "c")],
+        );
+    }
+}
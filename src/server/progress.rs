@@ -0,0 +1,80 @@
+//! Progress events for long running bulk requests.
+//!
+//! While a bulk request is processed, the server sends [`Response::Progress`]
+//! messages ahead of the final response, so that the client can show how far
+//! it has come instead of looking frozen.
+
+use std::future::Future;
+
+use futures_util::SinkExt;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::core::protocol::{Response, ServerToClientMessageStream};
+
+/// Bulk requests with fewer items than this finish quickly enough to not report progress.
+pub const PROGRESS_MIN_ITEMS: usize = 20;
+
+/// Reports how far the server has come with a bulk request.
+///
+/// A disabled reporter does nothing, for requests that are too small to be worth
+/// reporting on, and for callers that do not talk to a client.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    sender: Option<UnboundedSender<Response>>,
+    total: usize,
+}
+
+impl ProgressReporter {
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Report that `done` items are finished, and that `current_item` is processed next.
+    pub fn report(&self, done: usize, current_item: &str) {
+        if let Some(sender) = &self.sender {
+            // NOTE: the receiver is only gone once the request is finished.
+            let _ = sender.send(Response::Progress {
+                done,
+                total: self.total,
+                current_item: current_item.to_string(),
+            });
+        }
+    }
+}
+
+/// Run `operation` on `total` items, forwarding its progress to the client while it runs.
+///
+/// If the client can not be reached anymore, the operation still runs to completion,
+/// so that it is not interrupted halfway through.
+pub async fn with_progress<T, F>(
+    stream: &mut ServerToClientMessageStream,
+    total: usize,
+    operation: impl FnOnce(ProgressReporter) -> F,
+) -> T
+where
+    F: Future<Output = T>,
+{
+    if total < PROGRESS_MIN_ITEMS {
+        return operation(ProgressReporter::disabled()).await;
+    }
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let operation = operation(ProgressReporter {
+        sender: Some(sender),
+        total,
+    });
+    tokio::pin!(operation);
+
+    loop {
+        tokio::select! {
+            result = &mut operation => return result,
+            Some(progress) = receiver.recv() => {
+                if let Err(err) = stream.send(progress).await {
+                    tracing::warn!("Failed to send progress to client: {}", err);
+                    return operation.await;
+                }
+            }
+        }
+    }
+}
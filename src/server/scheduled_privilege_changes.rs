@@ -0,0 +1,318 @@
+//! Privilege changes that are applied by the server at a later time.
+//!
+//! The changes are approved by the unix user up front, like with `edit-privs`, and kept
+//! in a small state file until the `scheduled_privilege_changes` job applies them. The
+//! ownership of every database and user is checked both when the changes are scheduled
+//! and when they are applied, so a change of ownership in between is respected.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            SchedulePrivilegeChangesError, SchedulePrivilegeChangesRequest,
+            SchedulePrivilegeChangesResponse, ScheduledPrivilegeChange, ScheduledPrivilegeChangeId,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        types::DbOrUser,
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        bulk::BulkConnections,
+        config::{ScheduledPrivilegeChangesConfig, ServerConfig},
+        progress::ProgressReporter,
+        scheduler::format_utc_datetime,
+        sql::database_privilege_operations::apply_privilege_diffs,
+        state_file::{LockedStateFile, STORE_ERROR_MESSAGE},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ScheduledPrivilegeChangeState {
+    pub next_id: ScheduledPrivilegeChangeId,
+    pub changes: BTreeMap<ScheduledPrivilegeChangeId, ScheduledPrivilegeChange>,
+}
+
+impl ScheduledPrivilegeChangeState {
+    fn insert(&mut self, mut change: ScheduledPrivilegeChange) -> ScheduledPrivilegeChange {
+        self.next_id += 1;
+        change.id = self.next_id;
+        self.changes.insert(change.id, change.clone());
+        change
+    }
+
+    /// Removes and returns the changes that are due at `now`, in the order they were scheduled for.
+    fn take_due(&mut self, now: u64) -> Vec<ScheduledPrivilegeChange> {
+        let mut due = self
+            .changes
+            .extract_if(.., |_, change| change.apply_at <= now)
+            .collect::<Vec<_>>();
+        due.sort_by_key(|(id, change)| (change.apply_at, *id));
+        due.into_iter().map(|(_, change)| change).collect()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Store privilege changes to be applied at the requested time.
+///
+/// Only the ownership of the databases and users is checked here. Whether the changes
+/// still apply is checked when they are applied, as the databases and users might only
+/// be created in the meantime.
+pub async fn schedule_privilege_changes(
+    request: SchedulePrivilegeChangesRequest,
+    config: Option<&ScheduledPrivilegeChangesConfig>,
+    unix_user: &UnixUser,
+    group_denylist: &GroupDenylist,
+) -> SchedulePrivilegeChangesResponse {
+    let config = config.ok_or(SchedulePrivilegeChangesError::Disabled)?;
+
+    if request.apply_at <= now() {
+        return Err(SchedulePrivilegeChangesError::TimeIsInThePast);
+    }
+
+    for diff in &request.diffs {
+        for db_or_user in [
+            DbOrUser::Database(diff.get_database_name().to_owned()),
+            DbOrUser::User(diff.get_user_name().to_owned()),
+        ] {
+            if let Err(err) = validate_db_or_user_request(&db_or_user, unix_user, group_denylist) {
+                return Err(SchedulePrivilegeChangesError::ValidationError(
+                    db_or_user, err,
+                ));
+            }
+        }
+    }
+
+    let (state_file, mut state): (_, ScheduledPrivilegeChangeState) =
+        LockedStateFile::lock_and_read(&config.state_file)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to read scheduled privilege changes: {:#}", err);
+                SchedulePrivilegeChangesError::StoreError(STORE_ERROR_MESSAGE.to_string())
+            })?;
+
+    let change = state.insert(ScheduledPrivilegeChange {
+        id: 0,
+        scheduled_by: unix_user.username.clone(),
+        apply_at: request.apply_at,
+        atomic: request.atomic,
+        diffs: request.diffs,
+    });
+
+    state_file.write(&state).map_err(|err| {
+        tracing::error!("Failed to store scheduled privilege changes: {:#}", err);
+        SchedulePrivilegeChangesError::StoreError(STORE_ERROR_MESSAGE.to_string())
+    })?;
+
+    tracing::info!(
+        "User '{}' scheduled {} privilege changes for {} (scheduled change {})",
+        unix_user,
+        change.diffs.len(),
+        format_utc_datetime(change.apply_at),
+        change.id
+    );
+
+    Ok(change)
+}
+
+fn notify_scheduled_by(command: &[String], change: &ScheduledPrivilegeChange, success: bool) {
+    let Some((program, args)) = command.split_first() else {
+        return;
+    };
+
+    let result = std::process::Command::new(program)
+        .args(args)
+        .arg(&change.scheduled_by)
+        .arg(change.id.to_string())
+        .arg(if success { "success" } else { "failure" })
+        .stdin(std::process::Stdio::null())
+        .status();
+
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::warn!(
+            "Notification command for scheduled change {} exited with {}",
+            change.id,
+            status
+        ),
+        Err(err) => tracing::warn!(
+            "Failed to run notification command for scheduled change {}: {}",
+            change.id,
+            err
+        ),
+    }
+}
+
+/// Apply a single scheduled change as the unix user that scheduled it,
+/// and log the outcome of every diff. Returns whether all of them were applied.
+async fn apply_scheduled_privilege_change(
+    change: &ScheduledPrivilegeChange,
+    db_pool: &MySqlPool,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> anyhow::Result<bool> {
+    let unix_user = UnixUser::from_username(&change.scheduled_by)?;
+    let mut connection = db_pool.acquire().await?;
+
+    let result = apply_privilege_diffs(
+        change.diffs.clone(),
+        change.atomic,
+        &unix_user,
+        &mut BulkConnections::single(&mut connection),
+        backend_capabilities,
+        group_denylist,
+        ProgressReporter::disabled(),
+    )
+    .await;
+
+    for ((database_name, user_name), result) in &result {
+        match result {
+            Ok(()) => tracing::info!(
+                "Modified privileges for user '{}' on database '{}' as '{}' (scheduled change {})",
+                user_name,
+                database_name,
+                unix_user,
+                change.id
+            ),
+            Err(err) => tracing::warn!(
+                "Failed to modify privileges for user '{}' on database '{}' as '{}' (scheduled change {}): {}",
+                user_name,
+                database_name,
+                unix_user,
+                change.id,
+                err
+            ),
+        }
+    }
+
+    Ok(result.values().all(Result::is_ok))
+}
+
+/// Apply all scheduled changes that are due.
+///
+/// The changes are removed from the state file before they are applied,
+/// so a change is never applied twice, even if the server stops halfway.
+pub async fn apply_due_privilege_changes(
+    config: &ScheduledPrivilegeChangesConfig,
+    db_pool: &MySqlPool,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> anyhow::Result<()> {
+    let due = {
+        let (state_file, mut state): (_, ScheduledPrivilegeChangeState) =
+            LockedStateFile::lock_and_read(&config.state_file).await?;
+        let due = state.take_due(now());
+        if due.is_empty() {
+            return Ok(());
+        }
+        state_file.write(&state)?;
+        due
+    };
+
+    for change in due {
+        let success = match apply_scheduled_privilege_change(
+            &change,
+            db_pool,
+            backend_capabilities,
+            group_denylist,
+        )
+        .await
+        {
+            Ok(success) => success,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to apply scheduled change {} of '{}': {:#}",
+                    change.id,
+                    change.scheduled_by,
+                    err
+                );
+                false
+            }
+        };
+
+        if let Some(command) = config.notify_command.clone() {
+            tokio::task::spawn_blocking(move || notify_scheduled_by(&command, &change, success))
+                .await
+                .ok();
+        }
+    }
+
+    Ok(())
+}
+
+/// The body of the `scheduled_privilege_changes` job, which does nothing unless
+/// `scheduled_privilege_changes` is configured.
+pub async fn run_scheduled_privilege_changes_job(
+    config: Arc<Mutex<ServerConfig>>,
+    db_pool: Arc<RwLock<MySqlPool>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+    group_denylist: Arc<RwLock<GroupDenylist>>,
+) -> anyhow::Result<()> {
+    let Some(changes_config) = config.lock().await.scheduled_privilege_changes.clone() else {
+        tracing::debug!("Scheduled privilege changes are not configured, skipping");
+        return Ok(());
+    };
+
+    let db_pool = db_pool.read().await.clone();
+    apply_due_privilege_changes(
+        &changes_config,
+        &db_pool,
+        &*backend_capabilities.read().await,
+        &*group_denylist.read().await,
+    )
+    .await
+    .context("Failed to apply scheduled privilege changes")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn change(apply_at: u64) -> ScheduledPrivilegeChange {
+        ScheduledPrivilegeChange {
+            id: 0,
+            scheduled_by: "alice".to_string(),
+            apply_at,
+            atomic: false,
+            diffs: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_scheduled_privilege_change_state() {
+        let mut state = ScheduledPrivilegeChangeState::default();
+
+        assert_eq!(state.insert(change(300)).id, 1);
+        assert_eq!(state.insert(change(100)).id, 2);
+        assert_eq!(state.insert(change(200)).id, 3);
+
+        assert!(state.take_due(50).is_empty());
+        assert_eq!(
+            state
+                .take_due(200)
+                .iter()
+                .map(|change| change.id)
+                .collect::<Vec<_>>(),
+            vec![2, 3],
+        );
+        assert_eq!(state.changes.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(state.insert(change(400)).id, 4);
+    }
+}
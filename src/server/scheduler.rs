@@ -0,0 +1,187 @@
+//! A small scheduler for periodic maintenance jobs.
+//!
+//! Every job is registered with a name and its [`JobDefaults`], which can be
+//! overridden in the `[jobs.<name>]` section of the server config. The server
+//! restarts the scheduler when the `[jobs]` section changes on reload, so new
+//! schedules take effect right away.
+//!
+//! A random delay of up to the jitter is added before every run, so that many
+//! servers sharing a database server do not run their jobs at the same time.
+
+mod cron;
+
+pub use cron::*;
+
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::future::BoxFuture;
+use tokio::{
+    select,
+    sync::Mutex,
+    task::{JoinHandle, JoinSet},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::server::config::ServerConfig;
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, anyhow::Result<()>> + Send + Sync>;
+
+/// The settings of a job when it is not configured.
+#[derive(Debug, Clone, Copy)]
+pub struct JobDefaults {
+    /// A cron expression, see [`CronSchedule`].
+    pub schedule: &'static str,
+    pub jitter: Duration,
+    /// Jobs that change things nobody asked for should be opt-in.
+    pub enabled: bool,
+}
+
+struct Job {
+    name: &'static str,
+    default_schedule: CronSchedule,
+    default_jitter: Duration,
+    enabled_by_default: bool,
+    run: JobFn,
+}
+
+/// The schedule of a job after applying the config overrides.
+struct JobSettings {
+    enabled: bool,
+    schedule: CronSchedule,
+    jitter: Duration,
+}
+
+impl Job {
+    async fn settings(&self, config: &Mutex<ServerConfig>) -> JobSettings {
+        let config = config.lock().await;
+        let job_config = config.jobs.get(self.name);
+        JobSettings {
+            enabled: job_config
+                .and_then(|job_config| job_config.enabled)
+                .unwrap_or(self.enabled_by_default),
+            schedule: job_config
+                .and_then(|job_config| job_config.schedule.clone())
+                .unwrap_or_else(|| self.default_schedule.clone()),
+            jitter: job_config
+                .and_then(|job_config| job_config.jitter)
+                .map_or(self.default_jitter, Duration::from_secs),
+        }
+    }
+
+    async fn run_forever(self, config: Arc<Mutex<ServerConfig>>, cancel_token: CancellationToken) {
+        loop {
+            let settings = self.settings(&config).await;
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let Some(next_run) = settings.schedule.next_after(now) else {
+                tracing::error!(
+                    "Schedule '{}' never matches, not running job again",
+                    settings.schedule
+                );
+                return;
+            };
+
+            let jitter = Duration::from_secs(rand::random_range(0..=settings.jitter.as_secs()));
+            let delay = Duration::from_secs(next_run - now) + jitter;
+            tracing::debug!("Next run in {} seconds", delay.as_secs());
+            select! {
+                () = tokio::time::sleep(delay) => {}
+                () = cancel_token.cancelled() => return,
+            }
+
+            // NOTE: the job might have been disabled by a reload while sleeping
+            if !self.settings(&config).await.enabled {
+                tracing::debug!("Job is disabled, skipping");
+                continue;
+            }
+
+            tracing::debug!("Running job");
+            let start = Instant::now();
+            match (self.run)().await {
+                Ok(()) => tracing::debug!("Job finished in {:?}", start.elapsed()),
+                Err(err) => {
+                    tracing::error!("Job failed after {:?}: {:#}", start.elapsed(), err);
+                }
+            }
+        }
+    }
+}
+
+pub struct Scheduler {
+    config: Arc<Mutex<ServerConfig>>,
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    #[must_use]
+    pub fn new(config: Arc<Mutex<ServerConfig>>) -> Self {
+        Self {
+            config,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Register a job to be run periodically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the default schedule is not a valid cron expression.
+    pub fn register<F, Fut>(&mut self, name: &'static str, defaults: JobDefaults, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let default_schedule = CronSchedule::parse(defaults.schedule)
+            .unwrap_or_else(|err| panic!("Invalid default schedule for job '{name}': {err}"));
+        self.jobs.push(Job {
+            name,
+            default_schedule,
+            default_jitter: defaults.jitter,
+            enabled_by_default: defaults.enabled,
+            run: Arc::new(move || Box::pin(run())),
+        });
+    }
+
+    #[must_use]
+    pub fn job_names(&self) -> Vec<&'static str> {
+        self.jobs.iter().map(|job| job.name).collect()
+    }
+
+    /// Start running all registered jobs, each in its own task.
+    ///
+    /// The returned task finishes once `cancel_token` is cancelled. Jobs that are
+    /// running at that point are allowed to finish first, so they are not cut off halfway.
+    pub async fn spawn(self, cancel_token: CancellationToken) -> JoinHandle<()> {
+        let job_names = self.job_names();
+        for name in self.config.lock().await.jobs.keys() {
+            if !job_names.contains(&name.as_str()) {
+                tracing::warn!("Ignoring config for unknown job '{}'", name);
+            }
+        }
+
+        let mut tasks = JoinSet::new();
+        for job in self.jobs {
+            let span = tracing::info_span!("job", name = job.name);
+            tasks.spawn(
+                job.run_forever(self.config.clone(), cancel_token.clone())
+                    .instrument(span),
+            );
+        }
+
+        tokio::spawn(async move {
+            while let Some(result) = tasks.join_next().await {
+                if let Err(err) = result {
+                    tracing::error!("Scheduled job panicked: {}", err);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,375 @@
+//! A minimal parser and evaluator for cron expressions.
+//!
+//! Supports the classic five fields (minute, hour, day of month, month and day of week),
+//! with `*`, numbers, ranges (`1-5`), lists (`1,3,5`) and steps (`*/15`, `0-30/10`),
+//! as well as the `@hourly`, `@daily`, `@weekly` and `@monthly` shorthands.
+//! Names of months and weekdays are not supported.
+//!
+//! All times are in UTC.
+
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_MINUTE: u64 = 60;
+const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+/// How many days ahead to look for the next matching time before giving up,
+/// e.g. for `0 0 31 2 *`. Long enough to cover the 29th of February.
+const MAX_LOOKAHEAD_DAYS: u64 = 8 * 366;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day of month field was something other than `*`.
+    day_of_month_restricted: bool,
+    /// Whether the day of week field was something other than `*`.
+    day_of_week_restricted: bool,
+}
+
+fn parse_number(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    let number: u32 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if number < min || number > max {
+        return Err(format!("{number} is not between {min} and {max}"));
+    }
+    Ok(number)
+}
+
+/// Parse a single field into a bitmask, where bit `n` is set if `n` matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = parse_number(step, 1, max)?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (parse_number(start, min, max)?, parse_number(end, min, max)?),
+            // NOTE: `5/10` means every 10th starting at 5, like in most cron implementations.
+            None if part.contains('/') => (parse_number(range, min, max)?, max),
+            None => {
+                let value = parse_number(range, min, max)?;
+                (value, value)
+            }
+        };
+
+        if start > end {
+            return Err(format!("Invalid range '{range}'"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(format!(
+                "Expected 5 fields in cron expression '{expression}', found {}",
+                fields.len()
+            ));
+        };
+
+        let with_context = |name: &str, result: Result<u64, String>| {
+            result.map_err(|err| format!("Invalid {name} field: {err}"))
+        };
+
+        let mut days_of_week = with_context("day of week", parse_field(day_of_week, 0, 7))?;
+        // NOTE: both 0 and 7 mean sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            source: expression.to_string(),
+            minutes: with_context("minute", parse_field(minute, 0, 59))?,
+            hours: with_context("hour", parse_field(hour, 0, 23))?,
+            days_of_month: with_context("day of month", parse_field(day_of_month, 1, 31))?,
+            months: with_context("month", parse_field(month, 1, 12))?,
+            days_of_week,
+            day_of_month_restricted: day_of_month != "*",
+            day_of_week_restricted: day_of_week != "*",
+        })
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    fn matches_day(&self, days_since_epoch: u64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        // NOTE: 1970-01-01 was a thursday
+        let weekday = (days_since_epoch + 4) % 7;
+
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+
+        let day_of_month_matches = self.days_of_month & (1 << day) != 0;
+        let day_of_week_matches = self.days_of_week & (1 << weekday) != 0;
+
+        // NOTE: like in cron, if both day fields are restricted, either of them matching is enough.
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month_matches || day_of_week_matches,
+            _ => day_of_month_matches && day_of_week_matches,
+        }
+    }
+
+    /// Returns the first matching unix timestamp strictly after `timestamp`,
+    /// or `None` if nothing matches within the next few years.
+    #[must_use]
+    pub fn next_after(&self, timestamp: u64) -> Option<u64> {
+        let mut time = (timestamp / SECONDS_PER_MINUTE + 1) * SECONDS_PER_MINUTE;
+        let deadline = time + MAX_LOOKAHEAD_DAYS * SECONDS_PER_DAY;
+
+        while time < deadline {
+            if !self.matches_day(time / SECONDS_PER_DAY) {
+                time = (time / SECONDS_PER_DAY + 1) * SECONDS_PER_DAY;
+                continue;
+            }
+
+            let hour = (time % SECONDS_PER_DAY) / SECONDS_PER_HOUR;
+            if self.hours & (1 << hour) == 0 {
+                time = (time / SECONDS_PER_HOUR + 1) * SECONDS_PER_HOUR;
+                continue;
+            }
+
+            let minute = (time % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE;
+            if self.minutes & (1 << minute) == 0 {
+                time += SECONDS_PER_MINUTE;
+                continue;
+            }
+
+            return Some(time);
+        }
+
+        None
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(value: CronSchedule) -> Self {
+        value.source
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Convert a number of days since the unix epoch into a (year, month, day) date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days_since_epoch: u64) -> (u64, u64, u64) {
+    let days = days_since_epoch + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert a (year, month, day) date into a number of days since the unix epoch.
+///
+/// This is the inverse of [`civil_from_days`], for dates from 1970 onwards.
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse a UTC date and time in the format `YYYY-MM-DDTHH:MM`, optionally with seconds
+/// and a trailing `Z`, into a unix timestamp.
+pub fn parse_utc_datetime(value: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid time '{value}', expected YYYY-MM-DDTHH:MM in UTC");
+
+    let value_without_zone = value.strip_suffix('Z').unwrap_or(value);
+    let (date, time) = value_without_zone
+        .split_once(['T', ' '])
+        .ok_or_else(invalid)?;
+
+    let date_fields = date
+        .split('-')
+        .map(|field| field.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let time_fields = time
+        .split(':')
+        .map(|field| field.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (&[year, month, day], &[hour, minute, ref second @ ..]) =
+        (date_fields.as_slice(), time_fields.as_slice())
+    else {
+        return Err(invalid());
+    };
+    let second = match second {
+        [] => 0,
+        [second] => *second,
+        _ => return Err(invalid()),
+    };
+
+    if year < 1970
+        || !(1..=12).contains(&month)
+        || day == 0
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(invalid());
+    }
+
+    // NOTE: round trip the date to reject days that do not exist, like the 30th of February.
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+
+    Ok(days * SECONDS_PER_DAY + hour * SECONDS_PER_HOUR + minute * SECONDS_PER_MINUTE + second)
+}
+
+/// Format a unix timestamp as `YYYY-MM-DD HH:MM UTC`.
+#[must_use]
+pub fn format_utc_datetime(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    let hour = (timestamp % SECONDS_PER_DAY) / SECONDS_PER_HOUR;
+    let minute = (timestamp % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE;
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02} UTC")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-28 23:30:00 UTC, a wednesday
+    const TIMESTAMP: u64 = 1_709_163_000;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(TIMESTAMP / SECONDS_PER_DAY), (2024, 2, 28));
+        assert_eq!(
+            civil_from_days(TIMESTAMP / SECONDS_PER_DAY + 1),
+            (2024, 2, 29)
+        );
+    }
+
+    #[test]
+    fn test_parse_utc_datetime() {
+        assert_eq!(parse_utc_datetime("1970-01-01T00:00"), Ok(0));
+        assert_eq!(parse_utc_datetime("2024-02-28T23:30"), Ok(TIMESTAMP));
+        assert_eq!(parse_utc_datetime("2024-02-28T23:30:00Z"), Ok(TIMESTAMP));
+        assert_eq!(parse_utc_datetime("2024-02-28 23:30"), Ok(TIMESTAMP));
+        assert_eq!(
+            parse_utc_datetime("2024-02-29T00:00"),
+            Ok(TIMESTAMP + 30 * 60)
+        );
+
+        assert!(parse_utc_datetime("2024-02-28").is_err());
+        assert!(parse_utc_datetime("2024-02-30T00:00").is_err());
+        assert!(parse_utc_datetime("2023-02-29T00:00").is_err());
+        assert!(parse_utc_datetime("2024-13-01T00:00").is_err());
+        assert!(parse_utc_datetime("2024-01-01T24:00").is_err());
+        assert!(parse_utc_datetime("2024-01-01T02:00+02:00").is_err());
+    }
+
+    #[test]
+    fn test_format_utc_datetime() {
+        assert_eq!(format_utc_datetime(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc_datetime(TIMESTAMP), "2024-02-28 23:30 UTC");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* * 0 * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("@yearly").is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        let next = |expression: &str| {
+            CronSchedule::parse(expression)
+                .unwrap()
+                .next_after(TIMESTAMP)
+                .map(|time| time - TIMESTAMP)
+        };
+
+        assert_eq!(next("* * * * *"), Some(60));
+        assert_eq!(next("*/15 * * * *"), Some(15 * 60));
+        assert_eq!(next("@hourly"), Some(30 * 60));
+        assert_eq!(next("@daily"), Some(30 * 60));
+        assert_eq!(next("0 3 * * *"), Some(3 * 60 * 60 + 30 * 60));
+        assert_eq!(next("0 0 29 2 *"), Some(30 * 60));
+        // Friday, 2024-03-01
+        assert_eq!(next("0 0 * * 5"), Some(SECONDS_PER_DAY + 30 * 60));
+        // Either the 1st of the month or a thursday
+        assert_eq!(next("0 0 1 * 4"), Some(30 * 60));
+        assert_eq!(next("0 0 31 2 *"), None);
+    }
+
+    #[test]
+    fn test_sunday_as_seven() {
+        assert_eq!(
+            CronSchedule::parse("0 0 * * 7")
+                .unwrap()
+                .next_after(TIMESTAMP),
+            CronSchedule::parse("0 0 * * 0")
+                .unwrap()
+                .next_after(TIMESTAMP),
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! The seccomp filter of the worker processes started with `process_isolation`,
+//! see [`crate::server::worker`].
+//!
+//! Once a worker has connected to the database, it only has to talk to the client
+//! and the database server. The filter makes the system calls that a compromised
+//! session could use to run other programs, look into other processes or change
+//! the system fail with `EPERM`. All other system calls are allowed, so this is a
+//! deny list on top of the Landlock restrictions, not a full allow list.
+
+#[cfg(target_os = "linux")]
+const DENIED_SYSCALLS: &[i64] = {
+    use nix::libc;
+    &[
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_chroot,
+        libc::SYS_unshare,
+        libc::SYS_setns,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_userfaultfd,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_kexec_load,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_reboot,
+    ]
+};
+
+/// Apply the seccomp filter to every thread of the worker process.
+#[cfg(target_os = "linux")]
+pub fn seccomp_restrict_worker() -> anyhow::Result<()> {
+    use std::collections::BTreeMap;
+
+    use anyhow::Context;
+    use seccompiler::{BpfProgram, SeccompAction, SeccompFilter};
+
+    let rules = DENIED_SYSCALLS
+        .iter()
+        .map(|&syscall| (syscall, vec![]))
+        .collect::<BTreeMap<_, _>>();
+
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(nix::libc::EPERM as u32),
+        std::env::consts::ARCH
+            .try_into()
+            .context("Seccomp is not supported on this architecture")?,
+    )
+    .context("Failed to create seccomp filter")?;
+    let program: BpfProgram = filter
+        .try_into()
+        .context("Failed to compile seccomp filter")?;
+
+    // NOTE: the tokio runtime has already started its threads at this point.
+    seccompiler::apply_filter_all_threads(&program)
+        .context("Failed to apply seccomp filter to the worker process")?;
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn seccomp_restrict_worker() -> anyhow::Result<()> {
+    Ok(())
+}
@@ -1,8 +1,8 @@
-use std::{collections::BTreeSet, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use futures_util::{SinkExt, StreamExt};
 use indoc::concatdoc;
-use sqlx::{MySqlConnection, MySqlPool};
+use sqlx::{MySql, MySqlConnection, MySqlPool, pool::PoolConnection};
 use tokio::{net::UnixStream, sync::RwLock};
 use tracing::Instrument;
 
@@ -10,51 +10,161 @@ use crate::{
     core::{
         common::UnixUser,
         protocol::{
-            Request, Response, ServerToClientMessageStream, SetPasswordError,
-            create_server_to_client_message_stream, request_validation::GroupDenylist,
+            AdminListError, AdminLogLevelError, AdminReportError, HandshakeError, Request,
+            Response, SearchResponse, ServerInfoResponse, ServerToClientMessageStream,
+            SetPasswordError, create_server_to_client_message_stream,
+            request_validation::GroupDenylist, send_server_ready,
         },
     },
     server::{
-        authorization::check_authorization,
-        common::get_user_filtered_groups,
+        adopt::adopt,
+        authorization::{check_authorization, check_names},
+        backend_capabilities::BackendCapabilities,
+        bulk::BulkConnections,
+        common::{get_user_filtered_groups, new_request_id},
+        config::{
+            GrantOffersConfig, PrivilegeSnapshotsConfig, ScheduledPrivilegeChangesConfig,
+            ServerConfig,
+        },
+        grant_offers::{accept_grant, list_grant_offers, offer_grant},
+        log_level::handle_log_level_request,
+        policy::{PolicyDecision, PolicyEngine},
+        privilege_snapshots::{
+            get_privilege_snapshot, list_privilege_snapshots, snapshot_privileges,
+        },
+        progress::with_progress,
+        scheduled_privilege_changes::schedule_privilege_changes,
         sql::{
+            column_privilege_operations::{
+                apply_column_privilege_diffs, get_column_privilege_data,
+            },
+            connection_operations::{
+                kill_database_user_connections, show_database_user_connections,
+            },
             database_operations::{
                 complete_database_name, create_databases, drop_databases,
-                list_all_databases_for_user, list_databases,
+                list_all_databases_for_user, list_all_databases_with_privileges_for_user,
+                list_all_managed_databases, list_databases, list_databases_with_privileges,
+                transfer_database,
             },
             database_privilege_operations::{
-                apply_privilege_diffs, get_all_database_privileges, get_databases_privilege_data,
+                apply_privilege_diffs, delete_orphaned_privileges, get_all_database_privileges,
+                get_databases_privilege_data, list_orphaned_privileges, list_partial_revokes,
+                list_unused_databases,
             },
+            routine_privilege_operations::{
+                apply_routine_privilege_diffs, get_routine_privilege_data, list_routines,
+            },
+            stats_operations::{get_admin_report, get_prefix_stats},
             user_operations::{
-                complete_user_name, create_database_users, drop_database_users,
-                list_all_database_users_for_unix_user, list_database_users, lock_database_users,
-                set_password_for_database_user, unlock_database_users,
+                complete_user_name, count_database_user_connections, create_database_users,
+                drop_database_users, grant_role_to_database_users,
+                list_all_database_users_for_unix_user, list_all_managed_database_users,
+                list_database_users, lock_database_users, set_comment_for_database_user,
+                set_limits_for_database_user, set_password_for_database_user,
+                set_tls_requirement_for_database_user, unlock_database_users,
             },
         },
     },
 };
 
+const EXTENDED_PRIVILEGES_DISABLED_MESSAGE: &str = "The CREATE VIEW, SHOW VIEW, TRIGGER, EVENT, EXECUTE, CREATE ROUTINE, ALTER ROUTINE and GRANT OPTION privileges can not be granted on this server";
+
+/// Append the configured `error_footer` to an error message sent to the client.
+fn with_error_footer(message: &str, error_footer: Option<&str>) -> String {
+    match error_footer {
+        Some(footer) => format!("{}\n{footer}", message.trim_end()),
+        None => message.to_string(),
+    }
+}
+
+/// The configuration that sessions work with, taken from the [`ServerConfig`].
+///
+/// This is built when the configuration is (re)loaded, and shared between
+/// the sessions that are started with it.
+#[derive(Debug)]
+pub struct SessionContext {
+    pub group_denylist: GroupDenylist,
+    pub admin_groups: Vec<String>,
+    pub privilege_templates: BTreeMap<String, String>,
+    pub privilege_aliases: BTreeMap<String, String>,
+    pub extended_database_privileges: bool,
+    pub grant_offers: Option<GrantOffersConfig>,
+    pub privilege_snapshots: Option<PrivilegeSnapshotsConfig>,
+    pub scheduled_privilege_changes: Option<ScheduledPrivilegeChangesConfig>,
+    pub error_footer: Option<String>,
+    pub motd: Option<String>,
+    pub policy_engine: Option<PolicyEngine>,
+    pub handshake_timeout: Duration,
+    pub bulk_concurrency: usize,
+}
+
+impl SessionContext {
+    #[must_use]
+    pub fn new(
+        config: &ServerConfig,
+        group_denylist: GroupDenylist,
+        policy_engine: Option<PolicyEngine>,
+    ) -> Self {
+        Self {
+            group_denylist,
+            admin_groups: config.authorization.admin_groups.clone(),
+            privilege_templates: config.defaults.privileges.clone(),
+            privilege_aliases: config.privilege_aliases.clone(),
+            extended_database_privileges: config.extended_database_privileges,
+            grant_offers: config.grant_offers.clone(),
+            privilege_snapshots: config.privilege_snapshots.clone(),
+            scheduled_privilege_changes: config.scheduled_privilege_changes.clone(),
+            error_footer: config.error_footer.clone(),
+            motd: config.motd.clone(),
+            policy_engine,
+            handshake_timeout: Duration::from_secs(config.handshake_timeout),
+            bulk_concurrency: config.mysql.bulk_concurrency as usize,
+        }
+    }
+
+    /// Process bulk requests on the connection of the session only,
+    /// for sessions with a pool of a single connection.
+    #[must_use]
+    pub fn with_single_connection(mut self) -> Self {
+        self.bulk_concurrency = 1;
+        self
+    }
+}
+
 // TODO: don't use database connection unless necessary.
 
 pub async fn session_handler(
     socket: UnixStream,
     db_pool: Arc<RwLock<MySqlPool>>,
-    db_is_mariadb: bool,
-    group_denylist: &GroupDenylist,
+    db_replica_pool: Arc<RwLock<Option<MySqlPool>>>,
+    backend_capabilities: &BackendCapabilities,
+    context: Arc<SessionContext>,
+    maintenance_mode: bool,
 ) -> anyhow::Result<()> {
+    let error_footer = context.error_footer.as_deref();
+
     let uid = match socket.peer_cred() {
         Ok(cred) => cred.uid(),
         Err(e) => {
-            tracing::error!("Failed to get peer credentials from socket: {}", e);
+            let reference = new_request_id();
+            tracing::error!(
+                reference = %reference,
+                "Failed to get peer credentials from socket: {}",
+                e
+            );
             let mut message_stream = create_server_to_client_message_stream(socket);
             message_stream
-                .send(Response::Error(
-                    (concatdoc! {
-                        "Server failed to get peer credentials from socket\n",
-                        "Please check the server logs or contact the system administrators"
-                    })
-                    .to_string(),
-                ))
+                .send(Response::Error {
+                    message: with_error_footer(
+                        concatdoc! {
+                            "Server failed to get peer credentials from socket\n",
+                            "Please check the server logs or contact the system administrators"
+                        },
+                        error_footer,
+                    ),
+                    reference,
+                })
                 .await
                 .ok();
             anyhow::bail!("Failed to get peer credentials from socket");
@@ -66,22 +176,54 @@ pub async fn session_handler(
     let unix_user = match UnixUser::from_uid(uid) {
         Ok(user) => user,
         Err(e) => {
-            tracing::error!("Failed to get username from uid: {}", e);
+            let reference = new_request_id();
+            tracing::error!(
+                reference = %reference,
+                "Failed to get username from uid: {}",
+                e
+            );
             let mut message_stream = create_server_to_client_message_stream(socket);
             message_stream
-                .send(Response::Error(
-                    (concatdoc! {
-                        "Server failed to get user data from the system\n",
-                        "Please check the server logs or contact the system administrators"
-                    })
-                    .to_string(),
-                ))
+                .send(Response::Error {
+                    message: with_error_footer(
+                        concatdoc! {
+                            "Server failed to get user data from the system\n",
+                            "Please check the server logs or contact the system administrators"
+                        },
+                        error_footer,
+                    ),
+                    reference,
+                })
                 .await
                 .ok();
             anyhow::bail!("Failed to get username from uid: {e}");
         }
     };
 
+    if maintenance_mode
+        && !unix_user
+            .groups
+            .iter()
+            .any(|group| context.admin_groups.contains(group))
+    {
+        tracing::info!("Refusing connection from {} in maintenance mode", unix_user);
+        let mut message_stream = create_server_to_client_message_stream(socket);
+        message_stream
+            .send(Response::Error {
+                message: with_error_footer(
+                    concatdoc! {
+                        "The server is currently down for maintenance\n",
+                        "Please try again in a little while"
+                    },
+                    error_footer,
+                ),
+                reference: new_request_id(),
+            })
+            .await
+            .ok();
+        return Ok(());
+    }
+
     let span = tracing::info_span!("user_session", user = %unix_user);
 
     (async move {
@@ -91,8 +233,9 @@ pub async fn session_handler(
             socket,
             &unix_user,
             db_pool,
-            db_is_mariadb,
-            group_denylist,
+            db_replica_pool,
+            backend_capabilities,
+            &context,
         )
         .await;
 
@@ -111,8 +254,9 @@ pub async fn session_handler_with_unix_user(
     socket: UnixStream,
     unix_user: &UnixUser,
     db_pool: Arc<RwLock<MySqlPool>>,
-    db_is_mariadb: bool,
-    group_denylist: &GroupDenylist,
+    db_replica_pool: Arc<RwLock<Option<MySqlPool>>>,
+    backend_capabilities: &BackendCapabilities,
+    context: &SessionContext,
 ) -> anyhow::Result<()> {
     let mut message_stream = create_server_to_client_message_stream(socket);
 
@@ -120,15 +264,8 @@ pub async fn session_handler_with_unix_user(
     let mut db_connection = match db_pool.read().await.acquire().await {
         Ok(connection) => connection,
         Err(err) => {
-            message_stream
-                .send(Response::Error(
-                    (concatdoc! {
-                        "Server failed to connect to database\n",
-                        "Please check the server logs or contact the system administrators"
-                    })
-                    .to_string(),
-                ))
-                .await?;
+            tracing::error!("Failed to acquire database connection from pool: {}", err);
+            message_stream.send(Response::DatabaseUnavailable).await?;
             message_stream.flush().await?;
             return Err(err.into());
         }
@@ -139,8 +276,10 @@ pub async fn session_handler_with_unix_user(
         message_stream,
         unix_user,
         &mut db_connection,
-        db_is_mariadb,
-        group_denylist,
+        &db_pool,
+        &db_replica_pool,
+        backend_capabilities,
+        context,
     )
     .await;
 
@@ -156,258 +295,792 @@ async fn session_handler_with_db_connection(
     mut stream: ServerToClientMessageStream,
     unix_user: &UnixUser,
     db_connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
-    group_denylist: &GroupDenylist,
+    db_pool: &RwLock<MySqlPool>,
+    db_replica_pool: &RwLock<Option<MySqlPool>>,
+    backend_capabilities: &BackendCapabilities,
+    context: &SessionContext,
 ) -> anyhow::Result<()> {
-    stream.send(Response::Ready).await?;
+    let group_denylist = &context.group_denylist;
+    let error_footer = context.error_footer.as_deref();
+    let handshake_timeout = context.handshake_timeout;
+
+    let is_admin = unix_user
+        .groups
+        .iter()
+        .any(|group| context.admin_groups.contains(group));
+
+    send_server_ready(&mut stream, handshake_timeout, context.motd.as_deref()).await?;
+
+    // NOTE: the replica connection is only acquired once the first read-only request comes in.
+    let mut replica_connection: Option<PoolConnection<MySql>> = None;
+    let mut replica_unavailable = false;
+
+    // NOTE: once the session has sent a request that might change something, all of its
+    //       reads go to the primary, so that the client never sees a replica lagging
+    //       behind its own changes, nor plans a later change from stale data.
+    let mut has_written = false;
+
+    // NOTE: clients send their first request right after receiving the ready message,
+    //       so a client that stays silent is most likely stuck, or never going to talk.
+    let mut first_request = true;
     loop {
         // TODO: better error handling
-        // TODO: timeout for receiving requests
+        // TODO: timeout for receiving subsequent requests
         // TODO: cancel on request by supervisor
-        let request = match stream.next().await {
+        let next_request = if first_request {
+            first_request = false;
+            match tokio::time::timeout(handshake_timeout, stream.next()).await {
+                Ok(next_request) => next_request,
+                Err(_) => {
+                    tracing::warn!(
+                        "Client did not send a request within {}s, closing connection",
+                        handshake_timeout.as_secs()
+                    );
+                    return Err(HandshakeError::FirstRequestTimeout(handshake_timeout).into());
+                }
+            }
+        } else {
+            stream.next().await
+        };
+
+        let request = match next_request {
             Some(Ok(request)) => request,
-            Some(Err(e)) => return Err(e.into()),
+            Some(Err(e)) => {
+                let reference = new_request_id();
+                tracing::error!(
+                    reference = %reference,
+                    "Failed to read request from client: {}",
+                    e
+                );
+                stream
+                    .send(Response::Error {
+                        message: with_error_footer(
+                            "Server failed to read the request",
+                            error_footer,
+                        ),
+                        reference,
+                    })
+                    .await
+                    .ok();
+                return Err(e.into());
+            }
             None => {
                 tracing::warn!("Client disconnected without sending an exit message");
                 break;
             }
         };
 
-        match &request {
-            Request::Exit => tracing::debug!("Received request: {:#?}", request),
-            Request::PasswdUser((db_user, _)) => tracing::info!(
-                "Received request: {:#?}",
-                Request::PasswdUser((db_user.to_owned(), "<REDACTED>".to_string()))
-            ),
-            request => tracing::info!("Received request: {:#?}", request),
-        }
+        let request_id = new_request_id();
+        let span = tracing::info_span!("request", id = %request_id);
+
+        let response = async {
+            match &request {
+                Request::Exit => tracing::debug!("Received request: {:#?}", request),
+                request => tracing::info!("Received request: {:#?}", request),
+            }
+
+            if !request.is_read_only() {
+                has_written = true;
+                replica_connection = None;
+            }
 
-        let response = match request {
-            Request::CheckAuthorization(dbs_or_users) => {
-                let result = check_authorization(dbs_or_users, unix_user, group_denylist).await;
-                Response::CheckAuthorization(result)
+            if request.is_read_only()
+                && !has_written
+                && replica_connection.is_none()
+                && !replica_unavailable
+                && let Some(replica_pool) = &*db_replica_pool.read().await
+            {
+                match replica_pool.acquire().await {
+                    Ok(connection) => replica_connection = Some(connection),
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to acquire connection to the read-only replica, using the primary database instead: {}",
+                            err
+                        );
+                        replica_unavailable = true;
+                    }
+                }
             }
-            Request::ListValidNamePrefixes => {
-                let mut result = Vec::with_capacity(unix_user.groups.len() + 1);
-                result.push(unix_user.username.clone());
 
-                for group in get_user_filtered_groups(unix_user, group_denylist) {
-                    result.push(group.clone());
+            let request = match &context.policy_engine {
+                Some(policy_engine) if request.is_mutating() => {
+                    match policy_engine.check(request, unix_user).await {
+                        PolicyDecision::Allow(request) => request,
+                        PolicyDecision::Deny(message) => {
+                            return Some(Response::Error {
+                                message: with_error_footer(&message, error_footer),
+                                reference: request_id.clone(),
+                            });
+                        }
+                    }
                 }
+                _ => request,
+            };
 
-                Response::ListValidNamePrefixes(result)
+            if !context.extended_database_privileges && request.grants_extended_database_privileges() {
+                return Some(Response::Error {
+                    message: with_error_footer(EXTENDED_PRIVILEGES_DISABLED_MESSAGE, error_footer),
+                    reference: request_id.clone(),
+                });
             }
-            Request::CompleteDatabaseName(partial_database_name) => {
-                // TODO: more correct validation here
-                if partial_database_name
-                    .chars()
-                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
-                {
-                    let result = complete_database_name(
-                        partial_database_name,
+
+            let read_connection: &mut MySqlConnection = match &mut replica_connection {
+                Some(connection) => connection,
+                None => &mut *db_connection,
+            };
+
+            Some(match request {
+                Request::CheckAuthorization(dbs_or_users) => {
+                    let result = check_authorization(dbs_or_users, unix_user, group_denylist).await;
+                    Response::CheckAuthorization(result)
+                }
+                Request::CheckNames(dbs_or_users) => {
+                    let result =
+                        check_names(dbs_or_users, unix_user, db_connection, group_denylist).await;
+                    Response::CheckNames(result)
+                }
+                Request::ListValidNamePrefixes => {
+                    let mut result = Vec::with_capacity(unix_user.groups.len() + 1);
+                    result.push(unix_user.username.clone());
+
+                    for group in get_user_filtered_groups(unix_user, group_denylist) {
+                        result.push(group.clone());
+                    }
+
+                    Response::ListValidNamePrefixes(result)
+                }
+                Request::ListPrivilegeTemplates => {
+                    let groups = get_user_filtered_groups(unix_user, group_denylist);
+                    let result = context
+                        .privilege_templates
+                        .iter()
+                        .filter(|(prefix, _)| **prefix == unix_user.username || groups.contains(prefix))
+                        .map(|(prefix, template)| (prefix.clone(), template.clone()))
+                        .collect();
+
+                    Response::ListPrivilegeTemplates(result)
+                }
+                Request::ListPrivilegeAliases => {
+                    Response::ListPrivilegeAliases(context.privilege_aliases.clone())
+                }
+                Request::CompleteDatabaseName(partial_database_name) => {
+                    // TODO: more correct validation here
+                    if partial_database_name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        let result = complete_database_name(
+                            partial_database_name,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::CompleteDatabaseName(result)
+                    } else {
+                        Response::CompleteDatabaseName(vec![])
+                    }
+                }
+                Request::CompleteUserName(partial_user_name) => {
+                    // TODO: more correct validation here
+                    if partial_user_name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        let result = complete_user_name(
+                            partial_user_name,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::CompleteUserName(result)
+                    } else {
+                        Response::CompleteUserName(vec![])
+                    }
+                }
+                Request::CreateDatabases(databases_names) => {
+                    let mut connections = BulkConnections::acquire(
+                        db_connection,
+                        &*db_pool.read().await,
+                        context.bulk_concurrency,
+                        databases_names.len(),
+                    )
+                    .await;
+                    let result = with_progress(&mut stream, databases_names.len(), |progress| {
+                        create_databases(
+                            databases_names,
+                            unix_user,
+                            &mut connections,
+                            backend_capabilities,
+                            group_denylist,
+                            progress,
+                        )
+                    })
+                    .await;
+                    Response::CreateDatabases(result)
+                }
+                Request::DropDatabases(databases_names) => {
+                    let result = drop_databases(
+                        databases_names,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::CompleteDatabaseName(result)
-                } else {
-                    Response::CompleteDatabaseName(vec![])
+                    Response::DropDatabases(result)
                 }
-            }
-            Request::CompleteUserName(partial_user_name) => {
-                // TODO: more correct validation here
-                if partial_user_name
-                    .chars()
-                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
-                {
-                    let result = complete_user_name(
-                        partial_user_name,
+                Request::Adopt(request) => {
+                    let result = adopt(
+                        request,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
+                        context.extended_database_privileges,
                     )
                     .await;
-                    Response::CompleteUserName(result)
-                } else {
-                    Response::CompleteUserName(vec![])
+                    Response::Adopt(result)
                 }
-            }
-            Request::CreateDatabases(databases_names) => {
-                let result = create_databases(
-                    databases_names,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::CreateDatabases(result)
-            }
-            Request::DropDatabases(databases_names) => {
-                let result = drop_databases(
-                    databases_names,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::DropDatabases(result)
-            }
-            Request::ListDatabases(database_names) => {
-                if let Some(database_names) = database_names {
-                    let result = list_databases(
-                        database_names,
+                Request::TransferDatabase(request) => {
+                    let result = transfer_database(
+                        request,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::TransferDatabase(result)
+                }
+                Request::ListDatabases(database_names) => {
+                    if let Some(database_names) = database_names {
+                        let result = list_databases(
+                            database_names,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListDatabases(result)
+                    } else {
+                        let result = list_all_databases_for_user(
+                            None,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListAllDatabases(result)
+                    }
+                }
+                Request::ListDatabasesWithPrivileges(database_names) => {
+                    if let Some(database_names) = database_names {
+                        let result = list_databases_with_privileges(
+                            database_names,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListDatabasesWithPrivileges(result)
+                    } else {
+                        let result = list_all_databases_with_privileges_for_user(
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListAllDatabasesWithPrivileges(result)
+                    }
+                }
+                Request::ListUnusedDatabases => {
+                    let result = list_unused_databases(
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListUnusedDatabases(result)
+                }
+                Request::ListPrivileges(request) => {
+                    if let Some(database_names) = request.databases {
+                        let privilege_data = get_databases_privilege_data(
+                            database_names,
+                            request.user.as_ref(),
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListPrivileges(privilege_data)
+                    } else {
+                        let privilege_data = get_all_database_privileges(
+                            request.user.as_ref(),
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListAllPrivileges(privilege_data)
+                    }
+                }
+                Request::ModifyPrivileges(request) => {
+                    let mut connections = if request.atomic {
+                        BulkConnections::single(db_connection)
+                    } else {
+                        BulkConnections::acquire(
+                            db_connection,
+                            &*db_pool.read().await,
+                            context.bulk_concurrency,
+                            request.diffs.len(),
+                        )
+                        .await
+                    };
+                    let result = with_progress(&mut stream, request.diffs.len(), |progress| {
+                        apply_privilege_diffs(
+                            request.diffs,
+                            request.atomic,
+                            unix_user,
+                            &mut connections,
+                            backend_capabilities,
+                            group_denylist,
+                            progress,
+                        )
+                    })
+                    .await;
+                    Response::ModifyPrivileges(result)
+                }
+                Request::SchedulePrivilegeChanges(request) => {
+                    let result = schedule_privilege_changes(
+                        request,
+                        context.scheduled_privilege_changes.as_ref(),
+                        unix_user,
                         group_denylist,
                     )
                     .await;
-                    Response::ListDatabases(result)
-                } else {
-                    let result = list_all_databases_for_user(
+                    Response::SchedulePrivilegeChanges(result)
+                }
+                Request::ListOrphanedPrivileges => {
+                    let result = list_orphaned_privileges(
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListOrphanedPrivileges(result)
+                }
+                Request::DeleteOrphanedPrivileges(rows) => {
+                    let result = delete_orphaned_privileges(
+                        rows,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::ListAllDatabases(result)
+                    Response::DeleteOrphanedPrivileges(result)
                 }
-            }
-            Request::ListPrivileges(database_names) => {
-                if let Some(database_names) = database_names {
-                    let privilege_data = get_databases_privilege_data(
-                        database_names,
+                Request::OfferGrant(row) => {
+                    let result = offer_grant(
+                        row,
+                        context.grant_offers.as_ref(),
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::ListPrivileges(privilege_data)
-                } else {
-                    let privilege_data = get_all_database_privileges(
+                    Response::OfferGrant(result)
+                }
+                Request::ListGrantOffers => {
+                    let result =
+                        list_grant_offers(context.grant_offers.as_ref(), unix_user, group_denylist).await;
+                    Response::ListGrantOffers(result)
+                }
+                Request::AcceptGrant(id) => {
+                    let result = accept_grant(
+                        id,
+                        context.grant_offers.as_ref(),
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::ListAllPrivileges(privilege_data)
+                    Response::AcceptGrant(result)
                 }
-            }
-            Request::ModifyPrivileges(database_privilege_diffs) => {
-                let result = apply_privilege_diffs(
-                    BTreeSet::from_iter(database_privilege_diffs),
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::ModifyPrivileges(result)
-            }
-            Request::CreateUsers(db_users) => {
-                let result = create_database_users(
-                    db_users,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::CreateUsers(result)
-            }
-            Request::DropUsers(db_users) => {
-                let result = drop_database_users(
-                    db_users,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::DropUsers(result)
-            }
-            Request::PasswdUser((db_user, password)) => {
-                let result = set_password_for_database_user(
-                    &db_user,
-                    &password,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::SetUserPassword(result)
-            }
-            Request::ListUsers(db_users) => {
-                if let Some(db_users) = db_users {
-                    let result = list_database_users(
+                Request::SnapshotPrivileges(tag) => {
+                    let result = snapshot_privileges(
+                        tag,
+                        context.privilege_snapshots.as_ref(),
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::SnapshotPrivileges(result)
+                }
+                Request::ListPrivilegeSnapshots => {
+                    let result =
+                        list_privilege_snapshots(context.privilege_snapshots.as_ref(), unix_user).await;
+                    Response::ListPrivilegeSnapshots(result)
+                }
+                Request::GetPrivilegeSnapshot(tag) => {
+                    let result =
+                        get_privilege_snapshot(tag, context.privilege_snapshots.as_ref(), unix_user).await;
+                    Response::GetPrivilegeSnapshot(result)
+                }
+                Request::ListColumnPrivileges(request) => {
+                    let result = get_column_privilege_data(
+                        request.databases,
+                        request.user.as_ref(),
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListColumnPrivileges(result)
+                }
+                Request::ModifyColumnPrivileges(request) => {
+                    let result = apply_column_privilege_diffs(
+                        request.diffs,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ModifyColumnPrivileges(result)
+                }
+                Request::ListRoutines(databases) => {
+                    let result = list_routines(
+                        databases,
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListRoutines(result)
+                }
+                Request::ListRoutinePrivileges(request) => {
+                    let result = get_routine_privilege_data(
+                        request.databases,
+                        request.user.as_ref(),
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListRoutinePrivileges(result)
+                }
+                Request::ModifyRoutinePrivileges(request) => {
+                    let result = apply_routine_privilege_diffs(
+                        request.diffs,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ModifyRoutinePrivileges(result)
+                }
+                Request::CreateUsers(request) => {
+                    let result = create_database_users(
+                        request,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::CreateUsers(result)
+                }
+                Request::DropUsers(db_users) => {
+                    let result = drop_database_users(
                         db_users,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::ListUsers(result)
-                } else {
-                    let result = list_all_database_users_for_unix_user(
+                    Response::DropUsers(result)
+                }
+                Request::PasswdUser((db_user, password)) => {
+                    let result = set_password_for_database_user(
+                        &db_user,
+                        &password,
                         unix_user,
                         db_connection,
-                        db_is_mariadb,
+                        backend_capabilities,
                         group_denylist,
                     )
                     .await;
-                    Response::ListAllUsers(result)
+                    Response::SetUserPassword(result)
                 }
-            }
-            Request::LockUsers(db_users) => {
-                let result = lock_database_users(
-                    db_users,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::LockUsers(result)
-            }
-            Request::UnlockUsers(db_users) => {
-                let result = unlock_database_users(
-                    db_users,
-                    unix_user,
-                    db_connection,
-                    db_is_mariadb,
-                    group_denylist,
-                )
-                .await;
-                Response::UnlockUsers(result)
-            }
-            Request::Exit => {
-                break;
-            }
-        };
+                Request::SetUserComment(request) => {
+                    let result = set_comment_for_database_user(
+                        &request.user,
+                        &request.comment,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::SetUserComment(result)
+                }
+                Request::RequireSsl(request) => {
+                    let result = set_tls_requirement_for_database_user(
+                        &request.user,
+                        request.requirement,
+                        unix_user,
+                        db_connection,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::RequireSsl(result)
+                }
+                Request::SetUserLimits(request) => {
+                    let result =
+                        set_limits_for_database_user(request, unix_user, db_connection, group_denylist)
+                            .await;
+                    Response::SetUserLimits(result)
+                }
+                Request::ListUsers(db_users) => {
+                    if let Some(db_users) = db_users {
+                        let result = list_database_users(
+                            db_users,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListUsers(result)
+                    } else {
+                        let result = list_all_database_users_for_unix_user(
+                            None,
+                            unix_user,
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::ListAllUsers(result)
+                    }
+                }
+                Request::LockUsers(request) => {
+                    let result = lock_database_users(
+                        request,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
 
-        let response_to_display = match &response {
-            Response::SetUserPassword(Err(SetPasswordError::MySqlError(_))) => {
-                &Response::SetUserPassword(Err(SetPasswordError::MySqlError(
-                    "<REDACTED>".to_string(),
-                )))
-            }
-            response => response,
+                    // NOTE: locking a user does not close the connections it already has.
+                    for username in result
+                        .iter()
+                        .filter_map(|(username, result)| result.as_ref().ok().map(|()| username))
+                    {
+                        if let Ok(count @ 1..) =
+                            count_database_user_connections(username, db_connection).await
+                        {
+                            stream
+                                .send(Response::Warning(format!(
+                                    "User '{username}' still has {count} open connection(s), which are not closed by locking it. Use `muscl kill-connections` to close them."
+                                )))
+                                .await
+                                .ok();
+                        }
+                    }
+
+                    Response::LockUsers(result)
+                }
+                Request::UnlockUsers(request) => {
+                    let result = unlock_database_users(
+                        request,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::UnlockUsers(result)
+                }
+                Request::KillConnections(db_users) => {
+                    let result = kill_database_user_connections(
+                        db_users,
+                        unix_user,
+                        db_connection,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::KillConnections(result)
+                }
+                Request::ShowConnections(db_users) => {
+                    let result = show_database_user_connections(
+                        db_users,
+                        unix_user,
+                        db_connection,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ShowConnections(result)
+                }
+                Request::GrantRoles((role, db_users)) => {
+                    let result = grant_role_to_database_users(
+                        role,
+                        db_users,
+                        unix_user,
+                        db_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::GrantRoles(result)
+                }
+                Request::ServerInfo => Response::ServerInfo(ServerInfoResponse::for_current_server(
+                    backend_capabilities.flavor.to_string(),
+                    backend_capabilities.version_string.clone(),
+                    error_footer,
+                    context.extended_database_privileges,
+                )),
+                Request::Stats => {
+                    let result = get_prefix_stats(
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::Stats(result)
+                }
+                Request::Search(request) => {
+                    let name_pattern = request.to_mysql_regex();
+                    let databases = list_all_databases_for_user(
+                        Some(&name_pattern),
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    let users = list_all_database_users_for_unix_user(
+                        Some(&name_pattern),
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::Search(SearchResponse { databases, users })
+                }
+                Request::AdminReport => {
+                    if is_admin {
+                        let result =
+                            get_admin_report(read_connection, backend_capabilities, group_denylist)
+                                .await;
+                        Response::AdminReport(result)
+                    } else {
+                        tracing::warn!("Non-admin user requested an admin report");
+                        Response::AdminReport(Err(AdminReportError::NotAuthorized))
+                    }
+                }
+                Request::AdminListDatabases => {
+                    if is_admin {
+                        let result = list_all_managed_databases(
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::AdminListDatabases(result)
+                    } else {
+                        tracing::warn!("Non-admin user requested a list of all databases");
+                        Response::AdminListDatabases(Err(AdminListError::NotAuthorized))
+                    }
+                }
+                Request::AdminListUsers => {
+                    if is_admin {
+                        let result = list_all_managed_database_users(
+                            read_connection,
+                            backend_capabilities,
+                            group_denylist,
+                        )
+                        .await;
+                        Response::AdminListUsers(result)
+                    } else {
+                        tracing::warn!("Non-admin user requested a list of all users");
+                        Response::AdminListUsers(Err(AdminListError::NotAuthorized))
+                    }
+                }
+                Request::AdminLogLevel(level) => {
+                    if is_admin {
+                        let result = handle_log_level_request(level.as_deref());
+                        Response::AdminLogLevel(result)
+                    } else {
+                        tracing::warn!("Non-admin user requested to change the log level");
+                        Response::AdminLogLevel(Err(AdminLogLevelError::NotAuthorized))
+                    }
+                }
+                Request::ListPartialRevokes(db_users) => {
+                    let result = list_partial_revokes(
+                        db_users,
+                        unix_user,
+                        read_connection,
+                        backend_capabilities,
+                        group_denylist,
+                    )
+                    .await;
+                    Response::ListPartialRevokes(result)
+                }
+                Request::Exit => return None,
+            })
+        }
+        .instrument(span.clone())
+        .await;
+
+        let Some(response) = response else {
+            break;
         };
-        tracing::debug!("Response: {:#?}", response_to_display);
 
-        stream.send(response).await?;
-        stream.flush().await?;
-        tracing::debug!("Successfully processed request");
+        async {
+            let response_to_display = match &response {
+                Response::SetUserPassword(Err(SetPasswordError::MySqlError(_))) => {
+                    &Response::SetUserPassword(Err(SetPasswordError::MySqlError(
+                        "<REDACTED>".to_string(),
+                    )))
+                }
+                response => response,
+            };
+            tracing::debug!("Response: {:#?}", response_to_display);
+
+            stream.send(response).await?;
+            stream.flush().await?;
+            tracing::debug!("Successfully processed request");
+            anyhow::Ok(())
+        }
+        .instrument(span)
+        .await?;
     }
 
     Ok(())
@@ -1,17 +1,69 @@
+pub mod column_privilege_operations;
+pub mod connection_operations;
 pub mod database_operations;
 pub mod database_privilege_operations;
+pub mod routine_privilege_operations;
+pub mod stats_operations;
 pub mod user_operations;
 
+use sqlx::{MySql, MySqlConnection, Transaction, mysql::MySqlArguments, query::Query};
+use zeroize::Zeroizing;
+
+use crate::core::types::SecretString;
+
+/// Quote a string literal, escaping backslashes and single quotes.
 #[inline]
 #[must_use]
 pub fn quote_literal(s: &str) -> String {
-    format!("'{}'", s.replace('\'', r"\'"))
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// Like [`quote_literal`], but for secrets like passwords, which are quoted into
+/// `buffer` without leaving any copies of them behind.
+///
+/// `buffer` needs room for twice the length of the secret plus the quotes,
+/// as it would leave a copy behind if it had to grow.
+pub fn push_quoted_secret_literal(buffer: &mut Zeroizing<String>, secret: &SecretString) {
+    debug_assert!(buffer.capacity() - buffer.len() >= 2 * secret.len() + 2);
+
+    buffer.push('\'');
+    for c in secret.expose_secret().chars() {
+        if c == '\'' || c == '\\' {
+            buffer.push('\\');
+        }
+        buffer.push(c);
+    }
+    buffer.push('\'');
 }
 
+/// Quote an identifier like a database, table or column name.
+///
+/// A backslash is not an escape character inside identifiers, so a backtick
+/// has to be doubled to keep it from ending the identifier.
 #[inline]
 #[must_use]
 pub fn quote_identifier(s: &str) -> String {
-    format!("`{}`", s.replace('`', r"\`"))
+    format!("`{}`", s.replace('`', "``"))
+}
+
+/// Create a query for a statement with quoted identifiers or literals baked into it.
+///
+/// Statements with constant text are kept in the prepared statement cache of the
+/// connection, and are reused on every call. Statements like `CREATE USER 'alice_foo'@'%'`
+/// are different every time, and would only push the reusable statements out of the cache.
+#[inline]
+pub fn uncached_query(sql: &str) -> Query<'_, MySql, MySqlArguments> {
+    sqlx::query(sql).persistent(false)
+}
+
+/// Start a transaction on the connection.
+///
+/// `MySqlConnection` implements both [`sqlx::Connection`] and [`sqlx::Acquire`], which both
+/// have a `begin` method, so calling `connection.begin()` with the sqlx prelude in scope is ambiguous.
+pub async fn begin_transaction(
+    connection: &mut MySqlConnection,
+) -> Result<Transaction<'_, MySql>, sqlx::Error> {
+    sqlx::Connection::begin(connection).await
 }
 
 #[cfg(test)]
@@ -21,11 +73,25 @@ mod tests {
     fn test_quote_literal() {
         let payload = "' OR 1=1 --";
         assert_eq!(quote_literal(payload), r#"'\' OR 1=1 --'"#);
+
+        let payload = r"a\', root@localhost IDENTIFIED BY 'pwned' -- ";
+        assert_eq!(
+            quote_literal(payload),
+            r"'a\\\', root@localhost IDENTIFIED BY \'pwned\' -- '"
+        );
+    }
+
+    #[test]
+    fn test_push_quoted_secret_literal() {
+        let secret = SecretString::from(r"a\', root@localhost IDENTIFIED BY 'pwned' -- ");
+        let mut buffer = Zeroizing::new(String::with_capacity(2 * secret.len() + 2));
+        push_quoted_secret_literal(&mut buffer, &secret);
+        assert_eq!(buffer.as_str(), quote_literal(secret.expose_secret()));
     }
 
     #[test]
     fn test_quote_identifier() {
         let payload = "` OR 1=1 --";
-        assert_eq!(quote_identifier(payload), r#"`\` OR 1=1 --`"#);
+        assert_eq!(quote_identifier(payload), "``` OR 1=1 --`");
     }
 }
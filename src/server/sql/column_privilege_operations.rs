@@ -0,0 +1,279 @@
+//! Column privilege operations
+//!
+//! This module contains functions for listing and modifying the privileges
+//! that users have on single columns of the tables in a database.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use indoc::indoc;
+use itertools::Itertools;
+use sqlx::{MySqlConnection, mysql::MySqlRow, prelude::*};
+
+use crate::{
+    core::{
+        column_privileges::{ColumnPrivilegeKey, ColumnPrivilegeRow, ColumnPrivilegesDiff},
+        common::UnixUser,
+        database_privileges::DatabasePrivilegeChange,
+        protocol::{
+            ListColumnPrivilegesResponse, ListPrivilegesError, ModifyColumnPrivilegesError,
+            ModifyColumnPrivilegesResponse,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        common::{create_user_group_matching_regex, try_get_with_binary_fallback},
+        sql::{
+            database_operations::unsafe_database_exists, uncached_query,
+            user_operations::unsafe_user_exists,
+        },
+    },
+};
+
+impl FromRow<'_, MySqlRow> for ColumnPrivilegeRow {
+    fn from_row(row: &MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(ColumnPrivilegeRow::new(
+            try_get_with_binary_fallback(row, "Db")?.into(),
+            try_get_with_binary_fallback(row, "Table_name")?,
+            try_get_with_binary_fallback(row, "Column_name")?,
+            try_get_with_binary_fallback(row, "User")?.into(),
+        )
+        .with_privileges_from_mysql_set(&try_get_with_binary_fallback(row, "Column_priv")?))
+    }
+}
+
+// NOTE: only the rows for `'%'` are listed, as those are the ones that
+//       the `GRANT` and `REVOKE` statements below change.
+const SELECT_COLUMN_PRIVILEGES_FOR_DATABASE_QUERY: &str = indoc! {r"
+    SELECT `Db`, `Table_name`, `Column_name`, `User`, CAST(`Column_priv` AS CHAR) AS `Column_priv`
+    FROM `columns_priv`
+    WHERE `Db` = ? AND `Host` = '%'
+"};
+
+const SELECT_ALL_COLUMN_PRIVILEGES_QUERY: &str = indoc! {r"
+    SELECT `Db`, `Table_name`, `Column_name`, `User`, CAST(`Column_priv` AS CHAR) AS `Column_priv`
+    FROM `columns_priv`
+    WHERE `Db` REGEXP ? AND `Host` = '%'
+"};
+
+const SELECT_COLUMN_PRIVILEGE_ROW_QUERY: &str = indoc! {r"
+    SELECT `Db`, `Table_name`, `Column_name`, `User`, CAST(`Column_priv` AS CHAR) AS `Column_priv`
+    FROM `columns_priv`
+    WHERE `Db` = ? AND `Table_name` = ? AND `Column_name` = ? AND `User` = ? AND `Host` = '%'
+"};
+
+const COLUMN_EXISTS_QUERY: &str = indoc! {r"
+    SELECT EXISTS(
+      SELECT 1 FROM `information_schema`.`COLUMNS`
+      WHERE `TABLE_SCHEMA` = ? AND `TABLE_NAME` = ? AND `COLUMN_NAME` = ?
+    )
+"};
+
+fn filter_column_privilege_rows_by_user(
+    rows: Vec<ColumnPrivilegeRow>,
+    user_filter: Option<&MySQLUser>,
+) -> Vec<ColumnPrivilegeRow> {
+    match user_filter {
+        Some(user) => rows.into_iter().filter(|row| &row.user == user).collect(),
+        None => rows,
+    }
+}
+
+/// Get the column privileges on the given databases, or on all databases
+/// owned by the unix user, optionally only for a single user.
+pub async fn get_column_privilege_data(
+    database_names: Option<Vec<MySQLDatabase>>,
+    user_filter: Option<&MySQLUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListColumnPrivilegesResponse {
+    let Some(database_names) = database_names else {
+        let result = sqlx::query_as::<_, ColumnPrivilegeRow>(SELECT_ALL_COLUMN_PRIVILEGES_QUERY)
+            .bind(create_user_group_matching_regex(unix_user, group_denylist))
+            .fetch_all(connection)
+            .await;
+
+        return match result {
+            Ok(rows) => filter_column_privilege_rows_by_user(rows, user_filter)
+                .into_iter()
+                .into_group_map_by(|row| row.db.clone())
+                .into_iter()
+                .map(|(db, rows)| (db, Ok(rows)))
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to get all column privileges: {:?}", e);
+                BTreeMap::new()
+            }
+        };
+    };
+
+    let mut results = BTreeMap::new();
+
+    for database_name in database_names {
+        if let Err(err) = validate_db_or_user_request(
+            &DbOrUser::Database(database_name.clone()),
+            unix_user,
+            group_denylist,
+        )
+        .map_err(ListPrivilegesError::ValidationError)
+        {
+            results.insert(database_name, Err(err));
+            continue;
+        }
+
+        match unsafe_database_exists(&database_name, connection).await {
+            Ok(false) => {
+                results.insert(
+                    database_name,
+                    Err(ListPrivilegesError::DatabaseDoesNotExist),
+                );
+                continue;
+            }
+            Err(e) => {
+                results.insert(
+                    database_name,
+                    Err(ListPrivilegesError::MySqlError(e.to_string())),
+                );
+                continue;
+            }
+            Ok(true) => {}
+        }
+
+        let result =
+            sqlx::query_as::<_, ColumnPrivilegeRow>(SELECT_COLUMN_PRIVILEGES_FOR_DATABASE_QUERY)
+                .bind(database_name.as_str())
+                .fetch_all(&mut *connection)
+                .await
+                .map(|rows| filter_column_privilege_rows_by_user(rows, user_filter))
+                .map_err(|e| ListPrivilegesError::MySqlError(e.to_string()));
+
+        if let Err(e) = &result {
+            tracing::error!(
+                "Failed to get column privileges for '{}': {:?}",
+                &database_name,
+                e
+            );
+        }
+
+        results.insert(database_name, result);
+    }
+
+    results
+}
+
+/// Checks that the unix user is allowed to apply the diff, and that it applies
+/// to the current privileges on the column.
+async fn validate_column_privilege_diff_request(
+    diff: &ColumnPrivilegesDiff,
+    key: &ColumnPrivilegeKey,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyColumnPrivilegesError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(key.db.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(ModifyColumnPrivilegesError::DatabaseValidationError)?;
+
+    validate_db_or_user_request(&DbOrUser::User(key.user.clone()), unix_user, group_denylist)
+        .map_err(ModifyColumnPrivilegesError::UserValidationError)?;
+
+    match unsafe_database_exists(&key.db, connection).await {
+        Ok(false) => return Err(ModifyColumnPrivilegesError::DatabaseDoesNotExist),
+        Err(e) => return Err(ModifyColumnPrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    match unsafe_user_exists(&key.user, connection).await {
+        Ok(false) => return Err(ModifyColumnPrivilegesError::UserDoesNotExist),
+        Err(e) => return Err(ModifyColumnPrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    // NOTE: privileges on a column that has been dropped can still be revoked.
+    if diff
+        .changes()
+        .iter()
+        .any(|(_, change)| *change == DatabasePrivilegeChange::NoToYes)
+    {
+        let column_exists = sqlx::query_scalar::<_, bool>(COLUMN_EXISTS_QUERY)
+            .bind(key.db.as_str())
+            .bind(key.table.as_str())
+            .bind(key.column.as_str())
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|e| ModifyColumnPrivilegesError::MySqlError(e.to_string()))?;
+        if !column_exists {
+            return Err(ModifyColumnPrivilegesError::ColumnDoesNotExist);
+        }
+    }
+
+    let existing_row = sqlx::query_as::<_, ColumnPrivilegeRow>(SELECT_COLUMN_PRIVILEGE_ROW_QUERY)
+        .bind(key.db.as_str())
+        .bind(key.table.as_str())
+        .bind(key.column.as_str())
+        .bind(key.user.as_str())
+        .fetch_optional(&mut *connection)
+        .await
+        .map_err(|e| ModifyColumnPrivilegesError::MySqlError(e.to_string()))?;
+
+    if diff.applies_to(existing_row.as_ref()) {
+        Ok(())
+    } else {
+        Err(ModifyColumnPrivilegesError::DiffDoesNotApply)
+    }
+}
+
+/// Validates and applies a single diff, with `GRANT` and `REVOKE` statements.
+async fn apply_column_privilege_diff(
+    diff: &ColumnPrivilegesDiff,
+    key: &ColumnPrivilegeKey,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyColumnPrivilegesError> {
+    validate_column_privilege_diff_request(diff, key, unix_user, connection, group_denylist)
+        .await?;
+
+    for statement in diff.to_sql_statements() {
+        uncached_query(&statement)
+            .execute(&mut *connection)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to apply column privilege diff for {}: {:?}", key, e);
+                ModifyColumnPrivilegesError::MySqlError(e.to_string())
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Uses the result of
+/// [`diff_column_privileges`](crate::core::column_privileges::diff_column_privileges)
+/// to modify column privileges in the database.
+///
+/// Every diff is validated and applied on its own, so some of them might be
+/// applied even if others fail.
+pub async fn apply_column_privilege_diffs(
+    diffs: BTreeSet<ColumnPrivilegesDiff>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ModifyColumnPrivilegesResponse {
+    let mut results = BTreeMap::new();
+
+    for diff in diffs {
+        let key = diff.key();
+        let result =
+            apply_column_privilege_diff(&diff, &key, unix_user, connection, group_denylist).await;
+        results.insert(key, result);
+    }
+
+    results
+}
@@ -0,0 +1,212 @@
+//! Inspecting and terminating the open connections of database users.
+
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use indoc::formatdoc;
+use sqlx::{MySqlConnection, mysql::MySqlDatabaseError, prelude::*};
+
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            CONNECTION_STATEMENT_MAX_LENGTH, KillConnectionsError, KillConnectionsResponse,
+            ShowConnectionsError, ShowConnectionsResponse, UserConnection,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        types::{DbOrUser, MySQLUser},
+    },
+    server::{
+        common::try_get_with_binary_fallback,
+        sql::{uncached_query, user_operations::unsafe_user_exists},
+    },
+};
+
+/// `ER_NO_SUCH_THREAD`, returned by `KILL` when the connection has already been closed.
+const ER_NO_SUCH_THREAD: u16 = 1094;
+
+/// The connection of the server itself is excluded, so it can never kill itself,
+/// even if it was somehow asked to kill the connections of its own user.
+const DATABASE_USER_CONNECTION_IDS_QUERY: &str = r"
+    SELECT CAST(`ID` AS UNSIGNED)
+    FROM `information_schema`.`PROCESSLIST`
+    WHERE `USER` = ?
+      AND `ID` != CONNECTION_ID()
+    ORDER BY `ID`
+";
+
+static DATABASE_USER_CONNECTIONS_QUERY: LazyLock<String> = LazyLock::new(|| {
+    formatdoc!(
+        r"
+            SELECT
+              CAST(`ID` AS UNSIGNED) AS `id`,
+              CAST(`HOST` AS CHAR) AS `host`,
+              CAST(`DB` AS CHAR) AS `database`,
+              CAST(`COMMAND` AS CHAR) AS `command`,
+              CAST(GREATEST(`TIME`, 0) AS UNSIGNED) AS `time_seconds`,
+              CAST(`STATE` AS CHAR) AS `state`,
+              CAST(LEFT(`INFO`, {max_length}) AS CHAR) AS `statement`,
+              COALESCE(CHAR_LENGTH(`INFO`) > {max_length}, FALSE) AS `statement_truncated`
+            FROM `information_schema`.`PROCESSLIST`
+            WHERE `USER` = ?
+              AND `ID` != CONNECTION_ID()
+            ORDER BY `ID`
+        ",
+        max_length = CONNECTION_STATEMENT_MAX_LENGTH,
+    )
+});
+
+impl FromRow<'_, sqlx::mysql::MySqlRow> for UserConnection {
+    fn from_row(row: &sqlx::mysql::MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            host: try_get_with_binary_fallback(row, "host")?,
+            database: row.try_get("database")?,
+            command: try_get_with_binary_fallback(row, "command")?,
+            time_seconds: row.try_get("time_seconds")?,
+            state: row
+                .try_get::<Option<String>, _>("state")?
+                .filter(|state| !state.is_empty()),
+            statement: row.try_get("statement")?,
+            statement_truncated: row.try_get("statement_truncated")?,
+        })
+    }
+}
+
+pub async fn show_database_user_connections(
+    db_users: Vec<MySQLUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> ShowConnectionsResponse {
+    let mut results = BTreeMap::new();
+
+    for db_user in db_users {
+        if let Err(err) =
+            validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+                .map_err(ShowConnectionsError::ValidationError)
+        {
+            results.insert(db_user, Err(err));
+            continue;
+        }
+
+        match unsafe_user_exists(&db_user, &mut *connection).await {
+            Ok(false) => {
+                results.insert(db_user, Err(ShowConnectionsError::UserDoesNotExist));
+                continue;
+            }
+            Err(err) => {
+                results.insert(
+                    db_user,
+                    Err(ShowConnectionsError::MySqlError(err.to_string())),
+                );
+                continue;
+            }
+            _ => {}
+        }
+
+        let result = sqlx::query_as::<_, UserConnection>(&DATABASE_USER_CONNECTIONS_QUERY)
+            .bind(db_user.as_str())
+            .fetch_all(&mut *connection)
+            .await
+            .map_err(|err| ShowConnectionsError::MySqlError(err.to_string()));
+
+        if let Err(err) = &result {
+            tracing::error!(
+                "Failed to list connections of database user '{}': {:?}",
+                &db_user,
+                err
+            );
+        }
+
+        results.insert(db_user, result);
+    }
+
+    results
+}
+
+pub async fn kill_database_user_connections(
+    db_users: Vec<MySQLUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> KillConnectionsResponse {
+    let mut results = BTreeMap::new();
+
+    for db_user in db_users {
+        if let Err(err) =
+            validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+                .map_err(KillConnectionsError::ValidationError)
+        {
+            results.insert(db_user, Err(err));
+            continue;
+        }
+
+        match unsafe_user_exists(&db_user, &mut *connection).await {
+            Ok(false) => {
+                results.insert(db_user, Err(KillConnectionsError::UserDoesNotExist));
+                continue;
+            }
+            Err(err) => {
+                results.insert(
+                    db_user,
+                    Err(KillConnectionsError::MySqlError(err.to_string())),
+                );
+                continue;
+            }
+            _ => {}
+        }
+
+        let result = unsafe_kill_user_connections(&db_user, &mut *connection)
+            .await
+            .map_err(|err| KillConnectionsError::MySqlError(err.to_string()));
+
+        match &result {
+            Ok(ids) => tracing::info!(
+                "Killed {} connection(s) of database user '{}'",
+                ids.len(),
+                &db_user
+            ),
+            Err(err) => tracing::error!(
+                "Failed to kill connections of database user '{}': {:?}",
+                &db_user,
+                err
+            ),
+        }
+
+        results.insert(db_user, result);
+    }
+
+    results
+}
+
+/// Kill every connection of the user, returning the ids of the killed connections.
+///
+/// Connections that close by themselves between being listed and killed are left out.
+// NOTE: this function is unsafe because it does no input validation.
+async fn unsafe_kill_user_connections(
+    db_user: &MySQLUser,
+    connection: &mut MySqlConnection,
+) -> Result<Vec<u64>, sqlx::Error> {
+    let ids = sqlx::query_scalar::<_, u64>(DATABASE_USER_CONNECTION_IDS_QUERY)
+        .bind(db_user.as_str())
+        .fetch_all(&mut *connection)
+        .await?;
+
+    let mut killed = Vec::with_capacity(ids.len());
+    for id in ids {
+        match uncached_query(&format!("KILL CONNECTION {id}"))
+            .execute(&mut *connection)
+            .await
+        {
+            Ok(_) => killed.push(id),
+            Err(err)
+                if err
+                    .as_database_error()
+                    .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+                    .is_some_and(|err| err.number() == ER_NO_SUCH_THREAD) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(killed)
+}
@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
+use indoc::indoc;
+use itertools::Itertools;
 use sqlx::MySqlConnection;
 use sqlx::prelude::*;
 
@@ -15,16 +18,28 @@ use crate::{
     core::{
         common::UnixUser,
         protocol::{
-            CreateDatabaseError, CreateDatabasesResponse, DropDatabaseError, DropDatabasesResponse,
-            ListAllDatabasesError, ListAllDatabasesResponse, ListDatabasesError,
-            ListDatabasesResponse,
+            AdminListDatabasesResponse, AdminListError, CreateDatabaseError,
+            CreateDatabasesResponse, DatabaseWithPrivileges, DropDatabaseError,
+            DropDatabasesResponse, ListAllDatabasesError, ListAllDatabasesResponse,
+            ListAllDatabasesWithPrivilegesResponse, ListDatabasesError, ListDatabasesResponse,
+            ListDatabasesWithPrivilegesResponse, TransferDatabaseError, TransferDatabaseRequest,
+            TransferDatabaseResponse,
+        },
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        bulk::BulkConnections,
+        common::{create_user_group_matching_regex, find_name_owner},
+        progress::ProgressReporter,
+        sql::{
+            begin_transaction, database_privilege_operations::unsafe_get_privileges_for_databases,
+            quote_identifier, uncached_query,
         },
     },
-    server::{common::create_user_group_matching_regex, sql::quote_identifier},
 };
 
 // NOTE: this function is unsafe because it does no input validation.
-pub(super) async fn unsafe_database_exists(
+pub(crate) async fn unsafe_database_exists(
     database_name: &str,
     connection: &mut MySqlConnection,
 ) -> Result<bool, sqlx::Error> {
@@ -49,7 +64,7 @@ pub async fn complete_database_name(
     database_prefix: String,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> CompleteDatabaseNameResponse {
     let result = sqlx::query(
@@ -89,64 +104,102 @@ pub async fn complete_database_name(
 pub async fn create_databases(
     database_names: Vec<MySQLDatabase>,
     unix_user: &UnixUser,
-    connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    connections: &mut BulkConnections<'_>,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
+    progress: ProgressReporter,
 ) -> CreateDatabasesResponse {
-    let mut results = BTreeMap::new();
-
-    for database_name in database_names {
-        if let Err(err) = validate_db_or_user_request(
-            &DbOrUser::Database(database_name.clone()),
-            unix_user,
-            group_denylist,
+    let unix_user = Arc::new(unix_user.clone());
+    let group_denylist = Arc::new(group_denylist.clone());
+    connections
+        .process(
+            database_names,
+            &progress,
+            |database_name| database_name.to_string(),
+            |database_name, connection| {
+                let unix_user = Arc::clone(&unix_user);
+                let group_denylist = Arc::clone(&group_denylist);
+                Box::pin(async move {
+                    let result =
+                        create_database(&database_name, &unix_user, connection, &group_denylist)
+                            .await;
+                    (database_name, result)
+                })
+            },
         )
-        .map_err(CreateDatabaseError::ValidationError)
-        {
-            results.insert(database_name.clone(), Err(err));
-            continue;
-        }
+        .await
+        .into_iter()
+        .collect()
+}
 
-        match unsafe_database_exists(&database_name, &mut *connection).await {
-            Ok(true) => {
-                results.insert(
-                    database_name.clone(),
-                    Err(CreateDatabaseError::DatabaseAlreadyExists),
-                );
-                continue;
-            }
-            Err(err) => {
-                results.insert(
-                    database_name.clone(),
-                    Err(CreateDatabaseError::MySqlError(err.to_string())),
-                );
-                continue;
-            }
-            _ => {}
-        }
+async fn create_database(
+    database_name: &MySQLDatabase,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), CreateDatabaseError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(database_name.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(CreateDatabaseError::ValidationError)?;
 
-        let result =
-            sqlx::query(format!("CREATE DATABASE {}", quote_identifier(&database_name)).as_str())
-                .execute(&mut *connection)
-                .await
-                .map(|_| ())
-                .map_err(|err| CreateDatabaseError::MySqlError(err.to_string()));
+    match unsafe_database_exists(database_name, &mut *connection).await {
+        Ok(true) => return Err(CreateDatabaseError::DatabaseAlreadyExists),
+        Err(err) => return Err(CreateDatabaseError::MySqlError(err.to_string())),
+        _ => {}
+    }
 
-        if let Err(err) = &result {
-            tracing::error!("Failed to create database '{}': {:?}", &database_name, err);
-        }
+    let result = uncached_query(&format!(
+        "CREATE DATABASE {}",
+        quote_identifier(database_name)
+    ))
+    .execute(&mut *connection)
+    .await
+    .map(|_| ())
+    .map_err(|err| CreateDatabaseError::MySqlError(err.to_string()));
 
-        results.insert(database_name, result);
+    if let Err(err) = &result {
+        tracing::error!("Failed to create database '{}': {:?}", database_name, err);
     }
 
-    results
+    result
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Removes all privilege rows for a database, returning the users they belonged to.
+async fn unsafe_delete_privileges_for_database(
+    database_name: &MySQLDatabase,
+    connection: &mut MySqlConnection,
+) -> Result<Vec<MySQLUser>, sqlx::Error> {
+    let mut transaction = begin_transaction(connection).await?;
+
+    let users: Vec<String> = sqlx::query_scalar(indoc! {r"
+        SELECT DISTINCT CAST(`User` AS CHAR(128)) AS `User`
+        FROM `mysql`.`db`
+        WHERE `Db` = ?
+        ORDER BY `User`
+    "})
+    .bind(database_name.as_str())
+    .fetch_all(&mut *transaction)
+    .await?;
+
+    sqlx::query("DELETE FROM `mysql`.`db` WHERE `Db` = ?")
+        .bind(database_name.as_str())
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(users.into_iter().map(MySQLUser::from).collect())
 }
 
 pub async fn drop_databases(
     database_names: Vec<MySQLDatabase>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> DropDatabasesResponse {
     let mut results = BTreeMap::new();
@@ -181,23 +234,269 @@ pub async fn drop_databases(
             _ => {}
         }
 
-        let result =
-            sqlx::query(format!("DROP DATABASE {}", quote_identifier(&database_name)).as_str())
-                .execute(&mut *connection)
-                .await
-                .map(|_| ())
-                .map_err(|err| DropDatabaseError::MySqlError(err.to_string()));
+        let result = uncached_query(&format!(
+            "DROP DATABASE {}",
+            quote_identifier(&database_name)
+        ))
+        .execute(&mut *connection)
+        .await
+        .map(|_| ())
+        .map_err(|err| DropDatabaseError::MySqlError(err.to_string()));
 
-        if let Err(err) = &result {
+        if let Err(err) = result {
             tracing::error!("Failed to drop database '{}': {:?}", &database_name, err);
+            results.insert(database_name, Err(err));
+            continue;
         }
 
-        results.insert(database_name, result);
+        // NOTE: the privilege rows are removed after the database is gone, so that a failing
+        //       `DROP DATABASE` does not leave the database without its privileges.
+        //       If removing them fails, they are left for `cleanup-privs` to find.
+        let users = unsafe_delete_privileges_for_database(&database_name, &mut *connection)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::error!(
+                    "Failed to remove privileges for dropped database '{}': {:?}",
+                    &database_name,
+                    err
+                );
+                Vec::new()
+            });
+
+        results.insert(database_name, Ok(users));
     }
 
     results
 }
 
+/// Objects that `RENAME TABLE` can not move to another database.
+const UNMOVABLE_OBJECTS_QUERY: &str = indoc! {r"
+    SELECT 'view' AS `kind`, CAST(`TABLE_NAME` AS CHAR(64)) AS `name`
+    FROM `information_schema`.`VIEWS` WHERE `TABLE_SCHEMA` = ?
+    UNION ALL
+    SELECT 'trigger', CAST(`TRIGGER_NAME` AS CHAR(64))
+    FROM `information_schema`.`TRIGGERS` WHERE `TRIGGER_SCHEMA` = ?
+    UNION ALL
+    SELECT LOWER(`ROUTINE_TYPE`), CAST(`ROUTINE_NAME` AS CHAR(64))
+    FROM `information_schema`.`ROUTINES` WHERE `ROUTINE_SCHEMA` = ?
+    UNION ALL
+    SELECT 'event', CAST(`EVENT_NAME` AS CHAR(64))
+    FROM `information_schema`.`EVENTS` WHERE `EVENT_SCHEMA` = ?
+"};
+
+/// The tables in the `mysql` database with privilege rows that refer to a database by name.
+const DATABASE_PRIVILEGE_TABLES: [&str; 4] = ["db", "tables_priv", "columns_priv", "procs_priv"];
+
+fn rename_tables_statement(tables: &[String], from: &MySQLDatabase, to: &MySQLDatabase) -> String {
+    let renames = tables
+        .iter()
+        .map(|table| {
+            format!(
+                "{}.{} TO {}.{}",
+                quote_identifier(from),
+                quote_identifier(table),
+                quote_identifier(to),
+                quote_identifier(table),
+            )
+        })
+        .join(", ");
+    format!("RENAME TABLE {renames}")
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Moves the database, table, column and routine privilege rows of a database
+/// to another database name, all in one transaction.
+async fn unsafe_move_privilege_rows(
+    from: &MySQLDatabase,
+    to: &MySQLDatabase,
+    connection: &mut MySqlConnection,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = begin_transaction(connection).await?;
+    for table in DATABASE_PRIVILEGE_TABLES {
+        sqlx::query(&format!(
+            "UPDATE `mysql`.{} SET `Db` = ? WHERE `Db` = ?",
+            quote_identifier(table)
+        ))
+        .bind(to.as_str())
+        .bind(from.as_str())
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Moves all tables of a database into a new database with the same character set,
+/// moves its privilege rows along, and drops the old database.
+///
+/// There is no `RENAME DATABASE`, so the tables are moved with a single `RENAME TABLE`,
+/// which either moves all of them or none. `RENAME TABLE` commits implicitly, so the
+/// privilege rows are moved in a transaction of their own afterwards. If that fails,
+/// the tables are moved back and the new database is dropped again.
+///
+/// Once the tables and privileges have been moved, the transfer counts as done, even if
+/// the empty old database can not be dropped. That is only logged, and left for the user
+/// to drop.
+async fn unsafe_transfer_database(
+    from: &MySQLDatabase,
+    to: &MySQLDatabase,
+    connection: &mut MySqlConnection,
+) -> Result<(), sqlx::Error> {
+    let (character_set, collation): (String, String) = sqlx::query_as(indoc! {r"
+        SELECT
+          CAST(`DEFAULT_CHARACTER_SET_NAME` AS CHAR(64)),
+          CAST(`DEFAULT_COLLATION_NAME` AS CHAR(64))
+        FROM `information_schema`.`SCHEMATA`
+        WHERE `SCHEMA_NAME` = ?
+    "})
+    .bind(from.as_str())
+    .fetch_one(&mut *connection)
+    .await?;
+
+    let tables: Vec<String> = sqlx::query_scalar(indoc! {r"
+        SELECT CAST(`TABLE_NAME` AS CHAR(64))
+        FROM `information_schema`.`TABLES`
+        WHERE `TABLE_SCHEMA` = ? AND `TABLE_TYPE` = 'BASE TABLE'
+    "})
+    .bind(from.as_str())
+    .fetch_all(&mut *connection)
+    .await?;
+
+    uncached_query(&format!(
+        "CREATE DATABASE {} CHARACTER SET {} COLLATE {}",
+        quote_identifier(to),
+        quote_identifier(&character_set),
+        quote_identifier(&collation),
+    ))
+    .execute(&mut *connection)
+    .await?;
+
+    let drop_target = format!("DROP DATABASE {}", quote_identifier(to));
+
+    if !tables.is_empty()
+        && let Err(err) = uncached_query(&rename_tables_statement(&tables, from, to))
+            .execute(&mut *connection)
+            .await
+    {
+        uncached_query(&drop_target)
+            .execute(&mut *connection)
+            .await
+            .ok();
+        return Err(err);
+    }
+
+    if let Err(err) = unsafe_move_privilege_rows(from, to, connection).await {
+        if !tables.is_empty()
+            && let Err(rename_err) = uncached_query(&rename_tables_statement(&tables, to, from))
+                .execute(&mut *connection)
+                .await
+        {
+            tracing::error!(
+                "Failed to move the tables of '{}' back to '{}' after a failed transfer: {}",
+                to,
+                from,
+                rename_err
+            );
+            return Err(err);
+        }
+        uncached_query(&drop_target)
+            .execute(&mut *connection)
+            .await
+            .ok();
+        return Err(err);
+    }
+
+    if let Err(err) = uncached_query(&format!("DROP DATABASE {}", quote_identifier(from)))
+        .execute(&mut *connection)
+        .await
+    {
+        tracing::warn!(
+            "Transferred database '{}' to '{}', but failed to drop the empty database: {}",
+            from,
+            to,
+            err
+        );
+    }
+
+    Ok(())
+}
+
+/// Moves a database owned by the unix user to a new name under another of its prefixes,
+/// so that it changes hands without the data or the privileges getting lost.
+pub async fn transfer_database(
+    request: TransferDatabaseRequest,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> TransferDatabaseResponse {
+    validate_db_or_user_request(
+        &DbOrUser::Database(request.from.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(TransferDatabaseError::SourceValidationError)?;
+
+    validate_db_or_user_request(
+        &DbOrUser::Database(request.to.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(TransferDatabaseError::TargetValidationError)?;
+
+    if !unsafe_database_exists(&request.from, &mut *connection)
+        .await
+        .map_err(|err| TransferDatabaseError::MySqlError(err.to_string()))?
+    {
+        return Err(TransferDatabaseError::SourceDoesNotExist);
+    }
+
+    if unsafe_database_exists(&request.to, &mut *connection)
+        .await
+        .map_err(|err| TransferDatabaseError::MySqlError(err.to_string()))?
+    {
+        return Err(TransferDatabaseError::TargetAlreadyExists);
+    }
+
+    let unmovable_objects: Vec<(String, String)> = sqlx::query_as(UNMOVABLE_OBJECTS_QUERY)
+        .bind(request.from.as_str())
+        .bind(request.from.as_str())
+        .bind(request.from.as_str())
+        .bind(request.from.as_str())
+        .fetch_all(&mut *connection)
+        .await
+        .map_err(|err| TransferDatabaseError::MySqlError(err.to_string()))?;
+    if !unmovable_objects.is_empty() {
+        return Err(TransferDatabaseError::UnsupportedObjects(
+            unmovable_objects
+                .into_iter()
+                .map(|(kind, name)| format!("{kind} '{name}'"))
+                .collect(),
+        ));
+    }
+
+    let result = unsafe_transfer_database(&request.from, &request.to, connection)
+        .await
+        .map_err(|err| TransferDatabaseError::MySqlError(err.to_string()));
+
+    match &result {
+        Ok(()) => tracing::info!(
+            "User '{}' transferred database '{}' to '{}'",
+            unix_user,
+            request.from,
+            request.to
+        ),
+        Err(err) => tracing::error!(
+            "Failed to transfer database '{}' to '{}': {:?}",
+            request.from,
+            request.to,
+            err
+        ),
+    }
+
+    result
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DatabaseRow {
     pub database: MySQLDatabase,
@@ -206,6 +505,12 @@ pub struct DatabaseRow {
     pub collation: Option<String>,
     pub character_set: Option<String>,
     pub size_bytes: u64,
+    /// The latest `UPDATE_TIME` of the tables in the database, if the storage engine records it.
+    #[serde(default)]
+    pub last_updated: Option<String>,
+    /// The sum of the storage engines' estimated row counts, which can be off by a lot for `InnoDB`.
+    #[serde(default)]
+    pub approximate_rows: u64,
 }
 
 impl FromRow<'_, sqlx::mysql::MySqlRow> for DatabaseRow {
@@ -237,6 +542,8 @@ impl FromRow<'_, sqlx::mysql::MySqlRow> for DatabaseRow {
             collation: row.try_get::<Option<String>, _>("collation")?,
             character_set: row.try_get::<Option<String>, _>("character_set")?,
             size_bytes: row.try_get::<u64, _>("size_bytes")?,
+            last_updated: row.try_get::<Option<String>, _>("last_updated")?,
+            approximate_rows: row.try_get::<u64, _>("approximate_rows")?,
         })
     }
 }
@@ -245,7 +552,7 @@ pub async fn list_databases(
     database_names: Vec<MySQLDatabase>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> ListDatabasesResponse {
     let mut results = BTreeMap::new();
@@ -273,7 +580,9 @@ pub async fn list_databases(
                   CAST(IFNULL(
                     SUM(`information_schema`.`TABLES`.`DATA_LENGTH` + `information_schema`.`TABLES`.`INDEX_LENGTH`),
                     0
-                  ) AS UNSIGNED INTEGER) AS `size_bytes`
+                  ) AS UNSIGNED INTEGER) AS `size_bytes`,
+                  CAST(MAX(`information_schema`.`TABLES`.`UPDATE_TIME`) AS CHAR) AS `last_updated`,
+                  CAST(IFNULL(SUM(`information_schema`.`TABLES`.`TABLE_ROWS`), 0) AS UNSIGNED INTEGER) AS `approximate_rows`
                 FROM `information_schema`.`SCHEMATA`
                 LEFT OUTER JOIN `information_schema`.`TABLES`
                   ON `information_schema`.`SCHEMATA`.`SCHEMA_NAME` = `TABLES`.`TABLE_SCHEMA`
@@ -304,13 +613,14 @@ pub async fn list_databases(
     results
 }
 
-pub async fn list_all_databases_for_user(
-    unix_user: &UnixUser,
+// NOTE: this function is unsafe because it does no input validation.
+/// Get every database matching `name_regex`, and `name_pattern` if it is given.
+async fn unsafe_list_databases_matching(
+    name_regex: &str,
+    name_pattern: Option<&str>,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
-    group_denylist: &GroupDenylist,
-) -> ListAllDatabasesResponse {
-    let result = sqlx::query_as::<_, DatabaseRow>(
+) -> Result<Vec<DatabaseRow>, sqlx::Error> {
+    sqlx::query_as::<_, DatabaseRow>(
         r"
           SELECT
             CAST(`information_schema`.`SCHEMATA`.`SCHEMA_NAME` AS CHAR(64)) AS `database`,
@@ -321,7 +631,9 @@ pub async fn list_all_databases_for_user(
             CAST(IFNULL(
               SUM(`information_schema`.`TABLES`.`DATA_LENGTH` + `information_schema`.`TABLES`.`INDEX_LENGTH`),
               0
-            ) AS UNSIGNED INTEGER) AS `size_bytes`
+            ) AS UNSIGNED INTEGER) AS `size_bytes`,
+            CAST(MAX(`information_schema`.`TABLES`.`UPDATE_TIME`) AS CHAR) AS `last_updated`,
+            CAST(IFNULL(SUM(`information_schema`.`TABLES`.`TABLE_ROWS`), 0) AS UNSIGNED INTEGER) AS `approximate_rows`
           FROM `information_schema`.`SCHEMATA`
           LEFT OUTER JOIN `information_schema`.`TABLES`
             ON `information_schema`.`SCHEMATA`.`SCHEMA_NAME` = `TABLES`.`TABLE_SCHEMA`
@@ -329,12 +641,32 @@ pub async fn list_all_databases_for_user(
             ON `information_schema`.`SCHEMATA`.`SCHEMA_NAME` = `mysql`.`db`.`DB`
           WHERE `information_schema`.`SCHEMATA`.`SCHEMA_NAME` NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
             AND `information_schema`.`SCHEMATA`.`SCHEMA_NAME` REGEXP ?
+            AND (? IS NULL OR `information_schema`.`SCHEMATA`.`SCHEMA_NAME` REGEXP ?)
           GROUP BY `information_schema`.`SCHEMATA`.`SCHEMA_NAME`
         ",
     )
-    .bind(create_user_group_matching_regex(unix_user, group_denylist))
+    .bind(name_regex)
+    .bind(name_pattern)
+    .bind(name_pattern)
     .fetch_all(connection)
     .await
+}
+
+/// List the databases of the unix user, only including those matching `name_pattern`
+/// as a `MySQL` regular expression if it is given.
+pub async fn list_all_databases_for_user(
+    name_pattern: Option<&str>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListAllDatabasesResponse {
+    let result = unsafe_list_databases_matching(
+        &create_user_group_matching_regex(unix_user, group_denylist),
+        name_pattern,
+        connection,
+    )
+    .await
     .map_err(|err| ListAllDatabasesError::MySqlError(err.to_string()));
 
     // TODO: should we assert that the users are also owned by the unix_user from the request?
@@ -349,3 +681,105 @@ pub async fn list_all_databases_for_user(
 
     result
 }
+
+/// List every database on the server that is owned by a unix user or group, together with its owner.
+///
+/// This must only be used for admins.
+pub async fn list_all_managed_databases(
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> AdminListDatabasesResponse {
+    let databases = unsafe_list_databases_matching(".*", None, connection)
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to list all managed databases: {:?}", err);
+            AdminListError::MySqlError(err.to_string())
+        })?;
+
+    Ok(databases
+        .into_iter()
+        .filter_map(|database| {
+            find_name_owner(database.database.as_str(), group_denylist)
+                .map(|owner| (owner, database))
+        })
+        .collect())
+}
+
+/// Like [`list_databases`], but also includes the privileges of all users on each database.
+pub async fn list_databases_with_privileges(
+    database_names: Vec<MySQLDatabase>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListDatabasesWithPrivilegesResponse {
+    let databases = list_databases(
+        database_names,
+        unix_user,
+        &mut *connection,
+        backend_capabilities,
+        group_denylist,
+    )
+    .await;
+
+    let existing_databases = databases
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    let privileges = unsafe_get_privileges_for_databases(&existing_databases, &mut *connection)
+        .await
+        .map(|rows| rows.into_iter().into_group_map_by(|row| row.db.clone()));
+
+    databases
+        .into_iter()
+        .map(|(name, result)| {
+            let result = result.and_then(|database| match &privileges {
+                Ok(privileges) => Ok(DatabaseWithPrivileges {
+                    database,
+                    privileges: privileges.get(&name).cloned().unwrap_or_default(),
+                }),
+                Err(err) => Err(ListDatabasesError::MySqlError(err.to_string())),
+            });
+            (name, result)
+        })
+        .collect()
+}
+
+/// Like [`list_all_databases_for_user`], but also includes the privileges of all users on each database.
+pub async fn list_all_databases_with_privileges_for_user(
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListAllDatabasesWithPrivilegesResponse {
+    let databases = list_all_databases_for_user(
+        None,
+        unix_user,
+        &mut *connection,
+        backend_capabilities,
+        group_denylist,
+    )
+    .await?;
+
+    let database_names = databases
+        .iter()
+        .map(|database| database.database.clone())
+        .collect::<Vec<_>>();
+
+    let mut privileges = unsafe_get_privileges_for_databases(&database_names, &mut *connection)
+        .await
+        .map_err(|err| ListAllDatabasesError::MySqlError(err.to_string()))?
+        .into_iter()
+        .into_group_map_by(|row| row.db.clone());
+
+    Ok(databases
+        .into_iter()
+        .map(|database| DatabaseWithPrivileges {
+            privileges: privileges.remove(&database.database).unwrap_or_default(),
+            database,
+        })
+        .collect())
+}
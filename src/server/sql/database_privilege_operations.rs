@@ -14,10 +14,14 @@
 //!   changes will be made when applying a set of changes
 //!   to the list of database privileges.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, LazyLock},
+};
 
 use indoc::indoc;
 use itertools::Itertools;
+use serde::Deserialize;
 use sqlx::{MySqlConnection, mysql::MySqlRow, prelude::*};
 
 use crate::{
@@ -28,17 +32,25 @@ use crate::{
             DatabasePrivilegesDiff,
         },
         protocol::{
-            DiffDoesNotApplyError, ListAllPrivilegesError, ListAllPrivilegesResponse,
-            ListPrivilegesError, ListPrivilegesResponse, ModifyDatabasePrivilegesError,
-            ModifyPrivilegesResponse,
-            request_validation::{GroupDenylist, validate_db_or_user_request},
+            DeleteOrphanedPrivilegeError, DeleteOrphanedPrivilegesRequest,
+            DeleteOrphanedPrivilegesResponse, DiffDoesNotApplyError, ListAllPrivilegesError,
+            ListAllPrivilegesResponse, ListOrphanedPrivilegesError, ListOrphanedPrivilegesResponse,
+            ListPartialRevokesResponse, ListPrivilegesError, ListPrivilegesResponse,
+            ListUnusedDatabasesError, ListUnusedDatabasesResponse, ModifyDatabasePrivilegesError,
+            ModifyPrivilegesResponse, OrphanedPrivilege, PartialRevoke,
+            request_validation::{GroupDenylist, ValidationError, validate_db_or_user_request},
         },
         types::{DbOrUser, MySQLDatabase, MySQLUser},
     },
     server::{
+        backend_capabilities::BackendCapabilities,
+        bulk::BulkConnections,
         common::{create_user_group_matching_regex, try_get_with_binary_fallback},
+        progress::ProgressReporter,
         sql::{
-            database_operations::unsafe_database_exists, quote_identifier,
+            begin_transaction,
+            database_operations::{list_all_databases_for_user, unsafe_database_exists},
+            quote_identifier,
             user_operations::unsafe_user_exists,
         },
     },
@@ -74,26 +86,87 @@ impl FromRow<'_, MySqlRow> for DatabasePrivilegeRow {
             create_tmp_table_priv: get_mysql_row_priv_field(row, 10)?,
             lock_tables_priv: get_mysql_row_priv_field(row, 11)?,
             references_priv: get_mysql_row_priv_field(row, 12)?,
+            create_view_priv: get_mysql_row_priv_field(row, 13)?,
+            show_view_priv: get_mysql_row_priv_field(row, 14)?,
+            trigger_priv: get_mysql_row_priv_field(row, 15)?,
+            event_priv: get_mysql_row_priv_field(row, 16)?,
+            execute_priv: get_mysql_row_priv_field(row, 17)?,
+            create_routine_priv: get_mysql_row_priv_field(row, 18)?,
+            alter_routine_priv: get_mysql_row_priv_field(row, 19)?,
+            grant_priv: get_mysql_row_priv_field(row, 20)?,
         })
     }
 }
 
+/// The quoted columns of [`DATABASE_PRIVILEGE_FIELDS`], separated by commas.
+///
+/// The queries below are only built once, so that their text stays the same
+/// and the prepared statements can be reused from the cache of the connection.
+static PRIVILEGE_COLUMNS: LazyLock<String> = LazyLock::new(|| {
+    DATABASE_PRIVILEGE_FIELDS
+        .iter()
+        .map(|field| quote_identifier(field))
+        .join(",")
+});
+
+static SELECT_PRIVILEGES_FOR_DATABASE_QUERY: LazyLock<String> =
+    LazyLock::new(|| format!("SELECT {} FROM `db` WHERE `Db` = ?", *PRIVILEGE_COLUMNS));
+
+static SELECT_PRIVILEGES_FOR_DB_USER_PAIR_QUERY: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        "SELECT {} FROM `db` WHERE `Db` = ? AND `User` = ?",
+        *PRIVILEGE_COLUMNS
+    )
+});
+
+static SELECT_ALL_PRIVILEGES_QUERY: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        indoc! {r"
+            SELECT {} FROM `db` WHERE `db` IN
+            (SELECT DISTINCT CAST(`SCHEMA_NAME` AS CHAR(64)) AS `database`
+              FROM `information_schema`.`SCHEMATA`
+              WHERE `SCHEMA_NAME` NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
+                AND `SCHEMA_NAME` REGEXP ?)
+        "},
+        *PRIVILEGE_COLUMNS
+    )
+});
+
+static INSERT_PRIVILEGES_QUERY: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        "INSERT INTO `db` ({}) VALUES ({})",
+        *PRIVILEGE_COLUMNS,
+        std::iter::repeat_n("?", DATABASE_PRIVILEGE_FIELDS.len()).join(","),
+    )
+});
+
+/// Every privilege is set to its bound value, or left as is if it is bound to `NULL`.
+static UPDATE_PRIVILEGES_QUERY: LazyLock<String> = LazyLock::new(|| {
+    let changes = DATABASE_PRIVILEGE_FIELDS
+        .iter()
+        .skip(2) // Skip Db and User fields
+        .map(|field| {
+            format!(
+                "{} = COALESCE(?, {})",
+                quote_identifier(field),
+                quote_identifier(field)
+            )
+        })
+        .join(",");
+
+    format!("UPDATE `db` SET {changes} WHERE `Db` = ? AND `User` = ?")
+});
+
 // NOTE: this function is unsafe because it does no input validation.
 /// Get all users + privileges for a single database.
 async fn unsafe_get_database_privileges(
     database_name: &str,
     connection: &mut MySqlConnection,
 ) -> Result<Vec<DatabasePrivilegeRow>, sqlx::Error> {
-    let result = sqlx::query_as::<_, DatabasePrivilegeRow>(&format!(
-        "SELECT {} FROM `db` WHERE `Db` = ?",
-        DATABASE_PRIVILEGE_FIELDS
-            .iter()
-            .map(|field| quote_identifier(field))
-            .join(","),
-    ))
-    .bind(database_name)
-    .fetch_all(connection)
-    .await;
+    let result = sqlx::query_as::<_, DatabasePrivilegeRow>(&SELECT_PRIVILEGES_FOR_DATABASE_QUERY)
+        .bind(database_name)
+        .fetch_all(connection)
+        .await;
 
     if let Err(e) = &result {
         tracing::error!(
@@ -113,17 +186,12 @@ pub async fn unsafe_get_database_privileges_for_db_user_pair(
     user_name: &MySQLUser,
     connection: &mut MySqlConnection,
 ) -> Result<Option<DatabasePrivilegeRow>, sqlx::Error> {
-    let result = sqlx::query_as::<_, DatabasePrivilegeRow>(&format!(
-        "SELECT {} FROM `db` WHERE `Db` = ? AND `User` = ?",
-        DATABASE_PRIVILEGE_FIELDS
-            .iter()
-            .map(|field| quote_identifier(field))
-            .join(","),
-    ))
-    .bind(database_name.as_str())
-    .bind(user_name.as_str())
-    .fetch_optional(connection)
-    .await;
+    let result =
+        sqlx::query_as::<_, DatabasePrivilegeRow>(&SELECT_PRIVILEGES_FOR_DB_USER_PAIR_QUERY)
+            .bind(database_name.as_str())
+            .bind(user_name.as_str())
+            .fetch_optional(connection)
+            .await;
 
     if let Err(e) = &result {
         tracing::error!(
@@ -137,11 +205,47 @@ pub async fn unsafe_get_database_privileges_for_db_user_pair(
     result
 }
 
+// NOTE: this function is unsafe because it does no input validation.
+/// Get all users + privileges for several databases, using a single query.
+pub(super) async fn unsafe_get_privileges_for_databases(
+    database_names: &[MySQLDatabase],
+    connection: &mut MySqlConnection,
+) -> Result<Vec<DatabasePrivilegeRow>, sqlx::Error> {
+    if database_names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = format!(
+        "SELECT {} FROM `db` WHERE `Db` IN ({})",
+        *PRIVILEGE_COLUMNS,
+        std::iter::repeat_n("?", database_names.len()).join(","),
+    );
+
+    let mut query = sqlx::query_as::<_, DatabasePrivilegeRow>(&query);
+    for database_name in database_names {
+        query = query.bind(database_name.as_str());
+    }
+
+    let result = query.fetch_all(connection).await;
+
+    if let Err(e) = &result {
+        tracing::error!(
+            "Failed to get database privileges for {} databases: {}",
+            database_names.len(),
+            e
+        );
+    }
+
+    result
+}
+
+/// Get the privilege rows of the given databases, optionally only for a single user.
 pub async fn get_databases_privilege_data(
     database_names: Vec<MySQLDatabase>,
+    user_filter: Option<&MySQLUser>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> ListPrivilegesResponse {
     let mut results = BTreeMap::new();
@@ -178,6 +282,7 @@ pub async fn get_databases_privilege_data(
 
         let result = unsafe_get_database_privileges(database_name, connection)
             .await
+            .map(|rows| filter_privilege_rows_by_user(rows, user_filter))
             .map_err(|e| ListPrivilegesError::MySqlError(e.to_string()));
 
         results.insert(database_name.to_owned(), result);
@@ -188,34 +293,30 @@ pub async fn get_databases_privilege_data(
     results
 }
 
-/// TODO: make this constant
-fn get_all_db_privs_query() -> String {
-    format!(
-        indoc! {r"
-            SELECT {} FROM `db` WHERE `db` IN
-            (SELECT DISTINCT CAST(`SCHEMA_NAME` AS CHAR(64)) AS `database`
-              FROM `information_schema`.`SCHEMATA`
-              WHERE `SCHEMA_NAME` NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
-                AND `SCHEMA_NAME` REGEXP ?)
-        "},
-        DATABASE_PRIVILEGE_FIELDS
-            .iter()
-            .map(|field| quote_identifier(field))
-            .join(","),
-    )
+fn filter_privilege_rows_by_user(
+    rows: Vec<DatabasePrivilegeRow>,
+    user_filter: Option<&MySQLUser>,
+) -> Vec<DatabasePrivilegeRow> {
+    match user_filter {
+        Some(user) => rows.into_iter().filter(|row| &row.user == user).collect(),
+        None => rows,
+    }
 }
 
-/// Get all database + user + privileges pairs that are owned by the current user.
+/// Get all database + user + privileges pairs that are owned by the current user,
+/// optionally only for a single user.
 pub async fn get_all_database_privileges(
+    user_filter: Option<&MySQLUser>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> ListAllPrivilegesResponse {
-    let result = sqlx::query_as::<_, DatabasePrivilegeRow>(&get_all_db_privs_query())
+    let result = sqlx::query_as::<_, DatabasePrivilegeRow>(&SELECT_ALL_PRIVILEGES_QUERY)
         .bind(create_user_group_matching_regex(unix_user, group_denylist))
         .fetch_all(connection)
         .await
+        .map(|rows| filter_privilege_rows_by_user(rows, user_filter))
         .map_err(|e| ListAllPrivilegesError::MySqlError(e.to_string()));
 
     if let Err(e) = &result {
@@ -226,51 +327,37 @@ pub async fn get_all_database_privileges(
 }
 
 // TODO: make these queries constant strings.
-async fn unsafe_apply_privilege_diff(
+pub(crate) async fn unsafe_apply_privilege_diff(
     database_privilege_diff: &DatabasePrivilegesDiff,
     connection: &mut MySqlConnection,
 ) -> Result<(), sqlx::Error> {
     let result = match database_privilege_diff {
-        DatabasePrivilegesDiff::New(p) => {
-            let tables = DATABASE_PRIVILEGE_FIELDS
-                .iter()
-                .map(|field| quote_identifier(field))
-                .join(",");
-
-            let question_marks =
-                std::iter::repeat_n("?", DATABASE_PRIVILEGE_FIELDS.len()).join(",");
-
-            sqlx::query(format!("INSERT INTO `db` ({tables}) VALUES ({question_marks})").as_str())
-                .bind(p.db.to_string())
-                .bind(p.user.to_string())
-                .bind(yn(p.select_priv))
-                .bind(yn(p.insert_priv))
-                .bind(yn(p.update_priv))
-                .bind(yn(p.delete_priv))
-                .bind(yn(p.create_priv))
-                .bind(yn(p.drop_priv))
-                .bind(yn(p.alter_priv))
-                .bind(yn(p.index_priv))
-                .bind(yn(p.create_tmp_table_priv))
-                .bind(yn(p.lock_tables_priv))
-                .bind(yn(p.references_priv))
-                .execute(connection)
-                .await
-                .map(|_| ())
-        }
+        DatabasePrivilegesDiff::New(p) => sqlx::query(&INSERT_PRIVILEGES_QUERY)
+            .bind(p.db.to_string())
+            .bind(p.user.to_string())
+            .bind(yn(p.select_priv))
+            .bind(yn(p.insert_priv))
+            .bind(yn(p.update_priv))
+            .bind(yn(p.delete_priv))
+            .bind(yn(p.create_priv))
+            .bind(yn(p.drop_priv))
+            .bind(yn(p.alter_priv))
+            .bind(yn(p.index_priv))
+            .bind(yn(p.create_tmp_table_priv))
+            .bind(yn(p.lock_tables_priv))
+            .bind(yn(p.references_priv))
+            .bind(yn(p.create_view_priv))
+            .bind(yn(p.show_view_priv))
+            .bind(yn(p.trigger_priv))
+            .bind(yn(p.event_priv))
+            .bind(yn(p.execute_priv))
+            .bind(yn(p.create_routine_priv))
+            .bind(yn(p.alter_routine_priv))
+            .bind(yn(p.grant_priv))
+            .execute(connection)
+            .await
+            .map(|_| ()),
         DatabasePrivilegesDiff::Modified(p) => {
-            let changes = DATABASE_PRIVILEGE_FIELDS
-                .iter()
-                .skip(2) // Skip Db and User fields
-                .map(|field| {
-                    format!(
-                        "{} = COALESCE(?, {})",
-                        quote_identifier(field),
-                        quote_identifier(field)
-                    )
-                })
-                .join(",");
-
             fn change_to_yn(change: DatabasePrivilegeChange) -> &'static str {
                 match change {
                     DatabasePrivilegeChange::YesToNo => "N",
@@ -278,7 +365,7 @@ async fn unsafe_apply_privilege_diff(
                 }
             }
 
-            sqlx::query(format!("UPDATE `db` SET {changes} WHERE `Db` = ? AND `User` = ?").as_str())
+            sqlx::query(&UPDATE_PRIVILEGES_QUERY)
                 .bind(p.select_priv.map(change_to_yn))
                 .bind(p.insert_priv.map(change_to_yn))
                 .bind(p.update_priv.map(change_to_yn))
@@ -290,6 +377,14 @@ async fn unsafe_apply_privilege_diff(
                 .bind(p.create_tmp_table_priv.map(change_to_yn))
                 .bind(p.lock_tables_priv.map(change_to_yn))
                 .bind(p.references_priv.map(change_to_yn))
+                .bind(p.create_view_priv.map(change_to_yn))
+                .bind(p.show_view_priv.map(change_to_yn))
+                .bind(p.trigger_priv.map(change_to_yn))
+                .bind(p.event_priv.map(change_to_yn))
+                .bind(p.execute_priv.map(change_to_yn))
+                .bind(p.create_routine_priv.map(change_to_yn))
+                .bind(p.alter_routine_priv.map(change_to_yn))
+                .bind(p.grant_priv.map(change_to_yn))
                 .bind(p.db.to_string())
                 .bind(p.user.to_string())
                 .execute(connection)
@@ -398,87 +493,533 @@ async fn validate_diff(
     }
 }
 
+/// Checks that the unix user is allowed to apply the diff, and that it applies
+/// to the current state of the database.
+async fn validate_privilege_diff_request(
+    diff: &DatabasePrivilegesDiff,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyDatabasePrivilegesError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(diff.get_database_name().to_owned()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(ModifyDatabasePrivilegesError::UserValidationError)?;
+
+    validate_db_or_user_request(
+        &DbOrUser::User(diff.get_user_name().to_owned()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(ModifyDatabasePrivilegesError::UserValidationError)?;
+
+    match unsafe_database_exists(diff.get_database_name(), connection).await {
+        Ok(false) => return Err(ModifyDatabasePrivilegesError::DatabaseDoesNotExist),
+        Err(e) => return Err(ModifyDatabasePrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    match unsafe_user_exists(diff.get_user_name(), connection).await {
+        Ok(false) => return Err(ModifyDatabasePrivilegesError::UserDoesNotExist),
+        Err(e) => return Err(ModifyDatabasePrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    validate_diff(diff, connection).await
+}
+
 /// Uses the result of [`diff_privileges`] to modify privileges in the database.
+///
+/// Every diff is validated and applied on its own, so some of them might be
+/// applied even if others fail, unless `atomic` is set.
+/// See [`apply_privilege_diffs_atomically`].
+///
+/// Unless `atomic` is set, the diffs are spread across all of the given connections.
 pub async fn apply_privilege_diffs(
     database_privilege_diffs: BTreeSet<DatabasePrivilegesDiff>,
+    atomic: bool,
     unix_user: &UnixUser,
-    connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    connections: &mut BulkConnections<'_>,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
+    progress: ProgressReporter,
 ) -> ModifyPrivilegesResponse {
-    let mut results: BTreeMap<(MySQLDatabase, MySQLUser), _> = BTreeMap::new();
-
-    for diff in database_privilege_diffs {
-        let key = (
-            diff.get_database_name().to_owned(),
-            diff.get_user_name().to_owned(),
-        );
-        if let Err(err) = validate_db_or_user_request(
-            &DbOrUser::Database(diff.get_database_name().to_owned()),
+    if atomic {
+        return apply_privilege_diffs_atomically(
+            database_privilege_diffs,
             unix_user,
+            connections.session(),
             group_denylist,
+            progress,
+        )
+        .await;
+    }
+
+    let unix_user = Arc::new(unix_user.clone());
+    let group_denylist = Arc::new(group_denylist.clone());
+    connections
+        .process(
+            database_privilege_diffs.into_iter().collect(),
+            &progress,
+            |diff| {
+                let (database_name, user_name) = privilege_diff_key(diff);
+                format!("{database_name}: {user_name}")
+            },
+            |diff, connection| {
+                let unix_user = Arc::clone(&unix_user);
+                let group_denylist = Arc::clone(&group_denylist);
+                Box::pin(async move {
+                    let result =
+                        apply_privilege_diff(&diff, &unix_user, connection, &group_denylist).await;
+                    (privilege_diff_key(&diff), result)
+                })
+            },
         )
-        .map_err(ModifyDatabasePrivilegesError::UserValidationError)
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Validates and applies a single diff.
+async fn apply_privilege_diff(
+    diff: &DatabasePrivilegesDiff,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyDatabasePrivilegesError> {
+    validate_privilege_diff_request(diff, unix_user, connection, group_denylist).await?;
+
+    unsafe_apply_privilege_diff(diff, connection)
+        .await
+        .map_err(|e| ModifyDatabasePrivilegesError::MySqlError(e.to_string()))
+}
+
+#[inline]
+fn privilege_diff_key(diff: &DatabasePrivilegesDiff) -> (MySQLDatabase, MySQLUser) {
+    (
+        diff.get_database_name().to_owned(),
+        diff.get_user_name().to_owned(),
+    )
+}
+
+/// Validates every diff before applying any of them, and then applies them all
+/// in a single transaction.
+///
+/// If any diff fails, nothing is applied. The failing diffs get their own error,
+/// and every other diff gets [`ModifyDatabasePrivilegesError::AtomicRequestFailed`].
+///
+/// NOTE: The grant tables of `MariaDB` use the non-transactional Aria engine, so a
+///       rollback there can not undo diffs that were already applied. Validating
+///       every diff up front still catches everything but `MySQL` errors.
+async fn apply_privilege_diffs_atomically(
+    database_privilege_diffs: BTreeSet<DatabasePrivilegesDiff>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+    progress: ProgressReporter,
+) -> ModifyPrivilegesResponse {
+    /// Fail every diff, with the given errors for some of them, and `default_error` for the rest.
+    fn fail_all(
+        diffs: &BTreeSet<DatabasePrivilegesDiff>,
+        mut errors: BTreeMap<(MySQLDatabase, MySQLUser), ModifyDatabasePrivilegesError>,
+        default_error: &ModifyDatabasePrivilegesError,
+    ) -> ModifyPrivilegesResponse {
+        diffs
+            .iter()
+            .map(|diff| {
+                let key = privilege_diff_key(diff);
+                let error = errors.remove(&key).unwrap_or_else(|| default_error.clone());
+                (key, Err(error))
+            })
+            .collect()
+    }
+
+    let mut errors = BTreeMap::new();
+    for diff in &database_privilege_diffs {
+        if let Err(err) =
+            validate_privilege_diff_request(diff, unix_user, connection, group_denylist).await
         {
-            results.insert(key, Err(err));
-            continue;
+            errors.insert(privilege_diff_key(diff), err);
         }
+    }
 
-        if let Err(err) = validate_db_or_user_request(
-            &DbOrUser::User(diff.get_user_name().to_owned()),
-            unix_user,
-            group_denylist,
-        )
-        .map_err(ModifyDatabasePrivilegesError::UserValidationError)
+    if !errors.is_empty() {
+        return fail_all(
+            &database_privilege_diffs,
+            errors,
+            &ModifyDatabasePrivilegesError::AtomicRequestFailed,
+        );
+    }
+
+    let mut transaction = match begin_transaction(connection).await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            tracing::error!("Failed to start transaction for privilege diffs: {}", e);
+            return fail_all(
+                &database_privilege_diffs,
+                BTreeMap::new(),
+                &ModifyDatabasePrivilegesError::MySqlError(e.to_string()),
+            );
+        }
+    };
+
+    for (done, diff) in database_privilege_diffs.iter().enumerate() {
+        let (database_name, user_name) = privilege_diff_key(diff);
+        progress.report(done, &format!("{database_name}: {user_name}"));
+
+        if let Err(e) = unsafe_apply_privilege_diff(diff, &mut transaction).await {
+            if let Err(e) = transaction.rollback().await {
+                tracing::error!("Failed to roll back privilege diffs: {}", e);
+            }
+            let errors = BTreeMap::from([(
+                privilege_diff_key(diff),
+                ModifyDatabasePrivilegesError::MySqlError(e.to_string()),
+            )]);
+            return fail_all(
+                &database_privilege_diffs,
+                errors,
+                &ModifyDatabasePrivilegesError::AtomicRequestFailed,
+            );
+        }
+    }
+
+    if let Err(e) = transaction.commit().await {
+        tracing::error!("Failed to commit privilege diffs: {}", e);
+        return fail_all(
+            &database_privilege_diffs,
+            BTreeMap::new(),
+            &ModifyDatabasePrivilegesError::MySqlError(e.to_string()),
+        );
+    }
+
+    database_privilege_diffs
+        .iter()
+        .map(|diff| (privilege_diff_key(diff), Ok(())))
+        .collect()
+}
+
+/// The format of a single entry in `$.Restrictions` of `mysql.user.User_attributes`.
+#[derive(Deserialize)]
+struct MySqlUserRestriction {
+    #[serde(rename = "Database")]
+    database: String,
+    #[serde(rename = "Privileges")]
+    privileges: Vec<String>,
+}
+
+/// Lists the partial revokes for the given users.
+///
+/// Users that the unix user is not authorized to manage, or that have no partial
+/// revokes, are silently left out of the response, as this is only used to show warnings.
+pub async fn list_partial_revokes(
+    db_users: Vec<MySQLUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListPartialRevokesResponse {
+    let mut results = BTreeMap::new();
+
+    if !backend_capabilities.supports_partial_revokes {
+        return results;
+    }
+
+    for db_user in db_users {
+        if validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+            .is_err()
         {
-            results.insert(key, Err(err));
             continue;
         }
 
-        match unsafe_database_exists(diff.get_database_name(), connection).await {
-            Ok(false) => {
-                results.insert(
-                    key,
-                    Err(ModifyDatabasePrivilegesError::DatabaseDoesNotExist),
+        let restrictions: Result<Option<Option<String>>, sqlx::Error> =
+            sqlx::query_scalar(indoc! {r#"
+            SELECT CAST(JSON_EXTRACT(`User_attributes`, '$.Restrictions') AS CHAR)
+            FROM `mysql`.`user`
+            WHERE `User` = ?
+            AND `Host` = '%'
+        "#})
+            .bind(db_user.as_str())
+            .fetch_optional(&mut *connection)
+            .await;
+
+        let restrictions = match restrictions {
+            Ok(Some(Some(restrictions))) => restrictions,
+            Ok(_) => continue,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to get partial revokes for database user '{}': {:?}",
+                    &db_user,
+                    err
                 );
                 continue;
             }
-            Err(e) => {
+        };
+
+        match serde_json::from_str::<Vec<MySqlUserRestriction>>(&restrictions) {
+            Ok(restrictions) if !restrictions.is_empty() => {
                 results.insert(
-                    key,
-                    Err(ModifyDatabasePrivilegesError::MySqlError(e.to_string())),
+                    db_user,
+                    restrictions
+                        .into_iter()
+                        .map(|restriction| PartialRevoke {
+                            database: restriction.database.into(),
+                            privileges: restriction.privileges,
+                        })
+                        .collect(),
+                );
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to parse partial revokes for database user '{}': {}",
+                    &db_user,
+                    err
                 );
-                continue;
             }
-            Ok(true) => {}
         }
+    }
 
-        match unsafe_user_exists(diff.get_user_name(), connection).await {
-            Ok(false) => {
-                results.insert(key, Err(ModifyDatabasePrivilegesError::UserDoesNotExist));
-                continue;
+    results
+}
+
+// NOTE: rows with `%` or `\` in `Db` come from manual grants on database name
+//       patterns, which never refer to a single database and are left alone.
+const ORPHANED_PRIVILEGES_QUERY: &str = indoc! {r"
+    SELECT `Db`, `User`, `database_exists`, `user_exists` FROM (
+      SELECT DISTINCT
+        `db`.`Db`,
+        `db`.`User`,
+        EXISTS(
+          SELECT 1 FROM `information_schema`.`SCHEMATA`
+          WHERE CAST(`SCHEMA_NAME` AS CHAR(64)) = `db`.`Db`
+        ) AS `database_exists`,
+        EXISTS(
+          SELECT 1 FROM `mysql`.`user`
+          WHERE `user`.`User` = `db`.`User`
+        ) AS `user_exists`
+      FROM `mysql`.`db`
+      WHERE `db`.`User` != ''
+        AND `db`.`Db` NOT LIKE '%\\%%'
+        AND `db`.`Db` NOT LIKE '%\\\\%'
+    ) AS `privileges`
+    WHERE NOT `database_exists` OR NOT `user_exists`
+"};
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Get all privilege rows that refer to a database or user that does not exist,
+/// regardless of who owns them.
+pub(crate) async fn unsafe_list_orphaned_privileges(
+    connection: &mut MySqlConnection,
+) -> Result<Vec<OrphanedPrivilege>, sqlx::Error> {
+    let result = sqlx::query(ORPHANED_PRIVILEGES_QUERY)
+        .fetch_all(connection)
+        .await
+        .and_then(|rows| {
+            rows.iter()
+                .map(|row| {
+                    Ok(OrphanedPrivilege {
+                        database: try_get_with_binary_fallback(row, "Db")?.into(),
+                        user: try_get_with_binary_fallback(row, "User")?.into(),
+                        database_exists: row.try_get("database_exists")?,
+                        user_exists: row.try_get("user_exists")?,
+                    })
+                })
+                .collect()
+        });
+
+    if let Err(e) = &result {
+        tracing::error!("Failed to list orphaned privileges: {}", e);
+    }
+
+    result
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+pub(crate) async fn unsafe_delete_privilege_row(
+    database_name: &MySQLDatabase,
+    user_name: &MySQLUser,
+    connection: &mut MySqlConnection,
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query("DELETE FROM `db` WHERE `Db` = ? AND `User` = ?")
+        .bind(database_name.as_str())
+        .bind(user_name.as_str())
+        .execute(connection)
+        .await
+        .map(|_| ());
+
+    if let Err(e) = &result {
+        tracing::error!(
+            "Failed to delete privileges for '{}.{}': {}",
+            &database_name,
+            &user_name,
+            e
+        );
+    }
+
+    result
+}
+
+/// An orphaned privilege row belongs to the owner of either the database or the user,
+/// since one of them is gone.
+fn validate_orphaned_privilege_request(
+    database_name: &MySQLDatabase,
+    user_name: &MySQLUser,
+    unix_user: &UnixUser,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ValidationError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(database_name.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .or_else(|err| {
+        validate_db_or_user_request(
+            &DbOrUser::User(user_name.clone()),
+            unix_user,
+            group_denylist,
+        )
+        .map_err(|_| err)
+    })
+}
+
+/// List the orphaned privilege rows that are owned by the unix user.
+pub async fn list_orphaned_privileges(
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListOrphanedPrivilegesResponse {
+    unsafe_list_orphaned_privileges(connection)
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .filter(|row| {
+                    validate_orphaned_privilege_request(
+                        &row.database,
+                        &row.user,
+                        unix_user,
+                        group_denylist,
+                    )
+                    .is_ok()
+                })
+                .collect()
+        })
+        .map_err(|e| ListOrphanedPrivilegesError::MySqlError(e.to_string()))
+}
+
+/// Delete the given privilege rows, as long as they are still orphaned.
+pub async fn delete_orphaned_privileges(
+    rows: DeleteOrphanedPrivilegesRequest,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> DeleteOrphanedPrivilegesResponse {
+    let mut results = BTreeMap::new();
+
+    for (database_name, user_name) in rows {
+        let result: Result<(), DeleteOrphanedPrivilegeError> = async {
+            validate_orphaned_privilege_request(
+                &database_name,
+                &user_name,
+                unix_user,
+                group_denylist,
+            )?;
+
+            let row = unsafe_get_database_privileges_for_db_user_pair(
+                &database_name,
+                &user_name,
+                connection,
+            )
+            .await
+            .map_err(|e| DeleteOrphanedPrivilegeError::MySqlError(e.to_string()))?;
+            if row.is_none() {
+                return Err(DeleteOrphanedPrivilegeError::RowDoesNotExist);
             }
-            Err(e) => {
-                results.insert(
-                    key,
-                    Err(ModifyDatabasePrivilegesError::MySqlError(e.to_string())),
-                );
-                continue;
+
+            let database_exists = unsafe_database_exists(&database_name, connection)
+                .await
+                .map_err(|e| DeleteOrphanedPrivilegeError::MySqlError(e.to_string()))?;
+            let user_exists = unsafe_user_exists(&user_name, connection)
+                .await
+                .map_err(|e| DeleteOrphanedPrivilegeError::MySqlError(e.to_string()))?;
+            if database_exists && user_exists {
+                return Err(DeleteOrphanedPrivilegeError::RowIsNotOrphaned);
             }
-            Ok(true) => {}
-        }
 
-        if let Err(err) = validate_diff(&diff, connection).await {
-            results.insert(key, Err(err));
-            continue;
+            unsafe_delete_privilege_row(&database_name, &user_name, connection)
+                .await
+                .map_err(|e| DeleteOrphanedPrivilegeError::MySqlError(e.to_string()))
         }
+        .await;
+
+        results.insert((database_name, user_name), result);
+    }
+
+    results
+}
+
+/// Finds the owned databases where no existing user has a single privilege,
+/// by joining only the privilege rows that grant something to a user that exists.
+static UNUSED_DATABASES_QUERY: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        indoc! {r"
+            SELECT CAST(`SCHEMATA`.`SCHEMA_NAME` AS CHAR(64)) AS `database`
+            FROM `information_schema`.`SCHEMATA`
+            LEFT OUTER JOIN `mysql`.`db`
+              ON `SCHEMATA`.`SCHEMA_NAME` = `db`.`Db`
+              AND 'Y' IN ({})
+              AND EXISTS(SELECT 1 FROM `mysql`.`user` WHERE `user`.`User` = `db`.`User`)
+            WHERE `SCHEMATA`.`SCHEMA_NAME` NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
+              AND `SCHEMATA`.`SCHEMA_NAME` REGEXP ?
+            GROUP BY `SCHEMATA`.`SCHEMA_NAME`
+            HAVING COUNT(`db`.`User`) = 0
+        "},
+        DATABASE_PRIVILEGE_FIELDS
+            .iter()
+            .skip(2)
+            .map(|field| format!("`db`.{}", quote_identifier(field)))
+            .join(","),
+    )
+});
 
-        let result = unsafe_apply_privilege_diff(&diff, connection)
+/// List the databases owned by the unix user that no existing user has any privileges on.
+pub async fn list_unused_databases(
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListUnusedDatabasesResponse {
+    let unused_databases: BTreeSet<MySQLDatabase> =
+        sqlx::query_scalar::<_, String>(&UNUSED_DATABASES_QUERY)
+            .bind(create_user_group_matching_regex(unix_user, group_denylist))
+            .fetch_all(&mut *connection)
             .await
-            .map_err(|e| ModifyDatabasePrivilegesError::MySqlError(e.to_string()));
+            .map_err(|err| {
+                tracing::error!("Failed to list unused databases: {:?}", err);
+                ListUnusedDatabasesError::MySqlError(err.to_string())
+            })?
+            .into_iter()
+            .map(MySQLDatabase::from)
+            .collect();
 
-        results.insert(key, result);
+    if unused_databases.is_empty() {
+        return Ok(Vec::new());
     }
 
-    results
+    let databases = list_all_databases_for_user(
+        None,
+        unix_user,
+        &mut *connection,
+        backend_capabilities,
+        group_denylist,
+    )
+    .await?;
+
+    Ok(databases
+        .into_iter()
+        .filter(|database| unused_databases.contains(&database.database))
+        .collect())
 }
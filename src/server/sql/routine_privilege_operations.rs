@@ -0,0 +1,416 @@
+//! Stored routine privilege operations
+//!
+//! This module contains functions for listing the stored procedures and functions
+//! in a database, and for listing and modifying the privileges that users have on them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use indoc::indoc;
+use itertools::Itertools;
+use sqlx::{MySqlConnection, mysql::MySqlRow, prelude::*};
+
+use crate::{
+    core::{
+        common::UnixUser,
+        database_privileges::DatabasePrivilegeChange,
+        protocol::{
+            ListDatabasesError, ListPrivilegesError, ListRoutinePrivilegesResponse,
+            ListRoutinesResponse, ModifyRoutinePrivilegesError, ModifyRoutinePrivilegesResponse,
+            request_validation::{GroupDenylist, validate_db_or_user_request},
+        },
+        routine_privileges::{
+            RoutinePrivilegeKey, RoutinePrivilegeRow, RoutinePrivilegesDiff, RoutineType,
+            StoredRoutine,
+        },
+        types::{DbOrUser, MySQLDatabase, MySQLUser},
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        common::{create_user_group_matching_regex, try_get_with_binary_fallback},
+        sql::{
+            database_operations::unsafe_database_exists, uncached_query,
+            user_operations::unsafe_user_exists,
+        },
+    },
+};
+
+fn try_get_routine_type(row: &MySqlRow) -> Result<RoutineType, sqlx::Error> {
+    try_get_with_binary_fallback(row, "Routine_type")?
+        .parse::<RoutineType>()
+        .map_err(|e| sqlx::Error::Decode(e.into()))
+}
+
+impl FromRow<'_, MySqlRow> for StoredRoutine {
+    fn from_row(row: &MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(StoredRoutine {
+            db: try_get_with_binary_fallback(row, "Db")?.into(),
+            name: try_get_with_binary_fallback(row, "Routine_name")?,
+            routine_type: try_get_routine_type(row)?,
+        })
+    }
+}
+
+impl FromRow<'_, MySqlRow> for RoutinePrivilegeRow {
+    fn from_row(row: &MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(RoutinePrivilegeRow::new(
+            try_get_with_binary_fallback(row, "Db")?.into(),
+            try_get_with_binary_fallback(row, "Routine_name")?,
+            try_get_routine_type(row)?,
+            try_get_with_binary_fallback(row, "User")?.into(),
+        )
+        .with_privileges_from_mysql_set(&try_get_with_binary_fallback(row, "Proc_priv")?))
+    }
+}
+
+// NOTE: MariaDB also has packages, which muscl does not handle.
+const SELECT_ROUTINES_FOR_DATABASE_QUERY: &str = indoc! {r"
+    SELECT
+      `ROUTINE_SCHEMA` AS `Db`,
+      `ROUTINE_NAME` AS `Routine_name`,
+      `ROUTINE_TYPE` AS `Routine_type`
+    FROM `information_schema`.`ROUTINES`
+    WHERE `ROUTINE_SCHEMA` = ? AND `ROUTINE_TYPE` IN ('PROCEDURE', 'FUNCTION')
+"};
+
+const SELECT_ALL_ROUTINES_QUERY: &str = indoc! {r"
+    SELECT
+      `ROUTINE_SCHEMA` AS `Db`,
+      `ROUTINE_NAME` AS `Routine_name`,
+      `ROUTINE_TYPE` AS `Routine_type`
+    FROM `information_schema`.`ROUTINES`
+    WHERE `ROUTINE_SCHEMA` REGEXP ? AND `ROUTINE_TYPE` IN ('PROCEDURE', 'FUNCTION')
+"};
+
+const ROUTINE_EXISTS_QUERY: &str = indoc! {r"
+    SELECT EXISTS(
+      SELECT 1 FROM `information_schema`.`ROUTINES`
+      WHERE `ROUTINE_SCHEMA` = ? AND `ROUTINE_NAME` = ? AND `ROUTINE_TYPE` = ?
+    )
+"};
+
+// NOTE: only the rows for `'%'` are listed, as those are the ones that
+//       the `GRANT` and `REVOKE` statements below change.
+const SELECT_ROUTINE_PRIVILEGES_FOR_DATABASE_QUERY: &str = indoc! {r"
+    SELECT
+      `Db`,
+      `Routine_name`,
+      CAST(`Routine_type` AS CHAR) AS `Routine_type`,
+      `User`,
+      CAST(`Proc_priv` AS CHAR) AS `Proc_priv`
+    FROM `procs_priv`
+    WHERE `Db` = ? AND `Host` = '%' AND `Routine_type` IN ('PROCEDURE', 'FUNCTION')
+"};
+
+const SELECT_ALL_ROUTINE_PRIVILEGES_QUERY: &str = indoc! {r"
+    SELECT
+      `Db`,
+      `Routine_name`,
+      CAST(`Routine_type` AS CHAR) AS `Routine_type`,
+      `User`,
+      CAST(`Proc_priv` AS CHAR) AS `Proc_priv`
+    FROM `procs_priv`
+    WHERE `Db` REGEXP ? AND `Host` = '%' AND `Routine_type` IN ('PROCEDURE', 'FUNCTION')
+"};
+
+const SELECT_ROUTINE_PRIVILEGE_ROW_QUERY: &str = indoc! {r"
+    SELECT
+      `Db`,
+      `Routine_name`,
+      CAST(`Routine_type` AS CHAR) AS `Routine_type`,
+      `User`,
+      CAST(`Proc_priv` AS CHAR) AS `Proc_priv`
+    FROM `procs_priv`
+    WHERE `Db` = ? AND `Routine_name` = ? AND `Routine_type` = ? AND `User` = ? AND `Host` = '%'
+"};
+
+/// Checks that the unix user owns the database, and that it exists.
+async fn validate_database_exists(
+    database_name: &MySQLDatabase,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ListDatabasesError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(database_name.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(ListDatabasesError::ValidationError)?;
+
+    match unsafe_database_exists(database_name, connection).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ListDatabasesError::DatabaseDoesNotExist),
+        Err(e) => Err(ListDatabasesError::MySqlError(e.to_string())),
+    }
+}
+
+/// Get the stored procedures and functions in the given databases,
+/// or in all databases owned by the unix user.
+pub async fn list_routines(
+    database_names: Option<Vec<MySQLDatabase>>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListRoutinesResponse {
+    let Some(database_names) = database_names else {
+        let result = sqlx::query_as::<_, StoredRoutine>(SELECT_ALL_ROUTINES_QUERY)
+            .bind(create_user_group_matching_regex(unix_user, group_denylist))
+            .fetch_all(connection)
+            .await;
+
+        return match result {
+            Ok(routines) => routines
+                .into_iter()
+                .into_group_map_by(|routine| routine.db.clone())
+                .into_iter()
+                .map(|(db, routines)| (db, Ok(routines)))
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to list all stored routines: {:?}", e);
+                BTreeMap::new()
+            }
+        };
+    };
+
+    let mut results = BTreeMap::new();
+
+    for database_name in database_names {
+        if let Err(err) =
+            validate_database_exists(&database_name, unix_user, connection, group_denylist).await
+        {
+            results.insert(database_name, Err(err));
+            continue;
+        }
+
+        let result = sqlx::query_as::<_, StoredRoutine>(SELECT_ROUTINES_FOR_DATABASE_QUERY)
+            .bind(database_name.as_str())
+            .fetch_all(&mut *connection)
+            .await
+            .map_err(|e| ListDatabasesError::MySqlError(e.to_string()));
+
+        if let Err(e) = &result {
+            tracing::error!(
+                "Failed to list stored routines in '{}': {:?}",
+                &database_name,
+                e
+            );
+        }
+
+        results.insert(database_name, result);
+    }
+
+    results
+}
+
+fn filter_routine_privilege_rows_by_user(
+    rows: Vec<RoutinePrivilegeRow>,
+    user_filter: Option<&MySQLUser>,
+) -> Vec<RoutinePrivilegeRow> {
+    match user_filter {
+        Some(user) => rows.into_iter().filter(|row| &row.user == user).collect(),
+        None => rows,
+    }
+}
+
+/// Get the routine privileges on the given databases, or on all databases
+/// owned by the unix user, optionally only for a single user.
+pub async fn get_routine_privilege_data(
+    database_names: Option<Vec<MySQLDatabase>>,
+    user_filter: Option<&MySQLUser>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListRoutinePrivilegesResponse {
+    let Some(database_names) = database_names else {
+        let result = sqlx::query_as::<_, RoutinePrivilegeRow>(SELECT_ALL_ROUTINE_PRIVILEGES_QUERY)
+            .bind(create_user_group_matching_regex(unix_user, group_denylist))
+            .fetch_all(connection)
+            .await;
+
+        return match result {
+            Ok(rows) => filter_routine_privilege_rows_by_user(rows, user_filter)
+                .into_iter()
+                .into_group_map_by(|row| row.db.clone())
+                .into_iter()
+                .map(|(db, rows)| (db, Ok(rows)))
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to get all routine privileges: {:?}", e);
+                BTreeMap::new()
+            }
+        };
+    };
+
+    let mut results = BTreeMap::new();
+
+    for database_name in database_names {
+        if let Err(err) = validate_db_or_user_request(
+            &DbOrUser::Database(database_name.clone()),
+            unix_user,
+            group_denylist,
+        )
+        .map_err(ListPrivilegesError::ValidationError)
+        {
+            results.insert(database_name, Err(err));
+            continue;
+        }
+
+        match unsafe_database_exists(&database_name, connection).await {
+            Ok(false) => {
+                results.insert(
+                    database_name,
+                    Err(ListPrivilegesError::DatabaseDoesNotExist),
+                );
+                continue;
+            }
+            Err(e) => {
+                results.insert(
+                    database_name,
+                    Err(ListPrivilegesError::MySqlError(e.to_string())),
+                );
+                continue;
+            }
+            Ok(true) => {}
+        }
+
+        let result =
+            sqlx::query_as::<_, RoutinePrivilegeRow>(SELECT_ROUTINE_PRIVILEGES_FOR_DATABASE_QUERY)
+                .bind(database_name.as_str())
+                .fetch_all(&mut *connection)
+                .await
+                .map(|rows| filter_routine_privilege_rows_by_user(rows, user_filter))
+                .map_err(|e| ListPrivilegesError::MySqlError(e.to_string()));
+
+        if let Err(e) = &result {
+            tracing::error!(
+                "Failed to get routine privileges for '{}': {:?}",
+                &database_name,
+                e
+            );
+        }
+
+        results.insert(database_name, result);
+    }
+
+    results
+}
+
+/// Checks that the unix user is allowed to apply the diff, and that it applies
+/// to the current privileges on the routine.
+async fn validate_routine_privilege_diff_request(
+    diff: &RoutinePrivilegesDiff,
+    key: &RoutinePrivilegeKey,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyRoutinePrivilegesError> {
+    validate_db_or_user_request(
+        &DbOrUser::Database(key.db.clone()),
+        unix_user,
+        group_denylist,
+    )
+    .map_err(ModifyRoutinePrivilegesError::DatabaseValidationError)?;
+
+    validate_db_or_user_request(&DbOrUser::User(key.user.clone()), unix_user, group_denylist)
+        .map_err(ModifyRoutinePrivilegesError::UserValidationError)?;
+
+    match unsafe_database_exists(&key.db, connection).await {
+        Ok(false) => return Err(ModifyRoutinePrivilegesError::DatabaseDoesNotExist),
+        Err(e) => return Err(ModifyRoutinePrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    match unsafe_user_exists(&key.user, connection).await {
+        Ok(false) => return Err(ModifyRoutinePrivilegesError::UserDoesNotExist),
+        Err(e) => return Err(ModifyRoutinePrivilegesError::MySqlError(e.to_string())),
+        Ok(true) => {}
+    }
+
+    // NOTE: privileges on a routine that has been dropped can still be revoked.
+    if diff
+        .changes()
+        .iter()
+        .any(|(_, change)| *change == DatabasePrivilegeChange::NoToYes)
+    {
+        let routine_exists = sqlx::query_scalar::<_, bool>(ROUTINE_EXISTS_QUERY)
+            .bind(key.db.as_str())
+            .bind(key.routine.as_str())
+            .bind(key.routine_type.sql_keyword())
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|e| ModifyRoutinePrivilegesError::MySqlError(e.to_string()))?;
+        if !routine_exists {
+            return Err(ModifyRoutinePrivilegesError::RoutineDoesNotExist);
+        }
+    }
+
+    let existing_row = sqlx::query_as::<_, RoutinePrivilegeRow>(SELECT_ROUTINE_PRIVILEGE_ROW_QUERY)
+        .bind(key.db.as_str())
+        .bind(key.routine.as_str())
+        .bind(key.routine_type.sql_keyword())
+        .bind(key.user.as_str())
+        .fetch_optional(&mut *connection)
+        .await
+        .map_err(|e| ModifyRoutinePrivilegesError::MySqlError(e.to_string()))?;
+
+    if diff.applies_to(existing_row.as_ref()) {
+        Ok(())
+    } else {
+        Err(ModifyRoutinePrivilegesError::DiffDoesNotApply)
+    }
+}
+
+/// Validates and applies a single diff, with `GRANT` and `REVOKE` statements.
+async fn apply_routine_privilege_diff(
+    diff: &RoutinePrivilegesDiff,
+    key: &RoutinePrivilegeKey,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> Result<(), ModifyRoutinePrivilegesError> {
+    validate_routine_privilege_diff_request(diff, key, unix_user, connection, group_denylist)
+        .await?;
+
+    for statement in diff.to_sql_statements() {
+        uncached_query(&statement)
+            .execute(&mut *connection)
+            .await
+            .map_err(|e| {
+                tracing::error!(
+                    "Failed to apply routine privilege diff for {}: {:?}",
+                    key,
+                    e
+                );
+                ModifyRoutinePrivilegesError::MySqlError(e.to_string())
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Uses the result of
+/// [`diff_routine_privileges`](crate::core::routine_privileges::diff_routine_privileges)
+/// to modify routine privileges in the database.
+///
+/// Every diff is validated and applied on its own, so some of them might be
+/// applied even if others fail.
+pub async fn apply_routine_privilege_diffs(
+    diffs: BTreeSet<RoutinePrivilegesDiff>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    _backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ModifyRoutinePrivilegesResponse {
+    let mut results = BTreeMap::new();
+
+    for diff in diffs {
+        let key = diff.key();
+        let result =
+            apply_routine_privilege_diff(&diff, &key, unix_user, connection, group_denylist).await;
+        results.insert(key, result);
+    }
+
+    results
+}
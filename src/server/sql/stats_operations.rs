@@ -0,0 +1,275 @@
+//! Aggregated statistics about the databases and users of a unix user, or of the whole server.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sqlx::{MySqlConnection, prelude::*};
+
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            AdminReport, AdminReportError, AdminReportResponse, PrefixStats, StatsError,
+            StatsResponse, request_validation::GroupDenylist,
+        },
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        common::{
+            create_user_group_matching_regex, find_name_owner, get_user_filtered_groups,
+            try_get_with_binary_fallback,
+        },
+        sql::user_operations::{
+            DB_USER_SELECT_STATEMENT_MARIADB, DB_USER_SELECT_STATEMENT_MYSQL, DatabaseUser,
+        },
+    },
+};
+
+const DATABASE_SIZES_QUERY: &str = r"
+  SELECT
+    CAST(`information_schema`.`SCHEMATA`.`SCHEMA_NAME` AS CHAR(64)) AS `database`,
+    CAST(IFNULL(
+      SUM(`information_schema`.`TABLES`.`DATA_LENGTH` + `information_schema`.`TABLES`.`INDEX_LENGTH`),
+      0
+    ) AS UNSIGNED INTEGER) AS `size_bytes`
+  FROM `information_schema`.`SCHEMATA`
+  LEFT OUTER JOIN `information_schema`.`TABLES`
+    ON `information_schema`.`SCHEMATA`.`SCHEMA_NAME` = `TABLES`.`TABLE_SCHEMA`
+  WHERE `information_schema`.`SCHEMATA`.`SCHEMA_NAME` NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
+    AND `information_schema`.`SCHEMATA`.`SCHEMA_NAME` REGEXP ?
+  GROUP BY `information_schema`.`SCHEMATA`.`SCHEMA_NAME`
+";
+
+/// Find which of the prefixes a name belongs to.
+///
+/// If several prefixes match, e.g. `foo` and `foo_bar` for `foo_bar_db`, the longest one wins.
+fn find_prefix<'a>(name: &str, prefixes: &'a [String]) -> Option<&'a String> {
+    prefixes
+        .iter()
+        .filter(|prefix| {
+            name.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.len() > 1 && rest.starts_with('_'))
+        })
+        .max_by_key(|prefix| prefix.len())
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Get the name and size of every database matching the regex.
+async fn unsafe_get_database_sizes(
+    name_regex: &str,
+    connection: &mut MySqlConnection,
+) -> Result<Vec<(String, u64)>, sqlx::Error> {
+    let result = sqlx::query(DATABASE_SIZES_QUERY)
+        .bind(name_regex)
+        .fetch_all(connection)
+        .await
+        .and_then(|rows| {
+            rows.iter()
+                .map(|row| {
+                    Ok((
+                        try_get_with_binary_fallback(row, "database")?,
+                        row.try_get::<u64, _>("size_bytes")?,
+                    ))
+                })
+                .collect()
+        });
+
+    if let Err(err) = &result {
+        tracing::error!("Failed to get database sizes: {:?}", err);
+    }
+
+    result
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+/// Get every database user matching the regex, without their databases.
+async fn unsafe_get_database_users(
+    name_regex: &str,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+) -> Result<Vec<DatabaseUser>, sqlx::Error> {
+    let result = sqlx::query_as::<_, DatabaseUser>(
+        &(if backend_capabilities.supports_json_priv_column {
+            DB_USER_SELECT_STATEMENT_MARIADB.to_string()
+        } else {
+            DB_USER_SELECT_STATEMENT_MYSQL.to_string()
+        } + "WHERE `user`.`User` REGEXP ?"),
+    )
+    .bind(name_regex)
+    .fetch_all(connection)
+    .await;
+
+    if let Err(err) = &result {
+        tracing::error!("Failed to get database users: {:?}", err);
+    }
+
+    result
+}
+
+fn stats_for<'s>(
+    result: &'s mut BTreeMap<String, PrefixStats>,
+    fallback: &'s mut Option<&mut PrefixStats>,
+    prefix: Option<&str>,
+) -> Option<&'s mut PrefixStats> {
+    match prefix {
+        Some(prefix) => Some(result.entry(prefix.to_string()).or_default()),
+        None => fallback.as_deref_mut(),
+    }
+}
+
+/// Add the databases and users to the stats of the prefix returned by `find_prefix`,
+/// or to `fallback` if there is none.
+fn aggregate_stats<'a>(
+    result: &mut BTreeMap<String, PrefixStats>,
+    mut fallback: Option<&mut PrefixStats>,
+    databases: &[(String, u64)],
+    users: &[DatabaseUser],
+    find_prefix: impl Fn(&str) -> Option<&'a str>,
+) {
+    for (database, size_bytes) in databases {
+        if let Some(stats) = stats_for(result, &mut fallback, find_prefix(database)) {
+            stats.databases += 1;
+            stats.size_bytes += size_bytes;
+        }
+    }
+
+    // NOTE: a user with accounts for several hosts is only counted once,
+    //       and only as locked if all of its accounts are locked.
+    let mut users_locked: BTreeMap<&str, bool> = BTreeMap::new();
+    for user in users {
+        users_locked
+            .entry(user.user.as_str())
+            .and_modify(|is_locked| *is_locked &= user.is_locked)
+            .or_insert(user.is_locked);
+    }
+
+    for (user, is_locked) in users_locked {
+        if let Some(stats) = stats_for(result, &mut fallback, find_prefix(user)) {
+            stats.users += 1;
+            if is_locked {
+                stats.locked_users += 1;
+            }
+        }
+    }
+}
+
+/// Count the databases, users, total database size and locked users for each prefix.
+pub async fn get_prefix_stats(
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> StatsResponse {
+    let mut prefixes = vec![unix_user.username.clone()];
+    prefixes.extend(get_user_filtered_groups(unix_user, group_denylist));
+
+    let name_regex = create_user_group_matching_regex(unix_user, group_denylist);
+
+    let databases = unsafe_get_database_sizes(&name_regex, &mut *connection)
+        .await
+        .map_err(|err| StatsError::MySqlError(err.to_string()))?;
+    let users = unsafe_get_database_users(&name_regex, &mut *connection, backend_capabilities)
+        .await
+        .map_err(|err| StatsError::MySqlError(err.to_string()))?;
+
+    let mut result: BTreeMap<String, PrefixStats> = prefixes
+        .iter()
+        .map(|prefix| (prefix.clone(), PrefixStats::default()))
+        .collect();
+    aggregate_stats(&mut result, None, &databases, &users, |name| {
+        find_prefix(name, &prefixes).map(String::as_str)
+    });
+
+    Ok(result)
+}
+
+/// Count the databases, users, total database size and locked users for every owner on the server.
+///
+/// This must only be used for admins.
+pub async fn get_admin_report(
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> AdminReportResponse {
+    let databases = unsafe_get_database_sizes(".*", &mut *connection)
+        .await
+        .map_err(|err| AdminReportError::MySqlError(err.to_string()))?;
+    let users = unsafe_get_database_users(".*", &mut *connection, backend_capabilities)
+        .await
+        .map_err(|err| AdminReportError::MySqlError(err.to_string()))?;
+
+    let owners: BTreeSet<String> = databases
+        .iter()
+        .map(|(database, _)| database.as_str())
+        .chain(users.iter().map(|user| user.user.as_str()))
+        .filter_map(|name| find_name_owner(name, group_denylist))
+        .collect();
+    let owners: Vec<String> = owners.into_iter().collect();
+
+    let mut prefixes = BTreeMap::new();
+    let mut unowned = PrefixStats::default();
+    aggregate_stats(
+        &mut prefixes,
+        Some(&mut unowned),
+        &databases,
+        &users,
+        |name| find_prefix(name, &owners).map(String::as_str),
+    );
+
+    Ok(AdminReport {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        prefixes,
+        unowned,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_prefix() {
+        let prefixes = vec!["foo".to_string(), "foo_bar".to_string(), "baz".to_string()];
+
+        assert_eq!(find_prefix("foo_db", &prefixes), Some(&prefixes[0]));
+        assert_eq!(find_prefix("foo_bar_db", &prefixes), Some(&prefixes[1]));
+        assert_eq!(find_prefix("baz_db", &prefixes), Some(&prefixes[2]));
+        assert_eq!(find_prefix("foo_", &prefixes), None);
+        assert_eq!(find_prefix("foobar_db", &prefixes), None);
+        assert_eq!(find_prefix("qux_db", &prefixes), None);
+    }
+
+    #[test]
+    fn test_aggregate_stats_counts_users_once() {
+        let user = |name: &str, host: &str, is_locked: bool| DatabaseUser {
+            user: name.into(),
+            host: host.to_string(),
+            has_password: true,
+            is_locked,
+            tls_requirement: Default::default(),
+            limits: Default::default(),
+            comment: None,
+            lock_reason: None,
+            current_connections: None,
+            total_connections: None,
+            databases: vec![],
+        };
+        let users = vec![
+            user("foo_a", "%", true),
+            user("foo_a", "localhost", false),
+            user("foo_b", "%", true),
+            user("foo_b", "localhost", true),
+        ];
+
+        let mut result = BTreeMap::new();
+        aggregate_stats(&mut result, None, &[], &users, |_| Some("foo"));
+
+        assert_eq!(result["foo"].users, 2);
+        assert_eq!(result["foo"].locked_users, 1);
+    }
+}
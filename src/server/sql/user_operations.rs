@@ -1,35 +1,42 @@
 use indoc::formatdoc;
 use itertools::Itertools;
 use std::collections::BTreeMap;
+use std::sync::LazyLock;
 
 use serde::{Deserialize, Serialize};
 
 use sqlx::MySqlConnection;
 use sqlx::prelude::*;
+use zeroize::Zeroizing;
 
 use crate::core::protocol::request_validation::GroupDenylist;
-use crate::core::protocol::request_validation::validate_db_or_user_request;
+use crate::core::protocol::request_validation::{ValidationError, validate_db_or_user_request};
 use crate::core::types::DbOrUser;
 use crate::{
     core::{
         common::UnixUser,
         database_privileges::DATABASE_PRIVILEGE_FIELDS,
         protocol::{
-            CreateUserError, CreateUsersResponse, DropUserError, DropUsersResponse,
-            ListAllUsersError, ListAllUsersResponse, ListUsersError, ListUsersResponse,
-            LockUserError, LockUsersResponse, SetPasswordError, SetUserPasswordResponse,
-            UnlockUserError, UnlockUsersResponse,
+            AdminListError, AdminListUsersResponse, CreateUserError, CreateUsersRequest,
+            CreateUsersResponse, DropUserError, DropUsersResponse, GrantRoleError,
+            GrantRolesResponse, ListAllUsersError, ListAllUsersResponse, ListUsersError,
+            ListUsersResponse, LockUserError, LockUsersRequest, LockUsersResponse, RequireSslError,
+            RequireSslResponse, SetPasswordError, SetUserCommentError, SetUserCommentResponse,
+            SetUserLimitsError, SetUserLimitsRequest, SetUserLimitsResponse,
+            SetUserPasswordResponse, TlsRequirement, UnlockUserError, UnlockUsersRequest,
+            UnlockUsersResponse, UserLimits,
         },
-        types::MySQLUser,
+        types::{MySQLUser, SecretString},
     },
     server::{
-        common::{create_user_group_matching_regex, try_get_with_binary_fallback},
-        sql::quote_literal,
+        backend_capabilities::BackendCapabilities,
+        common::{create_user_group_matching_regex, find_name_owner, try_get_with_binary_fallback},
+        sql::{push_quoted_secret_literal, quote_literal, uncached_query},
     },
 };
 
 // NOTE: this function is unsafe because it does no input validation.
-pub(super) async fn unsafe_user_exists(
+pub(crate) async fn unsafe_user_exists(
     db_user: &str,
     connection: &mut MySqlConnection,
 ) -> Result<bool, sqlx::Error> {
@@ -58,7 +65,7 @@ pub async fn complete_user_name(
     user_prefix: String,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> Vec<MySQLUser> {
     let result = sqlx::query(
@@ -95,15 +102,15 @@ pub async fn complete_user_name(
 }
 
 pub async fn create_database_users(
-    db_users: Vec<MySQLUser>,
+    request: CreateUsersRequest,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> CreateUsersResponse {
     let mut results = BTreeMap::new();
 
-    for db_user in db_users {
+    for db_user in request.users {
         if let Err(err) =
             validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
                 .map_err(CreateUserError::ValidationError)
@@ -124,11 +131,20 @@ pub async fn create_database_users(
             _ => {}
         }
 
-        let result = sqlx::query(format!("CREATE USER {}@'%'", quote_literal(&db_user),).as_str())
-            .execute(&mut *connection)
-            .await
-            .map(|_| ())
-            .map_err(|err| CreateUserError::MySqlError(err.to_string()));
+        if request.locked && !backend_capabilities.supports_account_locking {
+            results.insert(db_user, Err(CreateUserError::AccountLockingNotSupported));
+            continue;
+        }
+
+        let result = uncached_query(&format!(
+            "CREATE USER {}@'%'{}",
+            quote_literal(&db_user),
+            if request.locked { " ACCOUNT LOCK" } else { "" },
+        ))
+        .execute(&mut *connection)
+        .await
+        .map(|_| ())
+        .map_err(|err| CreateUserError::MySqlError(err.to_string()));
 
         if let Err(err) = &result {
             tracing::error!("Failed to create database user '{}': {:?}", &db_user, err);
@@ -144,7 +160,7 @@ pub async fn drop_database_users(
     db_users: Vec<MySQLUser>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> DropUsersResponse {
     let mut results = BTreeMap::new();
@@ -170,10 +186,8 @@ pub async fn drop_database_users(
             _ => {}
         }
 
-        let result = sqlx::query(format!("DROP USER {}@'%'", quote_literal(&db_user),).as_str())
-            .execute(&mut *connection)
+        let result = unsafe_drop_user_with_privileges(&db_user, &mut *connection)
             .await
-            .map(|_| ())
             .map_err(|err| DropUserError::MySqlError(err.to_string()));
 
         if let Err(err) = &result {
@@ -186,12 +200,36 @@ pub async fn drop_database_users(
     results
 }
 
+/// Drop a user along with its rows in `mysql.db`, returning the number of rows removed.
+///
+/// NOTE: `DROP USER` commits implicitly, and the grant tables of `MariaDB` are not
+///       transactional, so the two statements are not run in a transaction. The privilege
+///       rows stay deleted if dropping the user fails, which leaves the user without
+///       privileges, and that is harmless.
+// NOTE: this function is unsafe because it does no input validation.
+async fn unsafe_drop_user_with_privileges(
+    db_user: &MySQLUser,
+    connection: &mut MySqlConnection,
+) -> Result<u64, sqlx::Error> {
+    let removed_privileges = sqlx::query("DELETE FROM `db` WHERE `User` = ? AND `Host` = '%'")
+        .bind(db_user.as_str())
+        .execute(&mut *connection)
+        .await?
+        .rows_affected();
+
+    uncached_query(&format!("DROP USER {}@'%'", quote_literal(db_user)))
+        .execute(&mut *connection)
+        .await?;
+
+    Ok(removed_privileges)
+}
+
 pub async fn set_password_for_database_user(
     db_user: &MySQLUser,
-    password: &str,
+    password: &SecretString,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    _db_is_mariadb: bool,
+    _backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> SetUserPasswordResponse {
     validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
@@ -203,23 +241,162 @@ pub async fn set_password_for_database_user(
         _ => {}
     }
 
-    let result = sqlx::query(
+    let statement_prefix = format!("ALTER USER {}@'%' IDENTIFIED BY ", quote_literal(db_user));
+    let mut statement = Zeroizing::new(String::with_capacity(
+        statement_prefix.len() + 2 * password.len() + 2,
+    ));
+    statement.push_str(&statement_prefix);
+    push_quoted_secret_literal(&mut statement, password);
+
+    let result = uncached_query(&statement)
+        .execute(&mut *connection)
+        .await
+        .map(|_| ())
+        .map_err(|err| SetPasswordError::MySqlError(err.to_string()));
+
+    if result.is_err() {
+        tracing::error!(
+            "Failed to set password for database user '{}': <REDACTED>",
+            &db_user,
+        );
+    }
+
+    result
+}
+
+pub async fn set_comment_for_database_user(
+    db_user: &MySQLUser,
+    comment: &str,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> SetUserCommentResponse {
+    validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+        .map_err(SetUserCommentError::ValidationError)?;
+
+    if !backend_capabilities.supports_user_comments {
+        return Err(SetUserCommentError::UserCommentsNotSupported);
+    }
+
+    match unsafe_user_exists(db_user, &mut *connection).await {
+        Ok(false) => return Err(SetUserCommentError::UserDoesNotExist),
+        Err(err) => return Err(SetUserCommentError::MySqlError(err.to_string())),
+        _ => {}
+    }
+
+    // `COMMENT` is merged into the `metadata` of the user attributes,
+    // and a JSON null removes the comment from the metadata again.
+    let query = if comment.is_empty() {
         format!(
-            "ALTER USER {}@'%' IDENTIFIED BY {}",
+            r#"ALTER USER {}@'%' ATTRIBUTE '{{"comment": null}}'"#,
             quote_literal(db_user),
-            quote_literal(password).as_str(),
         )
-        .as_str(),
-    )
+    } else {
+        format!(
+            "ALTER USER {}@'%' COMMENT {}",
+            quote_literal(db_user),
+            quote_literal(comment),
+        )
+    };
+
+    let result = uncached_query(&query)
+        .execute(&mut *connection)
+        .await
+        .map(|_| ())
+        .map_err(|err| SetUserCommentError::MySqlError(err.to_string()));
+
+    if let Err(err) = &result {
+        tracing::error!(
+            "Failed to set comment for database user '{}': {:?}",
+            &db_user,
+            err
+        );
+    }
+
+    result
+}
+
+pub async fn set_tls_requirement_for_database_user(
+    db_user: &MySQLUser,
+    requirement: TlsRequirement,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> RequireSslResponse {
+    validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+        .map_err(RequireSslError::ValidationError)?;
+
+    match unsafe_user_exists(db_user, &mut *connection).await {
+        Ok(false) => return Err(RequireSslError::UserDoesNotExist),
+        Err(err) => return Err(RequireSslError::MySqlError(err.to_string())),
+        _ => {}
+    }
+
+    let result = uncached_query(&format!(
+        "ALTER USER {}@'%' {}",
+        quote_literal(db_user),
+        requirement.require_clause(),
+    ))
     .execute(&mut *connection)
     .await
     .map(|_| ())
-    .map_err(|err| SetPasswordError::MySqlError(err.to_string()));
+    .map_err(|err| RequireSslError::MySqlError(err.to_string()));
 
-    if result.is_err() {
+    if let Err(err) = &result {
         tracing::error!(
-            "Failed to set password for database user '{}': <REDACTED>",
+            "Failed to set TLS requirement for database user '{}': {:?}",
+            &db_user,
+            err
+        );
+    }
+
+    result
+}
+
+pub async fn set_limits_for_database_user(
+    request: SetUserLimitsRequest,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+) -> SetUserLimitsResponse {
+    let db_user = &request.user;
+    validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+        .map_err(SetUserLimitsError::ValidationError)?;
+
+    match unsafe_user_exists(db_user, &mut *connection).await {
+        Ok(false) => return Err(SetUserLimitsError::UserDoesNotExist),
+        Err(err) => return Err(SetUserLimitsError::MySqlError(err.to_string())),
+        _ => {}
+    }
+
+    let limits = [
+        ("MAX_QUERIES_PER_HOUR", request.max_queries_per_hour),
+        ("MAX_UPDATES_PER_HOUR", request.max_updates_per_hour),
+    ]
+    .into_iter()
+    .filter_map(|(option, limit)| limit.map(|limit| format!("{option} {limit}")))
+    .join(" ");
+
+    if limits.is_empty() {
+        return Ok(());
+    }
+
+    let result = uncached_query(&format!(
+        "ALTER USER {}@'%' WITH {}",
+        quote_literal(db_user),
+        limits,
+    ))
+    .execute(&mut *connection)
+    .await
+    .map(|_| ())
+    .map_err(|err| SetUserLimitsError::MySqlError(err.to_string()));
+
+    if let Err(err) = &result {
+        tracing::error!(
+            "Failed to set limits for database user '{}': {:?}",
             &db_user,
+            err
         );
     }
 
@@ -244,12 +421,12 @@ const DATABASE_USER_LOCK_STATUS_QUERY_MYSQL: &str = r"
 ";
 
 // NOTE: this function is unsafe because it does no input validation.
-async fn database_user_is_locked_unsafe(
+pub(crate) async fn database_user_is_locked_unsafe(
     db_user: &str,
     connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
+    backend_capabilities: &BackendCapabilities,
 ) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query(if db_is_mariadb {
+    let result = sqlx::query(if backend_capabilities.supports_json_priv_column {
         DATABASE_USER_LOCK_STATUS_QUERY_MARIADB
     } else {
         DATABASE_USER_LOCK_STATUS_QUERY_MYSQL
@@ -271,15 +448,172 @@ async fn database_user_is_locked_unsafe(
     result
 }
 
-pub async fn lock_database_users(
+pub async fn grant_role_to_database_users(
+    role: MySQLUser,
     db_users: Vec<MySQLUser>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> GrantRolesResponse {
+    let role_error = if backend_capabilities.is_mariadb() || !backend_capabilities.supports_roles {
+        Some(GrantRoleError::RolesNotSupported)
+    } else if let Err(err) =
+        validate_db_or_user_request(&DbOrUser::User(role.clone()), unix_user, group_denylist)
+    {
+        Some(GrantRoleError::RoleValidationError(err))
+    } else {
+        match unsafe_user_exists(&role, &mut *connection).await {
+            Ok(true) => None,
+            Ok(false) => Some(GrantRoleError::RoleDoesNotExist),
+            Err(err) => Some(GrantRoleError::MySqlError(err.to_string())),
+        }
+    };
+
+    if let Some(err) = role_error {
+        return db_users
+            .into_iter()
+            .map(|db_user| (db_user, Err(err.clone())))
+            .collect();
+    }
+
+    let mut results = BTreeMap::new();
+
+    for db_user in db_users {
+        if let Err(err) =
+            validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
+                .map_err(GrantRoleError::ValidationError)
+        {
+            results.insert(db_user, Err(err));
+            continue;
+        }
+
+        match unsafe_user_exists(&db_user, &mut *connection).await {
+            Ok(true) => {}
+            Ok(false) => {
+                results.insert(db_user, Err(GrantRoleError::UserDoesNotExist));
+                continue;
+            }
+            Err(err) => {
+                results.insert(db_user, Err(GrantRoleError::MySqlError(err.to_string())));
+                continue;
+            }
+        }
+
+        let result = uncached_query(&format!(
+            "GRANT {}@'%' TO {}@'%'",
+            quote_literal(&role),
+            quote_literal(&db_user),
+        ))
+        .execute(&mut *connection)
+        .await
+        .map(|_| ())
+        .map_err(|err| GrantRoleError::MySqlError(err.to_string()));
+
+        if let Err(err) = &result {
+            tracing::error!(
+                "Failed to grant role '{}' to database user '{}': {:?}",
+                &role,
+                &db_user,
+                err
+            );
+        }
+
+        results.insert(db_user, result);
+    }
+
+    results
+}
+
+// NOTE: this function is unsafe because it does no input validation.
+async fn unsafe_list_users_with_prefix(
+    prefix: &str,
+    connection: &mut MySqlConnection,
+) -> Result<Vec<MySQLUser>, sqlx::Error> {
+    sqlx::query("SELECT `User` FROM `mysql`.`user` WHERE `User` REGEXP ? AND `Host` = '%'")
+        .bind(format!("^{prefix}_.+$"))
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| try_get_with_binary_fallback(row, "User").map(MySQLUser::from))
+        .collect()
+}
+
+/// Resolve every user under `prefix`, for the bulk variants of `lock-user` and `unlock-user`.
+///
+/// The prefix is validated as the name `<prefix>_`, so that the unix user can only
+/// resolve users under their own prefixes. If it fails, the error is returned
+/// together with that name, to be reported like the result for a single user.
+async fn users_with_prefix<E: From<ValidationError>>(
+    prefix: &str,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    group_denylist: &GroupDenylist,
+    mysql_error: fn(String) -> E,
+) -> Result<Vec<MySQLUser>, (MySQLUser, E)> {
+    let prefix_name = MySQLUser::from(format!("{prefix}_"));
+
+    if let Err(err) = validate_db_or_user_request(
+        &DbOrUser::User(prefix_name.clone()),
+        unix_user,
+        group_denylist,
+    ) {
+        return Err((prefix_name, err.into()));
+    }
+
+    let result = unsafe_list_users_with_prefix(prefix, connection).await;
+
+    if let Err(err) = &result {
+        tracing::error!(
+            "Failed to list database users with prefix '{}': {:?}",
+            prefix,
+            err
+        );
+    }
+
+    result.map_err(|err| (prefix_name, mysql_error(err.to_string())))
+}
+
+pub async fn lock_database_users(
+    request: LockUsersRequest,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> LockUsersResponse {
     let mut results = BTreeMap::new();
 
+    // The reason is stored in the `metadata` of the user attributes, next to the comment.
+    // NOTE: `quote_literal` does not escape backslashes, which would
+    //       let a reason ending in a backslash escape the closing quote.
+    let lock_attribute = request.reason.as_ref().map(|reason| {
+        let attribute = serde_json::json!({
+            "lock_reason": reason,
+            "locked_by": unix_user.username,
+        });
+        format!(" ATTRIBUTE {}", quote_literal(&attribute.to_string()))
+    });
+
+    let mut db_users = request.users;
+    if let Some(prefix) = &request.all_with_prefix {
+        match users_with_prefix(
+            prefix,
+            unix_user,
+            &mut *connection,
+            group_denylist,
+            LockUserError::MySqlError,
+        )
+        .await
+        {
+            Ok(users) => db_users.extend(users),
+            Err((name, err)) => {
+                results.insert(name, Err(err));
+            }
+        }
+    }
+    db_users.sort();
+    db_users.dedup();
+
     for db_user in db_users {
         if let Err(err) =
             validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
@@ -301,7 +635,8 @@ pub async fn lock_database_users(
             }
         }
 
-        match database_user_is_locked_unsafe(&db_user, &mut *connection, db_is_mariadb).await {
+        match database_user_is_locked_unsafe(&db_user, &mut *connection, backend_capabilities).await
+        {
             Ok(false) => {}
             Ok(true) => {
                 results.insert(db_user, Err(LockUserError::UserIsAlreadyLocked));
@@ -313,9 +648,16 @@ pub async fn lock_database_users(
             }
         }
 
-        let result = sqlx::query(
-            format!("ALTER USER {}@'%' ACCOUNT LOCK", quote_literal(&db_user),).as_str(),
-        )
+        if lock_attribute.is_some() && !backend_capabilities.supports_user_comments {
+            results.insert(db_user, Err(LockUserError::LockReasonsNotSupported));
+            continue;
+        }
+
+        let result = uncached_query(&format!(
+            "ALTER USER {}@'%' ACCOUNT LOCK{}",
+            quote_literal(&db_user),
+            lock_attribute.as_deref().unwrap_or_default(),
+        ))
         .execute(&mut *connection)
         .await
         .map(|_| ())
@@ -332,14 +674,34 @@ pub async fn lock_database_users(
 }
 
 pub async fn unlock_database_users(
-    db_users: Vec<MySQLUser>,
+    request: UnlockUsersRequest,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
+    backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> UnlockUsersResponse {
     let mut results = BTreeMap::new();
 
+    let mut db_users = request.users;
+    if let Some(prefix) = &request.all_with_prefix {
+        match users_with_prefix(
+            prefix,
+            unix_user,
+            &mut *connection,
+            group_denylist,
+            UnlockUserError::MySqlError,
+        )
+        .await
+        {
+            Ok(users) => db_users.extend(users),
+            Err((name, err)) => {
+                results.insert(name, Err(err));
+            }
+        }
+    }
+    db_users.sort();
+    db_users.dedup();
+
     for db_user in db_users {
         if let Err(err) =
             validate_db_or_user_request(&DbOrUser::User(db_user.clone()), unix_user, group_denylist)
@@ -361,7 +723,8 @@ pub async fn unlock_database_users(
             _ => {}
         }
 
-        match database_user_is_locked_unsafe(&db_user, &mut *connection, db_is_mariadb).await {
+        match database_user_is_locked_unsafe(&db_user, &mut *connection, backend_capabilities).await
+        {
             Ok(false) => {
                 results.insert(db_user, Err(UnlockUserError::UserIsAlreadyUnlocked));
                 continue;
@@ -373,9 +736,16 @@ pub async fn unlock_database_users(
             _ => {}
         }
 
-        let result = sqlx::query(
-            format!("ALTER USER {}@'%' ACCOUNT UNLOCK", quote_literal(&db_user),).as_str(),
-        )
+        // A JSON null removes the lock reason from the metadata again.
+        let result = uncached_query(&format!(
+            "ALTER USER {}@'%' ACCOUNT UNLOCK{}",
+            quote_literal(&db_user),
+            if backend_capabilities.supports_user_comments {
+                r#" ATTRIBUTE '{"lock_reason": null, "locked_by": null}'"#
+            } else {
+                ""
+            },
+        ))
         .execute(&mut *connection)
         .await
         .map(|_| ())
@@ -400,9 +770,36 @@ pub struct DatabaseUser {
     pub host: String,
     pub has_password: bool,
     pub is_locked: bool,
+    /// What kind of encrypted connection the user has to connect with.
+    #[serde(default)]
+    pub tls_requirement: TlsRequirement,
+    /// How many queries and updates the user may run per hour.
+    #[serde(default)]
+    pub limits: UserLimits,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Why the user was locked, if it was locked with `lock-user --reason`.
+    #[serde(default)]
+    pub lock_reason: Option<LockReason>,
+    /// The number of open connections for the user.
+    #[serde(default)]
+    pub current_connections: Option<u64>,
+    /// The number of connections for the user since the database server was started.
+    ///
+    /// `MySQL` and `MariaDB` do not record when a user last logged in,
+    /// so this is the closest thing to tell whether the user is still in use.
+    #[serde(default)]
+    pub total_connections: Option<u64>,
     pub databases: Vec<String>,
 }
 
+/// The reason given when a user was locked, and the unix user who locked it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockReason {
+    pub reason: String,
+    pub locked_by: Option<String>,
+}
+
 impl FromRow<'_, sqlx::mysql::MySqlRow> for DatabaseUser {
     fn from_row(row: &sqlx::mysql::MySqlRow) -> Result<Self, sqlx::Error> {
         Ok(Self {
@@ -410,12 +807,23 @@ impl FromRow<'_, sqlx::mysql::MySqlRow> for DatabaseUser {
             host: try_get_with_binary_fallback(row, "Host")?,
             has_password: row.try_get("has_password")?,
             is_locked: row.try_get("account_locked")?,
+            tls_requirement: TlsRequirement::from_ssl_type(&try_get_with_binary_fallback(
+                row, "ssl_type",
+            )?),
+            limits: UserLimits {
+                max_queries_per_hour: row.try_get("max_questions")?,
+                max_updates_per_hour: row.try_get("max_updates")?,
+            },
+            comment: None,
+            lock_reason: None,
+            current_connections: None,
+            total_connections: None,
             databases: Vec::new(),
         })
     }
 }
 
-const DB_USER_SELECT_STATEMENT_MARIADB: &str = r#"
+pub(super) const DB_USER_SELECT_STATEMENT_MARIADB: &str = r#"
 SELECT
   `user`.`User`,
   `user`.`Host`,
@@ -423,19 +831,25 @@ SELECT
   COALESCE(
     JSON_EXTRACT(`global_priv`.`priv`, "$.account_locked"),
     'false'
-  ) != 'false' AS `account_locked`
+  ) != 'false' AS `account_locked`,
+  `user`.`ssl_type`,
+  CAST(`user`.`max_questions` AS UNSIGNED) AS `max_questions`,
+  CAST(`user`.`max_updates` AS UNSIGNED) AS `max_updates`
 FROM `user`
 JOIN `global_priv` ON
   `user`.`User` = `global_priv`.`User`
   AND `user`.`Host` = `global_priv`.`Host`
 "#;
 
-const DB_USER_SELECT_STATEMENT_MYSQL: &str = r"
+pub(super) const DB_USER_SELECT_STATEMENT_MYSQL: &str = r"
 SELECT
   `user`.`User`,
   `user`.`Host`,
   `user`.`authentication_string` != '' AS `has_password`,
-  `user`.`account_locked` = 'Y' AS `account_locked`
+  `user`.`account_locked` = 'Y' AS `account_locked`,
+  `user`.`ssl_type`,
+  CAST(`user`.`max_questions` AS UNSIGNED) AS `max_questions`,
+  CAST(`user`.`max_updates` AS UNSIGNED) AS `max_updates`
 FROM `user`
 ";
 
@@ -443,7 +857,7 @@ pub async fn list_database_users(
     db_users: Vec<MySQLUser>,
     unix_user: &UnixUser,
     connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
+    backend_capabilities: &BackendCapabilities,
     group_denylist: &GroupDenylist,
 ) -> ListUsersResponse {
     let mut results = BTreeMap::new();
@@ -458,7 +872,7 @@ pub async fn list_database_users(
         }
 
         let mut result = sqlx::query_as::<_, DatabaseUser>(
-            &(if db_is_mariadb {
+            &(if backend_capabilities.supports_json_priv_column {
                 DB_USER_SELECT_STATEMENT_MARIADB.to_string()
             } else {
                 DB_USER_SELECT_STATEMENT_MYSQL.to_string()
@@ -478,6 +892,24 @@ pub async fn list_database_users(
             result = Err(err);
         }
 
+        if let Ok(Some(user)) = result.as_mut()
+            && let Err(err) =
+                set_database_user_comment(user, &mut *connection, backend_capabilities).await
+        {
+            result = Err(err);
+        }
+
+        if let Ok(Some(user)) = result.as_mut()
+            && let Err(err) =
+                set_database_user_lock_reason(user, &mut *connection, backend_capabilities).await
+        {
+            result = Err(err);
+        }
+
+        if let Ok(Some(user)) = result.as_mut() {
+            set_database_user_connection_stats(user, &mut *connection).await;
+        }
+
         match result {
             Ok(Some(user)) => results.insert(db_user, Ok(user)),
             Ok(None) => results.insert(db_user, Err(ListUsersError::UserDoesNotExist)),
@@ -488,21 +920,53 @@ pub async fn list_database_users(
     results
 }
 
-pub async fn list_all_database_users_for_unix_user(
-    unix_user: &UnixUser,
+// NOTE: this function is unsafe because it does no input validation.
+/// Get every database user matching `name_regex`, and `name_pattern` if it is given,
+/// together with their databases, comments and connection counts.
+async fn unsafe_list_database_users_matching(
+    name_regex: &str,
+    name_pattern: Option<&str>,
     connection: &mut MySqlConnection,
-    db_is_mariadb: bool,
-    group_denylist: &GroupDenylist,
-) -> ListAllUsersResponse {
-    let mut result = sqlx::query_as::<_, DatabaseUser>(
-        &(if db_is_mariadb {
+    backend_capabilities: &BackendCapabilities,
+) -> Result<Vec<DatabaseUser>, sqlx::Error> {
+    let mut users = sqlx::query_as::<_, DatabaseUser>(
+        &(if backend_capabilities.supports_json_priv_column {
             DB_USER_SELECT_STATEMENT_MARIADB.to_string()
         } else {
             DB_USER_SELECT_STATEMENT_MYSQL.to_string()
-        } + "WHERE `user`.`User` REGEXP ?"),
+        } + "WHERE `user`.`User` REGEXP ? AND (? IS NULL OR `user`.`User` REGEXP ?)"),
     )
-    .bind(create_user_group_matching_regex(unix_user, group_denylist))
+    .bind(name_regex)
+    .bind(name_pattern)
+    .bind(name_pattern)
     .fetch_all(&mut *connection)
+    .await?;
+
+    for user in &mut users {
+        set_databases_where_user_has_privileges(user, &mut *connection).await?;
+        set_database_user_comment(user, &mut *connection, backend_capabilities).await?;
+        set_database_user_lock_reason(user, &mut *connection, backend_capabilities).await?;
+        set_database_user_connection_stats(user, &mut *connection).await;
+    }
+
+    Ok(users)
+}
+
+/// List the database users of the unix user, only including those matching `name_pattern`
+/// as a `MySQL` regular expression if it is given.
+pub async fn list_all_database_users_for_unix_user(
+    name_pattern: Option<&str>,
+    unix_user: &UnixUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> ListAllUsersResponse {
+    let result = unsafe_list_database_users_matching(
+        &create_user_group_matching_regex(unix_user, group_denylist),
+        name_pattern,
+        connection,
+        backend_capabilities,
+    )
     .await
     .map_err(|err| ListAllUsersError::MySqlError(err.to_string()));
 
@@ -510,42 +974,57 @@ pub async fn list_all_database_users_for_unix_user(
         tracing::error!("Failed to list all database users: {:?}", err);
     }
 
-    if let Ok(users) = result.as_mut() {
-        for user in users {
-            if let Err(mysql_error) =
-                set_databases_where_user_has_privileges(user, &mut *connection).await
-            {
-                return Err(ListAllUsersError::MySqlError(mysql_error.to_string()));
-            }
-        }
-    }
-
     result
 }
 
+/// List every database user on the server that is owned by a unix user or group,
+/// together with its owner.
+///
+/// This must only be used for admins.
+pub async fn list_all_managed_database_users(
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+    group_denylist: &GroupDenylist,
+) -> AdminListUsersResponse {
+    let users = unsafe_list_database_users_matching(".*", None, connection, backend_capabilities)
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to list all managed database users: {:?}", err);
+            AdminListError::MySqlError(err.to_string())
+        })?;
+
+    Ok(users
+        .into_iter()
+        .filter_map(|user| {
+            find_name_owner(user.user.as_str(), group_denylist).map(|owner| (owner, user))
+        })
+        .collect())
+}
+
+static DATABASES_WHERE_USER_HAS_PRIVILEGES_QUERY: LazyLock<String> = LazyLock::new(|| {
+    formatdoc!(
+        r"
+            SELECT `Db` AS `database`
+            FROM `db`
+            WHERE `User` = ? AND ({})
+        ",
+        DATABASE_PRIVILEGE_FIELDS
+            .iter()
+            .map(|field| format!("`{field}` = 'Y'"))
+            .join(" OR "),
+    )
+});
+
 /// This function sets the `databases` field of the given `DatabaseUser`
 /// where the user has any privileges.
 pub async fn set_databases_where_user_has_privileges(
     db_user: &mut DatabaseUser,
     connection: &mut MySqlConnection,
 ) -> Result<(), sqlx::Error> {
-    let database_list = sqlx::query(
-        formatdoc!(
-            r"
-                SELECT `Db` AS `database`
-                FROM `db`
-                WHERE `User` = ? AND ({})
-            ",
-            DATABASE_PRIVILEGE_FIELDS
-                .iter()
-                .map(|field| format!("`{field}` = 'Y'"))
-                .join(" OR "),
-        )
-        .as_str(),
-    )
-    .bind(db_user.user.as_str())
-    .fetch_all(&mut *connection)
-    .await;
+    let database_list = sqlx::query(&DATABASES_WHERE_USER_HAS_PRIVILEGES_QUERY)
+        .bind(db_user.user.as_str())
+        .fetch_all(&mut *connection)
+        .await;
 
     if let Err(err) = &database_list {
         tracing::error!(
@@ -563,3 +1042,153 @@ pub async fn set_databases_where_user_has_privileges(
 
     Ok(())
 }
+
+const DATABASE_USER_COMMENT_QUERY: &str = r"
+    SELECT JSON_UNQUOTE(JSON_EXTRACT(`User_attributes`, '$.metadata.comment'))
+    FROM `mysql`.`user`
+    WHERE `User` = ?
+    AND `Host` = ?
+";
+
+/// This function sets the `comment` field of the given `DatabaseUser`,
+/// if the database server supports user comments.
+pub async fn set_database_user_comment(
+    db_user: &mut DatabaseUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+) -> Result<(), sqlx::Error> {
+    if !backend_capabilities.supports_user_comments {
+        return Ok(());
+    }
+
+    let comment = sqlx::query_scalar::<_, Option<String>>(DATABASE_USER_COMMENT_QUERY)
+        .bind(db_user.user.as_str())
+        .bind(db_user.host.as_str())
+        .fetch_optional(&mut *connection)
+        .await;
+
+    if let Err(err) = &comment {
+        tracing::error!(
+            "Failed to get comment for user '{}': {:?}",
+            &db_user.user,
+            err
+        );
+    }
+
+    db_user.comment = comment?.flatten();
+
+    Ok(())
+}
+
+const DATABASE_USER_LOCK_REASON_QUERY: &str = r"
+    SELECT
+      JSON_UNQUOTE(JSON_EXTRACT(`User_attributes`, '$.metadata.lock_reason')) AS `lock_reason`,
+      JSON_UNQUOTE(JSON_EXTRACT(`User_attributes`, '$.metadata.locked_by')) AS `locked_by`
+    FROM `mysql`.`user`
+    WHERE `User` = ?
+    AND `Host` = ?
+";
+
+/// This function sets the `lock_reason` field of the given `DatabaseUser`,
+/// if it is locked and the database server supports user attributes.
+pub async fn set_database_user_lock_reason(
+    db_user: &mut DatabaseUser,
+    connection: &mut MySqlConnection,
+    backend_capabilities: &BackendCapabilities,
+) -> Result<(), sqlx::Error> {
+    if !db_user.is_locked || !backend_capabilities.supports_user_comments {
+        return Ok(());
+    }
+
+    let row =
+        sqlx::query_as::<_, (Option<String>, Option<String>)>(DATABASE_USER_LOCK_REASON_QUERY)
+            .bind(db_user.user.as_str())
+            .bind(db_user.host.as_str())
+            .fetch_optional(&mut *connection)
+            .await;
+
+    if let Err(err) = &row {
+        tracing::error!(
+            "Failed to get lock reason for user '{}': {:?}",
+            &db_user.user,
+            err
+        );
+    }
+
+    db_user.lock_reason =
+        row?.and_then(|(reason, locked_by)| reason.map(|reason| LockReason { reason, locked_by }));
+
+    Ok(())
+}
+
+const DATABASE_USER_ACCOUNT_STATS_QUERY: &str = r"
+    SELECT
+      CAST(SUM(`CURRENT_CONNECTIONS`) AS UNSIGNED),
+      CAST(SUM(`TOTAL_CONNECTIONS`) AS UNSIGNED)
+    FROM `performance_schema`.`accounts`
+    WHERE `USER` = ?
+";
+
+const DATABASE_USER_PROCESSLIST_COUNT_QUERY: &str = r"
+    SELECT CAST(COUNT(*) AS UNSIGNED)
+    FROM `information_schema`.`PROCESSLIST`
+    WHERE `USER` = ?
+";
+
+/// Count the open connections of a user in the process list.
+pub async fn count_database_user_connections(
+    db_user: &MySQLUser,
+    connection: &mut MySqlConnection,
+) -> Result<u64, sqlx::Error> {
+    sqlx::query_scalar::<_, u64>(DATABASE_USER_PROCESSLIST_COUNT_QUERY)
+        .bind(db_user.as_str())
+        .fetch_one(connection)
+        .await
+}
+
+/// This function sets the `current_connections` and `total_connections` fields
+/// of the given `DatabaseUser`.
+///
+/// The numbers come from `performance_schema`, which is often disabled on `MariaDB`.
+/// In that case, the current connections are counted in the process list instead,
+/// and the total connections are left unset. Failures are only logged, as the
+/// connection numbers are not essential.
+pub async fn set_database_user_connection_stats(
+    db_user: &mut DatabaseUser,
+    connection: &mut MySqlConnection,
+) {
+    let account_stats =
+        sqlx::query_as::<_, (Option<u64>, Option<u64>)>(DATABASE_USER_ACCOUNT_STATS_QUERY)
+            .bind(db_user.user.as_str())
+            .fetch_one(&mut *connection)
+            .await;
+
+    match account_stats {
+        Ok((current_connections, total_connections)) => {
+            db_user.current_connections = current_connections;
+            db_user.total_connections = total_connections;
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Failed to get connection stats for user '{}' from performance_schema: {:?}",
+                &db_user.user,
+                err
+            );
+        }
+    }
+
+    if db_user.current_connections.is_some() {
+        return;
+    }
+
+    match count_database_user_connections(&db_user.user, connection).await {
+        Ok(count) => db_user.current_connections = Some(count),
+        Err(err) => {
+            tracing::warn!(
+                "Failed to count connections for user '{}': {:?}",
+                &db_user.user,
+                err
+            );
+        }
+    }
+}
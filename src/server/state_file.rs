@@ -0,0 +1,116 @@
+//! The small JSON state files kept by the server, e.g. for grant offers and privilege snapshots.
+//!
+//! A state file is read, changed and written back while holding an exclusive `flock`
+//! on a lock file next to it. This keeps the sessions and jobs of the server from
+//! overwriting each other's changes, also when sessions run in worker processes
+//! with `process_isolation` enabled.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use nix::fcntl::{Flock, FlockArg};
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The message sent to the client when a state file could not be read or written.
+///
+/// The actual error is only logged, as it is of no use to the user.
+pub const STORE_ERROR_MESSAGE: &str = "please contact the system administrators";
+
+/// An exclusive lock on a state file, which is released when this is dropped.
+pub struct LockedStateFile {
+    path: PathBuf,
+    _lock: Flock<File>,
+}
+
+impl LockedStateFile {
+    /// Wait until nobody else holds the lock on the state file at `path`, and take it.
+    pub async fn lock(path: &Path) -> anyhow::Result<Self> {
+        let lock_path = path.with_extension("lock");
+        let lock = tokio::task::spawn_blocking(move || {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)
+                .context(format!("Failed to open lock file at {lock_path:?}"))?;
+            // NOTE: the systemd unit runs with `UMask=0777`, which would leave the files unreadable.
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            Flock::lock(file, FlockArg::LockExclusive)
+                .map_err(|(_, errno)| errno)
+                .context(format!("Failed to lock {lock_path:?}"))
+        })
+        .await??;
+
+        Ok(Self {
+            path: path.to_owned(),
+            _lock: lock,
+        })
+    }
+
+    /// Take the lock on the state file at `path`, and read the state.
+    pub async fn lock_and_read<T: DeserializeOwned + Default>(
+        path: &Path,
+    ) -> anyhow::Result<(Self, T)> {
+        let state_file = Self::lock(path).await?;
+        let state = state_file.read()?;
+        Ok((state_file, state))
+    }
+
+    /// Read the state, or the default state if the file does not exist yet.
+    pub fn read<T: DeserializeOwned + Default>(&self) -> anyhow::Result<T> {
+        let path = &self.path;
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).context(format!("Failed to parse state at {path:?}"))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+            Err(err) => Err(err).context(format!("Failed to read state at {path:?}")),
+        }
+    }
+
+    /// Replace the state, by writing it to a temporary file and moving that in place.
+    pub fn write<T: Serialize>(&self, state: &T) -> anyhow::Result<()> {
+        let path = &self.path;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(state)?)
+            .context(format!("Failed to write state to {temp_path:?}"))?;
+        fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&temp_path, path).context(format!("Failed to write state to {path:?}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_locked_state_file() {
+        let dir = std::env::temp_dir().join(format!("muscl-state-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+
+        let state_file = LockedStateFile::lock(&path).await.unwrap();
+        assert_eq!(
+            state_file.read::<BTreeMap<String, u64>>().unwrap(),
+            BTreeMap::new()
+        );
+
+        let state = BTreeMap::from([("a".to_string(), 1)]);
+        state_file.write(&state).unwrap();
+        assert_eq!(state_file.read::<BTreeMap<String, u64>>().unwrap(), state);
+        drop(state_file);
+
+        // NOTE: the lock is released on drop, so this would hang otherwise.
+        let state_file = LockedStateFile::lock(&path).await.unwrap();
+        assert_eq!(state_file.read::<BTreeMap<String, u64>>().unwrap(), state);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
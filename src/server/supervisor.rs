@@ -1,13 +1,20 @@
 use std::{
     fs,
-    os::{fd::FromRawFd, unix::net::UnixListener as StdUnixListener},
+    os::{
+        fd::{AsRawFd, FromRawFd, RawFd},
+        unix::net::UnixListener as StdUnixListener,
+    },
     path::PathBuf,
-    sync::Arc,
-    time::Duration,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, anyhow};
-use sqlx::MySqlPool;
+use sqlx::{MySqlPool, mysql::MySqlConnectOptions};
 use tokio::{
     net::UnixListener as TokioUnixListener,
     select,
@@ -16,13 +23,27 @@ use tokio::{
     time::interval,
 };
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::level_filters::LevelFilter;
 
 use crate::{
-    core::protocol::request_validation::GroupDenylist,
+    core::{
+        common::{abstract_socket_name, bind_unix_listener},
+        protocol::request_validation::GroupDenylist,
+    },
     server::{
         authorization::read_and_parse_group_denylist,
+        backend_capabilities::BackendCapabilities,
         config::{MysqlConfig, ServerConfig},
-        session_handler::session_handler,
+        control_socket::{ControlContext, create_control_listener, spawn_control_socket_task},
+        handover::{HandoverState, read_handover_state, spawn_successor},
+        inactive_users::run_inactive_user_locking_job,
+        log_level::{current_log_level, set_log_level, toggle_debug_log_level},
+        maintenance::run_orphaned_privilege_cleanup_job,
+        policy::PolicyEngine,
+        scheduled_privilege_changes::run_scheduled_privilege_changes_job,
+        scheduler::{JobDefaults, Scheduler},
+        session_handler::{SessionContext, session_handler},
+        worker::run_isolated_session,
     },
 };
 
@@ -36,24 +57,45 @@ pub enum SupervisorMessage {
 #[derive(Clone, Debug)]
 pub struct ReloadEvent;
 
+/// Stop accepting new connections, wait for the active sessions to finish, and exit.
+#[derive(Clone, Debug)]
+pub struct DrainEvent {
+    /// How long to wait for the active sessions, or `None` to use `drain_timeout` from the config.
+    pub timeout: Option<Duration>,
+}
+
+/// Hand the listening sockets over to a new server process, see [`crate::server::handover`].
+#[derive(Clone, Debug)]
+pub struct HandoverEvent;
+
 #[allow(dead_code)]
 pub struct Supervisor {
     config_path: PathBuf,
     config: Arc<Mutex<ServerConfig>>,
     group_deny_list: Arc<RwLock<GroupDenylist>>,
+    session_context: Arc<RwLock<Arc<SessionContext>>>,
     systemd_mode: bool,
 
     shutdown_cancel_token: CancellationToken,
     reload_message_receiver: broadcast::Receiver<ReloadEvent>,
+    drain_message_receiver: broadcast::Receiver<DrainEvent>,
+    handover_message_receiver: broadcast::Receiver<HandoverEvent>,
     signal_handler_task: JoinHandle<()>,
 
     db_connection_pool: Arc<RwLock<MySqlPool>>,
-    db_is_mariadb: Arc<RwLock<bool>>,
+    db_replica_pool: Arc<RwLock<Option<MySqlPool>>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
     listener: Arc<RwLock<TokioUnixListener>>,
     listener_task: JoinHandle<anyhow::Result<()>>,
+    db_health_check_task: JoinHandle<()>,
+    scheduler_task: Mutex<Option<SchedulerTask>>,
     handler_task_tracker: TaskTracker,
     supervisor_message_sender: broadcast::Sender<SupervisorMessage>,
 
+    maintenance_mode: Arc<AtomicBool>,
+    control_listener_fd: Option<RawFd>,
+    control_socket_task: Option<JoinHandle<()>>,
+
     watchdog_timeout: Option<Duration>,
     systemd_watchdog_task: Option<JoinHandle<()>>,
 
@@ -63,6 +105,7 @@ pub struct Supervisor {
 impl Supervisor {
     pub async fn new(config_path: PathBuf, systemd_mode: bool) -> anyhow::Result<Self> {
         tracing::debug!("Starting server supervisor");
+        let started_at = Instant::now();
         tracing::debug!(
             "Running in tokio with {} worker threads",
             tokio::runtime::Handle::current().metrics().num_workers()
@@ -71,6 +114,14 @@ impl Supervisor {
         let config = ServerConfig::read_config_from_path(&config_path)
             .context("Failed to read server configuration")?;
 
+        let handover_state = read_handover_state()?;
+        if let Some(log_level) = handover_state
+            .as_ref()
+            .and_then(|state| state.log_level.as_deref())
+        {
+            set_log_level(LevelFilter::from_str(log_level)?);
+        }
+
         let group_deny_list = if let Some(denylist_path) = &config.authorization.group_denylist_file
         {
             let denylist = read_and_parse_group_denylist(denylist_path)
@@ -86,6 +137,14 @@ impl Supervisor {
             Arc::new(RwLock::new(GroupDenylist::new()))
         };
 
+        let policy_engine = PolicyEngine::from_config(config.policy.as_ref())
+            .context("Failed to load policy modules")?;
+        let session_context = Arc::new(RwLock::new(Arc::new(SessionContext::new(
+            &config,
+            group_deny_list.read().await.clone(),
+            policy_engine,
+        ))));
+
         let mut watchdog_duration = None;
         let mut watchdog_micro_seconds = 0;
         #[cfg(target_os = "linux")]
@@ -107,22 +166,11 @@ impl Supervisor {
 
         let db_connection_pool =
             Arc::new(RwLock::new(create_db_connection_pool(&config.mysql).await?));
+        let db_replica_pool = Arc::new(RwLock::new(create_db_replica_pool(&config.mysql).await));
 
-        let db_is_mariadb = {
-            let connection = db_connection_pool.read().await;
-            let version: String = sqlx::query_scalar("SELECT VERSION()")
-                .fetch_one(&*connection)
-                .await
-                .context("Failed to query database version")?;
-
-            let result = version.to_lowercase().contains("mariadb");
-            tracing::debug!(
-                "Connected to {} database server",
-                if result { "MariaDB" } else { "MySQL" }
-            );
-
-            Arc::new(RwLock::new(result))
-        };
+        let backend_capabilities = Arc::new(RwLock::new(
+            BackendCapabilities::detect(&*db_connection_pool.read().await).await?,
+        ));
 
         let task_tracker = TaskTracker::new();
 
@@ -137,56 +185,116 @@ impl Supervisor {
 
         let (tx, rx) = broadcast::channel(1);
 
-        // TODO: try to detech systemd socket before using the provided socket path
-        #[cfg(target_os = "linux")]
-        let listener = Arc::new(RwLock::new(match config.socket_path {
-            Some(ref path) => create_unix_listener_with_socket_path(path.clone()).await?,
-            None => create_unix_listener_with_systemd_socket().await?,
+        let listener = Arc::new(RwLock::new(match &handover_state {
+            Some(state) => unix_listener_from_raw_fd(state.listener_fd)?,
+            None => create_unix_listener(&config).await?,
         }));
-        #[cfg(not(target_os = "linux"))]
-        let listener = Arc::new(RwLock::new(
-            create_unix_listener_with_socket_path(
-                config
-                    .socket_path
-                    .as_ref()
-                    .ok_or(anyhow!("Socket path must be set"))?
-                    .clone(),
-            )
-            .await?,
-        ));
 
         let (reload_tx, reload_rx) = broadcast::channel(1);
+        let (drain_tx, drain_rx) = broadcast::channel(1);
+        let (handover_tx, handover_rx) = broadcast::channel(1);
         let shutdown_cancel_token = CancellationToken::new();
-        let signal_handler_task =
-            spawn_signal_handler_task(reload_tx, shutdown_cancel_token.clone());
+        let signal_handler_task = spawn_signal_handler_task(
+            reload_tx.clone(),
+            drain_tx.clone(),
+            handover_tx.clone(),
+            shutdown_cancel_token.clone(),
+        );
+
+        let control_listener = match &handover_state {
+            Some(state) => state
+                .control_listener_fd
+                .map(unix_listener_from_raw_fd)
+                .transpose()?,
+            None => config
+                .admin_socket_path
+                .as_deref()
+                .map(create_control_listener)
+                .transpose()?,
+        };
+        let control_listener_fd = control_listener
+            .as_ref()
+            .map(|control_listener| control_listener.as_raw_fd());
+
+        let config = Arc::new(Mutex::new(config));
+        let maintenance_mode = Arc::new(AtomicBool::new(
+            handover_state
+                .as_ref()
+                .is_some_and(|state| state.maintenance_mode),
+        ));
+
+        let control_socket_task = control_listener.map(|control_listener| {
+            spawn_control_socket_task(
+                control_listener,
+                ControlContext {
+                    config: config.clone(),
+                    handler_task_tracker: task_tracker.clone(),
+                    maintenance_mode: maintenance_mode.clone(),
+                    reload_sender: reload_tx,
+                    drain_sender: drain_tx,
+                    handover_sender: handover_tx,
+                    started_at,
+                },
+            )
+        });
 
         let listener_clone = listener.clone();
         let task_tracker_clone = task_tracker.clone();
         let listener_task = {
             tokio::spawn(listener_task(
                 listener_clone,
-                task_tracker_clone,
-                db_connection_pool.clone(),
                 rx,
-                db_is_mariadb.clone(),
-                group_deny_list.clone(),
+                ListenerContext {
+                    task_tracker: task_tracker_clone,
+                    db_pool: db_connection_pool.clone(),
+                    db_replica_pool: db_replica_pool.clone(),
+                    backend_capabilities: backend_capabilities.clone(),
+                    session_context: session_context.clone(),
+                    config: config.clone(),
+                    config_path: config_path.clone(),
+                    maintenance_mode: maintenance_mode.clone(),
+                },
             ))
         };
 
+        let db_health_check_task = spawn_db_health_check_task(
+            config.clone(),
+            db_connection_pool.clone(),
+            backend_capabilities.clone(),
+        );
+
+        let scheduler_task = spawn_scheduler(
+            config.clone(),
+            db_connection_pool.clone(),
+            backend_capabilities.clone(),
+            group_deny_list.clone(),
+            shutdown_cancel_token.child_token(),
+        )
+        .await;
+
         Ok(Self {
             config_path,
-            config: Arc::new(Mutex::new(config)),
+            config,
             group_deny_list,
+            session_context,
             systemd_mode,
             reload_message_receiver: reload_rx,
+            drain_message_receiver: drain_rx,
+            handover_message_receiver: handover_rx,
             shutdown_cancel_token,
             signal_handler_task,
             db_connection_pool,
-            db_is_mariadb,
+            db_replica_pool,
+            backend_capabilities,
             listener,
             listener_task,
+            db_health_check_task,
+            scheduler_task: Mutex::new(Some(scheduler_task)),
             handler_task_tracker: task_tracker,
             supervisor_message_sender: tx,
+            maintenance_mode,
+            control_listener_fd,
+            control_socket_task,
             watchdog_timeout: watchdog_duration,
             systemd_watchdog_task: watchdog_task,
             status_notifier_task,
@@ -235,6 +343,14 @@ impl Supervisor {
             tracing::debug!("No group denylist file specified, proceeding without a denylist");
             GroupDenylist::new()
         };
+        let policy_engine = PolicyEngine::from_config(config.policy.as_ref())
+            .context("Failed to load policy modules")?;
+        *self.session_context.write().await = Arc::new(SessionContext::new(
+            &config,
+            group_deny_list.clone(),
+            policy_engine,
+        ));
+
         let mut group_deny_list_lock = self.group_deny_list.write().await;
         *group_deny_list_lock = group_deny_list;
         Ok(())
@@ -243,26 +359,15 @@ impl Supervisor {
     async fn restart_db_connection_pool(&self) -> anyhow::Result<()> {
         let config = self.config.lock().await;
         let mut connection_pool = self.db_connection_pool.clone().write_owned().await;
-        let mut db_is_mariadb_lock = self.db_is_mariadb.write().await;
+        let mut backend_capabilities_lock = self.backend_capabilities.write().await;
 
         let new_db_pool = create_db_connection_pool(&config.mysql).await?;
-        let db_is_mariadb = {
-            let version: String = sqlx::query_scalar("SELECT VERSION()")
-                .fetch_one(&new_db_pool)
-                .await
-                .context("Failed to query database version")?;
-
-            let result = version.to_lowercase().contains("mariadb");
-            tracing::debug!(
-                "Connected to {} database server",
-                if result { "MariaDB" } else { "MySQL" }
-            );
-
-            result
-        };
+        let backend_capabilities = BackendCapabilities::detect(&new_db_pool).await?;
+        let new_db_replica_pool = create_db_replica_pool(&config.mysql).await;
 
         *connection_pool = new_db_pool;
-        *db_is_mariadb_lock = db_is_mariadb;
+        *backend_capabilities_lock = backend_capabilities;
+        *self.db_replica_pool.write().await = new_db_replica_pool;
         Ok(())
     }
 
@@ -270,20 +375,7 @@ impl Supervisor {
     //       first. Make sure to handle that appropriately to avoid a deadlock.
     async fn reload_listener(&self) -> anyhow::Result<()> {
         let config = self.config.lock().await;
-        #[cfg(target_os = "linux")]
-        let new_listener = match config.socket_path {
-            Some(ref path) => create_unix_listener_with_socket_path(path.clone()).await?,
-            None => create_unix_listener_with_systemd_socket().await?,
-        };
-        #[cfg(not(target_os = "linux"))]
-        let new_listener = create_unix_listener_with_socket_path(
-            config
-                .socket_path
-                .as_ref()
-                .ok_or(anyhow!("Socket path must be set"))?
-                .clone(),
-        )
-        .await?;
+        let new_listener = create_unix_listener(&config).await?;
 
         let mut listener = self.listener.write().await;
         *listener = new_listener;
@@ -328,6 +420,11 @@ impl Supervisor {
             self.resume_receiving_new_connections()?;
         }
 
+        if self.config.lock().await.jobs != previous_config.jobs {
+            tracing::debug!("Job configuration has changed, restarting scheduler");
+            self.restart_scheduler().await;
+        }
+
         #[cfg(target_os = "linux")]
         sd_notify::notify(false, &[sd_notify::NotifyState::Ready])?;
 
@@ -340,6 +437,10 @@ impl Supervisor {
 
         tracing::debug!("Stop accepting new connections");
         self.stop_receiving_new_connections()?;
+        self.stop_scheduler().await;
+
+        tracing::debug!("Stopping database health check");
+        self.db_health_check_task.abort();
 
         let connection_count = self.handler_task_tracker.len();
         tracing::debug!(
@@ -348,6 +449,155 @@ impl Supervisor {
         );
         self.wait_for_existing_connections_to_finish().await?;
 
+        self.exit().await
+    }
+
+    /// Stop accepting new connections, and wait up to `timeout` for the active
+    /// sessions to finish before exiting, reporting the progress to systemd.
+    pub async fn drain(&self, timeout: Option<Duration>) -> anyhow::Result<()> {
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => Duration::from_secs(self.config.lock().await.drain_timeout),
+        };
+
+        #[cfg(target_os = "linux")]
+        sd_notify::notify(false, &[sd_notify::NotifyState::Stopping])?;
+
+        // The drain progress replaces the regular status messages.
+        if let Some(status_notifier_task) = &self.status_notifier_task {
+            status_notifier_task.abort();
+        }
+
+        tracing::info!(
+            "Draining server, waiting up to {} seconds for {} active connections to finish",
+            timeout.as_secs(),
+            self.handler_task_tracker.len()
+        );
+        self.stop_receiving_new_connections()?;
+        self.stop_scheduler().await;
+        self.wait_for_existing_connections_until(timeout, true)
+            .await;
+
+        self.exit().await
+    }
+
+    /// Start a new server process that takes over the listening sockets, and exit
+    /// once the active sessions of this process have finished.
+    ///
+    /// If the new process can not be started, this process keeps running as before.
+    pub async fn handover(&self) -> anyhow::Result<()> {
+        let state = HandoverState {
+            listener_fd: self.listener.read().await.as_raw_fd(),
+            control_listener_fd: self.control_listener_fd,
+            maintenance_mode: self.maintenance_mode.load(Ordering::Relaxed),
+            log_level: current_log_level().map(|level| level.to_string()),
+        };
+
+        let successor_pid = spawn_successor(&state)?;
+
+        // From here on, the new process is the one talking to systemd.
+        #[cfg(target_os = "linux")]
+        sd_notify::notify(false, &[sd_notify::NotifyState::MainPid(successor_pid)])?;
+        for task in [
+            &self.status_notifier_task,
+            &self.systemd_watchdog_task,
+            &self.control_socket_task,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            task.abort();
+        }
+
+        let timeout = Duration::from_secs(self.config.lock().await.drain_timeout);
+        tracing::info!(
+            "Handed over to new server process with pid {}, waiting up to {} seconds for {} \
+             active connections to finish",
+            successor_pid,
+            timeout.as_secs(),
+            self.handler_task_tracker.len()
+        );
+        self.stop_receiving_new_connections()?;
+        // NOTE: the new process runs the jobs from here on.
+        self.stop_scheduler().await;
+        self.wait_for_existing_connections_until(timeout, false)
+            .await;
+
+        self.exit().await
+    }
+
+    /// Stop the scheduled jobs, before exiting.
+    ///
+    /// Jobs that are running are allowed to finish, so they are not cut off halfway.
+    async fn stop_scheduler(&self) {
+        tracing::debug!("Stopping scheduled jobs");
+        if let Some(scheduler_task) = self.scheduler_task.lock().await.take() {
+            scheduler_task.stop().await;
+        }
+    }
+
+    /// Stop the scheduler, and start it again with the current job configuration.
+    async fn restart_scheduler(&self) {
+        let mut scheduler_task = self.scheduler_task.lock().await;
+        let Some(previous_scheduler_task) = scheduler_task.take() else {
+            return;
+        };
+        previous_scheduler_task.stop().await;
+        *scheduler_task = Some(
+            spawn_scheduler(
+                self.config.clone(),
+                self.db_connection_pool.clone(),
+                self.backend_capabilities.clone(),
+                self.group_deny_list.clone(),
+                self.shutdown_cancel_token.child_token(),
+            )
+            .await,
+        );
+    }
+
+    /// Wait for the active sessions to finish, or for `timeout` to pass,
+    /// optionally reporting the number of remaining sessions to systemd.
+    async fn wait_for_existing_connections_until(&self, timeout: Duration, report_status: bool) {
+        const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut status_interval = interval(STATUS_INTERVAL);
+        loop {
+            select! {
+                () = self.handler_task_tracker.wait() => {
+                    tracing::info!("All connections finished");
+                    break;
+                }
+                () = tokio::time::sleep_until(deadline) => {
+                    tracing::warn!(
+                        "Deadline reached, exiting with {} connections still active",
+                        self.handler_task_tracker.len()
+                    );
+                    break;
+                }
+                _ = status_interval.tick(), if report_status => {
+                    #[cfg(target_os = "linux")]
+                    {
+                        let message = format!(
+                            "Draining, waiting for {} connections ({} seconds left)",
+                            self.handler_task_tracker.len(),
+                            deadline
+                                .saturating_duration_since(tokio::time::Instant::now())
+                                .as_secs()
+                        );
+                        if let Err(e) = sd_notify::notify(
+                            false,
+                            &[sd_notify::NotifyState::Status(message.as_str())],
+                        ) {
+                            tracing::warn!("Failed to send systemd status notification: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn exit(&self) -> anyhow::Result<()> {
         tracing::debug!("Shutting down listener task");
         self.supervisor_message_sender
             .send(SupervisorMessage::Shutdown)
@@ -384,6 +634,27 @@ impl Supervisor {
                     }
                 }
 
+                Ok(DrainEvent { timeout }) = async {
+                  let mut rx = self.drain_message_receiver.resubscribe();
+                  rx.recv().await
+                } => {
+                    self.drain(timeout).await?;
+                    break;
+                }
+
+                _ = async {
+                  let mut rx = self.handover_message_receiver.resubscribe();
+                  rx.recv().await
+                } => {
+                    tracing::info!("Handing over to a new server process");
+                    match self.handover().await {
+                        Ok(()) => break,
+                        Err(e) => {
+                            tracing::error!("Failed to hand over to a new server process: {:#}", e);
+                        }
+                    }
+                }
+
                 () = self.shutdown_cancel_token.cancelled() => {
                     tracing::info!("Shutting down server");
                     self.shutdown().await?;
@@ -441,6 +712,16 @@ fn spawn_status_notifier_task(task_tracker: TaskTracker) -> JoinHandle<()> {
 async fn create_unix_listener_with_socket_path(
     socket_path: PathBuf,
 ) -> anyhow::Result<TokioUnixListener> {
+    if abstract_socket_name(&socket_path).is_some() {
+        tracing::info!("Listening on abstract socket {:?}", socket_path);
+
+        let std_unix_listener = bind_unix_listener(&socket_path)?;
+        std_unix_listener
+            .set_nonblocking(true)
+            .context("Failed to set non-blocking mode on abstract socket")?;
+        return Ok(TokioUnixListener::from_std(std_unix_listener)?);
+    }
+
     let parent_directory = socket_path.parent().unwrap();
     if !parent_directory.exists() {
         tracing::debug!("Creating directory {:?}", parent_directory);
@@ -460,6 +741,26 @@ async fn create_unix_listener_with_socket_path(
     Ok(listener)
 }
 
+async fn create_unix_listener(config: &ServerConfig) -> anyhow::Result<TokioUnixListener> {
+    // TODO: try to detech systemd socket before using the provided socket path
+    #[cfg(target_os = "linux")]
+    let listener = match config.socket_path {
+        Some(ref path) => create_unix_listener_with_socket_path(path.clone()).await?,
+        None => create_unix_listener_with_systemd_socket().await?,
+    };
+    #[cfg(not(target_os = "linux"))]
+    let listener = create_unix_listener_with_socket_path(
+        config
+            .socket_path
+            .as_ref()
+            .ok_or(anyhow!("Socket path must be set"))?
+            .clone(),
+    )
+    .await?;
+
+    Ok(listener)
+}
+
 #[cfg(target_os = "linux")]
 async fn create_unix_listener_with_systemd_socket() -> anyhow::Result<TokioUnixListener> {
     let fd = sd_notify::listen_fds()
@@ -474,23 +775,58 @@ async fn create_unix_listener_with_systemd_socket() -> anyhow::Result<TokioUnixL
         fd
     );
 
+    unix_listener_from_raw_fd(fd)
+}
+
+/// Take ownership of an inherited listening socket, from systemd or a previous server process.
+fn unix_listener_from_raw_fd(fd: RawFd) -> anyhow::Result<TokioUnixListener> {
     let std_unix_listener = unsafe { StdUnixListener::from_raw_fd(fd) };
-    std_unix_listener
-        .set_nonblocking(true)
-        .context("Failed to set non-blocking mode on systemd socket")?;
+    std_unix_listener.set_nonblocking(true).context(format!(
+        "Failed to set non-blocking mode on socket with fd {fd}"
+    ))?;
     let listener = TokioUnixListener::from_std(std_unix_listener)?;
 
     Ok(listener)
 }
 
-async fn create_db_connection_pool(config: &MysqlConfig) -> anyhow::Result<MySqlPool> {
+pub(crate) async fn create_db_connection_pool(config: &MysqlConfig) -> anyhow::Result<MySqlPool> {
     let mysql_config = config.as_mysql_connect_options()?;
 
     config.log_connection_notice();
 
+    connect_db_pool(config, mysql_config).await
+}
+
+/// Create the connection pool for the read-only replica, if one is configured.
+///
+/// The replica is optional, so failing to connect to it is not fatal.
+/// Read-only requests are served by the primary database instead.
+pub(crate) async fn create_db_replica_pool(config: &MysqlConfig) -> Option<MySqlPool> {
+    let result = async {
+        let Some(replica_config) = config.as_replica_connect_options()? else {
+            return anyhow::Ok(None);
+        };
+        tracing::debug!("Connecting to read-only MySQL replica");
+        connect_db_pool(config, replica_config).await.map(Some)
+    }
+    .await;
+
+    result.unwrap_or_else(|err| {
+        tracing::warn!(
+            "Failed to connect to the read-only replica, using the primary database for all requests: {:#}",
+            err
+        );
+        None
+    })
+}
+
+async fn connect_db_pool(
+    config: &MysqlConfig,
+    connect_options: MySqlConnectOptions,
+) -> anyhow::Result<MySqlPool> {
     let pool = match tokio::time::timeout(
         Duration::from_secs(config.timeout),
-        MySqlPool::connect_with(mysql_config),
+        config.as_mysql_pool_options().connect_with(connect_options),
     )
     .await
     {
@@ -501,16 +837,197 @@ async fn create_db_connection_pool(config: &MysqlConfig) -> anyhow::Result<MySql
 
     let pool_opts = pool.options();
     tracing::debug!(
-        "Successfully opened database connection pool with options (max_connections: {}, min_connections: {})",
+        "Successfully opened database connection pool with options (max_connections: {}, min_connections: {}, acquire_timeout: {:?}, idle_timeout: {:?}, max_lifetime: {:?})",
         pool_opts.get_max_connections(),
         pool_opts.get_min_connections(),
+        pool_opts.get_acquire_timeout(),
+        pool_opts.get_idle_timeout(),
+        pool_opts.get_max_lifetime(),
     );
 
     Ok(pool)
 }
 
+const INACTIVE_USER_LOCKING_DEFAULTS: JobDefaults = JobDefaults {
+    schedule: "@hourly",
+    jitter: Duration::from_secs(5 * 60),
+    // NOTE: does nothing unless `inactive_user_locking` is configured
+    enabled: true,
+};
+
+const SCHEDULED_PRIVILEGE_CHANGES_DEFAULTS: JobDefaults = JobDefaults {
+    schedule: "* * * * *",
+    // NOTE: the changes are supposed to be applied on time
+    jitter: Duration::ZERO,
+    // NOTE: does nothing unless `scheduled_privilege_changes` is configured
+    enabled: true,
+};
+
+const ORPHANED_PRIVILEGE_CLEANUP_DEFAULTS: JobDefaults = JobDefaults {
+    schedule: "@daily",
+    jitter: Duration::from_secs(60 * 60),
+    enabled: false,
+};
+
+/// Register all periodic maintenance jobs.
+fn create_scheduler(
+    config: Arc<Mutex<ServerConfig>>,
+    db_pool: Arc<RwLock<MySqlPool>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+    group_denylist: Arc<RwLock<GroupDenylist>>,
+) -> Scheduler {
+    let mut scheduler = Scheduler::new(config.clone());
+
+    {
+        let config = config.clone();
+        let db_pool = db_pool.clone();
+        let backend_capabilities = backend_capabilities.clone();
+        let group_denylist = group_denylist.clone();
+        scheduler.register(
+            "scheduled_privilege_changes",
+            SCHEDULED_PRIVILEGE_CHANGES_DEFAULTS,
+            move || {
+                run_scheduled_privilege_changes_job(
+                    config.clone(),
+                    db_pool.clone(),
+                    backend_capabilities.clone(),
+                    group_denylist.clone(),
+                )
+            },
+        );
+    }
+
+    {
+        let db_pool = db_pool.clone();
+        let group_denylist = group_denylist.clone();
+        scheduler.register(
+            "inactive_user_locking",
+            INACTIVE_USER_LOCKING_DEFAULTS,
+            move || {
+                run_inactive_user_locking_job(
+                    config.clone(),
+                    db_pool.clone(),
+                    backend_capabilities.clone(),
+                    group_denylist.clone(),
+                )
+            },
+        );
+    }
+
+    scheduler.register(
+        "orphaned_privilege_cleanup",
+        ORPHANED_PRIVILEGE_CLEANUP_DEFAULTS,
+        move || run_orphaned_privilege_cleanup_job(db_pool.clone(), group_denylist.clone()),
+    );
+
+    scheduler
+}
+
+/// The scheduler of the periodic maintenance jobs, see [`create_scheduler`].
+struct SchedulerTask {
+    cancel_token: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl SchedulerTask {
+    /// Stop scheduling new runs, and wait for the running jobs to finish.
+    async fn stop(self) {
+        self.cancel_token.cancel();
+        if let Err(err) = self.task.await {
+            tracing::error!("Scheduler task failed: {}", err);
+        }
+    }
+}
+
+async fn spawn_scheduler(
+    config: Arc<Mutex<ServerConfig>>,
+    db_pool: Arc<RwLock<MySqlPool>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+    group_denylist: Arc<RwLock<GroupDenylist>>,
+    cancel_token: CancellationToken,
+) -> SchedulerTask {
+    let task = create_scheduler(config, db_pool, backend_capabilities, group_denylist)
+        .spawn(cancel_token.clone())
+        .await;
+    SchedulerTask { cancel_token, task }
+}
+
+const DB_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const DB_RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DB_RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Periodically checks that the database is reachable through the connection pool.
+///
+/// If the check fails (e.g. because the database server was restarted), the pool is
+/// re-established with exponential backoff until the database is reachable again.
+fn spawn_db_health_check_task(
+    config: Arc<Mutex<ServerConfig>>,
+    db_pool: Arc<RwLock<MySqlPool>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = interval(DB_HEALTH_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let timeout = Duration::from_secs(config.lock().await.mysql.timeout);
+            let health_check = async {
+                let pool = db_pool.read().await;
+                sqlx::query("SELECT 1").execute(&*pool).await
+            };
+
+            match tokio::time::timeout(timeout, health_check).await {
+                Ok(Ok(_)) => continue,
+                Ok(Err(err)) => tracing::warn!("Database health check failed: {}", err),
+                Err(_) => tracing::warn!(
+                    "Database health check timed out after {} seconds",
+                    timeout.as_secs()
+                ),
+            }
+
+            let mut backoff = DB_RECONNECT_INITIAL_BACKOFF;
+            loop {
+                let mysql_config = config.lock().await.mysql.clone();
+                let result = async {
+                    let new_db_pool = create_db_connection_pool(&mysql_config).await?;
+                    let new_backend_capabilities =
+                        BackendCapabilities::detect(&new_db_pool).await?;
+                    anyhow::Ok((new_db_pool, new_backend_capabilities))
+                }
+                .await;
+
+                match result {
+                    Ok((new_db_pool, new_backend_capabilities)) => {
+                        let old_db_pool = {
+                            let mut pool_lock = db_pool.write().await;
+                            *backend_capabilities.write().await = new_backend_capabilities;
+                            std::mem::replace(&mut *pool_lock, new_db_pool)
+                        };
+                        old_db_pool.close().await;
+                        tracing::info!("Re-established database connection pool");
+                        break;
+                    }
+                    Err(err) => {
+                        tracing::error!(
+                            "Failed to re-establish database connection pool, retrying in {} seconds: {:#}",
+                            backoff.as_secs(),
+                            err
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = std::cmp::min(backoff * 2, DB_RECONNECT_MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+    })
+}
+
 fn spawn_signal_handler_task(
     reload_sender: broadcast::Sender<ReloadEvent>,
+    drain_sender: broadcast::Sender<DrainEvent>,
+    handover_sender: broadcast::Sender<HandoverEvent>,
     shutdown_token: CancellationToken,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
@@ -520,6 +1037,15 @@ fn spawn_signal_handler_task(
         let mut sigterm_stream =
             tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
                 .expect("Failed to set up SIGTERM handler");
+        let mut sigusr1_stream =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())
+                .expect("Failed to set up SIGUSR1 handler");
+        let mut sigquit_stream =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::quit())
+                .expect("Failed to set up SIGQUIT handler");
+        let mut sigusr2_stream =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined2())
+                .expect("Failed to set up SIGUSR2 handler");
 
         loop {
             tokio::select! {
@@ -527,6 +1053,18 @@ fn spawn_signal_handler_task(
                     tracing::info!("Received SIGHUP signal");
                     reload_sender.send(ReloadEvent).ok();
                 }
+                _ = sigusr1_stream.recv() => {
+                    tracing::info!("Received SIGUSR1 signal");
+                    toggle_debug_log_level();
+                }
+                _ = sigquit_stream.recv() => {
+                    tracing::info!("Received SIGQUIT signal");
+                    drain_sender.send(DrainEvent { timeout: None }).ok();
+                }
+                _ = sigusr2_stream.recv() => {
+                    tracing::info!("Received SIGUSR2 signal");
+                    handover_sender.send(HandoverEvent).ok();
+                }
                 _ = sigterm_stream.recv() => {
                     tracing::info!("Received SIGTERM signal");
                     shutdown_token.cancel();
@@ -537,17 +1075,40 @@ fn spawn_signal_handler_task(
     })
 }
 
-async fn listener_task(
-    listener: Arc<RwLock<TokioUnixListener>>,
+/// The parts of the supervisor that the listener task needs to start sessions.
+struct ListenerContext {
     task_tracker: TaskTracker,
     db_pool: Arc<RwLock<MySqlPool>>,
+    db_replica_pool: Arc<RwLock<Option<MySqlPool>>>,
+    backend_capabilities: Arc<RwLock<BackendCapabilities>>,
+    session_context: Arc<RwLock<Arc<SessionContext>>>,
+    config: Arc<Mutex<ServerConfig>>,
+    config_path: PathBuf,
+    maintenance_mode: Arc<AtomicBool>,
+}
+
+async fn listener_task(
+    listener: Arc<RwLock<TokioUnixListener>>,
     mut supervisor_message_receiver: broadcast::Receiver<SupervisorMessage>,
-    db_is_mariadb: Arc<RwLock<bool>>,
-    group_denylist: Arc<RwLock<GroupDenylist>>,
+    context: ListenerContext,
 ) -> anyhow::Result<()> {
+    let ListenerContext {
+        task_tracker,
+        db_pool,
+        db_replica_pool,
+        backend_capabilities,
+        session_context,
+        config,
+        config_path,
+        maintenance_mode,
+    } = context;
+
     #[cfg(target_os = "linux")]
     sd_notify::notify(false, &[sd_notify::NotifyState::Ready])?;
 
+    // NOTE: while not accepting, new connections wait in the backlog of the socket.
+    let mut accepting = true;
+
     loop {
         tokio::select! {
             biased;
@@ -556,38 +1117,57 @@ async fn listener_task(
                 match message {
                     SupervisorMessage::StopAcceptingNewConnections => {
                         tracing::info!("Listener task received stop accepting new connections message, stopping listener");
-                        while let Ok(msg) = supervisor_message_receiver.try_recv() {
-                            if let SupervisorMessage::ResumeAcceptingNewConnections = msg {
-                                tracing::info!("Listener task received resume accepting new connections message, resuming listener");
-                                break;
-                            }
-                        }
+                        accepting = false;
+                    }
+                    SupervisorMessage::ResumeAcceptingNewConnections => {
+                        tracing::info!("Listener task received resume accepting new connections message, resuming listener");
+                        accepting = true;
                     }
                     SupervisorMessage::Shutdown => {
                         tracing::info!("Listener task received shutdown message, exiting listener task");
                         break;
                     }
-                    _ => {}
                 }
             }
 
             accept_result = async {
                 let listener = listener.read().await;
                 listener.accept().await
-            } => {
+            }, if accepting => {
                 match accept_result {
                     Ok((conn, _addr)) => {
                         tracing::debug!("Got new connection");
 
+                        if config.lock().await.process_isolation {
+                            let config_path = config_path.clone();
+                            let maintenance_mode = maintenance_mode.load(Ordering::Relaxed);
+                            let log_level = current_log_level().map(|level| level.to_string());
+                            task_tracker.spawn(async move {
+                                if let Err(e) = run_isolated_session(
+                                    conn,
+                                    &config_path,
+                                    maintenance_mode,
+                                    log_level,
+                                ).await {
+                                    tracing::error!("Failed to run worker process: {:#}", e);
+                                }
+                            });
+                            continue;
+                        }
+
                         let db_pool_clone = db_pool.clone();
-                        let db_is_mariadb_clone = *db_is_mariadb.read().await;
-                        let group_denylist_arc_clone = group_denylist.clone();
+                        let db_replica_pool_clone = db_replica_pool.clone();
+                        let backend_capabilities_clone = backend_capabilities.read().await.clone();
+                        let session_context_clone = session_context.read().await.clone();
+                        let maintenance_mode = maintenance_mode.load(Ordering::Relaxed);
                         task_tracker.spawn(async move {
                             match session_handler(
                                 conn,
                                 db_pool_clone,
-                                db_is_mariadb_clone,
-                                &*group_denylist_arc_clone.read().await,
+                                db_replica_pool_clone,
+                                &backend_capabilities_clone,
+                                session_context_clone,
+                                maintenance_mode,
                             ).await {
                                 Ok(()) => {}
                                 Err(e) => {
@@ -0,0 +1,114 @@
+//! Handling each connection in a separate worker process.
+//!
+//! With `process_isolation` enabled in the config, the supervisor starts a short-lived
+//! `muscl-server worker` process for every accepted connection, handing it the connection
+//! as its stdin. The worker applies the Landlock restrictions of the server to itself,
+//! opens its own database connection, applies the seccomp filter from
+//! [`crate::server::seccomp`], and exits when the session is over.
+//! This way, a compromised session can not get at the other sessions,
+//! or at the database connections of the supervisor.
+
+use std::{
+    os::{
+        fd::{FromRawFd, OwnedFd},
+        unix::net::UnixStream as StdUnixStream,
+    },
+    path::Path,
+    process::Stdio,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use tokio::{net::UnixStream as TokioUnixStream, process::Command, sync::RwLock};
+
+use crate::{
+    core::{
+        bootstrap::construct_single_connection_mysql_pool,
+        protocol::request_validation::GroupDenylist,
+    },
+    server::{
+        authorization::read_and_parse_group_denylist,
+        backend_capabilities::BackendCapabilities,
+        config::ServerConfig,
+        handover::server_executable,
+        policy::PolicyEngine,
+        seccomp::seccomp_restrict_worker,
+        session_handler::{SessionContext, session_handler},
+    },
+};
+
+/// Handle a connection in a new worker process, and wait for it to exit.
+pub async fn run_isolated_session(
+    connection: TokioUnixStream,
+    config_path: &Path,
+    maintenance_mode: bool,
+    log_level: Option<String>,
+) -> anyhow::Result<()> {
+    let connection: OwnedFd = connection.into_std()?.into();
+
+    let mut command = Command::new(server_executable()?);
+    command.arg("--config").arg(config_path).arg("worker");
+    if maintenance_mode {
+        command.arg("--maintenance-mode");
+    }
+    if let Some(log_level) = log_level {
+        command.arg("--log-level").arg(log_level);
+    }
+
+    let status = command
+        .stdin(Stdio::from(connection))
+        .kill_on_drop(true)
+        .status()
+        .await
+        .context("Failed to start worker process")?;
+
+    if !status.success() {
+        anyhow::bail!("Worker process exited with {status}");
+    }
+
+    Ok(())
+}
+
+/// Run a single session in a worker process, on the connection passed as stdin.
+pub async fn run_worker(config_path: &Path, maintenance_mode: bool) -> anyhow::Result<()> {
+    let config = ServerConfig::read_config_from_path(config_path)
+        .context("Failed to read server config in worker process")?;
+
+    let group_denylist = if let Some(denylist_path) = &config.authorization.group_denylist_file {
+        read_and_parse_group_denylist(denylist_path)
+            .context("Failed to read and parse group denylist")?
+    } else {
+        GroupDenylist::new()
+    };
+
+    let policy_engine = PolicyEngine::from_config(config.policy.as_ref())
+        .context("Failed to load policy modules")?;
+
+    let socket = unsafe { StdUnixStream::from_raw_fd(0) };
+    socket
+        .set_nonblocking(true)
+        .context("Failed to set non-blocking mode on connection")?;
+    let socket = TokioUnixStream::from_std(socket)?;
+
+    let db_pool = construct_single_connection_mysql_pool(&config.mysql).await?;
+    let backend_capabilities = BackendCapabilities::detect(&db_pool).await?;
+
+    // NOTE: this comes after connecting, as `password_command` may have to be run for it.
+    seccomp_restrict_worker()?;
+
+    let db_pool = Arc::new(RwLock::new(db_pool));
+    // NOTE: a single session is not worth a second connection to the read-only replica.
+    let db_replica_pool = Arc::new(RwLock::new(None));
+    let context = SessionContext::new(&config, group_denylist, policy_engine)
+        // NOTE: the pool only has a single connection.
+        .with_single_connection();
+    session_handler(
+        socket,
+        db_pool,
+        db_replica_pool,
+        &backend_capabilities,
+        Arc::new(context),
+        maintenance_mode,
+    )
+    .await
+}
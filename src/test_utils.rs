@@ -0,0 +1,33 @@
+//! Helpers for writing end-to-end tests against a real `MySQL`/`MariaDB` server.
+//!
+//! This module is only available with the `test-utils` feature.
+//!
+//! A [`TestDatabase`] is either a disposable `MariaDB` instance started in a
+//! temporary directory, or an external server given by the
+//! `MUSCL_TEST_MYSQL_SOCKET` environment variable. A [`TestServer`] runs the
+//! muscl server on a temporary socket in front of it, serving sessions for a
+//! fixed [`UnixUser`](crate::core::common::UnixUser).
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use muscl_lib::{core::common::UnixUser, core::protocol::Request, test_utils::*};
+//!
+//! let database = TestDatabase::start()?;
+//! let server = TestServer::start(&database, UnixUser {
+//!     username: "alice".to_string(),
+//!     groups: vec![],
+//! })
+//! .await?;
+//!
+//! let response = server
+//!     .request(Request::CreateDatabases(vec!["alice_test".into()]))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod database;
+mod server;
+
+pub use database::*;
+pub use server::*;
@@ -0,0 +1,253 @@
+use std::{
+    fs,
+    os::unix::net::UnixStream as StdUnixStream,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use sqlx::MySqlPool;
+
+use crate::server::{
+    config::{DEFAULT_PORT, MysqlConfig},
+    supervisor::create_db_connection_pool,
+};
+
+/// Path to the socket of an external server to use instead of starting `MariaDB`.
+pub const TEST_MYSQL_SOCKET_ENV_VAR: &str = "MUSCL_TEST_MYSQL_SOCKET";
+
+/// Host of an external server to use instead of starting `MariaDB`.
+pub const TEST_MYSQL_HOST_ENV_VAR: &str = "MUSCL_TEST_MYSQL_HOST";
+
+/// Port of the external server given by `MUSCL_TEST_MYSQL_HOST`, defaults to 3306.
+pub const TEST_MYSQL_PORT_ENV_VAR: &str = "MUSCL_TEST_MYSQL_PORT";
+
+/// Username for the external server, defaults to `root`.
+pub const TEST_MYSQL_USERNAME_ENV_VAR: &str = "MUSCL_TEST_MYSQL_USERNAME";
+
+/// Password for the external server, if any.
+pub const TEST_MYSQL_PASSWORD_ENV_VAR: &str = "MUSCL_TEST_MYSQL_PASSWORD";
+
+const MARIADB_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const MARIADB_STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestDatabaseAddress {
+    Socket(PathBuf),
+    Tcp { host: String, port: u16 },
+}
+
+/// A `MySQL`/`MariaDB` server to run tests against.
+///
+/// If the server was started by [`TestDatabase::spawn_mariadb`], it is
+/// stopped and its data directory removed when this is dropped.
+#[derive(Debug)]
+pub struct TestDatabase {
+    address: TestDatabaseAddress,
+    username: String,
+    password: Option<String>,
+    process: Option<Child>,
+    temp_dir: Option<PathBuf>,
+}
+
+impl TestDatabase {
+    /// Use the external server from `MUSCL_TEST_MYSQL_SOCKET` or `MUSCL_TEST_MYSQL_HOST`
+    /// if set, or start a disposable `MariaDB` instance otherwise.
+    pub fn start() -> anyhow::Result<Self> {
+        let address = if let Some(socket_path) = std::env::var_os(TEST_MYSQL_SOCKET_ENV_VAR) {
+            TestDatabaseAddress::Socket(PathBuf::from(socket_path))
+        } else if let Ok(host) = std::env::var(TEST_MYSQL_HOST_ENV_VAR) {
+            let port = match std::env::var(TEST_MYSQL_PORT_ENV_VAR) {
+                Ok(port) => port.parse().context(format!(
+                    "Invalid value for {TEST_MYSQL_PORT_ENV_VAR}: '{port}'"
+                ))?,
+                Err(_) => DEFAULT_PORT,
+            };
+            TestDatabaseAddress::Tcp { host, port }
+        } else {
+            return Self::spawn_mariadb();
+        };
+
+        Ok(Self::external(
+            address,
+            std::env::var(TEST_MYSQL_USERNAME_ENV_VAR).unwrap_or("root".to_string()),
+            std::env::var(TEST_MYSQL_PASSWORD_ENV_VAR).ok(),
+        ))
+    }
+
+    /// Use an already running server.
+    ///
+    /// **WARNING:** Tests will create and drop databases and users on this server.
+    /// The user needs the same privileges as the user of the muscl server.
+    #[must_use]
+    pub fn external(
+        address: TestDatabaseAddress,
+        username: String,
+        password: Option<String>,
+    ) -> Self {
+        Self {
+            address,
+            username,
+            password,
+            process: None,
+            temp_dir: None,
+        }
+    }
+
+    /// Start a fresh `MariaDB` instance in a temporary directory, listening only on a unix socket.
+    ///
+    /// This requires `mariadb-install-db` and `mariadbd` to be available in `PATH`.
+    pub fn spawn_mariadb() -> anyhow::Result<Self> {
+        let temp_dir = std::env::temp_dir().join(format!("muscl-test-{}", uuid::Uuid::new_v4()));
+        let data_dir = temp_dir.join("data");
+        let socket_path = temp_dir.join("mysql.sock");
+        fs::create_dir_all(&data_dir)
+            .context(format!("Failed to create data directory at {data_dir:?}"))?;
+
+        // NOTE: mariadbd refuses to run as root unless explicitly told to.
+        let user_args: &[&str] = if nix::unistd::geteuid().is_root() {
+            &["--user=root"]
+        } else {
+            &[]
+        };
+
+        let status = Command::new("mariadb-install-db")
+            .arg("--no-defaults")
+            .arg(format!("--datadir={}", data_dir.display()))
+            .arg("--auth-root-authentication-method=normal")
+            .arg("--skip-test-db")
+            .args(user_args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to run mariadb-install-db, is MariaDB installed?")?;
+
+        if !status.success() {
+            fs::remove_dir_all(&temp_dir).ok();
+            anyhow::bail!("mariadb-install-db exited with {status}");
+        }
+
+        let process = Command::new("mariadbd")
+            .arg("--no-defaults")
+            .arg(format!("--datadir={}", data_dir.display()))
+            .arg(format!("--socket={}", socket_path.display()))
+            .arg(format!(
+                "--pid-file={}",
+                temp_dir.join("mariadb.pid").display()
+            ))
+            .arg(format!(
+                "--log-error={}",
+                temp_dir.join("mariadb.log").display()
+            ))
+            .arg("--skip-networking")
+            .args(user_args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start mariadbd, is MariaDB installed?")?;
+
+        let mut database = Self {
+            address: TestDatabaseAddress::Socket(socket_path),
+            username: "root".to_string(),
+            password: None,
+            process: Some(process),
+            temp_dir: Some(temp_dir),
+        };
+        database.wait_until_ready()?;
+
+        Ok(database)
+    }
+
+    fn wait_until_ready(&mut self) -> anyhow::Result<()> {
+        let TestDatabaseAddress::Socket(socket_path) = &self.address else {
+            return Ok(());
+        };
+        let socket_path = socket_path.clone();
+        let deadline = Instant::now() + MARIADB_STARTUP_TIMEOUT;
+
+        loop {
+            if StdUnixStream::connect(&socket_path).is_ok() {
+                return Ok(());
+            }
+
+            if let Some(process) = &mut self.process
+                && let Some(status) = process.try_wait()?
+            {
+                anyhow::bail!(
+                    "mariadbd exited with {status} before accepting connections{}",
+                    self.log_excerpt()
+                );
+            }
+
+            if Instant::now() >= deadline {
+                anyhow::bail!(
+                    "mariadbd did not accept connections within {}s{}",
+                    MARIADB_STARTUP_TIMEOUT.as_secs(),
+                    self.log_excerpt()
+                );
+            }
+
+            std::thread::sleep(MARIADB_STARTUP_POLL_INTERVAL);
+        }
+    }
+
+    fn log_excerpt(&self) -> String {
+        self.temp_dir
+            .as_ref()
+            .and_then(|dir| fs::read_to_string(dir.join("mariadb.log")).ok())
+            .map(|log| format!(", log:\n{log}"))
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn address(&self) -> &TestDatabaseAddress {
+        &self.address
+    }
+
+    /// A config for connecting the muscl server to this database.
+    pub fn mysql_config(&self) -> anyhow::Result<MysqlConfig> {
+        let mut table = toml::Table::new();
+        match &self.address {
+            TestDatabaseAddress::Socket(socket_path) => {
+                table.insert(
+                    "socket_path".to_string(),
+                    socket_path.to_string_lossy().to_string().into(),
+                );
+            }
+            TestDatabaseAddress::Tcp { host, port } => {
+                table.insert("host".to_string(), host.clone().into());
+                table.insert("port".to_string(), i64::from(*port).into());
+            }
+        }
+        table.insert("username".to_string(), self.username.clone().into());
+        if let Some(password) = &self.password {
+            table.insert("password".to_string(), password.clone().into());
+        }
+
+        table
+            .try_into()
+            .context("Failed to construct MySQL config for test database")
+    }
+
+    /// A connection pool with the same privileges as the muscl server,
+    /// e.g. for testing the `server::sql` modules directly.
+    pub async fn connection_pool(&self) -> anyhow::Result<MySqlPool> {
+        create_db_connection_pool(&self.mysql_config()?).await
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            process.kill().ok();
+            process.wait().ok();
+        }
+
+        if let Some(temp_dir) = self.temp_dir.take() {
+            fs::remove_dir_all(temp_dir).ok();
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    net::{UnixListener as TokioUnixListener, UnixStream as TokioUnixStream},
+    sync::RwLock,
+    task::JoinHandle,
+};
+
+use crate::{
+    core::{
+        common::UnixUser,
+        protocol::{
+            ClientToServerMessageStream, DEFAULT_HANDSHAKE_TIMEOUT, Request, Response,
+            create_client_to_server_message_stream, request_validation::GroupDenylist,
+            wait_for_server_ready,
+        },
+    },
+    server::{
+        backend_capabilities::BackendCapabilities,
+        config::DEFAULT_BULK_CONCURRENCY,
+        session_handler::{SessionContext, session_handler_with_unix_user},
+    },
+    test_utils::TestDatabase,
+};
+
+/// A muscl server listening on a temporary socket.
+///
+/// Since every test connection comes from the same process, the peer credentials
+/// are not checked. Instead, every session is run as the given [`UnixUser`],
+/// which does not need to exist on the system.
+///
+/// The server is stopped and the socket removed when this is dropped.
+#[derive(Debug)]
+pub struct TestServer {
+    socket_path: PathBuf,
+    temp_dir: PathBuf,
+    listener_task: JoinHandle<()>,
+}
+
+impl TestServer {
+    pub async fn start(database: &TestDatabase, unix_user: UnixUser) -> anyhow::Result<Self> {
+        Self::start_with_group_denylist(database, unix_user, GroupDenylist::new()).await
+    }
+
+    pub async fn start_with_group_denylist(
+        database: &TestDatabase,
+        unix_user: UnixUser,
+        group_denylist: GroupDenylist,
+    ) -> anyhow::Result<Self> {
+        let db_pool = database.connection_pool().await?;
+        let backend_capabilities = BackendCapabilities::detect(&db_pool).await?;
+        let db_pool = Arc::new(RwLock::new(db_pool));
+
+        let temp_dir =
+            std::env::temp_dir().join(format!("muscl-test-server-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir)
+            .context(format!("Failed to create socket directory at {temp_dir:?}"))?;
+        let socket_path = temp_dir.join("muscl.sock");
+        let listener = TokioUnixListener::bind(&socket_path).context(format!(
+            "Failed to bind test server socket at {socket_path:?}"
+        ))?;
+
+        let unix_user = Arc::new(unix_user);
+        let context = Arc::new(SessionContext {
+            group_denylist,
+            admin_groups: Vec::new(),
+            privilege_templates: BTreeMap::new(),
+            privilege_aliases: BTreeMap::new(),
+            extended_database_privileges: true,
+            grant_offers: None,
+            privilege_snapshots: None,
+            scheduled_privilege_changes: None,
+            error_footer: None,
+            motd: None,
+            policy_engine: None,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            bulk_concurrency: DEFAULT_BULK_CONCURRENCY as usize,
+        });
+
+        let listener_task = tokio::spawn(async move {
+            while let Ok((conn, _addr)) = listener.accept().await {
+                let db_pool = db_pool.clone();
+                let backend_capabilities = backend_capabilities.clone();
+                let unix_user = unix_user.clone();
+                let context = context.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = session_handler_with_unix_user(
+                        conn,
+                        &unix_user,
+                        db_pool,
+                        Arc::new(RwLock::new(None)),
+                        &backend_capabilities,
+                        &context,
+                    )
+                    .await
+                    {
+                        tracing::error!("Test server session failed: {}", err);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            socket_path,
+            temp_dir,
+            listener_task,
+        })
+    }
+
+    #[must_use]
+    pub fn socket_path(&self) -> &std::path::Path {
+        &self.socket_path
+    }
+
+    /// Open a new session, ready to send requests.
+    ///
+    /// The stream can be passed to the functions in [`crate::client::commands`]
+    /// to run client commands against the server.
+    pub async fn connect(&self) -> anyhow::Result<ClientToServerMessageStream> {
+        let socket = TokioUnixStream::connect(&self.socket_path)
+            .await
+            .context(format!(
+                "Failed to connect to test server at {:?}",
+                self.socket_path
+            ))?;
+        let mut message_stream = create_client_to_server_message_stream(socket);
+        wait_for_server_ready(&mut message_stream, DEFAULT_HANDSHAKE_TIMEOUT).await?;
+        Ok(message_stream)
+    }
+
+    /// Send a single request in a new session, and return the response.
+    ///
+    /// Progress messages sent ahead of the response are skipped.
+    pub async fn request(&self, request: Request) -> anyhow::Result<Response> {
+        let mut message_stream = self.connect().await?;
+        message_stream.send(request).await?;
+        let response = loop {
+            let response = message_stream
+                .next()
+                .await
+                .context("Test server closed the connection without responding")??;
+            if !matches!(response, Response::Progress { .. }) {
+                break response;
+            }
+        };
+        message_stream.send(Request::Exit).await.ok();
+        Ok(response)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.listener_task.abort();
+        fs::remove_dir_all(&self.temp_dir).ok();
+    }
+}
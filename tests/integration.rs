@@ -0,0 +1,563 @@
+//! End-to-end tests against a real `MySQL`/`MariaDB` server.
+//!
+//! Run with `cargo test --features test-utils --test integration`.
+//! See `src/test_utils.rs` for how the database server is chosen.
+
+use std::collections::BTreeSet;
+
+use futures_util::{SinkExt, StreamExt};
+use muscl_lib::{
+    core::{
+        common::UnixUser,
+        database_privileges::{DatabasePrivilegeRow, DatabasePrivilegesDiff},
+        protocol::{
+            CreateUsersRequest, ListPrivilegesRequest, LockUsersRequest,
+            ModifyDatabasePrivilegesError, ModifyPrivilegesRequest, Request, Response,
+            SetUserLimitsRequest, TransferDatabaseRequest, UnlockUsersRequest, UserLimits,
+        },
+        types::{MySQLDatabase, MySQLUser},
+    },
+    test_utils::{TestDatabase, TestServer},
+};
+
+fn test_user() -> UnixUser {
+    UnixUser {
+        username: "alice".to_string(),
+        groups: vec!["wonderland".to_string()],
+    }
+}
+
+#[tokio::test]
+async fn test_create_list_and_drop_database() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let name = MySQLDatabase::from("alice_integration_db");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![name.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&name].is_ok());
+
+    let Response::ListDatabases(result) = server
+        .request(Request::ListDatabases(Some(vec![name.clone()])))
+        .await?
+    else {
+        panic!("Unexpected response to ListDatabases");
+    };
+    assert!(result[&name].is_ok());
+
+    let Response::DropDatabases(result) = server
+        .request(Request::DropDatabases(vec![name.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to DropDatabases");
+    };
+    assert!(result[&name].is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_database_with_unauthorized_prefix() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let name = MySQLDatabase::from("bob_integration_db");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![name.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&name].is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_database_is_unused() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let name = MySQLDatabase::from("alice_unused_db");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![name.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&name].is_ok());
+
+    let Response::ListUnusedDatabases(result) =
+        server.request(Request::ListUnusedDatabases).await?
+    else {
+        panic!("Unexpected response to ListUnusedDatabases");
+    };
+    assert!(result?.iter().any(|row| row.database == name));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_database_to_group() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let from = MySQLDatabase::from("alice_transfer_db");
+    let to = MySQLDatabase::from("wonderland_transfer_db");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![from.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&from].is_ok());
+
+    let Response::TransferDatabase(result) = server
+        .request(Request::TransferDatabase(TransferDatabaseRequest {
+            from: from.clone(),
+            to: to.clone(),
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to TransferDatabase");
+    };
+    assert!(result.is_ok());
+
+    let Response::ListDatabases(result) = server
+        .request(Request::ListDatabases(Some(vec![from.clone(), to.clone()])))
+        .await?
+    else {
+        panic!("Unexpected response to ListDatabases");
+    };
+    assert!(result[&from].is_err());
+    assert!(result[&to].is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_atomic_modify_privileges_applies_nothing_on_failure() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let db = MySQLDatabase::from("alice_atomic_db");
+    let missing_db = MySQLDatabase::from("alice_atomic_missing_db");
+    let user = MySQLUser::from("alice_atomic_user");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![db.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&db].is_ok());
+
+    let Response::CreateUsers(result) = server
+        .request(Request::CreateUsers(CreateUsersRequest {
+            users: vec![user.clone()],
+            locked: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to CreateUsers");
+    };
+    assert!(result[&user].is_ok());
+
+    let row = |db: &MySQLDatabase| DatabasePrivilegeRow {
+        db: db.clone(),
+        user: user.clone(),
+        select_priv: true,
+        insert_priv: false,
+        update_priv: false,
+        delete_priv: false,
+        create_priv: false,
+        drop_priv: false,
+        alter_priv: false,
+        index_priv: false,
+        create_tmp_table_priv: false,
+        lock_tables_priv: false,
+        references_priv: false,
+        create_view_priv: false,
+        show_view_priv: false,
+        trigger_priv: false,
+        event_priv: false,
+        execute_priv: false,
+        create_routine_priv: false,
+        alter_routine_priv: false,
+        grant_priv: false,
+    };
+
+    let Response::ModifyPrivileges(result) = server
+        .request(Request::ModifyPrivileges(ModifyPrivilegesRequest {
+            diffs: BTreeSet::from([
+                DatabasePrivilegesDiff::New(row(&db)),
+                DatabasePrivilegesDiff::New(row(&missing_db)),
+            ]),
+            atomic: true,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to ModifyPrivileges");
+    };
+    assert_eq!(
+        result[&(db.clone(), user.clone())],
+        Err(ModifyDatabasePrivilegesError::AtomicRequestFailed)
+    );
+    assert_eq!(
+        result[&(missing_db, user.clone())],
+        Err(ModifyDatabasePrivilegesError::DatabaseDoesNotExist)
+    );
+
+    let Response::ListPrivileges(result) = server
+        .request(Request::ListPrivileges(ListPrivilegesRequest {
+            databases: Some(vec![db.clone()]),
+            user: None,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to ListPrivileges");
+    };
+    assert!(result[&db].as_ref().is_ok_and(Vec::is_empty));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drop_user_removes_its_privileges() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let db = MySQLDatabase::from("alice_cascade_db");
+    let user = MySQLUser::from("alice_cascade_user");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![db.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&db].is_ok());
+
+    let Response::CreateUsers(result) = server
+        .request(Request::CreateUsers(CreateUsersRequest {
+            users: vec![user.clone()],
+            locked: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to CreateUsers");
+    };
+    assert!(result[&user].is_ok());
+
+    let Response::ModifyPrivileges(result) = server
+        .request(Request::ModifyPrivileges(ModifyPrivilegesRequest {
+            diffs: BTreeSet::from([DatabasePrivilegesDiff::New(DatabasePrivilegeRow {
+                db: db.clone(),
+                user: user.clone(),
+                select_priv: true,
+                insert_priv: true,
+                update_priv: false,
+                delete_priv: false,
+                create_priv: false,
+                drop_priv: false,
+                alter_priv: false,
+                index_priv: false,
+                create_tmp_table_priv: false,
+                lock_tables_priv: false,
+                references_priv: false,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
+            })]),
+            atomic: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to ModifyPrivileges");
+    };
+    assert!(result[&(db.clone(), user.clone())].is_ok());
+
+    let Response::DropUsers(result) = server
+        .request(Request::DropUsers(vec![user.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to DropUsers");
+    };
+    assert_eq!(result[&user], Ok(1));
+
+    let Response::ListPrivileges(result) = server
+        .request(Request::ListPrivileges(ListPrivilegesRequest {
+            databases: Some(vec![db.clone()]),
+            user: None,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to ListPrivileges");
+    };
+    assert!(result[&db].as_ref().is_ok_and(Vec::is_empty));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drop_database_removes_its_privileges() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let db = MySQLDatabase::from("alice_cascade_db2");
+    let user = MySQLUser::from("alice_cascade_user2");
+
+    let Response::CreateDatabases(result) = server
+        .request(Request::CreateDatabases(vec![db.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to CreateDatabases");
+    };
+    assert!(result[&db].is_ok());
+
+    let Response::CreateUsers(result) = server
+        .request(Request::CreateUsers(CreateUsersRequest {
+            users: vec![user.clone()],
+            locked: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to CreateUsers");
+    };
+    assert!(result[&user].is_ok());
+
+    let Response::ModifyPrivileges(result) = server
+        .request(Request::ModifyPrivileges(ModifyPrivilegesRequest {
+            diffs: BTreeSet::from([DatabasePrivilegesDiff::New(DatabasePrivilegeRow {
+                db: db.clone(),
+                user: user.clone(),
+                select_priv: true,
+                insert_priv: true,
+                update_priv: false,
+                delete_priv: false,
+                create_priv: false,
+                drop_priv: false,
+                alter_priv: false,
+                index_priv: false,
+                create_tmp_table_priv: false,
+                lock_tables_priv: false,
+                references_priv: false,
+                create_view_priv: false,
+                show_view_priv: false,
+                trigger_priv: false,
+                event_priv: false,
+                execute_priv: false,
+                create_routine_priv: false,
+                alter_routine_priv: false,
+                grant_priv: false,
+            })]),
+            atomic: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to ModifyPrivileges");
+    };
+    assert!(result[&(db.clone(), user.clone())].is_ok());
+
+    let Response::DropDatabases(result) = server
+        .request(Request::DropDatabases(vec![db.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to DropDatabases");
+    };
+    assert_eq!(result[&db], Ok(vec![user.clone()]));
+
+    let Response::DropUsers(result) = server
+        .request(Request::DropUsers(vec![user.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to DropUsers");
+    };
+    assert_eq!(result[&user], Ok(0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lock_and_unlock_users_by_prefix() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let users = vec![
+        MySQLUser::from("wonderland_bulk_a"),
+        MySQLUser::from("wonderland_bulk_b"),
+    ];
+
+    let Response::CreateUsers(result) = server
+        .request(Request::CreateUsers(CreateUsersRequest {
+            users: users.clone(),
+            locked: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to CreateUsers");
+    };
+    assert!(result.values().all(Result::is_ok));
+
+    let Response::LockUsers(result) = server
+        .request(Request::LockUsers(LockUsersRequest {
+            users: vec![],
+            all_with_prefix: Some("wonderland".to_string()),
+            reason: None,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to LockUsers");
+    };
+    for user in &users {
+        assert_eq!(result[user], Ok(()));
+    }
+
+    let Response::LockUsers(result) = server
+        .request(Request::LockUsers(LockUsersRequest {
+            users: vec![],
+            all_with_prefix: Some("bob".to_string()),
+            reason: None,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to LockUsers");
+    };
+    assert!(result[&MySQLUser::from("bob_")].is_err());
+
+    let Response::UnlockUsers(result) = server
+        .request(Request::UnlockUsers(UnlockUsersRequest {
+            users: vec![],
+            all_with_prefix: Some("wonderland".to_string()),
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to UnlockUsers");
+    };
+    for user in &users {
+        assert_eq!(result[user], Ok(()));
+    }
+
+    let Response::DropUsers(result) = server.request(Request::DropUsers(users.clone())).await?
+    else {
+        panic!("Unexpected response to DropUsers");
+    };
+    assert!(result.values().all(Result::is_ok));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_user_limits() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let user = MySQLUser::from("wonderland_limited");
+
+    let Response::CreateUsers(result) = server
+        .request(Request::CreateUsers(CreateUsersRequest {
+            users: vec![user.clone()],
+            locked: false,
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to CreateUsers");
+    };
+    assert!(result[&user].is_ok());
+
+    let Response::SetUserLimits(result) = server
+        .request(Request::SetUserLimits(SetUserLimitsRequest {
+            user: user.clone(),
+            max_queries_per_hour: Some(1000),
+            max_updates_per_hour: Some(100),
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to SetUserLimits");
+    };
+    assert_eq!(result, Ok(()));
+
+    let Response::SetUserLimits(result) = server
+        .request(Request::SetUserLimits(SetUserLimitsRequest {
+            user: user.clone(),
+            max_queries_per_hour: None,
+            max_updates_per_hour: Some(0),
+        }))
+        .await?
+    else {
+        panic!("Unexpected response to SetUserLimits");
+    };
+    assert_eq!(result, Ok(()));
+
+    let Response::ListUsers(result) = server
+        .request(Request::ListUsers(Some(vec![user.clone()])))
+        .await?
+    else {
+        panic!("Unexpected response to ListUsers");
+    };
+    assert_eq!(
+        result[&user].as_ref().map(|user| user.limits),
+        Ok(UserLimits {
+            max_queries_per_hour: 1000,
+            max_updates_per_hour: 0,
+        })
+    );
+
+    let Response::DropUsers(result) = server
+        .request(Request::DropUsers(vec![user.clone()]))
+        .await?
+    else {
+        panic!("Unexpected response to DropUsers");
+    };
+    assert!(result[&user].is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_create_databases_reports_progress() -> anyhow::Result<()> {
+    let database = TestDatabase::start()?;
+    let server = TestServer::start(&database, test_user()).await?;
+
+    let names = (0..25)
+        .map(|i| MySQLDatabase::from(format!("alice_progress_db{i:02}")))
+        .collect::<Vec<_>>();
+
+    let mut message_stream = server.connect().await?;
+    message_stream
+        .send(Request::CreateDatabases(names.clone()))
+        .await?;
+
+    let mut progress = Vec::new();
+    let result = loop {
+        match message_stream.next().await {
+            Some(Ok(Response::Progress { done, total, .. })) => progress.push((done, total)),
+            Some(Ok(Response::CreateDatabases(result))) => break result,
+            response => panic!("Unexpected response to CreateDatabases: {response:?}"),
+        }
+    };
+    message_stream.send(Request::Exit).await?;
+
+    assert!(result.values().all(Result::is_ok));
+    // NOTE: progress that is still queued when the request finishes is not sent.
+    assert!(!progress.is_empty());
+    assert!(progress.iter().all(|&(_, total)| total == names.len()));
+    assert!(progress.is_sorted());
+
+    server.request(Request::DropDatabases(names)).await?;
+
+    Ok(())
+}